@@ -1,5 +1,5 @@
 use pyo3::prelude::*;
-use pyo3::exceptions::PyIOError;
+use pyo3::exceptions::{PyFileNotFoundError, PyIOError, PyValueError};
 use pyo3::types::PyBytes;
 use std::io::{self, Read};
 
@@ -24,7 +24,7 @@ impl Read for PyFileReader {
             // Call the Python read() method
             let result = file
                 .call_method1("read", (buf.len(),))
-                .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+                .map_err(|e| io::Error::other(e.to_string()))?;
 
             // Get the bytes returned
             let bytes: &Bound<PyBytes> = result
@@ -48,10 +48,29 @@ impl Read for PyFileReader {
 // Mark PyFileReader as Send since we control access through Python's GIL
 unsafe impl Send for PyFileReader {}
 
+/// Convert a prseq error to a Python exception: FileNotFoundError or IOError
+/// for I/O failures, and ValueError (which includes the line and record
+/// number) for malformed input. Errors in a file read by path are
+/// classified by the error within, and named with the file.
+fn to_py_err(error: rust_prseq::PrseqError) -> PyErr {
+    let message = error.to_string();
+    let inner = match &error {
+        rust_prseq::PrseqError::InFile { source, .. } => source.as_ref(),
+        other => other,
+    };
+    match inner {
+        rust_prseq::PrseqError::Io(e) if e.kind() == io::ErrorKind::NotFound => {
+            PyFileNotFoundError::new_err(message)
+        }
+        rust_prseq::PrseqError::Io(_) => PyIOError::new_err(message),
+        _ => PyValueError::new_err(message),
+    }
+}
+
 #[pyclass]
 struct FastaRecord {
     #[pyo3(get)]
-    id: String,
+    header: String,
     #[pyo3(get)]
     sequence: String,
 }
@@ -59,14 +78,14 @@ struct FastaRecord {
 #[pymethods]
 impl FastaRecord {
     fn __repr__(&self) -> String {
-        format!("FastaRecord(id='{}', sequence='{}')", self.id, self.sequence)
+        format!("FastaRecord(header='{}', sequence='{}')", self.header, self.sequence)
     }
 }
 
 impl From<rust_prseq::FastaRecord> for FastaRecord {
     fn from(record: rust_prseq::FastaRecord) -> Self {
         FastaRecord {
-            id: record.id,
+            header: record.header,
             sequence: record.sequence,
         }
     }
@@ -75,7 +94,7 @@ impl From<rust_prseq::FastaRecord> for FastaRecord {
 #[pyclass]
 struct FastqRecord {
     #[pyo3(get)]
-    id: String,
+    header: String,
     #[pyo3(get)]
     sequence: String,
     #[pyo3(get)]
@@ -85,20 +104,106 @@ struct FastqRecord {
 #[pymethods]
 impl FastqRecord {
     fn __repr__(&self) -> String {
-        format!("FastqRecord(id='{}', sequence='{}', quality='{}')", self.id, self.sequence, self.quality)
+        format!("FastqRecord(header='{}', sequence='{}', quality='{}')", self.header, self.sequence, self.quality)
     }
 }
 
 impl From<rust_prseq::FastqRecord> for FastqRecord {
     fn from(record: rust_prseq::FastqRecord) -> Self {
         FastqRecord {
-            id: record.id,
+            header: record.header,
             sequence: record.sequence,
             quality: record.quality,
         }
     }
 }
 
+#[pyclass(get_all)]
+struct IlluminaHeader {
+    instrument: String,
+    run: Option<u32>,
+    flowcell: Option<String>,
+    lane: u32,
+    tile: u32,
+    x: u32,
+    y: u32,
+    umi: Option<String>,
+    read: Option<u8>,
+    filtered: Option<bool>,
+    control: Option<u32>,
+    index: Option<String>,
+}
+
+#[pymethods]
+impl IlluminaHeader {
+    fn __repr__(&self) -> String {
+        format!(
+            "IlluminaHeader(instrument='{}', lane={}, tile={}, x={}, y={})",
+            self.instrument, self.lane, self.tile, self.x, self.y
+        )
+    }
+}
+
+impl From<rust_prseq::IlluminaHeader> for IlluminaHeader {
+    fn from(header: rust_prseq::IlluminaHeader) -> Self {
+        IlluminaHeader {
+            instrument: header.instrument,
+            run: header.run,
+            flowcell: header.flowcell,
+            lane: header.lane,
+            tile: header.tile,
+            x: header.x,
+            y: header.y,
+            umi: header.umi,
+            read: header.read,
+            filtered: header.filtered,
+            control: header.control,
+            index: header.index,
+        }
+    }
+}
+
+#[pyclass(get_all)]
+struct NanoporeHeader {
+    read_id: String,
+    run_id: Option<String>,
+    read: Option<u64>,
+    channel: Option<u32>,
+    start_time: Option<String>,
+    flow_cell_id: Option<String>,
+    sample_id: Option<String>,
+    barcode: Option<String>,
+    basecall_model: Option<String>,
+    other: Vec<(String, String)>,
+}
+
+#[pymethods]
+impl NanoporeHeader {
+    fn __repr__(&self) -> String {
+        format!(
+            "NanoporeHeader(read_id='{}', channel={:?}, barcode={:?})",
+            self.read_id, self.channel, self.barcode
+        )
+    }
+}
+
+impl From<rust_prseq::NanoporeHeader> for NanoporeHeader {
+    fn from(header: rust_prseq::NanoporeHeader) -> Self {
+        NanoporeHeader {
+            read_id: header.read_id,
+            run_id: header.run_id,
+            read: header.read,
+            channel: header.channel,
+            start_time: header.start_time,
+            flow_cell_id: header.flow_cell_id,
+            sample_id: header.sample_id,
+            barcode: header.barcode,
+            basecall_model: header.basecall_model,
+            other: header.other,
+        }
+    }
+}
+
 #[pyclass(unsendable)]
 struct FastaReader {
     reader: rust_prseq::FastaReader,
@@ -154,7 +259,7 @@ impl FastaReader {
                 }
             }
         }
-        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        .map_err(to_py_err)?;
         Ok(FastaReader { reader })
     }
 
@@ -166,7 +271,7 @@ impl FastaReader {
             Some(hint) => rust_prseq::FastaReader::from_file_with_capacity(&path, hint),
             None => rust_prseq::FastaReader::from_file(&path),
         }
-        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        .map_err(to_py_err)?;
         Ok(FastaReader { reader })
     }
 
@@ -179,7 +284,14 @@ impl FastaReader {
             Some(hint) => rust_prseq::FastaReader::from_reader_with_capacity(py_reader, hint),
             None => rust_prseq::FastaReader::from_reader_with_capacity(py_reader, 64 * 1024),
         }
-        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        .map_err(to_py_err)?;
+        Ok(FastaReader { reader })
+    }
+
+    /// Create a FastaReader from bytes in memory (which may be compressed)
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        let reader = rust_prseq::FastaReader::from_bytes(data).map_err(to_py_err)?;
         Ok(FastaReader { reader })
     }
 
@@ -191,7 +303,7 @@ impl FastaReader {
             Some(hint) => rust_prseq::FastaReader::from_stdin_with_capacity(hint),
             None => rust_prseq::FastaReader::from_stdin(),
         }
-        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        .map_err(to_py_err)?;
         Ok(FastaReader { reader })
     }
 
@@ -204,7 +316,7 @@ impl FastaReader {
         // The performance gain is minimal for individual records
         match slf.reader.next() {
             Some(Ok(record)) => Ok(Some(record.into())),
-            Some(Err(e)) => Err(PyIOError::new_err(e.to_string())),
+            Some(Err(e)) => Err(to_py_err(e)),
             None => Ok(None),
         }
     }
@@ -212,16 +324,9 @@ impl FastaReader {
     /// Read multiple records at once with GIL released for better performance
     fn read_batch(&mut self, py: Python<'_>, count: usize) -> PyResult<Vec<FastaRecord>> {
         // Release GIL for batch operations where the performance benefit is significant
-        py.allow_threads(move || {
-            let mut records = Vec::with_capacity(count);
-            for _ in 0..count {
-                match self.reader.next() {
-                    Some(Ok(record)) => records.push(record.into()),
-                    Some(Err(e)) => return Err(PyIOError::new_err(e.to_string())),
-                    None => break,
-                }
-            }
-            Ok(records)
+        py.allow_threads(move || match self.reader.next_chunk(count) {
+            Ok(records) => Ok(records.into_iter().map(Into::into).collect()),
+            Err(e) => Err(to_py_err(e)),
         })
     }
 }
@@ -271,7 +376,7 @@ impl FastqReader {
                 }
             }
         }
-        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        .map_err(to_py_err)?;
         Ok(FastqReader { reader })
     }
 
@@ -283,7 +388,7 @@ impl FastqReader {
             Some(hint) => rust_prseq::FastqReader::from_file_with_capacity(&path, hint),
             None => rust_prseq::FastqReader::from_file(&path),
         }
-        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        .map_err(to_py_err)?;
         Ok(FastqReader { reader })
     }
 
@@ -296,7 +401,14 @@ impl FastqReader {
             Some(hint) => rust_prseq::FastqReader::from_reader_with_capacity(py_reader, hint),
             None => rust_prseq::FastqReader::from_reader_with_capacity(py_reader, 64 * 1024),
         }
-        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        .map_err(to_py_err)?;
+        Ok(FastqReader { reader })
+    }
+
+    /// Create a FastqReader from bytes in memory (which may be compressed)
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        let reader = rust_prseq::FastqReader::from_bytes(data).map_err(to_py_err)?;
         Ok(FastqReader { reader })
     }
 
@@ -308,7 +420,7 @@ impl FastqReader {
             Some(hint) => rust_prseq::FastqReader::from_stdin_with_capacity(hint),
             None => rust_prseq::FastqReader::from_stdin(),
         }
-        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        .map_err(to_py_err)?;
         Ok(FastqReader { reader })
     }
 
@@ -319,23 +431,16 @@ impl FastqReader {
     fn __next__(mut slf: PyRefMut<'_, Self>) -> PyResult<Option<FastqRecord>> {
         match slf.reader.next() {
             Some(Ok(record)) => Ok(Some(record.into())),
-            Some(Err(e)) => Err(PyIOError::new_err(e.to_string())),
+            Some(Err(e)) => Err(to_py_err(e)),
             None => Ok(None),
         }
     }
 
     /// Read multiple records at once with GIL released for better performance
     fn read_batch(&mut self, py: Python<'_>, count: usize) -> PyResult<Vec<FastqRecord>> {
-        py.allow_threads(move || {
-            let mut records = Vec::with_capacity(count);
-            for _ in 0..count {
-                match self.reader.next() {
-                    Some(Ok(record)) => records.push(record.into()),
-                    Some(Err(e)) => return Err(PyIOError::new_err(e.to_string())),
-                    None => break,
-                }
-            }
-            Ok(records)
+        py.allow_threads(move || match self.reader.next_chunk(count) {
+            Ok(records) => Ok(records.into_iter().map(Into::into).collect()),
+            Err(e) => Err(to_py_err(e)),
         })
     }
 }
@@ -348,7 +453,7 @@ fn read_fasta(path: String, sequence_size_hint: Option<usize>) -> PyResult<Vec<F
         Some(hint) => rust_prseq::read_fasta_with_capacity(&path, hint),
         None => rust_prseq::read_fasta(&path),
     }
-    .map_err(|e| PyIOError::new_err(e.to_string()))?;
+    .map_err(to_py_err)?;
     Ok(records.into_iter().map(|r| r.into()).collect())
 }
 
@@ -356,7 +461,7 @@ fn read_fasta(path: String, sequence_size_hint: Option<usize>) -> PyResult<Vec<F
 #[pyfunction]
 fn read_fasta_with_capacity(path: String, sequence_size_hint: usize) -> PyResult<Vec<FastaRecord>> {
     let records = rust_prseq::read_fasta_with_capacity(&path, sequence_size_hint)
-        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        .map_err(to_py_err)?;
     Ok(records.into_iter().map(|r| r.into()).collect())
 }
 
@@ -368,7 +473,7 @@ fn read_fastq(path: String, sequence_size_hint: Option<usize>) -> PyResult<Vec<F
         Some(hint) => rust_prseq::read_fastq_with_capacity(&path, hint),
         None => rust_prseq::read_fastq(&path),
     }
-    .map_err(|e| PyIOError::new_err(e.to_string()))?;
+    .map_err(to_py_err)?;
     Ok(records.into_iter().map(|r| r.into()).collect())
 }
 
@@ -376,19 +481,76 @@ fn read_fastq(path: String, sequence_size_hint: Option<usize>) -> PyResult<Vec<F
 #[pyfunction]
 fn read_fastq_with_capacity(path: String, sequence_size_hint: usize) -> PyResult<Vec<FastqRecord>> {
     let records = rust_prseq::read_fastq_with_capacity(&path, sequence_size_hint)
-        .map_err(|e| PyIOError::new_err(e.to_string()))?;
+        .map_err(to_py_err)?;
     Ok(records.into_iter().map(|r| r.into()).collect())
 }
 
+/// Parse an Illumina FASTQ header line, returning None if it is not one
+#[pyfunction]
+fn parse_illumina_header(header: &str) -> Option<IlluminaHeader> {
+    rust_prseq::IlluminaHeader::parse(header).map(IlluminaHeader::from)
+}
+
+/// Parse an Oxford Nanopore FASTQ header line's key=value fields,
+/// returning None if it has none
+#[pyfunction]
+fn parse_nanopore_header(header: &str) -> Option<NanoporeHeader> {
+    rust_prseq::NanoporeHeader::parse(header).map(NanoporeHeader::from)
+}
+
+/// Split a header into its ID and description, exactly as the Rust
+/// records' `id()` and `description()` do
+#[pyfunction]
+fn split_header(header: &str) -> (&str, Option<&str>) {
+    let defline = rust_prseq::defline::Defline::new(header);
+    (defline.id(), defline.description())
+}
+
+/// The fraction of the A, C, G and T bases of a sequence that are G or C
+#[pyfunction]
+fn gc_content(sequence: &str) -> Option<f64> {
+    rust_prseq::sequence::gc_content(sequence.as_bytes())
+}
+
+/// The number of Ns in a sequence
+#[pyfunction]
+fn n_count(sequence: &str) -> usize {
+    rust_prseq::sequence::n_count(sequence.as_bytes())
+}
+
+/// The number of each base in a sequence, keyed by A, C, G, T, N and other
+#[pyfunction]
+fn base_counts(sequence: &str) -> std::collections::HashMap<&'static str, u64> {
+    let counts = rust_prseq::sequence::BaseCounts::new(sequence.as_bytes());
+    [
+        ("A", counts.a),
+        ("C", counts.c),
+        ("G", counts.g),
+        ("T", counts.t),
+        ("N", counts.n),
+        ("other", counts.other),
+    ]
+    .into_iter()
+    .collect()
+}
+
 #[pymodule]
 fn _prseq(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<FastaRecord>()?;
     m.add_class::<FastaReader>()?;
     m.add_class::<FastqRecord>()?;
     m.add_class::<FastqReader>()?;
+    m.add_class::<IlluminaHeader>()?;
+    m.add_class::<NanoporeHeader>()?;
     m.add_function(wrap_pyfunction!(read_fasta, m)?)?;
     m.add_function(wrap_pyfunction!(read_fasta_with_capacity, m)?)?;
     m.add_function(wrap_pyfunction!(read_fastq, m)?)?;
     m.add_function(wrap_pyfunction!(read_fastq_with_capacity, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_illumina_header, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_nanopore_header, m)?)?;
+    m.add_function(wrap_pyfunction!(split_header, m)?)?;
+    m.add_function(wrap_pyfunction!(gc_content, m)?)?;
+    m.add_function(wrap_pyfunction!(n_count, m)?)?;
+    m.add_function(wrap_pyfunction!(base_counts, m)?)?;
     Ok(())
 }
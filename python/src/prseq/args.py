@@ -47,6 +47,6 @@ def parse_args(source: str | Path | BinaryIO | None) -> tuple[str | None, Binary
         return None, source
 
     raise TypeError(
-        "source must be a str, Path, file object, or None, not "
+        "source must be a str, Path, file object, bytes, or None, not "
         f"{type(source).__name__}"
     )
@@ -138,7 +138,7 @@ def fasta_filter() -> None:
 
         for record in reader:
             if len(record.sequence) >= args.min_length:
-                print(f">{record.id}")
+                print(f">{record.header}")
                 print(record.sequence)
                 kept += 1
             else:
@@ -288,7 +288,7 @@ def fastq_filter() -> None:
 
         for record in reader:
             if len(record.sequence) >= args.min_length:
-                print(f"@{record.id}")
+                print(f"@{record.header}")
                 print(record.sequence)
                 print("+")
                 print(record.quality)
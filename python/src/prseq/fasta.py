@@ -10,13 +10,35 @@ class FastaRecord(NamedTuple):
     """A single FASTA sequence record.
 
     Attributes:
-        id: The sequence identifier (without the '>' prefix)
+        header: The header line (without the '>' prefix)
         sequence: The sequence data
     """
 
-    id: str
+    header: str
     sequence: str
 
+    @property
+    def id(self) -> str:
+        """The sequence identifier: the header up to the first (ASCII) whitespace."""
+        return _prseq.split_header(self.header)[0]
+
+    @property
+    def description(self) -> str | None:
+        """The rest of the header after the identifier, or None if there is none."""
+        return _prseq.split_header(self.header)[1]
+
+    def gc_content(self) -> float | None:
+        """The fraction of the A, C, G and T bases that are G or C, or None if there are none."""
+        return _prseq.gc_content(self.sequence)
+
+    def base_counts(self) -> dict[str, int]:
+        """The number of each base, keyed by A, C, G, T (including U), N and other."""
+        return _prseq.base_counts(self.sequence)
+
+    def n_count(self) -> int:
+        """The number of Ns in the sequence."""
+        return _prseq.n_count(self.sequence)
+
 
 class FastaReader:
     """Iterator over FASTA records from a file, file object, or stdin.
@@ -33,7 +55,7 @@ class FastaReader:
 
     def __init__(
         self,
-        source: str | Path | BinaryIO | None = None,
+        source: str | Path | BinaryIO | bytes | None = None,
         sequence_size_hint: int | None = None,
     ) -> None:
         """Create a new FASTA reader.
@@ -42,6 +64,7 @@ class FastaReader:
             source: Input source, can be:
                 - str or Path: Path to a FASTA file (uncompressed, .gz, or .bz2)
                 - file object: An open file-like object in binary mode ('rb')
+                - bytes: FASTA data in memory (which may be compressed)
                 - None or "-": Read from stdin
             sequence_size_hint: Optional hint for expected sequence length in characters.
                               Helps optimize memory allocation. Use smaller values (100-1000)
@@ -52,12 +75,18 @@ class FastaReader:
             FileNotFoundError: If the file doesn't exist
             IOError: If there's an error reading the file, or if a file object
                     is opened in text mode instead of binary mode
+            ValueError: If the input is malformed (when records are read). The
+                    message gives the line and record number of the problem
 
         Note:
             File objects must be opened in binary mode ('rb'). Text mode ('r') will
             raise an error. Example: `with open("file.fasta", "rb") as f: ...`
         """
 
+        if isinstance(source, (bytes, bytearray, memoryview)):
+            self._reader = _prseq.FastaReader.from_bytes(bytes(source))
+            return
+
         path, fp = parse_args(source)
 
         self._reader = _prseq.FastaReader(
@@ -70,7 +99,7 @@ class FastaReader:
     def __next__(self) -> FastaRecord:
         try:
             rust_record = next(self._reader)
-            return FastaRecord(rust_record.id, rust_record.sequence)
+            return FastaRecord(rust_record.header, rust_record.sequence)
         except StopIteration:
             raise
 
@@ -83,4 +112,4 @@ def read_fasta(path: str, sequence_size_hint: int | None = None) -> list[FastaRe
     else:
         # Read from file - use efficient Rust convenience functions.
         rust_records = _prseq.read_fasta(path, sequence_size_hint)
-        return [FastaRecord(r.id, r.sequence) for r in rust_records]
+        return [FastaRecord(r.header, r.sequence) for r in rust_records]
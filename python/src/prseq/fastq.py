@@ -10,19 +10,64 @@ import prseq._prseq as _prseq
 class FastqRecord:
     """Represents a single FASTQ sequence record."""
 
-    def __init__(self, id: str, sequence: str, quality: str):
-        self.id = id
+    def __init__(self, header: str, sequence: str, quality: str):
+        self.header = header
         self.sequence = sequence
         self.quality = quality
 
+    @property
+    def id(self) -> str:
+        """The read identifier: the header up to the first (ASCII) whitespace."""
+        return _prseq.split_header(self.header)[0]
+
+    @property
+    def description(self) -> str | None:
+        """The rest of the header after the identifier, or None if there is none."""
+        return _prseq.split_header(self.header)[1]
+
+    def gc_content(self) -> float | None:
+        """The fraction of the A, C, G and T bases that are G or C, or None if there are none."""
+        return _prseq.gc_content(self.sequence)
+
+    def base_counts(self) -> dict[str, int]:
+        """The number of each base, keyed by A, C, G, T (including U), N and other."""
+        return _prseq.base_counts(self.sequence)
+
+    def n_count(self) -> int:
+        """The number of Ns in the sequence."""
+        return _prseq.n_count(self.sequence)
+
+    def parse_illumina_header(self) -> "_prseq.IlluminaHeader | None":
+        """Parse the header as an Illumina (Casava) read header.
+
+        Returns:
+            An object with instrument, run, flowcell, lane, tile, x, y, umi,
+            read, filtered, control and index attributes (None for fields
+            the header's layout does not have), or None if the header is
+            not an Illumina one.
+        """
+        return _prseq.parse_illumina_header(self.header)
+
+    def parse_nanopore_header(self) -> "_prseq.NanoporeHeader | None":
+        """Parse the key=value fields of an Oxford Nanopore read header.
+
+        Returns:
+            An object with read_id, run_id, read, channel, start_time,
+            flow_cell_id, sample_id, barcode and basecall_model attributes
+            (None for fields the header does not have) and other, a list of
+            (key, value) pairs for the remaining fields, or None if the
+            header has no key=value fields.
+        """
+        return _prseq.parse_nanopore_header(self.header)
+
     def __repr__(self) -> str:
-        return f"FastqRecord(id='{self.id}', sequence='{self.sequence}', quality='{self.quality}')"
+        return f"FastqRecord(header='{self.header}', sequence='{self.sequence}', quality='{self.quality}')"
 
     def __eq__(self, other) -> bool:
         if not isinstance(other, FastqRecord):
             return False
         return (
-            self.id == other.id
+            self.header == other.header
             and self.sequence == other.sequence
             and self.quality == other.quality
         )
@@ -43,7 +88,7 @@ class FastqReader:
 
     def __init__(
         self,
-        source: str | Path | BinaryIO | None = None,
+        source: str | Path | BinaryIO | bytes | None = None,
         sequence_size_hint: int | None = None,
     ):
         """Create a new FASTQ reader.
@@ -52,6 +97,7 @@ class FastqReader:
             source: Input source, can be:
                 - str or Path: Path to a FASTQ file (uncompressed, .gz, or .bz2)
                 - file object: An open file-like object in binary mode ('rb')
+                - bytes: FASTQ data in memory (which may be compressed)
                 - None or "-": Read from stdin
             sequence_size_hint: Optional hint for expected sequence length in characters.
                               Helps optimize memory allocation.
@@ -60,11 +106,17 @@ class FastqReader:
             FileNotFoundError: If the file doesn't exist
             IOError: If there's an error reading the file, or if a file object
                     is opened in text mode instead of binary mode
+            ValueError: If the input is malformed (when records are read). The
+                    message gives the line and record number of the problem
 
         Note:
             File objects must be opened in binary mode ('rb'). Text mode ('r') will
             raise an error.
         """
+        if isinstance(source, (bytes, bytearray, memoryview)):
+            self._reader = _prseq.FastqReader.from_bytes(bytes(source))
+            return
+
         path, fp = parse_args(source)
 
         self._reader = _prseq.FastqReader(
@@ -78,7 +130,7 @@ class FastqReader:
         try:
             rust_record = next(self._reader)
             return FastqRecord(
-                rust_record.id, rust_record.sequence, rust_record.quality
+                rust_record.header, rust_record.sequence, rust_record.quality
             )
         except StopIteration:
             raise
@@ -94,4 +146,4 @@ def read_fastq(
     else:
         # Read from file - use efficient Rust convenience functions.
         rust_records = _prseq.read_fastq(path, sequence_size_hint)
-        return [FastqRecord(r.id, r.sequence, r.quality) for r in rust_records]
+        return [FastqRecord(r.header, r.sequence, r.quality) for r in rust_records]
@@ -13,6 +13,7 @@ import pytest
 
 from prseq import cli
 from prseq.fasta import FastaReader, FastaRecord, read_fasta
+from prseq.fastq import FastqRecord
 
 
 def create_test_fasta() -> Path:
@@ -78,9 +79,9 @@ def test_fasta_reader_iterator() -> None:
         records: list[FastaRecord] = list(reader)
 
         assert len(records) == 2
-        assert records[0].id == "seq1 description one"
+        assert records[0].header == "seq1 description one"
         assert records[0].sequence == "ATCGATCGGCTAGCTA"
-        assert records[1].id == "seq2 description two"
+        assert records[1].header == "seq2 description two"
         assert records[1].sequence == "GGGGCCCC"
     finally:
         fasta_file.unlink()
@@ -113,17 +114,59 @@ def test_fasta_record_tuple() -> None:
         record = records[0]
 
         # Test tuple unpacking
-        record_id, sequence = record
-        assert record_id == "seq1 description one"
+        header, sequence = record
+        assert header == "seq1 description one"
         assert sequence == "ATCGATCGGCTAGCTA"
 
         # Test attribute access
-        assert record.id == record_id
+        assert record.header == header
         assert record.sequence == sequence
     finally:
         fasta_file.unlink()
 
 
+def test_fasta_record_id_and_description() -> None:
+    """Test that the header is split into an ID and a description."""
+    record = FastaRecord("seq1  description one", "ACGT")
+    assert record.id == "seq1"
+    assert record.description == "description one"
+
+    record = FastaRecord("seq2", "ACGT")
+    assert record.id == "seq2"
+    assert record.description is None
+
+
+# The cases of test_headers_split_at_ascii_whitespace in
+# rust/tests/test_defline.rs, which the Python records must split the same way.
+HEADER_SPLITS = [
+    ("id description", "id", "description"),
+    ("id \t two  words ", "id", "two  words "),
+    ("  id after spaces", "id", "after spaces"),
+    ("id\x0bvertical tab", "id\x0bvertical", "tab"),
+    ("id\u00a0no-break space", "id\u00a0no-break", "space"),
+    ("id\u2003em space", "id\u2003em", "space"),
+    ("   ", "", None),
+]
+
+
+@pytest.mark.parametrize("header,id_,description", HEADER_SPLITS)
+def test_record_id_and_description_match_rust(
+    header: str, id_: str, description: str | None
+) -> None:
+    """Test that headers are split at ASCII whitespace only, as in Rust."""
+    record = FastaRecord(header, "ACGT")
+    assert (record.id, record.description) == (id_, description)
+    record = FastqRecord(header, "ACGT", "IIII")
+    assert (record.id, record.description) == (id_, description)
+
+
+def test_record_id_skips_whitespace_after_marker() -> None:
+    """Test that a header with a space after '>' still has an ID."""
+    (record,) = FastaReader(b"> seq1 description one\nACGT\n")
+    assert record.id == "seq1"
+    assert record.description == "description one"
+
+
 def test_multiple_iterations() -> None:
     """Test that we can iterate multiple times."""
     fasta_file = create_test_fasta_multiline()
@@ -144,9 +187,9 @@ def test_gzip_compression() -> None:
         records: list[FastaRecord] = list(reader)
 
         assert len(records) == 2
-        assert records[0].id == "seq1 compressed test"
+        assert records[0].header == "seq1 compressed test"
         assert records[0].sequence == "ATCGATCGGCTAGCTA"
-        assert records[1].id == "seq2 another compressed"
+        assert records[1].header == "seq2 another compressed"
         assert records[1].sequence == "GGGGCCCC"
     finally:
         fasta_file.unlink()
@@ -160,9 +203,9 @@ def test_bz2_compression() -> None:
         records: list[FastaRecord] = list(reader)
 
         assert len(records) == 2
-        assert records[0].id == "seq1 compressed test"
+        assert records[0].header == "seq1 compressed test"
         assert records[0].sequence == "ATCGATCGGCTAGCTA"
-        assert records[1].id == "seq2 another compressed"
+        assert records[1].header == "seq2 another compressed"
         assert records[1].sequence == "GGGGCCCC"
     finally:
         fasta_file.unlink()
@@ -182,7 +225,7 @@ import sys
 from prseq.fasta import FastaReader
 records = list(FastaReader('-'))
 print(f"{len(records)}")
-print(f"{records[0].id}")
+print(f"{records[0].header}")
 print(f"{records[0].sequence}")
 """],
         input=fasta_content,
@@ -211,7 +254,7 @@ import sys
 from prseq.fasta import FastaReader
 records = list(FastaReader())
 print(f"{len(records)}")
-print(f"{records[0].id}")
+print(f"{records[0].header}")
 print(f"{records[0].sequence}")
 """],
         input=fasta_content,
@@ -242,7 +285,7 @@ import sys
 from prseq.fasta import FastaReader
 records = list(FastaReader())
 print(f"{len(records)}")
-print(f"{records[0].id}")
+print(f"{records[0].header}")
 print(f"{records[0].sequence}")
 """],
         input=compressed_content,
@@ -268,7 +311,7 @@ import sys
 from prseq.fasta import FastaReader
 records = list(FastaReader())  # None = stdin
 print(f"{len(records)}")
-print(f"{records[0].id}")
+print(f"{records[0].header}")
 """],
         input=fasta_content,
         text=True,
@@ -290,9 +333,9 @@ def test_file_object_with_open() -> None:
             records: list[FastaRecord] = list(reader)
 
         assert len(records) == 2
-        assert records[0].id == "seq1 description one"
+        assert records[0].header == "seq1 description one"
         assert records[0].sequence == "ATCGATCGGCTAGCTA"
-        assert records[1].id == "seq2 description two"
+        assert records[1].header == "seq2 description two"
         assert records[1].sequence == "GGGGCCCC"
     finally:
         fasta_file.unlink()
@@ -307,9 +350,9 @@ def test_file_object_gzip_compressed() -> None:
             records: list[FastaRecord] = list(reader)
 
         assert len(records) == 2
-        assert records[0].id == "seq1 compressed test"
+        assert records[0].header == "seq1 compressed test"
         assert records[0].sequence == "ATCGATCGGCTAGCTA"
-        assert records[1].id == "seq2 another compressed"
+        assert records[1].header == "seq2 another compressed"
         assert records[1].sequence == "GGGGCCCC"
     finally:
         fasta_file.unlink()
@@ -329,12 +372,23 @@ GGGGCCCC
     records: list[FastaRecord] = list(reader)
 
     assert len(records) == 2
-    assert records[0].id == "seq1 bytesio test"
+    assert records[0].header == "seq1 bytesio test"
     assert records[0].sequence == "ATCGATCG"
-    assert records[1].id == "seq2 another bytesio"
+    assert records[1].header == "seq2 another bytesio"
     assert records[1].sequence == "GGGGCCCC"
 
 
+def test_bytes_source() -> None:
+    """Test reading from bytes in memory, compressed or not."""
+    import gzip
+
+    fasta_content = b">seq1 from bytes\nATCG\nATCG\n>seq2\nGGCC\n"
+    for source in (fasta_content, bytearray(fasta_content), gzip.compress(fasta_content)):
+        records = list(FastaReader(source))
+        assert [record.header for record in records] == ["seq1 from bytes", "seq2"]
+        assert records[0].sequence == "ATCGATCG"
+
+
 def test_file_object_text_mode_error() -> None:
     """Test that opening a file in text mode raises a clear error."""
     fasta_file = create_test_fasta_multiline()
@@ -400,3 +454,12 @@ def test_fasta_filter_function() -> None:
                     assert "filtered 1" in stderr
     finally:
         fasta_file.unlink()
+
+
+def test_fasta_record_composition() -> None:
+    """Test the GC content, base count and N count methods."""
+    record = FastaRecord("seq1", "GGATNnR")
+    assert record.gc_content() == 0.5
+    assert record.n_count() == 2
+    assert record.base_counts() == {"A": 1, "C": 0, "G": 2, "T": 1, "N": 2, "other": 1}
+    assert FastaRecord("seq2", "NNN").gc_content() is None
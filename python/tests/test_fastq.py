@@ -69,19 +69,21 @@ def test_fastq_reader_iterator() -> None:
 
         # Test first record
         record1 = records[0]
-        assert record1.id == "seq1 test sequence"
+        assert record1.header == "seq1 test sequence"
+        assert record1.id == "seq1"
+        assert record1.description == "test sequence"
         assert record1.sequence == "ATCGGATCCTAG"
         assert record1.quality == "IIIIIIIIIIII"
 
         # Test second record with ID in '+' line
         record2 = records[1]
-        assert record2.id == "seq2 another test"
+        assert record2.header == "seq2 another test"
         assert record2.sequence == "GGCCTTAAGGGG"
         assert record2.quality == "JJJJJJJJJJJJ"
 
         # Test third record
         record3 = records[2]
-        assert record3.id == "seq3 short"
+        assert record3.header == "seq3 short"
         assert record3.sequence == "ATCG"
         assert record3.quality == "AAAA"
 
@@ -95,7 +97,7 @@ def test_read_fastq_convenience() -> None:
     try:
         records = read_fastq(str(fastq_file))
         assert len(records) == 3
-        assert records[0].id == "seq1 test sequence"
+        assert records[0].header == "seq1 test sequence"
         assert records[0].sequence == "ATCGGATCCTAG"
         assert records[0].quality == "IIIIIIIIIIII"
     finally:
@@ -105,7 +107,9 @@ def test_read_fastq_convenience() -> None:
 def test_fastq_record() -> None:
     """Test FastqRecord attributes and methods."""
     record = FastqRecord("test_id", "ATCG", "IIII")
+    assert record.header == "test_id"
     assert record.id == "test_id"
+    assert record.description is None
     assert record.sequence == "ATCG"
     assert record.quality == "IIII"
 
@@ -130,10 +134,10 @@ def test_gzip_compression() -> None:
         records = list(reader)
 
         assert len(records) == 2
-        assert records[0].id == "seq1 compressed"
+        assert records[0].header == "seq1 compressed"
         assert records[0].sequence == "ATCGGATCC"
         assert records[0].quality == "IIIIIIIII"
-        assert records[1].id == "seq2 compressed too"
+        assert records[1].header == "seq2 compressed too"
         assert records[1].sequence == "GGCCTTAA"
         assert records[1].quality == "JJJJJJJJ"
     finally:
@@ -148,7 +152,7 @@ def test_bzip2_compression() -> None:
         records = list(reader)
 
         assert len(records) == 2
-        assert records[0].id == "seq1 compressed"
+        assert records[0].header == "seq1 compressed"
         assert records[0].sequence == "ATCGGATCC"
         assert records[0].quality == "IIIIIIIII"
     finally:
@@ -177,10 +181,10 @@ def test_file_object_with_open() -> None:
             records: list[FastqRecord] = list(reader)
 
         assert len(records) == 3
-        assert records[0].id == "seq1 test sequence"
+        assert records[0].header == "seq1 test sequence"
         assert records[0].sequence == "ATCGGATCCTAG"
         assert records[0].quality == "IIIIIIIIIIII"
-        assert records[1].id == "seq2 another test"
+        assert records[1].header == "seq2 another test"
         assert records[1].sequence == "GGCCTTAAGGGG"
         assert records[1].quality == "JJJJJJJJJJJJ"
     finally:
@@ -196,10 +200,10 @@ def test_file_object_gzip_compressed() -> None:
             records: list[FastqRecord] = list(reader)
 
         assert len(records) == 2
-        assert records[0].id == "seq1 compressed"
+        assert records[0].header == "seq1 compressed"
         assert records[0].sequence == "ATCGGATCC"
         assert records[0].quality == "IIIIIIIII"
-        assert records[1].id == "seq2 compressed too"
+        assert records[1].header == "seq2 compressed too"
         assert records[1].sequence == "GGCCTTAA"
         assert records[1].quality == "JJJJJJJJ"
     finally:
@@ -224,14 +228,25 @@ JJJJJJJJ
     records: list[FastqRecord] = list(reader)
 
     assert len(records) == 2
-    assert records[0].id == "seq1 bytesio test"
+    assert records[0].header == "seq1 bytesio test"
     assert records[0].sequence == "ATCGGATCC"
     assert records[0].quality == "IIIIIIIII"
-    assert records[1].id == "seq2 another bytesio"
+    assert records[1].header == "seq2 another bytesio"
     assert records[1].sequence == "GGCCTTAA"
     assert records[1].quality == "JJJJJJJJ"
 
 
+def test_bytes_source() -> None:
+    """Test reading from bytes in memory, compressed or not."""
+    import gzip
+
+    fastq_content = b"@seq1 from bytes\nATCG\n+\nIIII\n@seq2\nGG\n+\nJJ\n"
+    for source in (fastq_content, memoryview(fastq_content), gzip.compress(fastq_content)):
+        records = list(FastqReader(source))
+        assert [record.header for record in records] == ["seq1 from bytes", "seq2"]
+        assert records[1].quality == "JJ"
+
+
 def test_file_object_text_mode_error() -> None:
     """Test that opening a file in text mode raises a clear error."""
     fastq_file = create_test_fastq()
@@ -240,4 +255,40 @@ def test_file_object_text_mode_error() -> None:
             with pytest.raises(IOError, match="binary mode"):
                 FastqReader(f)
     finally:
-        fastq_file.unlink()
\ No newline at end of file
+        fastq_file.unlink()
+
+
+def test_parse_illumina_header() -> None:
+    """Test parsing an Illumina read header."""
+    record = FastqRecord("M00123:45:FC1:1:1101:15589:1333 2:Y:0:ATCACG", "ACGT", "IIII")
+    header = record.parse_illumina_header()
+    assert header is not None
+    assert header.instrument == "M00123"
+    assert header.flowcell == "FC1"
+    assert (header.lane, header.tile, header.x, header.y) == (1, 1101, 15589, 1333)
+    assert header.read == 2
+    assert header.filtered is True
+    assert header.index == "ATCACG"
+
+    assert FastqRecord("read1", "ACGT", "IIII").parse_illumina_header() is None
+
+
+def test_parse_nanopore_header() -> None:
+    """Test parsing an Oxford Nanopore read header."""
+    record = FastqRecord(
+        "0e3c4b7a runid=8f2e read=5512 ch=118 start_time=2023-05-04T10:15:30Z "
+        "protocol_group_id=run1 barcode=barcode07",
+        "ACGT",
+        "IIII",
+    )
+    header = record.parse_nanopore_header()
+    assert header is not None
+    assert header.read_id == "0e3c4b7a"
+    assert header.run_id == "8f2e"
+    assert (header.read, header.channel) == (5512, 118)
+    assert header.start_time == "2023-05-04T10:15:30Z"
+    assert header.barcode == "barcode07"
+    assert header.sample_id is None
+    assert header.other == [("protocol_group_id", "run1")]
+
+    assert FastqRecord("read1", "ACGT", "IIII").parse_nanopore_header() is None
@@ -0,0 +1,71 @@
+use std::fmt;
+
+/// A set of characters that sequences can be checked against
+///
+/// All alphabets accept both upper and lower case (e.g. soft-masked
+/// sequence).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Alphabet {
+    /// A, C, G, T and N
+    Dna,
+    /// A, C, G, U and N
+    Rna,
+    /// The IUPAC nucleotide codes (A, C, G, T, U, R, Y, S, W, K, M, B, D,
+    /// H, V and N) and the gap character '-'
+    Iupac,
+    /// The 20 standard amino acids, the ambiguity codes B, J, X and Z, U
+    /// (selenocysteine), O (pyrrolysine), '*' (stop) and '-' (gap)
+    Protein,
+}
+
+const fn table(characters: &[u8]) -> [bool; 256] {
+    let mut table = [false; 256];
+    let mut i = 0;
+    while i < characters.len() {
+        let c = characters[i];
+        table[c as usize] = true;
+        table[c.to_ascii_lowercase() as usize] = true;
+        i += 1;
+    }
+    table
+}
+
+static DNA: [bool; 256] = table(b"ACGTN");
+static RNA: [bool; 256] = table(b"ACGUN");
+static IUPAC: [bool; 256] = table(b"ACGTURYSWKMBDHVN-");
+static PROTEIN: [bool; 256] = table(b"ACDEFGHIKLMNPQRSTVWYBJXZUO*-");
+
+impl Alphabet {
+    fn table(&self) -> &'static [bool; 256] {
+        match self {
+            Alphabet::Dna => &DNA,
+            Alphabet::Rna => &RNA,
+            Alphabet::Iupac => &IUPAC,
+            Alphabet::Protein => &PROTEIN,
+        }
+    }
+
+    /// Whether `byte` is in this alphabet
+    pub fn contains(&self, byte: u8) -> bool {
+        self.table()[byte as usize]
+    }
+
+    /// The offset of the first byte of `sequence` that is not in this
+    /// alphabet, or None if they all are
+    pub fn find_invalid(&self, sequence: &[u8]) -> Option<usize> {
+        let table = self.table();
+        sequence.iter().position(|&b| !table[b as usize])
+    }
+}
+
+impl fmt::Display for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Alphabet::Dna => "DNA",
+            Alphabet::Rna => "RNA",
+            Alphabet::Iupac => "IUPAC nucleotide",
+            Alphabet::Protein => "protein",
+        })
+    }
+}
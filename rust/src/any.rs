@@ -0,0 +1,240 @@
+use crate::common::{open_input, InputSource, LineReader};
+use crate::error::Result;
+use crate::fasta::{FastaReader, FastaRecord};
+use crate::fastq::{FastqReader, FastqRecord};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// The sequence file formats that can be detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SequenceFormat {
+    Fasta,
+    Fastq,
+}
+
+/// A record read by an AnyReader, from either a FASTA or a FASTQ file
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnyRecord {
+    Fasta(FastaRecord),
+    Fastq(FastqRecord),
+}
+
+impl AnyRecord {
+    /// The header line, without its '>' or '@'
+    pub fn header(&self) -> &str {
+        match self {
+            AnyRecord::Fasta(record) => &record.header,
+            AnyRecord::Fastq(record) => &record.header,
+        }
+    }
+
+    /// The ID: the header up to the first whitespace
+    pub fn id(&self) -> &str {
+        match self {
+            AnyRecord::Fasta(record) => record.id(),
+            AnyRecord::Fastq(record) => record.id(),
+        }
+    }
+
+    /// The description: the rest of the header after the ID, if there is any
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            AnyRecord::Fasta(record) => record.description(),
+            AnyRecord::Fastq(record) => record.description(),
+        }
+    }
+
+    /// The sequence
+    pub fn sequence(&self) -> &str {
+        match self {
+            AnyRecord::Fasta(record) => &record.sequence,
+            AnyRecord::Fastq(record) => &record.sequence,
+        }
+    }
+
+    /// The quality string, for FASTQ records
+    pub fn quality(&self) -> Option<&str> {
+        match self {
+            AnyRecord::Fasta(_) => None,
+            AnyRecord::Fastq(record) => Some(&record.quality),
+        }
+    }
+
+    /// The format of the file the record was read from
+    pub fn format(&self) -> SequenceFormat {
+        match self {
+            AnyRecord::Fasta(_) => SequenceFormat::Fasta,
+            AnyRecord::Fastq(_) => SequenceFormat::Fastq,
+        }
+    }
+}
+
+/// Displays the record as FASTA or FASTQ, as its own Display does
+impl fmt::Display for AnyRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnyRecord::Fasta(record) => record.fmt(f),
+            AnyRecord::Fastq(record) => record.fmt(f),
+        }
+    }
+}
+
+impl From<FastaRecord> for AnyRecord {
+    fn from(record: FastaRecord) -> Self {
+        AnyRecord::Fasta(record)
+    }
+}
+
+impl From<FastqRecord> for AnyRecord {
+    fn from(record: FastqRecord) -> Self {
+        AnyRecord::Fastq(record)
+    }
+}
+
+enum Inner {
+    Fasta(FastaReader),
+    Fastq(FastqReader),
+    Empty,
+}
+
+/// Iterator over the records of a FASTA or FASTQ file, with the format
+/// detected from the first non-blank character ('>' or '@')
+pub struct AnyReader {
+    inner: Inner,
+}
+
+impl AnyReader {
+    /// Create a new AnyReader from a file path
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_file_with_capacity(path, 64 * 1024)
+    }
+
+    /// Create a new AnyReader from a file path with a sequence size hint
+    pub fn from_file_with_capacity<P: AsRef<Path>>(
+        path: P,
+        sequence_size_hint: usize,
+    ) -> Result<Self> {
+        let file = File::open(path)?;
+        Self::from_reader_with_capacity(file, sequence_size_hint)
+    }
+
+    /// Create a new AnyReader from stdin
+    pub fn from_stdin() -> Result<Self> {
+        Self::from_reader_with_capacity(std::io::stdin(), 64 * 1024)
+    }
+
+    /// Create a new AnyReader from any readable source with compression detection
+    pub fn from_reader_with_capacity<R: Read + Send + 'static>(
+        reader: R,
+        sequence_size_hint: usize,
+    ) -> Result<Self> {
+        let mut input = open_input(reader, None, 1, 64 * 1024)?;
+        let (format, skipped, lines) = detect_format(input.as_mut())?;
+        let inner = match format {
+            Some(SequenceFormat::Fasta) => Inner::Fasta(FastaReader::from_line_reader(
+                LineReader::with_offset(input, skipped, lines),
+                sequence_size_hint,
+            )),
+            Some(SequenceFormat::Fastq) => Inner::Fastq(FastqReader::from_line_reader(
+                LineReader::with_offset(input, skipped, lines),
+                sequence_size_hint,
+            )),
+            None => Inner::Empty,
+        };
+        Ok(AnyReader { inner })
+    }
+
+    /// The byte offset, in the uncompressed input, of the start of the most
+    /// recently returned record
+    pub fn offset(&self) -> Option<u64> {
+        match &self.inner {
+            Inner::Fasta(reader) => reader.offset(),
+            Inner::Fastq(reader) => reader.offset(),
+            Inner::Empty => None,
+        }
+    }
+
+    /// The detected format, or None if the input was empty
+    pub fn format(&self) -> Option<SequenceFormat> {
+        match self.inner {
+            Inner::Fasta(_) => Some(SequenceFormat::Fasta),
+            Inner::Fastq(_) => Some(SequenceFormat::Fastq),
+            Inner::Empty => None,
+        }
+    }
+}
+
+/// Look at the first non-whitespace byte of the (decompressed) input to
+/// decide its format, without consuming anything but leading whitespace.
+/// Also returns the number of whitespace bytes, and of the line endings
+/// among them, consumed.
+pub(crate) fn detect_format(
+    input: &mut dyn InputSource,
+) -> io::Result<(Option<SequenceFormat>, u64, u64)> {
+    let mut skipped = 0;
+    let mut lines = 0;
+    let mut after_cr = false;
+    loop {
+        let buf = input.fill_buf()?;
+        if buf.is_empty() {
+            return Ok((None, skipped, lines));
+        }
+        let format = match buf.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'>') => SequenceFormat::Fasta,
+            Some(b'@') => SequenceFormat::Fastq,
+            Some(&other) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Cannot detect sequence format: input starts with {:?} \
+                         rather than '>' (FASTA) or '@' (FASTQ)",
+                        other as char
+                    ),
+                ))
+            }
+            None => {
+                let len = buf.len();
+                lines += count_line_endings(buf, &mut after_cr);
+                input.consume(len);
+                skipped += len as u64;
+                continue;
+            }
+        };
+        // The "\n" of a "\r\n" whose "\r" was consumed (and counted) with the
+        // previous buffer
+        if after_cr && buf[0] == b'\n' {
+            input.consume(1);
+            skipped += 1;
+        }
+        return Ok((Some(format), skipped, lines));
+    }
+}
+
+/// The number of line endings in `bytes`, counting "\n", "\r\n" and a
+/// lone "\r" as `LineReader` does, given whether the byte before them was
+/// a "\r"
+fn count_line_endings(bytes: &[u8], after_cr: &mut bool) -> u64 {
+    let mut count = 0;
+    for &byte in bytes {
+        if byte == b'\r' || (byte == b'\n' && !*after_cr) {
+            count += 1;
+        }
+        *after_cr = byte == b'\r';
+    }
+    count
+}
+
+impl Iterator for AnyReader {
+    type Item = Result<AnyRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            Inner::Fasta(reader) => reader.next().map(|r| r.map(AnyRecord::Fasta)),
+            Inner::Fastq(reader) => reader.next().map(|r| r.map(AnyRecord::Fastq)),
+            Inner::Empty => None,
+        }
+    }
+}
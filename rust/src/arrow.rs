@@ -0,0 +1,146 @@
+use crate::any::AnyRecord;
+use crate::common::split_header_str;
+use crate::error::Result;
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use arrow_array::builder::{ArrayBuilder, StringBuilder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use std::sync::Arc;
+
+/// The schema of the record batches made here: the non-null `id`,
+/// `sequence` and `length` columns, and the `description` and `quality`
+/// columns, which are null for records without them
+pub fn schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("description", DataType::Utf8, true),
+        Field::new("sequence", DataType::Utf8, false),
+        Field::new("quality", DataType::Utf8, true),
+        Field::new("length", DataType::UInt64, false),
+    ]))
+}
+
+/// A record that can be added to a `RecordBatchBuilder`
+pub trait ArrowRecord {
+    /// The header, sequence and (for FASTQ records) quality
+    fn fields(&self) -> (&str, &str, Option<&str>);
+}
+
+impl ArrowRecord for FastaRecord {
+    fn fields(&self) -> (&str, &str, Option<&str>) {
+        (&self.header, &self.sequence, None)
+    }
+}
+
+impl ArrowRecord for FastqRecord {
+    fn fields(&self) -> (&str, &str, Option<&str>) {
+        (&self.header, &self.sequence, Some(&self.quality))
+    }
+}
+
+impl ArrowRecord for AnyRecord {
+    fn fields(&self) -> (&str, &str, Option<&str>) {
+        (self.header(), self.sequence(), self.quality())
+    }
+}
+
+/// Collects records into Arrow columns, to be taken as a `RecordBatch`
+/// with the columns of `schema()`
+///
+/// ```
+/// use prseq::arrow::RecordBatchBuilder;
+/// use prseq::FastaRecord;
+///
+/// let mut builder = RecordBatchBuilder::new();
+/// builder.push(&FastaRecord { header: "s1 first".into(), sequence: "ACGT".into() });
+/// let batch = builder.finish();
+/// assert_eq!(batch.num_rows(), 1);
+/// assert_eq!(batch.num_columns(), 5);
+/// ```
+#[derive(Debug)]
+pub struct RecordBatchBuilder {
+    ids: StringBuilder,
+    descriptions: StringBuilder,
+    sequences: StringBuilder,
+    qualities: StringBuilder,
+    lengths: UInt64Builder,
+}
+
+impl Default for RecordBatchBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RecordBatchBuilder {
+    /// Create a new, empty RecordBatchBuilder
+    pub fn new() -> Self {
+        RecordBatchBuilder {
+            ids: StringBuilder::new(),
+            descriptions: StringBuilder::new(),
+            sequences: StringBuilder::new(),
+            qualities: StringBuilder::new(),
+            lengths: UInt64Builder::new(),
+        }
+    }
+
+    /// Add a record
+    pub fn push<R: ArrowRecord>(&mut self, record: &R) {
+        let (header, sequence, quality) = record.fields();
+        let (id, description) = split_header_str(header);
+        self.ids.append_value(id);
+        self.descriptions.append_option(description);
+        self.sequences.append_value(sequence);
+        self.qualities.append_option(quality);
+        self.lengths.append_value(sequence.len() as u64);
+    }
+
+    /// The number of records added since the last batch was taken
+    pub fn len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    /// Whether no records have been added since the last batch was taken
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Take the records added so far as a batch, leaving the builder empty
+    pub fn finish(&mut self) -> RecordBatch {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.ids.finish()),
+            Arc::new(self.descriptions.finish()),
+            Arc::new(self.sequences.finish()),
+            Arc::new(self.qualities.finish()),
+            Arc::new(self.lengths.finish()),
+        ];
+        RecordBatch::try_new(schema(), columns).expect("the columns match the schema")
+    }
+}
+
+/// Extension trait for turning iterators of records into Arrow record
+/// batches
+pub trait ArrowExt<R: ArrowRecord>: Iterator<Item = Result<R>> + Sized {
+    /// Collect the records into batches of `batch_size` records (the last
+    /// may be smaller; a size of 0 is taken as 1). A reading error is returned in place of a batch,
+    /// and the records before it stay in the next batch.
+    fn record_batches(mut self, batch_size: usize) -> impl Iterator<Item = Result<RecordBatch>> {
+        let batch_size = batch_size.max(1);
+        let mut builder = RecordBatchBuilder::new();
+        std::iter::from_fn(move || loop {
+            match self.next() {
+                Some(Ok(record)) => {
+                    builder.push(&record);
+                    if builder.len() == batch_size {
+                        return Some(Ok(builder.finish()));
+                    }
+                }
+                Some(Err(e)) => return Some(Err(e)),
+                None => return (!builder.is_empty()).then(|| Ok(builder.finish())),
+            }
+        })
+    }
+}
+
+impl<R: ArrowRecord, I: Iterator<Item = Result<R>>> ArrowExt<R> for I {}
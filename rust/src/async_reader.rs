@@ -0,0 +1,133 @@
+use crate::error::Result;
+use crate::fasta::{FastaReader, FastaRecord};
+use crate::fastq::{FastqReader, FastqRecord};
+use futures_core::Stream;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::AsyncRead;
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::io::SyncIoBridge;
+
+/// The number of records parsed ahead of the consumer
+const QUEUE_DEPTH: usize = 1024;
+
+/// An asynchronous stream of records
+///
+/// The input is read asynchronously, but (to share one parser with the
+/// synchronous readers) compression detection, decompression and parsing
+/// run on a tokio blocking thread, which sends records back over a bounded
+/// channel. Must be created from within a tokio runtime.
+pub struct AsyncReader<T> {
+    receiver: mpsc::Receiver<Result<T>>,
+}
+
+/// An asynchronous FASTA reader, yielding `Result<FastaRecord>`
+pub type AsyncFastaReader = AsyncReader<FastaRecord>;
+
+/// An asynchronous FASTQ reader, yielding `Result<FastqRecord>`
+pub type AsyncFastqReader = AsyncReader<FastqRecord>;
+
+impl AsyncReader<FastaRecord> {
+    /// Open a FASTA file, detecting its compression
+    pub async fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(tokio::fs::File::open(path).await?).await
+    }
+
+    /// Create a FASTA reader from any asynchronous source, detecting its
+    /// compression
+    pub async fn from_reader<R>(reader: R) -> Result<Self>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        Self::from_reader_with_capacity(reader, 64 * 1024).await
+    }
+
+    /// Create a FASTA reader from any asynchronous source with a sequence
+    /// size hint
+    pub async fn from_reader_with_capacity<R>(reader: R, sequence_size_hint: usize) -> Result<Self>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let input = SyncIoBridge::new(reader);
+        spawn_parser(move || FastaReader::from_reader_with_capacity(input, sequence_size_hint))
+            .await
+    }
+}
+
+impl AsyncReader<FastqRecord> {
+    /// Open a FASTQ file, detecting its compression
+    pub async fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(tokio::fs::File::open(path).await?).await
+    }
+
+    /// Create a FASTQ reader from any asynchronous source, detecting its
+    /// compression
+    pub async fn from_reader<R>(reader: R) -> Result<Self>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        Self::from_reader_with_capacity(reader, 64 * 1024).await
+    }
+
+    /// Create a FASTQ reader from any asynchronous source with a sequence
+    /// size hint
+    pub async fn from_reader_with_capacity<R>(reader: R, sequence_size_hint: usize) -> Result<Self>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let input = SyncIoBridge::new(reader);
+        spawn_parser(move || FastqReader::from_reader_with_capacity(input, sequence_size_hint))
+            .await
+    }
+}
+
+impl<T> AsyncReader<T> {
+    /// Read the next record, or None at end of input
+    pub async fn next_record(&mut self) -> Option<Result<T>> {
+        self.receiver.recv().await
+    }
+}
+
+impl<T> Stream for AsyncReader<T> {
+    type Item = Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<T>>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Open a reader on a blocking thread and stream its records back,
+/// returning once the reader has been opened (and so the compression of its
+/// input detected)
+async fn spawn_parser<I, T, F>(open: F) -> Result<AsyncReader<T>>
+where
+    F: FnOnce() -> Result<I> + Send + 'static,
+    I: Iterator<Item = Result<T>>,
+    T: Send + 'static,
+{
+    let (opened, ready) = oneshot::channel();
+    let (sender, receiver) = mpsc::channel(QUEUE_DEPTH);
+    tokio::task::spawn_blocking(move || {
+        let reader = match open() {
+            Ok(reader) => {
+                let _ = opened.send(Ok(()));
+                reader
+            }
+            Err(e) => {
+                let _ = opened.send(Err(e));
+                return;
+            }
+        };
+        for record in reader {
+            if sender.blocking_send(record).is_err() {
+                // The consumer has gone away.
+                break;
+            }
+        }
+    });
+    ready
+        .await
+        .map_err(|_| std::io::Error::other("Reader task failed before opening its input"))??;
+    Ok(AsyncReader { receiver })
+}
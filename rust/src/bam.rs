@@ -0,0 +1,245 @@
+use crate::bgzf::BgzfReader;
+use crate::error::Result;
+use crate::fastq::FastqRecord;
+use crate::sam::SamRecord;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+/// The magic bytes at the start of the (decompressed) BAM header
+const MAGIC: &[u8; 4] = b"BAM\x01";
+
+/// The bases for each 4-bit code in a packed BAM sequence
+const BASES: &[u8; 16] = b"=ACMGRSVTWYHKDBN";
+
+/// The highest quality score that has a Phred+33 character ('~')
+const MAX_SCORE: u8 = 93;
+
+/// The size of the fixed-length fields at the start of each record
+const FIXED_LENGTH: usize = 32;
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+/// Iterator over the reads in a BAM file, as FASTQ records
+///
+/// This is the binary counterpart of `SamReader`, and recovers reads in the
+/// same way: reverse-strand reads are turned back, and secondary and
+/// supplementary alignments are skipped unless `include_secondary(true)`
+/// is used. Only the name, flag, sequence and quality of each record are
+/// decoded, which is all an unaligned BAM (uBAM) holds of interest.
+///
+/// ```no_run
+/// use prseq::bam::BamReader;
+///
+/// let reader = BamReader::from_file("unaligned.bam").unwrap().mate_suffix(true);
+/// for read in reader {
+///     let read = read.unwrap(); // a FastqRecord
+/// }
+/// ```
+pub struct BamReader<R: Read = BufReader<File>> {
+    reader: BgzfReader<R>,
+    header_text: String,
+    references: Vec<(String, u32)>,
+    records: u64,
+    include_secondary: bool,
+    mate_suffix: bool,
+}
+
+impl BamReader<BufReader<File>> {
+    /// Open a BAM file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> BamReader<R> {
+    /// Create a new BamReader from BGZF-compressed BAM data, reading its
+    /// header
+    pub fn new(reader: R) -> Result<Self> {
+        let mut reader = BgzfReader::new(reader);
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("Not a BAM file (bad magic number)".to_string()).into());
+        }
+        let text_length = read_u32(&mut reader)?;
+        let mut text = read_bytes(&mut reader, text_length)?;
+        // The text may be padded with NULs.
+        let end = text.iter().position(|&b| b == 0).unwrap_or(text.len());
+        text.truncate(end);
+        let header_text = String::from_utf8(text)
+            .map_err(|_| invalid("BAM header text is not valid UTF-8".to_string()))?;
+
+        let count = read_u32(&mut reader)?;
+        let mut references = Vec::new();
+        for _ in 0..count {
+            let name_length = read_u32(&mut reader)?;
+            let mut name = read_bytes(&mut reader, name_length)?;
+            if name.last() == Some(&0) {
+                name.pop();
+            }
+            let name = String::from_utf8(name)
+                .map_err(|_| invalid("BAM reference name is not valid UTF-8".to_string()))?;
+            references.push((name, read_u32(&mut reader)?));
+        }
+
+        Ok(BamReader {
+            reader,
+            header_text,
+            references,
+            records: 0,
+            include_secondary: false,
+            mate_suffix: false,
+        })
+    }
+
+    /// Set whether secondary and supplementary alignments are returned
+    pub fn include_secondary(mut self, include: bool) -> Self {
+        self.include_secondary = include;
+        self
+    }
+
+    /// Set whether "/1" and "/2" are added to the names of paired reads
+    pub fn mate_suffix(mut self, mate_suffix: bool) -> Self {
+        self.mate_suffix = mate_suffix;
+        self
+    }
+
+    /// The SAM header text (the `@` lines)
+    pub fn header_text(&self) -> &str {
+        &self.header_text
+    }
+
+    /// The names and lengths of the reference sequences. This is empty for
+    /// unaligned BAM.
+    pub fn references(&self) -> &[(String, u32)] {
+        &self.references
+    }
+
+    /// Read the next record (including secondary alignments) as stored,
+    /// returning None at end of input
+    pub fn next_sam_record(&mut self) -> Result<Option<SamRecord>> {
+        if self.reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let number = self.records + 1;
+        let truncated = |_| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("BAM record {} is truncated", number),
+            )
+        };
+        let block_size = read_u32(&mut self.reader).map_err(truncated)?;
+        if (block_size as usize) < FIXED_LENGTH {
+            return Err(invalid(format!(
+                "BAM record {} has block size {}, which is too small",
+                number, block_size
+            ))
+            .into());
+        }
+        let block = read_bytes(&mut self.reader, block_size).map_err(truncated)?;
+        self.records = number;
+        Ok(Some(parse_record(&block, number)?))
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Read `length` bytes. The lengths in a BAM file are not trusted, so the
+/// buffer grows as the bytes arrive rather than being allocated up front.
+fn read_bytes<R: Read>(reader: &mut R, length: u32) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(length as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < length as usize {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
+
+/// Decode the name, flag, sequence and quality of a BAM record (without
+/// its block size)
+fn parse_record(block: &[u8], number: u64) -> io::Result<SamRecord> {
+    let name_length = block[8] as usize;
+    let cigar_length = u16_at(block, 12) as usize * 4;
+    let flag = u16_at(block, 14);
+    let length = u32_at(block, 16) as usize;
+
+    let name_start = FIXED_LENGTH;
+    let sequence_start = name_start + name_length + cigar_length;
+    let quality_start = sequence_start + length.div_ceil(2);
+    if quality_start + length > block.len() || name_length == 0 {
+        return Err(invalid(format!(
+            "BAM record {} is shorter than its fields",
+            number
+        )));
+    }
+
+    // The name is NUL-terminated.
+    let name = &block[name_start..name_start + name_length - 1];
+    let name = String::from_utf8(name.to_vec())
+        .map_err(|_| invalid(format!("BAM record {} name is not valid UTF-8", number)))?;
+    let sequence = (0..length)
+        .map(|i| {
+            let byte = block[sequence_start + i / 2];
+            let code = if i % 2 == 0 { byte >> 4 } else { byte & 0xf };
+            BASES[code as usize] as char
+        })
+        .collect();
+    let quality = &block[quality_start..quality_start + length];
+    // A missing quality is stored as 0xFF for every base.
+    let quality = if quality.first() == Some(&0xff) {
+        String::new()
+    } else if let Some(&score) = quality.iter().find(|&&score| score > MAX_SCORE) {
+        return Err(invalid(format!(
+            "BAM record {} has quality score {} (expected 0 to {})",
+            number, score, MAX_SCORE
+        )));
+    } else {
+        quality.iter().map(|&score| (score + 33) as char).collect()
+    };
+
+    Ok(SamRecord {
+        name,
+        flag,
+        sequence,
+        quality,
+    })
+}
+
+impl<R: Read> Iterator for BamReader<R> {
+    type Item = Result<FastqRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.next_sam_record() {
+                Ok(Some(record)) => {
+                    if record.is_secondary() && !self.include_secondary {
+                        continue;
+                    }
+                    return Some(Ok(record.to_fastq(self.mate_suffix)));
+                }
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
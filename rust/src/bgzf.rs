@@ -0,0 +1,503 @@
+use crate::error::Result;
+use flate2::read::DeflateDecoder;
+use flate2::Crc;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Size of the fixed part of a gzip member header (up to and including XLEN)
+const HEADER_SIZE: usize = 12;
+
+/// Size of the gzip member trailer (CRC32 and ISIZE)
+const TRAILER_SIZE: usize = 8;
+
+/// A BGZF virtual file offset.
+///
+/// The upper 48 bits hold the offset of a BGZF block in the compressed file
+/// and the lower 16 bits hold the offset within that block's uncompressed
+/// data, exactly as used by samtools/htslib.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct VirtualOffset(u64);
+
+impl VirtualOffset {
+    /// Create a virtual offset from a compressed block offset and an offset
+    /// within the uncompressed block data
+    pub fn new(compressed: u64, uncompressed: u16) -> Self {
+        VirtualOffset((compressed << 16) | u64::from(uncompressed))
+    }
+
+    /// The offset of the BGZF block in the compressed file
+    pub fn compressed(&self) -> u64 {
+        self.0 >> 16
+    }
+
+    /// The offset within the uncompressed data of the block
+    pub fn uncompressed(&self) -> u16 {
+        (self.0 & 0xffff) as u16
+    }
+}
+
+impl From<u64> for VirtualOffset {
+    fn from(value: u64) -> Self {
+        VirtualOffset(value)
+    }
+}
+
+impl From<VirtualOffset> for u64 {
+    fn from(offset: VirtualOffset) -> Self {
+        offset.0
+    }
+}
+
+impl fmt::Display for VirtualOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.compressed(), self.uncompressed())
+    }
+}
+
+/// Return true if `bytes` start with a BGZF block header (a gzip member
+/// header carrying the 'BC' extra subfield)
+pub fn is_bgzf(bytes: &[u8]) -> bool {
+    if bytes.len() < HEADER_SIZE + 6
+        || bytes[0] != 0x1f
+        || bytes[1] != 0x8b
+        || bytes[2] != 8
+        || bytes[3] & 4 == 0
+    {
+        return false;
+    }
+    let xlen = u16::from_le_bytes([bytes[10], bytes[11]]) as usize;
+    let end = (HEADER_SIZE + xlen).min(bytes.len());
+    find_block_size(&bytes[HEADER_SIZE..end]).is_some()
+}
+
+/// Find the BSIZE value in the 'BC' subfield of a gzip extra field
+fn find_block_size(extra: &[u8]) -> Option<u16> {
+    let mut i = 0;
+    while i + 4 <= extra.len() {
+        let slen = u16::from_le_bytes([extra[i + 2], extra[i + 3]]) as usize;
+        if extra[i] == b'B' && extra[i + 1] == b'C' && slen == 2 && i + 6 <= extra.len() {
+            return Some(u16::from_le_bytes([extra[i + 4], extra[i + 5]]));
+        }
+        i += 4 + slen;
+    }
+    None
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Decompress a single complete BGZF block (header, data, and trailer) into
+/// `output`, which is cleared first
+pub(crate) fn decompress_block(block: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+    let xlen = u16::from_le_bytes([block[10], block[11]]) as usize;
+    let data = &block[HEADER_SIZE + xlen..block.len() - TRAILER_SIZE];
+    let trailer = &block[block.len() - TRAILER_SIZE..];
+    let expected_crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let expected_size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+
+    output.clear();
+    output.reserve(expected_size as usize);
+    DeflateDecoder::new(data).read_to_end(output)?;
+
+    if output.len() != expected_size as usize {
+        return Err(invalid_data(format!(
+            "BGZF block decompressed to {} bytes, expected {}",
+            output.len(),
+            expected_size
+        )));
+    }
+    let mut crc = Crc::new();
+    crc.update(output);
+    if crc.sum() != expected_crc {
+        return Err(invalid_data("BGZF block CRC32 mismatch".to_string()));
+    }
+    Ok(())
+}
+
+/// Read the next raw BGZF block, which starts at compressed offset `offset`,
+/// from `reader` into `block`. Returns false at a clean end of input.
+pub(crate) fn read_raw_block<R: Read>(
+    reader: &mut R,
+    block: &mut Vec<u8>,
+    offset: u64,
+) -> io::Result<bool> {
+    block.clear();
+    block.resize(HEADER_SIZE, 0);
+    let mut filled = 0;
+    while filled < HEADER_SIZE {
+        match reader.read(&mut block[filled..])? {
+            0 if filled == 0 => return Ok(false),
+            0 => return Err(truncated(offset, offset + filled as u64)),
+            n => filled += n,
+        }
+    }
+
+    if block[0] != 0x1f || block[1] != 0x8b || block[2] != 8 || block[3] & 4 == 0 {
+        return Err(invalid_data(format!(
+            "Input is not in BGZF format (at offset {})",
+            offset
+        )));
+    }
+
+    let xlen = u16::from_le_bytes([block[10], block[11]]) as usize;
+    block.resize(HEADER_SIZE + xlen, 0);
+    read_block_part(reader, &mut block[HEADER_SIZE..], offset, HEADER_SIZE)?;
+
+    let block_size = find_block_size(&block[HEADER_SIZE..])
+        .ok_or_else(|| invalid_data("Gzip member has no BGZF 'BC' extra field".to_string()))?
+        as usize
+        + 1;
+    if block_size < HEADER_SIZE + xlen + TRAILER_SIZE {
+        return Err(invalid_data(format!(
+            "Invalid BGZF block size {}",
+            block_size
+        )));
+    }
+
+    block.resize(block_size, 0);
+    read_block_part(
+        reader,
+        &mut block[HEADER_SIZE + xlen..],
+        offset,
+        HEADER_SIZE + xlen,
+    )?;
+    Ok(true)
+}
+
+/// Fill `buf` from `reader`, reporting a truncated block if the input ends
+fn read_block_part<R: Read>(
+    reader: &mut R,
+    buf: &mut [u8],
+    offset: u64,
+    already_read: usize,
+) -> io::Result<()> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => return Err(truncated(offset, offset + (already_read + filled) as u64)),
+            n => filled += n,
+        }
+    }
+    Ok(())
+}
+
+fn truncated(block_offset: u64, reached: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!(
+            "Truncated BGZF stream: input ended at offset {} inside the block starting at offset {}",
+            reached, block_offset
+        ),
+    )
+}
+
+/// A reader for BGZF-compressed data (as produced by `bgzip`) that
+/// decompresses one block at a time and keeps track of virtual offsets
+pub struct BgzfReader<R> {
+    inner: R,
+    block_offset: u64,
+    next_block_offset: u64,
+    compressed: Vec<u8>,
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl<R: Read> BgzfReader<R> {
+    /// Create a new BgzfReader positioned at the start of `inner`
+    pub fn new(inner: R) -> Self {
+        BgzfReader {
+            inner,
+            block_offset: 0,
+            next_block_offset: 0,
+            compressed: Vec::with_capacity(64 * 1024),
+            buffer: Vec::with_capacity(64 * 1024),
+            position: 0,
+        }
+    }
+
+    /// The virtual offset of the next byte that will be read
+    pub fn virtual_offset(&self) -> VirtualOffset {
+        if self.position < self.buffer.len() {
+            VirtualOffset::new(self.block_offset, self.position as u16)
+        } else {
+            // At the end of a block, the next byte is the first byte of the
+            // following block.
+            VirtualOffset::new(self.next_block_offset, 0)
+        }
+    }
+
+    /// Consume this reader, returning the underlying reader
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn read_block(&mut self) -> io::Result<bool> {
+        if !read_raw_block(
+            &mut self.inner,
+            &mut self.compressed,
+            self.next_block_offset,
+        )? {
+            return Ok(false);
+        }
+        decompress_block(&self.compressed, &mut self.buffer)?;
+        self.block_offset = self.next_block_offset;
+        self.next_block_offset += self.compressed.len() as u64;
+        self.position = 0;
+        Ok(true)
+    }
+}
+
+impl<R: Read + Seek> BgzfReader<R> {
+    /// Seek to a virtual offset previously obtained from `virtual_offset()`
+    pub fn seek_virtual(&mut self, offset: VirtualOffset) -> Result<()> {
+        self.inner.seek(SeekFrom::Start(offset.compressed()))?;
+        self.next_block_offset = offset.compressed();
+        self.buffer.clear();
+        self.position = 0;
+
+        if self.read_block()? {
+            let within = offset.uncompressed() as usize;
+            if within > self.buffer.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Virtual offset {} is beyond the end of its block", offset),
+                )
+                .into());
+            }
+            self.position = within;
+        } else if offset.uncompressed() != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Virtual offset {} is beyond the end of the file", offset),
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for BgzfReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: Read> BufRead for BgzfReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        // Loop so that empty blocks (such as the BGZF EOF marker) are skipped.
+        while self.position >= self.buffer.len() {
+            if !self.read_block()? {
+                return Ok(&[]);
+            }
+        }
+        Ok(&self.buffer[self.position..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position = (self.position + amt).min(self.buffer.len());
+    }
+}
+
+/// A BGZF block index (.gzi), as written by `bgzip -i`, mapping offsets in
+/// the uncompressed data to the compressed blocks that hold them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GziIndex {
+    /// (compressed, uncompressed) start offsets of every block after the first
+    blocks: Vec<(u64, u64)>,
+}
+
+impl GziIndex {
+    /// Build an index by reading through all the blocks of a BGZF file
+    pub fn build<R: Read>(mut reader: R) -> Result<Self> {
+        let mut blocks = Vec::new();
+        let mut block = Vec::with_capacity(64 * 1024);
+        let (mut compressed, mut uncompressed) = (0u64, 0u64);
+        while read_raw_block(&mut reader, &mut block, compressed)? {
+            if compressed > 0 {
+                blocks.push((compressed, uncompressed));
+            }
+            let isize = &block[block.len() - 4..];
+            compressed += block.len() as u64;
+            uncompressed += u32::from_le_bytes([isize[0], isize[1], isize[2], isize[3]]) as u64;
+        }
+        Ok(GziIndex { blocks })
+    }
+
+    /// Read an index in .gzi format
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self> {
+        let count = read_u64(&mut reader)?;
+        let mut blocks = Vec::new();
+        for _ in 0..count {
+            blocks.push((read_u64(&mut reader)?, read_u64(&mut reader)?));
+        }
+        Ok(GziIndex { blocks })
+    }
+
+    /// Read a .gzi index file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Write the index in .gzi format
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(&(self.blocks.len() as u64).to_le_bytes())?;
+        for (compressed, uncompressed) in &self.blocks {
+            writer.write_all(&compressed.to_le_bytes())?;
+            writer.write_all(&uncompressed.to_le_bytes())?;
+        }
+        Ok(writer.flush()?)
+    }
+
+    /// The virtual offset of a byte offset in the uncompressed data
+    pub fn virtual_offset(&self, uncompressed: u64) -> VirtualOffset {
+        let i = self
+            .blocks
+            .partition_point(|&(_, start)| start <= uncompressed);
+        let (block_compressed, block_uncompressed) =
+            if i == 0 { (0, 0) } else { self.blocks[i - 1] };
+        VirtualOffset::new(block_compressed, (uncompressed - block_uncompressed) as u16)
+    }
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// A decompressed block (None marks the end of input), tagged with its
+/// position in the file so blocks can be put back in order
+type DecodedBlock = (u64, io::Result<Option<Vec<u8>>>);
+
+/// A BGZF reader that decompresses blocks on a pool of worker threads,
+/// ahead of the consumer, and returns the data in the original order
+pub struct ParallelBgzfReader {
+    results: Receiver<DecodedBlock>,
+    pending: BTreeMap<u64, io::Result<Option<Vec<u8>>>>,
+    next_index: u64,
+    buffer: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl ParallelBgzfReader {
+    /// Create a new reader that decompresses `inner` using `threads` worker
+    /// threads (at least one is always used)
+    pub fn new<R: Read + Send + 'static>(mut inner: R, threads: usize) -> Self {
+        let threads = threads.max(1);
+        let (job_tx, job_rx) = sync_channel::<(u64, Vec<u8>)>(threads * 4);
+        let (result_tx, result_rx) = sync_channel::<DecodedBlock>(threads * 4);
+        let job_rx = Arc::new(Mutex::new(job_rx));
+
+        for _ in 0..threads {
+            let job_rx = Arc::clone(&job_rx);
+            let result_tx = result_tx.clone();
+            thread::spawn(move || loop {
+                let job = job_rx.lock().map(|rx| rx.recv());
+                let Ok(Ok((index, block))) = job else {
+                    break;
+                };
+                let mut output = Vec::new();
+                let result = decompress_block(&block, &mut output).map(|_| Some(output));
+                if result_tx.send((index, result)).is_err() {
+                    break;
+                }
+            });
+        }
+
+        // The producer reads raw blocks and hands them to the workers. It
+        // reports the end of input (or a read error) directly to the consumer.
+        thread::spawn(move || {
+            let mut index = 0;
+            let mut offset = 0;
+            loop {
+                let mut block = Vec::new();
+                match read_raw_block(&mut inner, &mut block, offset) {
+                    Ok(true) => {
+                        offset += block.len() as u64;
+                        if job_tx.send((index, block)).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(false) => {
+                        let _ = result_tx.send((index, Ok(None)));
+                        break;
+                    }
+                    Err(e) => {
+                        let _ = result_tx.send((index, Err(e)));
+                        break;
+                    }
+                }
+                index += 1;
+            }
+        });
+
+        ParallelBgzfReader {
+            results: result_rx,
+            pending: BTreeMap::new(),
+            next_index: 0,
+            buffer: Vec::new(),
+            position: 0,
+            finished: false,
+        }
+    }
+
+    fn next_block(&mut self) -> io::Result<bool> {
+        loop {
+            if let Some(result) = self.pending.remove(&self.next_index) {
+                self.next_index += 1;
+                return match result {
+                    Ok(Some(data)) => {
+                        self.buffer = data;
+                        self.position = 0;
+                        Ok(true)
+                    }
+                    Ok(None) => {
+                        self.finished = true;
+                        Ok(false)
+                    }
+                    Err(e) => {
+                        self.finished = true;
+                        Err(e)
+                    }
+                };
+            }
+            match self.results.recv() {
+                Ok((index, result)) => {
+                    self.pending.insert(index, result);
+                }
+                Err(_) => {
+                    self.finished = true;
+                    return Err(io::Error::other(
+                        "BGZF decompression threads exited unexpectedly",
+                    ));
+                }
+            }
+        }
+    }
+}
+
+impl Read for ParallelBgzfReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position >= self.buffer.len() {
+            if self.finished || !self.next_block()? {
+                return Ok(0);
+            }
+        }
+        let n = (self.buffer.len() - self.position).min(buf.len());
+        buf[..n].copy_from_slice(&self.buffer[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
@@ -0,0 +1,160 @@
+use crate::common::{open_input, Compression, LineReader};
+use crate::error::Result;
+use crate::fasta::FastaReader;
+use crate::fastq::FastqReader;
+use crate::options::ParserOptions;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Where a ReaderBuilder reads its input from
+enum Source {
+    Stdin,
+    Path(PathBuf),
+    Reader(Box<dyn Read + Send>),
+}
+
+/// Builder for FASTA and FASTQ readers, for when the defaults used by the
+/// `from_*` constructors need to be overridden.
+///
+/// ```no_run
+/// use prseq::{Compression, ReaderBuilder};
+///
+/// let reader = ReaderBuilder::new()
+///     .path("reads.fastq.gz")
+///     .compression(Compression::Gzip)
+///     .buffer_capacity(1 << 20)
+///     .build_fastq()?;
+/// # Ok::<(), prseq::PrseqError>(())
+/// ```
+pub struct ReaderBuilder {
+    source: Source,
+    compression: Option<Compression>,
+    buffer_capacity: usize,
+    sequence_size_hint: usize,
+    threads: usize,
+    options: ParserOptions,
+}
+
+impl Default for ReaderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReaderBuilder {
+    /// Create a builder that reads from stdin with compression detection
+    /// and the default buffer sizes
+    pub fn new() -> Self {
+        ReaderBuilder {
+            source: Source::Stdin,
+            compression: None,
+            buffer_capacity: 64 * 1024,
+            sequence_size_hint: 64 * 1024,
+            threads: 1,
+            options: ParserOptions::default(),
+        }
+    }
+
+    /// Read from the file at `path`
+    pub fn path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.source = Source::Path(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Read from any readable source
+    pub fn reader<R: Read + Send + 'static>(mut self, reader: R) -> Self {
+        self.source = Source::Reader(Box::new(reader));
+        self
+    }
+
+    /// Read from stdin (the default)
+    pub fn stdin(mut self) -> Self {
+        self.source = Source::Stdin;
+        self
+    }
+
+    /// Use the given compression format instead of detecting it from the
+    /// input's magic bytes
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Detect the compression format from the input's magic bytes (the
+    /// default)
+    pub fn detect_compression(mut self) -> Self {
+        self.compression = None;
+        self
+    }
+
+    /// Set the capacity of the buffer holding decompressed input
+    pub fn buffer_capacity(mut self, capacity: usize) -> Self {
+        self.buffer_capacity = capacity;
+        self
+    }
+
+    /// Set the sequence size hint (see `FastaReader::from_file_with_capacity`)
+    pub fn sequence_size_hint(mut self, hint: usize) -> Self {
+        self.sequence_size_hint = hint;
+        self
+    }
+
+    /// Set the number of threads used for decompression (see
+    /// `FastaReader::from_file_with_threads`)
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Set the options used to parse the input, e.g. to skip malformed
+    /// records
+    pub fn options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Build a FastaReader
+    pub fn build_fasta(self) -> Result<FastaReader> {
+        let sequence_size_hint = self.sequence_size_hint;
+        let options = self.options.clone();
+        let path = self.source_path();
+        let reader = FastaReader::from_line_reader(self.open()?, sequence_size_hint);
+        let reader = reader.with_options(options);
+        Ok(match path {
+            Some(path) => reader.with_path(&path),
+            None => reader,
+        })
+    }
+
+    /// Build a FastqReader
+    pub fn build_fastq(self) -> Result<FastqReader> {
+        let sequence_size_hint = self.sequence_size_hint;
+        let options = self.options.clone();
+        let path = self.source_path();
+        let reader = FastqReader::from_line_reader(self.open()?, sequence_size_hint);
+        let reader = reader.with_options(options);
+        Ok(match path {
+            Some(path) => reader.with_path(&path),
+            None => reader,
+        })
+    }
+
+    /// The path being read, if the source is a file
+    fn source_path(&self) -> Option<PathBuf> {
+        match &self.source {
+            Source::Path(path) => Some(path.clone()),
+            _ => None,
+        }
+    }
+
+    fn open(self) -> std::io::Result<LineReader> {
+        let reader: Box<dyn Read + Send> = match self.source {
+            Source::Stdin => Box::new(std::io::stdin()),
+            Source::Path(path) => Box::new(File::open(path)?),
+            Source::Reader(reader) => reader,
+        };
+        let input = open_input(reader, self.compression, self.threads, self.buffer_capacity)?;
+        Ok(LineReader::new(input))
+    }
+}
@@ -0,0 +1,182 @@
+use crate::bgzf::{BgzfReader, VirtualOffset};
+use crate::common::{
+    create_reader_with_compression, detect_compression, Compression, InputSource, LineReader,
+};
+use crate::error::Result;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::str::FromStr;
+
+/// A saved position in a FASTA or FASTQ file, from `checkpoint()` on the
+/// readers, from which `resume_from` continues reading
+///
+/// It holds the offset of the next record (and its BGZF virtual offset,
+/// for BGZF input) along with the reader's counts, so that record numbers
+/// in errors and `stats()` carry on from where they were. Checkpoints can
+/// be saved as text (with `to_string()`, and read back with `parse()`) or,
+/// with the `serde` feature, in any serde format.
+///
+/// ```
+/// use prseq::checkpoint::Checkpoint;
+/// use prseq::FastaReader;
+/// # let dir = tempfile::tempdir().unwrap();
+/// # let path = dir.path().join("contigs.fasta");
+/// # std::fs::write(&path, ">a\nACGT\n>b\nGG\n>c\nTT\n").unwrap();
+///
+/// let mut reader = FastaReader::from_file(&path).unwrap();
+/// reader.next().unwrap().unwrap();
+/// let saved = reader.checkpoint().to_string();
+///
+/// // ...after a restart
+/// let checkpoint: Checkpoint = saved.parse().unwrap();
+/// let rest: Vec<String> = FastaReader::resume_from(&path, &checkpoint)
+///     .unwrap()
+///     .map(|record| record.unwrap().header)
+///     .collect();
+/// assert_eq!(rest, ["b", "c"]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Checkpoint {
+    pub(crate) offset: u64,
+    pub(crate) virtual_offset: Option<u64>,
+    /// The number of records read and of lines before the next record
+    pub(crate) records: u64,
+    pub(crate) line: u64,
+    /// The reader's `stats()` counts
+    pub(crate) returned: u64,
+    pub(crate) bases: u64,
+    pub(crate) skipped: u64,
+}
+
+impl Checkpoint {
+    /// The byte offset, in the uncompressed input, of the next record
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The BGZF virtual offset of the next record, for BGZF input
+    pub fn virtual_offset(&self) -> Option<VirtualOffset> {
+        self.virtual_offset.map(VirtualOffset::from)
+    }
+
+    /// The number of records read before the checkpoint
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+}
+
+/// The version of the text form, in case it needs to change
+const TEXT_VERSION: &str = "1";
+
+impl fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let virtual_offset = match self.virtual_offset {
+            Some(offset) => offset.to_string(),
+            None => "-".to_string(),
+        };
+        write!(
+            f,
+            "{}:{}:{}:{}:{}:{}:{}:{}",
+            TEXT_VERSION,
+            self.offset,
+            virtual_offset,
+            self.records,
+            self.line,
+            self.returned,
+            self.bases,
+            self.skipped
+        )
+    }
+}
+
+impl FromStr for Checkpoint {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid checkpoint '{}'", s),
+            )
+        };
+        let fields: Vec<&str> = s.trim().split(':').collect();
+        let [TEXT_VERSION, offset, virtual_offset, records, line, returned, bases, skipped] =
+            fields[..]
+        else {
+            return Err(invalid());
+        };
+        let number = |field: &str| field.parse::<u64>().map_err(|_| invalid());
+        Ok(Checkpoint {
+            offset: number(offset)?,
+            virtual_offset: match virtual_offset {
+                "-" => None,
+                offset => Some(number(offset)?),
+            },
+            records: number(records)?,
+            line: number(line)?,
+            returned: number(returned)?,
+            bases: number(bases)?,
+            skipped: number(skipped)?,
+        })
+    }
+}
+
+/// Open `path` positioned at a checkpoint taken reading the same file
+///
+/// Uncompressed files, and BGZF files read with virtual offsets, are
+/// seeked straight to the checkpoint. Other compressed files cannot be, so
+/// they are decompressed up to it, and what comes before is discarded
+/// without being parsed.
+pub(crate) fn open_at(path: &Path, checkpoint: &Checkpoint) -> Result<LineReader> {
+    let mut file = File::open(path)?;
+    // The clone shares the file's position, which is set below.
+    let (compression, _) = detect_compression(file.try_clone()?)?;
+    let input: Box<dyn InputSource> = match (compression, checkpoint.virtual_offset()) {
+        (Compression::Bgzf, Some(virtual_offset)) => {
+            let mut reader = BgzfReader::new(file);
+            reader.seek_virtual(virtual_offset)?;
+            Box::new(reader)
+        }
+        (_, Some(_)) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Checkpoint has a BGZF virtual offset, but '{}' is not BGZF",
+                    path.display()
+                ),
+            )
+            .into())
+        }
+        (Compression::None, None) => {
+            file.seek(SeekFrom::Start(checkpoint.offset))?;
+            let file: Box<dyn Read + Send> = Box::new(file);
+            Box::new(BufReader::with_capacity(64 * 1024, file))
+        }
+        (_, None) => {
+            file.seek(SeekFrom::Start(0))?;
+            let mut input = create_reader_with_compression(file)?;
+            let skipped = io::copy(&mut (&mut input).take(checkpoint.offset), &mut io::sink())?;
+            if skipped < checkpoint.offset {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "'{}' ends at offset {}, before the checkpoint at offset {}",
+                        path.display(),
+                        skipped,
+                        checkpoint.offset
+                    ),
+                )
+                .into());
+            }
+            Box::new(input)
+        }
+    };
+    Ok(LineReader::with_offset(
+        input,
+        checkpoint.offset,
+        checkpoint.line,
+    ))
+}
@@ -0,0 +1,176 @@
+use crate::any::AnyReader;
+use crate::error::Result;
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use crate::record::SequenceRecord;
+use crate::sequence::reverse_complement;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// The sequence as checksummed: upper case, without gaps ('-' and '.')
+/// or whitespace, so that sequences that differ only in those ways have
+/// the same checksums
+pub fn normalize_sequence(sequence: &[u8]) -> Vec<u8> {
+    sequence
+        .iter()
+        .filter(|&&b| b != b'-' && b != b'.' && !b.is_ascii_whitespace())
+        .map(u8::to_ascii_uppercase)
+        .collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The SHA-256 of the normalized sequence, in hex
+pub fn sequence_sha256(sequence: &[u8]) -> String {
+    hex(&Sha256::digest(normalize_sequence(sequence)))
+}
+
+/// The MD5 of the normalized sequence, in hex
+pub fn sequence_md5(sequence: &[u8]) -> String {
+    hex(&Md5::digest(normalize_sequence(sequence)))
+}
+
+impl FastaRecord {
+    /// The SHA-256 of the sequence, upper-cased and without gaps, in hex
+    ///
+    /// ```
+    /// use prseq::FastaRecord;
+    ///
+    /// let a = FastaRecord { header: "a".into(), sequence: "AC-GT".into() };
+    /// let b = FastaRecord { header: "b".into(), sequence: "acgt".into() };
+    /// assert_eq!(a.sha256(), b.sha256());
+    /// assert_eq!(a.md5(), "f1f8f4bf413b16ad135722aa4591043e");
+    /// ```
+    pub fn sha256(&self) -> String {
+        sequence_sha256(self.sequence.as_bytes())
+    }
+
+    /// The MD5 of the sequence, upper-cased and without gaps, in hex
+    pub fn md5(&self) -> String {
+        sequence_md5(self.sequence.as_bytes())
+    }
+}
+
+impl FastqRecord {
+    /// The SHA-256 of the sequence, upper-cased and without gaps, in hex
+    pub fn sha256(&self) -> String {
+        sequence_sha256(self.sequence.as_bytes())
+    }
+
+    /// The MD5 of the sequence, upper-cased and without gaps, in hex
+    pub fn md5(&self) -> String {
+        sequence_md5(self.sequence.as_bytes())
+    }
+}
+
+/// A digest of all the sequences of a file, accumulated one record at a
+/// time, that ignores record order, IDs and descriptions, case and gaps
+///
+/// As in `seqkit sum`, each normalized sequence is hashed, the hashes are
+/// sorted, and the sorted hashes, total length and number of sequences are
+/// hashed with MD5. Two files therefore have the same digest exactly when
+/// they hold the same sequences, however they are named or ordered. With
+/// `both_strands`, a sequence and its reverse complement count as the same.
+///
+/// ```
+/// use prseq::checksum::FileDigest;
+/// use prseq::FastaReader;
+///
+/// let first = FastaReader::from_string(">a\nACGT\n>b\nGGA\n").unwrap();
+/// let second = FastaReader::from_string(">x\nGGA\n>y\nac-gt\n").unwrap();
+/// assert_eq!(
+///     FileDigest::collect(first).unwrap().digest(),
+///     FileDigest::collect(second).unwrap().digest()
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FileDigest {
+    hashes: Vec<u128>,
+    bases: u64,
+    both_strands: bool,
+}
+
+impl FileDigest {
+    /// An empty digest
+    pub fn new() -> Self {
+        FileDigest::default()
+    }
+
+    /// Set whether a sequence and its reverse complement are counted as the
+    /// same (for double-stranded DNA). Set this before adding records.
+    pub fn both_strands(mut self, both_strands: bool) -> Self {
+        self.both_strands = both_strands;
+        self
+    }
+
+    /// Add a sequence
+    pub fn add(&mut self, sequence: &[u8]) {
+        let sequence = normalize_sequence(sequence);
+        let mut hash = u128::from_be_bytes(Md5::digest(&sequence).into());
+        if self.both_strands {
+            let reverse = reverse_complement(&sequence);
+            hash = hash.min(u128::from_be_bytes(Md5::digest(reverse).into()));
+        }
+        self.hashes.push(hash);
+        self.bases += sequence.len() as u64;
+    }
+
+    /// Add a record's sequence
+    pub fn add_record<R: SequenceRecord + ?Sized>(&mut self, record: &R) {
+        self.add(record.sequence().as_bytes());
+    }
+
+    /// The number of sequences added
+    pub fn records(&self) -> u64 {
+        self.hashes.len() as u64
+    }
+
+    /// The total length of the (normalized) sequences added
+    pub fn total_bases(&self) -> u64 {
+        self.bases
+    }
+
+    /// The MD5 digest of the sequences, in hex
+    pub fn digest(&self) -> String {
+        let mut hashes = self.hashes.clone();
+        hashes.sort_unstable();
+        let mut md5 = Md5::new();
+        for hash in hashes {
+            md5.update(hash.to_be_bytes());
+        }
+        md5.update(self.bases.to_be_bytes());
+        md5.update(self.records().to_be_bytes());
+        hex(&md5.finalize())
+    }
+
+    /// Digest a reader (or any iterator of record results) in one pass,
+    /// stopping at the first error
+    pub fn collect<I, R>(records: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = Result<R>>,
+        R: SequenceRecord,
+    {
+        let mut digest = FileDigest::new();
+        for record in records {
+            digest.add_record(&record?);
+        }
+        Ok(digest)
+    }
+}
+
+impl<R: SequenceRecord> Extend<R> for FileDigest {
+    fn extend<I: IntoIterator<Item = R>>(&mut self, records: I) {
+        for record in records {
+            self.add_record(&record);
+        }
+    }
+}
+
+/// The digest of the sequences of a FASTA or FASTQ file (which may be
+/// compressed), as given by `FileDigest::digest`
+pub fn file_digest<P: AsRef<Path>>(path: P) -> Result<String> {
+    Ok(FileDigest::collect(AnyReader::from_file(path)?)?.digest())
+}
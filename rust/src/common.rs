@@ -1,13 +1,41 @@
-use bzip2::read::BzDecoder;
-use flate2::read::GzDecoder;
-use std::io::{BufReader, Cursor, Read, Result};
+use crate::bgzf::{is_bgzf, BgzfReader, ParallelBgzfReader, VirtualOffset};
+use crate::error::{Position, PrseqError};
+use crate::line_ending::{LineEnding, LineEndings};
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use std::io::{BufRead, BufReader, Cursor, Read, Result};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::Arc;
+use std::thread;
+use xz2::read::XzDecoder;
 
-/// Create a reader with automatic compression detection
-pub fn create_reader_with_compression<R: Read + Send + 'static>(
+/// Compression formats understood by the readers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// Uncompressed input
+    None,
+    /// Gzip, including multi-member (concatenated) files
+    Gzip,
+    /// Blocked gzip, as written by bgzip. Readable as ordinary gzip, but
+    /// decompressed block-by-block so virtual offsets are available.
+    Bgzf,
+    /// Bzip2, including concatenated streams
+    Bzip2,
+    /// XZ, including concatenated streams
+    Xz,
+    /// Zstandard (requires the `zstd` feature)
+    Zstd,
+}
+
+/// Peek at the start of `reader` to detect its compression format. Returns
+/// the format and a reader that still yields the peeked bytes.
+pub(crate) fn detect_compression<R: Read + Send + 'static>(
     mut reader: R,
-) -> Result<BufReader<Box<dyn Read + Send>>> {
-    // Peek at first few bytes to detect compression
-    let mut magic_buf = [0u8; 3];
+) -> Result<(Compression, Box<dyn Read + Send>)> {
+    // Peek at enough bytes to recognize the BGZF extra field, which also
+    // covers the magic bytes of every other format.
+    let mut magic_buf = [0u8; 18];
     let mut bytes_read = 0;
 
     // Try to read magic bytes
@@ -17,31 +45,654 @@ pub fn create_reader_with_compression<R: Read + Send + 'static>(
             n => bytes_read += n,
         }
     }
+    let magic = &magic_buf[..bytes_read];
+
+    let compression = if is_bgzf(magic) {
+        Compression::Bgzf
+    } else if magic.starts_with(&[0x1f, 0x8b]) {
+        Compression::Gzip
+    } else if magic.starts_with(&[0x42, 0x5a, 0x68]) {
+        Compression::Bzip2
+    } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Compression::Zstd
+    } else if magic.starts_with(&[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00]) {
+        Compression::Xz
+    } else {
+        Compression::None
+    };
+
+    // Put the magic bytes back in front of the rest of the input.
+    let magic_copy = magic.to_vec();
+    Ok((compression, Box::new(Cursor::new(magic_copy).chain(reader))))
+}
+
+/// Wrap `reader` in the decoder for `compression`. Decoders for compressed
+/// formats report truncated input with a clear error.
+pub(crate) fn decompress(
+    compression: Compression,
+    reader: Box<dyn Read + Send>,
+) -> Result<Box<dyn Read + Send>> {
+    if compression == Compression::None {
+        return Ok(reader);
+    }
+
+    let (counted, progress) = CountingReader::new(reader);
+    let decoded: Box<dyn Read + Send> = match compression {
+        // BGZF is valid gzip. Use the multi-member decoder so concatenated
+        // files (cat a.gz b.gz) are read in full.
+        Compression::Gzip | Compression::Bgzf => Box::new(TruncationCheck::new(
+            "gzip",
+            MultiGzDecoder::new(counted),
+            progress,
+        )),
+        // As with gzip, handle concatenated streams (e.g., pbzip2 output).
+        Compression::Bzip2 => Box::new(TruncationCheck::new(
+            "bzip2",
+            MultiBzDecoder::new(counted),
+            progress,
+        )),
+        Compression::Xz => Box::new(TruncationCheck::new(
+            "xz",
+            XzDecoder::new_multi_decoder(counted),
+            progress,
+        )),
+        Compression::Zstd => Box::new(TruncationCheck::new(
+            "zstd",
+            zstd_decoder(counted)?,
+            progress,
+        )),
+        Compression::None => unreachable!(),
+    };
+    Ok(decoded)
+}
+
+/// Create a reader with automatic compression detection
+pub fn create_reader_with_compression<R: Read + Send + 'static>(
+    reader: R,
+) -> Result<BufReader<Box<dyn Read + Send>>> {
+    let (compression, reader) = detect_compression(reader)?;
+    Ok(BufReader::with_capacity(
+        64 * 1024,
+        decompress(compression, reader)?,
+    ))
+}
+
+/// How much of a source has been read, shared between a CountingReader
+/// and the TruncationCheck wrapped around its decoder (or the CountedInput
+/// that reports it)
+#[derive(Default)]
+pub(crate) struct ReadProgress {
+    bytes: AtomicU64,
+    at_eof: AtomicBool,
+}
+
+/// A reader that counts the compressed bytes read from its source and
+/// notes when the end of the source is reached
+pub(crate) struct CountingReader<R> {
+    inner: R,
+    progress: Arc<ReadProgress>,
+}
+
+impl<R: Read> CountingReader<R> {
+    pub(crate) fn new(inner: R) -> (Self, Arc<ReadProgress>) {
+        let progress = Arc::new(ReadProgress::default());
+        let reader = CountingReader {
+            inner,
+            progress: Arc::clone(&progress),
+        };
+        (reader, progress)
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            self.progress.at_eof.store(true, Ordering::Relaxed);
+        }
+        self.progress.bytes.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+/// Wraps a decoder so that a decompression error caused by the compressed
+/// input ending early is reported as a truncated stream, with the offsets
+/// reached, rather than as the decoder's own (often cryptic) error
+struct TruncationCheck<D> {
+    format: &'static str,
+    decoder: D,
+    progress: Arc<ReadProgress>,
+    decompressed: u64,
+}
+
+impl<D: Read> TruncationCheck<D> {
+    fn new(format: &'static str, decoder: D, progress: Arc<ReadProgress>) -> Self {
+        TruncationCheck {
+            format,
+            decoder,
+            progress,
+            decompressed: 0,
+        }
+    }
+}
+
+impl<D: Read> Read for TruncationCheck<D> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        match self.decoder.read(buf) {
+            Ok(n) => {
+                self.decompressed += n as u64;
+                Ok(n)
+            }
+            Err(e) if self.progress.at_eof.load(Ordering::Relaxed) => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!(
+                    "Truncated {} stream: compressed input ended after {} bytes \
+                     ({} bytes decompressed) ({})",
+                    self.format,
+                    self.progress.bytes.load(Ordering::Relaxed),
+                    self.decompressed,
+                    e
+                ),
+            )),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Create a reader with automatic compression detection that decompresses
+/// using `threads` threads.
+///
+/// BGZF input is decompressed block-by-block on a pool of worker threads.
+/// Other compressed input cannot be split into independent blocks, so it is
+/// decompressed on a single background thread, which still overlaps
+/// decompression with parsing. A `threads` value of 0 or 1 is the same as
+/// calling `create_reader_with_compression`.
+pub fn create_reader_with_threads<R: Read + Send + 'static>(
+    reader: R,
+    threads: usize,
+) -> Result<BufReader<Box<dyn Read + Send>>> {
+    let (compression, reader) = detect_compression(reader)?;
+    Ok(BufReader::with_capacity(
+        64 * 1024,
+        threaded_decompress(compression, reader, threads)?,
+    ))
+}
+
+/// Like `decompress`, but using `threads` threads when that is more than one
+fn threaded_decompress(
+    compression: Compression,
+    reader: Box<dyn Read + Send>,
+    threads: usize,
+) -> Result<Box<dyn Read + Send>> {
+    if threads <= 1 {
+        decompress(compression, reader)
+    } else if compression == Compression::Bgzf {
+        Ok(Box::new(ParallelBgzfReader::new(reader, threads)))
+    } else {
+        Ok(Box::new(BackgroundReader::new(decompress(
+            compression,
+            reader,
+        )?)))
+    }
+}
+
+/// Open the parser input for `reader`.
+///
+/// The compression is detected unless given. Single-threaded BGZF input is
+/// read with a BgzfReader so that records have virtual offsets.
+pub(crate) fn open_input<R: Read + Send + 'static>(
+    reader: R,
+    compression: Option<Compression>,
+    threads: usize,
+    buffer_capacity: usize,
+) -> Result<Box<dyn InputSource>> {
+    counted_input(reader, |reader| {
+        let (compression, reader): (Compression, Box<dyn Read + Send>) = match compression {
+            Some(compression) => (compression, Box::new(reader)),
+            None => detect_compression(reader)?,
+        };
+
+        if compression == Compression::Bgzf && threads <= 1 {
+            return Ok(Box::new(BgzfReader::new(reader)) as Box<dyn InputSource>);
+        }
+        Ok(Box::new(BufReader::with_capacity(
+            buffer_capacity.max(1),
+            threaded_decompress(compression, reader, threads)?,
+        )))
+    })
+}
+
+/// Open the parser input for `reader` with `open` (e.g. with
+/// `create_reader_with_compression`), counting the bytes read from
+/// `reader` itself, before any decompression, for progress reports
+pub(crate) fn counted_input<R, S, F>(reader: R, open: F) -> Result<Box<dyn InputSource>>
+where
+    R: Read + Send + 'static,
+    S: InputSource + 'static,
+    F: FnOnce(CountingReader<R>) -> Result<S>,
+{
+    let (reader, source) = CountingReader::new(reader);
+    Ok(Box::new(CountedInput {
+        input: open(reader)?,
+        source,
+    }))
+}
+
+/// A reader that reads (and so decompresses) its input on a background
+/// thread, handing over chunks through a bounded channel
+struct BackgroundReader {
+    chunks: Receiver<Result<Vec<u8>>>,
+    buffer: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl BackgroundReader {
+    fn new<R: Read + Send + 'static>(mut inner: R) -> Self {
+        let (tx, rx) = sync_channel(4);
+        thread::spawn(move || loop {
+            let mut chunk = vec![0u8; 64 * 1024];
+            let result = match inner.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => {
+                    chunk.truncate(n);
+                    Ok(chunk)
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(e),
+            };
+            let failed = result.is_err();
+            if tx.send(result).is_err() || failed {
+                break;
+            }
+        });
+        BackgroundReader {
+            chunks: rx,
+            buffer: Vec::new(),
+            position: 0,
+            finished: false,
+        }
+    }
+}
+
+impl Read for BackgroundReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        while self.position >= self.buffer.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            match self.chunks.recv() {
+                Ok(Ok(chunk)) => {
+                    self.buffer = chunk;
+                    self.position = 0;
+                }
+                Ok(Err(e)) => {
+                    self.finished = true;
+                    return Err(e);
+                }
+                // The sender is dropped once the input is exhausted.
+                Err(_) => self.finished = true,
+            }
+        }
+        let n = (self.buffer.len() - self.position).min(buf.len());
+        buf[..n].copy_from_slice(&self.buffer[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decoder<R: Read + Send + 'static>(reader: R) -> Result<Box<dyn Read + Send>> {
+    Ok(Box::new(zstd::stream::read::Decoder::new(reader)?))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_decoder<R: Read + Send + 'static>(_reader: R) -> Result<Box<dyn Read + Send>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "Input is zstd-compressed, but prseq was built without the 'zstd' feature",
+    ))
+}
+
+/// Buffered input that the parsers read lines from. Sources that know their
+/// BGZF virtual offset report it, so records can be located again later.
+pub(crate) trait InputSource: BufRead + Send {
+    fn virtual_offset(&self) -> Option<VirtualOffset> {
+        None
+    }
+
+    /// The number of bytes read from the underlying source (compressed, if
+    /// it is), when they are counted
+    fn input_bytes(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl InputSource for BufReader<Box<dyn Read + Send>> {}
+
+impl InputSource for Box<dyn InputSource> {
+    fn virtual_offset(&self) -> Option<VirtualOffset> {
+        self.as_ref().virtual_offset()
+    }
+
+    fn input_bytes(&self) -> Option<u64> {
+        self.as_ref().input_bytes()
+    }
+}
+
+/// Parser input whose source is read through a CountingReader, so that it
+/// can report how much of the source has been read
+struct CountedInput<S> {
+    input: S,
+    source: Arc<ReadProgress>,
+}
+
+impl<S: BufRead> Read for CountedInput<S> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+impl<S: BufRead> BufRead for CountedInput<S> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        self.input.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.input.consume(amount)
+    }
+}
+
+impl<S: InputSource> InputSource for CountedInput<S> {
+    fn virtual_offset(&self) -> Option<VirtualOffset> {
+        self.input.virtual_offset()
+    }
+
+    fn input_bytes(&self) -> Option<u64> {
+        Some(self.source.bytes.load(Ordering::Relaxed))
+    }
+}
+
+impl<R: Read + Send> InputSource for BgzfReader<R> {
+    fn virtual_offset(&self) -> Option<VirtualOffset> {
+        Some(BgzfReader::virtual_offset(self))
+    }
+}
 
-    // Create appropriate decoder based on magic bytes
-    let decoded_reader: Box<dyn Read + Send> =
-        if bytes_read >= 2 && magic_buf[0] == 0x1f && magic_buf[1] == 0x8b {
-            // Gzip format - make owned copy of magic bytes
-            let magic_copy = magic_buf[..bytes_read].to_vec();
-            let chained = Cursor::new(magic_copy).chain(reader);
-            let gz_reader = GzDecoder::new(chained);
-            Box::new(gz_reader)
-        } else if bytes_read >= 3
-            && magic_buf[0] == 0x42
-            && magic_buf[1] == 0x5a
-            && magic_buf[2] == 0x68
-        {
-            // Bzip2 format - make owned copy of magic bytes
-            let magic_copy = magic_buf[..bytes_read].to_vec();
-            let chained = Cursor::new(magic_copy).chain(reader);
-            let bz_reader = BzDecoder::new(chained);
-            Box::new(bz_reader)
+/// The longest lines a record reader accepts, from its `max_header_length`
+/// and `max_sequence_length`, so that input with no line breaks fails
+/// before it has all been read into memory
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct LineLimits {
+    pub(crate) format: &'static str,
+    /// The bytes that start header lines
+    pub(crate) markers: &'static [u8],
+    /// The limit for lines starting with one of `markers`, not counting it
+    pub(crate) header: Option<usize>,
+    /// The limit for other lines
+    pub(crate) sequence: Option<usize>,
+}
+
+impl LineLimits {
+    /// A RecordTooLong error if `line` (so far) is over its limit. Leading
+    /// and trailing whitespace, which readers trim, does not count.
+    fn check(&self, line: &[u8], line_number: u64, record: u64) -> Result<()> {
+        let line = line.trim_ascii();
+        let Some(first) = line.first() else {
+            return Ok(());
+        };
+        let (part, limit, length) = if self.markers.contains(first) {
+            ("header", self.header, line.len() - 1)
         } else {
-            // Uncompressed - put magic bytes back
-            let magic_copy = magic_buf[..bytes_read].to_vec();
-            let cursor = Cursor::new(magic_copy);
-            Box::new(cursor.chain(reader))
+            ("sequence", self.sequence, line.len())
         };
+        match limit {
+            Some(limit) if length > limit => Err(PrseqError::RecordTooLong {
+                format: self.format,
+                part,
+                limit,
+                position: Position {
+                    line: line_number,
+                    record,
+                },
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Reads lines from an input source, returning them borrowed from the
+/// input's buffer where possible
+///
+/// Lines end at "\n", "\r\n" or a lone "\r", found with `memchr2`. A line
+/// that lies entirely within the buffered data (with the byte after a "\r",
+/// to tell whether it is a "\r\n") is returned without copying; only lines
+/// that straddle a buffer refill are copied (into `spill`).
+pub(crate) struct LineReader {
+    input: Box<dyn InputSource>,
+    offset: u64,
+    /// The 1-based number of the last line returned
+    line: u64,
+    /// Bytes of the input buffer used by the last line but not yet consumed
+    pending: usize,
+    spill: Vec<u8>,
+    /// Whether the rest of the current line is to be skipped, after it
+    /// went over its limit
+    discarding: bool,
+    endings: LineEndings,
+}
+
+impl LineReader {
+    pub(crate) fn new(input: Box<dyn InputSource>) -> Self {
+        Self::with_offset(input, 0, 0)
+    }
+
+    /// Create a LineReader for input of which `offset` bytes, containing
+    /// `lines` line terminators, have already been consumed
+    pub(crate) fn with_offset(input: Box<dyn InputSource>, offset: u64, lines: u64) -> Self {
+        LineReader {
+            input,
+            offset,
+            line: lines,
+            pending: 0,
+            spill: Vec::new(),
+            discarding: false,
+            endings: LineEndings::default(),
+        }
+    }
 
-    Ok(BufReader::with_capacity(64 * 1024, decoded_reader))
+    fn consume_pending(&mut self) {
+        if self.pending > 0 {
+            self.input.consume(self.pending);
+            self.pending = 0;
+        }
+    }
+
+    /// Read the next line, without its line terminator, along with the
+    /// offset just past its terminator. Returns None at end of input.
+    pub(crate) fn next_line(&mut self) -> Result<Option<(&[u8], u64)>> {
+        self.next_line_within(&LineLimits::default(), 0)
+    }
+
+    /// Read the next line like `next_line`, but fail with a RecordTooLong
+    /// error (for record number `record`) as soon as the line is longer than
+    /// `limits` allow, without reading the rest of it into memory. The rest
+    /// of such a line is skipped by the next call.
+    pub(crate) fn next_line_within(
+        &mut self,
+        limits: &LineLimits,
+        record: u64,
+    ) -> Result<Option<(&[u8], u64)>> {
+        self.consume_pending();
+        if self.discarding {
+            self.discarding = false;
+            self.read_rest(None)?;
+        }
+        let buf = self.input.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(None);
+        }
+        let found = memchr::memchr2(b'\n', b'\r', buf).and_then(|i| match buf[i] {
+            b'\n' => Some((i, 1, LineEnding::Lf)),
+            // A "\r" at the end of the buffer may be the start of a "\r\n".
+            _ => match buf.get(i + 1) {
+                Some(b'\n') => Some((i, 2, LineEnding::CrLf)),
+                Some(_) => Some((i, 1, LineEnding::Cr)),
+                None => None,
+            },
+        });
+        self.line += 1;
+        if let Some((end, length, ending)) = found {
+            // The whole line is in the buffer: borrow it.
+            let check = limits.check(&buf[..end], self.line, record);
+            self.endings.add(ending);
+            self.pending = end + length;
+            self.offset += self.pending as u64;
+            check?;
+            return Ok(Some((&self.input.fill_buf()?[..end], self.offset)));
+        }
+
+        // The line continues past the buffer: collect it in `spill`.
+        self.spill.clear();
+        self.read_rest(Some((limits, record)))?;
+        Ok(Some((&self.spill[..], self.offset)))
+    }
+
+    /// Read up to and past the end of the current line, keeping it in
+    /// `spill` if there are `limits` to check it against (as it grows), or
+    /// discarding it if not
+    fn read_rest(&mut self, limits: Option<(&LineLimits, u64)>) -> Result<()> {
+        loop {
+            let buf = self.input.fill_buf()?;
+            let Some(end) = memchr::memchr2(b'\n', b'\r', buf) else {
+                let length = buf.len();
+                if length == 0 {
+                    break;
+                }
+                if limits.is_some() {
+                    self.spill.extend_from_slice(buf);
+                }
+                self.input.consume(length);
+                self.offset += length as u64;
+                if let Some((line_limits, record)) = limits {
+                    if let Err(error) = line_limits.check(&self.spill, self.line, record) {
+                        // Skip the rest of the line on the next call.
+                        self.discarding = true;
+                        return Err(error);
+                    }
+                }
+                continue;
+            };
+            if limits.is_some() {
+                self.spill.extend_from_slice(&buf[..end]);
+            }
+            let ending = if buf[end] == b'\n' {
+                LineEnding::Lf
+            } else {
+                self.input.consume(end + 1);
+                self.offset += (end + 1) as u64;
+                match self.input.fill_buf()?.first() {
+                    Some(b'\n') => LineEnding::CrLf,
+                    _ => LineEnding::Cr,
+                }
+            };
+            match ending {
+                LineEnding::Lf => {
+                    self.input.consume(end + 1);
+                    self.offset += (end + 1) as u64;
+                }
+                LineEnding::CrLf => {
+                    self.input.consume(1);
+                    self.offset += 1;
+                }
+                LineEnding::Cr => {}
+            }
+            self.endings.add(ending);
+            break;
+        }
+        if let Some((line_limits, record)) = limits {
+            line_limits.check(&self.spill, self.line, record)?;
+        }
+        Ok(())
+    }
+
+    /// The number of lines read with each style of terminator
+    pub(crate) fn line_endings(&self) -> LineEndings {
+        self.endings
+    }
+
+    /// The byte offset of the next line in the uncompressed input
+    pub(crate) fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The 1-based number of the last line returned, for error messages
+    pub(crate) fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// The number of bytes read from the source, before decompression, if
+    /// they are counted
+    pub(crate) fn input_bytes(&self) -> Option<u64> {
+        self.input.input_bytes()
+    }
+
+    /// The BGZF virtual offset of the next line, if the input is BGZF
+    pub(crate) fn virtual_offset(&mut self) -> Option<VirtualOffset> {
+        self.consume_pending();
+        self.input.virtual_offset()
+    }
+}
+
+/// Convert bytes read from a record to a String, reporting invalid UTF-8
+pub(crate) fn utf8_string(
+    bytes: Vec<u8>,
+    format: &'static str,
+    position: Option<Position>,
+) -> crate::error::Result<String> {
+    String::from_utf8(bytes).map_err(|e| PrseqError::InvalidUtf8 {
+        format,
+        position,
+        source: e.utf8_error(),
+    })
+}
+
+/// View bytes read from a record as a str, reporting invalid UTF-8
+pub(crate) fn utf8_str<'a>(
+    bytes: &'a [u8],
+    format: &'static str,
+    position: Option<Position>,
+) -> crate::error::Result<&'a str> {
+    std::str::from_utf8(bytes).map_err(|source| PrseqError::InvalidUtf8 {
+        format,
+        position,
+        source,
+    })
+}
+
+/// Split a header line into its ID (up to the first whitespace) and its
+/// description (the rest, after that whitespace), if it has one. Whitespace
+/// is ASCII whitespace, and any before the ID (as in "> id") is skipped.
+pub(crate) fn split_header(header: &[u8]) -> (&[u8], Option<&[u8]>) {
+    let header = header.trim_ascii_start();
+    match header.iter().position(u8::is_ascii_whitespace) {
+        Some(end) => {
+            let description = header[end..].trim_ascii_start();
+            (&header[..end], Some(description).filter(|d| !d.is_empty()))
+        }
+        None => (header, None),
+    }
+}
+
+/// Split a header line into its ID and description, as `split_header`
+pub(crate) fn split_header_str(header: &str) -> (&str, Option<&str>) {
+    let header = header.trim_start_matches(|c: char| c.is_ascii_whitespace());
+    match header.split_once(|c: char| c.is_ascii_whitespace()) {
+        Some((id, description)) => {
+            let description = description.trim_start_matches(|c: char| c.is_ascii_whitespace());
+            (id, Some(description).filter(|d| !d.is_empty()))
+        }
+        None => (header, None),
+    }
 }
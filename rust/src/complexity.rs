@@ -0,0 +1,112 @@
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use crate::kmer::for_each_packed;
+use std::collections::HashMap;
+
+/// The longest k-mers whose entropy can be measured
+const MAX_ENTROPY_K: usize = 32;
+
+/// The Shannon entropy of the k-mers of a sequence, scaled to between 0
+/// (one k-mer repeated) and 1 (every k-mer different, or all 4^k equally
+/// common)
+///
+/// The entropy is divided by its greatest possible value, the log of the
+/// number of k-mers or of 4^k if that is smaller, so that it does not
+/// depend on the length of the sequence. Only k-mers of A, C, G and T are
+/// counted (see `kmer::Counter`); a sequence with fewer than two of them
+/// has an entropy of 0. Panics if `k` is 0 or more than 32.
+///
+/// ```
+/// use prseq::complexity::entropy;
+///
+/// assert_eq!(entropy(b"AAAAAAAAAAAA", 3), 0.0);
+/// assert_eq!(entropy(b"ACACACACACAC", 1), 1.0 / 2.0);
+/// assert!(entropy(b"ACGTTGCAAGGCTTAACCGGATCG", 3) > 0.9);
+/// ```
+pub fn entropy(sequence: &[u8], k: usize) -> f64 {
+    assert!(
+        (1..=MAX_ENTROPY_K).contains(&k),
+        "entropy k-mers must be 1 to 32 bases long"
+    );
+    let mut counts: HashMap<u64, u64> = HashMap::new();
+    let mut total = 0u64;
+    for_each_packed(sequence, k, false, |code| {
+        *counts.entry(code).or_default() += 1;
+        total += 1;
+    });
+    let possible = if k < 16 {
+        4f64.powi(k as i32)
+    } else {
+        f64::MAX
+    };
+    let max = possible.min(total as f64).log2();
+    if max <= 0.0 {
+        return 0.0;
+    }
+    let total = total as f64;
+    let entropy: f64 = counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum();
+    entropy / max
+}
+
+/// A DUST score of a sequence: how often its triplets (3-mers) repeat, so
+/// higher scores mean lower complexity
+///
+/// Each triplet seen c times adds c(c - 1) / 2, and the sum is divided by
+/// one less than the number of triplets, as in the DUST algorithm (here
+/// over the whole sequence, rather than in windows). Random sequence scores
+/// below 1 and a homopolymer scores half its number of triplets; scores
+/// above about 2 are commonly taken as low complexity. Only triplets of A,
+/// C, G and T are counted, and a sequence with fewer than two scores 0.
+///
+/// ```
+/// use prseq::complexity::dust_score;
+///
+/// assert_eq!(dust_score(b"AAAAAAAAAAAA"), 5.0);
+/// assert!(dust_score(b"ACGTTGCAAGGCTTAACCGGATCG") < 1.0);
+/// ```
+pub fn dust_score(sequence: &[u8]) -> f64 {
+    let mut counts = [0u64; 64];
+    let mut triplets = 0u64;
+    for_each_packed(sequence, 3, false, |code| {
+        counts[code as usize] += 1;
+        triplets += 1;
+    });
+    if triplets < 2 {
+        return 0.0;
+    }
+    let repeats: u128 = counts
+        .iter()
+        .map(|&count| u128::from(count) * u128::from(count.saturating_sub(1)) / 2)
+        .sum();
+    repeats as f64 / (triplets - 1) as f64
+}
+
+impl FastaRecord {
+    /// The scaled k-mer entropy of the sequence (see `complexity::entropy`)
+    pub fn entropy(&self, k: usize) -> f64 {
+        entropy(self.sequence.as_bytes(), k)
+    }
+
+    /// The DUST score of the sequence (see `complexity::dust_score`)
+    pub fn dust_score(&self) -> f64 {
+        dust_score(self.sequence.as_bytes())
+    }
+}
+
+impl FastqRecord {
+    /// The scaled k-mer entropy of the sequence (see `complexity::entropy`)
+    pub fn entropy(&self, k: usize) -> f64 {
+        entropy(self.sequence.as_bytes(), k)
+    }
+
+    /// The DUST score of the sequence (see `complexity::dust_score`)
+    pub fn dust_score(&self) -> f64 {
+        dust_score(self.sequence.as_bytes())
+    }
+}
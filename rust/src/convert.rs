@@ -0,0 +1,98 @@
+use crate::any::AnyRecord;
+use crate::error::Result;
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+
+impl From<FastqRecord> for FastaRecord {
+    /// Drop the quality string
+    fn from(record: FastqRecord) -> Self {
+        FastaRecord {
+            header: record.header,
+            sequence: record.sequence,
+        }
+    }
+}
+
+impl From<AnyRecord> for FastaRecord {
+    /// Drop the quality string, if there is one
+    fn from(record: AnyRecord) -> Self {
+        match record {
+            AnyRecord::Fasta(record) => record,
+            AnyRecord::Fastq(record) => record.into(),
+        }
+    }
+}
+
+impl FastqRecord {
+    /// The record as FASTA, without its quality string
+    pub fn to_fasta(&self) -> FastaRecord {
+        FastaRecord {
+            header: self.header.clone(),
+            sequence: self.sequence.clone(),
+        }
+    }
+}
+
+impl FastaRecord {
+    /// The record as FASTQ, with every base given `default_quality` (see
+    /// `fasta_to_fastq`)
+    pub fn to_fastq(&self, default_quality: char) -> FastqRecord {
+        fasta_to_fastq(self.clone(), default_quality)
+    }
+}
+
+/// Convert a FASTA record to FASTQ, giving every base the quality character
+/// `default_quality` (e.g. 'I', Phred 40 in the usual Phred+33 encoding)
+///
+/// ```
+/// use prseq::convert::fasta_to_fastq;
+/// use prseq::FastaRecord;
+///
+/// let record = FastaRecord { header: "r1".into(), sequence: "ACGT".into() };
+/// assert_eq!(fasta_to_fastq(record, 'I').quality, "IIII");
+/// ```
+pub fn fasta_to_fastq(record: FastaRecord, default_quality: char) -> FastqRecord {
+    let quality = std::iter::repeat_n(default_quality, record.sequence.len()).collect();
+    FastqRecord {
+        header: record.header,
+        sequence: record.sequence,
+        quality,
+    }
+}
+
+/// Conversion to FASTA for readers (or any iterator of record results).
+/// Errors are passed through.
+///
+/// ```
+/// use prseq::convert::ConvertExt;
+/// use prseq::FastqReader;
+///
+/// let input = b"@r1\nACGT\n+\nIIII\n";
+/// let reader = FastqReader::from_reader_with_capacity(&input[..], 64).unwrap();
+/// let records: Vec<_> = reader.to_fasta().collect::<Result<_, _>>().unwrap();
+/// assert_eq!(records[0].sequence, "ACGT");
+/// ```
+pub trait ConvertExt<R: Into<FastaRecord>>: Iterator<Item = Result<R>> + Sized {
+    /// Convert FASTQ (or any) records to FASTA, dropping qualities
+    fn to_fasta(self) -> impl Iterator<Item = Result<FastaRecord>> {
+        self.map(|result| result.map(Into::into))
+    }
+}
+
+impl<I, R> ConvertExt<R> for I
+where
+    I: Iterator<Item = Result<R>>,
+    R: Into<FastaRecord>,
+{
+}
+
+/// Conversion of FASTA records to FASTQ for readers (or any iterator of
+/// record results). Errors are passed through.
+pub trait FastaConvertExt: Iterator<Item = Result<FastaRecord>> + Sized {
+    /// Convert records to FASTQ, giving every base `default_quality`
+    fn to_fastq(self, default_quality: char) -> impl Iterator<Item = Result<FastqRecord>> {
+        self.map(move |result| result.map(|record| fasta_to_fastq(record, default_quality)))
+    }
+}
+
+impl<I> FastaConvertExt for I where I: Iterator<Item = Result<FastaRecord>> {}
@@ -0,0 +1,141 @@
+use crate::any::{detect_format, SequenceFormat};
+use crate::common::{open_input, LineReader};
+use crate::error::{Position, PrseqError, Result};
+use crate::fastq::unexpected_eof;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// The number of records and bases in a FASTA or FASTQ file, from `count`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Counts {
+    pub records: u64,
+    pub bases: u64,
+}
+
+/// Count the records and bases of a FASTA or FASTQ file (which may be
+/// compressed), without parsing it into records
+///
+/// The format is detected as by `AnyReader`, and the file is scanned line
+/// by line straight from the reader's buffer, so nothing is allocated per
+/// record and headers, sequences and quality are not checked beyond what
+/// is needed to find the records (in particular, not for UTF-8). Multi-line
+/// sequences and quality are handled as by the parsers, and a FASTQ file
+/// that ends part way through a record or whose quality and sequence
+/// lengths differ is an error. Parser options (e.g. skipping malformed
+/// records) do not apply.
+///
+/// ```no_run
+/// use prseq::count::{count, count_records};
+///
+/// let reads = count_records("reads.fastq.gz")?;
+/// let counts = count("genome.fasta")?;
+/// println!("{} reads; {} contigs of {} bp", reads, counts.records, counts.bases);
+/// # Ok::<(), prseq::PrseqError>(())
+/// ```
+pub fn count<P: AsRef<Path>>(path: P) -> Result<Counts> {
+    count_reader(File::open(path)?)
+}
+
+/// The number of records in a FASTA or FASTQ file (see `count`)
+pub fn count_records<P: AsRef<Path>>(path: P) -> Result<u64> {
+    Ok(count(path)?.records)
+}
+
+/// The total sequence length of a FASTA or FASTQ file (see `count`)
+pub fn count_bases<P: AsRef<Path>>(path: P) -> Result<u64> {
+    Ok(count(path)?.bases)
+}
+
+/// Count the records and bases of FASTA or FASTQ input from any readable
+/// source, with compression detection (see `count`)
+///
+/// ```
+/// use prseq::count::{count_reader, Counts};
+///
+/// let input = b">a\nACGT\nAC\n>b\nGGG\n";
+/// assert_eq!(count_reader(&input[..]).unwrap(), Counts { records: 2, bases: 9 });
+/// ```
+pub fn count_reader<R: Read + Send + 'static>(reader: R) -> Result<Counts> {
+    let mut input = open_input(reader, None, 1, 64 * 1024)?;
+    let (format, skipped, lines) = detect_format(input.as_mut())?;
+    let mut lines = LineReader::with_offset(input, skipped, lines);
+    match format {
+        Some(SequenceFormat::Fasta) => count_fasta(&mut lines),
+        Some(SequenceFormat::Fastq) => count_fastq(&mut lines),
+        None => Ok(Counts::default()),
+    }
+}
+
+/// Count FASTA records, which detection has checked start with '>'
+fn count_fasta(lines: &mut LineReader) -> Result<Counts> {
+    let mut counts = Counts::default();
+    while let Some((line, _)) = lines.next_line()? {
+        let trimmed = line.trim_ascii();
+        if trimmed.first() == Some(&b'>') {
+            counts.records += 1;
+        } else {
+            counts.bases += trimmed.len() as u64;
+        }
+    }
+    Ok(counts)
+}
+
+/// Count FASTQ records, reading sequence lines up to the '+' line and then
+/// quality lines until they are as long as the sequence, as
+/// `FastqReader` does
+fn count_fastq(lines: &mut LineReader) -> Result<Counts> {
+    let mut counts = Counts::default();
+    loop {
+        let position = |lines: &LineReader, records: u64| Position {
+            line: lines.line() + 1,
+            record: records + 1,
+        };
+        let header_position = position(lines, counts.records);
+        let Some((line, _)) = lines.next_line()? else {
+            return Ok(counts);
+        };
+        let trimmed = line.trim_ascii();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed.first() != Some(&b'@') {
+            return Err(PrseqError::invalid_fastq(
+                "FASTQ record must start with '@'",
+                header_position,
+                trimmed,
+            ));
+        }
+
+        let mut sequence_length = 0;
+        loop {
+            let line_position = position(lines, counts.records);
+            let Some((line, _)) = lines.next_line()? else {
+                return Err(unexpected_eof("sequence", line_position));
+            };
+            let trimmed = line.trim_ascii();
+            if trimmed.first() == Some(&b'+') {
+                break;
+            }
+            sequence_length += trimmed.len();
+        }
+        let mut quality_length = 0;
+        while quality_length < sequence_length {
+            let line_position = position(lines, counts.records);
+            let Some((line, _)) = lines.next_line()? else {
+                return Err(unexpected_eof("quality scores", line_position));
+            };
+            quality_length += line.trim_ascii().len();
+        }
+        if quality_length != sequence_length {
+            return Err(PrseqError::LengthMismatch {
+                sequence_length,
+                quality_length,
+                position: header_position,
+            });
+        }
+        counts.records += 1;
+        counts.bases += sequence_length as u64;
+    }
+}
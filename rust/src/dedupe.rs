@@ -0,0 +1,275 @@
+use crate::error::Result;
+use crate::filter::Filterable;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fs::File;
+use std::hash::Hasher;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// What makes two records duplicates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DedupeKey {
+    /// Identical sequences (the default)
+    #[default]
+    Sequence,
+    /// Identical IDs and sequences
+    IdAndSequence,
+}
+
+/// A 128-bit hash of a record's key. Two independent 64-bit hashes make
+/// an accidental collision vanishingly unlikely, even over billions of
+/// records.
+fn key_hash<R: Filterable>(record: &R, key: DedupeKey) -> u128 {
+    let mut high = DefaultHasher::new();
+    let mut low = DefaultHasher::new();
+    low.write_u8(0xa5);
+    let mut add = |bytes: &[u8]| {
+        for hasher in [&mut high, &mut low] {
+            hasher.write_usize(bytes.len());
+            hasher.write(bytes);
+        }
+    };
+    if key == DedupeKey::IdAndSequence {
+        add(record.id().as_bytes());
+    }
+    add(record.sequence().as_bytes());
+    (u128::from(high.finish()) << 64) | u128::from(low.finish())
+}
+
+/// Bits of Bloom filter kept in memory per spilled hash, with which about
+/// 2% of the checks of a new key against a run read the disk
+const BLOOM_BITS: u64 = 8;
+
+/// The number of bits of the Bloom filter set for each hash
+const BLOOM_PROBES: u64 = 5;
+
+/// A Bloom filter over the hashes of a run, which rules out most hashes
+/// that are not in it without reading the run
+struct Bloom {
+    words: Vec<u64>,
+    bits: u64,
+}
+
+impl Bloom {
+    fn new(count: u64) -> Self {
+        let bits = (count * BLOOM_BITS).max(64);
+        Bloom {
+            words: vec![0; bits.div_ceil(64) as usize],
+            bits,
+        }
+    }
+
+    /// The bits for `hash` in a filter of `bits` bits, by double hashing
+    /// with its two halves, as word indexes and masks
+    fn probes(bits: u64, hash: u128) -> impl Iterator<Item = (usize, u64)> {
+        let (first, step) = (hash as u64, (hash >> 64) as u64 | 1);
+        (0..BLOOM_PROBES).map(move |i| {
+            let bit = first.wrapping_add(i.wrapping_mul(step)) % bits;
+            ((bit / 64) as usize, 1 << (bit % 64))
+        })
+    }
+
+    fn insert(&mut self, hash: u128) {
+        for (word, mask) in Bloom::probes(self.bits, hash) {
+            self.words[word] |= mask;
+        }
+    }
+
+    fn may_contain(&self, hash: u128) -> bool {
+        Bloom::probes(self.bits, hash).all(|(word, mask)| self.words[word] & mask != 0)
+    }
+}
+
+/// A sorted run of hashes spilled to an anonymous temporary file (which
+/// the operating system removes once it is closed), searched in place
+struct SpillRun {
+    file: File,
+    len: u64,
+    bloom: Bloom,
+    /// The number of times the hashes have been merged
+    level: u32,
+}
+
+impl SpillRun {
+    /// Write `count` sorted hashes to a new file in `directory`
+    fn write(
+        directory: &Path,
+        count: u64,
+        hashes: impl Iterator<Item = io::Result<u128>>,
+    ) -> io::Result<SpillRun> {
+        let mut file = tempfile::tempfile_in(directory)?;
+        let mut bloom = Bloom::new(count);
+        let mut len = 0;
+        let mut writer = BufWriter::new(&mut file);
+        for hash in hashes {
+            let hash = hash?;
+            bloom.insert(hash);
+            writer.write_all(&hash.to_be_bytes())?;
+            len += 1;
+        }
+        writer.flush()?;
+        drop(writer);
+        Ok(SpillRun {
+            file,
+            len,
+            bloom,
+            level: 0,
+        })
+    }
+
+    /// Merge two runs into one new run
+    fn merge(directory: &Path, mut first: SpillRun, mut second: SpillRun) -> io::Result<SpillRun> {
+        let count = first.len + second.len;
+        let level = first.level.max(second.level) + 1;
+        let mut first = first.hashes()?.peekable();
+        let mut second = second.hashes()?.peekable();
+        let merged = std::iter::from_fn(|| match (first.peek(), second.peek()) {
+            (Some(Ok(a)), Some(Ok(b))) if b < a => second.next(),
+            (Some(_), _) => first.next(),
+            (None, _) => second.next(),
+        });
+        let mut run = SpillRun::write(directory, count, merged)?;
+        run.level = level;
+        Ok(run)
+    }
+
+    /// The hashes, in order, read from the start of the file
+    fn hashes(&mut self) -> io::Result<impl Iterator<Item = io::Result<u128>> + '_> {
+        self.file.seek(SeekFrom::Start(0))?;
+        let mut reader = BufReader::new(&mut self.file);
+        Ok((0..self.len).map(move |_| {
+            let mut entry = [0u8; 16];
+            reader.read_exact(&mut entry)?;
+            Ok(u128::from_be_bytes(entry))
+        }))
+    }
+
+    /// Whether the run holds `hash`: ruled out by the Bloom filter if
+    /// possible, or found by binary search
+    fn contains(&mut self, hash: u128) -> io::Result<bool> {
+        if !self.bloom.may_contain(hash) {
+            return Ok(false);
+        }
+        let (mut low, mut high) = (0, self.len);
+        let mut entry = [0u8; 16];
+        while low < high {
+            let middle = low + (high - low) / 2;
+            self.file.seek(SeekFrom::Start(middle * 16))?;
+            self.file.read_exact(&mut entry)?;
+            match u128::from_be_bytes(entry).cmp(&hash) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => return Ok(true),
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// An iterator over the records of another, dropping those whose key (see
+/// `DedupeKey`) has been seen before, from `FilterExt::dedupe`
+///
+/// Only a 128-bit hash of each key is kept. By default the hashes are held
+/// in memory; `spill_to` bounds that and writes the excess to disk. Errors
+/// are passed through.
+pub struct Dedupe<I> {
+    records: I,
+    key: DedupeKey,
+    seen: HashSet<u128>,
+    spill: Option<(PathBuf, usize)>,
+    runs: Vec<SpillRun>,
+    spilled: usize,
+    removed: u64,
+}
+
+impl<I> Dedupe<I> {
+    pub(crate) fn new(records: I, key: DedupeKey) -> Self {
+        Dedupe {
+            records,
+            key,
+            seen: HashSet::new(),
+            spill: None,
+            runs: Vec::new(),
+            spilled: 0,
+            removed: 0,
+        }
+    }
+
+    /// Keep at most `max_in_memory` hashes in memory, writing each batch
+    /// beyond that to a temporary file in `directory` (which must exist).
+    /// The files are merged as they accumulate, and a Bloom filter of about
+    /// a byte per spilled hash is kept in memory so that a new key seldom
+    /// needs them to be searched. They are removed when the iterator is
+    /// dropped.
+    pub fn spill_to<P: AsRef<Path>>(mut self, directory: P, max_in_memory: usize) -> Self {
+        self.spill = Some((directory.as_ref().to_path_buf(), max_in_memory.max(1)));
+        self
+    }
+
+    /// The number of duplicate records dropped so far
+    pub fn removed(&self) -> u64 {
+        self.removed
+    }
+
+    /// The number of batches of hashes spilled to disk so far
+    pub fn spilled_runs(&self) -> usize {
+        self.spilled
+    }
+
+    /// Record `hash` as seen, returning whether it had been seen before
+    fn check(&mut self, hash: u128) -> io::Result<bool> {
+        if self.seen.contains(&hash) {
+            return Ok(true);
+        }
+        for run in &mut self.runs {
+            if run.contains(hash)? {
+                return Ok(true);
+            }
+        }
+        self.seen.insert(hash);
+        if let Some((directory, max_in_memory)) = &self.spill {
+            if self.seen.len() >= *max_in_memory {
+                let mut hashes: Vec<u128> = self.seen.drain().collect();
+                hashes.sort_unstable();
+                let count = hashes.len() as u64;
+                let run = SpillRun::write(directory, count, hashes.into_iter().map(Ok))?;
+                self.runs.push(run);
+                self.spilled += 1;
+                // Merge runs that have been merged as often, so that there
+                // are only logarithmically many to search.
+                while let [.., previous, last] = &self.runs[..] {
+                    if previous.level != last.level {
+                        break;
+                    }
+                    let last = self.runs.pop().expect("there are two runs");
+                    let previous = self.runs.pop().expect("there are two runs");
+                    self.runs.push(SpillRun::merge(directory, previous, last)?);
+                }
+            }
+        }
+        Ok(false)
+    }
+}
+
+impl<I, R> Iterator for Dedupe<I>
+where
+    I: Iterator<Item = Result<R>>,
+    R: Filterable,
+{
+    type Item = Result<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let record = match self.records.next()? {
+                Ok(record) => record,
+                Err(e) => return Some(Err(e)),
+            };
+            match self.check(key_hash(&record, self.key)) {
+                Ok(true) => self.removed += 1,
+                Ok(false) => return Some(Ok(record)),
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use crate::common::split_header_str;
+use crate::fasta::FastaRecord;
+
+/// An NCBI-style FASTA header, parsed as its parts are asked for
+///
+/// The ID (the header up to the first whitespace) is either a plain
+/// accession such as `NC_045512.2`, or a chain of `|`-separated database
+/// tags and their values, such as `gi|9626243|ref|NC_001477.1|`. The rest
+/// of the header is the description, which may end with the organism in
+/// square brackets.
+///
+/// ```
+/// use prseq::defline::Defline;
+///
+/// let defline = Defline::new("gi|9626243|ref|NC_001477.1| Dengue virus 1, complete genome [Dengue virus 1]");
+/// assert_eq!(defline.gi(), Some(9626243));
+/// assert_eq!(defline.accession(), Some("NC_001477.1"));
+/// assert_eq!(defline.version(), Some(1));
+/// assert_eq!(defline.organism(), Some("Dengue virus 1"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Defline<'a> {
+    id: &'a str,
+    description: Option<&'a str>,
+}
+
+/// One database tag of a `|`-separated NCBI ID, with its values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeqId<'a> {
+    /// The tag, e.g. "gi", "ref" or "sp"
+    pub database: &'a str,
+    /// The values that follow the tag, e.g. the accession and locus name.
+    /// Empty trailing values (as in `ref|NC_001477.1|`) are kept.
+    pub values: Vec<&'a str>,
+}
+
+/// Database tags whose first value is an accession (with its version)
+const ACCESSION_DATABASES: &[&str] = &[
+    "ref", "gb", "emb", "dbj", "tpg", "tpe", "tpd", "sp", "tr", "pir", "prf",
+];
+
+/// The number of values that follow a database tag
+fn arity(database: &str) -> usize {
+    match database {
+        "gi" | "lcl" | "bbs" | "bbm" | "gim" => 1,
+        "pat" | "pgp" => 3,
+        _ => 2,
+    }
+}
+
+impl<'a> Defline<'a> {
+    /// Split a FASTA header (without its '>') into its ID and description
+    pub fn new(header: &'a str) -> Self {
+        let (id, description) = split_header_str(header);
+        Defline { id, description }
+    }
+
+    /// The ID: the header up to the first whitespace
+    pub fn id(&self) -> &'a str {
+        self.id
+    }
+
+    /// The description: the rest of the header, if there is any
+    pub fn description(&self) -> Option<&'a str> {
+        self.description
+    }
+
+    /// The database tags of a `|`-separated ID, in order. A plain ID has
+    /// none.
+    pub fn ids(&self) -> Vec<SeqId<'a>> {
+        if !self.id.contains('|') {
+            return Vec::new();
+        }
+        let mut fields = self.id.split('|').peekable();
+        let mut ids = Vec::new();
+        while let Some(database) = fields.next() {
+            if database.is_empty() && fields.peek().is_none() {
+                break;
+            }
+            let values = fields.by_ref().take(arity(database)).collect();
+            ids.push(SeqId { database, values });
+        }
+        ids
+    }
+
+    /// The values following the first `database` tag in the ID
+    pub fn get(&self, database: &str) -> Option<Vec<&'a str>> {
+        self.ids()
+            .into_iter()
+            .find(|id| id.database == database)
+            .map(|id| id.values)
+    }
+
+    /// The accession (with its version, if it has one): the first value of
+    /// the first tag that carries one, or the whole ID if it is not
+    /// `|`-separated
+    pub fn accession(&self) -> Option<&'a str> {
+        if !self.id.contains('|') {
+            return Some(self.id).filter(|id| !id.is_empty());
+        }
+        self.ids()
+            .into_iter()
+            .filter(|id| ACCESSION_DATABASES.contains(&id.database))
+            .find_map(|id| id.values.first().copied().filter(|a| !a.is_empty()))
+    }
+
+    /// The version of the accession (the number after its last '.')
+    pub fn version(&self) -> Option<u32> {
+        let (_, version) = self.accession()?.rsplit_once('.')?;
+        version.parse().ok()
+    }
+
+    /// The GenInfo identifier, from a `gi|` tag
+    pub fn gi(&self) -> Option<u64> {
+        self.get("gi")?.first()?.parse().ok()
+    }
+
+    /// The organism, from a bracketed name at the end of the description
+    pub fn organism(&self) -> Option<&'a str> {
+        let description = self.description?.strip_suffix(']')?;
+        let start = description.rfind('[')?;
+        Some(description[start + 1..].trim()).filter(|organism| !organism.is_empty())
+    }
+}
+
+impl FastaRecord {
+    /// The record's header as an NCBI-style defline
+    pub fn defline(&self) -> Defline<'_> {
+        Defline::new(&self.header)
+    }
+}
@@ -0,0 +1,355 @@
+use crate::error::{PrseqError, Result};
+use crate::fastq::FastqRecord;
+use crate::illumina::IlluminaHeader;
+use crate::paired::check_pair;
+use crate::trim::trim_str;
+use crate::writer::FastqWriter;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// The name of the bin for reads that match no barcode (or more than one
+/// equally well)
+pub const UNDETERMINED: &str = "undetermined";
+
+/// Where each read's barcode is found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarcodeLocation {
+    /// The index field of an Illumina header (see `IlluminaHeader`), e.g.
+    /// `ATCACG` or, for dual indexes, `ATCACG+GTACTG`
+    Header,
+    /// The first bases of the read, which are removed from the read unless
+    /// `Demultiplexer::trim_barcode` is turned off
+    ReadStart,
+    /// A separate index read (e.g. an I1 file), given alongside the reads
+    IndexRead,
+}
+
+/// The number of reads assigned to each sample
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DemuxReport {
+    /// The number of reads for each sample, and for `UNDETERMINED`
+    pub counts: BTreeMap<String, u64>,
+}
+
+impl DemuxReport {
+    /// The number of reads assigned to `sample`
+    pub fn count(&self, sample: &str) -> u64 {
+        self.counts.get(sample).copied().unwrap_or(0)
+    }
+
+    /// The number of reads that matched no barcode
+    pub fn undetermined(&self) -> u64 {
+        self.count(UNDETERMINED)
+    }
+
+    /// The total number of reads
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+}
+
+/// Assigns reads to samples by their barcodes, allowing mismatches
+///
+/// A read is assigned to the sample whose barcode is nearest (by Hamming
+/// distance) to its own, if that is within `max_mismatches` and no other
+/// barcode is as near. Other reads are undetermined.
+///
+/// ```
+/// use prseq::demux::{BarcodeLocation, Demultiplexer};
+///
+/// let demux = Demultiplexer::new(
+///     [("ACGT", "sample1"), ("TTTT", "sample2")],
+///     BarcodeLocation::ReadStart,
+/// )
+/// .unwrap()
+/// .max_mismatches(1);
+/// assert_eq!(demux.assign(b"ACGA"), Some("sample1"));
+/// assert_eq!(demux.assign(b"GGGG"), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Demultiplexer {
+    /// The barcodes (upper case) and their samples
+    barcodes: Vec<(Vec<u8>, String)>,
+    location: BarcodeLocation,
+    /// The most mismatches allowed between a read's barcode and a sample's
+    pub max_mismatches: usize,
+    /// Whether a barcode at the start of a read is removed from it
+    pub trim_barcode: bool,
+}
+
+impl Demultiplexer {
+    /// A demultiplexer for `barcodes`, mapping each barcode to its sample,
+    /// that allows no mismatches
+    ///
+    /// Fails if a barcode is empty or given twice, or, for barcodes at the
+    /// start of reads, if the barcodes are not all the same length. Sample
+    /// names become file names (see `demultiplex_to_dir`), so one that is
+    /// empty, is "." or "..", contains a path separator, or is
+    /// `UNDETERMINED` (whose reads it would be mixed with) also fails.
+    pub fn new<I, B, S>(barcodes: I, location: BarcodeLocation) -> Result<Self>
+    where
+        I: IntoIterator<Item = (B, S)>,
+        B: AsRef<[u8]>,
+        S: Into<String>,
+    {
+        let mut seen = HashMap::new();
+        let mut entries = Vec::new();
+        for (barcode, sample) in barcodes {
+            let barcode = barcode.as_ref().to_ascii_uppercase();
+            let sample = sample.into();
+            let text = String::from_utf8_lossy(&barcode).into_owned();
+            check_sample_name(&sample)?;
+            if barcode.is_empty() {
+                return Err(invalid_barcodes(format!(
+                    "Empty barcode for sample '{}'",
+                    sample
+                )));
+            }
+            if let Some(other) = seen.insert(barcode.clone(), sample.clone()) {
+                return Err(invalid_barcodes(format!(
+                    "Barcode {} is given for both '{}' and '{}'",
+                    text, other, sample
+                )));
+            }
+            entries.push((barcode, sample));
+        }
+        if location == BarcodeLocation::ReadStart
+            && entries.iter().any(|(b, _)| b.len() != entries[0].0.len())
+        {
+            return Err(invalid_barcodes(
+                "Barcodes at the start of reads must all be the same length".to_string(),
+            ));
+        }
+        Ok(Demultiplexer {
+            barcodes: entries,
+            location,
+            max_mismatches: 0,
+            trim_barcode: true,
+        })
+    }
+
+    /// Set the most mismatches allowed
+    pub fn max_mismatches(mut self, mismatches: usize) -> Self {
+        self.max_mismatches = mismatches;
+        self
+    }
+
+    /// Set whether a barcode at the start of a read is removed from it
+    pub fn trim_barcode(mut self, trim: bool) -> Self {
+        self.trim_barcode = trim;
+        self
+    }
+
+    /// Where the barcodes are found
+    pub fn location(&self) -> BarcodeLocation {
+        self.location
+    }
+
+    /// The names of the samples, in the order given
+    pub fn samples(&self) -> impl Iterator<Item = &str> {
+        self.barcodes.iter().map(|(_, sample)| sample.as_str())
+    }
+
+    /// The sample `barcode` belongs to, or None if it is undetermined. An N
+    /// in the barcode counts as a mismatch.
+    pub fn assign(&self, barcode: &[u8]) -> Option<&str> {
+        let mut best = None;
+        let mut best_distance = self.max_mismatches + 1;
+        let mut tied = false;
+        for (candidate, sample) in &self.barcodes {
+            if candidate.len() != barcode.len() {
+                continue;
+            }
+            let distance = candidate
+                .iter()
+                .zip(barcode)
+                .filter(|(&a, &b)| a != b.to_ascii_uppercase() || b | 0x20 == b'n')
+                .count();
+            if distance < best_distance {
+                best = Some(sample.as_str());
+                best_distance = distance;
+                tied = false;
+            } else if distance == best_distance {
+                tied = true;
+            }
+        }
+        if tied {
+            None
+        } else {
+            best
+        }
+    }
+
+    /// The sample a read belongs to, and the read as it should be written
+    /// (without its barcode, if that was at its start and is trimmed).
+    /// `index` is the read's index read, for `BarcodeLocation::IndexRead`.
+    pub fn classify(
+        &self,
+        record: FastqRecord,
+        index: Option<&FastqRecord>,
+    ) -> (Option<&str>, FastqRecord) {
+        match self.location {
+            BarcodeLocation::Header => {
+                let index = IlluminaHeader::parse(&record.header).and_then(|header| header.index);
+                let sample = index.and_then(|index| self.assign(index.as_bytes()));
+                (sample, record)
+            }
+            BarcodeLocation::ReadStart => {
+                let length = self.barcodes.first().map_or(0, |(b, _)| b.len());
+                let barcode = record.sequence.as_bytes().get(..length);
+                let sample = barcode.and_then(|barcode| self.assign(barcode));
+                if self.trim_barcode && sample.is_some() {
+                    let range = length..record.sequence.len();
+                    let trimmed = FastqRecord {
+                        sequence: trim_str(&record.sequence, &range),
+                        quality: trim_str(&record.quality, &range),
+                        header: record.header,
+                    };
+                    return (sample, trimmed);
+                }
+                (sample, record)
+            }
+            BarcodeLocation::IndexRead => {
+                let sample = index.and_then(|index| self.assign(index.sequence.as_bytes()));
+                (sample, record)
+            }
+        }
+    }
+
+    /// Write each read to the writer for its sample (or `UNDETERMINED`) in
+    /// `writers`, returning the number of reads for each. Reads whose sample
+    /// has no writer are counted but not written. The writers are flushed
+    /// at the end.
+    pub fn demultiplex<I, W>(
+        &self,
+        records: I,
+        writers: &mut HashMap<String, FastqWriter<W>>,
+    ) -> Result<DemuxReport>
+    where
+        I: IntoIterator<Item = Result<FastqRecord>>,
+        W: Write,
+    {
+        let mut report = DemuxReport::default();
+        for record in records {
+            let (sample, record) = self.classify(record?, None);
+            route(sample, &record, writers, &mut report)?;
+        }
+        flush_all(writers)?;
+        Ok(report)
+    }
+
+    /// Demultiplex reads by the barcodes in their index reads (for
+    /// `BarcodeLocation::IndexRead`), as `demultiplex`. The reads and index
+    /// reads must be in the same order, which is checked by their IDs.
+    pub fn demultiplex_with_index<I, J, W>(
+        &self,
+        records: I,
+        index_records: J,
+        writers: &mut HashMap<String, FastqWriter<W>>,
+    ) -> Result<DemuxReport>
+    where
+        I: IntoIterator<Item = Result<FastqRecord>>,
+        J: IntoIterator<Item = Result<FastqRecord>>,
+        W: Write,
+    {
+        let mut report = DemuxReport::default();
+        let mut index_records = index_records.into_iter();
+        for (pair_number, record) in (1..).zip(records) {
+            let record = record?;
+            let index = match index_records.next() {
+                Some(index) => index?,
+                None => {
+                    return Err(PrseqError::PairMismatch {
+                        pair: pair_number,
+                        message: format!(
+                            "The index reads ended before read {} ('{}')",
+                            pair_number, record.header
+                        ),
+                    })
+                }
+            };
+            check_pair(&record, &index, pair_number)?;
+            let (sample, record) = self.classify(record, Some(&index));
+            route(sample, &record, writers, &mut report)?;
+        }
+        flush_all(writers)?;
+        Ok(report)
+    }
+
+    /// Demultiplex reads to `{sample}.fastq` files (and `undetermined.fastq`)
+    /// in `directory`, which is created if need be. Files are only created
+    /// for samples that have reads.
+    pub fn demultiplex_to_dir<I, P>(&self, records: I, directory: P) -> Result<DemuxReport>
+    where
+        I: IntoIterator<Item = Result<FastqRecord>>,
+        P: AsRef<Path>,
+    {
+        let directory = directory.as_ref();
+        fs::create_dir_all(directory)?;
+        let mut writers: HashMap<String, FastqWriter<BufWriter<File>>> = HashMap::new();
+        let mut report = DemuxReport::default();
+        for record in records {
+            let (sample, record) = self.classify(record?, None);
+            let name = sample.unwrap_or(UNDETERMINED);
+            if !writers.contains_key(name) {
+                let path = directory.join(format!("{}.fastq", name));
+                writers.insert(name.to_string(), FastqWriter::to_file(path)?);
+            }
+            route(sample, &record, &mut writers, &mut report)?;
+        }
+        flush_all(&mut writers)?;
+        Ok(report)
+    }
+}
+
+fn invalid_barcodes(message: String) -> PrseqError {
+    io::Error::new(io::ErrorKind::InvalidInput, message).into()
+}
+
+/// Check that a sample name can be used as the name of its output file,
+/// and is not taken by the undetermined reads
+fn check_sample_name(sample: &str) -> Result<()> {
+    let problem = if sample.is_empty() {
+        "is empty"
+    } else if sample == "." || sample == ".." || sample.contains(['/', '\\', '\0']) {
+        "cannot be used as a file name"
+    } else if sample == UNDETERMINED {
+        "is reserved for reads that match no barcode"
+    } else {
+        return Ok(());
+    };
+    Err(invalid_barcodes(format!(
+        "Sample name '{}' {}",
+        sample, problem
+    )))
+}
+
+/// Count a read for its sample and write it, if the sample has a writer
+fn route<W: Write>(
+    sample: Option<&str>,
+    record: &FastqRecord,
+    writers: &mut HashMap<String, FastqWriter<W>>,
+    report: &mut DemuxReport,
+) -> Result<()> {
+    let name = sample.unwrap_or(UNDETERMINED);
+    match report.counts.get_mut(name) {
+        Some(count) => *count += 1,
+        None => {
+            report.counts.insert(name.to_string(), 1);
+        }
+    }
+    if let Some(writer) = writers.get_mut(name) {
+        writer.write_record(record)?;
+    }
+    Ok(())
+}
+
+fn flush_all<W: Write>(writers: &mut HashMap<String, FastqWriter<W>>) -> Result<()> {
+    for writer in writers.values_mut() {
+        writer.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,293 @@
+use crate::error::Result;
+use crate::record::SequenceRecord;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hasher;
+
+/// How a record compares between the two inputs of a `diff`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DiffEntry {
+    /// A record in both inputs with the same description, sequence and
+    /// quality
+    Identical(String),
+    /// A record in both inputs that differs in the parts marked true
+    Changed {
+        id: String,
+        description: bool,
+        sequence: bool,
+        quality: bool,
+    },
+    /// A record only in the first input
+    OnlyInFirst(String),
+    /// A record only in the second input
+    OnlyInSecond(String),
+}
+
+impl DiffEntry {
+    /// The ID of the record
+    pub fn id(&self) -> &str {
+        match self {
+            DiffEntry::Identical(id)
+            | DiffEntry::Changed { id, .. }
+            | DiffEntry::OnlyInFirst(id)
+            | DiffEntry::OnlyInSecond(id) => id,
+        }
+    }
+}
+
+/// The number of records of each kind found by a `diff`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffSummary {
+    pub identical: u64,
+    pub changed: u64,
+    pub only_in_first: u64,
+    pub only_in_second: u64,
+}
+
+impl DiffSummary {
+    /// Whether the inputs hold the same records
+    pub fn is_identical(&self) -> bool {
+        self.changed == 0 && self.only_in_first == 0 && self.only_in_second == 0
+    }
+
+    /// Count an entry
+    pub fn add(&mut self, entry: &DiffEntry) {
+        match entry {
+            DiffEntry::Identical(_) => self.identical += 1,
+            DiffEntry::Changed { .. } => self.changed += 1,
+            DiffEntry::OnlyInFirst(_) => self.only_in_first += 1,
+            DiffEntry::OnlyInSecond(_) => self.only_in_second += 1,
+        }
+    }
+}
+
+/// A 128-bit hash of some text, made of two independent 64-bit hashes
+fn text_hash(text: Option<&str>) -> u128 {
+    let mut high = DefaultHasher::new();
+    let mut low = DefaultHasher::new();
+    low.write_u8(0xa5);
+    for hasher in [&mut high, &mut low] {
+        match text {
+            Some(text) => {
+                hasher.write_usize(text.len());
+                hasher.write(text.as_bytes());
+            }
+            None => hasher.write_u8(0),
+        }
+    }
+    (u128::from(high.finish()) << 64) | u128::from(low.finish())
+}
+
+/// What is kept of a record while it waits for its match: hashes of its
+/// description, sequence and quality, and its place in its input
+struct Fingerprint {
+    index: u64,
+    description: u128,
+    sequence: u128,
+    quality: u128,
+}
+
+impl Fingerprint {
+    fn new<R: SequenceRecord>(record: &R, index: u64) -> Self {
+        Fingerprint {
+            index,
+            description: text_hash(record.description()),
+            sequence: text_hash(Some(record.sequence())),
+            quality: text_hash(record.quality()),
+        }
+    }
+}
+
+/// The records of one input that have not yet been matched, by ID, with a
+/// queue for IDs that are repeated
+#[derive(Default)]
+struct Pending {
+    records: HashMap<String, VecDeque<Fingerprint>>,
+    count: u64,
+}
+
+impl Pending {
+    fn push(&mut self, id: &str, fingerprint: Fingerprint) {
+        self.records
+            .entry(id.to_string())
+            .or_default()
+            .push_back(fingerprint);
+    }
+
+    fn take(&mut self, id: &str) -> Option<Fingerprint> {
+        let queue = self.records.get_mut(id)?;
+        let fingerprint = queue.pop_front();
+        if queue.is_empty() {
+            self.records.remove(id);
+        }
+        fingerprint
+    }
+
+    /// The IDs left, in input order
+    fn drain(&mut self) -> Vec<String> {
+        let mut left: Vec<(u64, String)> = self
+            .records
+            .drain()
+            .flat_map(|(id, queue)| queue.into_iter().map(move |f| (f.index, id.clone())))
+            .collect();
+        left.sort_unstable();
+        left.into_iter().map(|(_, id)| id).collect()
+    }
+}
+
+/// Compare the records of two inputs, matching them by ID
+///
+/// The inputs are read in step, and only records whose match has not yet
+/// been seen are remembered, as hashes, so inputs with their records in
+/// the same (or nearly the same) order are compared in little memory.
+/// Matched records are reported as they are found, and the records only in
+/// one input at the end, in input order. A repeated ID is matched with the
+/// next unmatched record with that ID in the other input.
+///
+/// ```
+/// use prseq::diff::{diff, DiffEntry};
+/// use prseq::FastaReader;
+///
+/// let first = FastaReader::from_string(">a\nACGT\n>b\nGG\n>c\nT\n").unwrap();
+/// let second = FastaReader::from_string(">a\nACGT\n>c\nA\n").unwrap();
+/// let entries: Vec<DiffEntry> = diff(first, second).map(Result::unwrap).collect();
+/// assert_eq!(entries[0], DiffEntry::Identical("a".into()));
+/// assert!(matches!(entries[1], DiffEntry::Changed { sequence: true, .. }));
+/// assert_eq!(entries[2], DiffEntry::OnlyInFirst("b".into()));
+/// ```
+pub fn diff<A, B, R1, R2>(first: A, second: B) -> Diff<A::IntoIter, B::IntoIter>
+where
+    A: IntoIterator<Item = Result<R1>>,
+    B: IntoIterator<Item = Result<R2>>,
+    R1: SequenceRecord,
+    R2: SequenceRecord,
+{
+    Diff {
+        first: Some(first.into_iter()),
+        second: Some(second.into_iter()),
+        first_pending: Pending::default(),
+        second_pending: Pending::default(),
+        from_first: true,
+        unmatched: VecDeque::new(),
+        finished: false,
+    }
+}
+
+/// Compare two inputs, as `diff` does, returning just the counts
+pub fn diff_summary<A, B, R1, R2>(first: A, second: B) -> Result<DiffSummary>
+where
+    A: IntoIterator<Item = Result<R1>>,
+    B: IntoIterator<Item = Result<R2>>,
+    R1: SequenceRecord,
+    R2: SequenceRecord,
+{
+    let mut summary = DiffSummary::default();
+    for entry in diff(first, second) {
+        summary.add(&entry?);
+    }
+    Ok(summary)
+}
+
+/// An iterator over how the records of two inputs compare, from `diff`
+pub struct Diff<A, B> {
+    first: Option<A>,
+    second: Option<B>,
+    first_pending: Pending,
+    second_pending: Pending,
+    /// Which input the next record is read from
+    from_first: bool,
+    /// The records only in one input, once both have been read
+    unmatched: VecDeque<DiffEntry>,
+    finished: bool,
+}
+
+/// Match a record from one input against the pending records of the other
+fn compare<R: SequenceRecord>(
+    record: &R,
+    pending: &mut Pending,
+    other_pending: &mut Pending,
+) -> Option<DiffEntry> {
+    let index = pending.count;
+    pending.count += 1;
+    let fingerprint = Fingerprint::new(record, index);
+    let Some(other) = other_pending.take(record.id()) else {
+        pending.push(record.id(), fingerprint);
+        return None;
+    };
+    let id = record.id().to_string();
+    let description = fingerprint.description != other.description;
+    let sequence = fingerprint.sequence != other.sequence;
+    let quality = fingerprint.quality != other.quality;
+    Some(if description || sequence || quality {
+        DiffEntry::Changed {
+            id,
+            description,
+            sequence,
+            quality,
+        }
+    } else {
+        DiffEntry::Identical(id)
+    })
+}
+
+impl<A, B, R1, R2> Iterator for Diff<A, B>
+where
+    A: Iterator<Item = Result<R1>>,
+    B: Iterator<Item = Result<R2>>,
+    R1: SequenceRecord,
+    R2: SequenceRecord,
+{
+    type Item = Result<DiffEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.first.is_none() && self.second.is_none() {
+                if !self.finished {
+                    self.finished = true;
+                    let first = self.first_pending.drain();
+                    let second = self.second_pending.drain();
+                    self.unmatched
+                        .extend(first.into_iter().map(DiffEntry::OnlyInFirst));
+                    self.unmatched
+                        .extend(second.into_iter().map(DiffEntry::OnlyInSecond));
+                }
+                return self.unmatched.pop_front().map(Ok);
+            }
+            let read_first = (self.from_first && self.first.is_some()) || self.second.is_none();
+            self.from_first = !self.from_first;
+            let entry = if read_first {
+                let input = self.first.as_mut().expect("the first input is unfinished");
+                match input.next() {
+                    Some(Ok(record)) => {
+                        compare(&record, &mut self.first_pending, &mut self.second_pending)
+                    }
+                    Some(Err(e)) => return Some(Err(e)),
+                    None => {
+                        self.first = None;
+                        None
+                    }
+                }
+            } else {
+                let input = self
+                    .second
+                    .as_mut()
+                    .expect("the second input is unfinished");
+                match input.next() {
+                    Some(Ok(record)) => {
+                        compare(&record, &mut self.second_pending, &mut self.first_pending)
+                    }
+                    Some(Err(e)) => return Some(Err(e)),
+                    None => {
+                        self.second = None;
+                        None
+                    }
+                }
+            };
+            if let Some(entry) = entry {
+                return Some(Ok(entry));
+            }
+        }
+    }
+}
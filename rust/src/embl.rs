@@ -0,0 +1,230 @@
+use crate::common::{create_reader_with_compression, utf8_str, LineReader};
+use crate::error::{Position, PrseqError, Result};
+use crate::fasta::FastaRecord;
+use crate::genbank::{parse_feature_line, Feature};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// An entry from an EMBL flat file
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EmblRecord {
+    /// The entry name, from the ID line
+    pub name: String,
+    /// The sequence length given on the ID line
+    pub length: Option<usize>,
+    /// The description (DE lines), with continuation lines joined
+    pub description: String,
+    /// The primary (first) accession, from the AC lines
+    pub accession: Option<String>,
+    /// The accession.version, from the sequence version on the ID line
+    pub version: Option<String>,
+    /// The organism (the first OS line)
+    pub organism: Option<String>,
+    pub features: Vec<Feature>,
+    /// The sequence from the SQ section, in upper case
+    pub sequence: String,
+}
+
+impl EmblRecord {
+    /// The best identifier: the accession.version, else the accession,
+    /// else the entry name
+    pub fn id(&self) -> &str {
+        self.version
+            .as_deref()
+            .or(self.accession.as_deref())
+            .unwrap_or(&self.name)
+    }
+
+    /// The features with the given key, such as "CDS"
+    pub fn features_of_kind<'a>(&'a self, kind: &'a str) -> impl Iterator<Item = &'a Feature> {
+        self.features
+            .iter()
+            .filter(move |feature| feature.kind == kind)
+    }
+
+    /// The record as FASTA, with the ID and description as its header
+    pub fn to_fasta(&self) -> FastaRecord {
+        FastaRecord {
+            header: self.fasta_header(),
+            sequence: self.sequence.clone(),
+        }
+    }
+
+    fn fasta_header(&self) -> String {
+        if self.description.is_empty() {
+            self.id().to_string()
+        } else {
+            format!("{} {}", self.id(), self.description)
+        }
+    }
+}
+
+impl From<EmblRecord> for FastaRecord {
+    fn from(record: EmblRecord) -> Self {
+        FastaRecord {
+            header: record.fasta_header(),
+            sequence: record.sequence,
+        }
+    }
+}
+
+/// Fill in the name, sequence version and length from an ID line, in
+/// either the current form (`X56734; SV 1; linear; mRNA; STD; PLN; 1859
+/// BP.`) or the pre-2006 one (`X56734 standard; RNA; PLN; 1859 BP.`)
+fn parse_id_line(value: &str, record: &mut EmblRecord) -> Option<u32> {
+    let mut fields = value.split(';').map(str::trim);
+    record.name = fields
+        .next()
+        .and_then(|name| name.split_whitespace().next())
+        .unwrap_or("")
+        .to_string();
+    let mut version = None;
+    for field in fields {
+        if let Some(number) = field.strip_prefix("SV ") {
+            version = number.trim().parse().ok();
+        }
+        let words: Vec<&str> = field.split_whitespace().collect();
+        if let [length, unit] = words[..] {
+            if unit == "BP." || unit == "AA." {
+                record.length = length.parse().ok();
+            }
+        }
+    }
+    version
+}
+
+/// Iterator over the entries of an EMBL flat file (such as an ENA
+/// download)
+///
+/// The ID, AC, DE and OS lines, the feature table and the sequence are
+/// read; other lines are skipped. Records convert to `FastaRecord`s with
+/// `to_fasta` or `into()`.
+///
+/// ```
+/// use prseq::embl::EmblReader;
+///
+/// let input = b"ID   X56734; SV 1; linear; mRNA; STD; PLN; 8 BP.
+/// XX
+/// AC   X56734; S46826;
+/// XX
+/// DE   Trifolium repens mRNA for non-cyanogenic beta-glucosidase
+/// XX
+/// SQ   Sequence 8 BP; 4 A; 1 C; 1 G; 2 T; 0 other;
+///      aaacaaacct                                                         8
+/// //
+/// ";
+/// let mut reader = EmblReader::from_reader(&input[..]).unwrap();
+/// let record = reader.next().unwrap().unwrap();
+/// assert_eq!(record.id(), "X56734.1");
+/// assert_eq!(record.sequence, "AAACAAACCT");
+/// ```
+pub struct EmblReader {
+    lines: LineReader,
+    records: u64,
+}
+
+impl EmblReader {
+    /// Create a new EmblReader from a file path
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Create a new EmblReader from any readable source with compression
+    /// detection
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> Result<Self> {
+        let buf_reader = create_reader_with_compression(reader)?;
+        Ok(EmblReader {
+            lines: LineReader::new(Box::new(buf_reader)),
+            records: 0,
+        })
+    }
+
+    fn read_next(&mut self) -> Result<Option<EmblRecord>> {
+        let mut record = EmblRecord::default();
+        let mut started = false;
+        let mut in_sequence = false;
+        // Whether the last qualifier's quoted value continues on the next line
+        let mut open_quote = false;
+        loop {
+            let position = Position {
+                line: self.lines.line() + 1,
+                record: self.records + 1,
+            };
+            let Some((line, _)) = self.lines.next_line()? else {
+                if started {
+                    return Err(PrseqError::UnexpectedEof {
+                        message: "EMBL entry ended without '//'".to_string(),
+                        position,
+                    });
+                }
+                return Ok(None);
+            };
+            if !started {
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                if !line.starts_with(b"ID ") {
+                    return Err(PrseqError::invalid_format(
+                        "EMBL",
+                        "EMBL entry must start with an 'ID' line",
+                        position,
+                        line,
+                    ));
+                }
+                started = true;
+            }
+            if line.starts_with(b"//") {
+                self.records += 1;
+                return Ok(Some(record));
+            }
+            let text = utf8_str(line, "EMBL", Some(position))?;
+            if in_sequence {
+                record.sequence.extend(
+                    text.bytes()
+                        .filter(u8::is_ascii_alphabetic)
+                        .map(|base| base.to_ascii_uppercase() as char),
+                );
+                continue;
+            }
+            let code = text.get(..2).unwrap_or(text);
+            let value = text.get(2..).unwrap_or("").trim();
+            match code {
+                "ID" => {
+                    if let Some(version) = parse_id_line(value, &mut record) {
+                        record.version = Some(format!("{}.{}", record.name, version));
+                    }
+                }
+                "AC" if record.accession.is_none() => {
+                    record.accession = value
+                        .split(';')
+                        .map(str::trim)
+                        .find(|accession| !accession.is_empty())
+                        .map(String::from);
+                }
+                "DE" => {
+                    if !record.description.is_empty() {
+                        record.description.push(' ');
+                    }
+                    record.description.push_str(value);
+                }
+                "OS" if record.organism.is_none() => {
+                    record.organism = Some(value.to_string());
+                }
+                "FT" => parse_feature_line(text, &mut record.features, &mut open_quote).map_err(
+                    |message| PrseqError::invalid_format("EMBL", message, position, line),
+                )?,
+                "SQ" => in_sequence = true,
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Iterator for EmblReader {
+    type Item = Result<EmblRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_next().transpose()
+    }
+}
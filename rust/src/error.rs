@@ -0,0 +1,392 @@
+use crate::alphabet::Alphabet;
+use crate::quality::QualityEncoding;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::Utf8Error;
+
+/// The result type returned by the record readers
+pub type Result<T> = std::result::Result<T, PrseqError>;
+
+/// Where in its input a parse error occurred
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    /// The 1-based number of the line being read
+    pub line: u64,
+    /// The 1-based number of the record being read
+    pub record: u64,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, record {}", self.line, self.record)
+    }
+}
+
+/// An error from reading sequence records
+///
+/// Parse errors carry the `Position` they occurred at and, where there is
+/// one, a snippet of the offending line. `kind()` gives the closest
+/// `io::ErrorKind`, and a `PrseqError` converts into an `io::Error` (and
+/// back) so that `?` works in functions returning `io::Result`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PrseqError {
+    /// An I/O error, including errors from decompression
+    Io(io::Error),
+    /// Malformed FASTA input
+    InvalidFasta {
+        message: String,
+        position: Position,
+        content: String,
+    },
+    /// Malformed FASTQ input
+    InvalidFastq {
+        message: String,
+        position: Position,
+        content: String,
+    },
+    /// Malformed input in one of the other formats read (such as GenBank)
+    InvalidFormat {
+        format: &'static str,
+        message: String,
+        position: Position,
+        content: String,
+    },
+    /// A FASTQ record whose quality is not the same length as its sequence
+    LengthMismatch {
+        sequence_length: usize,
+        quality_length: usize,
+        position: Position,
+    },
+    /// Input that ended part way through a record
+    UnexpectedEof { message: String, position: Position },
+    /// A sequence with a character outside the alphabet it was validated
+    /// against (see `ParserOptions::validate`)
+    InvalidSequence {
+        id: String,
+        alphabet: Alphabet,
+        /// The 0-based offset of the first invalid byte in the sequence
+        offset: usize,
+        byte: u8,
+        position: Position,
+    },
+    /// A FASTQ quality character that is out of range for its encoding
+    InvalidQuality {
+        id: String,
+        encoding: QualityEncoding,
+        /// The 0-based offset of the invalid character in the quality
+        offset: usize,
+        byte: u8,
+        /// None for records converted after they were read
+        position: Option<Position>,
+    },
+    /// A record that is not valid UTF-8. The position is None for records
+    /// converted after they were read, e.g. with `FastaRecordBytes::into_record`.
+    InvalidUtf8 {
+        format: &'static str,
+        position: Option<Position>,
+        source: Utf8Error,
+    },
+    /// The two reads of a pair have different IDs, or one source ended
+    /// before the other
+    PairMismatch { pair: usize, message: String },
+    /// A record with the same ID as an earlier record (see
+    /// `ParserOptions::duplicate_ids`)
+    DuplicateId {
+        id: String,
+        /// The (1-based) number of the record the ID was first seen in
+        first: u64,
+        position: Position,
+    },
+    /// A record's header or sequence that is longer than the reader allows
+    /// (see `ParserOptions::max_sequence_length`)
+    RecordTooLong {
+        format: &'static str,
+        /// "header" or "sequence"
+        part: &'static str,
+        limit: usize,
+        position: Position,
+    },
+    /// A range that does not fit in the sequence it was applied to
+    OutOfRange { range: String, length: usize },
+    /// An error in a file read by path, or in one of several files being
+    /// read in turn
+    InFile {
+        path: PathBuf,
+        source: Box<PrseqError>,
+    },
+}
+
+/// The longest snippet of offending input included in an error
+const MAX_SNIPPET: usize = 60;
+
+impl PrseqError {
+    /// An InvalidFasta error for the line `content`
+    pub(crate) fn invalid_fasta(
+        message: impl Into<String>,
+        position: Position,
+        content: &[u8],
+    ) -> Self {
+        PrseqError::InvalidFasta {
+            message: message.into(),
+            position,
+            content: snippet(content),
+        }
+    }
+
+    /// An InvalidFastq error for the line `content`
+    pub(crate) fn invalid_fastq(
+        message: impl Into<String>,
+        position: Position,
+        content: &[u8],
+    ) -> Self {
+        PrseqError::InvalidFastq {
+            message: message.into(),
+            position,
+            content: snippet(content),
+        }
+    }
+
+    /// An InvalidFormat error for the line `content` of a `format` file
+    pub(crate) fn invalid_format(
+        format: &'static str,
+        message: impl Into<String>,
+        position: Position,
+        content: &[u8],
+    ) -> Self {
+        PrseqError::InvalidFormat {
+            format,
+            message: message.into(),
+            position,
+            content: snippet(content),
+        }
+    }
+
+    /// The closest `io::ErrorKind` to this error
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            PrseqError::Io(e) => e.kind(),
+            PrseqError::UnexpectedEof { .. } => io::ErrorKind::UnexpectedEof,
+            PrseqError::OutOfRange { .. } => io::ErrorKind::InvalidInput,
+            PrseqError::InFile { source, .. } => source.kind(),
+            _ => io::ErrorKind::InvalidData,
+        }
+    }
+
+    /// Where in its input the error occurred, if it is a parse error
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            PrseqError::InvalidFasta { position, .. }
+            | PrseqError::InvalidFastq { position, .. }
+            | PrseqError::InvalidFormat { position, .. }
+            | PrseqError::LengthMismatch { position, .. }
+            | PrseqError::InvalidSequence { position, .. }
+            | PrseqError::DuplicateId { position, .. }
+            | PrseqError::RecordTooLong { position, .. }
+            | PrseqError::UnexpectedEof { position, .. } => Some(*position),
+            PrseqError::InvalidUtf8 { position, .. }
+            | PrseqError::InvalidQuality { position, .. } => *position,
+            PrseqError::InFile { source, .. } => source.position(),
+            _ => None,
+        }
+    }
+
+    /// Name the file the error occurred in, unless it already names one
+    pub(crate) fn in_file(self, path: Option<&Path>) -> Self {
+        match (self, path) {
+            (PrseqError::InFile { path, source }, _) => PrseqError::InFile { path, source },
+            (error, Some(path)) => PrseqError::InFile {
+                path: path.to_path_buf(),
+                source: Box::new(error),
+            },
+            (error, None) => error,
+        }
+    }
+
+    /// The file the error occurred in, when reading from a path
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            PrseqError::InFile { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+}
+
+/// Make a printable snippet of a line of input
+fn snippet(content: &[u8]) -> String {
+    let text = String::from_utf8_lossy(content);
+    match text.char_indices().nth(MAX_SNIPPET) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.into_owned(),
+    }
+}
+
+impl fmt::Display for PrseqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrseqError::Io(e) => write!(f, "{}", e),
+            PrseqError::InvalidFasta {
+                message,
+                position,
+                content,
+            }
+            | PrseqError::InvalidFastq {
+                message,
+                position,
+                content,
+            }
+            | PrseqError::InvalidFormat {
+                message,
+                position,
+                content,
+                ..
+            } => {
+                write!(f, "{} at {}", message, position)?;
+                if !content.is_empty() {
+                    write!(f, ": '{}'", content)?;
+                }
+                Ok(())
+            }
+            PrseqError::LengthMismatch {
+                sequence_length,
+                quality_length,
+                position,
+            } => write!(
+                f,
+                "FASTQ sequence length ({}) does not match quality length ({}) at {}",
+                sequence_length, quality_length, position
+            ),
+            PrseqError::InvalidSequence {
+                id,
+                alphabet,
+                offset,
+                byte,
+                position,
+            } => write!(
+                f,
+                "Invalid {} character '{}' at offset {} of sequence '{}' at {}",
+                alphabet,
+                std::ascii::escape_default(*byte),
+                offset,
+                id,
+                position
+            ),
+            PrseqError::InvalidQuality {
+                id,
+                encoding,
+                offset,
+                byte,
+                position,
+            } => {
+                write!(
+                    f,
+                    "Invalid {} quality character '{}' at offset {} of record '{}'",
+                    encoding,
+                    std::ascii::escape_default(*byte),
+                    offset,
+                    id
+                )?;
+                if let Some(position) = position {
+                    write!(f, " at {}", position)?;
+                }
+                Ok(())
+            }
+            PrseqError::UnexpectedEof { message, position } => {
+                write!(f, "{} at {}", message, position)
+            }
+            PrseqError::InvalidUtf8 {
+                format,
+                position,
+                source,
+            } => {
+                write!(f, "{} record is not valid UTF-8: {}", format, source)?;
+                if let Some(position) = position {
+                    write!(f, " at {}", position)?;
+                }
+                Ok(())
+            }
+            PrseqError::DuplicateId {
+                id,
+                first,
+                position,
+            } => write!(
+                f,
+                "Duplicate ID '{}' at {} (first seen in record {})",
+                id, position, first
+            ),
+            PrseqError::RecordTooLong {
+                format,
+                part,
+                limit,
+                position,
+            } => write!(
+                f,
+                "{} record's {} is longer than the limit of {} bytes at {} \
+                 (is the input malformed?)",
+                format, part, limit, position
+            ),
+            PrseqError::PairMismatch { message, .. } => write!(f, "{}", message),
+            PrseqError::OutOfRange { range, length } => write!(
+                f,
+                "Range {} is out of bounds for a sequence of length {}",
+                range, length
+            ),
+            PrseqError::InFile { path, source } => write!(f, "{}: {}", path.display(), source),
+        }
+    }
+}
+
+/// Errors are serialized (but, as they may hold I/O errors, not
+/// deserialized) as their kind, message, position and path, e.g.
+/// `{"kind":"InvalidData","message":"...","position":{"line":3,"record":2},"path":null}`
+#[cfg(feature = "serde")]
+impl serde::Serialize for PrseqError {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("PrseqError", 4)?;
+        state.serialize_field("kind", &format!("{:?}", self.kind()))?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("position", &self.position())?;
+        state.serialize_field("path", &self.path())?;
+        state.end()
+    }
+}
+
+impl std::error::Error for PrseqError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PrseqError::Io(e) => e.source(),
+            PrseqError::InvalidUtf8 { source, .. } => Some(source),
+            PrseqError::InFile { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PrseqError {
+    fn from(error: io::Error) -> Self {
+        // Unwrap a PrseqError that was passed through an io::Error.
+        if error
+            .get_ref()
+            .is_some_and(|inner| inner.is::<PrseqError>())
+        {
+            let inner = error.into_inner().and_then(|e| e.downcast().ok());
+            return *inner.expect("the io::Error wraps a PrseqError");
+        }
+        PrseqError::Io(error)
+    }
+}
+
+impl From<PrseqError> for io::Error {
+    fn from(error: PrseqError) -> Self {
+        match error {
+            PrseqError::Io(e) => e,
+            other => io::Error::new(other.kind(), other),
+        }
+    }
+}
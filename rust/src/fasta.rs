@@ -1,20 +1,262 @@
-use crate::common::create_reader_with_compression;
+use crate::alphabet::Alphabet;
+use crate::bgzf::{BgzfReader, VirtualOffset};
+use crate::checkpoint::{open_at, Checkpoint};
+use crate::common::{
+    counted_input, create_reader_with_compression, create_reader_with_threads, split_header,
+    split_header_str, utf8_str, utf8_string, LineLimits, LineReader,
+};
+use crate::error::{Position, PrseqError, Result};
+use crate::index::{FaiBuilder, FastaIndex};
+use crate::line_ending::LineEndings;
+use crate::location::{Located, Location, WithLocations};
+use crate::multi::{MultiFastaReader, MultiFileReader};
+use crate::options::{IdTracker, ParserOptions, SoftMask};
+use crate::packed::{PackedRecord, PackedSeq};
+use crate::progress::{Progress, ProgressHook, ReaderStats};
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, Read, Result};
+use std::io::{BufRead, Cursor, Read};
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
 
-/// Represents a single FASTA sequence with its id and sequence data
+/// Represents a single FASTA sequence with its header and sequence data
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FastaRecord {
-    pub id: String,
+    pub header: String,
     pub sequence: String,
 }
 
+impl FastaRecord {
+    /// The ID: the header up to the first whitespace
+    pub fn id(&self) -> &str {
+        split_header_str(&self.header).0
+    }
+
+    /// The description: the rest of the header after the ID and the
+    /// whitespace following it, if there is any
+    pub fn description(&self) -> Option<&str> {
+        split_header_str(&self.header).1
+    }
+
+    /// Display the record as FASTA with its sequence wrapped at
+    /// `line_width` characters (0 means no wrapping)
+    ///
+    /// ```
+    /// use prseq::FastaRecord;
+    ///
+    /// let record = FastaRecord { header: "s1".into(), sequence: "ACGTACGT".into() };
+    /// assert_eq!(record.wrapped(3).to_string(), ">s1\nACG\nTAC\nGT");
+    /// ```
+    pub fn wrapped(&self, line_width: usize) -> WrappedFasta<'_> {
+        WrappedFasta {
+            record: self,
+            line_width,
+        }
+    }
+}
+
+/// Displays a FASTA record with its sequence on one line, without a final
+/// newline (so `writeln!` writes a complete record)
+impl fmt::Display for FastaRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.wrapped(0).fmt(f)
+    }
+}
+
+impl FromStr for FastaRecord {
+    type Err = PrseqError;
+
+    /// Parse a string holding exactly one FASTA record
+    fn from_str(text: &str) -> Result<Self> {
+        let mut reader = FastaReader::from_string(text)?;
+        let Some(record) = reader.next().transpose()? else {
+            return Err(PrseqError::invalid_fasta(
+                "No FASTA record found",
+                Position { line: 1, record: 1 },
+                b"",
+            ));
+        };
+        match reader.next().transpose()? {
+            Some(extra) => Err(PrseqError::invalid_fasta(
+                "Expected a single FASTA record",
+                reader.record_position(),
+                format!(">{}", extra.header).as_bytes(),
+            )),
+            None => Ok(record),
+        }
+    }
+}
+
+/// A FASTA record displayed with its sequence wrapped, as returned by
+/// `FastaRecord::wrapped`
+#[derive(Debug, Clone, Copy)]
+pub struct WrappedFasta<'a> {
+    record: &'a FastaRecord,
+    line_width: usize,
+}
+
+impl fmt::Display for WrappedFasta<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, ">{}", self.record.header)?;
+        let sequence = &self.record.sequence;
+        if self.line_width == 0 || sequence.len() <= self.line_width {
+            if !sequence.is_empty() {
+                write!(f, "\n{}", sequence)?;
+            }
+            return Ok(());
+        }
+        for line in sequence.as_bytes().chunks(self.line_width) {
+            write!(f, "\n{}", String::from_utf8_lossy(line))?;
+        }
+        Ok(())
+    }
+}
+
+/// A FASTA record holding raw bytes, for input that may not be UTF-8
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct FastaRecordBytes {
+    pub header: Vec<u8>,
+    pub sequence: Vec<u8>,
+}
+
+impl FastaRecordBytes {
+    /// The ID: the header up to the first whitespace
+    pub fn id(&self) -> &[u8] {
+        split_header(&self.header).0
+    }
+
+    /// The description: the rest of the header after the ID and the
+    /// whitespace following it, if there is any
+    pub fn description(&self) -> Option<&[u8]> {
+        split_header(&self.header).1
+    }
+
+    /// Convert to a FastaRecord, failing if the header or sequence is not UTF-8
+    pub fn into_record(self) -> Result<FastaRecord> {
+        Ok(FastaRecord {
+            header: utf8_string(self.header, "FASTA", None)?,
+            sequence: utf8_string(self.sequence, "FASTA", None)?,
+        })
+    }
+
+    /// Convert to a FastaRecord, replacing any invalid UTF-8 with U+FFFD
+    pub fn to_record_lossy(&self) -> FastaRecord {
+        FastaRecord {
+            header: String::from_utf8_lossy(&self.header).into_owned(),
+            sequence: String::from_utf8_lossy(&self.sequence).into_owned(),
+        }
+    }
+}
+
+impl From<FastaRecord> for FastaRecordBytes {
+    fn from(record: FastaRecord) -> Self {
+        FastaRecordBytes {
+            header: record.header.into_bytes(),
+            sequence: record.sequence.into_bytes(),
+        }
+    }
+}
+
+impl TryFrom<FastaRecordBytes> for FastaRecord {
+    type Error = PrseqError;
+
+    fn try_from(record: FastaRecordBytes) -> Result<Self> {
+        record.into_record()
+    }
+}
+
+/// A FASTA record borrowed from a reader's internal buffers, as returned by
+/// `FastaReader::next_ref`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefFastaRecord<'a> {
+    header: &'a [u8],
+    sequence: &'a [u8],
+    position: Position,
+}
+
+impl<'a> RefFastaRecord<'a> {
+    /// The header line, without its '>'
+    pub fn header(&self) -> &'a [u8] {
+        self.header
+    }
+
+    /// The ID: the header up to the first whitespace
+    pub fn id(&self) -> &'a [u8] {
+        split_header(self.header).0
+    }
+
+    /// The description: the rest of the header after the ID and the
+    /// whitespace following it, if there is any
+    pub fn description(&self) -> Option<&'a [u8]> {
+        split_header(self.header).1
+    }
+
+    /// The sequence, with the lines of multi-line records joined
+    pub fn sequence(&self) -> &'a [u8] {
+        self.sequence
+    }
+
+    /// Copy this record into an owned byte record
+    pub fn to_owned(&self) -> FastaRecordBytes {
+        FastaRecordBytes {
+            header: self.header.to_vec(),
+            sequence: self.sequence.to_vec(),
+        }
+    }
+
+    /// Copy this record into an owned FastaRecord, failing if it is not UTF-8
+    pub fn to_record(&self) -> Result<FastaRecord> {
+        Ok(FastaRecord {
+            header: utf8_str(self.header, "FASTA", Some(self.position))?.to_string(),
+            sequence: utf8_str(self.sequence, "FASTA", Some(self.position))?.to_string(),
+        })
+    }
+
+    /// Pack this record into a PackedRecord, failing if it is not UTF-8
+    pub fn to_packed(&self) -> Result<PackedRecord> {
+        let sequence = utf8_str(self.sequence, "FASTA", Some(self.position))?;
+        Ok(PackedRecord {
+            header: utf8_str(self.header, "FASTA", Some(self.position))?.to_string(),
+            sequence: PackedSeq::new(sequence.as_bytes()),
+        })
+    }
+}
+
 /// Iterator over FASTA records from any readable source
 pub struct FastaReader {
-    lines: std::io::Lines<std::io::BufReader<Box<dyn Read + Send>>>,
-    next_header: Option<String>,
-    sequence_size_hint: usize,
+    lines: LineReader,
+    header: Vec<u8>,
+    sequence: Vec<u8>,
+    next_header: Vec<u8>,
+    has_next_header: bool,
+    /// The position of a next header that was too long to read
+    next_header_too_long: Option<Position>,
+    next_virtual_offset: Option<VirtualOffset>,
+    record_virtual_offset: Option<VirtualOffset>,
+    next_offset: u64,
+    record_offset: Option<u64>,
+    /// The number of records read, and the lines their headers are on
+    records: u64,
+    record_line: u64,
+    next_line: u64,
+    options: ParserOptions,
+    /// The number of malformed records skipped
+    skipped: u64,
+    /// Whether lines are being skipped to find the next header, after a
+    /// malformed record
+    resyncing: bool,
+    ids: IdTracker,
+    indexer: Option<FaiBuilder>,
+    /// The number of records returned, and their total length
+    returned: u64,
+    bases: u64,
+    progress: Option<ProgressHook>,
+    /// The file being read, if the reader was made from a path
+    path: Option<Arc<Path>>,
+    /// A record read by `peek` but not yet returned
+    peeked: Option<Result<FastaRecord>>,
 }
 
 impl FastaReader {
@@ -32,8 +274,23 @@ impl FastaReader {
         path: P,
         sequence_size_hint: usize,
     ) -> Result<Self> {
-        let file = File::open(path)?;
-        Self::from_reader_with_capacity(file, sequence_size_hint)
+        let file = File::open(path.as_ref())?;
+        Ok(Self::from_reader_with_capacity(file, sequence_size_hint)?.with_path(path.as_ref()))
+    }
+
+    /// Create a reader that reads several files in turn, each with its own
+    /// compression detection
+    pub fn from_paths<P: AsRef<Path>>(paths: &[P]) -> MultiFastaReader {
+        MultiFileReader::new(
+            paths.iter().map(|path| path.as_ref().to_path_buf()),
+            |path| Self::from_file(path),
+        )
+    }
+
+    /// Create a reader that reads the files matching a glob pattern (such
+    /// as `reads/*.fastq.gz`) in turn, in alphabetical order
+    pub fn from_glob(pattern: &str) -> Result<MultiFastaReader> {
+        MultiFileReader::from_glob(pattern, |path| Self::from_file(path))
     }
 
     /// Create a new FastaReader from stdin
@@ -47,69 +304,643 @@ impl FastaReader {
         Self::from_reader_with_capacity(stdin, sequence_size_hint)
     }
 
+    /// Create a new FastaReader over data in memory, with compression detection.
+    /// The data is copied; use `from_vec` to hand over a buffer instead.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Self::from_vec(data.to_vec())
+    }
+
+    /// Create a new FastaReader over a string
+    pub fn from_string(text: &str) -> Result<Self> {
+        Self::from_bytes(text.as_bytes())
+    }
+
+    /// Create a new FastaReader that takes ownership of data in memory, with
+    /// compression detection
+    pub fn from_vec(data: Vec<u8>) -> Result<Self> {
+        Self::from_reader_with_capacity(Cursor::new(data), 64 * 1024)
+    }
+
     /// Create a new FastaReader from any readable source with compression detection
     pub fn from_reader_with_capacity<R: Read + Send + 'static>(
         reader: R,
         sequence_size_hint: usize,
     ) -> Result<Self> {
-        let buf_reader = create_reader_with_compression(reader)?;
-        let lines = buf_reader.lines();
+        let input = counted_input(reader, create_reader_with_compression)?;
+        Ok(Self::from_line_reader(
+            LineReader::new(input),
+            sequence_size_hint,
+        ))
+    }
+
+    /// Create a new FastaReader that streams from an HTTP(S) URL, with
+    /// compression detection
+    #[cfg(feature = "remote")]
+    pub fn from_url(url: &str) -> Result<Self> {
+        Self::from_reader_with_capacity(crate::remote::open_url(url)?, 64 * 1024)
+    }
 
-        Ok(FastaReader {
+    /// Create a new FastaReader that streams an object from a cloud object
+    /// store, such as `s3://bucket/key`, with compression detection
+    ///
+    /// See `object::open_object` for the supported URLs and credentials.
+    #[cfg(feature = "object_store")]
+    pub fn from_object_store(url: &str) -> Result<Self> {
+        Self::from_reader_with_capacity(crate::object::open_object(url)?, 64 * 1024)
+    }
+
+    /// Create a new FastaReader from a file path, decompressing with
+    /// `threads` threads
+    ///
+    /// BGZF files are decompressed in parallel, block by block. Other
+    /// compressed files are decompressed on one background thread.
+    pub fn from_file_with_threads<P: AsRef<Path>>(path: P, threads: usize) -> Result<Self> {
+        let file = File::open(path.as_ref())?;
+        Ok(Self::from_reader_with_threads(file, 64 * 1024, threads)?.with_path(path.as_ref()))
+    }
+
+    /// Create a new FastaReader from any readable source with compression
+    /// detection, decompressing with `threads` threads
+    pub fn from_reader_with_threads<R: Read + Send + 'static>(
+        reader: R,
+        sequence_size_hint: usize,
+        threads: usize,
+    ) -> Result<Self> {
+        let input = counted_input(reader, |reader| create_reader_with_threads(reader, threads))?;
+        Ok(Self::from_line_reader(
+            LineReader::new(input),
+            sequence_size_hint,
+        ))
+    }
+
+    /// Create a new FastaReader from a BGZF-compressed file path
+    ///
+    /// Records read this way report their BGZF virtual offset via
+    /// `virtual_offset()`.
+    pub fn from_bgzf_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let input = counted_input(File::open(path.as_ref())?, |file| Ok(BgzfReader::new(file)))?;
+        Ok(Self::from_line_reader(LineReader::new(input), 64 * 1024).with_path(path.as_ref()))
+    }
+
+    /// Create a new FastaReader from a BGZF reader, which may already have
+    /// been positioned with `BgzfReader::seek_virtual`
+    pub fn from_bgzf<R: Read + Send + 'static>(
+        reader: BgzfReader<R>,
+        sequence_size_hint: usize,
+    ) -> Self {
+        Self::from_line_reader(LineReader::new(Box::new(reader)), sequence_size_hint)
+    }
+
+    /// Create a new FastaReader that continues reading a file from a checkpoint
+    /// taken while reading it before (see `checkpoint`), without reparsing
+    /// what came before
+    ///
+    /// Uncompressed files, and BGZF files read with `from_bgzf_file` (so
+    /// that checkpoints have a virtual offset), are seeked straight to the
+    /// checkpoint. Other compressed files are decompressed up to it, which
+    /// is quicker than parsing but not instant.
+    ///
+    /// Record numbers in errors and `stats()` carry on from the checkpoint.
+    /// The parser options are not saved, so set them again; duplicate IDs
+    /// are only detected among the records read after resuming.
+    pub fn resume_from<P: AsRef<Path>>(path: P, checkpoint: &Checkpoint) -> Result<Self> {
+        let path = path.as_ref();
+        let mut reader =
+            Self::from_line_reader(open_at(path, checkpoint)?, 64 * 1024).with_path(path);
+        reader.records = checkpoint.records;
+        reader.returned = checkpoint.returned;
+        reader.bases = checkpoint.bases;
+        reader.skipped = checkpoint.skipped;
+        Ok(reader)
+    }
+
+    /// Create a new FastaReader for the sequences in the `##FASTA` section
+    /// that ends many GFF3 files, skipping the annotation before it. A file
+    /// with no `##FASTA` section has no records. Errors report line numbers
+    /// in the whole file.
+    pub fn from_gff3<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::from_gff3_reader(File::open(path.as_ref())?)?.with_path(path.as_ref()))
+    }
+
+    /// Create a new FastaReader for the `##FASTA` section of GFF3 from any
+    /// readable source with compression detection (see `from_gff3`)
+    pub fn from_gff3_reader<R: Read + Send + 'static>(reader: R) -> Result<Self> {
+        let mut input = counted_input(reader, create_reader_with_compression)?;
+        let mut line = Vec::new();
+        let (mut offset, mut lines) = (0, 0);
+        loop {
+            line.clear();
+            let read = input.read_until(b'\n', &mut line)?;
+            if read == 0 {
+                break;
+            }
+            offset += read as u64;
+            lines += 1;
+            if line.trim_ascii_end() == b"##FASTA" {
+                break;
+            }
+        }
+        Ok(Self::from_line_reader(
+            LineReader::with_offset(input, offset, lines),
+            64 * 1024,
+        ))
+    }
+
+    pub(crate) fn from_line_reader(lines: LineReader, sequence_size_hint: usize) -> Self {
+        FastaReader {
             lines,
-            next_header: None,
-            sequence_size_hint: sequence_size_hint.max(64),
-        })
+            header: Vec::new(),
+            sequence: Vec::with_capacity(sequence_size_hint.max(64)),
+            next_header: Vec::new(),
+            has_next_header: false,
+            next_header_too_long: None,
+            next_virtual_offset: None,
+            record_virtual_offset: None,
+            next_offset: 0,
+            record_offset: None,
+            records: 0,
+            record_line: 0,
+            next_line: 0,
+            options: ParserOptions::default(),
+            skipped: 0,
+            resyncing: false,
+            ids: IdTracker::default(),
+            indexer: None,
+            returned: 0,
+            bases: 0,
+            progress: None,
+            peeked: None,
+            path: None,
+        }
+    }
+
+    /// Record the path of the file being read, for `location()`
+    pub(crate) fn with_path(mut self, path: &Path) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// The file being read, if the reader was made from a path
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Turn this reader into an iterator that pairs each record with its
+    /// `Location`: the file, line and index it was read from
+    pub fn with_locations(self) -> WithLocations<Self> {
+        WithLocations::new(self)
     }
 
-    fn read_next(&mut self) -> Result<Option<FastaRecord>> {
-        let header = if let Some(h) = self.next_header.take() {
-            h
+    /// The BGZF virtual offset of the start of the most recently returned
+    /// record, or None if the input is not being read with a BGZF reader
+    pub fn virtual_offset(&self) -> Option<VirtualOffset> {
+        self.record_virtual_offset
+    }
+
+    /// The byte offset, in the uncompressed input, of the start of the most
+    /// recently returned record, or None if no record has been returned
+    pub fn offset(&self) -> Option<u64> {
+        self.record_offset
+    }
+
+    /// The byte offset, in the uncompressed input, at which the next record
+    /// (if any) starts
+    ///
+    /// Everything before this offset has been returned as records, so it
+    /// can be used to resume reading after an interruption.
+    pub fn tell(&self) -> u64 {
+        if self.peeked.is_some() {
+            self.record_offset.unwrap_or(0)
+        } else if self.has_next_header {
+            self.next_offset
+        } else {
+            self.lines.offset()
+        }
+    }
+
+    /// Save the reader's position, before the next record, so that
+    /// reading can be resumed from there with `resume_from` (e.g. after the
+    /// job is preempted)
+    ///
+    /// A peeked record has not been returned, so it is read again after
+    /// resuming.
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        let (offset, virtual_offset, line) = if self.peeked.is_some() {
+            (
+                self.record_offset.unwrap_or(0),
+                self.record_virtual_offset,
+                self.record_line.saturating_sub(1),
+            )
+        } else if self.has_next_header {
+            (
+                self.next_offset,
+                self.next_virtual_offset,
+                self.next_line.saturating_sub(1),
+            )
+        } else {
+            (
+                self.lines.offset(),
+                self.lines.virtual_offset(),
+                self.lines.line(),
+            )
+        };
+        let (mut records, mut returned, mut bases) = (self.records, self.returned, self.bases);
+        match &self.peeked {
+            Some(Ok(record)) => {
+                records -= 1;
+                returned -= 1;
+                bases -= record.sequence.len() as u64;
+            }
+            Some(Err(_)) => records = records.saturating_sub(1),
+            None => {}
+        }
+        Checkpoint {
+            offset,
+            virtual_offset: virtual_offset.map(u64::from),
+            records,
+            line,
+            returned,
+            bases,
+            skipped: self.skipped,
+        }
+    }
+
+    /// Set the options used to parse the input
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The options used to parse the input
+    pub fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
+    /// Check that every sequence contains only characters from `alphabet`
+    /// (see `ParserOptions::validate`)
+    pub fn validate(mut self, alphabet: Alphabet) -> Self {
+        self.options.alphabet = Some(alphabet);
+        self
+    }
+
+    /// Unmask or hard-mask soft-masked (lower case) bases as they are read
+    /// (see `ParserOptions::soft_mask`)
+    pub fn soft_mask(mut self, soft_mask: SoftMask) -> Self {
+        self.options.soft_mask = soft_mask;
+        self
+    }
+
+    /// The number of malformed records skipped so far, when the options'
+    /// `on_error` is `Skip` or `Warn`
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// How far through the input the reader has got
+    pub fn progress(&self) -> Progress {
+        Progress {
+            records: self.records,
+            bytes: self.lines.offset(),
+            input_bytes: self.lines.input_bytes(),
+            finished: false,
+        }
+    }
+
+    /// Cumulative counts of the records, bases and bytes read so far
+    pub fn stats(&self) -> ReaderStats {
+        ReaderStats {
+            records: self.returned,
+            bases: self.bases,
+            skipped: self.skipped,
+            duplicates: self.ids.duplicates(),
+            bytes: self.lines.offset(),
+            input_bytes: self.lines.input_bytes(),
+        }
+    }
+
+    /// The number of lines read so far with each style of line terminator,
+    /// e.g. to write output in the same style
+    pub fn line_endings(&self) -> LineEndings {
+        self.lines.line_endings()
+    }
+
+    /// Call `callback` with the reader's progress after every `every`
+    /// records, and once more (with `finished` set) at the end of the
+    /// input, e.g. to drive a progress bar
+    pub fn on_progress<F>(mut self, every: u64, callback: F) -> Self
+    where
+        F: FnMut(&Progress) + Send + 'static,
+    {
+        self.progress = Some(ProgressHook::new(every, callback));
+        self
+    }
+
+    /// Build a samtools-compatible FASTA index of the records as they are
+    /// read. Call this before reading any records, and `take_index` once
+    /// they have all been read.
+    pub fn with_index(mut self) -> Self {
+        self.indexer = Some(FaiBuilder::default());
+        self
+    }
+
+    /// Finish and return the index built since `with_index` was called, or
+    /// None if indexing was not requested
+    ///
+    /// An error is returned if the records read do not have the regular line
+    /// lengths an index requires, or if a sequence name is repeated.
+    pub fn take_index(&mut self) -> Option<Result<FastaIndex>> {
+        self.indexer.take().map(FaiBuilder::finish)
+    }
+
+    /// Read the next record (or error) without consuming it: the next
+    /// call to `next`, `next_ref`, `next_bytes` or `read_into` returns it.
+    /// None at end of input.
+    ///
+    /// After a peek, `offset` and `virtual_offset` describe the peeked
+    /// record, and `tell` gives its start, as it has not been returned.
+    pub fn peek(&mut self) -> Option<&Result<FastaRecord>> {
+        if self.peeked.is_none() {
+            self.peeked = self.next();
+        }
+        self.peeked.as_ref()
+    }
+
+    /// Read the next record into the reader's internal buffers and return
+    /// it borrowed from them, avoiding any per-record allocation
+    ///
+    /// The record is only valid until the next call; use `to_owned()` or
+    /// `to_record()` to keep it.
+    pub fn next_ref(&mut self) -> Option<Result<RefFastaRecord<'_>>> {
+        match self.peeked.take() {
+            Some(Ok(record)) => {
+                self.header.clear();
+                self.header.extend_from_slice(record.header.as_bytes());
+                self.sequence.clear();
+                self.sequence.extend_from_slice(record.sequence.as_bytes());
+            }
+            Some(Err(e)) => return Some(Err(e)),
+            None => match self.read_record() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(e) => return Some(Err(e)),
+            },
+        }
+        Some(Ok(RefFastaRecord {
+            header: &self.header,
+            sequence: &self.sequence,
+            position: self.record_position(),
+        }))
+    }
+
+    /// Read up to `n` records, returning fewer only at end of input (and an
+    /// empty Vec once the input is exhausted)
+    pub fn next_chunk(&mut self, n: usize) -> Result<Vec<FastaRecord>> {
+        let mut records = Vec::with_capacity(n);
+        while records.len() < n {
+            match self.next() {
+                Some(record) => records.push(record?),
+                None => break,
+            }
+        }
+        Ok(records)
+    }
+
+    /// Read the next record into `record`, reusing its strings' allocations,
+    /// and return false (leaving `record` unchanged) at end of input
+    pub fn read_into(&mut self, record: &mut FastaRecord) -> Result<bool> {
+        if let Some(peeked) = self.peeked.take() {
+            *record = peeked?;
+            return Ok(true);
+        }
+        loop {
+            if !self.read_record()? {
+                return Ok(false);
+            }
+            let position = Some(self.record_position());
+            let fields = utf8_str(&self.header, "FASTA", position)
+                .and_then(|id| Ok((id, utf8_str(&self.sequence, "FASTA", position)?)));
+            let error = match fields {
+                Ok((id, sequence)) => {
+                    record.header.clear();
+                    record.header.push_str(id);
+                    record.sequence.clear();
+                    record.sequence.push_str(sequence);
+                    return Ok(true);
+                }
+                Err(e) => e,
+            };
+            self.unreturn();
+            if !self.skip_error(&error) {
+                return Err(error.in_file(self.path.as_deref()));
+            }
+        }
+    }
+
+    /// Read the next record as raw bytes, without checking that it is
+    /// valid UTF-8
+    pub fn next_bytes(&mut self) -> Option<Result<FastaRecordBytes>> {
+        self.next_ref().map(|r| r.map(|record| record.to_owned()))
+    }
+
+    /// Turn this reader into an iterator over byte records
+    pub fn byte_records(self) -> FastaByteRecords {
+        FastaByteRecords { reader: self }
+    }
+
+    /// The limits on line length, so that an over-long line fails while it
+    /// is being read
+    fn line_limits(&self) -> LineLimits {
+        LineLimits {
+            format: "FASTA",
+            markers: b">",
+            header: self.options.max_header_length,
+            sequence: self.options.max_sequence_length,
+        }
+    }
+
+    /// The position of the most recently read record, for errors
+    fn record_position(&self) -> Position {
+        Position {
+            line: self.record_line,
+            record: self.records,
+        }
+    }
+
+    /// Take back the count of the record just read, which could not be
+    /// returned after all (it is not UTF-8)
+    fn unreturn(&mut self) {
+        self.returned -= 1;
+        self.bases -= self.sequence.len() as u64;
+    }
+
+    /// Count a malformed record as skipped if the options allow, returning
+    /// whether it was
+    fn skip_error(&mut self, error: &PrseqError) -> bool {
+        if !self.options.skips(error) {
+            return false;
+        }
+        self.skipped += 1;
+        self.resyncing = true;
+        true
+    }
+
+    /// Read the next record into `header` and `sequence`, skipping malformed
+    /// records if the options allow, and returning false at end of input
+    fn read_record(&mut self) -> Result<bool> {
+        let result = loop {
+            match self.parse_record() {
+                Err(e) if self.skip_error(&e) => continue,
+                Ok(true) if self.options.skips_empty(&self.sequence) => continue,
+                result => break result,
+            }
+        };
+        if let Ok(true) = result {
+            self.returned += 1;
+            self.bases += self.sequence.len() as u64;
+        }
+        if let Some(mut hook) = self.progress.take() {
+            hook.report(|| self.progress(), matches!(result, Ok(false)));
+            self.progress = Some(hook);
+        }
+        result.map_err(|e| e.in_file(self.path.as_deref()))
+    }
+
+    /// Parse the next record into `header` and `sequence`, returning false
+    /// at end of input
+    fn parse_record(&mut self) -> Result<bool> {
+        let limits = self.line_limits();
+        if let Some(position) = self.next_header_too_long.take() {
+            self.records += 1;
+            self.record_line = position.line;
+            return Err(PrseqError::RecordTooLong {
+                format: "FASTA",
+                part: "header",
+                limit: limits.header.unwrap_or_default(),
+                position,
+            });
+        }
+        if self.has_next_header {
+            self.has_next_header = false;
+            std::mem::swap(&mut self.header, &mut self.next_header);
+            self.record_virtual_offset = self.next_virtual_offset;
+            self.record_offset = Some(self.next_offset);
+            self.record_line = self.next_line;
         } else {
             loop {
-                match self.lines.next() {
-                    Some(Ok(line)) => {
-                        if line.is_empty() || line.chars().all(|c| c.is_whitespace()) {
-                            continue;
-                        }
-                        let trimmed = line.trim();
-                        if !trimmed.starts_with('>') {
-                            return Err(std::io::Error::new(
-                                std::io::ErrorKind::InvalidData,
-                                "FASTA record must start with '>'",
-                            ));
+                let offset = self.lines.virtual_offset();
+                let byte_offset = self.lines.offset();
+                let position = Position {
+                    line: self.lines.line() + 1,
+                    record: self.records + 1,
+                };
+                let Some((line, line_end)) =
+                    self.lines.next_line_within(&limits, position.record)?
+                else {
+                    return Ok(false);
+                };
+                let trimmed = line.trim_ascii();
+                if trimmed.is_empty() || self.options.is_comment(trimmed) {
+                    continue;
+                }
+                match trimmed.strip_prefix(b">") {
+                    Some(header) => {
+                        self.record_virtual_offset = offset;
+                        self.record_offset = Some(byte_offset);
+                        self.record_line = position.line;
+                        self.resyncing = false;
+                        if let Some(indexer) = &mut self.indexer {
+                            indexer.start_record(header, line_end);
                         }
-                        break trimmed[1..].to_string();
+                        self.header.clear();
+                        self.header.extend_from_slice(header);
+                        break;
+                    }
+                    // After a malformed record, look for the next header.
+                    None if self.resyncing => continue,
+                    None => {
+                        return Err(PrseqError::invalid_fasta(
+                            "FASTA record must start with '>'",
+                            position,
+                            trimmed,
+                        ));
                     }
-                    Some(Err(e)) => return Err(e),
-                    None => return Ok(None),
                 }
             }
-        };
+        }
+        self.records += 1;
+        self.options
+            .check_header_length("FASTA", &self.header, self.record_position())?;
 
-        let mut sequence = String::with_capacity(self.sequence_size_hint);
+        self.sequence.clear();
         loop {
-            match self.lines.next() {
-                Some(Ok(line)) => {
-                    if line.is_empty() || line.chars().all(|c| c.is_whitespace()) {
-                        continue;
-                    }
-                    let trimmed = line.trim();
-                    if trimmed.starts_with('>') {
-                        self.next_header = Some(trimmed[1..].to_string());
+            let offset = self.lines.virtual_offset();
+            let byte_offset = self.lines.offset();
+            let next = match self.lines.next_line_within(&limits, self.records) {
+                // A header that is too long belongs to the next record, so
+                // it is that record that fails.
+                Err(error) => match PrseqError::from(error) {
+                    PrseqError::RecordTooLong {
+                        part: "header",
+                        position,
+                        ..
+                    } => {
+                        self.next_header_too_long = Some(Position {
+                            record: position.record + 1,
+                            ..position
+                        });
                         break;
                     }
-                    sequence.push_str(trimmed);
+                    error => return Err(error),
+                },
+                Ok(next) => next,
+            };
+            let Some((line, line_end)) = next else {
+                break;
+            };
+            let trimmed = line.trim_ascii();
+            if let Some(header) = trimmed.strip_prefix(b">") {
+                if let Some(indexer) = &mut self.indexer {
+                    indexer.start_record(header, line_end);
                 }
-                Some(Err(e)) => return Err(e),
-                None => break,
+                self.next_header.clear();
+                self.next_header.extend_from_slice(header);
+                self.has_next_header = true;
+                self.next_virtual_offset = offset;
+                self.next_offset = byte_offset;
+                self.next_line = self.lines.line();
+                break;
+            }
+            if self.options.is_comment(trimmed) {
+                continue;
             }
+            if let Some(indexer) = &mut self.indexer {
+                indexer.add_line(trimmed.len() as u64, line_end - byte_offset);
+            }
+            self.sequence.extend_from_slice(trimmed);
+            self.options.check_sequence_length(
+                "FASTA",
+                self.sequence.len(),
+                self.record_position(),
+            )?;
         }
-
-        Ok(Some(FastaRecord {
-            id: header,
-            sequence,
-        }))
+        if self.options.strip_trailing_stop && self.sequence.last() == Some(&b'*') {
+            self.sequence.pop();
+        }
+        self.options.apply_soft_mask(&mut self.sequence);
+        self.options.check_empty(
+            "FASTA",
+            &self.header,
+            &self.sequence,
+            self.record_position(),
+        )?;
+        if self.options.skips_empty(&self.sequence) {
+            return Ok(true);
+        }
+        self.options
+            .check_sequence(&self.header, &self.sequence, self.record_position())?;
+        self.ids
+            .check(&self.options, &self.header, self.record_position())?;
+        Ok(true)
     }
 }
 
@@ -117,14 +948,45 @@ impl Iterator for FastaReader {
     type Item = Result<FastaRecord>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.read_next() {
-            Ok(Some(record)) => Some(Ok(record)),
-            Ok(None) => None,
-            Err(e) => Some(Err(e)),
+        if let Some(peeked) = self.peeked.take() {
+            return Some(peeked);
+        }
+        loop {
+            let result = match self.next_ref()? {
+                Ok(record) => record.to_record(),
+                Err(e) => return Some(Err(e)),
+            };
+            if result.is_err() {
+                self.unreturn();
+            }
+            match result {
+                Err(e) if self.skip_error(&e) => continue,
+                result => return Some(result.map_err(|e| e.in_file(self.path.as_deref()))),
+            }
         }
     }
 }
 
+/// Iterator over FASTA records as raw bytes, from `FastaReader::byte_records`
+pub struct FastaByteRecords {
+    reader: FastaReader,
+}
+
+impl FastaByteRecords {
+    /// The underlying reader, e.g. to query record offsets
+    pub fn reader(&self) -> &FastaReader {
+        &self.reader
+    }
+}
+
+impl Iterator for FastaByteRecords {
+    type Item = Result<FastaRecordBytes>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.next_bytes()
+    }
+}
+
 pub fn read_fasta<P: AsRef<Path>>(path: P) -> Result<Vec<FastaRecord>> {
     read_fasta_with_capacity(path, 64 * 1024)
 }
@@ -136,3 +998,13 @@ pub fn read_fasta_with_capacity<P: AsRef<Path>>(
     let reader = FastaReader::from_file_with_capacity(path, sequence_size_hint)?;
     reader.collect()
 }
+
+impl Located for FastaReader {
+    fn location(&self) -> Location {
+        Location {
+            file: self.path.clone(),
+            line_number: self.record_line,
+            record_index: self.records.saturating_sub(1),
+        }
+    }
+}
@@ -1,20 +1,270 @@
-use crate::common::create_reader_with_compression;
+use crate::alphabet::Alphabet;
+use crate::bgzf::{BgzfReader, VirtualOffset};
+use crate::checkpoint::{open_at, Checkpoint};
+use crate::common::{
+    counted_input, create_reader_with_compression, create_reader_with_threads, split_header,
+    split_header_str, utf8_str, utf8_string, LineLimits, LineReader,
+};
+use crate::error::{Position, PrseqError, Result};
+use crate::line_ending::LineEndings;
+use crate::location::{Located, Location, WithLocations};
+use crate::multi::{MultiFastqReader, MultiFileReader};
+use crate::options::{IdTracker, OnError, ParserOptions};
+use crate::progress::{Progress, ProgressHook, ReaderStats};
+use crate::quality::{self, QualityEncoding};
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead, Read, Result};
+use std::io::{Cursor, Read};
 use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
 
 /// Represents a single FASTQ sequence record
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FastqRecord {
-    pub id: String,
+    pub header: String,
     pub sequence: String,
     pub quality: String,
 }
 
+impl FastqRecord {
+    /// The ID: the header up to the first whitespace
+    pub fn id(&self) -> &str {
+        split_header_str(&self.header).0
+    }
+
+    /// The description: the rest of the header after the ID and the
+    /// whitespace following it, if there is any
+    pub fn description(&self) -> Option<&str> {
+        split_header_str(&self.header).1
+    }
+
+    /// The Phred+33 quality scores of the bases
+    pub fn quality_scores(&self) -> Vec<u8> {
+        quality::phred_scores(self.quality.as_bytes()).collect()
+    }
+
+    /// The mean quality score, or None for an empty record
+    pub fn mean_quality(&self) -> Option<f64> {
+        quality::mean_quality(self.quality.as_bytes())
+    }
+
+    /// The expected number of errors in the read, from its quality scores
+    pub fn expected_errors(&self) -> f64 {
+        quality::expected_errors(self.quality.as_bytes())
+    }
+
+    /// Convert the quality from Phred+64 to Phred+33
+    pub fn to_phred33(&self) -> Result<FastqRecord> {
+        self.convert_quality(QualityEncoding::Phred64, QualityEncoding::Phred33)
+    }
+
+    /// Convert the quality from Phred+33 to Phred+64
+    pub fn to_phred64(&self) -> Result<FastqRecord> {
+        self.convert_quality(QualityEncoding::Phred33, QualityEncoding::Phred64)
+    }
+
+    fn convert_quality(&self, from: QualityEncoding, to: QualityEncoding) -> Result<FastqRecord> {
+        let mut quality = self.quality.clone().into_bytes();
+        from.convert(to, &mut quality)
+            .map_err(|offset| PrseqError::InvalidQuality {
+                id: self.id().to_string(),
+                encoding: from,
+                offset,
+                byte: quality[offset],
+                position: None,
+            })?;
+        Ok(FastqRecord {
+            header: self.header.clone(),
+            sequence: self.sequence.clone(),
+            quality: String::from_utf8(quality).expect("converted quality is ASCII"),
+        })
+    }
+}
+
+/// Displays a FASTQ record as four lines, without a final newline (so
+/// `writeln!` writes a complete record)
+///
+/// ```
+/// use prseq::FastqRecord;
+///
+/// let record: FastqRecord = "@r1\nACGT\n+\nIIII\n".parse().unwrap();
+/// assert_eq!(record.to_string(), "@r1\nACGT\n+\nIIII");
+/// ```
+impl fmt::Display for FastqRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "@{}\n{}\n+\n{}",
+            self.header, self.sequence, self.quality
+        )
+    }
+}
+
+impl FromStr for FastqRecord {
+    type Err = PrseqError;
+
+    /// Parse a string holding exactly one FASTQ record
+    fn from_str(text: &str) -> Result<Self> {
+        let mut reader = FastqReader::from_string(text)?;
+        let Some(record) = reader.next().transpose()? else {
+            return Err(PrseqError::invalid_fastq(
+                "No FASTQ record found",
+                Position { line: 1, record: 1 },
+                b"",
+            ));
+        };
+        match reader.next().transpose()? {
+            Some(extra) => Err(PrseqError::invalid_fastq(
+                "Expected a single FASTQ record",
+                reader.record_position(),
+                format!("@{}", extra.header).as_bytes(),
+            )),
+            None => Ok(record),
+        }
+    }
+}
+
+/// A FASTQ record holding raw bytes, for input that may not be UTF-8
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct FastqRecordBytes {
+    pub header: Vec<u8>,
+    pub sequence: Vec<u8>,
+    pub quality: Vec<u8>,
+}
+
+impl FastqRecordBytes {
+    /// The ID: the header up to the first whitespace
+    pub fn id(&self) -> &[u8] {
+        split_header(&self.header).0
+    }
+
+    /// The description: the rest of the header after the ID and the
+    /// whitespace following it, if there is any
+    pub fn description(&self) -> Option<&[u8]> {
+        split_header(&self.header).1
+    }
+
+    /// Convert to a FastqRecord, failing if any field is not UTF-8
+    pub fn into_record(self) -> Result<FastqRecord> {
+        Ok(FastqRecord {
+            header: utf8_string(self.header, "FASTQ", None)?,
+            sequence: utf8_string(self.sequence, "FASTQ", None)?,
+            quality: utf8_string(self.quality, "FASTQ", None)?,
+        })
+    }
+
+    /// Convert to a FastqRecord, replacing any invalid UTF-8 with U+FFFD
+    pub fn to_record_lossy(&self) -> FastqRecord {
+        FastqRecord {
+            header: String::from_utf8_lossy(&self.header).into_owned(),
+            sequence: String::from_utf8_lossy(&self.sequence).into_owned(),
+            quality: String::from_utf8_lossy(&self.quality).into_owned(),
+        }
+    }
+}
+
+impl From<FastqRecord> for FastqRecordBytes {
+    fn from(record: FastqRecord) -> Self {
+        FastqRecordBytes {
+            header: record.header.into_bytes(),
+            sequence: record.sequence.into_bytes(),
+            quality: record.quality.into_bytes(),
+        }
+    }
+}
+
+impl TryFrom<FastqRecordBytes> for FastqRecord {
+    type Error = PrseqError;
+
+    fn try_from(record: FastqRecordBytes) -> Result<Self> {
+        record.into_record()
+    }
+}
+
+/// A FASTQ record borrowed from a reader's internal buffers, as returned by
+/// `FastqReader::next_ref`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefFastqRecord<'a> {
+    header: &'a [u8],
+    sequence: &'a [u8],
+    quality: &'a [u8],
+    position: Position,
+}
+
+impl<'a> RefFastqRecord<'a> {
+    /// The header line, without its '@'
+    pub fn header(&self) -> &'a [u8] {
+        self.header
+    }
+
+    /// The ID: the header up to the first whitespace
+    pub fn id(&self) -> &'a [u8] {
+        split_header(self.header).0
+    }
+
+    /// The description: the rest of the header after the ID and the
+    /// whitespace following it, if there is any
+    pub fn description(&self) -> Option<&'a [u8]> {
+        split_header(self.header).1
+    }
+
+    /// The sequence
+    pub fn sequence(&self) -> &'a [u8] {
+        self.sequence
+    }
+
+    /// The quality string
+    pub fn quality(&self) -> &'a [u8] {
+        self.quality
+    }
+
+    /// Copy this record into an owned byte record
+    pub fn to_owned(&self) -> FastqRecordBytes {
+        FastqRecordBytes {
+            header: self.header.to_vec(),
+            sequence: self.sequence.to_vec(),
+            quality: self.quality.to_vec(),
+        }
+    }
+
+    /// Copy this record into an owned FastqRecord, failing if it is not UTF-8
+    pub fn to_record(&self) -> Result<FastqRecord> {
+        Ok(FastqRecord {
+            header: utf8_str(self.header, "FASTQ", Some(self.position))?.to_string(),
+            sequence: utf8_str(self.sequence, "FASTQ", Some(self.position))?.to_string(),
+            quality: utf8_str(self.quality, "FASTQ", Some(self.position))?.to_string(),
+        })
+    }
+}
+
 /// Iterator over FASTQ records from any readable source
 pub struct FastqReader {
-    lines: std::io::Lines<std::io::BufReader<Box<dyn Read + Send>>>,
-    sequence_size_hint: usize,
+    lines: LineReader,
+    header: Vec<u8>,
+    sequence: Vec<u8>,
+    quality: Vec<u8>,
+    record_virtual_offset: Option<VirtualOffset>,
+    record_offset: Option<u64>,
+    /// The number of records read, and the line the last header is on
+    records: u64,
+    record_line: u64,
+    options: ParserOptions,
+    /// The number of malformed records skipped
+    skipped: u64,
+    /// Whether lines are being skipped to find the next header, after a
+    /// malformed record
+    resyncing: bool,
+    ids: IdTracker,
+    /// The number of records returned, and their total length
+    returned: u64,
+    bases: u64,
+    progress: Option<ProgressHook>,
+    /// The file being read, if the reader was made from a path
+    path: Option<Arc<Path>>,
+    /// A record read by `peek` but not yet returned
+    peeked: Option<Result<FastqRecord>>,
 }
 
 impl FastqReader {
@@ -32,8 +282,29 @@ impl FastqReader {
         path: P,
         sequence_size_hint: usize,
     ) -> Result<Self> {
-        let file = File::open(path)?;
-        Self::from_reader_with_capacity(file, sequence_size_hint)
+        let file = File::open(path.as_ref())?;
+        Ok(Self::from_reader_with_capacity(file, sequence_size_hint)?.with_path(path.as_ref()))
+    }
+
+    /// Create a new FastqReader from a file path that parses strictly as
+    /// four-line records (see `four_line`)
+    pub fn four_line_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::from_file(path)?.four_line())
+    }
+
+    /// Create a reader that reads several files in turn, each with its own
+    /// compression detection
+    pub fn from_paths<P: AsRef<Path>>(paths: &[P]) -> MultiFastqReader {
+        MultiFileReader::new(
+            paths.iter().map(|path| path.as_ref().to_path_buf()),
+            |path| Self::from_file(path),
+        )
+    }
+
+    /// Create a reader that reads the files matching a glob pattern (such
+    /// as `reads/*.fastq.gz`) in turn, in alphabetical order
+    pub fn from_glob(pattern: &str) -> Result<MultiFastqReader> {
+        MultiFileReader::from_glob(pattern, |path| Self::from_file(path))
     }
 
     /// Create a new FastqReader from stdin
@@ -47,125 +318,680 @@ impl FastqReader {
         Self::from_reader_with_capacity(stdin, sequence_size_hint)
     }
 
+    /// Create a new FastqReader over data in memory, with compression detection.
+    /// The data is copied; use `from_vec` to hand over a buffer instead.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        Self::from_vec(data.to_vec())
+    }
+
+    /// Create a new FastqReader over a string
+    pub fn from_string(text: &str) -> Result<Self> {
+        Self::from_bytes(text.as_bytes())
+    }
+
+    /// Create a new FastqReader that takes ownership of data in memory, with
+    /// compression detection
+    pub fn from_vec(data: Vec<u8>) -> Result<Self> {
+        Self::from_reader_with_capacity(Cursor::new(data), 64 * 1024)
+    }
+
     /// Create a new FastqReader from any readable source with compression detection
     pub fn from_reader_with_capacity<R: Read + Send + 'static>(
         reader: R,
         sequence_size_hint: usize,
     ) -> Result<Self> {
-        let buf_reader = create_reader_with_compression(reader)?;
-        let lines = buf_reader.lines();
+        let input = counted_input(reader, create_reader_with_compression)?;
+        Ok(Self::from_line_reader(
+            LineReader::new(input),
+            sequence_size_hint,
+        ))
+    }
+
+    /// Create a new FastqReader that streams from an HTTP(S) URL, with
+    /// compression detection
+    #[cfg(feature = "remote")]
+    pub fn from_url(url: &str) -> Result<Self> {
+        Self::from_reader_with_capacity(crate::remote::open_url(url)?, 64 * 1024)
+    }
+
+    /// Create a new FastqReader that streams an object from a cloud object
+    /// store, such as `s3://bucket/key`, with compression detection
+    ///
+    /// See `object::open_object` for the supported URLs and credentials.
+    #[cfg(feature = "object_store")]
+    pub fn from_object_store(url: &str) -> Result<Self> {
+        Self::from_reader_with_capacity(crate::object::open_object(url)?, 64 * 1024)
+    }
+
+    /// Create a new FastqReader from a file path, decompressing with
+    /// `threads` threads
+    ///
+    /// BGZF files are decompressed in parallel, block by block. Other
+    /// compressed files are decompressed on one background thread.
+    pub fn from_file_with_threads<P: AsRef<Path>>(path: P, threads: usize) -> Result<Self> {
+        let file = File::open(path.as_ref())?;
+        Ok(Self::from_reader_with_threads(file, 64 * 1024, threads)?.with_path(path.as_ref()))
+    }
+
+    /// Create a new FastqReader from any readable source with compression
+    /// detection, decompressing with `threads` threads
+    pub fn from_reader_with_threads<R: Read + Send + 'static>(
+        reader: R,
+        sequence_size_hint: usize,
+        threads: usize,
+    ) -> Result<Self> {
+        let input = counted_input(reader, |reader| create_reader_with_threads(reader, threads))?;
+        Ok(Self::from_line_reader(
+            LineReader::new(input),
+            sequence_size_hint,
+        ))
+    }
+
+    /// Create a new FastqReader from a BGZF-compressed file path
+    ///
+    /// Records read this way report their BGZF virtual offset via
+    /// `virtual_offset()`.
+    pub fn from_bgzf_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let input = counted_input(File::open(path.as_ref())?, |file| Ok(BgzfReader::new(file)))?;
+        Ok(Self::from_line_reader(LineReader::new(input), 64 * 1024).with_path(path.as_ref()))
+    }
+
+    /// Create a new FastqReader from a BGZF reader, which may already have
+    /// been positioned with `BgzfReader::seek_virtual`
+    pub fn from_bgzf<R: Read + Send + 'static>(
+        reader: BgzfReader<R>,
+        sequence_size_hint: usize,
+    ) -> Self {
+        Self::from_line_reader(LineReader::new(Box::new(reader)), sequence_size_hint)
+    }
 
-        Ok(FastqReader {
+    /// Create a new FastqReader that continues reading a file from a checkpoint
+    /// taken while reading it before (see `checkpoint`), without reparsing
+    /// what came before
+    ///
+    /// Uncompressed files, and BGZF files read with `from_bgzf_file` (so
+    /// that checkpoints have a virtual offset), are seeked straight to the
+    /// checkpoint. Other compressed files are decompressed up to it, which
+    /// is quicker than parsing but not instant.
+    ///
+    /// Record numbers in errors and `stats()` carry on from the checkpoint.
+    /// The parser options are not saved, so set them again; duplicate IDs
+    /// are only detected among the records read after resuming.
+    pub fn resume_from<P: AsRef<Path>>(path: P, checkpoint: &Checkpoint) -> Result<Self> {
+        let path = path.as_ref();
+        let mut reader =
+            Self::from_line_reader(open_at(path, checkpoint)?, 64 * 1024).with_path(path);
+        reader.records = checkpoint.records;
+        reader.returned = checkpoint.returned;
+        reader.bases = checkpoint.bases;
+        reader.skipped = checkpoint.skipped;
+        Ok(reader)
+    }
+
+    pub(crate) fn from_line_reader(lines: LineReader, sequence_size_hint: usize) -> Self {
+        FastqReader {
             lines,
-            sequence_size_hint: sequence_size_hint.max(64),
-        })
+            header: Vec::new(),
+            sequence: Vec::with_capacity(sequence_size_hint.max(64)),
+            quality: Vec::with_capacity(sequence_size_hint.max(64)),
+            record_virtual_offset: None,
+            record_offset: None,
+            records: 0,
+            record_line: 0,
+            options: ParserOptions::default(),
+            skipped: 0,
+            resyncing: false,
+            ids: IdTracker::default(),
+            returned: 0,
+            bases: 0,
+            progress: None,
+            peeked: None,
+            path: None,
+        }
     }
 
-    fn read_next(&mut self) -> Result<Option<FastqRecord>> {
-        // Read header line (@id)
-        let id = loop {
-            match self.lines.next() {
-                Some(Ok(line)) => {
-                    if line.is_empty() || line.chars().all(|c| c.is_whitespace()) {
-                        continue;
-                    }
-                    let trimmed = line.trim();
-                    if !trimmed.starts_with('@') {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            "FASTQ record must start with '@'",
-                        ));
-                    }
-                    break trimmed[1..].to_string();
+    /// Record the path of the file being read, for `location()`
+    pub(crate) fn with_path(mut self, path: &Path) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// The file being read, if the reader was made from a path
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Turn this reader into an iterator that pairs each record with its
+    /// `Location`: the file, line and index it was read from
+    pub fn with_locations(self) -> WithLocations<Self> {
+        WithLocations::new(self)
+    }
+
+    /// The BGZF virtual offset of the start of the most recently returned
+    /// record, or None if the input is not being read with a BGZF reader
+    pub fn virtual_offset(&self) -> Option<VirtualOffset> {
+        self.record_virtual_offset
+    }
+
+    /// The byte offset, in the uncompressed input, of the start of the most
+    /// recently returned record, or None if no record has been returned
+    pub fn offset(&self) -> Option<u64> {
+        self.record_offset
+    }
+
+    /// The byte offset, in the uncompressed input, at which the next record
+    /// (if any) starts
+    pub fn tell(&self) -> u64 {
+        match self.peeked {
+            Some(_) => self.record_offset.unwrap_or(0),
+            None => self.lines.offset(),
+        }
+    }
+
+    /// Read the next record (or error) without consuming it: the next
+    /// call to `next`, `next_ref`, `next_bytes` or `read_into` returns it.
+    /// None at end of input.
+    ///
+    /// After a peek, `offset` and `virtual_offset` describe the peeked
+    /// record, and `tell` gives its start, as it has not been returned.
+    pub fn peek(&mut self) -> Option<&Result<FastqRecord>> {
+        if self.peeked.is_none() {
+            self.peeked = self.next();
+        }
+        self.peeked.as_ref()
+    }
+
+    /// Read the next record into the reader's internal buffers and return
+    /// it borrowed from them, avoiding any per-record allocation
+    ///
+    /// The record is only valid until the next call; use `to_owned()` or
+    /// `to_record()` to keep it.
+    pub fn next_ref(&mut self) -> Option<Result<RefFastqRecord<'_>>> {
+        match self.peeked.take() {
+            Some(Ok(record)) => {
+                self.header.clear();
+                self.header.extend_from_slice(record.header.as_bytes());
+                self.sequence.clear();
+                self.sequence.extend_from_slice(record.sequence.as_bytes());
+                self.quality.clear();
+                self.quality.extend_from_slice(record.quality.as_bytes());
+            }
+            Some(Err(e)) => return Some(Err(e)),
+            None => match self.read_record() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(e) => return Some(Err(e)),
+            },
+        }
+        Some(Ok(RefFastqRecord {
+            header: &self.header,
+            sequence: &self.sequence,
+            quality: &self.quality,
+            position: self.record_position(),
+        }))
+    }
+
+    /// Read up to `n` records, returning fewer only at end of input (and an
+    /// empty Vec once the input is exhausted)
+    pub fn next_chunk(&mut self, n: usize) -> Result<Vec<FastqRecord>> {
+        let mut records = Vec::with_capacity(n);
+        while records.len() < n {
+            match self.next() {
+                Some(record) => records.push(record?),
+                None => break,
+            }
+        }
+        Ok(records)
+    }
+
+    /// Read the next record into `record`, reusing its strings' allocations,
+    /// and return false (leaving `record` unchanged) at end of input
+    pub fn read_into(&mut self, record: &mut FastqRecord) -> Result<bool> {
+        if let Some(peeked) = self.peeked.take() {
+            *record = peeked?;
+            return Ok(true);
+        }
+        loop {
+            if !self.read_record()? {
+                return Ok(false);
+            }
+            let position = Some(self.record_position());
+            let fields = utf8_str(&self.header, "FASTQ", position).and_then(|id| {
+                Ok((
+                    id,
+                    utf8_str(&self.sequence, "FASTQ", position)?,
+                    utf8_str(&self.quality, "FASTQ", position)?,
+                ))
+            });
+            let error = match fields {
+                Ok((id, sequence, quality)) => {
+                    record.header.clear();
+                    record.header.push_str(id);
+                    record.sequence.clear();
+                    record.sequence.push_str(sequence);
+                    record.quality.clear();
+                    record.quality.push_str(quality);
+                    return Ok(true);
                 }
-                Some(Err(e)) => return Err(e),
-                None => return Ok(None),
+                Err(e) => e,
+            };
+            self.unreturn();
+            if !self.skip_error(&error) {
+                return Err(error.in_file(self.path.as_deref()));
             }
+        }
+    }
+
+    /// Read the next record as raw bytes, without checking that it is
+    /// valid UTF-8
+    pub fn next_bytes(&mut self) -> Option<Result<FastqRecordBytes>> {
+        self.next_ref().map(|r| r.map(|record| record.to_owned()))
+    }
+
+    /// Turn this reader into an iterator over byte records
+    pub fn byte_records(self) -> FastqByteRecords {
+        FastqByteRecords { reader: self }
+    }
+
+    /// Save the reader's position, before the next record, so that
+    /// reading can be resumed from there with `resume_from` (e.g. after the
+    /// job is preempted)
+    ///
+    /// A peeked record has not been returned, so it is read again after
+    /// resuming.
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        let (offset, virtual_offset, line) = if self.peeked.is_some() {
+            (
+                self.record_offset.unwrap_or(0),
+                self.record_virtual_offset,
+                self.record_line.saturating_sub(1),
+            )
+        } else {
+            (
+                self.lines.offset(),
+                self.lines.virtual_offset(),
+                self.lines.line(),
+            )
         };
+        let (mut records, mut returned, mut bases) = (self.records, self.returned, self.bases);
+        match &self.peeked {
+            Some(Ok(record)) => {
+                records -= 1;
+                returned -= 1;
+                bases -= record.sequence.len() as u64;
+            }
+            Some(Err(_)) => records = records.saturating_sub(1),
+            None => {}
+        }
+        Checkpoint {
+            offset,
+            virtual_offset: virtual_offset.map(u64::from),
+            records,
+            line,
+            returned,
+            bases,
+            skipped: self.skipped,
+        }
+    }
 
-        // Read sequence lines (until we hit a '+' line)
-        let mut sequence = String::with_capacity(self.sequence_size_hint);
-        let plus_line = loop {
-            match self.lines.next() {
-                Some(Ok(line)) => {
-                    let trimmed = line.trim();
-                    if trimmed.starts_with('+') {
-                        break trimmed.to_string();
-                    }
-                    if !line.is_empty() && !line.chars().all(|c| c.is_whitespace()) {
-                        sequence.push_str(trimmed);
-                    }
+    /// Set the options used to parse the input
+    pub fn with_options(mut self, options: ParserOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// The options used to parse the input
+    pub fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
+    /// The number of malformed records skipped so far, when the options'
+    /// `on_error` is `Skip` or `Warn`
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// How far through the input the reader has got
+    pub fn progress(&self) -> Progress {
+        Progress {
+            records: self.records,
+            bytes: self.lines.offset(),
+            input_bytes: self.lines.input_bytes(),
+            finished: false,
+        }
+    }
+
+    /// Cumulative counts of the records, bases and bytes read so far
+    pub fn stats(&self) -> ReaderStats {
+        ReaderStats {
+            records: self.returned,
+            bases: self.bases,
+            skipped: self.skipped,
+            duplicates: self.ids.duplicates(),
+            bytes: self.lines.offset(),
+            input_bytes: self.lines.input_bytes(),
+        }
+    }
+
+    /// The number of lines read so far with each style of line terminator,
+    /// e.g. to write output in the same style
+    pub fn line_endings(&self) -> LineEndings {
+        self.lines.line_endings()
+    }
+
+    /// Call `callback` with the reader's progress after every `every`
+    /// records, and once more (with `finished` set) at the end of the
+    /// input, e.g. to drive a progress bar
+    pub fn on_progress<F>(mut self, every: u64, callback: F) -> Self
+    where
+        F: FnMut(&Progress) + Send + 'static,
+    {
+        self.progress = Some(ProgressHook::new(every, callback));
+        self
+    }
+
+    /// Parse the input strictly as four-line records (header, sequence,
+    /// '+' and quality), as written by modern instruments
+    ///
+    /// This is faster than the default parsing, which allows sequence and
+    /// quality to be split over several lines, and unambiguous when quality
+    /// lines start with '@' or '+'. It sets `ParserOptions::strict_four_line`;
+    /// `four_line_from_file` opens a file in this mode.
+    pub fn four_line(mut self) -> Self {
+        self.options.strict_four_line = true;
+        self
+    }
+
+    /// Check that every sequence contains only characters from `alphabet`
+    /// (see `ParserOptions::validate`)
+    pub fn validate(mut self, alphabet: Alphabet) -> Self {
+        self.options.alphabet = Some(alphabet);
+        self
+    }
+
+    /// The position of the most recently read record, for errors
+    fn record_position(&self) -> Position {
+        Position {
+            line: self.record_line,
+            record: self.records,
+        }
+    }
+
+    /// The limits on line length, so that an over-long line fails while it
+    /// is being read. A line starting with '@' or '+' may be a header or a
+    /// line of quality scores.
+    fn line_limits(&self) -> LineLimits {
+        let header = match (
+            self.options.max_header_length,
+            self.options.max_sequence_length,
+        ) {
+            (Some(header), Some(sequence)) => Some(header.max(sequence)),
+            _ => None,
+        };
+        LineLimits {
+            format: "FASTQ",
+            markers: b"@+",
+            header,
+            sequence: self.options.max_sequence_length,
+        }
+    }
+
+    /// The position of the next line, for errors
+    fn next_line_position(&self) -> Position {
+        Position {
+            line: self.lines.line() + 1,
+            record: self.records,
+        }
+    }
+
+    /// Take back the count of the record just read, which could not be
+    /// returned after all (it is not UTF-8)
+    fn unreturn(&mut self) {
+        self.returned -= 1;
+        self.bases -= self.sequence.len() as u64;
+    }
+
+    /// Count a malformed record as skipped if the options allow, returning
+    /// whether it was
+    fn skip_error(&mut self, error: &PrseqError) -> bool {
+        if !self.options.skips(error) {
+            return false;
+        }
+        self.skipped += 1;
+        self.resyncing = true;
+        true
+    }
+
+    /// Read the next record into `header`, `sequence` and `quality`,
+    /// skipping malformed records if the options allow, and returning false
+    /// at end of input
+    fn read_record(&mut self) -> Result<bool> {
+        let result = loop {
+            match self.parse_record() {
+                Err(e) if self.skip_error(&e) => continue,
+                Ok(true) if self.options.skips_empty(&self.sequence) => continue,
+                result => break result,
+            }
+        };
+        if let Ok(true) = result {
+            self.returned += 1;
+            self.bases += self.sequence.len() as u64;
+        }
+        if let Some(mut hook) = self.progress.take() {
+            hook.report(|| self.progress(), matches!(result, Ok(false)));
+            self.progress = Some(hook);
+        }
+        result.map_err(|e| e.in_file(self.path.as_deref()))
+    }
+
+    /// Parse the next record into `header`, `sequence` and `quality`,
+    /// returning false at end of input
+    fn parse_record(&mut self) -> Result<bool> {
+        let limits = self.line_limits();
+        // Read header line (@id)
+        loop {
+            let offset = self.lines.virtual_offset();
+            let byte_offset = self.lines.offset();
+            let position = Position {
+                line: self.lines.line() + 1,
+                record: self.records + 1,
+            };
+            let Some((line, _)) = self.lines.next_line_within(&limits, position.record)? else {
+                return Ok(false);
+            };
+            let trimmed = line.trim_ascii();
+            if trimmed.is_empty() {
+                continue;
+            }
+            match trimmed.strip_prefix(b"@") {
+                Some(id) => {
+                    self.record_virtual_offset = offset;
+                    self.record_offset = Some(byte_offset);
+                    self.records = position.record;
+                    self.record_line = position.line;
+                    self.resyncing = false;
+                    self.header.clear();
+                    self.header.extend_from_slice(id);
+                    break;
                 }
-                Some(Err(e)) => return Err(e),
+                // After a malformed record, look for the next header.
+                None if self.resyncing => continue,
                 None => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::UnexpectedEof,
-                        "Unexpected end of file while reading FASTQ sequence",
+                    return Err(PrseqError::invalid_fastq(
+                        "FASTQ record must start with '@'",
+                        position,
+                        trimmed,
                     ));
                 }
             }
+        }
+
+        self.options
+            .check_header_length("FASTQ", &self.header, self.record_position())?;
+        if self.options.strict_four_line {
+            self.read_four_line_body()?;
+        } else {
+            self.read_multi_line_body()?;
+        }
+        self.options.check_empty(
+            "FASTQ",
+            &self.header,
+            &self.sequence,
+            self.record_position(),
+        )?;
+        if self.options.skips_empty(&self.sequence) {
+            return Ok(true);
+        }
+        self.options
+            .check_sequence(&self.header, &self.sequence, self.record_position())?;
+        let encoding = self.options.quality_encoding;
+        encoding
+            .convert(QualityEncoding::Phred33, &mut self.quality)
+            .map_err(|offset| PrseqError::InvalidQuality {
+                id: String::from_utf8_lossy(split_header(&self.header).0).into_owned(),
+                encoding,
+                offset,
+                byte: self.quality[offset],
+                position: Some(self.record_position()),
+            })?;
+        self.ids
+            .check(&self.options, &self.header, self.record_position())?;
+        Ok(true)
+    }
+
+    /// Read the sequence, '+' and quality lines of a record that is exactly
+    /// four lines long
+    fn read_four_line_body(&mut self) -> Result<()> {
+        let limits = self.line_limits();
+        let position = self.next_line_position();
+        let Some((line, _)) = self.lines.next_line_within(&limits, position.record)? else {
+            return Err(unexpected_eof("sequence", position));
         };
+        self.sequence.clear();
+        self.sequence.extend_from_slice(line.trim_ascii());
 
-        // Validate the '+' line if it contains an ID
-        if plus_line.len() > 1 {
-            let plus_id = &plus_line[1..];
-            if plus_id != id {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!(
-                        "FASTQ '+' line ID '{}' does not match header ID '{}'",
-                        plus_id, id
-                    ),
-                ));
-            }
+        let position = self.next_line_position();
+        let Some((line, _)) = self.lines.next_line_within(&limits, position.record)? else {
+            return Err(unexpected_eof("sequence", position));
+        };
+        let trimmed = line.trim_ascii();
+        // Read the quality line even if this one is wrong, so that a skipped
+        // record is skipped completely.
+        let plus_error = match trimmed.strip_prefix(b"+") {
+            Some(plus_id) => plus_line_error(plus_id, &self.header, position, trimmed),
+            None => Some(PrseqError::invalid_fastq(
+                "FASTQ record's third line must start with '+'",
+                position,
+                trimmed,
+            )),
+        };
+
+        let position = self.next_line_position();
+        let Some((line, _)) = self.lines.next_line_within(&limits, position.record)? else {
+            return Err(unexpected_eof("quality scores", position));
+        };
+        self.quality.clear();
+        self.quality.extend_from_slice(line.trim_ascii());
+
+        if let Some(error) = plus_error {
+            return Err(error);
         }
+        self.options
+            .check_sequence_length("FASTQ", self.sequence.len(), self.record_position())?;
+        self.check_lengths()
+    }
 
-        // Read quality lines (must match sequence length)
-        let mut quality = String::with_capacity(sequence.len());
-        let sequence_len = sequence.len();
-
-        while quality.len() < sequence_len {
-            match self.lines.next() {
-                Some(Ok(line)) => {
-                    let trimmed = line.trim();
-                    if !line.is_empty() && !line.chars().all(|c| c.is_whitespace()) {
-                        // Only add as many characters as we need
-                        let needed = sequence_len - quality.len();
-                        let to_add = if trimmed.len() <= needed {
-                            trimmed
-                        } else {
-                            &trimmed[..needed]
-                        };
-                        quality.push_str(to_add);
+    /// Read sequence and quality lines that may each be split over several
+    /// lines
+    fn read_multi_line_body(&mut self) -> Result<()> {
+        let limits = self.line_limits();
+        // Read sequence lines (until we hit a '+' line)
+        self.sequence.clear();
+        let mut plus_error = None;
+        loop {
+            let position = self.next_line_position();
+            let Some((line, _)) = self.lines.next_line_within(&limits, position.record)? else {
+                return Err(unexpected_eof("sequence", position));
+            };
+            let trimmed = line.trim_ascii();
+            if let Some(plus_id) = trimmed.strip_prefix(b"+") {
+                if let Some(error) = plus_line_error(plus_id, &self.header, position, trimmed) {
+                    if self.options.on_error == OnError::Fail {
+                        return Err(error);
                     }
+                    // Read the quality too, so that a skipped record is
+                    // skipped completely.
+                    plus_error = Some(error);
                 }
-                Some(Err(e)) => return Err(e),
-                None => {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::UnexpectedEof,
-                        "Unexpected end of file while reading FASTQ quality scores",
-                    ));
-                }
+                break;
             }
+            self.sequence.extend_from_slice(trimmed);
+            self.options.check_sequence_length(
+                "FASTQ",
+                self.sequence.len(),
+                self.record_position(),
+            )?;
         }
 
-        // Validate that sequence and quality have the same length
-        if sequence.len() != quality.len() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!(
-                    "FASTQ sequence length ({}) does not match quality length ({})",
-                    sequence.len(),
-                    quality.len()
-                ),
-            ));
+        // Read quality lines (must match sequence length)
+        self.quality.clear();
+        let sequence_len = self.sequence.len();
+
+        while self.quality.len() < sequence_len {
+            let position = self.next_line_position();
+            let Some((line, _)) = self.lines.next_line_within(&limits, position.record)? else {
+                return Err(unexpected_eof("quality scores", position));
+            };
+            // Quality that ends up longer than the sequence is reported by
+            // check_lengths, rather than truncated.
+            self.quality.extend_from_slice(line.trim_ascii());
         }
 
-        Ok(Some(FastqRecord {
-            id,
-            sequence,
-            quality,
-        }))
+        if let Some(error) = plus_error {
+            return Err(error);
+        }
+        self.check_lengths()
+    }
+
+    /// Validate that sequence and quality have the same length
+    fn check_lengths(&self) -> Result<()> {
+        if self.sequence.len() != self.quality.len() {
+            return Err(PrseqError::LengthMismatch {
+                sequence_length: self.sequence.len(),
+                quality_length: self.quality.len(),
+                position: self.record_position(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Check the ID on a '+' line, which if present must match the header
+fn plus_line_error(
+    plus_id: &[u8],
+    header: &[u8],
+    position: Position,
+    line: &[u8],
+) -> Option<PrseqError> {
+    if plus_id.is_empty() || plus_id == header {
+        return None;
+    }
+    Some(PrseqError::invalid_fastq(
+        format!(
+            "FASTQ '+' line ID '{}' does not match header ID '{}'",
+            String::from_utf8_lossy(plus_id),
+            String::from_utf8_lossy(header)
+        ),
+        position,
+        line,
+    ))
+}
+
+/// The error for input that ends part way through a record
+pub(crate) fn unexpected_eof(reading: &str, position: Position) -> PrseqError {
+    PrseqError::UnexpectedEof {
+        message: format!("Unexpected end of file while reading FASTQ {}", reading),
+        position,
     }
 }
 
@@ -173,14 +999,45 @@ impl Iterator for FastqReader {
     type Item = Result<FastqRecord>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.read_next() {
-            Ok(Some(record)) => Some(Ok(record)),
-            Ok(None) => None,
-            Err(e) => Some(Err(e)),
+        if let Some(peeked) = self.peeked.take() {
+            return Some(peeked);
+        }
+        loop {
+            let result = match self.next_ref()? {
+                Ok(record) => record.to_record(),
+                Err(e) => return Some(Err(e)),
+            };
+            if result.is_err() {
+                self.unreturn();
+            }
+            match result {
+                Err(e) if self.skip_error(&e) => continue,
+                result => return Some(result.map_err(|e| e.in_file(self.path.as_deref()))),
+            }
         }
     }
 }
 
+/// Iterator over FASTQ records as raw bytes, from `FastqReader::byte_records`
+pub struct FastqByteRecords {
+    reader: FastqReader,
+}
+
+impl FastqByteRecords {
+    /// The underlying reader, e.g. to query record offsets
+    pub fn reader(&self) -> &FastqReader {
+        &self.reader
+    }
+}
+
+impl Iterator for FastqByteRecords {
+    type Item = Result<FastqRecordBytes>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.next_bytes()
+    }
+}
+
 pub fn read_fastq<P: AsRef<Path>>(path: P) -> Result<Vec<FastqRecord>> {
     read_fastq_with_capacity(path, 64 * 1024)
 }
@@ -192,3 +1049,13 @@ pub fn read_fastq_with_capacity<P: AsRef<Path>>(
     let reader = FastqReader::from_file_with_capacity(path, sequence_size_hint)?;
     reader.collect()
 }
+
+impl Located for FastqReader {
+    fn location(&self) -> Location {
+        Location {
+            file: self.path.clone(),
+            line_number: self.record_line,
+            record_index: self.records.saturating_sub(1),
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use crate::common::{create_reader_with_compression, split_header_str};
+use crate::complexity::{dust_score, entropy};
+use crate::dedupe::{Dedupe, DedupeKey};
+use crate::error::Result;
+use crate::nanopore::NanoporeFilter;
+use crate::record::SequenceRecord;
+use crate::search::Search;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::ops::{Range, RangeBounds};
+use std::path::Path;
+
+/// A record that the filtering adaptors can inspect: any `SequenceRecord`
+pub trait Filterable: SequenceRecord {}
+
+impl<R: SequenceRecord + ?Sized> Filterable for R {}
+
+/// What part of a record's header an `IdSet` is matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdMatch {
+    /// The ID: the header up to the first whitespace (the default)
+    #[default]
+    Id,
+    /// The whole header line
+    Header,
+}
+
+/// A set of record IDs (or headers) to select records by, as with
+/// `seqkit grep -f`
+///
+/// ```
+/// use prseq::filter::{FilterExt, IdSet};
+/// use prseq::FastaReader;
+///
+/// let input = b">a x\nAC\n>b y\nGT\n>c z\nTT\n";
+/// let reader = FastaReader::from_reader_with_capacity(&input[..], 64).unwrap();
+/// let kept: Vec<String> = reader
+///     .select_by_ids(IdSet::new(["a", "c"]).invert())
+///     .map(|record| record.unwrap().header)
+///     .collect();
+/// assert_eq!(kept, ["b y"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IdSet {
+    ids: HashSet<String>,
+    match_on: IdMatch,
+    invert: bool,
+}
+
+impl IdSet {
+    /// A set of IDs, matched against the first word of each header
+    pub fn new<I, S>(ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        IdSet {
+            ids: ids.into_iter().map(Into::into).collect(),
+            ..IdSet::default()
+        }
+    }
+
+    /// A set of whole header lines, matched against each full header
+    pub fn headers<I, S>(headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        IdSet {
+            match_on: IdMatch::Header,
+            ..IdSet::new(headers)
+        }
+    }
+
+    /// Read a set from a file (which may be compressed) with one entry per
+    /// line. Blank lines are ignored. When matching IDs, only the first
+    /// word of each line is used, so a list of headers also works.
+    pub fn from_file<P: AsRef<Path>>(path: P, match_on: IdMatch) -> io::Result<Self> {
+        Self::from_reader(File::open(path)?, match_on)
+    }
+
+    /// Read a set with one entry per line, as `from_file`
+    pub fn from_reader<R: io::Read + Send + 'static>(
+        reader: R,
+        match_on: IdMatch,
+    ) -> io::Result<Self> {
+        let mut ids = HashSet::new();
+        for line in create_reader_with_compression(reader)?.lines() {
+            let line = line?;
+            let entry = match match_on {
+                IdMatch::Id => split_header_str(line.trim_start()).0,
+                IdMatch::Header => line.trim(),
+            };
+            if !entry.is_empty() {
+                ids.insert(entry.to_string());
+            }
+        }
+        Ok(IdSet {
+            ids,
+            match_on,
+            invert: false,
+        })
+    }
+
+    /// Select the records that are not in the set instead
+    pub fn invert(mut self) -> Self {
+        self.invert = !self.invert;
+        self
+    }
+
+    /// The number of IDs in the set
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Whether the set has no IDs
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Whether `record` is selected
+    pub fn matches<R: Filterable>(&self, record: &R) -> bool {
+        let found = match self.match_on {
+            IdMatch::Id => self.ids.contains(record.id()),
+            IdMatch::Header => self.ids.contains(record.header()),
+        };
+        found != self.invert
+    }
+}
+
+/// An iterator over the records of another that satisfy a predicate
+///
+/// Errors are passed through, so a filtered reader still reports malformed
+/// input.
+pub struct FilterRecords<I, P> {
+    records: I,
+    predicate: P,
+}
+
+impl<I, P, R> Iterator for FilterRecords<I, P>
+where
+    I: Iterator<Item = Result<R>>,
+    P: FnMut(&R) -> bool,
+{
+    type Item = Result<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.records.next()? {
+                Ok(record) if !(self.predicate)(&record) => continue,
+                result => return Some(result),
+            }
+        }
+    }
+}
+
+/// Filtering adaptors for readers (or any iterator of record results)
+///
+/// ```
+/// use prseq::filter::FilterExt;
+/// use prseq::FastaReader;
+///
+/// let input = b">a\nACGT\n>b\nACGTACGT\n>c\nA\n";
+/// let reader = FastaReader::from_reader_with_capacity(&input[..], 64).unwrap();
+/// let ids: Vec<String> = reader
+///     .filter_length(2..=4)
+///     .map(|record| record.unwrap().header)
+///     .collect();
+/// assert_eq!(ids, ["a"]);
+/// ```
+pub trait FilterExt<R: Filterable>: Iterator<Item = Result<R>> + Sized {
+    /// Keep the records for which `predicate` returns true
+    fn filter_records<P>(self, predicate: P) -> FilterRecords<Self, P>
+    where
+        P: FnMut(&R) -> bool,
+    {
+        FilterRecords {
+            records: self,
+            predicate,
+        }
+    }
+
+    /// Keep the records whose sequence length is in `lengths`
+    fn filter_length<B>(self, lengths: B) -> FilterRecords<Self, impl FnMut(&R) -> bool>
+    where
+        B: RangeBounds<usize>,
+    {
+        self.filter_records(move |record| lengths.contains(&record.sequence().len()))
+    }
+
+    /// Keep the records whose ID (the header up to the first whitespace) is
+    /// in `ids`
+    fn filter_ids(self, ids: HashSet<String>) -> FilterRecords<Self, impl FnMut(&R) -> bool> {
+        self.filter_records(move |record| ids.contains(record.id()))
+    }
+
+    /// Keep the records selected by `ids`
+    fn select_by_ids(self, ids: IdSet) -> FilterRecords<Self, impl FnMut(&R) -> bool> {
+        self.filter_records(move |record| ids.matches(record))
+    }
+
+    /// Keep the records in which `search` finds a match
+    fn grep(self, search: Search) -> FilterRecords<Self, impl FnMut(&R) -> bool> {
+        self.filter_records(move |record| search.is_match(record))
+    }
+
+    /// The records in which `search` finds a match, each with the ranges
+    /// of its matches
+    fn grep_matches(self, search: Search) -> impl Iterator<Item = Result<(R, Vec<Range<usize>>)>> {
+        self.filter_map(move |result| match result {
+            Ok(record) => {
+                let matches = search.find(&record);
+                (!matches.is_empty()).then_some(Ok((record, matches)))
+            }
+            Err(error) => Some(Err(error)),
+        })
+    }
+
+    /// Keep the records whose scaled k-mer entropy is at least
+    /// `min_entropy`, dropping low-complexity sequences (see
+    /// `complexity::entropy`)
+    fn filter_entropy(
+        self,
+        k: usize,
+        min_entropy: f64,
+    ) -> FilterRecords<Self, impl FnMut(&R) -> bool> {
+        self.filter_records(move |record| entropy(record.sequence().as_bytes(), k) >= min_entropy)
+    }
+
+    /// Keep the records whose DUST score is at most `max_score`, dropping
+    /// low-complexity sequences (see `complexity::dust_score`)
+    fn filter_dust(self, max_score: f64) -> FilterRecords<Self, impl FnMut(&R) -> bool> {
+        self.filter_records(move |record| dust_score(record.sequence().as_bytes()) <= max_score)
+    }
+
+    /// Keep the nanopore reads whose header fields meet `filter`, e.g. by
+    /// channel, start time or barcode (see `nanopore::NanoporeFilter`)
+    fn filter_nanopore(
+        self,
+        filter: NanoporeFilter,
+    ) -> FilterRecords<Self, impl FnMut(&R) -> bool> {
+        self.filter_records(move |record| filter.matches_header(record.header()))
+    }
+
+    /// Drop the records whose sequence (or ID and sequence) has been seen
+    /// before, keeping the first of each
+    fn dedupe(self, key: DedupeKey) -> Dedupe<Self> {
+        Dedupe::new(self, key)
+    }
+
+    /// Keep the records whose full header matches `pattern`
+    #[cfg(feature = "regex")]
+    fn filter_header_regex(
+        self,
+        pattern: regex::Regex,
+    ) -> FilterRecords<Self, impl FnMut(&R) -> bool> {
+        self.filter_records(move |record| pattern.is_match(record.header()))
+    }
+}
+
+impl<I, R> FilterExt<R> for I
+where
+    I: Iterator<Item = Result<R>>,
+    R: Filterable,
+{
+}
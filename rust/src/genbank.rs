@@ -0,0 +1,331 @@
+use crate::common::{create_reader_with_compression, utf8_str, LineReader};
+use crate::error::{Position, PrseqError, Result};
+use crate::fasta::FastaRecord;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// The column at which feature locations and qualifiers start
+const FEATURE_COLUMN: usize = 21;
+
+/// A feature from a GenBank feature table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feature {
+    /// The feature key, such as "gene" or "CDS"
+    pub kind: String,
+    /// The location, as written (e.g. "complement(join(1..10,20..30))")
+    pub location: String,
+    /// The qualifiers in order, without their '/' and with quotes removed.
+    /// Qualifiers with no value (such as /pseudo) have None.
+    pub qualifiers: Vec<(String, Option<String>)>,
+}
+
+impl Feature {
+    /// The value of the first qualifier called `name`. A qualifier with no
+    /// value gives Some("").
+    pub fn qualifier(&self, name: &str) -> Option<&str> {
+        self.qualifiers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_deref().unwrap_or(""))
+    }
+}
+
+/// A record from a GenBank flat file
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GenBankRecord {
+    /// The locus name, from the LOCUS line
+    pub name: String,
+    /// The sequence length given on the LOCUS line
+    pub length: Option<usize>,
+    /// The DEFINITION, with continuation lines joined
+    pub definition: String,
+    /// The primary (first) accession
+    pub accession: Option<String>,
+    /// The accession.version, from the VERSION line
+    pub version: Option<String>,
+    /// The ORGANISM name from the SOURCE section
+    pub organism: Option<String>,
+    pub features: Vec<Feature>,
+    /// The sequence from the ORIGIN section, in upper case
+    pub sequence: String,
+}
+
+impl GenBankRecord {
+    /// The best identifier: the accession.version, else the accession,
+    /// else the locus name
+    pub fn id(&self) -> &str {
+        self.version
+            .as_deref()
+            .or(self.accession.as_deref())
+            .unwrap_or(&self.name)
+    }
+
+    /// The features with the given key, such as "CDS"
+    pub fn features_of_kind<'a>(&'a self, kind: &'a str) -> impl Iterator<Item = &'a Feature> {
+        self.features
+            .iter()
+            .filter(move |feature| feature.kind == kind)
+    }
+
+    /// The record as FASTA, with a header in the style of NCBI's FASTA
+    /// downloads: the ID, then the definition without its final period
+    pub fn to_fasta(&self) -> FastaRecord {
+        FastaRecord {
+            header: self.fasta_header(),
+            sequence: self.sequence.clone(),
+        }
+    }
+
+    fn fasta_header(&self) -> String {
+        let definition = self
+            .definition
+            .strip_suffix('.')
+            .unwrap_or(&self.definition);
+        if definition.is_empty() {
+            self.id().to_string()
+        } else {
+            format!("{} {}", self.id(), definition)
+        }
+    }
+}
+
+impl From<GenBankRecord> for FastaRecord {
+    fn from(record: GenBankRecord) -> Self {
+        FastaRecord {
+            header: record.fasta_header(),
+            sequence: record.sequence,
+        }
+    }
+}
+
+/// The section of a record being read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Definition,
+    Features,
+    Origin,
+    /// Any other section, which is skipped
+    Other,
+}
+
+/// Iterator over the records of a GenBank flat file (such as a GenBank or
+/// RefSeq download)
+///
+/// The LOCUS, DEFINITION, ACCESSION, VERSION and ORGANISM fields, the
+/// feature table and the sequence are read; other sections are skipped.
+/// Records convert to `FastaRecord`s with `to_fasta` or `into()`.
+///
+/// ```
+/// use prseq::genbank::GenBankReader;
+/// use prseq::FastaRecord;
+///
+/// let input = b"LOCUS       AB000001   8 bp    DNA     linear   VRL 01-JAN-2000
+/// DEFINITION  Example virus, complete genome.
+/// ACCESSION   AB000001
+/// VERSION     AB000001.1
+/// ORIGIN
+///         1 acgtacgt
+/// //
+/// ";
+/// let mut reader = GenBankReader::from_reader(&input[..]).unwrap();
+/// let record: FastaRecord = reader.next().unwrap().unwrap().into();
+/// assert_eq!(record.header, "AB000001.1 Example virus, complete genome");
+/// assert_eq!(record.sequence, "ACGTACGT");
+/// ```
+pub struct GenBankReader {
+    lines: LineReader,
+    records: u64,
+}
+
+impl GenBankReader {
+    /// Create a new GenBankReader from a file path
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Create a new GenBankReader from any readable source with compression
+    /// detection
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> Result<Self> {
+        let buf_reader = create_reader_with_compression(reader)?;
+        Ok(GenBankReader {
+            lines: LineReader::new(Box::new(buf_reader)),
+            records: 0,
+        })
+    }
+
+    fn read_next(&mut self) -> Result<Option<GenBankRecord>> {
+        let mut record = GenBankRecord::default();
+        let mut section = Section::Other;
+        let mut started = false;
+        // Whether the last qualifier's quoted value continues on the next line
+        let mut open_quote = false;
+        loop {
+            let position = Position {
+                line: self.lines.line() + 1,
+                record: self.records + 1,
+            };
+            let Some((line, _)) = self.lines.next_line()? else {
+                if started {
+                    return Err(PrseqError::UnexpectedEof {
+                        message: "GenBank record ended without '//'".to_string(),
+                        position,
+                    });
+                }
+                return Ok(None);
+            };
+            if !started {
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                if !line.starts_with(b"LOCUS") {
+                    return Err(PrseqError::invalid_format(
+                        "GenBank",
+                        "GenBank record must start with 'LOCUS'",
+                        position,
+                        line,
+                    ));
+                }
+                started = true;
+            }
+            if line.starts_with(b"//") {
+                self.records += 1;
+                return Ok(Some(record));
+            }
+            let text = utf8_str(line, "GenBank", Some(position))?;
+
+            if !text.starts_with(' ') && !text.is_empty() {
+                // A new top-level field
+                let (keyword, value) = match text.find(' ') {
+                    Some(i) => (&text[..i], text[i..].trim()),
+                    None => (text, ""),
+                };
+                section = Section::Other;
+                match keyword {
+                    "LOCUS" => {
+                        let fields: Vec<&str> = value.split_whitespace().collect();
+                        record.name = fields.first().unwrap_or(&"").to_string();
+                        if matches!(fields.get(2), Some(&"bp") | Some(&"aa")) {
+                            record.length = fields[1].parse().ok();
+                        }
+                    }
+                    "DEFINITION" => {
+                        record.definition = value.to_string();
+                        section = Section::Definition;
+                    }
+                    "ACCESSION" => {
+                        record.accession = value.split_whitespace().next().map(String::from);
+                    }
+                    "VERSION" => {
+                        record.version = value.split_whitespace().next().map(String::from);
+                    }
+                    "FEATURES" => section = Section::Features,
+                    "ORIGIN" => section = Section::Origin,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match section {
+                Section::Definition => {
+                    record.definition.push(' ');
+                    record.definition.push_str(text.trim());
+                }
+                Section::Origin => record.sequence.extend(
+                    text.bytes()
+                        .filter(u8::is_ascii_alphabetic)
+                        .map(|base| base.to_ascii_uppercase() as char),
+                ),
+                Section::Features => {
+                    parse_feature_line(text, &mut record.features, &mut open_quote).map_err(
+                        |message| PrseqError::invalid_format("GenBank", message, position, line),
+                    )?;
+                }
+                Section::Other => {
+                    if let Some(organism) = text.strip_prefix("  ORGANISM") {
+                        record.organism = Some(organism.trim().to_string());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Add a line of a feature table (other than its header) to `features`.
+/// The first two columns are ignored, as they hold the line code ("FT") in
+/// EMBL files; the layout is otherwise the same as GenBank's.
+pub(crate) fn parse_feature_line(
+    text: &str,
+    features: &mut Vec<Feature>,
+    open_quote: &mut bool,
+) -> std::result::Result<(), String> {
+    let key = text
+        .get(2..text.len().min(FEATURE_COLUMN))
+        .unwrap_or("")
+        .trim();
+    let rest = text.get(FEATURE_COLUMN..).unwrap_or("").trim_end();
+    if !key.is_empty() {
+        features.push(Feature {
+            kind: key.to_string(),
+            location: rest.trim().to_string(),
+            qualifiers: Vec::new(),
+        });
+        *open_quote = false;
+        return Ok(());
+    }
+    let Some(feature) = features.last_mut() else {
+        return Err("Feature table line before any feature".to_string());
+    };
+    match feature.qualifiers.last_mut() {
+        Some((name, Some(value))) if *open_quote => {
+            // Translations are split without spaces; other text with them
+            if name != "translation" {
+                value.push(' ');
+            }
+            value.push_str(rest);
+            if value.matches('"').count() % 2 == 0 {
+                *open_quote = false;
+                *value = unquote(value);
+            }
+        }
+        _ => {
+            if let Some(qualifier) = rest.strip_prefix('/') {
+                let (name, value) = match qualifier.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (qualifier, None),
+                };
+                let mut value = value;
+                if let Some(text) = &mut value {
+                    if text.starts_with('"') && text.matches('"').count() % 2 == 1 {
+                        *open_quote = true;
+                    } else {
+                        *text = unquote(text);
+                    }
+                }
+                feature.qualifiers.push((name.to_string(), value));
+            } else if feature.qualifiers.is_empty() {
+                // A location continued from the line before
+                feature.location.push_str(rest.trim());
+            } else {
+                return Err(format!("Unexpected feature table line '{}'", rest.trim()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Remove the quotes around a qualifier value, and undouble quotes in it
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => inner.replace("\"\"", "\""),
+        None => value.to_string(),
+    }
+}
+
+impl Iterator for GenBankReader {
+    type Item = Result<GenBankRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_next().transpose()
+    }
+}
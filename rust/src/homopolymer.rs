@@ -0,0 +1,105 @@
+use crate::fasta::FastaRecord;
+use std::ops::Range;
+
+/// A homopolymer-compressed sequence: each run of a repeated base replaced
+/// by a single base, with the runs' positions in the original sequence so
+/// that coordinates can be mapped between the two
+///
+/// Runs are of identical bytes, so case matters ("AAaa" is two runs).
+///
+/// ```
+/// use prseq::homopolymer::homopolymer_compress;
+///
+/// let compressed = homopolymer_compress(b"AAACGGGGT");
+/// assert_eq!(compressed.sequence(), b"ACGT");
+/// assert_eq!(compressed.run_lengths().collect::<Vec<_>>(), [3, 1, 4, 1]);
+/// assert_eq!(compressed.original_range(2), Some(4..8));
+/// assert_eq!(compressed.compressed_position(6), Some(2));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct HomopolymerCompressed {
+    sequence: Vec<u8>,
+    /// The offset in the original sequence of the start of each run, and
+    /// then the original length
+    starts: Vec<usize>,
+}
+
+/// Compress the homopolymer runs of a sequence
+pub fn homopolymer_compress(sequence: &[u8]) -> HomopolymerCompressed {
+    let mut compressed = Vec::new();
+    let mut starts = Vec::new();
+    for (i, &base) in sequence.iter().enumerate() {
+        if compressed.last() != Some(&base) {
+            compressed.push(base);
+            starts.push(i);
+        }
+    }
+    starts.push(sequence.len());
+    HomopolymerCompressed {
+        sequence: compressed,
+        starts,
+    }
+}
+
+impl HomopolymerCompressed {
+    /// The compressed sequence
+    pub fn sequence(&self) -> &[u8] {
+        &self.sequence
+    }
+
+    /// The length of the compressed sequence (the number of runs)
+    pub fn len(&self) -> usize {
+        self.sequence.len()
+    }
+
+    /// Whether the sequence is empty
+    pub fn is_empty(&self) -> bool {
+        self.sequence.is_empty()
+    }
+
+    /// The length of the original sequence
+    pub fn original_len(&self) -> usize {
+        self.starts[self.starts.len() - 1]
+    }
+
+    /// The length of the run each base of the compressed sequence stands for
+    pub fn run_lengths(&self) -> impl Iterator<Item = usize> + '_ {
+        self.starts.windows(2).map(|run| run[1] - run[0])
+    }
+
+    /// The range of the original sequence that the base at compressed
+    /// offset `i` stands for, or None if it is past the end
+    pub fn original_range(&self, i: usize) -> Option<Range<usize>> {
+        (i < self.len()).then(|| self.starts[i]..self.starts[i + 1])
+    }
+
+    /// The original range of a range of the compressed sequence (e.g. of a
+    /// match found in it), or None if it is not within the sequence
+    pub fn original_span(&self, range: Range<usize>) -> Option<Range<usize>> {
+        (range.start <= range.end && range.end <= self.len())
+            .then(|| self.starts[range.start]..self.starts[range.end])
+    }
+
+    /// The compressed offset of the run holding original offset `i`, or
+    /// None if it is past the end
+    pub fn compressed_position(&self, i: usize) -> Option<usize> {
+        (i < self.original_len()).then(|| self.starts.partition_point(|&start| start <= i) - 1)
+    }
+
+    /// The original sequence, rebuilt from the runs
+    pub fn expand(&self) -> Vec<u8> {
+        self.sequence
+            .iter()
+            .zip(self.run_lengths())
+            .flat_map(|(&base, length)| std::iter::repeat_n(base, length))
+            .collect()
+    }
+}
+
+impl FastaRecord {
+    /// The homopolymer-compressed sequence, with what is needed to map its
+    /// coordinates back to the original (see `homopolymer_compress`)
+    pub fn homopolymer_compress(&self) -> HomopolymerCompressed {
+        homopolymer_compress(self.sequence.as_bytes())
+    }
+}
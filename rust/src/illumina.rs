@@ -0,0 +1,135 @@
+use crate::common::split_header_str;
+use crate::fastq::FastqRecord;
+
+/// The fields of an Illumina read header
+///
+/// Two layouts are recognised. Casava 1.8 and later write
+///
+/// ```text
+/// @<instrument>:<run>:<flowcell>:<lane>:<tile>:<x>:<y>[:<UMI>] <read>:<filtered>:<control>:<index>
+/// ```
+///
+/// (the comment after the whitespace is optional), and earlier versions
+/// write
+///
+/// ```text
+/// @<instrument>:<lane>:<tile>:<x>:<y>[#<index>][/<read>]
+/// ```
+///
+/// Fields that a layout does not have are None.
+///
+/// ```
+/// use prseq::IlluminaHeader;
+///
+/// let header = IlluminaHeader::parse("M00123:45:000000000-ABCDE:1:1101:15589:1333 1:N:0:ATCACG").unwrap();
+/// assert_eq!(header.lane, 1);
+/// assert_eq!(header.read, Some(1));
+/// assert_eq!(header.index.as_deref(), Some("ATCACG"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IlluminaHeader {
+    pub instrument: String,
+    pub run: Option<u32>,
+    pub flowcell: Option<String>,
+    pub lane: u32,
+    pub tile: u32,
+    pub x: u32,
+    pub y: u32,
+    /// The UMI sequence, when one was added to the read name
+    pub umi: Option<String>,
+    /// The read number: 1 or 2 for paired reads (3 and 4 for index reads)
+    pub read: Option<u8>,
+    /// Whether the read failed the instrument's filter ('Y' in the header)
+    pub filtered: Option<bool>,
+    /// The control bits, 0 when the read is not a control
+    pub control: Option<u32>,
+    /// The index (barcode) sequence, or the sample number for some runs
+    pub index: Option<String>,
+}
+
+impl IlluminaHeader {
+    /// Parse a FASTQ header line (without its '@'), returning None if it is
+    /// not in either Illumina layout
+    pub fn parse(header: &str) -> Option<IlluminaHeader> {
+        let (name, comment) = split_header_str(header);
+        let fields: Vec<&str> = name.split(':').collect();
+        match fields.len() {
+            7 | 8 => Self::parse_casava(&fields, comment),
+            5 => Self::parse_legacy(&fields),
+            _ => None,
+        }
+    }
+
+    /// Parse the Casava 1.8+ layout, split into its name fields
+    fn parse_casava(fields: &[&str], comment: Option<&str>) -> Option<IlluminaHeader> {
+        let mut header = IlluminaHeader {
+            instrument: non_empty(fields[0])?.to_string(),
+            run: Some(fields[1].parse().ok()?),
+            flowcell: Some(non_empty(fields[2])?.to_string()),
+            lane: fields[3].parse().ok()?,
+            tile: fields[4].parse().ok()?,
+            x: fields[5].parse().ok()?,
+            y: fields[6].parse().ok()?,
+            umi: fields.get(7).map(|umi| umi.to_string()),
+            read: None,
+            filtered: None,
+            control: None,
+            index: None,
+        };
+        if let Some(comment) = comment {
+            let comment = comment.split_ascii_whitespace().next()?;
+            let mut parts = comment.splitn(4, ':');
+            header.read = Some(parts.next()?.parse().ok()?);
+            header.filtered = Some(match parts.next()? {
+                "Y" => true,
+                "N" => false,
+                _ => return None,
+            });
+            header.control = Some(parts.next()?.parse().ok()?);
+            header.index = parts
+                .next()
+                .filter(|index| !index.is_empty())
+                .map(str::to_string);
+        }
+        Some(header)
+    }
+
+    /// Parse the pre-1.8 layout, whose last name field carries the index
+    /// and read number
+    fn parse_legacy(fields: &[&str]) -> Option<IlluminaHeader> {
+        let (y, read) = match fields[4].rsplit_once('/') {
+            Some((y, read)) => (y, Some(read.parse().ok()?)),
+            None => (fields[4], None),
+        };
+        let (y, index) = match y.split_once('#') {
+            Some((y, index)) => (y, non_empty(index).map(str::to_string)),
+            None => (y, None),
+        };
+        Some(IlluminaHeader {
+            instrument: non_empty(fields[0])?.to_string(),
+            run: None,
+            flowcell: None,
+            lane: fields[1].parse().ok()?,
+            tile: fields[2].parse().ok()?,
+            x: fields[3].parse().ok()?,
+            y: y.parse().ok()?,
+            umi: None,
+            read,
+            filtered: None,
+            control: None,
+            index,
+        })
+    }
+}
+
+fn non_empty(field: &str) -> Option<&str> {
+    Some(field).filter(|field| !field.is_empty())
+}
+
+impl FastqRecord {
+    /// Parse the record's header as an Illumina read header, returning None
+    /// if it is not one (see `IlluminaHeader`)
+    pub fn parse_illumina_header(&self) -> Option<IlluminaHeader> {
+        IlluminaHeader::parse(&self.header)
+    }
+}
@@ -0,0 +1,257 @@
+use crate::error::{Position, PrseqError, Result};
+use crate::fasta::FastaReader;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// One line of a samtools-compatible FASTA index (.fai)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaiRecord {
+    /// The sequence name (the first word of its header)
+    pub name: String,
+    /// The number of bases in the sequence
+    pub length: u64,
+    /// The byte offset of the first base in the uncompressed file
+    pub offset: u64,
+    /// The number of bases on each full line
+    pub line_bases: u64,
+    /// The number of bytes in each full line, including its terminator
+    pub line_width: u64,
+}
+
+/// A FASTA index, as written by `samtools faidx`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FastaIndex {
+    records: Vec<FaiRecord>,
+    by_name: HashMap<String, usize>,
+}
+
+impl FastaIndex {
+    /// Build an index of a FASTA file, which may be compressed (offsets are
+    /// always in the uncompressed data)
+    pub fn build_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::build_from_reader(FastaReader::from_file(path)?)
+    }
+
+    /// Build an index by reading all the records of a FASTA reader, which
+    /// must not have returned any records yet
+    pub fn build_from_reader(reader: FastaReader) -> Result<Self> {
+        let mut reader = reader.with_index();
+        for record in reader.by_ref() {
+            record?;
+        }
+        reader.take_index().unwrap_or_else(|| Ok(Self::default()))
+    }
+
+    /// Read an index in .fai format
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        let mut records = Vec::new();
+        for (number, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let position = Position {
+                line: number as u64 + 1,
+                record: records.len() as u64 + 1,
+            };
+            records.push(parse_fai_line(&line).ok_or_else(|| {
+                PrseqError::invalid_format(
+                    "fai",
+                    "Invalid FASTA index line",
+                    position,
+                    line.as_bytes(),
+                )
+            })?);
+        }
+        Self::from_records(records)
+    }
+
+    /// Read a .fai index file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Create an index from its records, which must have unique names and
+    /// line lengths that can locate their bases
+    pub fn from_records(records: Vec<FaiRecord>) -> Result<Self> {
+        let mut by_name = HashMap::with_capacity(records.len());
+        for (i, record) in records.iter().enumerate() {
+            let position = Position {
+                line: 0,
+                record: i as u64 + 1,
+            };
+            if (record.line_bases == 0 && record.length > 0)
+                || record.line_width < record.line_bases
+            {
+                return Err(PrseqError::invalid_format(
+                    "fai",
+                    format!(
+                        "Invalid line lengths for sequence '{}' in FASTA index: {} bases in {} bytes",
+                        record.name, record.line_bases, record.line_width
+                    ),
+                    position,
+                    &[],
+                ));
+            }
+            if by_name.insert(record.name.clone(), i).is_some() {
+                return Err(PrseqError::invalid_format(
+                    "fai",
+                    format!("Duplicate sequence name '{}' in FASTA index", record.name),
+                    position,
+                    &[],
+                ));
+            }
+        }
+        Ok(FastaIndex { records, by_name })
+    }
+
+    /// Write the index in .fai format
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        for record in &self.records {
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}\t{}",
+                record.name, record.length, record.offset, record.line_bases, record.line_width
+            )?;
+        }
+        Ok(writer.flush()?)
+    }
+
+    /// Write the index to a .fai file
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.write(BufWriter::new(File::create(path)?))
+    }
+
+    /// The index records, in file order
+    pub fn records(&self) -> &[FaiRecord] {
+        &self.records
+    }
+
+    /// Look up the index record for a sequence name
+    pub fn get(&self, name: &str) -> Option<&FaiRecord> {
+        self.by_name.get(name).map(|&i| &self.records[i])
+    }
+
+    /// The number of sequences in the index
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether the index has no sequences
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+/// The conventional index path for a FASTA file: its path with ".fai"
+/// appended
+pub fn fai_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut fai = path.as_ref().as_os_str().to_owned();
+    fai.push(".fai");
+    PathBuf::from(fai)
+}
+
+/// Index a FASTA file and write the index next to it (like `samtools
+/// faidx`), returning the index
+pub fn index_fasta<P: AsRef<Path>>(path: P) -> Result<FastaIndex> {
+    let index = FastaIndex::build_from_file(&path)?;
+    index.write_to_file(fai_path(&path))?;
+    Ok(index)
+}
+
+fn parse_fai_line(line: &str) -> Option<FaiRecord> {
+    let mut fields = line.split('\t');
+    let record = FaiRecord {
+        name: fields.next()?.to_string(),
+        length: fields.next()?.parse().ok()?,
+        offset: fields.next()?.parse().ok()?,
+        line_bases: fields.next()?.parse().ok()?,
+        line_width: fields.next()?.parse().ok()?,
+    };
+    Some(record)
+}
+
+/// Accumulates index records from the lines a FastaReader reads
+#[derive(Default)]
+pub(crate) struct FaiBuilder {
+    records: Vec<FaiRecord>,
+    current: Option<FaiRecord>,
+    short_line_seen: bool,
+    /// The first error seen, and the number of the record it is in
+    error: Option<(String, u64)>,
+}
+
+impl FaiBuilder {
+    /// Start a new record whose header line ends at `offset`
+    pub(crate) fn start_record(&mut self, header: &[u8], offset: u64) {
+        self.finish_record();
+        let name = header
+            .split(|b| b.is_ascii_whitespace())
+            .next()
+            .unwrap_or(b"");
+        self.current = Some(FaiRecord {
+            name: String::from_utf8_lossy(name).into_owned(),
+            length: 0,
+            offset,
+            line_bases: 0,
+            line_width: 0,
+        });
+        self.short_line_seen = false;
+    }
+
+    /// Add a sequence line with `bases` bases, taking `width` bytes
+    pub(crate) fn add_line(&mut self, bases: u64, width: u64) {
+        if self.error.is_some() {
+            return;
+        }
+        let Some(record) = self.current.as_mut() else {
+            return;
+        };
+        if bases == 0 {
+            if record.length == 0 {
+                // Blank lines before the sequence: its first base is later.
+                record.offset += width;
+            } else {
+                self.short_line_seen = true;
+            }
+            return;
+        }
+        if record.line_bases == 0 {
+            record.line_bases = bases;
+            record.line_width = width;
+        } else if self.short_line_seen
+            || bases > record.line_bases
+            || (bases == record.line_bases && width != record.line_width)
+        {
+            self.error = Some((
+                format!("Different line length in sequence '{}'", record.name),
+                self.records.len() as u64 + 1,
+            ));
+            return;
+        } else if bases < record.line_bases {
+            self.short_line_seen = true;
+        }
+        record.length += bases;
+    }
+
+    fn finish_record(&mut self) {
+        if let Some(record) = self.current.take() {
+            self.records.push(record);
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> Result<FastaIndex> {
+        if let Some((message, record)) = self.error {
+            return Err(PrseqError::invalid_format(
+                "fai",
+                message,
+                Position { line: 0, record },
+                &[],
+            ));
+        }
+        self.finish_record();
+        FastaIndex::from_records(self.records)
+    }
+}
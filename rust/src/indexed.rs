@@ -0,0 +1,213 @@
+use crate::bgzf::{is_bgzf, BgzfReader, GziIndex};
+use crate::error::{PrseqError, Result};
+use crate::index::{fai_path, FaiRecord, FastaIndex};
+use crate::region::Region;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+enum Input<R> {
+    Plain(R),
+    Bgzf(BgzfReader<R>, GziIndex),
+}
+
+/// Random access to the sequences of an indexed FASTA file
+///
+/// Subsequences are read directly from their position in the file using a
+/// `.fai` index, so only the requested bases are read. BGZF-compressed files
+/// are supported with the help of a `.gzi` block index.
+pub struct IndexedFastaReader<R: Read + Seek = File> {
+    input: Input<R>,
+    index: FastaIndex,
+    buffer: Vec<u8>,
+}
+
+impl IndexedFastaReader<File> {
+    /// Open an uncompressed or BGZF-compressed FASTA file
+    ///
+    /// The `.fai` index (and, for BGZF files, the `.gzi` index) next to the
+    /// file is used if present, and otherwise built in memory.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let fai = fai_path(path);
+        let index = if fai.exists() {
+            FastaIndex::from_file(fai)?
+        } else {
+            FastaIndex::build_from_file(path)?
+        };
+
+        let gzi = || {
+            let mut gzi = path.as_os_str().to_owned();
+            gzi.push(".gzi");
+            let gzi = Path::new(&gzi);
+            if gzi.exists() {
+                GziIndex::from_file(gzi)
+            } else {
+                GziIndex::build(io::BufReader::new(File::open(path)?))
+            }
+        };
+        Self::open(File::open(path)?, index, gzi, &path.display().to_string())
+    }
+}
+
+#[cfg(feature = "remote")]
+impl IndexedFastaReader<crate::remote::HttpRangeReader> {
+    /// Open a remote uncompressed or BGZF-compressed FASTA file, reading
+    /// regions with HTTP range requests
+    ///
+    /// The `.fai` index (and, for BGZF files, the `.gzi` index) must be
+    /// available at the same URL with ".fai" (or ".gzi") appended.
+    pub fn from_url(url: &str) -> Result<Self> {
+        use crate::remote::{open_url, HttpRangeReader};
+        let index = FastaIndex::from_reader(open_url(&format!("{}.fai", url))?)?;
+        let gzi = || GziIndex::from_reader(open_url(&format!("{}.gzi", url))?);
+        Self::open(HttpRangeReader::new(url)?, index, gzi, url)
+    }
+}
+
+impl<R: Read + Seek> IndexedFastaReader<R> {
+    /// Create a new IndexedFastaReader for uncompressed FASTA data
+    pub fn new(reader: R, index: FastaIndex) -> Self {
+        IndexedFastaReader {
+            input: Input::Plain(reader),
+            index,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Create a new IndexedFastaReader for BGZF-compressed FASTA data
+    pub fn new_bgzf(reader: R, index: FastaIndex, gzi: GziIndex) -> Self {
+        IndexedFastaReader {
+            input: Input::Bgzf(BgzfReader::new(reader), gzi),
+            index,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Create a reader for data that may be BGZF-compressed, loading its
+    /// .gzi index with `gzi` if so
+    fn open<G>(mut reader: R, index: FastaIndex, gzi: G, name: &str) -> Result<Self>
+    where
+        G: FnOnce() -> Result<GziIndex>,
+    {
+        let mut magic = [0; 18];
+        let n = read_up_to(&mut reader, &mut magic)?;
+        reader.seek(SeekFrom::Start(0))?;
+
+        if is_bgzf(&magic[..n]) {
+            Ok(Self::new_bgzf(reader, index, gzi()?))
+        } else if n >= 2 && magic[0] == 0x1f && magic[1] == 0x8b {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} is gzip-compressed but not BGZF, so cannot be read randomly \
+                     (recompress it with bgzip)",
+                    name
+                ),
+            )
+            .into())
+        } else {
+            Ok(Self::new(reader, index))
+        }
+    }
+
+    /// The FASTA index
+    pub fn index(&self) -> &FastaIndex {
+        &self.index
+    }
+
+    /// Fetch the bases from `start` (0-based, inclusive) to `end` (exclusive)
+    /// of the named sequence. `end` is clipped to the sequence length.
+    pub fn fetch(&mut self, name: &str, start: u64, end: u64) -> Result<String> {
+        let record = self.record(name)?.clone();
+        if start > end.min(record.length) {
+            return Err(PrseqError::OutOfRange {
+                range: format!("{}:{}-{}", name, start, end),
+                length: record.length as usize,
+            });
+        }
+        let end = end.min(record.length);
+        if start == end {
+            return Ok(String::new());
+        }
+
+        let first = byte_position(&record, start);
+        let last = byte_position(&record, end - 1);
+        self.buffer.resize((last - first + 1) as usize, 0);
+        match &mut self.input {
+            Input::Plain(reader) => {
+                reader.seek(SeekFrom::Start(first))?;
+                reader.read_exact(&mut self.buffer)?;
+            }
+            Input::Bgzf(reader, gzi) => {
+                reader.seek_virtual(gzi.virtual_offset(first))?;
+                reader.read_exact(&mut self.buffer)?;
+            }
+        }
+
+        let mut sequence = String::with_capacity((end - start) as usize);
+        sequence.extend(
+            self.buffer
+                .iter()
+                .filter(|b| !b.is_ascii_whitespace())
+                .map(|&b| b as char),
+        );
+        Ok(sequence)
+    }
+
+    /// Fetch the whole of the named sequence
+    pub fn fetch_sequence(&mut self, name: &str) -> Result<String> {
+        let length = self.record(name)?.length;
+        self.fetch(name, 0, length)
+    }
+
+    /// Fetch a region given as a samtools-style string (1-based, inclusive),
+    /// such as `chr2`, `chr2:1,000-2,000` or `chr2:1000-`
+    ///
+    /// As with samtools, a string that is itself the name of a sequence in
+    /// the index is taken to mean that whole sequence, even if it contains
+    /// a colon.
+    pub fn fetch_region(&mut self, region: &str) -> Result<String> {
+        if self.index.get(region).is_some() {
+            return self.fetch_sequence(region);
+        }
+        let region: Region = region.parse()?;
+        self.fetch_parsed_region(&region)
+    }
+
+    /// Fetch a parsed region
+    pub fn fetch_parsed_region(&mut self, region: &Region) -> Result<String> {
+        let (start, end) = region.zero_based();
+        let end = match end {
+            Some(end) => end,
+            None => self.record(&region.name)?.length,
+        };
+        self.fetch(&region.name, start, end)
+    }
+
+    fn record(&self, name: &str) -> Result<&FaiRecord> {
+        self.index.get(name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Sequence '{}' is not in the FASTA index", name),
+            )
+            .into()
+        })
+    }
+}
+
+/// The byte offset of a (0-based) base of a sequence
+fn byte_position(record: &FaiRecord, base: u64) -> u64 {
+    record.offset + (base / record.line_bases) * record.line_width + base % record.line_bases
+}
+
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
@@ -0,0 +1,188 @@
+use crate::any::AnyRecord;
+use crate::common::{create_reader_with_compression, LineReader};
+use crate::error::{Position, PrseqError, Result};
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+/// A record as a line of JSON: `{"id": ..., "description": ..., "sequence":
+/// ..., "quality": ...}`. The description is left out if the header has
+/// none, and the quality for FASTA records.
+#[derive(Debug, Serialize, Deserialize)]
+struct Line<'a> {
+    #[serde(borrow)]
+    id: Cow<'a, str>,
+    #[serde(borrow, default, skip_serializing_if = "Option::is_none")]
+    description: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    sequence: Cow<'a, str>,
+    #[serde(borrow, default, skip_serializing_if = "Option::is_none")]
+    quality: Option<Cow<'a, str>>,
+}
+
+impl Line<'_> {
+    fn into_record(self) -> AnyRecord {
+        let header = match self.description {
+            Some(description) => format!("{} {}", self.id, description),
+            None => self.id.into_owned(),
+        };
+        let sequence = self.sequence.into_owned();
+        match self.quality {
+            Some(quality) => AnyRecord::Fastq(FastqRecord {
+                header,
+                sequence,
+                quality: quality.into_owned(),
+            }),
+            None => AnyRecord::Fasta(FastaRecord { header, sequence }),
+        }
+    }
+}
+
+/// Iterator over records stored as JSON Lines, one JSON object per line
+///
+/// Objects with a "quality" field are returned as FASTQ records and others
+/// as FASTA records, and the "id" and optional "description" fields are
+/// joined to make the header. Other fields are ignored, and blank lines are
+/// skipped.
+///
+/// ```
+/// use prseq::jsonl::JsonlReader;
+///
+/// let input = br#"{"id": "r1", "sequence": "ACGT", "quality": "IIII"}"#;
+/// let mut reader = JsonlReader::from_reader(&input[..]).unwrap();
+/// let record = reader.next().unwrap().unwrap();
+/// assert_eq!(record.quality(), Some("IIII"));
+/// ```
+pub struct JsonlReader {
+    lines: LineReader,
+    records: u64,
+}
+
+impl JsonlReader {
+    /// Create a new JsonlReader from a file path
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Create a new JsonlReader from any readable source with compression
+    /// detection
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> Result<Self> {
+        let buf_reader = create_reader_with_compression(reader)?;
+        Ok(JsonlReader {
+            lines: LineReader::new(Box::new(buf_reader)),
+            records: 0,
+        })
+    }
+
+    fn read_next(&mut self) -> Result<Option<AnyRecord>> {
+        loop {
+            let position = Position {
+                line: self.lines.line() + 1,
+                record: self.records + 1,
+            };
+            let Some((line, _)) = self.lines.next_line()? else {
+                return Ok(None);
+            };
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let parsed: Line = serde_json::from_slice(line).map_err(|e| {
+                PrseqError::invalid_format(
+                    "JSONL",
+                    format!("Invalid JSONL record: {}", e),
+                    position,
+                    line,
+                )
+            })?;
+            self.records += 1;
+            return Ok(Some(parsed.into_record()));
+        }
+    }
+}
+
+impl Iterator for JsonlReader {
+    type Item = Result<AnyRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_next().transpose()
+    }
+}
+
+/// Writes records as JSON Lines, in the form `JsonlReader` reads
+///
+/// ```
+/// use prseq::jsonl::JsonlWriter;
+/// use prseq::FastaRecord;
+///
+/// let mut writer = JsonlWriter::new(Vec::new());
+/// let record = FastaRecord { header: "s1 first".into(), sequence: "ACGT".into() };
+/// writer.write_fasta(&record).unwrap();
+/// assert_eq!(
+///     String::from_utf8(writer.into_inner()).unwrap(),
+///     "{\"id\":\"s1\",\"description\":\"first\",\"sequence\":\"ACGT\"}\n"
+/// );
+/// ```
+pub struct JsonlWriter<W: Write> {
+    writer: W,
+}
+
+impl JsonlWriter<BufWriter<File>> {
+    /// Create a new JsonlWriter that writes (uncompressed) to a file path
+    pub fn to_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> JsonlWriter<W> {
+    /// Create a new JsonlWriter
+    pub fn new(writer: W) -> Self {
+        JsonlWriter { writer }
+    }
+
+    fn write_line(&mut self, line: &Line) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, line)?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Write a FASTA record, without a quality
+    pub fn write_fasta(&mut self, record: &FastaRecord) -> io::Result<()> {
+        self.write_line(&Line {
+            id: record.id().into(),
+            description: record.description().map(Cow::from),
+            sequence: record.sequence.as_str().into(),
+            quality: None,
+        })
+    }
+
+    /// Write a FASTQ record
+    pub fn write_fastq(&mut self, record: &FastqRecord) -> io::Result<()> {
+        self.write_line(&Line {
+            id: record.id().into(),
+            description: record.description().map(Cow::from),
+            sequence: record.sequence.as_str().into(),
+            quality: Some(record.quality.as_str().into()),
+        })
+    }
+
+    /// Write a record of either kind
+    pub fn write_record(&mut self, record: &AnyRecord) -> io::Result<()> {
+        match record {
+            AnyRecord::Fasta(record) => self.write_fasta(record),
+            AnyRecord::Fastq(record) => self.write_fastq(record),
+        }
+    }
+
+    /// Flush any buffered output
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Consume the writer, returning the underlying destination
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
@@ -0,0 +1,703 @@
+use crate::error::Result;
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use crate::record::SequenceRecord;
+use crate::sequence::complement;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// An iterator over the k-mers of a sequence, as overlapping windows of the
+/// sequence itself, from `kmers`
+#[derive(Debug, Clone)]
+pub struct Kmers<'a> {
+    sequence: &'a [u8],
+    k: usize,
+    start: usize,
+}
+
+/// The k-mers of `sequence`, in order, as slices of it. There are none if
+/// `k` is 0 or longer than the sequence. Bases are not checked, so k-mers
+/// may hold Ns or gaps.
+///
+/// ```
+/// use prseq::kmer::kmers;
+///
+/// let kmers: Vec<&[u8]> = kmers(b"ACGTA", 3).collect();
+/// assert_eq!(kmers, [b"ACG", b"CGT", b"GTA"]);
+/// ```
+pub fn kmers(sequence: &[u8], k: usize) -> Kmers<'_> {
+    Kmers {
+        sequence,
+        k,
+        start: 0,
+    }
+}
+
+impl<'a> Iterator for Kmers<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.k == 0 || self.start + self.k > self.sequence.len() {
+            return None;
+        }
+        let kmer = &self.sequence[self.start..self.start + self.k];
+        self.start += 1;
+        Some(kmer)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = if self.k == 0 {
+            0
+        } else {
+            (self.sequence.len() + 1).saturating_sub(self.start + self.k)
+        };
+        (left, Some(left))
+    }
+}
+
+impl ExactSizeIterator for Kmers<'_> {}
+
+/// A canonical k-mer: the lexicographically smaller (ignoring case) of a
+/// window of a sequence and its reverse complement
+///
+/// The reverse complement is not built: a canonical k-mer refers to the
+/// window, and its bases are read from either end of it. Canonical k-mers
+/// compare, order and hash by their upper-cased bases, so a k-mer and its
+/// reverse complement are equal.
+#[derive(Debug, Clone, Copy)]
+pub struct CanonicalKmer<'a> {
+    window: &'a [u8],
+    reverse: bool,
+}
+
+impl<'a> CanonicalKmer<'a> {
+    /// The canonical form of the k-mer `window`
+    pub fn new(window: &'a [u8]) -> Self {
+        let forward = window.iter().map(u8::to_ascii_uppercase);
+        let reverse = window
+            .iter()
+            .rev()
+            .map(|&base| complement(base).to_ascii_uppercase());
+        CanonicalKmer {
+            window,
+            reverse: forward.cmp(reverse) == Ordering::Greater,
+        }
+    }
+
+    /// The window of the sequence the k-mer was taken from
+    pub fn window(&self) -> &'a [u8] {
+        self.window
+    }
+
+    /// Whether the canonical k-mer is the reverse complement of the window
+    pub fn is_reverse(&self) -> bool {
+        self.reverse
+    }
+
+    /// The length of the k-mer
+    pub fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Whether the k-mer is empty
+    pub fn is_empty(&self) -> bool {
+        self.window.is_empty()
+    }
+
+    /// The (upper-cased) bases of the canonical k-mer
+    pub fn bases(&self) -> impl Iterator<Item = u8> + 'a {
+        let (window, reverse) = (self.window, self.reverse);
+        (0..window.len()).map(move |i| {
+            if reverse {
+                complement(window[window.len() - 1 - i]).to_ascii_uppercase()
+            } else {
+                window[i].to_ascii_uppercase()
+            }
+        })
+    }
+
+    /// The bases of the canonical k-mer, copied
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.bases().collect()
+    }
+}
+
+impl PartialEq for CanonicalKmer<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.bases().eq(other.bases())
+    }
+}
+
+impl Eq for CanonicalKmer<'_> {}
+
+impl PartialOrd for CanonicalKmer<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CanonicalKmer<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bases().cmp(other.bases())
+    }
+}
+
+impl Hash for CanonicalKmer<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for base in self.bases() {
+            state.write_u8(base);
+        }
+    }
+}
+
+impl fmt::Display for CanonicalKmer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for base in self.bases() {
+            write!(f, "{}", base as char)?;
+        }
+        Ok(())
+    }
+}
+
+/// An iterator over the canonical k-mers of a sequence, from
+/// `canonical_kmers`
+#[derive(Debug, Clone)]
+pub struct CanonicalKmers<'a> {
+    kmers: Kmers<'a>,
+}
+
+/// The canonical k-mers of `sequence`, in order, for counting k-mers
+/// without regard to strand
+///
+/// ```
+/// use prseq::kmer::canonical_kmers;
+///
+/// let kmers: Vec<String> = canonical_kmers(b"AATT", 3).map(|k| k.to_string()).collect();
+/// assert_eq!(kmers, ["AAT", "AAT"]);
+/// ```
+pub fn canonical_kmers(sequence: &[u8], k: usize) -> CanonicalKmers<'_> {
+    CanonicalKmers {
+        kmers: kmers(sequence, k),
+    }
+}
+
+impl<'a> Iterator for CanonicalKmers<'a> {
+    type Item = CanonicalKmer<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.kmers.next().map(CanonicalKmer::new)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.kmers.size_hint()
+    }
+}
+
+impl ExactSizeIterator for CanonicalKmers<'_> {}
+
+impl FastaRecord {
+    /// The k-mers of the sequence (see `kmers`)
+    ///
+    /// ```
+    /// use prseq::FastaRecord;
+    ///
+    /// let record = FastaRecord { header: "s1".into(), sequence: "ACGT".into() };
+    /// assert_eq!(record.kmers(2).count(), 3);
+    /// ```
+    pub fn kmers(&self, k: usize) -> Kmers<'_> {
+        kmers(self.sequence.as_bytes(), k)
+    }
+
+    /// The canonical k-mers of the sequence (see `canonical_kmers`)
+    pub fn canonical_kmers(&self, k: usize) -> CanonicalKmers<'_> {
+        canonical_kmers(self.sequence.as_bytes(), k)
+    }
+}
+
+impl FastqRecord {
+    /// The k-mers of the sequence (see `kmers`)
+    pub fn kmers(&self, k: usize) -> Kmers<'_> {
+        kmers(self.sequence.as_bytes(), k)
+    }
+
+    /// The canonical k-mers of the sequence (see `canonical_kmers`)
+    pub fn canonical_kmers(&self, k: usize) -> CanonicalKmers<'_> {
+        canonical_kmers(self.sequence.as_bytes(), k)
+    }
+}
+
+/// The 2-bit code of a base (A, C, G, T or U, in either case), or None for
+/// anything else
+fn base_code(base: u8) -> Option<u64> {
+    match base {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' | b'U' | b'u' => Some(3),
+        _ => None,
+    }
+}
+
+/// The 2-bit codes of a k-mer, in the order its bases are given
+fn encode(kmer: &[u8]) -> Option<u64> {
+    kmer.iter()
+        .try_fold(0, |code, &base| Some(code << 2 | base_code(base)?))
+}
+
+/// The 2-bit code of the reverse complement of a k-mer's code
+fn reverse_complement_code(code: u64, k: usize) -> u64 {
+    (0..k).fold(0, |reverse, i| reverse << 2 | (3 - (code >> (2 * i) & 3)))
+}
+
+fn decode(code: u64, k: usize) -> Vec<u8> {
+    (0..k)
+        .rev()
+        .map(|i| b"ACGT"[(code >> (2 * i) & 3) as usize])
+        .collect()
+}
+
+/// The longest k-mers that are packed into 64 bits
+const MAX_PACKED_K: usize = 32;
+
+/// Call `f` with the 2-bit code of each k-mer of `sequence` made only of
+/// A, C, G and T (k at most 32), rolling the codes along the sequence
+pub(crate) fn for_each_packed(sequence: &[u8], k: usize, canonical: bool, mut f: impl FnMut(u64)) {
+    let mask = if k == MAX_PACKED_K {
+        u64::MAX
+    } else {
+        (1 << (2 * k)) - 1
+    };
+    let top = 2 * (k as u64 - 1);
+    let (mut forward, mut reverse, mut valid) = (0u64, 0u64, 0usize);
+    for &base in sequence {
+        let Some(code) = base_code(base) else {
+            valid = 0;
+            continue;
+        };
+        forward = (forward << 2 | code) & mask;
+        reverse = reverse >> 2 | (3 - code) << top;
+        valid += 1;
+        if valid >= k {
+            f(if canonical {
+                forward.min(reverse)
+            } else {
+                forward
+            });
+        }
+    }
+}
+
+/// Put the upper-cased k-mer (with U as T), or the smaller of it and its
+/// reverse complement, in `buffer`. False if it is not all A, C, G and T.
+fn normalize_kmer(kmer: &[u8], canonical: bool, buffer: &mut Vec<u8>) -> bool {
+    buffer.clear();
+    for &base in kmer {
+        match base_code(base) {
+            Some(code) => buffer.push(b"ACGT"[code as usize]),
+            None => return false,
+        }
+    }
+    if canonical {
+        let reverse = buffer.iter().rev().map(|&base| complement(base));
+        if reverse.clone().lt(buffer.iter().copied()) {
+            let reverse: Vec<u8> = reverse.collect();
+            buffer.copy_from_slice(&reverse);
+        }
+    }
+    true
+}
+
+/// The stored form of each k-mer counted
+#[derive(Debug, Clone)]
+enum Counts {
+    /// 2-bit codes, for k up to 32
+    Packed(HashMap<u64, u64>),
+    /// Upper-case bases, for longer k
+    Bytes(HashMap<Vec<u8>, u64>),
+}
+
+/// Exact counts of the k-mers of a set of sequences, accumulated one record
+/// at a time
+///
+/// Only k-mers made of A, C, G and T (or U, counted as T) are counted, in
+/// either case; k-mers holding Ns or other codes are skipped. With k up to
+/// 32, each k-mer is stored in a 64-bit integer, so memory grows with the
+/// number of distinct k-mers, not their length. For bounded memory, see
+/// `CountMinSketch`.
+///
+/// ```
+/// use prseq::kmer::Counter;
+/// use prseq::FastaReader;
+///
+/// let reader = FastaReader::from_string(">a\nACGTAC\n>b\nGTAC\n").unwrap();
+/// let counter = Counter::new(3).collect(reader).unwrap();
+/// assert_eq!(counter.count(b"GTA"), 2);
+/// assert_eq!(counter.counts()[0], (b"ACG".to_vec(), 1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Counter {
+    k: usize,
+    canonical: bool,
+    counts: Counts,
+    total: u64,
+    buffer: Vec<u8>,
+}
+
+impl Counter {
+    /// A counter of k-mers of length `k`, which must be at least 1
+    pub fn new(k: usize) -> Self {
+        assert!(k > 0, "k-mers must be at least 1 base long");
+        Counter {
+            k,
+            canonical: false,
+            counts: if k <= MAX_PACKED_K {
+                Counts::Packed(HashMap::new())
+            } else {
+                Counts::Bytes(HashMap::new())
+            },
+            total: 0,
+            buffer: Vec::with_capacity(k),
+        }
+    }
+
+    /// Set whether k-mers are counted as their canonical form, so that a
+    /// k-mer and its reverse complement are counted together. Set this
+    /// before adding sequences.
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// The length of the k-mers counted
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Count the k-mers of a sequence
+    pub fn add(&mut self, sequence: &[u8]) {
+        match &mut self.counts {
+            Counts::Packed(counts) => {
+                for_each_packed(sequence, self.k, self.canonical, |code| {
+                    *counts.entry(code).or_default() += 1;
+                    self.total += 1;
+                });
+            }
+            Counts::Bytes(counts) => {
+                for kmer in kmers(sequence, self.k) {
+                    if !normalize_kmer(kmer, self.canonical, &mut self.buffer) {
+                        continue;
+                    }
+                    match counts.get_mut(&self.buffer) {
+                        Some(count) => *count += 1,
+                        None => {
+                            counts.insert(self.buffer.clone(), 1);
+                        }
+                    }
+                    self.total += 1;
+                }
+            }
+        }
+    }
+
+    /// Count the k-mers of a record's sequence
+    pub fn add_record<R: SequenceRecord + ?Sized>(&mut self, record: &R) {
+        self.add(record.sequence().as_bytes());
+    }
+
+    /// Count the k-mers of a reader (or any iterator of record results) in
+    /// one pass, stopping at the first error
+    pub fn collect<I, R>(mut self, records: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = Result<R>>,
+        R: SequenceRecord,
+    {
+        for record in records {
+            self.add_record(&record?);
+        }
+        Ok(self)
+    }
+
+    /// Add the counts of another counter with the same k and strandedness,
+    /// e.g. one that counted another part of the input
+    pub fn merge(&mut self, other: &Counter) {
+        assert!(
+            self.k == other.k && self.canonical == other.canonical,
+            "merged k-mer counters must count the same k-mers"
+        );
+        match (&mut self.counts, &other.counts) {
+            (Counts::Packed(counts), Counts::Packed(other)) => {
+                for (&code, &count) in other {
+                    *counts.entry(code).or_default() += count;
+                }
+            }
+            (Counts::Bytes(counts), Counts::Bytes(other)) => {
+                for (kmer, &count) in other {
+                    *counts.entry(kmer.clone()).or_default() += count;
+                }
+            }
+            _ => unreachable!("counters with the same k store k-mers the same way"),
+        }
+        self.total += other.total;
+    }
+
+    /// The number of times a k-mer was seen (as its canonical form, if
+    /// counting canonical k-mers)
+    pub fn count(&self, kmer: &[u8]) -> u64 {
+        if kmer.len() != self.k {
+            return 0;
+        }
+        match &self.counts {
+            Counts::Packed(counts) => {
+                let Some(mut code) = encode(kmer) else {
+                    return 0;
+                };
+                if self.canonical {
+                    code = code.min(reverse_complement_code(code, self.k));
+                }
+                counts.get(&code).copied().unwrap_or(0)
+            }
+            Counts::Bytes(counts) => {
+                let mut buffer = Vec::with_capacity(self.k);
+                if !normalize_kmer(kmer, self.canonical, &mut buffer) {
+                    return 0;
+                }
+                counts.get(&buffer).copied().unwrap_or(0)
+            }
+        }
+    }
+
+    /// The number of distinct k-mers seen
+    pub fn distinct(&self) -> usize {
+        match &self.counts {
+            Counts::Packed(counts) => counts.len(),
+            Counts::Bytes(counts) => counts.len(),
+        }
+    }
+
+    /// The number of k-mers seen
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Each k-mer seen (upper case) and its count, sorted by k-mer
+    pub fn counts(&self) -> Vec<(Vec<u8>, u64)> {
+        match &self.counts {
+            Counts::Packed(counts) => {
+                // A, C, G and T have codes in alphabetical order, so codes
+                // sort as their k-mers do.
+                let mut codes: Vec<(u64, u64)> =
+                    counts.iter().map(|(&code, &count)| (code, count)).collect();
+                codes.sort_unstable();
+                codes
+                    .into_iter()
+                    .map(|(code, count)| (decode(code, self.k), count))
+                    .collect()
+            }
+            Counts::Bytes(counts) => {
+                let mut counts: Vec<(Vec<u8>, u64)> = counts
+                    .iter()
+                    .map(|(kmer, &count)| (kmer.clone(), count))
+                    .collect();
+                counts.sort_unstable();
+                counts
+            }
+        }
+    }
+}
+
+impl<R: SequenceRecord> Extend<R> for Counter {
+    fn extend<I: IntoIterator<Item = R>>(&mut self, records: I) {
+        for record in records {
+            self.add_record(&record);
+        }
+    }
+}
+
+/// Approximate counts of the k-mers of a set of sequences in a fixed amount
+/// of memory (a count-min sketch)
+///
+/// Each k-mer is counted in one cell of each of `depth` rows of `width`
+/// cells, and its count is estimated as the smallest of its cells. The
+/// estimate is never too low, and is too high by at most about
+/// `e / width` of the total number of k-mers with probability
+/// `1 - exp(-depth)`. The k-mers counted are those `Counter` counts, but
+/// they cannot be listed.
+///
+/// ```
+/// use prseq::kmer::CountMinSketch;
+///
+/// let mut sketch = CountMinSketch::new(3, 1 << 16, 4);
+/// sketch.add(b"ACGTACG");
+/// assert_eq!(sketch.count(b"ACG"), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CountMinSketch {
+    k: usize,
+    canonical: bool,
+    width: usize,
+    depth: usize,
+    cells: Vec<u64>,
+    total: u64,
+    buffer: Vec<u8>,
+}
+
+impl CountMinSketch {
+    /// A sketch of k-mers of length `k`, with `depth` rows of `width`
+    /// counts each (all at least 1)
+    pub fn new(k: usize, width: usize, depth: usize) -> Self {
+        assert!(k > 0, "k-mers must be at least 1 base long");
+        assert!(
+            width > 0 && depth > 0,
+            "count-min sketches need at least one cell"
+        );
+        CountMinSketch {
+            k,
+            canonical: false,
+            width,
+            depth,
+            cells: vec![0; width * depth],
+            total: 0,
+            buffer: Vec::with_capacity(k),
+        }
+    }
+
+    /// Set whether k-mers are counted as their canonical form. Set this
+    /// before adding sequences.
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// The length of the k-mers counted
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The cells of a k-mer's hash, one per row
+    fn cells(width: usize, depth: usize, hash: u64) -> impl Iterator<Item = usize> {
+        // Double hashing: row i uses h1 + i * h2.
+        let (h1, h2) = (hash & 0xffff_ffff, hash >> 32 | 1);
+        (0..depth).map(move |row| {
+            let cell = h1.wrapping_add((row as u64).wrapping_mul(h2)) % width as u64;
+            row * width + cell as usize
+        })
+    }
+
+    /// Count the k-mers of a sequence
+    pub fn add(&mut self, sequence: &[u8]) {
+        let (width, depth) = (self.width, self.depth);
+        let cells = &mut self.cells;
+        let mut added = 0;
+        let mut increment = |hash| {
+            for cell in Self::cells(width, depth, hash) {
+                cells[cell] += 1;
+            }
+            added += 1;
+        };
+        for_each_kmer_hash(
+            sequence,
+            self.k,
+            self.canonical,
+            &mut self.buffer,
+            &mut increment,
+        );
+        self.total += added;
+    }
+
+    /// Count the k-mers of a record's sequence
+    pub fn add_record<R: SequenceRecord + ?Sized>(&mut self, record: &R) {
+        self.add(record.sequence().as_bytes());
+    }
+
+    /// Count the k-mers of a reader (or any iterator of record results) in
+    /// one pass, stopping at the first error
+    pub fn collect<I, R>(mut self, records: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = Result<R>>,
+        R: SequenceRecord,
+    {
+        for record in records {
+            self.add_record(&record?);
+        }
+        Ok(self)
+    }
+
+    /// The estimated number of times a k-mer was seen
+    pub fn count(&self, kmer: &[u8]) -> u64 {
+        if kmer.len() != self.k {
+            return 0;
+        }
+        let Some(hash) = kmer_hash(kmer, self.canonical) else {
+            return 0;
+        };
+        Self::cells(self.width, self.depth, hash)
+            .map(|cell| self.cells[cell])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// The number of k-mers seen
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+impl<R: SequenceRecord> Extend<R> for CountMinSketch {
+    fn extend<I: IntoIterator<Item = R>>(&mut self, records: I) {
+        for record in records {
+            self.add_record(&record);
+        }
+    }
+}
+
+fn hash_code(code: u64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write_u64(code);
+    hasher.finish()
+}
+
+fn hash_bytes(kmer: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(kmer);
+    hasher.finish()
+}
+
+/// Call `f` with a hash of each k-mer of `sequence` made only of A, C, G
+/// and T, as given by `kmer_hash`. `buffer` is used for k-mers too long to
+/// pack into 64 bits.
+pub(crate) fn for_each_kmer_hash(
+    sequence: &[u8],
+    k: usize,
+    canonical: bool,
+    buffer: &mut Vec<u8>,
+    mut f: impl FnMut(u64),
+) {
+    if k <= MAX_PACKED_K {
+        for_each_packed(sequence, k, canonical, |code| f(hash_code(code)));
+    } else {
+        for kmer in kmers(sequence, k) {
+            if normalize_kmer(kmer, canonical, buffer) {
+                f(hash_bytes(buffer));
+            }
+        }
+    }
+}
+
+/// A 64-bit hash of a k-mer (or of its canonical form), the same for
+/// either case and for U and T, or None if it is not all A, C, G and T
+pub(crate) fn kmer_hash(kmer: &[u8], canonical: bool) -> Option<u64> {
+    if kmer.len() <= MAX_PACKED_K {
+        let mut code = encode(kmer)?;
+        if canonical {
+            code = code.min(reverse_complement_code(code, kmer.len()));
+        }
+        Some(hash_code(code))
+    } else {
+        let mut buffer = Vec::with_capacity(kmer.len());
+        normalize_kmer(kmer, canonical, &mut buffer).then(|| hash_bytes(&buffer))
+    }
+}
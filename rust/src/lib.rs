@@ -1,9 +1,150 @@
+pub mod alphabet;
+pub mod any;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "async")]
+pub mod async_reader;
+#[cfg(feature = "bam")]
+pub mod bam;
+pub mod bgzf;
+pub mod builder;
+pub mod checkpoint;
+#[cfg(feature = "checksum")]
+pub mod checksum;
 mod common;
+pub mod complexity;
+pub mod convert;
+pub mod count;
+pub mod dedupe;
+pub mod defline;
+pub mod demux;
+pub mod diff;
+pub mod embl;
+pub mod error;
 pub mod fasta;
 pub mod fastq;
+pub mod filter;
+pub mod genbank;
+pub mod homopolymer;
+pub mod illumina;
+pub mod index;
+pub mod indexed;
+#[cfg(feature = "serde")]
+pub mod jsonl;
+pub mod kmer;
+pub mod line_ending;
+pub mod location;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod msa;
+pub mod multi;
+pub mod nanopore;
+pub mod normalize;
+#[cfg(feature = "object_store")]
+pub mod object;
+pub mod options;
+pub mod packed;
+pub mod paired;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod pipeline;
+pub mod prefetch;
+pub mod progress;
+pub mod qual;
+pub mod quality;
+pub mod record;
+pub mod region;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod rename;
+pub mod sam;
+pub mod sample;
+pub mod search;
+pub mod select;
+pub mod sequence;
+pub mod sketch;
+pub mod sort;
+pub mod split;
+pub mod stats;
+pub mod tabular;
+pub mod translate;
+pub mod trim;
+pub mod twobit;
+pub mod umi;
+pub mod writer;
+
+// Re-export the error types
+pub use error::{Position, PrseqError};
 
 // Re-export the main FASTA types for backward compatibility
-pub use fasta::{read_fasta, read_fasta_with_capacity, FastaReader, FastaRecord};
+pub use fasta::{
+    read_fasta, read_fasta_with_capacity, FastaReader, FastaRecord, FastaRecordBytes,
+    RefFastaRecord,
+};
+
+// Re-export the record trait
+pub use record::SequenceRecord;
 
 // Re-export FASTQ types
-pub use fastq::{read_fastq, read_fastq_with_capacity, FastqReader, FastqRecord};
+pub use fastq::{
+    read_fastq, read_fastq_with_capacity, FastqReader, FastqRecord, FastqRecordBytes,
+    RefFastqRecord,
+};
+pub use paired::{
+    deinterleave, interleave, InterleavedFastqReader, PairedFastqReader, PairedFilterExt,
+};
+
+// Re-export header parsing types
+pub use defline::Defline;
+pub use illumina::IlluminaHeader;
+pub use nanopore::NanoporeHeader;
+
+// Re-export record filtering types
+pub use filter::{FilterExt, FilterRecords, Filterable, IdMatch, IdSet};
+
+// Re-export background reading types
+#[cfg(feature = "async")]
+pub use async_reader::{AsyncFastaReader, AsyncFastqReader};
+#[cfg(feature = "rayon")]
+pub use parallel::ParallelRecords;
+pub use prefetch::{spawn_reader, PrefetchReader};
+
+// Re-export parsing option types
+pub use alphabet::Alphabet;
+pub use line_ending::{LineEnding, LineEndings};
+pub use options::{DuplicateIds, EmptyRecords, OnError, ParserOptions, SoftMask};
+pub use quality::QualityEncoding;
+
+// Re-export statistics types
+pub use stats::{collect_quality_profile, collect_stats, QualityProfile, SeqStats};
+
+// Re-export record location types
+pub use location::{Located, Location};
+
+// Re-export multi-file reader types
+pub use multi::{MultiFastaReader, MultiFastqReader, MultiFileReader};
+
+// Re-export writer types
+pub use writer::{FastaWriter, FastqWriter, RecordWriter};
+
+// Re-export pipeline types
+pub use pipeline::{Pipeline, PipelineRecords, PipelineStats};
+
+// Re-export FASTA index types
+pub use index::{FaiRecord, FastaIndex};
+pub use indexed::IndexedFastaReader;
+#[cfg(feature = "mmap")]
+pub use mmap::MmapFastaReader;
+pub use region::Region;
+
+// Re-export BGZF types
+pub use bgzf::{BgzfReader, GziIndex, VirtualOffset};
+
+// Re-export reader construction types
+pub use builder::ReaderBuilder;
+pub use common::Compression;
+
+// Re-export format-detecting reader types
+pub use any::{AnyReader, AnyRecord, SequenceFormat};
@@ -0,0 +1,87 @@
+/// A style of line terminator
+///
+/// The readers accept all three, in any mix: a "\r" that is not followed
+/// by "\n" ends a line just as "\n" does. `line_endings()` on a reader
+/// reports which were read, so that a writer can be set to write the same.
+///
+/// ```
+/// use prseq::{FastaReader, FastaWriter, LineEnding};
+///
+/// let mut reader = FastaReader::from_bytes(b">a\r\nACGT\r\nAC\r\n").unwrap();
+/// let record = reader.next().unwrap().unwrap();
+/// assert_eq!(record.sequence, "ACGTAC");
+/// let ending = reader.line_endings().style().unwrap_or_default();
+/// assert_eq!(ending, LineEnding::CrLf);
+///
+/// let mut writer = FastaWriter::new(Vec::new()).line_ending(ending);
+/// writer.write_record(&record).unwrap();
+/// assert_eq!(writer.into_inner(), b">a\r\nACGTAC\r\n");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineEnding {
+    /// "\n", as on Unix (the default)
+    #[default]
+    Lf,
+    /// "\r\n", as on Windows
+    CrLf,
+    /// A lone "\r", as on classic Mac OS
+    Cr,
+}
+
+impl LineEnding {
+    /// The terminator's bytes
+    pub fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+            LineEnding::Cr => b"\r",
+        }
+    }
+}
+
+/// The number of lines read with each style of terminator, from a reader's
+/// `line_endings()`
+///
+/// A last line with no terminator is not counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineEndings {
+    pub lf: u64,
+    pub crlf: u64,
+    pub cr: u64,
+}
+
+impl LineEndings {
+    /// The most common style (LF, then CRLF, in a tie), or None if no
+    /// line terminator has been read
+    pub fn style(&self) -> Option<LineEnding> {
+        [
+            (self.lf, LineEnding::Lf),
+            (self.crlf, LineEnding::CrLf),
+            (self.cr, LineEnding::Cr),
+        ]
+        .into_iter()
+        .filter(|&(count, _)| count > 0)
+        .reduce(|most, other| if other.0 > most.0 { other } else { most })
+        .map(|(_, style)| style)
+    }
+
+    /// Whether more than one style has been read
+    pub fn is_mixed(&self) -> bool {
+        [self.lf, self.crlf, self.cr]
+            .iter()
+            .filter(|&&count| count > 0)
+            .count()
+            > 1
+    }
+
+    /// Count a line terminator
+    pub(crate) fn add(&mut self, ending: LineEnding) {
+        match ending {
+            LineEnding::Lf => self.lf += 1,
+            LineEnding::CrLf => self.crlf += 1,
+            LineEnding::Cr => self.cr += 1,
+        }
+    }
+}
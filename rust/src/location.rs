@@ -0,0 +1,90 @@
+use crate::error::{Position, Result};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Where a record was read from: its file (when known), the line its
+/// header is on, and its index among the file's records, for pointing
+/// users at a record in messages about it
+///
+/// ```
+/// use prseq::FastqReader;
+/// # let dir = tempfile::tempdir().unwrap();
+/// # let path = dir.path().join("reads.fastq");
+/// # std::fs::write(&path, "@r1\nACGT\n+\nIIII\n@r2\nNNNN\n+\n!!!!\n").unwrap();
+///
+/// for result in FastqReader::from_file(&path).unwrap().with_locations() {
+///     let (location, record) = result.unwrap();
+///     if record.sequence.bytes().all(|base| base == b'N') {
+///         eprintln!("{}: read '{}' has no called bases", location, record.id());
+///         # assert_eq!((location.line_number, location.record_index), (5, 1));
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Location {
+    /// The file the record was read from, if the reader was made from a
+    /// path
+    pub file: Option<Arc<Path>>,
+    /// The 1-based number of the line the record starts on
+    pub line_number: u64,
+    /// The 0-based index of the record in its file, counting any malformed
+    /// records skipped before it
+    pub record_index: u64,
+}
+
+impl Location {
+    /// The location as a `Position`, as used in errors (whose record
+    /// numbers are 1-based)
+    pub fn position(&self) -> Position {
+        Position {
+            line: self.line_number,
+            record: self.record_index + 1,
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}, ", file.display())?;
+        }
+        write!(f, "{}", self.position())
+    }
+}
+
+/// Readers that can say where the record they most recently returned was
+/// read from
+pub trait Located {
+    /// The location of the most recently returned record
+    fn location(&self) -> Location;
+}
+
+/// Iterator over records paired with their locations, created by
+/// `with_locations()` on the readers
+pub struct WithLocations<R> {
+    reader: R,
+}
+
+impl<R> WithLocations<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        WithLocations { reader }
+    }
+
+    /// The underlying reader, e.g. to query its `stats()`
+    pub fn reader(&self) -> &R {
+        &self.reader
+    }
+}
+
+impl<R, T> Iterator for WithLocations<R>
+where
+    R: Iterator<Item = Result<T>> + Located,
+{
+    type Item = Result<(Location, T)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.reader.next()?;
+        Some(record.map(|record| (self.reader.location(), record)))
+    }
+}
@@ -0,0 +1,177 @@
+use crate::common::{split_header, utf8_str};
+use crate::error::{Position, PrseqError, Result};
+use crate::fasta::FastaRecord;
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::fs::File;
+use std::path::Path;
+
+/// A FASTA reader over a memory-mapped, uncompressed file
+///
+/// Records are parsed directly from the mapped bytes and borrow from the
+/// reader, so reading involves no read calls and no per-record allocation.
+/// The file must not be modified while it is mapped.
+pub struct MmapFastaReader {
+    mmap: Mmap,
+}
+
+impl MmapFastaReader {
+    /// Memory-map an uncompressed FASTA file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the mapping is read-only; as documented above, the caller
+        // must not modify the file while it is mapped.
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(MmapFastaReader { mmap })
+    }
+
+    /// Iterate over the records in the file
+    pub fn records(&self) -> MmapFastaRecords<'_> {
+        MmapFastaRecords::new(&self.mmap)
+    }
+
+    /// The mapped bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+}
+
+/// A FASTA record borrowed from mapped bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MmapFastaRecord<'a> {
+    header: &'a [u8],
+    raw_sequence: &'a [u8],
+}
+
+impl<'a> MmapFastaRecord<'a> {
+    /// The header line, without its '>'
+    pub fn header(&self) -> &'a [u8] {
+        self.header
+    }
+
+    /// The ID: the header up to the first whitespace
+    pub fn id(&self) -> &'a [u8] {
+        split_header(self.header).0
+    }
+
+    /// The description: the rest of the header after the ID and the
+    /// whitespace following it, if there is any
+    pub fn description(&self) -> Option<&'a [u8]> {
+        split_header(self.header).1
+    }
+
+    /// The sequence lines exactly as they appear in the file, including any
+    /// line terminators between them
+    pub fn raw_sequence(&self) -> &'a [u8] {
+        self.raw_sequence
+    }
+
+    /// The lines of the sequence, without line terminators or blank lines
+    pub fn sequence_lines(&self) -> impl Iterator<Item = &'a [u8]> {
+        self.raw_sequence
+            .split(|&b| b == b'\n' || b == b'\r')
+            .map(|line| line.trim_ascii())
+            .filter(|line| !line.is_empty())
+    }
+
+    /// The sequence, borrowed if it is on a single line and copied otherwise
+    pub fn sequence(&self) -> Cow<'a, [u8]> {
+        if !self.raw_sequence.iter().any(|b| b.is_ascii_whitespace()) {
+            return Cow::Borrowed(self.raw_sequence);
+        }
+        let mut sequence = Vec::with_capacity(self.raw_sequence.len());
+        for line in self.sequence_lines() {
+            sequence.extend_from_slice(line);
+        }
+        Cow::Owned(sequence)
+    }
+
+    /// Copy this record into an owned FastaRecord
+    pub fn to_record(&self) -> Result<FastaRecord> {
+        Ok(FastaRecord {
+            header: utf8_str(self.header, "FASTA", None)?.to_string(),
+            sequence: utf8_str(&self.sequence(), "FASTA", None)?.to_string(),
+        })
+    }
+}
+
+/// Iterator over the FASTA records in a byte slice
+pub struct MmapFastaRecords<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> MmapFastaRecords<'a> {
+    /// Iterate over the FASTA records in `data`
+    pub fn new(data: &'a [u8]) -> Self {
+        MmapFastaRecords { data, position: 0 }
+    }
+
+    /// Return the next line (without its "\n", "\r\n" or "\r"), advancing
+    /// past it
+    fn next_line(&mut self) -> Option<&'a [u8]> {
+        if self.position >= self.data.len() {
+            return None;
+        }
+        let rest = &self.data[self.position..];
+        let end = memchr::memchr2(b'\n', b'\r', rest).unwrap_or(rest.len());
+        let terminator = if rest[end..].starts_with(b"\r\n") {
+            2
+        } else {
+            1
+        };
+        self.position += (end + terminator).min(rest.len());
+        Some(&rest[..end])
+    }
+}
+
+impl<'a> Iterator for MmapFastaRecords<'a> {
+    type Item = Result<MmapFastaRecord<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut lines = 0;
+        let header = loop {
+            let line = self.next_line()?.trim_ascii();
+            lines += 1;
+            if line.is_empty() {
+                continue;
+            }
+            match line.strip_prefix(b">") {
+                Some(header) => break header,
+                None => {
+                    // Every record after the first starts at a '>' line, so
+                    // this is the first record, after `lines` lines. Skip to
+                    // the end so iteration stops after the error.
+                    self.position = self.data.len();
+                    return Some(Err(PrseqError::invalid_fasta(
+                        "FASTA record must start with '>'",
+                        Position {
+                            line: lines,
+                            record: 1,
+                        },
+                        line,
+                    )));
+                }
+            }
+        };
+
+        let start = self.position;
+        let mut end = start;
+        loop {
+            let line_start = self.position;
+            match self.next_line() {
+                Some(line) if line.trim_ascii_start().starts_with(b">") => {
+                    self.position = line_start;
+                    break;
+                }
+                Some(_) => end = self.position,
+                None => break,
+            }
+        }
+
+        Some(Ok(MmapFastaRecord {
+            header,
+            raw_sequence: self.data[start..end].trim_ascii(),
+        }))
+    }
+}
@@ -0,0 +1,832 @@
+use crate::common::{create_reader_with_compression, utf8_str, LineReader};
+use crate::error::{Position, PrseqError, Result};
+use crate::fasta::FastaRecord;
+use crate::sequence::base_bits;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// The multiple sequence alignment formats that can be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlignmentFormat {
+    Clustal,
+    Phylip,
+    Stockholm,
+}
+
+/// The headers that start Clustal-format output, from Clustal and from the
+/// aligners that imitate it
+const CLUSTAL_HEADERS: [&[u8]; 3] = [b"CLUSTAL", b"MUSCLE", b"PROBCONS"];
+
+/// A reader of multiple sequence alignments, each returned as aligned
+/// `FastaRecord`s with their gap characters kept
+pub trait AlignmentReader {
+    /// Read the next alignment, returning None at end of input
+    fn read_alignment(&mut self) -> Result<Option<Vec<FastaRecord>>>;
+
+    /// The records of all the alignments, in order, as for writing them as
+    /// aligned FASTA
+    fn records(mut self) -> impl Iterator<Item = Result<FastaRecord>>
+    where
+        Self: Sized,
+    {
+        let mut pending = Vec::new().into_iter();
+        std::iter::from_fn(move || loop {
+            if let Some(record) = pending.next() {
+                return Some(Ok(record));
+            }
+            match self.read_alignment() {
+                Ok(Some(records)) => pending = records.into_iter(),
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        })
+    }
+}
+
+impl<A: AlignmentReader + ?Sized> AlignmentReader for Box<A> {
+    fn read_alignment(&mut self) -> Result<Option<Vec<FastaRecord>>> {
+        (**self).read_alignment()
+    }
+}
+
+/// The sequences of an alignment given in blocks, in the order their names
+/// first appear
+#[derive(Default)]
+struct Blocks {
+    records: Vec<FastaRecord>,
+    positions: HashMap<String, usize>,
+}
+
+impl Blocks {
+    fn push(&mut self, name: &str, residues: &str) {
+        let index = match self.positions.get(name) {
+            Some(&index) => index,
+            None => {
+                self.positions.insert(name.to_string(), self.records.len());
+                self.records.push(FastaRecord {
+                    header: name.to_string(),
+                    sequence: String::new(),
+                });
+                self.records.len() - 1
+            }
+        };
+        self.records[index].sequence.push_str(residues);
+    }
+
+    /// The records, once checked to all be the same length
+    fn finish(self, format: &'static str, position: Position) -> Result<Vec<FastaRecord>> {
+        check_lengths(&self.records, format, position)?;
+        Ok(self.records)
+    }
+}
+
+fn check_lengths(records: &[FastaRecord], format: &'static str, position: Position) -> Result<()> {
+    if let Some(first) = records.first() {
+        if let Some(other) = records
+            .iter()
+            .find(|record| record.sequence.len() != first.sequence.len())
+        {
+            return Err(PrseqError::invalid_format(
+                format,
+                format!(
+                    "{} alignment sequences differ in length: '{}' has {} but '{}' has {}",
+                    format,
+                    first.header,
+                    first.sequence.len(),
+                    other.header,
+                    other.sequence.len()
+                ),
+                position,
+                &[],
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Split a "name residues" line of a block into its name and residues
+fn name_and_residues<'a>(
+    text: &'a str,
+    format: &'static str,
+    position: Position,
+) -> Result<(&'a str, &'a str)> {
+    let mut words = text.split_whitespace();
+    match (words.next(), words.next()) {
+        (Some(name), Some(residues)) => Ok((name, residues)),
+        _ => Err(PrseqError::invalid_format(
+            format,
+            format!("{} line must have a name and residues", format),
+            position,
+            text.as_bytes(),
+        )),
+    }
+}
+
+fn open<R: Read + Send + 'static>(reader: R) -> Result<LineReader> {
+    let buf_reader = create_reader_with_compression(reader)?;
+    Ok(LineReader::new(Box::new(buf_reader)))
+}
+
+/// Reader of Clustal alignments (`.aln` files, as written by Clustal W and
+/// Clustal Omega, MUSCLE and others)
+///
+/// The conservation lines under each block and the residue counts at the
+/// ends of lines are ignored.
+///
+/// ```
+/// use prseq::msa::{AlignmentReader, ClustalReader};
+///
+/// let input = b"CLUSTAL W (1.83) multiple sequence alignment
+///
+/// seq1    ACG-T 4
+/// seq2    AC-GT 4
+///         **  *
+/// ";
+/// let mut reader = ClustalReader::from_reader(&input[..]).unwrap();
+/// let records = reader.read_alignment().unwrap().unwrap();
+/// assert_eq!(records[1].sequence, "AC-GT");
+/// ```
+pub struct ClustalReader {
+    lines: LineReader,
+    alignments: u64,
+    /// Whether the header of the next alignment has been read
+    started: bool,
+}
+
+impl ClustalReader {
+    /// Create a new ClustalReader from a file path
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Create a new ClustalReader from any readable source with compression
+    /// detection
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> Result<Self> {
+        Ok(Self::from_line_reader(open(reader)?))
+    }
+
+    fn from_line_reader(lines: LineReader) -> Self {
+        ClustalReader {
+            lines,
+            alignments: 0,
+            started: false,
+        }
+    }
+}
+
+impl AlignmentReader for ClustalReader {
+    fn read_alignment(&mut self) -> Result<Option<Vec<FastaRecord>>> {
+        let mut blocks = Blocks::default();
+        loop {
+            let position = Position {
+                line: self.lines.line() + 1,
+                record: self.alignments + 1,
+            };
+            let Some((line, _)) = self.lines.next_line()? else {
+                break;
+            };
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            if CLUSTAL_HEADERS
+                .iter()
+                .any(|header| line.starts_with(header))
+            {
+                if self.started {
+                    // The start of another alignment
+                    self.alignments += 1;
+                    return blocks.finish("Clustal", position).map(Some);
+                }
+                self.started = true;
+                continue;
+            }
+            if !self.started {
+                return Err(PrseqError::invalid_format(
+                    "Clustal",
+                    "Clustal alignment must start with a 'CLUSTAL' header line",
+                    position,
+                    line,
+                ));
+            }
+            if line[0].is_ascii_whitespace() {
+                // A conservation line
+                continue;
+            }
+            let text = utf8_str(line, "Clustal", Some(position))?;
+            let (name, residues) = name_and_residues(text, "Clustal", position)?;
+            blocks.push(name, residues);
+        }
+        if !self.started {
+            return Ok(None);
+        }
+        self.started = false;
+        self.alignments += 1;
+        let position = Position {
+            line: self.lines.line(),
+            record: self.alignments,
+        };
+        blocks.finish("Clustal", position).map(Some)
+    }
+}
+
+/// Reader of Stockholm alignments (as used by Pfam and Rfam), of which a
+/// file may hold several
+///
+/// Markup lines are skipped, except that a `#=GS <name> DE` description is
+/// added to the header of its sequence.
+///
+/// ```
+/// use prseq::msa::{AlignmentReader, StockholmReader};
+///
+/// let input = b"# STOCKHOLM 1.0
+/// #=GF ID example
+/// seq1  ACG.T
+/// seq2  AC-GT
+/// //
+/// ";
+/// let mut reader = StockholmReader::from_reader(&input[..]).unwrap();
+/// let records = reader.read_alignment().unwrap().unwrap();
+/// assert_eq!(records[0].sequence, "ACG.T");
+/// ```
+pub struct StockholmReader {
+    lines: LineReader,
+    alignments: u64,
+}
+
+impl StockholmReader {
+    /// Create a new StockholmReader from a file path
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Create a new StockholmReader from any readable source with
+    /// compression detection
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> Result<Self> {
+        Ok(Self::from_line_reader(open(reader)?))
+    }
+
+    fn from_line_reader(lines: LineReader) -> Self {
+        StockholmReader {
+            lines,
+            alignments: 0,
+        }
+    }
+}
+
+impl AlignmentReader for StockholmReader {
+    fn read_alignment(&mut self) -> Result<Option<Vec<FastaRecord>>> {
+        let mut blocks = Blocks::default();
+        let mut descriptions: HashMap<String, String> = HashMap::new();
+        let mut started = false;
+        loop {
+            let position = Position {
+                line: self.lines.line() + 1,
+                record: self.alignments + 1,
+            };
+            let Some((line, _)) = self.lines.next_line()? else {
+                if started {
+                    return Err(PrseqError::UnexpectedEof {
+                        message: "Stockholm alignment ended without '//'".to_string(),
+                        position,
+                    });
+                }
+                return Ok(None);
+            };
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            if !started {
+                if !line.starts_with(b"# STOCKHOLM") {
+                    return Err(PrseqError::invalid_format(
+                        "Stockholm",
+                        "Stockholm alignment must start with '# STOCKHOLM'",
+                        position,
+                        line,
+                    ));
+                }
+                started = true;
+                continue;
+            }
+            if line.starts_with(b"//") {
+                self.alignments += 1;
+                let mut records = blocks.finish("Stockholm", position)?;
+                for record in &mut records {
+                    if let Some(description) = descriptions.get(&record.header) {
+                        record.header = format!("{} {}", record.header, description);
+                    }
+                }
+                return Ok(Some(records));
+            }
+            let text = utf8_str(line, "Stockholm", Some(position))?;
+            if let Some(markup) = text.strip_prefix("#=GS") {
+                let mut words = markup.split_whitespace();
+                if let (Some(name), Some("DE")) = (words.next(), words.next()) {
+                    let description = words.collect::<Vec<_>>().join(" ");
+                    let existing = descriptions.entry(name.to_string()).or_default();
+                    if !existing.is_empty() {
+                        existing.push(' ');
+                    }
+                    existing.push_str(&description);
+                }
+                continue;
+            }
+            if text.starts_with('#') {
+                continue;
+            }
+            let (name, residues) = name_and_residues(text, "Stockholm", position)?;
+            blocks.push(name, residues);
+        }
+    }
+}
+
+/// Reader of PHYLIP alignments, in either the sequential or the
+/// interleaved layout, of which a file may hold several
+///
+/// Names are read as in relaxed PHYLIP: the first word of a sequence's
+/// first line, with the rest of the line (and any later lines) being its
+/// residues. Whitespace in residues is ignored. The layout is found by
+/// which of the two gives every sequence the length given in the header.
+///
+/// ```
+/// use prseq::msa::{AlignmentReader, PhylipReader};
+///
+/// let input = b" 2 8
+/// seq1  ACGT
+/// seq2  AC-G
+/// AAAA
+/// T-TT
+/// ";
+/// let mut reader = PhylipReader::from_reader(&input[..]).unwrap();
+/// let records = reader.read_alignment().unwrap().unwrap();
+/// assert_eq!(records[1].sequence, "AC-GT-TT");
+/// ```
+pub struct PhylipReader {
+    lines: LineReader,
+    alignments: u64,
+    /// The sequence and site counts and line number of the next alignment's
+    /// header, once it has been read
+    next_header: Option<(usize, usize, u64)>,
+}
+
+/// Parse a PHYLIP header line's counts of sequences and sites
+fn phylip_header(line: &[u8]) -> Option<(usize, usize)> {
+    let text = std::str::from_utf8(line).ok()?;
+    let mut words = text.split_whitespace();
+    let sequences = words.next()?.parse().ok()?;
+    let sites = words.next()?.parse().ok()?;
+    Some((sequences, sites))
+}
+
+impl PhylipReader {
+    /// Create a new PhylipReader from a file path
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Create a new PhylipReader from any readable source with compression
+    /// detection
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> Result<Self> {
+        Ok(Self::from_line_reader(open(reader)?))
+    }
+
+    fn from_line_reader(lines: LineReader) -> Self {
+        PhylipReader {
+            lines,
+            alignments: 0,
+            next_header: None,
+        }
+    }
+
+    /// Find the first header, skipping blank lines
+    fn read_header(&mut self) -> Result<Option<(usize, usize, u64)>> {
+        loop {
+            let position = Position {
+                line: self.lines.line() + 1,
+                record: self.alignments + 1,
+            };
+            let Some((line, _)) = self.lines.next_line()? else {
+                return Ok(None);
+            };
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            return match phylip_header(line) {
+                Some((sequences, sites)) => Ok(Some((sequences, sites, position.line))),
+                None => Err(PrseqError::invalid_format(
+                    "PHYLIP",
+                    "PHYLIP alignment must start with its numbers of sequences and sites",
+                    position,
+                    line,
+                )),
+            };
+        }
+    }
+}
+
+/// Lay out the lines of a PHYLIP alignment as interleaved blocks
+fn phylip_interleaved(lines: &[String], count: usize) -> Vec<FastaRecord> {
+    let mut records: Vec<FastaRecord> = Vec::with_capacity(count);
+    if count == 0 {
+        return records;
+    }
+    for (i, line) in lines.iter().enumerate() {
+        if i < count {
+            let (name, residues) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            records.push(FastaRecord {
+                header: name.to_string(),
+                sequence: residues.split_whitespace().collect(),
+            });
+        } else {
+            records[i % count].sequence.extend(line.split_whitespace());
+        }
+    }
+    records
+}
+
+/// Lay out the lines of a PHYLIP alignment as one sequence after another,
+/// each continuing until it has `sites` residues
+fn phylip_sequential(lines: &[String], count: usize, sites: usize) -> Vec<FastaRecord> {
+    let mut records: Vec<FastaRecord> = Vec::with_capacity(count);
+    for line in lines {
+        match records.last_mut() {
+            Some(record) if record.sequence.len() < sites => {
+                record.sequence.extend(line.split_whitespace());
+            }
+            _ => {
+                let (name, residues) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+                records.push(FastaRecord {
+                    header: name.to_string(),
+                    sequence: residues.split_whitespace().collect(),
+                });
+            }
+        }
+    }
+    records
+}
+
+impl AlignmentReader for PhylipReader {
+    fn read_alignment(&mut self) -> Result<Option<Vec<FastaRecord>>> {
+        let header = match self.next_header.take() {
+            Some(header) => Some(header),
+            None => self.read_header()?,
+        };
+        let Some((count, sites, header_line)) = header else {
+            return Ok(None);
+        };
+        self.alignments += 1;
+        let position = Position {
+            line: header_line,
+            record: self.alignments,
+        };
+
+        // Gather the lines up to the next alignment's header
+        let mut lines = Vec::new();
+        loop {
+            let line_position = Position {
+                line: self.lines.line() + 1,
+                record: self.alignments,
+            };
+            let Some((line, _)) = self.lines.next_line()? else {
+                break;
+            };
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            if let Some((count, sites)) = phylip_header(line) {
+                self.next_header = Some((count, sites, line_position.line));
+                break;
+            }
+            let text = utf8_str(line, "PHYLIP", Some(line_position))?;
+            lines.push(text.trim().to_string());
+        }
+
+        let complete = |records: &Vec<FastaRecord>| {
+            records.len() == count && records.iter().all(|r| r.sequence.len() == sites)
+        };
+        let interleaved = phylip_interleaved(&lines, count);
+        if complete(&interleaved) {
+            return Ok(Some(interleaved));
+        }
+        let sequential = phylip_sequential(&lines, count, sites);
+        if complete(&sequential) {
+            return Ok(Some(sequential));
+        }
+        Err(PrseqError::invalid_format(
+            "PHYLIP",
+            format!(
+                "PHYLIP alignment does not have the {} sequences of {} sites its header gives",
+                count, sites
+            ),
+            position,
+            &[],
+        ))
+    }
+}
+
+enum Inner {
+    Clustal(ClustalReader),
+    Phylip(PhylipReader),
+    Stockholm(StockholmReader),
+    Empty,
+}
+
+/// Reader of alignments in any of the supported formats, detected from the
+/// start of the input
+///
+/// ```
+/// use prseq::msa::{AlignmentFormat, AlignmentReader, AnyAlignmentReader};
+///
+/// let input = b"# STOCKHOLM 1.0\nseq1 AC-T\nseq2 ACGT\n//\n";
+/// let reader = AnyAlignmentReader::from_reader(&input[..]).unwrap();
+/// assert_eq!(reader.format(), Some(AlignmentFormat::Stockholm));
+/// assert_eq!(reader.records().count(), 2);
+/// ```
+pub struct AnyAlignmentReader {
+    inner: Inner,
+}
+
+impl AnyAlignmentReader {
+    /// Create a new AnyAlignmentReader from a file path
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Create a new AnyAlignmentReader from any readable source with
+    /// compression detection
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> Result<Self> {
+        let mut buf_reader = create_reader_with_compression(reader)?;
+        let format = detect_format(&mut buf_reader)?;
+        let lines = LineReader::new(Box::new(buf_reader));
+        let inner = match format {
+            Some(AlignmentFormat::Clustal) => {
+                Inner::Clustal(ClustalReader::from_line_reader(lines))
+            }
+            Some(AlignmentFormat::Phylip) => Inner::Phylip(PhylipReader::from_line_reader(lines)),
+            Some(AlignmentFormat::Stockholm) => {
+                Inner::Stockholm(StockholmReader::from_line_reader(lines))
+            }
+            None => Inner::Empty,
+        };
+        Ok(AnyAlignmentReader { inner })
+    }
+
+    /// The detected format, or None if the input is empty
+    pub fn format(&self) -> Option<AlignmentFormat> {
+        match self.inner {
+            Inner::Clustal(_) => Some(AlignmentFormat::Clustal),
+            Inner::Phylip(_) => Some(AlignmentFormat::Phylip),
+            Inner::Stockholm(_) => Some(AlignmentFormat::Stockholm),
+            Inner::Empty => None,
+        }
+    }
+}
+
+impl AlignmentReader for AnyAlignmentReader {
+    fn read_alignment(&mut self) -> Result<Option<Vec<FastaRecord>>> {
+        match &mut self.inner {
+            Inner::Clustal(reader) => reader.read_alignment(),
+            Inner::Phylip(reader) => reader.read_alignment(),
+            Inner::Stockholm(reader) => reader.read_alignment(),
+            Inner::Empty => Ok(None),
+        }
+    }
+}
+
+/// Detect the format from the first non-blank text in the buffer, without
+/// consuming it
+fn detect_format<R: Read>(reader: &mut BufReader<R>) -> Result<Option<AlignmentFormat>> {
+    let buffer = reader.fill_buf()?;
+    let Some(start) = buffer.iter().position(|b| !b.is_ascii_whitespace()) else {
+        return Ok(None);
+    };
+    let text = &buffer[start..];
+    if CLUSTAL_HEADERS
+        .iter()
+        .any(|header| text.starts_with(header))
+    {
+        Ok(Some(AlignmentFormat::Clustal))
+    } else if text.starts_with(b"# STOCKHOLM") {
+        Ok(Some(AlignmentFormat::Stockholm))
+    } else if text[0].is_ascii_digit() {
+        Ok(Some(AlignmentFormat::Phylip))
+    } else {
+        let line = buffer[..start].iter().filter(|&&b| b == b'\n').count() as u64 + 1;
+        let end = text.iter().position(|&b| b == b'\n').unwrap_or(text.len());
+        Err(PrseqError::invalid_format(
+            "alignment",
+            "Input is not a Clustal, PHYLIP or Stockholm alignment",
+            Position { line, record: 1 },
+            &text[..end],
+        ))
+    }
+}
+
+/// The characters taken as gaps in alignments
+fn is_gap(residue: u8) -> bool {
+    residue == b'-' || residue == b'.'
+}
+
+/// The IUPAC code for each set of bases, as a bit set over T, C, A and G
+const IUPAC_CODES: &[u8; 16] = b"NTCYAWMHGKSBRDVN";
+
+/// How many times each residue (in upper case) appears in a column, with
+/// gaps not counted
+fn residue_counts(column: &[u8]) -> [usize; 256] {
+    let mut counts = [0; 256];
+    for &residue in column.iter().filter(|&&residue| !is_gap(residue)) {
+        counts[residue.to_ascii_uppercase() as usize] += 1;
+    }
+    counts
+}
+
+/// A multiple sequence alignment: records whose sequences are all the same
+/// length, with gaps as '-' or '.'
+///
+/// Columns are worked on as bytes, and residues are compared ignoring case.
+///
+/// ```
+/// use prseq::msa::Alignment;
+/// use prseq::FastaRecord;
+///
+/// let records = vec![
+///     FastaRecord { header: "s1".into(), sequence: "AC-T".into() },
+///     FastaRecord { header: "s2".into(), sequence: "AG-T".into() },
+/// ];
+/// let mut alignment = Alignment::new(records).unwrap();
+/// assert_eq!(alignment.remove_gap_only_columns(), 1);
+/// assert_eq!(alignment.iupac_consensus(), "AST");
+/// assert_eq!(alignment.column_identity(), [1.0, 0.5, 1.0]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alignment {
+    records: Vec<FastaRecord>,
+    width: usize,
+}
+
+impl Alignment {
+    /// Create an Alignment, checking that the sequences are all the same
+    /// length
+    pub fn new(records: Vec<FastaRecord>) -> Result<Self> {
+        let width = records.first().map_or(0, |record| record.sequence.len());
+        if let Some((index, record)) = records
+            .iter()
+            .enumerate()
+            .find(|(_, r)| r.sequence.len() != width)
+        {
+            return Err(PrseqError::invalid_format(
+                "alignment",
+                format!(
+                    "Aligned sequences must be the same length, but '{}' has {} and '{}' has {}",
+                    records[0].header,
+                    width,
+                    record.header,
+                    record.sequence.len()
+                ),
+                Position {
+                    line: 0,
+                    record: index as u64 + 1,
+                },
+                &[],
+            ));
+        }
+        Ok(Alignment { records, width })
+    }
+
+    /// The records
+    pub fn records(&self) -> &[FastaRecord] {
+        &self.records
+    }
+
+    /// Consume the alignment, returning its records
+    pub fn into_records(self) -> Vec<FastaRecord> {
+        self.records
+    }
+
+    /// The number of sequences
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether there are no sequences
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The number of columns
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The residues of column `index` (0-based), one per sequence, or None
+    /// if there is no such column
+    pub fn column(&self, index: usize) -> Option<Vec<u8>> {
+        (index < self.width).then(|| {
+            self.records
+                .iter()
+                .map(|record| record.sequence.as_bytes()[index])
+                .collect()
+        })
+    }
+
+    /// The columns, in order
+    pub fn columns(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        (0..self.width).filter_map(|index| self.column(index))
+    }
+
+    /// Remove the columns in which every sequence has a gap (as happens
+    /// after taking some of the sequences of a larger alignment), returning
+    /// the number removed
+    pub fn remove_gap_only_columns(&mut self) -> usize {
+        let keep: Vec<bool> = self
+            .columns()
+            .map(|column| !column.iter().all(|&residue| is_gap(residue)))
+            .collect();
+        let removed = keep.iter().filter(|&&keep| !keep).count();
+        if removed > 0 {
+            for record in &mut self.records {
+                let kept: Vec<u8> = record
+                    .sequence
+                    .bytes()
+                    .zip(&keep)
+                    .filter_map(|(residue, &keep)| keep.then_some(residue))
+                    .collect();
+                // Only gaps are removed, which are ASCII, so this is lossless.
+                record.sequence = String::from_utf8_lossy(&kept).into_owned();
+            }
+            self.width -= removed;
+        }
+        removed
+    }
+
+    /// The most common residue (upper case) of each column, ignoring gaps,
+    /// with ties going to the residue that sorts first. Columns of only gaps
+    /// give '-'.
+    pub fn consensus(&self) -> String {
+        self.columns()
+            .map(|column| {
+                let (residue, count) = residue_counts(&column).iter().enumerate().fold(
+                    (b'-', 0),
+                    |best, (residue, &count)| {
+                        if count > best.1 {
+                            (residue as u8, count)
+                        } else {
+                            best
+                        }
+                    },
+                );
+                if count == 0 {
+                    '-'
+                } else {
+                    residue as char
+                }
+            })
+            .collect()
+    }
+
+    /// The IUPAC nucleotide code for all the bases seen in each column,
+    /// ignoring gaps (so a column of A and G gives R). Ambiguity codes in
+    /// the sequences count as all the bases they stand for, and anything
+    /// that is not a nucleotide code gives N. Columns of only gaps give '-'.
+    pub fn iupac_consensus(&self) -> String {
+        self.columns()
+            .map(|column| {
+                let bases = column.iter().filter(|&&residue| !is_gap(residue)).fold(
+                    0,
+                    |bases, &residue| match base_bits(residue) {
+                        0 => 0b1111,
+                        bits => bases | bits,
+                    },
+                );
+                if bases == 0 {
+                    '-'
+                } else {
+                    IUPAC_CODES[bases as usize] as char
+                }
+            })
+            .collect()
+    }
+
+    /// The identity of each column: the fraction of the sequences that have
+    /// the column's most common residue (ignoring case). Gaps are never
+    /// counted as the most common residue, so they lower the identity, and
+    /// a column of only gaps has identity 0.
+    pub fn column_identity(&self) -> Vec<f64> {
+        let count = self.records.len();
+        self.columns()
+            .map(|column| {
+                let most = residue_counts(&column).iter().max().copied().unwrap_or(0);
+                most as f64 / count as f64
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<Vec<FastaRecord>> for Alignment {
+    type Error = PrseqError;
+
+    fn try_from(records: Vec<FastaRecord>) -> Result<Self> {
+        Alignment::new(records)
+    }
+}
@@ -0,0 +1,148 @@
+use crate::error::{PrseqError, Result};
+use crate::fasta::FastaReader;
+use crate::fastq::FastqReader;
+use crate::location::{Located, Location, WithLocations};
+use std::collections::VecDeque;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Reads the records of several files in turn, as if they were one
+///
+/// Each file is opened only when the previous one is finished, with its own
+/// compression detection. Errors are prefixed with the path of the file
+/// they occurred in; after an error in one file, reading continues with the
+/// next record (or file).
+pub struct MultiFileReader<R> {
+    paths: VecDeque<PathBuf>,
+    open: fn(&Path) -> Result<R>,
+    current: Option<R>,
+    current_path: Option<Arc<Path>>,
+}
+
+/// Reads the records of several FASTA files in turn
+pub type MultiFastaReader = MultiFileReader<FastaReader>;
+
+/// Reads the records of several FASTQ files in turn
+pub type MultiFastqReader = MultiFileReader<FastqReader>;
+
+impl<R> MultiFileReader<R> {
+    /// Create a reader for `paths`, opening each with `open`
+    pub fn new<I, P>(paths: I, open: fn(&Path) -> Result<R>) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        MultiFileReader {
+            paths: paths.into_iter().map(Into::into).collect(),
+            open,
+            current: None,
+            current_path: None,
+        }
+    }
+
+    /// Create a reader for the files matching a glob pattern (such as
+    /// `reads/*.fastq.gz`), in alphabetical order
+    pub fn from_glob(pattern: &str, open: fn(&Path) -> Result<R>) -> Result<Self> {
+        let paths = glob_paths(pattern)?;
+        Ok(Self::new(paths, open))
+    }
+
+    /// The path of the file the most recently returned record came from
+    pub fn current_path(&self) -> Option<&Path> {
+        self.current_path.as_deref()
+    }
+
+    /// Turn this reader into an iterator that tags each record with the
+    /// path of the file it came from
+    pub fn with_source(self) -> WithSource<R> {
+        WithSource { reader: self }
+    }
+
+    /// Turn this reader into an iterator that pairs each record with its
+    /// `Location`: the file, line and index it was read from
+    pub fn with_locations(self) -> WithLocations<Self> {
+        WithLocations::new(self)
+    }
+}
+
+impl<R, T> Iterator for MultiFileReader<R>
+where
+    R: Iterator<Item = Result<T>>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        loop {
+            if let Some(reader) = &mut self.current {
+                match reader.next() {
+                    Some(Ok(record)) => return Some(Ok(record)),
+                    Some(Err(e)) => return Some(Err(self.error_in_current(e))),
+                    None => self.current = None,
+                }
+            }
+            let path: Arc<Path> = self.paths.pop_front()?.into();
+            self.current_path = Some(path.clone());
+            match (self.open)(&path) {
+                Ok(reader) => self.current = Some(reader),
+                Err(e) => return Some(Err(self.error_in_current(e))),
+            }
+        }
+    }
+}
+
+impl<R> MultiFileReader<R> {
+    fn error_in_current(&self, error: PrseqError) -> PrseqError {
+        error.in_file(self.current_path.as_deref())
+    }
+}
+
+impl<R: Located> Located for MultiFileReader<R> {
+    fn location(&self) -> Location {
+        let location = self.current.as_ref().map(R::location).unwrap_or_default();
+        Location {
+            file: location.file.or_else(|| self.current_path.clone()),
+            ..location
+        }
+    }
+}
+
+/// Iterator over records tagged with the file they came from, created by
+/// `MultiFileReader::with_source`
+pub struct WithSource<R> {
+    reader: MultiFileReader<R>,
+}
+
+impl<R, T> Iterator for WithSource<R>
+where
+    R: Iterator<Item = Result<T>>,
+{
+    type Item = Result<(Arc<Path>, T)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.reader.next()?;
+        let path = self.reader.current_path.clone()?;
+        Some(record.map(|record| (path, record)))
+    }
+}
+
+fn glob_paths(pattern: &str) -> Result<Vec<PathBuf>> {
+    let entries = glob::glob(pattern).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid glob pattern '{}': {}", pattern, e),
+        )
+    })?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        paths.push(entry.map_err(io::Error::from)?);
+    }
+    if paths.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No files match '{}'", pattern),
+        )
+        .into());
+    }
+    Ok(paths)
+}
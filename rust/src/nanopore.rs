@@ -0,0 +1,193 @@
+use crate::common::split_header_str;
+use crate::fastq::FastqRecord;
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+/// The fields of an Oxford Nanopore read header, as written by Guppy,
+/// MinKNOW and dorado
+///
+/// ```text
+/// @<read id> runid=<run> read=<n> ch=<channel> start_time=<time> flow_cell_id=<id> sample_id=<id> barcode=<barcode> ...
+/// ```
+///
+/// The key=value fields may be in any order and any of them may be
+/// missing. Those with no field here (e.g. `protocol_group_id` or
+/// `parent_read_id`) are kept in `other`, in header order.
+///
+/// ```
+/// use prseq::NanoporeHeader;
+///
+/// let header = NanoporeHeader::parse(
+///     "0e3c4b7a-5f1d-4c8e-9a2b-7d6f1e0c3b9a runid=a1b2c3 read=5512 ch=118 \
+///      start_time=2023-05-04T10:15:30Z barcode=barcode07",
+/// )
+/// .unwrap();
+/// assert_eq!(header.channel, Some(118));
+/// assert_eq!(header.read, Some(5512));
+/// assert_eq!(header.barcode.as_deref(), Some("barcode07"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NanoporeHeader {
+    /// The read's ID (a UUID), the header up to the first whitespace
+    pub read_id: String,
+    /// The sequencing run's ID (`runid`)
+    pub run_id: Option<String>,
+    /// The read's number within its channel (`read`)
+    pub read: Option<u64>,
+    /// The pore channel the read came from (`ch`)
+    pub channel: Option<u32>,
+    /// When the read started, as an ISO 8601 timestamp (`start_time`)
+    pub start_time: Option<String>,
+    pub flow_cell_id: Option<String>,
+    /// The sample ID (`sample_id`, or `sampleid` from older Guppy versions)
+    pub sample_id: Option<String>,
+    /// The barcode assigned by demultiplexing, e.g. "barcode01" or
+    /// "unclassified" (`barcode`)
+    pub barcode: Option<String>,
+    /// The basecalling model (`basecall_model_version_id`)
+    pub basecall_model: Option<String>,
+    /// The other key=value fields
+    pub other: Vec<(String, String)>,
+}
+
+impl NanoporeHeader {
+    /// Parse a FASTQ (or FASTA) header line (without its '@'), returning
+    /// None if it has no key=value fields or a numeric field is not a
+    /// number
+    pub fn parse(header: &str) -> Option<NanoporeHeader> {
+        let (read_id, comment) = split_header_str(header);
+        let mut parsed = NanoporeHeader {
+            read_id: read_id.to_string(),
+            ..NanoporeHeader::default()
+        };
+        let mut found = false;
+        for (key, value) in comment
+            .into_iter()
+            .flat_map(str::split_ascii_whitespace)
+            .filter_map(|field| field.split_once('='))
+        {
+            found = true;
+            match key {
+                "runid" => parsed.run_id = Some(value.to_string()),
+                "read" => parsed.read = Some(value.parse().ok()?),
+                "ch" => parsed.channel = Some(value.parse().ok()?),
+                "start_time" => parsed.start_time = Some(value.to_string()),
+                "flow_cell_id" => parsed.flow_cell_id = Some(value.to_string()),
+                "sample_id" | "sampleid" => parsed.sample_id = Some(value.to_string()),
+                "barcode" => parsed.barcode = Some(value.to_string()),
+                "basecall_model_version_id" => parsed.basecall_model = Some(value.to_string()),
+                _ => parsed.other.push((key.to_string(), value.to_string())),
+            }
+        }
+        (found && !read_id.is_empty()).then_some(parsed)
+    }
+}
+
+impl FastqRecord {
+    /// Parse the record's header as a nanopore read header, returning None
+    /// if it is not one (see `NanoporeHeader`)
+    pub fn parse_nanopore_header(&self) -> Option<NanoporeHeader> {
+        NanoporeHeader::parse(&self.header)
+    }
+}
+
+/// Which nanopore reads to keep, by channel, start time and barcode, for
+/// `FilterExt::filter_nanopore`
+///
+/// A read must meet every criterion set, and a read whose header lacks a
+/// field that a criterion is set for (or is not a nanopore header) is
+/// dropped. Start times are compared as strings, which orders ISO 8601
+/// timestamps correctly when they are written the same way, as they are
+/// within a run.
+///
+/// ```
+/// use prseq::nanopore::NanoporeFilter;
+/// use prseq::NanoporeHeader;
+///
+/// let filter = NanoporeFilter::new()
+///     .channels(1..=256)
+///     .barcodes(["barcode01", "barcode02"])
+///     .started_before("2023-05-04T12:00:00Z");
+/// let header = NanoporeHeader::parse(
+///     "read1 ch=7 start_time=2023-05-04T10:15:30Z barcode=barcode02",
+/// )
+/// .unwrap();
+/// assert!(filter.matches(&header));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NanoporeFilter {
+    /// The channels to keep reads from
+    pub channels: Option<RangeInclusive<u32>>,
+    /// The barcodes to keep reads with
+    pub barcodes: Option<HashSet<String>>,
+    /// The earliest start time to keep (inclusive)
+    pub started_from: Option<String>,
+    /// The start time to keep reads before (exclusive)
+    pub started_before: Option<String>,
+}
+
+impl NanoporeFilter {
+    /// A filter that keeps every nanopore read
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep reads from these channels
+    pub fn channels(mut self, channels: RangeInclusive<u32>) -> Self {
+        self.channels = Some(channels);
+        self
+    }
+
+    /// Keep reads with one of these barcodes
+    pub fn barcodes<I, S>(mut self, barcodes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.barcodes = Some(barcodes.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Keep reads that started at or after `time`
+    pub fn started_from(mut self, time: impl Into<String>) -> Self {
+        self.started_from = Some(time.into());
+        self
+    }
+
+    /// Keep reads that started before `time`
+    pub fn started_before(mut self, time: impl Into<String>) -> Self {
+        self.started_before = Some(time.into());
+        self
+    }
+
+    /// Whether a parsed header meets the criteria
+    pub fn matches(&self, header: &NanoporeHeader) -> bool {
+        let channel_ok = match &self.channels {
+            Some(channels) => header.channel.is_some_and(|ch| channels.contains(&ch)),
+            None => true,
+        };
+        let barcode_ok = match &self.barcodes {
+            Some(barcodes) => header
+                .barcode
+                .as_ref()
+                .is_some_and(|barcode| barcodes.contains(barcode)),
+            None => true,
+        };
+        let start = header.start_time.as_deref();
+        let from_ok = match &self.started_from {
+            Some(from) => start.is_some_and(|start| start >= from.as_str()),
+            None => true,
+        };
+        let before_ok = match &self.started_before {
+            Some(before) => start.is_some_and(|start| start < before.as_str()),
+            None => true,
+        };
+        channel_ok && barcode_ok && from_ok && before_ok
+    }
+
+    /// Whether a header line is a nanopore header that meets the criteria
+    pub fn matches_header(&self, header: &str) -> bool {
+        NanoporeHeader::parse(header).is_some_and(|header| self.matches(&header))
+    }
+}
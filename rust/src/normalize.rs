@@ -0,0 +1,225 @@
+use crate::alphabet::Alphabet;
+use crate::any::AnyRecord;
+use crate::error::Result;
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+
+/// Converting between DNA and RNA
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BaseConversion {
+    /// U to T
+    RnaToDna,
+    /// T to U
+    DnaToRna,
+}
+
+/// What `normalize` changes in records. Nothing is changed by default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizeOptions {
+    /// Upper-case the sequence
+    pub uppercase: bool,
+    /// Convert U to T or T to U, preserving case
+    pub convert: Option<BaseConversion>,
+    /// Remove gaps ('-' and '.') from the sequence
+    pub strip_gaps: bool,
+    /// Remove whitespace from the sequence, and turn runs of whitespace in
+    /// the header into single spaces (trimming the ends)
+    pub collapse_whitespace: bool,
+    /// Replace characters that are not IUPAC nucleotide codes (or gaps) in
+    /// the sequence with N
+    pub replace_invalid: bool,
+}
+
+impl NormalizeOptions {
+    /// Create options that change nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create options that change everything but the bases: upper-casing,
+    /// stripping gaps, collapsing whitespace and replacing invalid
+    /// characters
+    pub fn all() -> Self {
+        NormalizeOptions {
+            uppercase: true,
+            convert: None,
+            strip_gaps: true,
+            collapse_whitespace: true,
+            replace_invalid: true,
+        }
+    }
+
+    /// Set whether to upper-case the sequence
+    pub fn uppercase(mut self, uppercase: bool) -> Self {
+        self.uppercase = uppercase;
+        self
+    }
+
+    /// Set a conversion between DNA and RNA
+    pub fn convert(mut self, conversion: BaseConversion) -> Self {
+        self.convert = Some(conversion);
+        self
+    }
+
+    /// Set whether to remove gaps from the sequence
+    pub fn strip_gaps(mut self, strip_gaps: bool) -> Self {
+        self.strip_gaps = strip_gaps;
+        self
+    }
+
+    /// Set whether to remove whitespace from the sequence and collapse it in
+    /// the header
+    pub fn collapse_whitespace(mut self, collapse_whitespace: bool) -> Self {
+        self.collapse_whitespace = collapse_whitespace;
+        self
+    }
+
+    /// Set whether to replace non-IUPAC characters with N
+    pub fn replace_invalid(mut self, replace_invalid: bool) -> Self {
+        self.replace_invalid = replace_invalid;
+        self
+    }
+
+    /// Whether a sequence character is removed
+    fn removes(&self, base: u8) -> bool {
+        (self.strip_gaps && (base == b'-' || base == b'.'))
+            || (self.collapse_whitespace && base.is_ascii_whitespace())
+    }
+
+    /// The character a kept sequence character becomes
+    fn map(&self, base: u8) -> u8 {
+        let base = if self.uppercase {
+            base.to_ascii_uppercase()
+        } else {
+            base
+        };
+        let base = match (self.convert, base) {
+            (Some(BaseConversion::RnaToDna), b'U') => b'T',
+            (Some(BaseConversion::RnaToDna), b'u') => b't',
+            (Some(BaseConversion::DnaToRna), b'T') => b'U',
+            (Some(BaseConversion::DnaToRna), b't') => b'u',
+            _ => base,
+        };
+        if self.replace_invalid && base != b'.' && !Alphabet::Iupac.contains(base) {
+            b'N'
+        } else {
+            base
+        }
+    }
+
+    fn header(&self, header: &mut String) {
+        if self.collapse_whitespace && header.contains(|c: char| c.is_ascii_whitespace()) {
+            *header = header
+                .split_ascii_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+    }
+
+    /// Normalize a sequence and (for FASTQ) the quality, removing the
+    /// quality characters of the bases removed
+    fn sequence(&self, sequence: &mut String, quality: Option<&mut String>) {
+        let mut bases = Vec::with_capacity(sequence.len());
+        let mut scores = Vec::new();
+        let quality_bytes = quality.as_deref().map(String::as_bytes);
+        for (i, &base) in sequence.as_bytes().iter().enumerate() {
+            if self.removes(base) {
+                continue;
+            }
+            bases.push(self.map(base));
+            if let Some(score) = quality_bytes.and_then(|q| q.get(i)) {
+                scores.push(*score);
+            }
+        }
+        // Only ASCII characters are changed or removed, so the bytes are
+        // still UTF-8. The quality need not be ASCII, and removing bytes
+        // from a character in it is done lossily rather than panicking.
+        *sequence = String::from_utf8(bases).expect("normalized sequences are UTF-8");
+        if let Some(quality) = quality {
+            *quality = match String::from_utf8(scores) {
+                Ok(scores) => scores,
+                Err(error) => String::from_utf8_lossy(error.as_bytes()).into_owned(),
+            };
+        }
+    }
+}
+
+impl FastaRecord {
+    /// Normalize the record in place, as set by `options`
+    ///
+    /// ```
+    /// use prseq::normalize::{BaseConversion, NormalizeOptions};
+    /// use prseq::FastaRecord;
+    ///
+    /// let mut record = FastaRecord { header: "s1  x".into(), sequence: "ac-gu .X".into() };
+    /// record.normalize(&NormalizeOptions::all().convert(BaseConversion::RnaToDna));
+    /// assert_eq!(record.header, "s1 x");
+    /// assert_eq!(record.sequence, "ACGTN");
+    /// ```
+    pub fn normalize(&mut self, options: &NormalizeOptions) {
+        options.header(&mut self.header);
+        options.sequence(&mut self.sequence, None);
+    }
+}
+
+impl FastqRecord {
+    /// Normalize the record in place, as set by `options`. The quality of
+    /// any bases removed is removed with them.
+    pub fn normalize(&mut self, options: &NormalizeOptions) {
+        options.header(&mut self.header);
+        options.sequence(&mut self.sequence, Some(&mut self.quality));
+    }
+}
+
+impl AnyRecord {
+    /// Normalize the record in place, as set by `options`
+    pub fn normalize(&mut self, options: &NormalizeOptions) {
+        match self {
+            AnyRecord::Fasta(record) => record.normalize(options),
+            AnyRecord::Fastq(record) => record.normalize(options),
+        }
+    }
+}
+
+/// A record that can be normalized
+pub trait Normalizable {
+    fn normalize(&mut self, options: &NormalizeOptions);
+}
+
+impl Normalizable for FastaRecord {
+    fn normalize(&mut self, options: &NormalizeOptions) {
+        FastaRecord::normalize(self, options)
+    }
+}
+
+impl Normalizable for FastqRecord {
+    fn normalize(&mut self, options: &NormalizeOptions) {
+        FastqRecord::normalize(self, options)
+    }
+}
+
+impl Normalizable for AnyRecord {
+    fn normalize(&mut self, options: &NormalizeOptions) {
+        AnyRecord::normalize(self, options)
+    }
+}
+
+/// Normalization for readers (or any iterator of record results)
+pub trait NormalizeExt<R: Normalizable>: Iterator<Item = Result<R>> + Sized {
+    /// Normalize each record, as set by `options`
+    fn normalized(self, options: NormalizeOptions) -> impl Iterator<Item = Result<R>> {
+        self.map(move |record| {
+            record.map(|mut record| {
+                record.normalize(&options);
+                record
+            })
+        })
+    }
+}
+
+impl<I, R> NormalizeExt<R> for I
+where
+    I: Iterator<Item = Result<R>>,
+    R: Normalizable,
+{
+}
@@ -0,0 +1,114 @@
+use bytes::Bytes;
+use futures_util::StreamExt;
+use std::io::{Read, Result};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread;
+
+/// The prefixes of the environment variables that configure the stores
+const STORE_VARIABLES: &[&str] = &["AWS_", "GOOGLE_", "AZURE_"];
+
+/// The store options given by the environment: only the variables meant
+/// for a store, so that nothing else in the environment is passed on
+fn store_options() -> impl Iterator<Item = (String, String)> {
+    std::env::vars()
+        .filter(|(key, _)| STORE_VARIABLES.iter().any(|prefix| key.starts_with(prefix)))
+        .map(|(key, value)| (key.to_ascii_lowercase(), value))
+}
+
+/// Open an object in a cloud object store (or any other store supported by
+/// the `object_store` crate) for reading, given its URL
+///
+/// Supported URLs include `s3://bucket/key`, `gs://bucket/key`,
+/// `az://container/key` and `file:///path`. Credentials and other options are
+/// taken from the usual environment variables (e.g. `AWS_ACCESS_KEY_ID`,
+/// `AWS_REGION`, `GOOGLE_SERVICE_ACCOUNT`, `AZURE_STORAGE_ACCOUNT_NAME`).
+///
+/// The object is downloaded as a stream on a background thread, so it can
+/// be parsed while it is arriving.
+pub fn open_object(url: &str) -> Result<Box<dyn Read + Send>> {
+    let parsed = url::Url::parse(url).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Invalid object URL '{}': {}", url, e),
+        )
+    })?;
+    let (store, path) =
+        object_store::parse_url_opts(&parsed, store_options()).map_err(|e| object_error(url, e))?;
+
+    let (sender, receiver) = sync_channel(4);
+    let url = url.to_string();
+    thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                let _ = sender.send(Err(e));
+                return;
+            }
+        };
+        runtime.block_on(async {
+            let mut stream = match store.get(&path).await {
+                Ok(result) => result.into_stream(),
+                Err(e) => {
+                    let _ = sender.send(Err(object_error(&url, e)));
+                    return;
+                }
+            };
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk.map(Some).map_err(|e| object_error(&url, e));
+                if sender.send(chunk).is_err() {
+                    // The reader has been dropped.
+                    return;
+                }
+            }
+            let _ = sender.send(Ok(None));
+        });
+    });
+
+    Ok(Box::new(ObjectReader {
+        receiver,
+        chunk: Bytes::new(),
+        finished: false,
+    }))
+}
+
+/// Reads the chunks of an object sent by the download thread
+struct ObjectReader {
+    receiver: Receiver<Result<Option<Bytes>>>,
+    chunk: Bytes,
+    finished: bool,
+}
+
+impl Read for ObjectReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        while self.chunk.is_empty() {
+            if self.finished {
+                return Ok(0);
+            }
+            match self.receiver.recv() {
+                Ok(Ok(Some(chunk))) => self.chunk = chunk,
+                Ok(Ok(None)) => self.finished = true,
+                Ok(Err(e)) => return Err(e),
+                Err(_) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::UnexpectedEof,
+                        "Object download stopped before the end of the object",
+                    ))
+                }
+            }
+        }
+        let n = self.chunk.len().min(buf.len());
+        buf[..n].copy_from_slice(&self.chunk.split_to(n));
+        Ok(n)
+    }
+}
+
+fn object_error(url: &str, error: object_store::Error) -> std::io::Error {
+    let kind = match error {
+        object_store::Error::NotFound { .. } => std::io::ErrorKind::NotFound,
+        _ => std::io::ErrorKind::Other,
+    };
+    std::io::Error::new(kind, format!("Could not read {}: {}", url, error))
+}
@@ -0,0 +1,350 @@
+use crate::alphabet::Alphabet;
+use crate::common::split_header;
+use crate::error::{Position, PrseqError, Result};
+use crate::quality::QualityEncoding;
+use crate::sequence::{mask_to_n, unmask};
+use std::collections::HashMap;
+
+/// What a reader does when it finds a malformed record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnError {
+    /// Return the error (the default)
+    #[default]
+    Fail,
+    /// Skip the record and carry on with the next one
+    Skip,
+    /// Skip the record, logging the error as a warning (with the `log`
+    /// crate)
+    Warn,
+}
+
+/// What a reader does when a record has the same ID as an earlier one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateIds {
+    /// Nothing: IDs are not tracked (the default)
+    #[default]
+    Allow,
+    /// Log a warning (with the `log` crate) and return the record
+    Warn,
+    /// Return a DuplicateId error. With `OnError::Skip` or `OnError::Warn`
+    /// the duplicate is skipped, so only the first record with each ID is
+    /// read.
+    Error,
+}
+
+/// What a FASTA reader does with soft-masked (lower case) bases, which
+/// mark repeats and low-complexity regions in many references
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SoftMask {
+    /// Leave them as they are (the default)
+    #[default]
+    Keep,
+    /// Upper-case them, removing the masking
+    Unmask,
+    /// Replace them with N (hard masking)
+    MaskToN,
+}
+
+/// What a reader does with a record that has an empty sequence, such as a
+/// FASTA header followed straight away by another header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmptyRecords {
+    /// Return it, with an empty sequence (the default)
+    #[default]
+    Keep,
+    /// Skip it. Skipped empty records are not malformed, so are not counted
+    /// by `skipped()`.
+    Skip,
+    /// Report it as a malformed record, so that it is an error, skipped,
+    /// or skipped with a warning according to `on_error`
+    Error,
+}
+
+/// Options controlling how FASTA and FASTQ input is parsed
+///
+/// ```
+/// use prseq::{OnError, ParserOptions};
+///
+/// let options = ParserOptions::default().on_error(OnError::Skip);
+/// # assert_eq!(options.on_error, OnError::Skip);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ParserOptions {
+    /// What to do with malformed records. Only parse errors can be skipped:
+    /// I/O errors (including from decompression) are always returned.
+    pub on_error: OnError,
+    /// Read FASTQ records as exactly four lines (header, sequence, '+' and
+    /// quality), rather than allowing sequence and quality to be split over
+    /// several lines. Ignored for FASTA.
+    pub strict_four_line: bool,
+    /// Check that every sequence contains only characters from this
+    /// alphabet. A sequence that does not is a malformed record, so it is
+    /// an error, skipped, or skipped with a warning according to `on_error`.
+    pub alphabet: Option<Alphabet>,
+    /// The encoding of the input's FASTQ quality scores. Phred+64 quality
+    /// is converted to Phred+33 as it is read.
+    pub quality_encoding: QualityEncoding,
+    /// Whether records with the same ID as an earlier record are reported.
+    /// Tracking IDs keeps every ID read in memory.
+    pub duplicate_ids: DuplicateIds,
+    /// What to do with soft-masked bases. Ignored for FASTQ.
+    pub soft_mask: SoftMask,
+    /// What to do with records whose sequence is empty
+    pub empty_records: EmptyRecords,
+    /// Skip lines starting with ';', which old FASTA files use for
+    /// comments, rather than reporting them as malformed. Ignored for FASTQ.
+    pub skip_comments: bool,
+    /// Remove a '*' from the end of each sequence, as some protein FASTA
+    /// files end sequences with one. Ignored for FASTQ.
+    pub strip_trailing_stop: bool,
+    /// The longest sequence a record may have. Input that is missing its
+    /// record separators (or is not FASTA or FASTQ at all) can otherwise be
+    /// read as one huge record, so a longer sequence is a RecordTooLong
+    /// error, reported as soon as the limit is passed (even part way
+    /// through a line, so input with no line breaks is not read into
+    /// memory).
+    pub max_sequence_length: Option<usize>,
+    /// The longest header line a record may have
+    pub max_header_length: Option<usize>,
+}
+
+impl ParserOptions {
+    /// Set what to do with malformed records
+    pub fn on_error(mut self, on_error: OnError) -> Self {
+        self.on_error = on_error;
+        self
+    }
+
+    /// Set whether FASTQ records are read as exactly four lines
+    pub fn strict_four_line(mut self, strict: bool) -> Self {
+        self.strict_four_line = strict;
+        self
+    }
+
+    /// Check that every sequence contains only characters from `alphabet`
+    pub fn validate(mut self, alphabet: Alphabet) -> Self {
+        self.alphabet = Some(alphabet);
+        self
+    }
+
+    /// Set the encoding of the input's FASTQ quality scores
+    pub fn quality_encoding(mut self, encoding: QualityEncoding) -> Self {
+        self.quality_encoding = encoding;
+        self
+    }
+
+    /// Set whether records with duplicate IDs are reported
+    pub fn duplicate_ids(mut self, duplicate_ids: DuplicateIds) -> Self {
+        self.duplicate_ids = duplicate_ids;
+        self
+    }
+
+    /// Set what to do with soft-masked bases
+    pub fn soft_mask(mut self, soft_mask: SoftMask) -> Self {
+        self.soft_mask = soft_mask;
+        self
+    }
+
+    /// Set what to do with records whose sequence is empty
+    pub fn empty_records(mut self, empty_records: EmptyRecords) -> Self {
+        self.empty_records = empty_records;
+        self
+    }
+
+    /// Set whether FASTA lines starting with ';' are skipped as comments
+    pub fn skip_comments(mut self, skip: bool) -> Self {
+        self.skip_comments = skip;
+        self
+    }
+
+    /// Set whether a trailing '*' is removed from FASTA sequences
+    pub fn strip_trailing_stop(mut self, strip: bool) -> Self {
+        self.strip_trailing_stop = strip;
+        self
+    }
+
+    /// Limit the length of sequences, to guard against malformed input
+    pub fn max_sequence_length(mut self, length: usize) -> Self {
+        self.max_sequence_length = Some(length);
+        self
+    }
+
+    /// Limit the length of header lines
+    pub fn max_header_length(mut self, length: usize) -> Self {
+        self.max_header_length = Some(length);
+        self
+    }
+
+    /// Check a header's length against the limit, if there is one
+    pub(crate) fn check_header_length(
+        &self,
+        format: &'static str,
+        header: &[u8],
+        position: Position,
+    ) -> Result<()> {
+        check_length(
+            format,
+            "header",
+            self.max_header_length,
+            header.len(),
+            position,
+        )
+    }
+
+    /// Check the length of the sequence read so far against the limit, if
+    /// there is one
+    pub(crate) fn check_sequence_length(
+        &self,
+        format: &'static str,
+        length: usize,
+        position: Position,
+    ) -> Result<()> {
+        check_length(
+            format,
+            "sequence",
+            self.max_sequence_length,
+            length,
+            position,
+        )
+    }
+
+    /// Check whether a record's sequence is empty, if empty records are
+    /// errors
+    pub(crate) fn check_empty(
+        &self,
+        format: &'static str,
+        header: &[u8],
+        sequence: &[u8],
+        position: Position,
+    ) -> Result<()> {
+        if !sequence.is_empty() || self.empty_records != EmptyRecords::Error {
+            return Ok(());
+        }
+        let message = format!("{} record has an empty sequence", format);
+        Err(match format {
+            "FASTQ" => PrseqError::invalid_fastq(message, position, header),
+            _ => PrseqError::invalid_fasta(message, position, header),
+        })
+    }
+
+    /// Whether a record with this sequence is to be skipped as empty
+    pub(crate) fn skips_empty(&self, sequence: &[u8]) -> bool {
+        sequence.is_empty() && self.empty_records == EmptyRecords::Skip
+    }
+
+    /// Whether a (trimmed) FASTA line is a comment to skip
+    pub(crate) fn is_comment(&self, line: &[u8]) -> bool {
+        self.skip_comments && line.starts_with(b";")
+    }
+
+    /// Apply the soft-mask option to a sequence
+    pub(crate) fn apply_soft_mask(&self, sequence: &mut [u8]) {
+        match self.soft_mask {
+            SoftMask::Keep => {}
+            SoftMask::Unmask => unmask(sequence),
+            SoftMask::MaskToN => mask_to_n(sequence),
+        }
+    }
+
+    /// Check a record's sequence against the alphabet, if there is one
+    pub(crate) fn check_sequence(
+        &self,
+        header: &[u8],
+        sequence: &[u8],
+        position: Position,
+    ) -> Result<()> {
+        let Some(alphabet) = self.alphabet else {
+            return Ok(());
+        };
+        match alphabet.find_invalid(sequence) {
+            Some(offset) => Err(PrseqError::InvalidSequence {
+                id: String::from_utf8_lossy(split_header(header).0).into_owned(),
+                alphabet,
+                offset,
+                byte: sequence[offset],
+                position,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Decide whether a reader should skip the record that caused `error`,
+    /// logging a warning if asked to
+    pub(crate) fn skips(&self, error: &PrseqError) -> bool {
+        if matches!(error, PrseqError::Io(_)) {
+            return false;
+        }
+        match self.on_error {
+            OnError::Fail => false,
+            OnError::Skip => true,
+            OnError::Warn => {
+                log::warn!("Skipping malformed record: {}", error);
+                true
+            }
+        }
+    }
+}
+
+/// A RecordTooLong error if `length` is over `limit`
+fn check_length(
+    format: &'static str,
+    part: &'static str,
+    limit: Option<usize>,
+    length: usize,
+    position: Position,
+) -> Result<()> {
+    match limit {
+        Some(limit) if length > limit => Err(PrseqError::RecordTooLong {
+            format,
+            part,
+            limit,
+            position,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// The IDs a reader has seen, and the record number each was first seen at
+#[derive(Debug, Default)]
+pub(crate) struct IdTracker {
+    seen: HashMap<Box<[u8]>, u64>,
+    duplicates: u64,
+}
+
+impl IdTracker {
+    /// Check a record's ID against those seen before, if the options ask
+    /// for duplicates to be reported
+    pub(crate) fn check(
+        &mut self,
+        options: &ParserOptions,
+        header: &[u8],
+        position: Position,
+    ) -> Result<()> {
+        if options.duplicate_ids == DuplicateIds::Allow {
+            return Ok(());
+        }
+        let id = split_header(header).0;
+        let Some(&first) = self.seen.get(id) else {
+            self.seen.insert(id.into(), position.record);
+            return Ok(());
+        };
+        self.duplicates += 1;
+        let error = PrseqError::DuplicateId {
+            id: String::from_utf8_lossy(id).into_owned(),
+            first,
+            position,
+        };
+        match options.duplicate_ids {
+            DuplicateIds::Error => Err(error),
+            _ => {
+                log::warn!("{}", error);
+                Ok(())
+            }
+        }
+    }
+
+    /// The number of records found with an ID seen before
+    pub(crate) fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+}
@@ -0,0 +1,270 @@
+use crate::error::Result;
+use crate::fasta::{FastaReader, FastaRecord};
+use std::fmt;
+use std::ops::Range;
+
+/// The bases for each 2-bit code
+const BASES: [u8; 4] = [b'A', b'C', b'G', b'T'];
+
+/// The 2-bit code of an upper case base, or None if it is not A, C, G or T
+fn code(base: u8) -> Option<u8> {
+    match base {
+        b'A' => Some(0),
+        b'C' => Some(1),
+        b'G' => Some(2),
+        b'T' => Some(3),
+        _ => None,
+    }
+}
+
+/// A run of a byte other than A, C, G or T (e.g. N, another IUPAC code or
+/// a gap), stored upper case
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Exception {
+    start: usize,
+    length: usize,
+    byte: u8,
+}
+
+/// A sequence stored in 2 bits per base, for holding whole genomes in
+/// memory
+///
+/// A, C, G and T are packed four to a byte. Anything else (N, other IUPAC
+/// codes, gaps, U) is kept in a list of runs, as are the runs of lower case
+/// (soft-masked) bases, so converting back gives exactly the sequence
+/// packed. A genome's long runs of N cost a few bytes each.
+///
+/// ```
+/// use prseq::packed::PackedSeq;
+///
+/// let packed = PackedSeq::new(b"ACGTNNNNacgtRY");
+/// assert_eq!(packed.len(), 14);
+/// assert_eq!(packed.get(4), Some(b'N'));
+/// assert_eq!(packed.get(8), Some(b'a'));
+/// assert_eq!(packed.to_string(), "ACGTNNNNacgtRY");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct PackedSeq {
+    length: usize,
+    packed: Vec<u8>,
+    exceptions: Vec<Exception>,
+    /// (start, length) of each run of lower case bases
+    lower: Vec<(usize, usize)>,
+}
+
+/// Add position `i` to a list of runs, extending the last run if `i`
+/// follows it
+fn extend_runs(runs: &mut Vec<(usize, usize)>, i: usize) {
+    match runs.last_mut() {
+        Some((start, length)) if *start + *length == i => *length += 1,
+        _ => runs.push((i, 1)),
+    }
+}
+
+/// The runs that overlap `range`, as ranges relative to its start
+fn overlaps<'a, T: 'a>(
+    runs: &'a [T],
+    range: &'a Range<usize>,
+    bounds: impl Fn(&T) -> (usize, usize) + Copy + 'a,
+) -> impl Iterator<Item = (Range<usize>, &'a T)> + 'a {
+    // The runs are sorted and do not overlap, so the first run that can
+    // overlap the range is the first that ends after its start.
+    let first = runs.partition_point(|run| {
+        let (start, length) = bounds(run);
+        start + length <= range.start
+    });
+    runs[first..]
+        .iter()
+        .take_while(move |run| bounds(run).0 < range.end)
+        .map(move |run| {
+            let (start, length) = bounds(run);
+            let from = start.max(range.start) - range.start;
+            let to = (start + length).min(range.end) - range.start;
+            (from..to, run)
+        })
+}
+
+impl PackedSeq {
+    /// Pack a sequence
+    pub fn new(sequence: &[u8]) -> Self {
+        let mut packed = vec![0; sequence.len().div_ceil(4)];
+        let mut exceptions: Vec<Exception> = Vec::new();
+        let mut lower = Vec::new();
+        for (i, &base) in sequence.iter().enumerate() {
+            if base.is_ascii_lowercase() {
+                extend_runs(&mut lower, i);
+            }
+            let base = base.to_ascii_uppercase();
+            match code(base) {
+                Some(code) => packed[i / 4] |= code << (6 - 2 * (i % 4)),
+                None => match exceptions.last_mut() {
+                    Some(run) if run.byte == base && run.start + run.length == i => run.length += 1,
+                    _ => exceptions.push(Exception {
+                        start: i,
+                        length: 1,
+                        byte: base,
+                    }),
+                },
+            }
+        }
+        PackedSeq {
+            length: sequence.len(),
+            packed,
+            exceptions,
+            lower,
+        }
+    }
+
+    /// The number of bases
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Whether there are no bases
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// The base at offset `i`, or None if it is past the end
+    pub fn get(&self, i: usize) -> Option<u8> {
+        self.get_range(i..i.checked_add(1)?).map(|bases| bases[0])
+    }
+
+    /// The bases in `range` (0-based, end-exclusive), or None if it is not
+    /// within the sequence
+    pub fn get_range(&self, range: Range<usize>) -> Option<Vec<u8>> {
+        if range.start > range.end || range.end > self.length {
+            return None;
+        }
+        let mut bases: Vec<u8> = range
+            .clone()
+            .map(|i| BASES[(self.packed[i / 4] >> (6 - 2 * (i % 4)) & 3) as usize])
+            .collect();
+        for (run, exception) in overlaps(&self.exceptions, &range, |e| (e.start, e.length)) {
+            bases[run].fill(exception.byte);
+        }
+        for (run, _) in overlaps(&self.lower, &range, |&bounds| bounds) {
+            bases[run].make_ascii_lowercase();
+        }
+        Some(bases)
+    }
+
+    /// The whole sequence, unpacked
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.get_range(0..self.length)
+            .expect("the whole sequence is in range")
+    }
+
+    /// The number of bases that are not A, C, G or T (in either case)
+    pub fn exception_count(&self) -> usize {
+        self.exceptions.iter().map(|run| run.length).sum()
+    }
+
+    /// The approximate number of bytes of memory used by the packed
+    /// sequence, beyond the size of the PackedSeq itself
+    pub fn heap_size(&self) -> usize {
+        self.packed.capacity()
+            + self.exceptions.capacity() * std::mem::size_of::<Exception>()
+            + self.lower.capacity() * std::mem::size_of::<(usize, usize)>()
+    }
+}
+
+impl From<&[u8]> for PackedSeq {
+    fn from(sequence: &[u8]) -> Self {
+        PackedSeq::new(sequence)
+    }
+}
+
+impl From<&str> for PackedSeq {
+    fn from(sequence: &str) -> Self {
+        PackedSeq::new(sequence.as_bytes())
+    }
+}
+
+impl fmt::Display for PackedSeq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from_utf8_lossy(&self.to_vec()))
+    }
+}
+
+/// A FASTA record with its sequence packed (see `PackedSeq`)
+///
+/// ```
+/// use prseq::packed::PackedRecord;
+/// use prseq::FastaRecord;
+///
+/// let record = FastaRecord { header: "chr1".into(), sequence: "ACGTNNacgt".into() };
+/// let packed = PackedRecord::from(&record);
+/// assert_eq!(packed.to_record(), record);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct PackedRecord {
+    pub header: String,
+    pub sequence: PackedSeq,
+}
+
+impl PackedRecord {
+    /// Unpack into a FastaRecord
+    pub fn to_record(&self) -> FastaRecord {
+        FastaRecord {
+            header: self.header.clone(),
+            // Only the bytes of a str were packed, so they are still UTF-8.
+            sequence: String::from_utf8(self.sequence.to_vec())
+                .expect("packed sequences are UTF-8"),
+        }
+    }
+}
+
+impl From<&FastaRecord> for PackedRecord {
+    fn from(record: &FastaRecord) -> Self {
+        PackedRecord {
+            header: record.header.clone(),
+            sequence: PackedSeq::new(record.sequence.as_bytes()),
+        }
+    }
+}
+
+impl From<FastaRecord> for PackedRecord {
+    fn from(record: FastaRecord) -> Self {
+        PackedRecord {
+            sequence: PackedSeq::new(record.sequence.as_bytes()),
+            header: record.header,
+        }
+    }
+}
+
+impl From<PackedRecord> for FastaRecord {
+    fn from(record: PackedRecord) -> Self {
+        record.to_record()
+    }
+}
+
+/// Iterator over packed FASTA records, from `FastaReader::packed_records`
+pub struct PackedRecords {
+    reader: FastaReader,
+}
+
+impl PackedRecords {
+    /// The underlying reader, e.g. to query record offsets
+    pub fn reader(&self) -> &FastaReader {
+        &self.reader
+    }
+}
+
+impl Iterator for PackedRecords {
+    type Item = Result<PackedRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader
+            .next_ref()
+            .map(|result| result.and_then(|record| record.to_packed()))
+    }
+}
+
+impl FastaReader {
+    /// Turn this reader into an iterator over packed records, packing each
+    /// sequence straight from the reader's buffer
+    pub fn packed_records(self) -> PackedRecords {
+        PackedRecords { reader: self }
+    }
+}
@@ -0,0 +1,454 @@
+use crate::error::{PrseqError, Result};
+use crate::fastq::{FastqReader, FastqRecord};
+use crate::quality::mean_quality;
+use crate::writer::FastqWriter;
+use std::io::{self, Read, Write};
+use std::ops::RangeBounds;
+use std::path::Path;
+
+/// The part of a FASTQ header that both reads of a pair share
+///
+/// This is the first whitespace-separated word of the header, which drops
+/// Illumina (CASAVA 1.8+) comments such as ` 1:N:0:ATCACG`, with any
+/// trailing `/1` or `/2` removed.
+pub fn pair_id(header: &str) -> &str {
+    let name = header.split_whitespace().next().unwrap_or("");
+    name.strip_suffix("/1")
+        .or_else(|| name.strip_suffix("/2"))
+        .unwrap_or(name)
+}
+
+/// Check that two records form a pair, returning an error naming the
+/// (1-based) pair number if their IDs differ
+pub(crate) fn check_pair(r1: &FastqRecord, r2: &FastqRecord, pair_number: usize) -> Result<()> {
+    if pair_id(&r1.header) == pair_id(&r2.header) {
+        Ok(())
+    } else {
+        Err(PrseqError::PairMismatch {
+            pair: pair_number,
+            message: format!(
+                "Paired reads are out of sync at pair {}: R1 ID '{}' does not match R2 ID '{}'",
+                pair_number, r1.header, r2.header
+            ),
+        })
+    }
+}
+
+/// Iterator over read pairs from two FASTQ sources (R1 and R2)
+///
+/// The IDs of the two reads in each pair are checked with `pair_id`, and an
+/// error is returned if they differ or if one source ends before the other.
+pub struct PairedFastqReader {
+    r1: FastqReader,
+    r2: FastqReader,
+    pairs: usize,
+    finished: bool,
+}
+
+impl PairedFastqReader {
+    /// Create a new PairedFastqReader from two FASTQ readers
+    pub fn new(r1: FastqReader, r2: FastqReader) -> Self {
+        PairedFastqReader {
+            r1,
+            r2,
+            pairs: 0,
+            finished: false,
+        }
+    }
+
+    /// Create a new PairedFastqReader from two file paths
+    pub fn from_files<P: AsRef<Path>, Q: AsRef<Path>>(r1: P, r2: Q) -> Result<Self> {
+        Self::from_files_with_capacity(r1, r2, 64 * 1024)
+    }
+
+    /// Create a new PairedFastqReader from two file paths with a sequence
+    /// size hint
+    pub fn from_files_with_capacity<P: AsRef<Path>, Q: AsRef<Path>>(
+        r1: P,
+        r2: Q,
+        sequence_size_hint: usize,
+    ) -> Result<Self> {
+        Ok(Self::new(
+            FastqReader::from_file_with_capacity(r1, sequence_size_hint)?,
+            FastqReader::from_file_with_capacity(r2, sequence_size_hint)?,
+        ))
+    }
+
+    /// Create a new PairedFastqReader from two readable sources with
+    /// compression detection
+    pub fn from_readers_with_capacity<R1, R2>(
+        r1: R1,
+        r2: R2,
+        sequence_size_hint: usize,
+    ) -> Result<Self>
+    where
+        R1: Read + Send + 'static,
+        R2: Read + Send + 'static,
+    {
+        Ok(Self::new(
+            FastqReader::from_reader_with_capacity(r1, sequence_size_hint)?,
+            FastqReader::from_reader_with_capacity(r2, sequence_size_hint)?,
+        ))
+    }
+
+    fn read_next(&mut self) -> Result<Option<(FastqRecord, FastqRecord)>> {
+        let pair_number = self.pairs + 1;
+        let (r1, r2) = match (self.r1.next().transpose()?, self.r2.next().transpose()?) {
+            (Some(r1), Some(r2)) => (r1, r2),
+            (None, None) => return Ok(None),
+            (r1, _) => {
+                let (ended, other) = if r1.is_some() {
+                    ("R2", "R1")
+                } else {
+                    ("R1", "R2")
+                };
+                return Err(PrseqError::PairMismatch {
+                    pair: pair_number,
+                    message: format!(
+                        "Paired reads are out of sync: {} ended after {} records but {} has more",
+                        ended, self.pairs, other
+                    ),
+                });
+            }
+        };
+        check_pair(&r1, &r2, pair_number)?;
+        self.pairs = pair_number;
+        Ok(Some((r1, r2)))
+    }
+}
+
+impl Iterator for PairedFastqReader {
+    type Item = Result<(FastqRecord, FastqRecord)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.read_next() {
+            Ok(Some(pair)) => Some(Ok(pair)),
+            Ok(None) => None,
+            Err(e) => {
+                // Once the two sources disagree, later pairs are meaningless.
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Iterator over read pairs from a single interleaved FASTQ source, in
+/// which each R1 read is immediately followed by its R2 mate
+pub struct InterleavedFastqReader {
+    reader: FastqReader,
+    pairs: usize,
+    finished: bool,
+}
+
+impl InterleavedFastqReader {
+    /// Create a new InterleavedFastqReader from a FASTQ reader
+    pub fn new(reader: FastqReader) -> Self {
+        InterleavedFastqReader {
+            reader,
+            pairs: 0,
+            finished: false,
+        }
+    }
+
+    /// Create a new InterleavedFastqReader from a file path
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(FastqReader::from_file(path)?))
+    }
+
+    /// Create a new InterleavedFastqReader from any readable source with
+    /// compression detection
+    pub fn from_reader_with_capacity<R: Read + Send + 'static>(
+        reader: R,
+        sequence_size_hint: usize,
+    ) -> Result<Self> {
+        Ok(Self::new(FastqReader::from_reader_with_capacity(
+            reader,
+            sequence_size_hint,
+        )?))
+    }
+
+    fn read_next(&mut self) -> Result<Option<(FastqRecord, FastqRecord)>> {
+        let pair_number = self.pairs + 1;
+        let r1 = match self.reader.next().transpose()? {
+            Some(r1) => r1,
+            None => return Ok(None),
+        };
+        let r2 = match self.reader.next().transpose()? {
+            Some(r2) => r2,
+            None => {
+                return Err(PrseqError::PairMismatch {
+                    pair: pair_number,
+                    message: format!(
+                        "Interleaved FASTQ ends with an unpaired read '{}' (pair {})",
+                        r1.header, pair_number
+                    ),
+                })
+            }
+        };
+        check_pair(&r1, &r2, pair_number)?;
+        self.pairs = pair_number;
+        Ok(Some((r1, r2)))
+    }
+}
+
+impl Iterator for InterleavedFastqReader {
+    type Item = Result<(FastqRecord, FastqRecord)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.read_next() {
+            Ok(Some(pair)) => Some(Ok(pair)),
+            Ok(None) => None,
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Write read pairs (e.g. from a PairedFastqReader) to a single interleaved
+/// FASTQ writer, returning the number of pairs written
+pub fn interleave<I, W>(pairs: I, writer: &mut FastqWriter<W>) -> Result<usize>
+where
+    I: IntoIterator<Item = Result<(FastqRecord, FastqRecord)>>,
+    W: Write,
+{
+    let mut count = 0;
+    for pair in pairs {
+        let (r1, r2) = pair?;
+        writer.write_record(&r1)?;
+        writer.write_record(&r2)?;
+        count += 1;
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+/// Write read pairs (e.g. from an InterleavedFastqReader) to separate R1
+/// and R2 writers, returning the number of pairs written
+pub fn deinterleave<I, W1, W2>(
+    pairs: I,
+    r1_writer: &mut FastqWriter<W1>,
+    r2_writer: &mut FastqWriter<W2>,
+) -> Result<usize>
+where
+    I: IntoIterator<Item = Result<(FastqRecord, FastqRecord)>>,
+    W1: Write,
+    W2: Write,
+{
+    let mut count = 0;
+    for pair in pairs {
+        let (r1, r2) = pair?;
+        r1_writer.write_record(&r1)?;
+        r2_writer.write_record(&r2)?;
+        count += 1;
+    }
+    r1_writer.flush()?;
+    r2_writer.flush()?;
+    Ok(count)
+}
+
+/// Iterator adaptor that keeps the read pairs whose mates both pass a
+/// predicate, from `PairedFilterExt::filter_pairs`
+///
+/// A pair is dropped when either mate fails, so R1 and R2 stay in sync.
+/// When exactly one mate passes it is an orphan, which can be written to a
+/// separate (singletons) writer with `orphans_to`. Errors are passed
+/// through.
+pub struct FilterPairs<I, P, W: Write = io::Sink> {
+    pairs: I,
+    predicate: P,
+    orphans: Option<FastqWriter<W>>,
+    kept: u64,
+    dropped: u64,
+    orphaned: u64,
+}
+
+impl<I, P, W: Write> FilterPairs<I, P, W> {
+    /// Write each orphan (the mate that passed, when the other failed) to
+    /// `writer`. The writer is flushed when the pairs run out.
+    pub fn orphans_to<V: Write>(self, writer: FastqWriter<V>) -> FilterPairs<I, P, V> {
+        FilterPairs {
+            pairs: self.pairs,
+            predicate: self.predicate,
+            orphans: Some(writer),
+            kept: self.kept,
+            dropped: self.dropped,
+            orphaned: self.orphaned,
+        }
+    }
+
+    /// The number of pairs kept so far
+    pub fn kept(&self) -> u64 {
+        self.kept
+    }
+
+    /// The number of pairs dropped so far, including those with an orphan
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// The number of orphans so far (written to the orphan writer, if
+    /// there is one)
+    pub fn orphans(&self) -> u64 {
+        self.orphaned
+    }
+
+    /// Return the orphan writer, if there is one
+    pub fn into_orphan_writer(self) -> Option<FastqWriter<W>> {
+        self.orphans
+    }
+}
+
+impl<I, P, W> Iterator for FilterPairs<I, P, W>
+where
+    I: Iterator<Item = Result<(FastqRecord, FastqRecord)>>,
+    P: FnMut(&FastqRecord) -> bool,
+    W: Write,
+{
+    type Item = Result<(FastqRecord, FastqRecord)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (r1, r2) = match self.pairs.next() {
+                Some(Ok(pair)) => pair,
+                Some(Err(e)) => return Some(Err(e)),
+                None => {
+                    let flushed = self.orphans.as_mut().map_or(Ok(()), FastqWriter::flush);
+                    return flushed.err().map(|e| Err(e.into()));
+                }
+            };
+            // Both mates are always tested, so the predicate sees every read.
+            let (r1_passed, r2_passed) = ((self.predicate)(&r1), (self.predicate)(&r2));
+            let orphan = match (r1_passed, r2_passed) {
+                (true, true) => {
+                    self.kept += 1;
+                    return Some(Ok((r1, r2)));
+                }
+                (true, false) => Some(&r1),
+                (false, true) => Some(&r2),
+                (false, false) => None,
+            };
+            self.dropped += 1;
+            if let Some(orphan) = orphan {
+                self.orphaned += 1;
+                if let Some(writer) = &mut self.orphans {
+                    if let Err(e) = writer.write_record(orphan) {
+                        return Some(Err(e.into()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A read pair with whether each mate passed a predicate, from
+/// `PairedFilterExt::flag_pairs`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlaggedPair {
+    pub r1: FastqRecord,
+    pub r2: FastqRecord,
+    pub r1_passed: bool,
+    pub r2_passed: bool,
+}
+
+impl FlaggedPair {
+    /// Whether both mates passed, so the pair would be kept by
+    /// `filter_pairs`
+    pub fn passed(&self) -> bool {
+        self.r1_passed && self.r2_passed
+    }
+
+    /// The mate that passed when the other failed
+    pub fn orphan(&self) -> Option<&FastqRecord> {
+        match (self.r1_passed, self.r2_passed) {
+            (true, false) => Some(&self.r1),
+            (false, true) => Some(&self.r2),
+            _ => None,
+        }
+    }
+}
+
+/// Filtering for read pairs (e.g. from a `PairedFastqReader` or
+/// `InterleavedFastqReader`) that keeps the mates in sync
+///
+/// ```
+/// use prseq::paired::PairedFilterExt;
+/// use prseq::{FastqWriter, PairedFastqReader};
+/// use std::io::Cursor;
+///
+/// let r1 = b"@a/1\nACGTACGT\n+\nIIIIIIII\n@b/1\nACGTACGT\n+\nIIIIIIII\n";
+/// let r2 = b"@a/2\nTTTTTTTT\n+\nIIIIIIII\n@b/2\nTT\n+\nII\n";
+/// let pairs = PairedFastqReader::from_readers_with_capacity(Cursor::new(r1), Cursor::new(r2), 64)
+///     .unwrap();
+/// let mut filtered = pairs
+///     .filter_pair_length(4..)
+///     .orphans_to(FastqWriter::new(Vec::new()));
+/// let kept: Vec<String> = filtered.by_ref().map(|pair| pair.unwrap().0.header).collect();
+/// assert_eq!(kept, ["a/1"]);
+/// let orphans = filtered.into_orphan_writer().unwrap().into_inner();
+/// assert_eq!(orphans, b"@b/1\nACGTACGT\n+\nIIIIIIII\n");
+/// ```
+pub trait PairedFilterExt: Iterator<Item = Result<(FastqRecord, FastqRecord)>> + Sized {
+    /// Keep the pairs for which `predicate` returns true for both mates
+    fn filter_pairs<P>(self, predicate: P) -> FilterPairs<Self, P>
+    where
+        P: FnMut(&FastqRecord) -> bool,
+    {
+        FilterPairs {
+            pairs: self,
+            predicate,
+            orphans: None,
+            kept: 0,
+            dropped: 0,
+            orphaned: 0,
+        }
+    }
+
+    /// Keep the pairs whose mates' lengths are both in `lengths`
+    fn filter_pair_length<B>(
+        self,
+        lengths: B,
+    ) -> FilterPairs<Self, impl FnMut(&FastqRecord) -> bool>
+    where
+        B: RangeBounds<usize>,
+    {
+        self.filter_pairs(move |record| lengths.contains(&record.sequence.len()))
+    }
+
+    /// Keep the pairs whose mates both have a mean (Phred+33) quality of at
+    /// least `min_quality`. Empty reads fail.
+    fn filter_pair_quality(
+        self,
+        min_quality: f64,
+    ) -> FilterPairs<Self, impl FnMut(&FastqRecord) -> bool> {
+        self.filter_pairs(move |record| {
+            mean_quality(record.quality.as_bytes()).is_some_and(|mean| mean >= min_quality)
+        })
+    }
+
+    /// Test both mates of every pair with `predicate`, keeping all pairs
+    /// but flagging which mates passed
+    fn flag_pairs<P>(self, mut predicate: P) -> impl Iterator<Item = Result<FlaggedPair>>
+    where
+        P: FnMut(&FastqRecord) -> bool,
+    {
+        self.map(move |pair| {
+            pair.map(|(r1, r2)| FlaggedPair {
+                r1_passed: predicate(&r1),
+                r2_passed: predicate(&r2),
+                r1,
+                r2,
+            })
+        })
+    }
+}
+
+impl<I> PairedFilterExt for I where I: Iterator<Item = Result<(FastqRecord, FastqRecord)>> {}
@@ -0,0 +1,126 @@
+use crate::error::{PrseqError, Result};
+use rayon::prelude::*;
+use rayon::ThreadPool;
+
+/// The number of records read (and then processed in parallel) at a time
+pub const DEFAULT_BATCH_SIZE: usize = 1024;
+
+/// Parallel processing of the records from a reader
+///
+/// Records are still parsed sequentially, in batches, but each batch is
+/// processed on a rayon thread pool while the next batch is being read.
+/// This is implemented for every iterator of `Result`s, so in particular
+/// for FastaReader and FastqReader.
+pub trait ParallelRecords<T>: Iterator<Item = Result<T>> + Sized {
+    /// Apply `f` to every record on `threads` threads (0 for one per CPU),
+    /// returning an iterator over the results in input order
+    ///
+    /// Errors from the reader are passed through in their original position.
+    fn par_map<U, F>(self, threads: usize, f: F) -> Result<ParMap<Self, T, U, F>>
+    where
+        F: Fn(T) -> U + Send + Sync,
+    {
+        Ok(ParMap {
+            records: self,
+            pool: build_pool(threads)?,
+            f,
+            batch_size: DEFAULT_BATCH_SIZE,
+            pending: None,
+            output: Vec::new().into_iter(),
+        })
+    }
+
+    /// Call `f` on every record on `threads` threads (0 for one per CPU), in
+    /// no particular order, stopping at the first error from the reader
+    fn process_parallel<F>(mut self, threads: usize, f: F) -> Result<()>
+    where
+        Self: Send,
+        T: Send,
+        F: Fn(T) + Send + Sync,
+    {
+        let pool = build_pool(threads)?;
+        pool.install(|| {
+            let mut batch = read_batch(&mut self, DEFAULT_BATCH_SIZE);
+            while !batch.is_empty() {
+                let (next, processed) = rayon::join(
+                    || read_batch(&mut self, DEFAULT_BATCH_SIZE),
+                    || batch.into_par_iter().try_for_each(|record| record.map(&f)),
+                );
+                processed?;
+                batch = next;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<I, T> ParallelRecords<T> for I where I: Iterator<Item = Result<T>> {}
+
+/// Iterator over the results of processing records in parallel, created by
+/// `ParallelRecords::par_map`
+pub struct ParMap<I, T, U, F> {
+    records: I,
+    pool: ThreadPool,
+    f: F,
+    batch_size: usize,
+    pending: Option<Vec<Result<T>>>,
+    output: std::vec::IntoIter<Result<U>>,
+}
+
+impl<I, T, U, F> ParMap<I, T, U, F> {
+    /// Set the number of records read and processed at a time
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+}
+
+impl<I, T, U, F> Iterator for ParMap<I, T, U, F>
+where
+    I: Iterator<Item = Result<T>> + Send,
+    T: Send,
+    U: Send,
+    F: Fn(T) -> U + Send + Sync,
+{
+    type Item = Result<U>;
+
+    fn next(&mut self) -> Option<Result<U>> {
+        if let Some(result) = self.output.next() {
+            return Some(result);
+        }
+        let batch = match self.pending.take() {
+            Some(batch) => batch,
+            None => read_batch(&mut self.records, self.batch_size),
+        };
+        if batch.is_empty() {
+            return None;
+        }
+
+        let (records, f, batch_size) = (&mut self.records, &self.f, self.batch_size);
+        let (next, processed) = self.pool.install(|| {
+            rayon::join(
+                || read_batch(records, batch_size),
+                || {
+                    batch
+                        .into_par_iter()
+                        .map(|record| record.map(f))
+                        .collect::<Vec<_>>()
+                },
+            )
+        });
+        self.pending = Some(next);
+        self.output = processed.into_iter();
+        self.output.next()
+    }
+}
+
+fn read_batch<I: Iterator>(records: &mut I, batch_size: usize) -> Vec<I::Item> {
+    records.by_ref().take(batch_size).collect()
+}
+
+fn build_pool(threads: usize) -> Result<ThreadPool> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(|e| PrseqError::Io(std::io::Error::other(e)))
+}
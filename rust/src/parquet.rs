@@ -0,0 +1,164 @@
+use crate::arrow::{schema, ArrowRecord, RecordBatchBuilder};
+use ::parquet::arrow::ArrowWriter;
+use ::parquet::basic::{Compression, GzipLevel, ZstdLevel};
+use ::parquet::file::properties::WriterProperties;
+use arrow_array::RecordBatch;
+use std::fs::File;
+use std::io::{self, Result, Write};
+use std::path::Path;
+
+/// The number of records gathered before they are handed to the Parquet
+/// writer, which buffers them until it has a whole row group
+const BATCH_SIZE: usize = 8192;
+
+/// The compression codec for the pages of a Parquet file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParquetCompression {
+    None,
+    Snappy,
+    Gzip,
+    Zstd,
+}
+
+/// Options for writing Parquet files
+#[derive(Debug, Clone)]
+pub struct ParquetOptions {
+    /// The maximum number of records in each row group (default 1,048,576)
+    pub row_group_size: usize,
+    /// The compression codec (default Zstd)
+    pub compression: ParquetCompression,
+}
+
+impl Default for ParquetOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParquetOptions {
+    /// Create options with the defaults
+    pub fn new() -> Self {
+        ParquetOptions {
+            row_group_size: 1024 * 1024,
+            compression: ParquetCompression::Zstd,
+        }
+    }
+
+    /// Set the maximum number of records in each row group
+    pub fn row_group_size(mut self, row_group_size: usize) -> Self {
+        self.row_group_size = row_group_size;
+        self
+    }
+
+    /// Set the compression codec
+    pub fn compression(mut self, compression: ParquetCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    fn properties(&self) -> Result<WriterProperties> {
+        if self.row_group_size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Parquet row group size must be at least 1",
+            ));
+        }
+        let compression = match self.compression {
+            ParquetCompression::None => Compression::UNCOMPRESSED,
+            ParquetCompression::Snappy => Compression::SNAPPY,
+            ParquetCompression::Gzip => Compression::GZIP(GzipLevel::default()),
+            ParquetCompression::Zstd => Compression::ZSTD(ZstdLevel::default()),
+        };
+        Ok(WriterProperties::builder()
+            .set_max_row_group_size(self.row_group_size)
+            .set_compression(compression)
+            .build())
+    }
+}
+
+/// Writes records to a Parquet file, with the columns of
+/// `prseq::arrow::schema()`
+///
+/// Records are streamed out a row group at a time. The file is only valid
+/// once `close` has been called, which writes its footer.
+///
+/// ```no_run
+/// use prseq::parquet::{ParquetCompression, ParquetOptions, ParquetSequenceWriter};
+/// use prseq::FastqReader;
+///
+/// let options = ParquetOptions::new().compression(ParquetCompression::Snappy);
+/// let mut writer = ParquetSequenceWriter::to_file_with_options("reads.parquet", &options)?;
+/// for record in FastqReader::from_file("reads.fastq.gz")? {
+///     writer.write_record(&record?)?;
+/// }
+/// writer.close()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct ParquetSequenceWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    builder: RecordBatchBuilder,
+}
+
+fn parquet_error(error: ::parquet::errors::ParquetError) -> io::Error {
+    io::Error::other(error)
+}
+
+impl ParquetSequenceWriter<File> {
+    /// Create a new ParquetSequenceWriter that writes to a file path, with
+    /// the default options
+    pub fn to_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::to_file_with_options(path, &ParquetOptions::new())
+    }
+
+    /// Create a new ParquetSequenceWriter that writes to a file path
+    pub fn to_file_with_options<P: AsRef<Path>>(path: P, options: &ParquetOptions) -> Result<Self> {
+        Self::with_options(File::create(path)?, options)
+    }
+}
+
+impl<W: Write + Send> ParquetSequenceWriter<W> {
+    /// Create a new ParquetSequenceWriter with the default options
+    pub fn new(writer: W) -> Result<Self> {
+        Self::with_options(writer, &ParquetOptions::new())
+    }
+
+    /// Create a new ParquetSequenceWriter
+    pub fn with_options(writer: W, options: &ParquetOptions) -> Result<Self> {
+        let properties = options.properties()?;
+        Ok(ParquetSequenceWriter {
+            writer: ArrowWriter::try_new(writer, schema(), Some(properties))
+                .map_err(parquet_error)?,
+            builder: RecordBatchBuilder::new(),
+        })
+    }
+
+    /// Write a record
+    pub fn write_record<R: ArrowRecord>(&mut self, record: &R) -> Result<()> {
+        self.builder.push(record);
+        if self.builder.len() >= BATCH_SIZE {
+            self.write_pending()?;
+        }
+        Ok(())
+    }
+
+    /// Write a batch made by `prseq::arrow`
+    pub fn write_batch(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.write_pending()?;
+        self.writer.write(batch).map_err(parquet_error)
+    }
+
+    fn write_pending(&mut self) -> Result<()> {
+        if self.builder.is_empty() {
+            return Ok(());
+        }
+        let batch = self.builder.finish();
+        self.writer.write(&batch).map_err(parquet_error)
+    }
+
+    /// Write any buffered records and the file footer, returning the
+    /// underlying destination
+    pub fn close(mut self) -> Result<W> {
+        self.write_pending()?;
+        self.writer.into_inner().map_err(parquet_error)
+    }
+}
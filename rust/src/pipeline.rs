@@ -0,0 +1,229 @@
+use crate::error::Result;
+use crate::fastq::FastqRecord;
+use crate::record::SequenceRecord;
+use crate::trim::{AdapterTrimmer, PolyTrim};
+use crate::writer::RecordWriter;
+use std::ops::RangeBounds;
+
+/// A step of a pipeline: the record to pass on, or None to drop it
+type Step<R> = Box<dyn FnMut(R) -> Option<R> + Send>;
+
+/// A chain of filters and transformations applied to each record of a
+/// reader in a single streaming pass, ending (usually) in a writer
+///
+/// Each record is moved through the steps in turn, so that it is never
+/// copied between them, and is dropped by the first filter that rejects
+/// it. A pipeline can also be iterated over (with `into_iter()`, as its
+/// `map` and `filter` are not those of `Iterator`). Errors from the reader
+/// are passed through: `write_to` stops at the first, and iterating returns
+/// them (to skip malformed records instead, set the reader's
+/// `ParserOptions::on_error`).
+///
+/// ```
+/// use prseq::{FastqReader, FastqWriter, Pipeline};
+///
+/// let input = "@r1\nACGTACGT\n+\nIIIII###\n@r2\nACG\n+\nIII\n@r3\nGGGGCC\n+\nIIIIII\n";
+/// let mut writer = FastqWriter::new(Vec::new());
+/// let stats = Pipeline::new(FastqReader::from_string(input).unwrap())
+///     .trim_quality(2, 20)
+///     .filter_length(4..)
+///     .map(|mut record| {
+///         record.header.push_str(" trimmed");
+///         record
+///     })
+///     .write_to(&mut writer)
+///     .unwrap();
+/// assert_eq!((stats.read, stats.written), (3, 2));
+/// assert!(String::from_utf8(writer.into_inner()).unwrap().starts_with("@r1 trimmed\nACGTA\n"));
+/// ```
+pub struct Pipeline<I, R> {
+    records: I,
+    steps: Vec<Step<R>>,
+}
+
+/// The number of records a pipeline has read, and passed through all its
+/// steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PipelineStats {
+    /// The number of records read from the reader
+    pub read: u64,
+    /// The number of records that were not dropped (and were written, by
+    /// `write_to`)
+    pub written: u64,
+}
+
+impl<I, R> Pipeline<I, R>
+where
+    I: Iterator<Item = Result<R>>,
+{
+    /// Start a pipeline reading from `records`, usually a reader
+    pub fn new(records: I) -> Self {
+        Pipeline {
+            records,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Add a step that transforms each record, or drops it by returning
+    /// None
+    pub fn filter_map<F>(mut self, step: F) -> Self
+    where
+        F: FnMut(R) -> Option<R> + Send + 'static,
+    {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    /// Keep the records for which `predicate` returns true
+    pub fn filter<P>(self, mut predicate: P) -> Self
+    where
+        P: FnMut(&R) -> bool + Send + 'static,
+    {
+        self.filter_map(move |record| predicate(&record).then_some(record))
+    }
+
+    /// Transform each record
+    pub fn map<F>(self, mut transform: F) -> Self
+    where
+        F: FnMut(R) -> R + Send + 'static,
+    {
+        self.filter_map(move |record| Some(transform(record)))
+    }
+
+    /// Call `callback` with each record that reaches this point, e.g. to
+    /// collect statistics
+    pub fn inspect<F>(self, mut callback: F) -> Self
+    where
+        F: FnMut(&R) + Send + 'static,
+    {
+        self.map(move |record| {
+            callback(&record);
+            record
+        })
+    }
+
+    /// Run the pipeline to the end of its input, writing the records that
+    /// come through it to `writer` and then flushing it
+    ///
+    /// Pass `&mut writer` to keep the writer, e.g. to call `into_inner`.
+    pub fn write_to<W: RecordWriter<R>>(self, mut writer: W) -> Result<PipelineStats> {
+        let mut records = self.into_iter();
+        for record in records.by_ref() {
+            writer.write_record(&record?)?;
+        }
+        writer.flush()?;
+        Ok(records.stats())
+    }
+
+    /// Run the pipeline to the end of its input, discarding the records
+    /// that come through it, e.g. when a step collects what is needed
+    pub fn run(self) -> Result<PipelineStats> {
+        let mut records = self.into_iter();
+        for record in records.by_ref() {
+            record?;
+        }
+        Ok(records.stats())
+    }
+}
+
+impl<I, R> Pipeline<I, R>
+where
+    I: Iterator<Item = Result<R>>,
+    R: SequenceRecord,
+{
+    /// Keep the records whose sequence length is in `lengths`
+    pub fn filter_length<B>(self, lengths: B) -> Self
+    where
+        B: RangeBounds<usize> + Send + 'static,
+    {
+        self.filter(move |record| lengths.contains(&record.len()))
+    }
+}
+
+impl<I> Pipeline<I, FastqRecord>
+where
+    I: Iterator<Item = Result<FastqRecord>>,
+{
+    /// Trim adapters from each read (see `FastqRecord::trim_adapters`)
+    pub fn trim_adapters(self, trimmer: AdapterTrimmer) -> Self {
+        self.map(move |record| record.trim_adapters(&trimmer).0)
+    }
+
+    /// Trim each read by sliding-window quality trimming (see
+    /// `FastqRecord::trim_quality`)
+    pub fn trim_quality(self, window: usize, min_quality: u8) -> Self {
+        self.map(move |record| record.trim_quality(window, min_quality))
+    }
+
+    /// Trim the bases below `min_quality` from both ends of each read
+    pub fn trim_quality_ends(self, min_quality: u8) -> Self {
+        self.map(move |record| record.trim_quality_ends(min_quality))
+    }
+
+    /// Trim a homopolymer tail from each read
+    pub fn trim_poly_tail(self, trim: PolyTrim) -> Self {
+        self.map(move |record| record.trim_poly_tail(&trim))
+    }
+
+    /// Trim runs of at least `min_length` Ns from both ends of each read
+    pub fn trim_ns(self, min_length: usize) -> Self {
+        self.map(move |record| record.trim_ns(min_length))
+    }
+}
+
+impl<I, R> IntoIterator for Pipeline<I, R>
+where
+    I: Iterator<Item = Result<R>>,
+{
+    type Item = Result<R>;
+    type IntoIter = PipelineRecords<I, R>;
+
+    fn into_iter(self) -> PipelineRecords<I, R> {
+        PipelineRecords {
+            records: self.records,
+            steps: self.steps,
+            stats: PipelineStats::default(),
+        }
+    }
+}
+
+/// Iterator over the records that come through a pipeline, from
+/// `Pipeline::into_iter`
+pub struct PipelineRecords<I, R> {
+    records: I,
+    steps: Vec<Step<R>>,
+    stats: PipelineStats,
+}
+
+impl<I, R> PipelineRecords<I, R> {
+    /// The counts of records read and passed through so far
+    pub fn stats(&self) -> PipelineStats {
+        self.stats
+    }
+}
+
+impl<I, R> Iterator for PipelineRecords<I, R>
+where
+    I: Iterator<Item = Result<R>>,
+{
+    type Item = Result<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        'records: loop {
+            let mut record = match self.records.next()? {
+                Ok(record) => record,
+                Err(error) => return Some(Err(error)),
+            };
+            self.stats.read += 1;
+            for step in &mut self.steps {
+                match step(record) {
+                    Some(next) => record = next,
+                    None => continue 'records,
+                }
+            }
+            self.stats.written += 1;
+            return Some(Ok(record));
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread::{self, JoinHandle};
+
+/// The default number of records `spawn_reader` buffers ahead of the consumer
+pub const DEFAULT_QUEUE_DEPTH: usize = 1024;
+
+/// Run a reader (or any iterator) on a background thread, so that parsing
+/// and decompression overlap with the caller's processing of records
+///
+/// Up to `queue_depth` records are read ahead. When the queue is full the
+/// background thread waits for the consumer to catch up, so memory use is
+/// bounded. Works with FastaReader, FastqReader and any other iterator whose
+/// items can be sent between threads. If the background thread panics, the
+/// panic is resumed on the consumer's thread.
+pub fn spawn_reader<I>(reader: I, queue_depth: usize) -> PrefetchReader<I::Item>
+where
+    I: Iterator + Send + 'static,
+    I::Item: Send + 'static,
+{
+    let (sender, receiver) = sync_channel(queue_depth.max(1));
+    let handle = thread::spawn(move || {
+        for item in reader {
+            if sender.send(item).is_err() {
+                // The consumer has gone away.
+                break;
+            }
+        }
+    });
+    PrefetchReader {
+        receiver,
+        handle: Some(handle),
+    }
+}
+
+/// Iterator over records read ahead on a background thread, created by
+/// `spawn_reader`
+pub struct PrefetchReader<T> {
+    receiver: Receiver<T>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl<T> Iterator for PrefetchReader<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match self.receiver.recv() {
+            Ok(item) => Some(item),
+            Err(_) => {
+                // The sender has finished; report a panic if that's why.
+                if let Some(handle) = self.handle.take() {
+                    if let Err(panic) = handle.join() {
+                        std::panic::resume_unwind(panic);
+                    }
+                }
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+/// How far a reader has got through its input, from `progress()` on the
+/// readers or passed to their `on_progress` callbacks
+///
+/// Byte counts are of the input read so far, which may run ahead of the
+/// records returned by up to the size of the reader's buffers. To show a
+/// progress bar for a file, compare `input_bytes` with its size on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Progress {
+    /// The number of records read, including any skipped as malformed
+    pub records: u64,
+    /// The number of bytes of (uncompressed) input parsed
+    pub bytes: u64,
+    /// The number of bytes read from the source before decompression (the
+    /// same as `bytes`, give or take buffering, for uncompressed input), or
+    /// None for readers made from a `BgzfReader`, whose source is not
+    /// counted
+    pub input_bytes: Option<u64>,
+    /// Whether the end of the input has been reached
+    pub finished: bool,
+}
+
+/// Cumulative counts of what a reader has read, from `stats()` on the
+/// readers, e.g. for logging at the end of a pipeline step
+///
+/// Comparing `records` and `bytes` with what was expected shows up inputs
+/// that were silently cut short, and `skipped` counts the malformed
+/// records passed over when the options' `on_error` is `Skip` or `Warn`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReaderStats {
+    /// The number of records returned
+    pub records: u64,
+    /// The total sequence length of the records returned
+    pub bases: u64,
+    /// The number of malformed records skipped
+    pub skipped: u64,
+    /// The number of records with the same ID as an earlier record,
+    /// counted when the options' `duplicate_ids` is `Warn` or `Error`
+    pub duplicates: u64,
+    /// The number of bytes of (uncompressed) input parsed: the output of
+    /// the decompressor, for compressed input
+    pub bytes: u64,
+    /// The number of bytes read from the source: the input to the
+    /// decompressor, for compressed input. None when not counted (see
+    /// `Progress::input_bytes`).
+    pub input_bytes: Option<u64>,
+}
+
+/// A progress callback and how often to call it
+pub(crate) struct ProgressHook {
+    every: u64,
+    next: u64,
+    finished: bool,
+    callback: Box<dyn FnMut(&Progress) + Send>,
+}
+
+impl ProgressHook {
+    pub(crate) fn new<F>(every: u64, callback: F) -> Self
+    where
+        F: FnMut(&Progress) + Send + 'static,
+    {
+        let every = every.max(1);
+        ProgressHook {
+            every,
+            next: every,
+            finished: false,
+            callback: Box::new(callback),
+        }
+    }
+
+    /// Call the callback if another `every` records have been read since
+    /// the last call, or once when the input is finished
+    pub(crate) fn report(&mut self, progress: impl FnOnce() -> Progress, finished: bool) {
+        if self.finished {
+            return;
+        }
+        let progress = progress();
+        if finished {
+            self.finished = true;
+            (self.callback)(&Progress {
+                finished: true,
+                ..progress
+            });
+        } else if progress.records >= self.next {
+            self.next = progress.records - progress.records % self.every + self.every;
+            (self.callback)(&progress);
+        }
+    }
+}
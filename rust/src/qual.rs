@@ -0,0 +1,275 @@
+use crate::common::{create_reader_with_compression, split_header_str, utf8_str, LineReader};
+use crate::error::{Position, PrseqError, Result};
+use crate::fasta::FastaReader;
+use crate::fastq::FastqRecord;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// The highest quality score that has a Phred+33 character ('~')
+const MAX_SCORE: u8 = 93;
+
+/// A record from a QUAL file: a header and numeric Phred quality scores
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QualRecord {
+    /// The header line, without its '>'
+    pub header: String,
+    pub scores: Vec<u8>,
+}
+
+impl QualRecord {
+    /// The ID: the header up to the first whitespace
+    pub fn id(&self) -> &str {
+        split_header_str(&self.header).0
+    }
+
+    /// The scores as a Phred+33 quality string
+    pub fn quality(&self) -> String {
+        self.scores
+            .iter()
+            .map(|&score| (score + 33) as char)
+            .collect()
+    }
+}
+
+/// Iterator over the records of a QUAL file (the companion of a FASTA file
+/// in old 454 and Sanger datasets), in which each header line is followed
+/// by whitespace-separated quality scores over any number of lines
+pub struct QualReader {
+    lines: LineReader,
+    /// The header that starts the next record, and its line number
+    next_header: Option<(String, u64)>,
+    started: bool,
+    records: u64,
+    /// The line number of the last record's header
+    record_line: u64,
+}
+
+impl QualReader {
+    /// Create a new QualReader from a file path
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Create a new QualReader from any readable source with compression
+    /// detection
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> Result<Self> {
+        let buf_reader = create_reader_with_compression(reader)?;
+        Ok(QualReader {
+            lines: LineReader::new(Box::new(buf_reader)),
+            next_header: None,
+            started: false,
+            records: 0,
+            record_line: 0,
+        })
+    }
+
+    /// The position of the last record's header
+    fn position(&self) -> Position {
+        Position {
+            line: self.record_line,
+            record: self.records,
+        }
+    }
+
+    /// Find the first header, skipping blank lines
+    fn start(&mut self) -> Result<()> {
+        self.started = true;
+        loop {
+            let line_number = self.lines.line() + 1;
+            let Some((line, _)) = self.lines.next_line()? else {
+                break;
+            };
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let position = Position {
+                line: line_number,
+                record: 1,
+            };
+            match line.strip_prefix(b">") {
+                Some(header) => {
+                    let header = utf8_str(header, "QUAL", Some(position))?.to_string();
+                    self.next_header = Some((header, line_number));
+                }
+                None => {
+                    return Err(PrseqError::invalid_fasta(
+                        "QUAL input does not start with a '>' header line",
+                        position,
+                        line,
+                    ))
+                }
+            }
+            break;
+        }
+        Ok(())
+    }
+
+    fn read_next(&mut self) -> Result<Option<QualRecord>> {
+        if !self.started {
+            self.start()?;
+        }
+        let Some((header, line)) = self.next_header.take() else {
+            return Ok(None);
+        };
+        self.records += 1;
+        self.record_line = line;
+        let mut scores = Vec::new();
+        loop {
+            let position = Position {
+                line: self.lines.line() + 1,
+                record: self.records,
+            };
+            let Some((line, _)) = self.lines.next_line()? else {
+                break;
+            };
+            if let Some(next) = line.strip_prefix(b">") {
+                let next = utf8_str(next, "QUAL", Some(position))?.to_string();
+                self.next_header = Some((next, position.line));
+                break;
+            }
+            for value in line
+                .split(u8::is_ascii_whitespace)
+                .filter(|v| !v.is_empty())
+            {
+                let score = std::str::from_utf8(value)
+                    .ok()
+                    .and_then(|value| value.parse::<u8>().ok())
+                    .filter(|&score| score <= MAX_SCORE);
+                match score {
+                    Some(score) => scores.push(score),
+                    None => {
+                        return Err(PrseqError::invalid_fasta(
+                            format!(
+                                "Invalid quality score '{}' (expected 0 to {})",
+                                String::from_utf8_lossy(value),
+                                MAX_SCORE
+                            ),
+                            position,
+                            line,
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(Some(QualRecord { header, scores }))
+    }
+}
+
+impl Iterator for QualReader {
+    type Item = Result<QualRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_next().transpose()
+    }
+}
+
+/// Iterator over a FASTA file and its QUAL file read in lockstep, yielding
+/// FASTQ records with the scores as Phred+33 quality strings
+///
+/// The IDs and lengths of each sequence and its scores are checked, and an
+/// error is returned if they differ or if one file ends before the other.
+///
+/// ```
+/// use prseq::qual::{FastaQualReader, QualReader};
+/// use prseq::FastaReader;
+///
+/// let fasta = FastaReader::from_reader_with_capacity(&b">r1\nACG\n"[..], 64).unwrap();
+/// let qual = QualReader::from_reader(&b">r1\n40 30\n20\n"[..]).unwrap();
+/// let mut reader = FastaQualReader::new(fasta, qual);
+/// assert_eq!(reader.next().unwrap().unwrap().quality, "I?5");
+/// ```
+pub struct FastaQualReader {
+    fasta: FastaReader,
+    qual: QualReader,
+    records: usize,
+    finished: bool,
+}
+
+impl FastaQualReader {
+    /// Create a new FastaQualReader from a FASTA reader and a QUAL reader
+    pub fn new(fasta: FastaReader, qual: QualReader) -> Self {
+        FastaQualReader {
+            fasta,
+            qual,
+            records: 0,
+            finished: false,
+        }
+    }
+
+    /// Create a new FastaQualReader from two file paths
+    pub fn from_files<P: AsRef<Path>, Q: AsRef<Path>>(fasta: P, qual: Q) -> Result<Self> {
+        Ok(Self::new(
+            FastaReader::from_file(fasta)?,
+            QualReader::from_file(qual)?,
+        ))
+    }
+
+    fn read_next(&mut self) -> Result<Option<FastqRecord>> {
+        let number = self.records + 1;
+        let (record, qual) = match (
+            self.fasta.next().transpose()?,
+            self.qual.next().transpose()?,
+        ) {
+            (Some(record), Some(qual)) => (record, qual),
+            (None, None) => return Ok(None),
+            (record, _) => {
+                let (ended, other) = if record.is_some() {
+                    ("QUAL", "FASTA")
+                } else {
+                    ("FASTA", "QUAL")
+                };
+                return Err(PrseqError::PairMismatch {
+                    pair: number,
+                    message: format!(
+                        "FASTA and QUAL files are out of sync: {} ended after {} records but {} has more",
+                        ended, self.records, other
+                    ),
+                });
+            }
+        };
+        if record.id() != qual.id() {
+            return Err(PrseqError::PairMismatch {
+                pair: number,
+                message: format!(
+                    "FASTA and QUAL files are out of sync at record {}: FASTA ID '{}' does not match QUAL ID '{}'",
+                    number,
+                    record.id(),
+                    qual.id()
+                ),
+            });
+        }
+        if record.sequence.len() != qual.scores.len() {
+            return Err(PrseqError::LengthMismatch {
+                sequence_length: record.sequence.len(),
+                quality_length: qual.scores.len(),
+                position: self.qual.position(),
+            });
+        }
+        self.records = number;
+        Ok(Some(FastqRecord {
+            quality: qual.quality(),
+            header: record.header,
+            sequence: record.sequence,
+        }))
+    }
+}
+
+impl Iterator for FastaQualReader {
+    type Item = Result<FastqRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.read_next() {
+            Ok(Some(record)) => Some(Ok(record)),
+            Ok(None) => None,
+            Err(e) => {
+                // Once the two files disagree, later records are meaningless.
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
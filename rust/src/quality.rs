@@ -0,0 +1,123 @@
+use crate::error::Result;
+use crate::fastq::FastqReader;
+use std::fmt;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// The ASCII offset used to encode FASTQ quality scores
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QualityEncoding {
+    /// Phred+33 (Sanger, Illumina 1.8+), the encoding of modern FASTQ
+    #[default]
+    Phred33,
+    /// Phred+64 (Illumina 1.3 to 1.7)
+    Phred64,
+}
+
+/// The highest quality character seen in Phred+33 data in practice ('J' is
+/// Q41 for Illumina 1.8+; a little headroom is left for other platforms)
+const MAX_PHRED33: u8 = b'K';
+
+impl QualityEncoding {
+    /// The ASCII value of quality 0
+    pub fn offset(&self) -> u8 {
+        match self {
+            QualityEncoding::Phred33 => 33,
+            QualityEncoding::Phred64 => 64,
+        }
+    }
+
+    /// Guess the encoding of some quality strings from the range of their
+    /// characters, returning None if there are none or they fit both
+    /// encodings
+    ///
+    /// Any character below '@' means Phred+33. Otherwise, a character above
+    /// 'K' (beyond the qualities Phred+33 data contains) means Phred+64.
+    pub fn detect<I, Q>(qualities: I) -> Option<QualityEncoding>
+    where
+        I: IntoIterator<Item = Q>,
+        Q: AsRef<[u8]>,
+    {
+        let mut max = None;
+        for quality in qualities {
+            for &b in quality.as_ref() {
+                if b < b'@' {
+                    return Some(QualityEncoding::Phred33);
+                }
+                max = max.max(Some(b));
+            }
+        }
+        match max {
+            Some(max) if max > MAX_PHRED33 => Some(QualityEncoding::Phred64),
+            _ => None,
+        }
+    }
+
+    /// Guess the encoding of a FASTQ file (which may be compressed) from the
+    /// quality strings of up to `sample_size` of its first records
+    pub fn detect_file<P: AsRef<Path>>(
+        path: P,
+        sample_size: usize,
+    ) -> Result<Option<QualityEncoding>> {
+        let mut reader = FastqReader::from_file(path)?;
+        let records = reader.next_chunk(sample_size)?;
+        Ok(Self::detect(records.iter().map(|record| &record.quality)))
+    }
+
+    /// Re-encode `quality` in place from this encoding to `to`, returning
+    /// the offset of the first character that is out of range for either
+    /// encoding (in which case `quality` is partly converted)
+    pub(crate) fn convert(
+        &self,
+        to: QualityEncoding,
+        quality: &mut [u8],
+    ) -> std::result::Result<(), usize> {
+        if *self == to {
+            return Ok(());
+        }
+        let (from, to) = (self.offset(), to.offset());
+        let max = b'~' - from.max(to) + from;
+        for (offset, b) in quality.iter_mut().enumerate() {
+            if *b < from || *b > max {
+                return Err(offset);
+            }
+            *b = *b - from + to;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for QualityEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Phred+{}", self.offset())
+    }
+}
+
+/// Decode Phred+33 quality characters to quality scores
+///
+/// Characters below '!' (which are not valid quality) decode to 0.
+pub fn phred_scores(quality: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    quality.iter().map(|b| b.saturating_sub(33))
+}
+
+/// The mean of the Phred+33 quality scores in `quality`, or None if it is
+/// empty
+pub fn mean_quality(quality: &[u8]) -> Option<f64> {
+    if quality.is_empty() {
+        return None;
+    }
+    let total: u64 = phred_scores(quality).map(u64::from).sum();
+    Some(total as f64 / quality.len() as f64)
+}
+
+/// The expected number of errors in a read with Phred+33 quality `quality`:
+/// the sum of the error probabilities, 10^(-Q/10), of its bases
+pub fn expected_errors(quality: &[u8]) -> f64 {
+    static PROBABILITIES: OnceLock<[f64; 256]> = OnceLock::new();
+    let probabilities = PROBABILITIES
+        .get_or_init(|| std::array::from_fn(|score| 10f64.powf(-(score as f64) / 10.0)));
+    phred_scores(quality)
+        .map(|score| probabilities[score as usize])
+        .sum()
+}
@@ -0,0 +1,147 @@
+use crate::any::AnyRecord;
+use crate::common::split_header_str;
+use crate::error::Result;
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+
+/// The parts common to FASTA and FASTQ records, so that code can be
+/// written once for either
+///
+/// ```
+/// use prseq::{FastaRecord, SequenceRecord};
+///
+/// fn describe(record: &dyn SequenceRecord) -> String {
+///     format!("{}: {} bp", record.id(), record.len())
+/// }
+///
+/// let record = FastaRecord { header: "s1 first".into(), sequence: "ACGT".into() };
+/// assert_eq!(describe(&record), "s1: 4 bp");
+/// ```
+pub trait SequenceRecord {
+    /// The header line, without its '>' or '@'
+    fn header(&self) -> &str;
+
+    /// The sequence
+    fn sequence(&self) -> &str;
+
+    /// The quality string, for FASTQ records
+    fn quality(&self) -> Option<&str>;
+
+    /// The ID: the header up to the first whitespace
+    fn id(&self) -> &str {
+        split_header_str(self.header()).0
+    }
+
+    /// The description: the rest of the header after the ID, if there is any
+    fn description(&self) -> Option<&str> {
+        split_header_str(self.header()).1
+    }
+
+    /// The length of the sequence
+    fn len(&self) -> usize {
+        self.sequence().len()
+    }
+
+    /// Whether the sequence is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl SequenceRecord for FastaRecord {
+    fn header(&self) -> &str {
+        &self.header
+    }
+
+    fn sequence(&self) -> &str {
+        &self.sequence
+    }
+
+    fn quality(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl SequenceRecord for FastqRecord {
+    fn header(&self) -> &str {
+        &self.header
+    }
+
+    fn sequence(&self) -> &str {
+        &self.sequence
+    }
+
+    fn quality(&self) -> Option<&str> {
+        Some(&self.quality)
+    }
+}
+
+impl SequenceRecord for AnyRecord {
+    fn header(&self) -> &str {
+        AnyRecord::header(self)
+    }
+
+    fn sequence(&self) -> &str {
+        AnyRecord::sequence(self)
+    }
+
+    fn quality(&self) -> Option<&str> {
+        AnyRecord::quality(self)
+    }
+}
+
+impl<R: SequenceRecord + ?Sized> SequenceRecord for Box<R> {
+    fn header(&self) -> &str {
+        (**self).header()
+    }
+
+    fn sequence(&self) -> &str {
+        (**self).sequence()
+    }
+
+    fn quality(&self) -> Option<&str> {
+        (**self).quality()
+    }
+}
+
+/// A record of either format, boxed
+pub type DynRecord = Box<dyn SequenceRecord + Send>;
+
+/// A reader (or other iterator of records) whose format is only known at
+/// run time, as made by `BoxRecordsExt::boxed`
+pub type DynReader = Box<dyn Iterator<Item = Result<DynRecord>> + Send>;
+
+/// Extension trait for boxing readers so that readers of different
+/// formats have the same type
+///
+/// ```
+/// use prseq::record::{BoxRecordsExt, DynReader};
+/// use prseq::{FastaReader, FastqReader, SequenceRecord};
+///
+/// fn open(fastq: bool) -> prseq::error::Result<DynReader> {
+///     Ok(if fastq {
+///         FastqReader::from_string("@r1\nACG\n+\nIII\n")?.boxed()
+///     } else {
+///         FastaReader::from_string(">s1\nACGT\n")?.boxed()
+///     })
+/// }
+///
+/// let record = open(true).unwrap().next().unwrap().unwrap();
+/// assert_eq!(record.quality(), Some("III"));
+/// ```
+pub trait BoxRecordsExt<R>: Iterator<Item = Result<R>> + Sized + Send + 'static
+where
+    R: SequenceRecord + Send + 'static,
+{
+    /// Box the records, and the iterator
+    fn boxed(self) -> DynReader {
+        Box::new(self.map(|record| record.map(|record| Box::new(record) as DynRecord)))
+    }
+}
+
+impl<R, I> BoxRecordsExt<R> for I
+where
+    R: SequenceRecord + Send + 'static,
+    I: Iterator<Item = Result<R>> + Send + 'static,
+{
+}
@@ -0,0 +1,109 @@
+use crate::error::{Position, PrseqError, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// A region of a named sequence, in 1-based inclusive coordinates (as used
+/// by samtools)
+///
+/// Regions are written as `name`, `name:start-end`, `name:start-` or
+/// `name:start`, where the last two both extend to the end of the sequence.
+/// Positions may contain commas, e.g. `chr2:1,000-2,000`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Region {
+    pub name: String,
+    /// The first position (1-based)
+    pub start: u64,
+    /// The last position (inclusive), or None for the end of the sequence
+    pub end: Option<u64>,
+}
+
+impl Region {
+    /// Create a new region, checking that `start` is at least 1 and not
+    /// after `end`
+    pub fn new(name: &str, start: u64, end: Option<u64>) -> Result<Self> {
+        if start == 0 || end.is_some_and(|end| end < start) {
+            return Err(invalid_region(&format_region(name, start, end)));
+        }
+        Ok(Region {
+            name: name.to_string(),
+            start,
+            end,
+        })
+    }
+
+    /// A region covering the whole of the named sequence
+    pub fn whole(name: &str) -> Self {
+        Region {
+            name: name.to_string(),
+            start: 1,
+            end: None,
+        }
+    }
+
+    /// The region as a 0-based, end-exclusive range (end None meaning the
+    /// end of the sequence)
+    pub fn zero_based(&self) -> (u64, Option<u64>) {
+        (self.start - 1, self.end)
+    }
+}
+
+impl FromStr for Region {
+    type Err = PrseqError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(invalid_region(s));
+        }
+        let Some((name, range)) = s.rsplit_once(':') else {
+            return Ok(Region::whole(s));
+        };
+        if name.is_empty() {
+            return Err(invalid_region(s));
+        }
+        let (start, end) = match range.split_once('-') {
+            Some((start, "")) => (parse_position(start), None),
+            Some((start, end)) => match parse_position(end) {
+                Some(end) => (parse_position(start), Some(end)),
+                None => return Err(invalid_region(s)),
+            },
+            None => (parse_position(range), None),
+        };
+        match start {
+            Some(start) => Region::new(name, start, end).map_err(|_| invalid_region(s)),
+            None => Err(invalid_region(s)),
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_region(&self.name, self.start, self.end))
+    }
+}
+
+fn format_region(name: &str, start: u64, end: Option<u64>) -> String {
+    match (start, end) {
+        (1, None) => name.to_string(),
+        (start, None) => format!("{}:{}-", name, start),
+        (start, Some(end)) => format!("{}:{}-{}", name, start, end),
+    }
+}
+
+fn parse_position(s: &str) -> Option<u64> {
+    let digits: String = s.chars().filter(|&c| c != ',').collect();
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// A region is a single line of text, so errors are at its first line
+fn invalid_region(region: &str) -> PrseqError {
+    PrseqError::invalid_format(
+        "region",
+        "Invalid region",
+        Position { line: 1, record: 1 },
+        region.as_bytes(),
+    )
+}
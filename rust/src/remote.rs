@@ -0,0 +1,97 @@
+use std::io::{Read, Result, Seek, SeekFrom};
+
+/// Open a URL for reading with an HTTP(S) GET request
+pub fn open_url(url: &str) -> Result<Box<dyn Read + Send>> {
+    let response = ureq::get(url).call().map_err(|e| request_failed(url, e))?;
+    Ok(Box::new(response.into_reader()))
+}
+
+/// A seekable reader over a remote file, which uses HTTP range requests to
+/// read from any position without downloading the whole file
+///
+/// Each seek drops the current response; the next read starts a new request
+/// for the data from the new position onwards.
+pub struct HttpRangeReader {
+    url: String,
+    position: u64,
+    length: u64,
+    response: Option<Box<dyn Read + Send + Sync>>,
+}
+
+impl HttpRangeReader {
+    /// Create a reader for a URL, whose server must support range requests
+    pub fn new(url: &str) -> Result<Self> {
+        let response = ureq::head(url).call().map_err(|e| request_failed(url, e))?;
+        let length = response
+            .header("Content-Length")
+            .and_then(|length| length.parse().ok())
+            .ok_or_else(|| {
+                std::io::Error::other(format!("No Content-Length in response for {}", url))
+            })?;
+        Ok(HttpRangeReader {
+            url: url.to_string(),
+            position: 0,
+            length,
+            response: None,
+        })
+    }
+
+    /// The length of the remote file
+    pub fn len(&self) -> u64 {
+        self.length
+    }
+
+    /// Whether the remote file is empty
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+}
+
+impl Read for HttpRangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() || self.position >= self.length {
+            return Ok(0);
+        }
+        if self.response.is_none() {
+            let response = ureq::get(&self.url)
+                .set("Range", &format!("bytes={}-", self.position))
+                .call()
+                .map_err(|e| request_failed(&self.url, e))?;
+            if response.status() != 206 && self.position > 0 {
+                return Err(std::io::Error::other(format!(
+                    "Server for {} does not support range requests",
+                    self.url
+                )));
+            }
+            self.response = Some(response.into_reader());
+        }
+        let n = self.response.as_mut().unwrap().read(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for HttpRangeReader {
+    fn seek(&mut self, position: SeekFrom) -> Result<u64> {
+        let position = match position {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(delta) => self.length.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Seek to a negative position",
+            )
+        })?;
+        if position != self.position {
+            self.position = position;
+            self.response = None;
+        }
+        Ok(position)
+    }
+}
+
+fn request_failed(url: &str, error: ureq::Error) -> std::io::Error {
+    std::io::Error::other(format!("HTTP request for {} failed: {}", url, error))
+}
@@ -0,0 +1,187 @@
+use crate::any::AnyRecord;
+use crate::common::split_header_str;
+use crate::error::Result;
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use crate::filter::Filterable;
+use std::collections::HashMap;
+
+/// A record whose header can be rewritten
+pub trait Renameable: Filterable {
+    /// Replace the header line
+    fn set_header(&mut self, header: String);
+}
+
+impl Renameable for FastaRecord {
+    fn set_header(&mut self, header: String) {
+        self.header = header;
+    }
+}
+
+impl Renameable for FastqRecord {
+    fn set_header(&mut self, header: String) {
+        self.header = header;
+    }
+}
+
+impl Renameable for AnyRecord {
+    fn set_header(&mut self, header: String) {
+        match self {
+            AnyRecord::Fasta(record) => record.header = header,
+            AnyRecord::Fastq(record) => record.header = header,
+        }
+    }
+}
+
+/// A piece of a renaming template
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    /// The record number
+    Number,
+    /// The original ID
+    Id,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Scheme {
+    Template(Vec<Part>),
+    Map(HashMap<String, String>),
+}
+
+/// Rewrites record IDs, from a template or a map of old to new IDs
+///
+/// The rest of the header (the description) is kept after the new ID,
+/// unless `keep_original` is set, in which case the whole original header
+/// follows the new ID so nothing is lost.
+///
+/// ```
+/// use prseq::rename::Renamer;
+/// use prseq::FastaRecord;
+///
+/// let mut renamer = Renamer::template("sample1_{n}");
+/// let mut record = FastaRecord { header: "NODE_1 len=500".into(), sequence: "ACGT".into() };
+/// renamer.rename(&mut record);
+/// assert_eq!(record.header, "sample1_1 len=500");
+///
+/// let mut renamer = Renamer::template("contig{n}").keep_original(true);
+/// let mut record = FastaRecord { header: "NODE_1 len=500".into(), sequence: "ACGT".into() };
+/// renamer.rename(&mut record);
+/// assert_eq!(record.header, "contig1 NODE_1 len=500");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Renamer {
+    scheme: Scheme,
+    /// Whether the whole original header is kept after the new ID
+    pub keep_original: bool,
+    /// The number given to the next record renamed with a template
+    pub next_number: u64,
+}
+
+impl Renamer {
+    /// Rename records from a template, in which `{n}` is replaced by the
+    /// record number (from 1) and `{id}` by the original ID. Anything else,
+    /// including other text in braces, is kept as it is.
+    pub fn template(template: &str) -> Self {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while !rest.is_empty() {
+            let (part, length) = if rest.starts_with("{n}") {
+                (Part::Number, 3)
+            } else if rest.starts_with("{id}") {
+                (Part::Id, 4)
+            } else {
+                let length = rest[1..].find('{').map_or(rest.len(), |offset| offset + 1);
+                (Part::Text(rest[..length].to_string()), length)
+            };
+            parts.push(part);
+            rest = &rest[length..];
+        }
+        Renamer::new(Scheme::Template(parts))
+    }
+
+    /// Rename records whose IDs are keys of `ids` to the corresponding
+    /// values. Other records are left as they are.
+    pub fn map(ids: HashMap<String, String>) -> Self {
+        Renamer::new(Scheme::Map(ids))
+    }
+
+    fn new(scheme: Scheme) -> Self {
+        Renamer {
+            scheme,
+            keep_original: false,
+            next_number: 1,
+        }
+    }
+
+    /// Set whether the whole original header is kept after the new ID
+    pub fn keep_original(mut self, keep: bool) -> Self {
+        self.keep_original = keep;
+        self
+    }
+
+    /// Set the number given to the first record
+    pub fn start_at(mut self, number: u64) -> Self {
+        self.next_number = number;
+        self
+    }
+
+    /// The new ID for a record with ID `id`, or None if it is not renamed.
+    /// A template uses up a record number.
+    pub fn new_id(&mut self, id: &str) -> Option<String> {
+        match &self.scheme {
+            Scheme::Template(parts) => {
+                let mut new_id = String::new();
+                for part in parts {
+                    match part {
+                        Part::Text(text) => new_id.push_str(text),
+                        Part::Number => new_id.push_str(&self.next_number.to_string()),
+                        Part::Id => new_id.push_str(id),
+                    }
+                }
+                self.next_number += 1;
+                Some(new_id)
+            }
+            Scheme::Map(ids) => ids.get(id).cloned(),
+        }
+    }
+
+    /// Rewrite a record's ID
+    pub fn rename<R: Renameable>(&mut self, record: &mut R) {
+        let (id, description) = split_header_str(record.header());
+        let Some(new_id) = self.new_id(id) else {
+            return;
+        };
+        let rest = if self.keep_original {
+            Some(record.header())
+        } else {
+            description
+        };
+        let header = match rest {
+            Some(rest) => format!("{} {}", new_id, rest),
+            None => new_id,
+        };
+        record.set_header(header);
+    }
+}
+
+/// Renaming for readers (or any iterator of record results)
+pub trait RenameExt<R: Renameable>: Iterator<Item = Result<R>> + Sized {
+    /// Rename each record with `renamer`. Errors are passed through (and
+    /// do not use up a record number).
+    fn rename(self, mut renamer: Renamer) -> impl Iterator<Item = Result<R>> {
+        self.map(move |result| {
+            result.map(|mut record| {
+                renamer.rename(&mut record);
+                record
+            })
+        })
+    }
+}
+
+impl<I, R> RenameExt<R> for I
+where
+    I: Iterator<Item = Result<R>>,
+    R: Renameable,
+{
+}
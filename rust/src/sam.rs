@@ -0,0 +1,213 @@
+use crate::common::{create_reader_with_compression, utf8_str, LineReader};
+use crate::error::{Position, PrseqError, Result};
+use crate::fastq::FastqRecord;
+use crate::sequence::reverse_complement;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// The FLAG bit for a read that is part of a pair
+pub const FLAG_PAIRED: u16 = 0x1;
+/// The FLAG bit for a read that is reverse-complemented in the record
+pub const FLAG_REVERSE: u16 = 0x10;
+/// The FLAG bit for the first read of a pair
+pub const FLAG_READ1: u16 = 0x40;
+/// The FLAG bit for the second read of a pair
+pub const FLAG_READ2: u16 = 0x80;
+/// The FLAG bit for a secondary alignment
+pub const FLAG_SECONDARY: u16 = 0x100;
+/// The FLAG bit for a supplementary alignment
+pub const FLAG_SUPPLEMENTARY: u16 = 0x800;
+
+/// The quality character given to bases of reads with no quality ('*'),
+/// as by `samtools fastq`
+const DEFAULT_QUALITY: char = '"';
+
+/// The parts of a SAM (or BAM) record needed to recover the read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SamRecord {
+    /// The read name (QNAME)
+    pub name: String,
+    /// The bitwise FLAG
+    pub flag: u16,
+    /// The sequence (SEQ) as stored, which for reverse-strand alignments is
+    /// the reverse complement of the read. Empty if it was '*'.
+    pub sequence: String,
+    /// The Phred+33 quality (QUAL) as stored. Empty if it was '*'.
+    pub quality: String,
+}
+
+impl SamRecord {
+    /// Whether the read is stored reverse-complemented
+    pub fn is_reverse(&self) -> bool {
+        self.flag & FLAG_REVERSE != 0
+    }
+
+    /// Whether the record is a secondary or supplementary alignment, which
+    /// repeats a read found in its primary record
+    pub fn is_secondary(&self) -> bool {
+        self.flag & (FLAG_SECONDARY | FLAG_SUPPLEMENTARY) != 0
+    }
+
+    /// The read as it was sequenced: reverse-complemented back if it is
+    /// stored reversed. With `mate_suffix`, "/1" or "/2" is added to the
+    /// names of paired reads.
+    pub fn to_fastq(&self, mate_suffix: bool) -> FastqRecord {
+        let mut header = self.name.clone();
+        if mate_suffix && self.flag & FLAG_PAIRED != 0 {
+            if self.flag & FLAG_READ1 != 0 {
+                header.push_str("/1");
+            } else if self.flag & FLAG_READ2 != 0 {
+                header.push_str("/2");
+            }
+        }
+        let mut quality = if self.quality.is_empty() {
+            std::iter::repeat_n(DEFAULT_QUALITY, self.sequence.len()).collect()
+        } else {
+            self.quality.clone()
+        };
+        let sequence = if self.is_reverse() {
+            quality = quality.chars().rev().collect();
+            String::from_utf8_lossy(&reverse_complement(self.sequence.as_bytes())).into_owned()
+        } else {
+            self.sequence.clone()
+        };
+        FastqRecord {
+            header,
+            sequence,
+            quality,
+        }
+    }
+}
+
+/// Iterator over the reads in a SAM file, as FASTQ records
+///
+/// Header lines are skipped, reads stored reverse-complemented are turned
+/// back, and secondary and supplementary alignments are skipped (unless
+/// `include_secondary(true)` is used), so each read appears once, as with
+/// `samtools fastq`. Reads without a quality get '"' (Phred 1) for each
+/// base. Only the fields needed are parsed; alignments are not checked.
+///
+/// ```
+/// use prseq::sam::SamReader;
+///
+/// let input = b"@HD\tVN:1.6\nr1\t4\t*\t0\t0\t*\t*\t0\t0\tACGG\tIIII\n";
+/// let mut reader = SamReader::from_reader(&input[..]).unwrap();
+/// assert_eq!(reader.next().unwrap().unwrap().sequence, "ACGG");
+/// ```
+pub struct SamReader {
+    lines: LineReader,
+    records: u64,
+    include_secondary: bool,
+    mate_suffix: bool,
+}
+
+impl SamReader {
+    /// Create a new SamReader from a file path
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Create a new SamReader from any readable source with compression
+    /// detection
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> Result<Self> {
+        let buf_reader = create_reader_with_compression(reader)?;
+        Ok(SamReader {
+            lines: LineReader::new(Box::new(buf_reader)),
+            records: 0,
+            include_secondary: false,
+            mate_suffix: false,
+        })
+    }
+
+    /// Set whether secondary and supplementary alignments are returned
+    pub fn include_secondary(mut self, include: bool) -> Self {
+        self.include_secondary = include;
+        self
+    }
+
+    /// Set whether "/1" and "/2" are added to the names of paired reads
+    pub fn mate_suffix(mut self, mate_suffix: bool) -> Self {
+        self.mate_suffix = mate_suffix;
+        self
+    }
+
+    /// Read the next record (including secondary alignments) as stored,
+    /// returning None at end of input
+    pub fn next_sam_record(&mut self) -> Result<Option<SamRecord>> {
+        loop {
+            let position = Position {
+                line: self.lines.line() + 1,
+                record: self.records + 1,
+            };
+            let Some((line, _)) = self.lines.next_line()? else {
+                return Ok(None);
+            };
+            if line.is_empty() || line.starts_with(b"@") {
+                continue;
+            }
+            self.records += 1;
+            let text = utf8_str(line, "SAM", Some(position))?;
+            return parse_line(text, position).map(Some);
+        }
+    }
+}
+
+/// Parse the fields of a SAM alignment line that SamRecord holds
+fn parse_line(text: &str, position: Position) -> Result<SamRecord> {
+    let invalid =
+        |message: String| PrseqError::invalid_format("SAM", message, position, text.as_bytes());
+    let fields: Vec<&str> = text.splitn(12, '\t').collect();
+    if fields.len() < 11 {
+        return Err(invalid(format!(
+            "SAM line has {} fields but needs at least 11",
+            fields.len()
+        )));
+    }
+    let flag = fields[1]
+        .parse()
+        .map_err(|_| invalid(format!("Invalid SAM FLAG '{}'", fields[1])))?;
+    let field = |value: &str| match value {
+        "*" => String::new(),
+        value => value.to_string(),
+    };
+    let record = SamRecord {
+        name: fields[0].to_string(),
+        flag,
+        sequence: field(fields[9]),
+        quality: field(fields[10]),
+    };
+    check_lengths(&record, position)?;
+    Ok(record)
+}
+
+/// Check that a record's quality (if it has one) matches its sequence
+pub(crate) fn check_lengths(record: &SamRecord, position: Position) -> Result<()> {
+    if !record.quality.is_empty() && record.quality.len() != record.sequence.len() {
+        return Err(PrseqError::LengthMismatch {
+            sequence_length: record.sequence.len(),
+            quality_length: record.quality.len(),
+            position,
+        });
+    }
+    Ok(())
+}
+
+impl Iterator for SamReader {
+    type Item = Result<FastqRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.next_sam_record() {
+                Ok(Some(record)) => {
+                    if record.is_secondary() && !self.include_secondary {
+                        continue;
+                    }
+                    return Some(Ok(record.to_fastq(self.mate_suffix)));
+                }
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
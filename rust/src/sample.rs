@@ -0,0 +1,82 @@
+use crate::error::Result;
+
+/// A small, fast pseudo-random number generator (SplitMix64). It is not
+/// suitable for cryptography, but gives the same sequence for a seed on
+/// every platform and in every version, so samples are reproducible.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in [0, 1)
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A number in [0, n), for n > 0
+    pub(crate) fn below(&mut self, n: u64) -> u64 {
+        ((u128::from(self.next_u64()) * u128::from(n)) >> 64) as u64
+    }
+}
+
+/// Reproducible random subsampling of readers (or any iterator of record
+/// results)
+///
+/// The items can be single records or read pairs (e.g. from a
+/// `PairedFastqReader`), so mates are kept or dropped together. The same
+/// seed always selects the same records from the same input.
+///
+/// ```
+/// use prseq::sample::SampleExt;
+/// use prseq::FastaReader;
+///
+/// let input = b">a\nA\n>b\nC\n>c\nG\n>d\nT\n";
+/// let reader = FastaReader::from_reader_with_capacity(&input[..], 64).unwrap();
+/// let sample = reader.sample_count(2, 42).unwrap();
+/// assert_eq!(sample.len(), 2);
+/// ```
+pub trait SampleExt<R>: Iterator<Item = Result<R>> + Sized {
+    /// Keep each record with probability `fraction` (between 0 and 1),
+    /// independently of the others. Errors are passed through.
+    fn sample_fraction(self, fraction: f64, seed: u64) -> impl Iterator<Item = Result<R>> {
+        let mut rng = Rng::new(seed);
+        self.filter(move |result| result.is_err() || rng.next_f64() < fraction)
+    }
+
+    /// Choose `count` records uniformly at random (or all of them, if there
+    /// are fewer), returning them in their input order
+    ///
+    /// The input is read in one pass, keeping only `count` records in
+    /// memory (reservoir sampling), so it can be much larger than memory.
+    /// Stops at the first error.
+    fn sample_count(self, count: usize, seed: u64) -> Result<Vec<R>> {
+        let mut rng = Rng::new(seed);
+        let mut reservoir: Vec<(u64, R)> = Vec::with_capacity(count.min(1 << 16));
+        for (seen, record) in (0u64..).zip(self) {
+            let record = record?;
+            if reservoir.len() < count {
+                reservoir.push((seen, record));
+            } else {
+                let slot = rng.below(seen + 1);
+                if (slot as usize) < count {
+                    reservoir[slot as usize] = (seen, record);
+                }
+            }
+        }
+        reservoir.sort_unstable_by_key(|&(index, _)| index);
+        Ok(reservoir.into_iter().map(|(_, record)| record).collect())
+    }
+}
+
+impl<I, R> SampleExt<R> for I where I: Iterator<Item = Result<R>> {}
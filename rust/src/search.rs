@@ -0,0 +1,252 @@
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use crate::filter::Filterable;
+use crate::sequence::{base_bits, reverse_complement};
+use std::ops::Range;
+
+/// What a `Search` looks for
+#[derive(Debug, Clone)]
+enum Pattern {
+    Substring(String),
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+/// The part of a record a `Search` looks in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchTarget {
+    /// The sequence (the default)
+    #[default]
+    Sequence,
+    /// The whole header line
+    Header,
+}
+
+/// A plain substring or regular expression to look for in records'
+/// sequences or headers, as with `grep`
+///
+/// ```
+/// use prseq::filter::FilterExt;
+/// use prseq::search::Search;
+/// use prseq::FastaReader;
+///
+/// let input = b">a\nAAGATCGGAAGA\n>b\nCCCC\n";
+/// let reader = FastaReader::from_reader_with_capacity(&input[..], 64).unwrap();
+/// let hits: Vec<_> = reader
+///     .grep_matches(Search::substring("AGA"))
+///     .map(|result| result.unwrap())
+///     .map(|(record, matches)| (record.header, matches))
+///     .collect();
+/// assert_eq!(hits, [("a".to_string(), vec![1..4, 9..12])]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Search {
+    pattern: Pattern,
+    target: SearchTarget,
+}
+
+impl Search {
+    /// Search for a plain substring (case-sensitive)
+    pub fn substring(pattern: impl Into<String>) -> Self {
+        Search {
+            pattern: Pattern::Substring(pattern.into()),
+            target: SearchTarget::default(),
+        }
+    }
+
+    /// Search for a regular expression. Use `(?i)` in the pattern to ignore
+    /// case.
+    #[cfg(feature = "regex")]
+    pub fn regex(pattern: regex::Regex) -> Self {
+        Search {
+            pattern: Pattern::Regex(pattern),
+            target: SearchTarget::default(),
+        }
+    }
+
+    /// Set the part of each record to search
+    pub fn target(mut self, target: SearchTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Search headers rather than sequences
+    pub fn in_headers(self) -> Self {
+        self.target(SearchTarget::Header)
+    }
+
+    /// Whether the pattern occurs in `text`
+    pub fn is_match_in(&self, text: &str) -> bool {
+        match &self.pattern {
+            Pattern::Substring(pattern) => text.contains(pattern.as_str()),
+            #[cfg(feature = "regex")]
+            Pattern::Regex(pattern) => pattern.is_match(text),
+        }
+    }
+
+    /// The 0-based, end-exclusive ranges of the non-overlapping matches in
+    /// `text`, from left to right
+    pub fn find_in(&self, text: &str) -> Vec<Range<usize>> {
+        match &self.pattern {
+            Pattern::Substring(pattern) if pattern.is_empty() => Vec::new(),
+            Pattern::Substring(pattern) => text
+                .match_indices(pattern.as_str())
+                .map(|(start, found)| start..start + found.len())
+                .collect(),
+            #[cfg(feature = "regex")]
+            Pattern::Regex(pattern) => pattern.find_iter(text).map(|m| m.range()).collect(),
+        }
+    }
+
+    /// Whether the pattern occurs in the record's sequence (or header)
+    pub fn is_match<R: Filterable>(&self, record: &R) -> bool {
+        self.is_match_in(self.text(record))
+    }
+
+    /// The ranges of the matches in the record's sequence (or header)
+    pub fn find<R: Filterable>(&self, record: &R) -> Vec<Range<usize>> {
+        self.find_in(self.text(record))
+    }
+
+    fn text<'a, R: Filterable>(&self, record: &'a R) -> &'a str {
+        match self.target {
+            SearchTarget::Sequence => record.sequence(),
+            SearchTarget::Header => record.header(),
+        }
+    }
+}
+
+/// The strand a motif was found on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strand {
+    Forward,
+    /// The reverse complement of the motif was found on the forward strand
+    Reverse,
+}
+
+/// A place a motif was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MotifMatch {
+    /// The 0-based start of the match on the forward strand
+    pub start: usize,
+    /// The end (exclusive) of the match on the forward strand
+    pub end: usize,
+    pub strand: Strand,
+    /// The number of positions that did not match
+    pub mismatches: usize,
+}
+
+/// A nucleotide motif, such as a primer, whose IUPAC ambiguity codes match
+/// any of the bases they stand for
+///
+/// A sequence base matches a motif position if every base it could be is
+/// allowed there, so an N in the sequence only matches an N in the motif.
+///
+/// ```
+/// use prseq::search::{find_motif, Motif, Strand};
+///
+/// let motif = Motif::new("GGNCC").unwrap();
+/// let matches = find_motif(b"AAGGACCTTGGTTCC", &motif, 0);
+/// assert_eq!(matches.len(), 1);
+/// assert_eq!((matches[0].start, matches[0].end), (2, 7));
+/// assert_eq!(matches[0].strand, Strand::Forward);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Motif {
+    forward: Vec<u8>,
+    /// The reverse complement, or None if it is the same as the motif
+    reverse: Option<Vec<u8>>,
+}
+
+impl Motif {
+    /// Make a motif from IUPAC nucleotide codes (in either case), returning
+    /// None if it is empty or contains anything else
+    pub fn new(pattern: &str) -> Option<Motif> {
+        let forward: Vec<u8> = pattern.bytes().map(base_bits).collect();
+        if forward.is_empty() || forward.contains(&0) {
+            return None;
+        }
+        let reverse: Vec<u8> = reverse_complement(pattern.as_bytes())
+            .into_iter()
+            .map(base_bits)
+            .collect();
+        Some(Motif {
+            reverse: (reverse != forward).then_some(reverse),
+            forward,
+        })
+    }
+
+    /// The length of the motif
+    pub fn len(&self) -> usize {
+        self.forward.len()
+    }
+
+    /// Whether the motif is empty (never true for a motif made by `new`)
+    pub fn is_empty(&self) -> bool {
+        self.forward.is_empty()
+    }
+}
+
+/// Find all the places `motif` occurs in `sequence` with at most
+/// `max_mismatches` mismatches, on both strands, ordered by start
+///
+/// A motif that is its own reverse complement is only reported on the
+/// forward strand.
+pub fn find_motif(sequence: &[u8], motif: &Motif, max_mismatches: usize) -> Vec<MotifMatch> {
+    let length = motif.forward.len();
+    let mut matches = Vec::new();
+    if sequence.len() < length {
+        return matches;
+    }
+    let strands = [
+        (Strand::Forward, Some(&motif.forward)),
+        (Strand::Reverse, motif.reverse.as_ref()),
+    ];
+    for start in 0..=sequence.len() - length {
+        let window = &sequence[start..start + length];
+        for (strand, pattern) in strands {
+            let Some(pattern) = pattern else {
+                continue;
+            };
+            if let Some(mismatches) = count_mismatches(window, pattern, max_mismatches) {
+                matches.push(MotifMatch {
+                    start,
+                    end: start + length,
+                    strand,
+                    mismatches,
+                });
+            }
+        }
+    }
+    matches
+}
+
+/// The number of positions of `window` not allowed by `pattern`, or None
+/// if there are more than `max_mismatches`
+fn count_mismatches(window: &[u8], pattern: &[u8], max_mismatches: usize) -> Option<usize> {
+    let mut mismatches = 0;
+    for (&base, &allowed) in window.iter().zip(pattern) {
+        let bits = base_bits(base);
+        if bits == 0 || bits & !allowed != 0 {
+            mismatches += 1;
+            if mismatches > max_mismatches {
+                return None;
+            }
+        }
+    }
+    Some(mismatches)
+}
+
+impl FastaRecord {
+    /// Find a motif in the sequence (see `find_motif`)
+    pub fn find_motif(&self, motif: &Motif, max_mismatches: usize) -> Vec<MotifMatch> {
+        find_motif(self.sequence.as_bytes(), motif, max_mismatches)
+    }
+}
+
+impl FastqRecord {
+    /// Find a motif in the sequence (see `find_motif`)
+    pub fn find_motif(&self, motif: &Motif, max_mismatches: usize) -> Vec<MotifMatch> {
+        find_motif(self.sequence.as_bytes(), motif, max_mismatches)
+    }
+}
@@ -0,0 +1,126 @@
+use crate::error::Result;
+use crate::fasta::FastaReader;
+use crate::fastq::FastqReader;
+use std::ops::{Bound, RangeBounds};
+
+/// Selecting records by their position in a reader: the first `n`, all but
+/// the first `n`, or a range
+///
+/// Skipped records are parsed but not converted to owned records (or
+/// checked to be UTF-8), and once the end of the selection is reached
+/// nothing more is read, so looking at the first reads of a large
+/// compressed file only decompresses its start.
+///
+/// ```
+/// use prseq::select::SelectRecords;
+/// use prseq::FastaReader;
+///
+/// let input = b">a\nA\n>b\nC\n>c\nG\n>d\nT\n";
+/// let reader = FastaReader::from_reader_with_capacity(&input[..], 64).unwrap();
+/// let ids: Vec<String> = reader
+///     .record_range(1..3)
+///     .map(|record| record.unwrap().header)
+///     .collect();
+/// assert_eq!(ids, ["b", "c"]);
+/// ```
+pub trait SelectRecords: Iterator + Sized {
+    /// Read past the next record without converting it, returning None at
+    /// end of input
+    fn skip_record(&mut self) -> Option<Result<()>>;
+
+    /// The first `n` records
+    fn take_records(self, n: usize) -> RecordRange<Self> {
+        self.record_range(..n)
+    }
+
+    /// All but the first `n` records
+    fn skip_records(self, n: usize) -> RecordRange<Self> {
+        self.record_range(n..)
+    }
+
+    /// The records at the (0-based) positions in `range`
+    fn record_range<B: RangeBounds<usize>>(self, range: B) -> RecordRange<Self> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => Some(end.saturating_add(1)),
+            Bound::Excluded(&end) => Some(end),
+            Bound::Unbounded => None,
+        };
+        RecordRange {
+            reader: self,
+            skip: start,
+            remaining: end.map(|end| end.saturating_sub(start)),
+        }
+    }
+}
+
+impl SelectRecords for FastaReader {
+    fn skip_record(&mut self) -> Option<Result<()>> {
+        self.next_ref().map(|record| record.map(|_| ()))
+    }
+}
+
+impl SelectRecords for FastqReader {
+    fn skip_record(&mut self) -> Option<Result<()>> {
+        self.next_ref().map(|record| record.map(|_| ()))
+    }
+}
+
+/// An iterator over a range of a reader's records, from `SelectRecords`
+///
+/// Errors in skipped records are returned (each counting as a record), as
+/// the reader may not be able to continue past them.
+pub struct RecordRange<R> {
+    reader: R,
+    /// The number of records still to skip
+    skip: usize,
+    /// The number of records still to return, if limited
+    remaining: Option<usize>,
+}
+
+impl<R> RecordRange<R> {
+    /// The underlying reader
+    pub fn reader(&self) -> &R {
+        &self.reader
+    }
+
+    /// Return the underlying reader, to continue reading after the range
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R, T> Iterator for RecordRange<R>
+where
+    R: SelectRecords + Iterator<Item = Result<T>>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == Some(0) {
+            return None;
+        }
+        while self.skip > 0 {
+            self.skip -= 1;
+            if let Err(e) = self.reader.skip_record()? {
+                return Some(Err(e));
+            }
+        }
+        let record = self.reader.next()?;
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= 1;
+        }
+        Some(record)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.remaining {
+            Some(remaining) => (0, Some(remaining)),
+            None => (0, None),
+        }
+    }
+}
@@ -0,0 +1,361 @@
+use crate::error::{PrseqError, Result};
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use std::ops::{Bound, Range, RangeBounds};
+
+/// The complement of each IUPAC nucleotide code, preserving case. Other
+/// bytes (including gaps) are their own complement.
+static COMPLEMENT: [u8; 256] = {
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = i as u8;
+        i += 1;
+    }
+    let pairs: &[(u8, u8)] = &[
+        (b'A', b'T'),
+        (b'C', b'G'),
+        (b'U', b'A'),
+        (b'R', b'Y'),
+        (b'K', b'M'),
+        (b'B', b'V'),
+        (b'D', b'H'),
+    ];
+    let mut i = 0;
+    while i < pairs.len() {
+        let (a, b) = pairs[i];
+        table[a as usize] = b;
+        table[a.to_ascii_lowercase() as usize] = b.to_ascii_lowercase();
+        if a != b'U' {
+            table[b as usize] = a;
+            table[b.to_ascii_lowercase() as usize] = a.to_ascii_lowercase();
+        }
+        i += 1;
+    }
+    table
+};
+
+/// The bases each nucleotide code stands for, as a bit set over T, C, A
+/// and G (the order of the NCBI codon tables). Zero for anything that is
+/// not a code.
+static BASE_BITS: [u8; 256] = {
+    let mut table = [0u8; 256];
+    let codes: &[(u8, u8)] = &[
+        (b'T', 0b0001),
+        (b'U', 0b0001),
+        (b'C', 0b0010),
+        (b'A', 0b0100),
+        (b'G', 0b1000),
+        (b'Y', 0b0011),
+        (b'R', 0b1100),
+        (b'W', 0b0101),
+        (b'S', 0b1010),
+        (b'K', 0b1001),
+        (b'M', 0b0110),
+        (b'B', 0b1011),
+        (b'D', 0b1101),
+        (b'H', 0b0111),
+        (b'V', 0b1110),
+        (b'N', 0b1111),
+    ];
+    let mut i = 0;
+    while i < codes.len() {
+        let (code, bases) = codes[i];
+        table[code as usize] = bases;
+        table[code.to_ascii_lowercase() as usize] = bases;
+        i += 1;
+    }
+    table
+};
+
+/// The bases a nucleotide code stands for, as a bit set over T, C, A and G
+/// (bits 0 to 3), or 0 if it is not a nucleotide code
+pub(crate) fn base_bits(base: u8) -> u8 {
+    BASE_BITS[base as usize]
+}
+
+/// The complement of a nucleotide (IUPAC codes included), preserving case
+pub fn complement(base: u8) -> u8 {
+    COMPLEMENT[base as usize]
+}
+
+/// The reverse complement of a nucleotide sequence
+///
+/// IUPAC ambiguity codes are complemented (e.g. R, A or G, becomes Y) and
+/// case is preserved. U complements to A; anything else, such as a gap, is
+/// left as it is.
+pub fn reverse_complement(sequence: &[u8]) -> Vec<u8> {
+    sequence.iter().rev().map(|&b| complement(b)).collect()
+}
+
+/// The number of each base in one or more sequences
+///
+/// Upper and lower case are counted together, and U is counted as T.
+/// Ambiguity codes other than N, gaps and anything else are counted as
+/// `other`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BaseCounts {
+    pub a: u64,
+    pub c: u64,
+    pub g: u64,
+    pub t: u64,
+    pub n: u64,
+    pub other: u64,
+}
+
+impl BaseCounts {
+    /// Count the bases of `sequence`
+    pub fn new(sequence: &[u8]) -> Self {
+        let mut counts = BaseCounts::default();
+        counts.add(sequence);
+        counts
+    }
+
+    /// Add the bases of `sequence` to the counts
+    pub fn add(&mut self, sequence: &[u8]) {
+        let mut bytes = [0u64; 256];
+        for &b in sequence {
+            bytes[b as usize] += 1;
+        }
+        let count = |base: u8| bytes[base as usize] + bytes[base.to_ascii_lowercase() as usize];
+        let a = count(b'A');
+        let c = count(b'C');
+        let g = count(b'G');
+        let t = count(b'T') + count(b'U');
+        let n = count(b'N');
+        self.a += a;
+        self.c += c;
+        self.g += g;
+        self.t += t;
+        self.n += n;
+        self.other += sequence.len() as u64 - (a + c + g + t + n);
+    }
+
+    /// Add a single base to the counts
+    pub fn add_base(&mut self, base: u8) {
+        match base.to_ascii_uppercase() {
+            b'A' => self.a += 1,
+            b'C' => self.c += 1,
+            b'G' => self.g += 1,
+            b'T' | b'U' => self.t += 1,
+            b'N' => self.n += 1,
+            _ => self.other += 1,
+        }
+    }
+
+    /// Add another set of counts to these
+    pub fn merge(&mut self, other: &BaseCounts) {
+        self.a += other.a;
+        self.c += other.c;
+        self.g += other.g;
+        self.t += other.t;
+        self.n += other.n;
+        self.other += other.other;
+    }
+
+    /// The total number of bases counted
+    pub fn total(&self) -> u64 {
+        self.a + self.c + self.g + self.t + self.n + self.other
+    }
+
+    /// The fraction of A, C, G and T bases that are G or C, or None if there
+    /// are none (N and other codes are left out)
+    pub fn gc_content(&self) -> Option<f64> {
+        let acgt = self.a + self.c + self.g + self.t;
+        (acgt > 0).then(|| (self.g + self.c) as f64 / acgt as f64)
+    }
+}
+
+/// The fraction of the A, C, G and T (or U) bases of `sequence` that are G
+/// or C, or None if it has none
+pub fn gc_content(sequence: &[u8]) -> Option<f64> {
+    BaseCounts::new(sequence).gc_content()
+}
+
+/// The number of Ns (upper or lower case) in `sequence`
+pub fn n_count(sequence: &[u8]) -> usize {
+    sequence.iter().filter(|&&b| b | 0x20 == b'n').count()
+}
+
+/// Replace soft-masked (lower case) bases with N
+pub fn mask_to_n(sequence: &mut [u8]) {
+    for base in sequence.iter_mut().filter(|b| b.is_ascii_lowercase()) {
+        *base = b'N';
+    }
+}
+
+/// Remove soft-masking by upper-casing every base
+pub fn unmask(sequence: &mut [u8]) {
+    sequence.make_ascii_uppercase();
+}
+
+/// The fraction of `sequence` that is soft-masked (lower case), or None if
+/// it is empty
+pub fn masked_fraction(sequence: &[u8]) -> Option<f64> {
+    let masked = sequence.iter().filter(|b| b.is_ascii_lowercase()).count();
+    (!sequence.is_empty()).then(|| masked as f64 / sequence.len() as f64)
+}
+
+/// Resolve a range over a sequence of `length` to 0-based, end-exclusive
+/// offsets. Negative bounds count back from the end of the sequence, as in
+/// Python, so `..-1` drops the last base.
+fn resolve_range<R: RangeBounds<isize>>(range: &R, length: usize) -> Result<Range<usize>> {
+    let out_of_range = || PrseqError::OutOfRange {
+        range: format_range(range),
+        length,
+    };
+    let offset = |position: isize| {
+        let offset = if position < 0 {
+            length.checked_sub(position.unsigned_abs())
+        } else {
+            Some(position as usize)
+        };
+        offset.filter(|&offset| offset <= length)
+    };
+    let start = match range.start_bound() {
+        Bound::Included(&start) => offset(start),
+        Bound::Excluded(&start) => offset(start).map(|start| start + 1),
+        Bound::Unbounded => Some(0),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => offset(end).map(|end| end + 1),
+        Bound::Excluded(&end) => offset(end),
+        Bound::Unbounded => Some(length),
+    };
+    match (start, end) {
+        (Some(start), Some(end)) if start <= end && end <= length => Ok(start..end),
+        _ => Err(out_of_range()),
+    }
+}
+
+/// Write a range the way it would appear in Rust source, e.g. `2..-3`
+fn format_range<R: RangeBounds<isize>>(range: &R) -> String {
+    let start = match range.start_bound() {
+        Bound::Included(start) => start.to_string(),
+        Bound::Excluded(start) => (start + 1).to_string(),
+        Bound::Unbounded => String::new(),
+    };
+    match range.end_bound() {
+        Bound::Included(end) => format!("{}..={}", start, end),
+        Bound::Excluded(end) => format!("{}..{}", start, end),
+        Bound::Unbounded => format!("{}..", start),
+    }
+}
+
+/// Slice a string by byte offsets, failing rather than panicking if they
+/// are out of range (a quality shorter than its sequence) or not on
+/// character boundaries (a sequence that is not ASCII)
+fn slice_str(text: &str, range: Range<usize>) -> Result<&str> {
+    text.get(range.clone())
+        .ok_or_else(|| PrseqError::OutOfRange {
+            range: format!("{}..{}", range.start, range.end),
+            length: text.len(),
+        })
+}
+
+impl FastaRecord {
+    /// The number of each base in the sequence
+    pub fn base_counts(&self) -> BaseCounts {
+        BaseCounts::new(self.sequence.as_bytes())
+    }
+
+    /// The GC content of the sequence (see `gc_content`)
+    pub fn gc_content(&self) -> Option<f64> {
+        gc_content(self.sequence.as_bytes())
+    }
+
+    /// The number of Ns in the sequence
+    pub fn n_count(&self) -> usize {
+        n_count(self.sequence.as_bytes())
+    }
+
+    /// Replace the soft-masked (lower case) bases with N
+    ///
+    /// ```
+    /// use prseq::FastaRecord;
+    ///
+    /// let mut record = FastaRecord { header: "chr1".into(), sequence: "ACgtNa".into() };
+    /// assert_eq!(record.masked_fraction(), Some(0.5));
+    /// record.mask_to_n();
+    /// assert_eq!(record.sequence, "ACNNNN");
+    /// ```
+    pub fn mask_to_n(&mut self) {
+        let mut sequence = std::mem::take(&mut self.sequence).into_bytes();
+        mask_to_n(&mut sequence);
+        // Only ASCII letters are replaced, so the bytes are still UTF-8.
+        self.sequence = String::from_utf8(sequence).expect("masked sequences are UTF-8");
+    }
+
+    /// Remove soft-masking by upper-casing the sequence
+    pub fn unmask(&mut self) {
+        self.sequence.make_ascii_uppercase();
+    }
+
+    /// The fraction of the sequence that is soft-masked (see
+    /// `masked_fraction`)
+    pub fn masked_fraction(&self) -> Option<f64> {
+        masked_fraction(self.sequence.as_bytes())
+    }
+
+    /// A new record with the same header and the part of the sequence in
+    /// `range` (0-based; negative bounds count back from the end), or an
+    /// OutOfRange error if it does not fit in the sequence
+    ///
+    /// ```
+    /// use prseq::FastaRecord;
+    ///
+    /// let record = FastaRecord { header: "seq1".into(), sequence: "ACGTACGT".into() };
+    /// assert_eq!(record.slice(2..-2).unwrap().sequence, "GTAC");
+    /// assert_eq!(record.slice(-3..).unwrap().sequence, "CGT");
+    /// assert!(record.slice(4..9).is_err());
+    /// ```
+    pub fn slice<R: RangeBounds<isize>>(&self, range: R) -> Result<FastaRecord> {
+        let range = resolve_range(&range, self.sequence.len())?;
+        Ok(FastaRecord {
+            header: self.header.clone(),
+            sequence: slice_str(&self.sequence, range)?.to_string(),
+        })
+    }
+
+    /// The record cut to the sequence from `start` up to (not including)
+    /// `end`, as `slice(start..end)`
+    pub fn subsequence(&self, start: isize, end: isize) -> Result<FastaRecord> {
+        self.slice(start..end)
+    }
+}
+
+impl FastqRecord {
+    /// The number of each base in the sequence
+    pub fn base_counts(&self) -> BaseCounts {
+        BaseCounts::new(self.sequence.as_bytes())
+    }
+
+    /// The GC content of the sequence (see `gc_content`)
+    pub fn gc_content(&self) -> Option<f64> {
+        gc_content(self.sequence.as_bytes())
+    }
+
+    /// The number of Ns in the sequence
+    pub fn n_count(&self) -> usize {
+        n_count(self.sequence.as_bytes())
+    }
+
+    /// A new record with the same header and the part of the sequence and
+    /// quality in `range` (0-based; negative bounds count back from the
+    /// end), or an OutOfRange error if it does not fit in the sequence
+    pub fn slice<R: RangeBounds<isize>>(&self, range: R) -> Result<FastqRecord> {
+        let range = resolve_range(&range, self.sequence.len())?;
+        Ok(FastqRecord {
+            header: self.header.clone(),
+            sequence: slice_str(&self.sequence, range.clone())?.to_string(),
+            quality: slice_str(&self.quality, range)?.to_string(),
+        })
+    }
+
+    /// The record cut to the bases from `start` up to (not including)
+    /// `end`, as `slice(start..end)`
+    pub fn subsequence(&self, start: isize, end: isize) -> Result<FastqRecord> {
+        self.slice(start..end)
+    }
+}
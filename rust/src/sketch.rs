@@ -0,0 +1,274 @@
+use crate::error::Result;
+use crate::kmer::{for_each_kmer_hash, kmer_hash};
+use crate::record::SequenceRecord;
+use std::collections::BTreeSet;
+
+/// A MinHash sketch of the k-mers of one or more sequences: the smallest
+/// `size` hashes of their distinct k-mers (a bottom-k sketch)
+///
+/// Sketches are small and fixed in size however much sequence is added,
+/// and two sketches with the same k and size estimate the Jaccard
+/// similarity of the k-mer sets they were made from, so records or whole
+/// files can be compared quickly, e.g. to check reads for contamination.
+/// As in `kmer::Counter`, only k-mers made of A, C, G and T (or U) are
+/// used, in either case. The hashes are not those of Mash or sourmash, so
+/// sketches can only be compared with others made by prseq.
+///
+/// ```
+/// use prseq::sketch::MinHash;
+///
+/// let mut first = MinHash::new(4, 100).canonical(true);
+/// first.add(b"ACGTTGCAAGGCTTAACCGGTTAA");
+/// let mut second = MinHash::new(4, 100).canonical(true);
+/// second.add(b"ACGTTGCAAGGCTTAACCGGTTCC");
+/// let similarity = first.jaccard(&second);
+/// assert!(similarity > 0.7 && similarity < 1.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "MinHashData")
+)]
+pub struct MinHash {
+    k: usize,
+    size: usize,
+    canonical: bool,
+    hashes: BTreeSet<u64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    buffer: Vec<u8>,
+}
+
+/// A deserialized sketch, checked as `MinHash::new` checks its arguments
+/// before it becomes a MinHash
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct MinHashData {
+    k: usize,
+    size: usize,
+    canonical: bool,
+    hashes: BTreeSet<u64>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<MinHashData> for MinHash {
+    type Error = String;
+
+    fn try_from(data: MinHashData) -> std::result::Result<Self, String> {
+        if data.k == 0 {
+            return Err("k-mers must be at least 1 base long".to_string());
+        }
+        if data.size == 0 {
+            return Err("MinHash sketches must keep at least one hash".to_string());
+        }
+        if data.hashes.len() > data.size {
+            return Err(format!(
+                "MinHash sketch has {} hashes, more than its size of {}",
+                data.hashes.len(),
+                data.size
+            ));
+        }
+        Ok(MinHash {
+            k: data.k,
+            size: data.size,
+            canonical: data.canonical,
+            hashes: data.hashes,
+            buffer: Vec::new(),
+        })
+    }
+}
+
+impl MinHash {
+    /// An empty sketch keeping the `size` smallest hashes of k-mers of
+    /// length `k` (both at least 1)
+    pub fn new(k: usize, size: usize) -> Self {
+        assert!(k > 0, "k-mers must be at least 1 base long");
+        assert!(size > 0, "MinHash sketches must keep at least one hash");
+        MinHash {
+            k,
+            size,
+            canonical: false,
+            hashes: BTreeSet::new(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Set whether k-mers are hashed as their canonical form, so that a
+    /// sequence and its reverse complement have the same sketch. Set this
+    /// before adding sequences.
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// An empty sketch with the same k, size and strandedness
+    pub fn empty_like(&self) -> Self {
+        MinHash::new(self.k, self.size).canonical(self.canonical)
+    }
+
+    /// The length of the k-mers sketched
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// The greatest number of hashes kept
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The hashes kept, in increasing order
+    pub fn hashes(&self) -> impl Iterator<Item = u64> + '_ {
+        self.hashes.iter().copied()
+    }
+
+    /// The number of hashes kept (less than the size if fewer distinct
+    /// k-mers have been seen)
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Whether no k-mers have been seen
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    fn insert(hashes: &mut BTreeSet<u64>, size: usize, hash: u64) {
+        if hashes.len() >= size && hashes.last().is_some_and(|&last| hash >= last) {
+            return;
+        }
+        if hashes.insert(hash) && hashes.len() > size {
+            hashes.pop_last();
+        }
+    }
+
+    /// Add the k-mers of a sequence
+    pub fn add(&mut self, sequence: &[u8]) {
+        let (hashes, size) = (&mut self.hashes, self.size);
+        for_each_kmer_hash(sequence, self.k, self.canonical, &mut self.buffer, |hash| {
+            Self::insert(hashes, size, hash)
+        });
+    }
+
+    /// Add the k-mers of a record's sequence
+    pub fn add_record<R: SequenceRecord + ?Sized>(&mut self, record: &R) {
+        self.add(record.sequence().as_bytes());
+    }
+
+    /// Sketch a reader (or any iterator of record results) as a whole, in
+    /// one pass, stopping at the first error
+    pub fn collect<I, R>(mut self, records: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = Result<R>>,
+        R: SequenceRecord,
+    {
+        for record in records {
+            self.add_record(&record?);
+        }
+        Ok(self)
+    }
+
+    /// Whether a k-mer's hash is in the sketch (so the k-mer was probably
+    /// seen; a k-mer that was seen may not be, if its hash is too large)
+    pub fn contains(&self, kmer: &[u8]) -> bool {
+        kmer.len() == self.k
+            && kmer_hash(kmer, self.canonical).is_some_and(|hash| self.hashes.contains(&hash))
+    }
+
+    fn check_compatible(&self, other: &MinHash) {
+        assert!(
+            self.k == other.k && self.size == other.size && self.canonical == other.canonical,
+            "MinHash sketches must have the same k, size and strandedness to be combined"
+        );
+    }
+
+    /// Add the hashes of another sketch, giving the sketch of both sets of
+    /// sequences
+    pub fn merge(&mut self, other: &MinHash) {
+        self.check_compatible(other);
+        for &hash in &other.hashes {
+            Self::insert(&mut self.hashes, self.size, hash);
+        }
+    }
+
+    /// The estimated Jaccard similarity of the k-mer sets of the two
+    /// sketches: the fraction of the smallest hashes of their union that
+    /// are in both. Zero if both are empty.
+    ///
+    /// Panics if the sketches differ in k, size or strandedness.
+    pub fn jaccard(&self, other: &MinHash) -> f64 {
+        self.check_compatible(other);
+        let union: Vec<u64> = self
+            .hashes
+            .union(&other.hashes)
+            .take(self.size)
+            .copied()
+            .collect();
+        if union.is_empty() {
+            return 0.0;
+        }
+        let shared = union
+            .iter()
+            .filter(|hash| self.hashes.contains(hash) && other.hashes.contains(hash))
+            .count();
+        shared as f64 / union.len() as f64
+    }
+
+    /// The Mash distance of the sketches, estimating the fraction of bases
+    /// that differ between the sequences from their Jaccard similarity.
+    /// Infinite if they share no k-mers.
+    pub fn distance(&self, other: &MinHash) -> f64 {
+        let jaccard = self.jaccard(other);
+        if jaccard == 0.0 {
+            return f64::INFINITY;
+        }
+        -(2.0 * jaccard / (1.0 + jaccard)).ln() / self.k as f64
+    }
+}
+
+impl<R: SequenceRecord> Extend<R> for MinHash {
+    fn extend<I: IntoIterator<Item = R>>(&mut self, records: I) {
+        for record in records {
+            self.add_record(&record);
+        }
+    }
+}
+
+/// The estimated Jaccard similarity of two sketches (see `MinHash::jaccard`)
+pub fn jaccard(first: &MinHash, second: &MinHash) -> f64 {
+    first.jaccard(second)
+}
+
+/// Sketching for readers (or any iterator of record results)
+pub trait SketchExt<R: SequenceRecord>: Iterator<Item = Result<R>> + Sized {
+    /// Sketch each record separately, giving the record and its sketch.
+    /// Each sketch has the k, size and strandedness of `template`.
+    ///
+    /// ```
+    /// use prseq::sketch::{MinHash, SketchExt};
+    /// use prseq::FastaReader;
+    ///
+    /// let reader = FastaReader::from_string(">a\nACGTTGCA\n>b\nTGCAACGT\n").unwrap();
+    /// let sketches: Vec<MinHash> = reader
+    ///     .sketch_each(MinHash::new(3, 10))
+    ///     .map(|result| result.unwrap().1)
+    ///     .collect();
+    /// // 4 of the 8 3-mers are shared
+    /// assert_eq!(sketches[0].jaccard(&sketches[1]), 0.5);
+    /// ```
+    fn sketch_each(self, template: MinHash) -> impl Iterator<Item = Result<(R, MinHash)>> {
+        self.map(move |result| {
+            result.map(|record| {
+                let mut sketch = template.empty_like();
+                sketch.add_record(&record);
+                (record, sketch)
+            })
+        })
+    }
+}
+
+impl<I, R> SketchExt<R> for I
+where
+    I: Iterator<Item = Result<R>>,
+    R: SequenceRecord,
+{
+}
@@ -0,0 +1,620 @@
+use crate::any::AnyRecord;
+use crate::error::{Position, PrseqError, Result};
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use crate::filter::Filterable;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+mod private {
+    use std::io::{self, Read, Write};
+
+    /// Writing records to, and reading them back from, spill files
+    pub trait Spill: Sized {
+        fn write_spill<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+        /// Read a record, returning None at end of file
+        fn read_spill<R: Read>(reader: &mut R) -> io::Result<Option<Self>>;
+        /// Roughly how much memory the record uses
+        fn memory_size(&self) -> usize;
+    }
+}
+
+use private::Spill;
+
+/// Records that can be sorted, with spill files if need be
+pub trait Sortable: Filterable + Spill {}
+
+impl<R: Filterable + Spill> Sortable for R {}
+
+/// Bookkeeping for each record held in memory, on top of its strings
+const RECORD_OVERHEAD: usize = 64;
+
+/// The most runs merged at once, which bounds the number of temporary
+/// files open at a time
+const MERGE_FAN_IN: usize = 64;
+
+fn write_fields<W: Write>(writer: &mut W, fields: &[&str]) -> io::Result<()> {
+    writer.write_all(&[fields.len() as u8])?;
+    for field in fields {
+        writer.write_all(&(field.len() as u64).to_le_bytes())?;
+        writer.write_all(field.as_bytes())?;
+    }
+    Ok(())
+}
+
+fn read_fields<R: Read>(reader: &mut R) -> io::Result<Option<Vec<String>>> {
+    let mut count = [0u8];
+    if reader.read(&mut count)? == 0 {
+        return Ok(None);
+    }
+    let mut fields = Vec::with_capacity(count[0] as usize);
+    for _ in 0..count[0] {
+        let mut length = [0u8; 8];
+        reader.read_exact(&mut length)?;
+        let mut field = vec![0; u64::from_le_bytes(length) as usize];
+        reader.read_exact(&mut field)?;
+        fields.push(
+            String::from_utf8(field).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        );
+    }
+    Ok(Some(fields))
+}
+
+fn field_count_error(count: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Unexpected number of fields ({}) in sort spill file", count),
+    )
+}
+
+impl Spill for FastaRecord {
+    fn write_spill<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_fields(writer, &[&self.header, &self.sequence])
+    }
+
+    fn read_spill<R: Read>(reader: &mut R) -> io::Result<Option<Self>> {
+        match AnyRecord::read_spill(reader)? {
+            Some(AnyRecord::Fasta(record)) => Ok(Some(record)),
+            Some(AnyRecord::Fastq(_)) => Err(field_count_error(3)),
+            None => Ok(None),
+        }
+    }
+
+    fn memory_size(&self) -> usize {
+        self.header.len() + self.sequence.len() + RECORD_OVERHEAD
+    }
+}
+
+impl Spill for FastqRecord {
+    fn write_spill<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_fields(writer, &[&self.header, &self.sequence, &self.quality])
+    }
+
+    fn read_spill<R: Read>(reader: &mut R) -> io::Result<Option<Self>> {
+        match AnyRecord::read_spill(reader)? {
+            Some(AnyRecord::Fastq(record)) => Ok(Some(record)),
+            Some(AnyRecord::Fasta(_)) => Err(field_count_error(2)),
+            None => Ok(None),
+        }
+    }
+
+    fn memory_size(&self) -> usize {
+        self.header.len() + self.sequence.len() + self.quality.len() + RECORD_OVERHEAD
+    }
+}
+
+impl Spill for AnyRecord {
+    fn write_spill<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self {
+            AnyRecord::Fasta(record) => record.write_spill(writer),
+            AnyRecord::Fastq(record) => record.write_spill(writer),
+        }
+    }
+
+    fn read_spill<R: Read>(reader: &mut R) -> io::Result<Option<Self>> {
+        let Some(fields) = read_fields(reader)? else {
+            return Ok(None);
+        };
+        let count = fields.len();
+        let mut fields = fields.into_iter();
+        let mut next = || fields.next().unwrap_or_default();
+        match count {
+            2 => Ok(Some(AnyRecord::Fasta(FastaRecord {
+                header: next(),
+                sequence: next(),
+            }))),
+            3 => Ok(Some(AnyRecord::Fastq(FastqRecord {
+                header: next(),
+                sequence: next(),
+                quality: next(),
+            }))),
+            _ => Err(field_count_error(count)),
+        }
+    }
+
+    fn memory_size(&self) -> usize {
+        match self {
+            AnyRecord::Fasta(record) => record.memory_size(),
+            AnyRecord::Fastq(record) => record.memory_size(),
+        }
+    }
+}
+
+/// Options for sorting records
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortOptions {
+    /// Roughly how many bytes of records to hold in memory before sorting
+    /// them and writing them to a temporary file (default 1 GiB)
+    pub max_memory: usize,
+    /// Where temporary files go (default: the system temporary directory)
+    pub temp_dir: Option<PathBuf>,
+    /// Sort in descending order (longest first, or IDs from Z to A)
+    pub reverse: bool,
+}
+
+impl Default for SortOptions {
+    fn default() -> Self {
+        SortOptions {
+            max_memory: 1 << 30,
+            temp_dir: None,
+            reverse: false,
+        }
+    }
+}
+
+impl SortOptions {
+    /// Create options with the defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how many bytes of records to hold in memory
+    pub fn max_memory(mut self, bytes: usize) -> Self {
+        self.max_memory = bytes;
+        self
+    }
+
+    /// Set where temporary files go
+    pub fn temp_dir<P: AsRef<Path>>(mut self, directory: P) -> Self {
+        self.temp_dir = Some(directory.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set whether to sort in descending order
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+}
+
+/// What records are sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortKey {
+    /// The ID, in byte order
+    Id,
+    /// The sequence length
+    Length,
+}
+
+impl SortKey {
+    fn compare<R: Filterable>(self, a: &R, b: &R) -> Ordering {
+        match self {
+            SortKey::Id => a.id().cmp(b.id()),
+            SortKey::Length => a.len().cmp(&b.len()),
+        }
+    }
+}
+
+/// Sort records by sequence length, shortest first (unless
+/// `options.reverse` is set). Records of equal length keep their input
+/// order.
+///
+/// ```
+/// use prseq::sort::{sort_by_length, SortOptions};
+/// use prseq::FastaReader;
+///
+/// let input = b">a\nAAA\n>b\nA\n>c\nAA\n";
+/// let reader = FastaReader::from_reader_with_capacity(&input[..], 64).unwrap();
+/// let ids: Vec<String> = sort_by_length(reader, &SortOptions::new().reverse(true))
+///     .unwrap()
+///     .map(|record| record.unwrap().header)
+///     .collect();
+/// assert_eq!(ids, ["a", "c", "b"]);
+/// ```
+pub fn sort_by_length<I, R>(records: I, options: &SortOptions) -> Result<SortedRecords<R>>
+where
+    I: IntoIterator<Item = Result<R>>,
+    R: Sortable,
+{
+    sort_records(records, options, |a: &R, b: &R| {
+        SortKey::Length.compare(a, b)
+    })
+}
+
+/// Sort records by ID, in byte order (unless `options.reverse` is set).
+/// Records with the same ID keep their input order.
+pub fn sort_by_id<I, R>(records: I, options: &SortOptions) -> Result<SortedRecords<R>>
+where
+    I: IntoIterator<Item = Result<R>>,
+    R: Sortable,
+{
+    sort_records(records, options, |a: &R, b: &R| SortKey::Id.compare(a, b))
+}
+
+type Compare<R> = Arc<dyn Fn(&R, &R) -> Ordering + Send + Sync>;
+
+/// Read all the records, sorting them in memory and spilling sorted runs to
+/// disk whenever `options.max_memory` is exceeded. Stops at the first error.
+fn sort_records<I, R, F>(records: I, options: &SortOptions, compare: F) -> Result<SortedRecords<R>>
+where
+    I: IntoIterator<Item = Result<R>>,
+    R: Sortable,
+    F: Fn(&R, &R) -> Ordering + Send + Sync + 'static,
+{
+    let compare: Compare<R> = if options.reverse {
+        Arc::new(move |a, b| compare(b, a))
+    } else {
+        Arc::new(compare)
+    };
+    let directory = options.temp_dir.clone().unwrap_or_else(std::env::temp_dir);
+    let mut buffer = Vec::new();
+    let mut buffered = 0;
+    let mut runs = Runs::new(directory, compare.clone());
+    for record in records {
+        let record = record?;
+        buffered += record.memory_size();
+        buffer.push(record);
+        if buffered > options.max_memory {
+            // A stable sort keeps equal records in input order.
+            buffer.sort_by(|a, b| compare(a, b));
+            runs.push(buffer.drain(..))?;
+            buffered = 0;
+        }
+    }
+    buffer.sort_by(|a, b| compare(a, b));
+    if runs.spilled == 0 {
+        return Ok(SortedRecords {
+            state: State::Memory(buffer.into_iter()),
+            spilled: 0,
+        });
+    }
+    if !buffer.is_empty() {
+        runs.push(buffer.drain(..))?;
+    }
+    drop(buffer);
+    let spilled = runs.spilled;
+    Ok(SortedRecords {
+        state: State::Merge(runs.finish()?),
+        spilled,
+    })
+}
+
+/// The sorted runs written so far, in input order, merged as they
+/// accumulate so that no more than `MERGE_FAN_IN` (plus one per level of
+/// merging) are open at once
+struct Runs<R> {
+    directory: PathBuf,
+    compare: Compare<R>,
+    /// Each run, with the number of times its records have been merged
+    runs: Vec<(SortRun<R>, u32)>,
+    /// The number of runs written from memory
+    spilled: usize,
+}
+
+impl<R: Sortable> Runs<R> {
+    fn new(directory: PathBuf, compare: Compare<R>) -> Self {
+        Runs {
+            directory,
+            compare,
+            runs: Vec::new(),
+            spilled: 0,
+        }
+    }
+
+    /// Write sorted records as a new run, merging the last `MERGE_FAN_IN`
+    /// runs into one whenever they have all been merged as often
+    fn push(&mut self, records: impl Iterator<Item = R>) -> io::Result<()> {
+        let run = SortRun::write(&self.directory, records.map(Ok))?;
+        self.runs.push((run, 0));
+        self.spilled += 1;
+        while self.runs.len() >= MERGE_FAN_IN {
+            let tail = self.runs.len() - MERGE_FAN_IN;
+            let level = self.runs[tail].1;
+            if self.runs[tail..].iter().any(|(_, other)| *other != level) {
+                break;
+            }
+            let merged = self.merge(tail)?;
+            self.runs.push((merged, level + 1));
+        }
+        Ok(())
+    }
+
+    /// Merge the runs from `first` on into one new run. Merging
+    /// consecutive runs keeps equal records in input order.
+    fn merge(&mut self, first: usize) -> io::Result<SortRun<R>> {
+        let runs = self.runs.drain(first..).map(|(run, _)| run).collect();
+        let merge = Merge::new(runs, self.compare.clone())?;
+        SortRun::write(&self.directory, merge)
+    }
+
+    /// Merge the runs down to at most `MERGE_FAN_IN`, to be merged as they
+    /// are read
+    fn finish(mut self) -> io::Result<Merge<R>> {
+        while self.runs.len() > MERGE_FAN_IN {
+            let first = self.runs.len() - MERGE_FAN_IN;
+            let merged = self.merge(first)?;
+            self.runs.push((merged, 0));
+        }
+        let runs = self.runs.into_iter().map(|(run, _)| run).collect();
+        Merge::new(runs, self.compare)
+    }
+}
+
+/// A sorted run of records in an anonymous temporary file, which the
+/// operating system removes once it is closed
+struct SortRun<R> {
+    reader: BufReader<File>,
+    records: std::marker::PhantomData<R>,
+}
+
+impl<R: Sortable> SortRun<R> {
+    fn write(
+        directory: &Path,
+        records: impl Iterator<Item = io::Result<R>>,
+    ) -> io::Result<SortRun<R>> {
+        let mut file = tempfile::tempfile_in(directory)?;
+        let mut writer = BufWriter::new(&mut file);
+        for record in records {
+            record?.write_spill(&mut writer)?;
+        }
+        writer.flush()?;
+        drop(writer);
+        file.seek(SeekFrom::Start(0))?;
+        Ok(SortRun {
+            reader: BufReader::new(file),
+            records: std::marker::PhantomData,
+        })
+    }
+
+    fn next(&mut self) -> io::Result<Option<R>> {
+        R::read_spill(&mut self.reader)
+    }
+}
+
+/// The next record of a run, ordered for the merge heap
+struct Head<R> {
+    record: R,
+    run: usize,
+    compare: Compare<R>,
+}
+
+impl<R> Ord for Head<R> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap, so the order is reversed. Ties go to the
+        // earlier run, which holds earlier input, keeping the sort stable.
+        (self.compare)(&other.record, &self.record).then(other.run.cmp(&self.run))
+    }
+}
+
+impl<R> PartialOrd for Head<R> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<R> PartialEq for Head<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<R> Eq for Head<R> {}
+
+/// A k-way merge of sorted runs
+struct Merge<R> {
+    runs: Vec<SortRun<R>>,
+    heap: BinaryHeap<Head<R>>,
+    compare: Compare<R>,
+}
+
+impl<R: Sortable> Merge<R> {
+    fn new(runs: Vec<SortRun<R>>, compare: Compare<R>) -> io::Result<Self> {
+        let mut merge = Merge {
+            runs,
+            heap: BinaryHeap::new(),
+            compare,
+        };
+        for run in 0..merge.runs.len() {
+            merge.refill(run)?;
+        }
+        Ok(merge)
+    }
+
+    /// Put the next record of `run`, if any, on the heap
+    fn refill(&mut self, run: usize) -> io::Result<()> {
+        if let Some(record) = self.runs[run].next()? {
+            self.heap.push(Head {
+                record,
+                run,
+                compare: self.compare.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<R: Sortable> Iterator for Merge<R> {
+    type Item = io::Result<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let head = self.heap.pop()?;
+        if let Err(e) = self.refill(head.run) {
+            self.heap.clear();
+            return Some(Err(e));
+        }
+        Some(Ok(head.record))
+    }
+}
+
+enum State<R> {
+    Memory(std::vec::IntoIter<R>),
+    Merge(Merge<R>),
+}
+
+/// An iterator over sorted records, from `sort_by_length` or `sort_by_id`
+///
+/// If the records fitted in memory they are simply returned. Otherwise
+/// they are merged from temporary files, which are removed when the
+/// iterator is dropped; an error reading one ends the iteration.
+pub struct SortedRecords<R> {
+    state: State<R>,
+    spilled: usize,
+}
+
+impl<R> SortedRecords<R> {
+    /// The number of sorted runs written to temporary files (0 if the
+    /// records fitted in memory)
+    pub fn spilled_runs(&self) -> usize {
+        self.spilled
+    }
+}
+
+impl<R: Sortable> Iterator for SortedRecords<R> {
+    type Item = Result<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.state {
+            State::Memory(records) => records.next().map(Ok),
+            State::Merge(merge) => Some(merge.next()?.map_err(Into::into)),
+        }
+    }
+}
+
+/// Merge streams of records that are each already sorted by `key` (such as
+/// the outputs of `sort_by_id` or `sort_by_length` on parts of the input)
+/// into one sorted stream
+///
+/// Records that compare equal come from earlier inputs first, so merging
+/// stable sorts of consecutive chunks gives a stable sort of the whole.
+/// An error is returned if an input turns out not to be sorted, or fails
+/// to read, and ends the merge.
+///
+/// ```
+/// use prseq::sort::{merge_sorted, SortKey};
+/// use prseq::FastaReader;
+///
+/// let first = FastaReader::from_string(">a\nA\n>c\nC\n").unwrap();
+/// let second = FastaReader::from_string(">b\nG\n>d\nT\n").unwrap();
+/// let ids: Vec<String> = merge_sorted([first, second], SortKey::Id)
+///     .map(|record| record.unwrap().header)
+///     .collect();
+/// assert_eq!(ids, ["a", "b", "c", "d"]);
+/// ```
+pub fn merge_sorted<I, R>(inputs: impl IntoIterator<Item = I>, key: SortKey) -> MergeSorted<I, R>
+where
+    I: Iterator<Item = Result<R>>,
+    R: Filterable + 'static,
+{
+    let inputs: Vec<I> = inputs.into_iter().collect();
+    MergeSorted {
+        counts: vec![0; inputs.len()],
+        inputs,
+        heap: BinaryHeap::new(),
+        compare: Arc::new(move |a: &R, b: &R| key.compare(a, b)),
+        key,
+        started: false,
+    }
+}
+
+/// An iterator over the records of several sorted inputs, merged, from
+/// `merge_sorted`
+pub struct MergeSorted<I, R> {
+    inputs: Vec<I>,
+    /// The number of records read from each input
+    counts: Vec<u64>,
+    heap: BinaryHeap<Head<R>>,
+    compare: Compare<R>,
+    key: SortKey,
+    started: bool,
+}
+
+impl<I, R> MergeSorted<I, R>
+where
+    I: Iterator<Item = Result<R>>,
+    R: Filterable + 'static,
+{
+    /// Set whether the inputs are in descending order (as written by a
+    /// sort with `SortOptions::reverse`). Call this before reading.
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        let key = self.key;
+        self.compare = if reverse {
+            Arc::new(move |a: &R, b: &R| key.compare(b, a))
+        } else {
+            Arc::new(move |a: &R, b: &R| key.compare(a, b))
+        };
+        self
+    }
+
+    /// Put the next record of `input`, if any, on the heap, checking that
+    /// it does not sort before `previous`, the record it follows
+    fn refill(&mut self, input: usize, previous: Option<&R>) -> Result<()> {
+        let Some(record) = self.inputs[input].next().transpose()? else {
+            return Ok(());
+        };
+        self.counts[input] += 1;
+        if let Some(previous) = previous {
+            if (self.compare)(&record, previous) == Ordering::Less {
+                return Err(PrseqError::invalid_format(
+                    "sorted",
+                    format!(
+                        "Input {} is not sorted: record '{}' follows '{}'",
+                        input + 1,
+                        record.id(),
+                        previous.id()
+                    ),
+                    Position {
+                        line: 0,
+                        record: self.counts[input],
+                    },
+                    &[],
+                ));
+            }
+        }
+        self.heap.push(Head {
+            record,
+            run: input,
+            compare: self.compare.clone(),
+        });
+        Ok(())
+    }
+}
+
+impl<I, R> Iterator for MergeSorted<I, R>
+where
+    I: Iterator<Item = Result<R>>,
+    R: Filterable + 'static,
+{
+    type Item = Result<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            for input in 0..self.inputs.len() {
+                if let Err(e) = self.refill(input, None) {
+                    self.heap.clear();
+                    return Some(Err(e));
+                }
+            }
+        }
+        let head = self.heap.pop()?;
+        if let Err(e) = self.refill(head.run, Some(&head.record)) {
+            self.heap.clear();
+            return Some(Err(e));
+        }
+        Some(Ok(head.record))
+    }
+}
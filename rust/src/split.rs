@@ -0,0 +1,144 @@
+use crate::error::Result;
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use crate::writer::{FastaWriter, FastqWriter};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// How `split` divides records between files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitBy {
+    /// Into this many files, dealing records out in turn (round-robin)
+    Parts(usize),
+    /// Into files of this many records (the last may have fewer)
+    Records(u64),
+    /// Into files of at most this many bases. A record longer than that
+    /// gets a file of its own.
+    Bases(u64),
+}
+
+/// Records that `split` can write
+pub trait SplitRecord {
+    /// The extension of the files written (without the dot)
+    const EXTENSION: &'static str;
+
+    /// Write the record to `writer`
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+
+    /// The number of bases, for `SplitBy::Bases`
+    fn bases(&self) -> usize;
+}
+
+impl SplitRecord for FastaRecord {
+    const EXTENSION: &'static str = "fasta";
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        FastaWriter::new(writer).write_record(self)
+    }
+
+    fn bases(&self) -> usize {
+        self.sequence.len()
+    }
+}
+
+impl SplitRecord for FastqRecord {
+    const EXTENSION: &'static str = "fastq";
+
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        FastqWriter::new(writer).write_record(self)
+    }
+
+    fn bases(&self) -> usize {
+        self.sequence.len()
+    }
+}
+
+/// The path of part `part` (from 1)
+fn part_path<R: SplitRecord>(directory: &Path, prefix: &str, part: usize) -> PathBuf {
+    directory.join(format!("{}.part_{:03}.{}", prefix, part, R::EXTENSION))
+}
+
+/// Split records into several files in `directory` (created if need be),
+/// returning their paths in order
+///
+/// The files are named `{prefix}.part_001.fasta` (or `.fastq`), numbering
+/// from 1. With `SplitBy::Parts(n)` all `n` files are written, even if
+/// some are empty; otherwise there are only as many as the records fill.
+/// Stops at the first error.
+///
+/// ```no_run
+/// use prseq::split::{split, SplitBy};
+/// use prseq::FastqReader;
+///
+/// let reader = FastqReader::from_file("reads.fastq.gz")?;
+/// let paths = split(reader, "shards", "reads", SplitBy::Records(1_000_000))?;
+/// # Ok::<(), prseq::PrseqError>(())
+/// ```
+pub fn split<I, R, P>(records: I, directory: P, prefix: &str, by: SplitBy) -> Result<Vec<PathBuf>>
+where
+    I: IntoIterator<Item = Result<R>>,
+    R: SplitRecord,
+    P: AsRef<Path>,
+{
+    let size = match by {
+        SplitBy::Parts(n) => n as u64,
+        SplitBy::Records(n) | SplitBy::Bases(n) => n,
+    };
+    if size == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Cannot split with {:?}", by),
+        )
+        .into());
+    }
+    let directory = directory.as_ref();
+    fs::create_dir_all(directory)?;
+    let mut paths = Vec::new();
+
+    if let SplitBy::Parts(n) = by {
+        let mut writers = Vec::with_capacity(n);
+        for part in 1..=n {
+            let path = part_path::<R>(directory, prefix, part);
+            writers.push(BufWriter::new(File::create(&path)?));
+            paths.push(path);
+        }
+        for (index, record) in records.into_iter().enumerate() {
+            record?.write_to(&mut writers[index % n])?;
+        }
+        for writer in &mut writers {
+            writer.flush()?;
+        }
+    } else {
+        // Only the current (last) file is open, with the records or bases
+        // written to it so far.
+        let mut current: Option<(BufWriter<File>, u64)> = None;
+        for record in records {
+            let record = record?;
+            let amount = match by {
+                SplitBy::Bases(_) => record.bases() as u64,
+                _ => 1,
+            };
+            let full = match &current {
+                Some((_, filled)) => *filled > 0 && filled + amount > size,
+                None => true,
+            };
+            if full {
+                if let Some((mut writer, _)) = current.take() {
+                    writer.flush()?;
+                }
+                let path = part_path::<R>(directory, prefix, paths.len() + 1);
+                current = Some((BufWriter::new(File::create(&path)?), 0));
+                paths.push(path);
+            }
+            if let Some((writer, filled)) = &mut current {
+                record.write_to(writer)?;
+                *filled += amount;
+            }
+        }
+        if let Some((mut writer, _)) = current {
+            writer.flush()?;
+        }
+    }
+    Ok(paths)
+}
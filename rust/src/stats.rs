@@ -0,0 +1,416 @@
+use crate::error::Result;
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use crate::quality::phred_scores;
+use crate::record::SequenceRecord;
+use crate::sequence::BaseCounts;
+use std::collections::BTreeMap;
+
+/// Summary statistics of a set of records, accumulated one record at a
+/// time
+///
+/// Only the number of records of each length is kept, not the sequences,
+/// so memory use depends on the number of distinct lengths rather than on
+/// the input's size.
+///
+/// ```
+/// use prseq::stats::collect_stats;
+/// use prseq::FastaReader;
+///
+/// let input = b">a\nACGTACGTAC\n>b\nGGGCC\n>c\nAT\n";
+/// let reader = FastaReader::from_reader_with_capacity(&input[..], 64).unwrap();
+/// let stats = collect_stats(reader).unwrap();
+/// assert_eq!(stats.records(), 3);
+/// assert_eq!(stats.total_bases(), 17);
+/// assert_eq!(stats.n50(), Some(10));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeqStats {
+    records: u64,
+    bases: u64,
+    /// The number of records of each length
+    lengths: BTreeMap<usize, u64>,
+    counts: BaseCounts,
+    quality_bases: u64,
+    quality_total: u64,
+    q20_bases: u64,
+    q30_bases: u64,
+}
+
+impl SeqStats {
+    /// An empty set of statistics
+    pub fn new() -> Self {
+        SeqStats::default()
+    }
+
+    /// Add a record's sequence and (Phred+33) quality, if it has one
+    pub fn add(&mut self, sequence: &[u8], quality: Option<&[u8]>) {
+        self.records += 1;
+        self.bases += sequence.len() as u64;
+        *self.lengths.entry(sequence.len()).or_insert(0) += 1;
+        self.counts.add(sequence);
+        if let Some(quality) = quality {
+            self.quality_bases += quality.len() as u64;
+            for score in phred_scores(quality) {
+                self.quality_total += u64::from(score);
+                self.q20_bases += u64::from(score >= 20);
+                self.q30_bases += u64::from(score >= 30);
+            }
+        }
+    }
+
+    /// Add the statistics of another set of records (e.g. one collected
+    /// from another file or on another thread)
+    pub fn merge(&mut self, other: &SeqStats) {
+        self.records += other.records;
+        self.bases += other.bases;
+        for (&length, &count) in &other.lengths {
+            *self.lengths.entry(length).or_insert(0) += count;
+        }
+        self.counts.merge(&other.counts);
+        self.quality_bases += other.quality_bases;
+        self.quality_total += other.quality_total;
+        self.q20_bases += other.q20_bases;
+        self.q30_bases += other.q30_bases;
+    }
+
+    /// The number of records
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    /// The total length of the records' sequences
+    pub fn total_bases(&self) -> u64 {
+        self.bases
+    }
+
+    /// The length of the shortest sequence, or None if there are no records
+    pub fn min_length(&self) -> Option<usize> {
+        self.lengths.keys().next().copied()
+    }
+
+    /// The length of the longest sequence, or None if there are no records
+    pub fn max_length(&self) -> Option<usize> {
+        self.lengths.keys().next_back().copied()
+    }
+
+    /// The mean sequence length, or None if there are no records
+    pub fn mean_length(&self) -> Option<f64> {
+        (self.records > 0).then(|| self.bases as f64 / self.records as f64)
+    }
+
+    /// The length L such that sequences of length L or more hold at least
+    /// `fraction` (between 0 and 1) of all the bases: `nx(0.5)` is the N50
+    pub fn nx(&self, fraction: f64) -> Option<usize> {
+        let target = self.bases as f64 * fraction;
+        let mut total = 0;
+        for (&length, &count) in self.lengths.iter().rev() {
+            total += length as u64 * count;
+            if total as f64 >= target {
+                return Some(length);
+            }
+        }
+        None
+    }
+
+    /// The N50 length
+    pub fn n50(&self) -> Option<usize> {
+        self.nx(0.5)
+    }
+
+    /// The N90 length
+    pub fn n90(&self) -> Option<usize> {
+        self.nx(0.9)
+    }
+
+    /// The number of each base across all the sequences
+    pub fn base_counts(&self) -> BaseCounts {
+        self.counts
+    }
+
+    /// The GC content across all the sequences (see `BaseCounts::gc_content`)
+    pub fn gc_content(&self) -> Option<f64> {
+        self.counts.gc_content()
+    }
+
+    /// The mean quality score over all bases with a quality, or None if
+    /// there are none (e.g. for FASTA)
+    pub fn mean_quality(&self) -> Option<f64> {
+        self.quality_fraction(self.quality_total)
+    }
+
+    /// The fraction of bases with a quality score of 20 or more
+    pub fn q20_fraction(&self) -> Option<f64> {
+        self.quality_fraction(self.q20_bases)
+    }
+
+    /// The fraction of bases with a quality score of 30 or more
+    pub fn q30_fraction(&self) -> Option<f64> {
+        self.quality_fraction(self.q30_bases)
+    }
+
+    /// Add a record of either format
+    pub fn add_record<R: SequenceRecord + ?Sized>(&mut self, record: &R) {
+        self.add(
+            record.sequence().as_bytes(),
+            record.quality().map(str::as_bytes),
+        );
+    }
+
+    fn quality_fraction(&self, count: u64) -> Option<f64> {
+        (self.quality_bases > 0).then(|| count as f64 / self.quality_bases as f64)
+    }
+}
+
+impl<'a> Extend<&'a FastaRecord> for SeqStats {
+    fn extend<I: IntoIterator<Item = &'a FastaRecord>>(&mut self, records: I) {
+        for record in records {
+            self.add(record.sequence.as_bytes(), None);
+        }
+    }
+}
+
+impl<'a> Extend<&'a FastqRecord> for SeqStats {
+    fn extend<I: IntoIterator<Item = &'a FastqRecord>>(&mut self, records: I) {
+        for record in records {
+            self.add(record.sequence.as_bytes(), Some(record.quality.as_bytes()));
+        }
+    }
+}
+
+impl<R: SequenceRecord> Extend<R> for SeqStats {
+    fn extend<I: IntoIterator<Item = R>>(&mut self, records: I) {
+        for record in records {
+            self.add_record(&record);
+        }
+    }
+}
+
+/// Collect statistics from a reader (or any iterator of record results) in
+/// one pass, stopping at the first error
+pub fn collect_stats<I, R>(records: I) -> Result<SeqStats>
+where
+    I: IntoIterator<Item = Result<R>>,
+    SeqStats: Extend<R>,
+{
+    let mut stats = SeqStats::new();
+    for record in records {
+        stats.extend([record?]);
+    }
+    Ok(stats)
+}
+
+/// The highest Phred+33 quality score ('~')
+const MAX_QUALITY: usize = 93;
+
+/// The qualities and bases seen at one position (cycle) of the reads
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionProfile {
+    /// The number of bases with each quality score, indexed by score
+    #[cfg_attr(feature = "serde", serde(with = "quality_counts"))]
+    pub qualities: [u64; MAX_QUALITY + 1],
+    /// The number of each base
+    pub bases: BaseCounts,
+}
+
+/// (De)serializes the quality counts of a `PositionProfile` as a sequence,
+/// as serde only implements its traits for arrays of up to 32 elements
+#[cfg(feature = "serde")]
+mod quality_counts {
+    use super::MAX_QUALITY;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        counts: &[u64; MAX_QUALITY + 1],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(counts)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[u64; MAX_QUALITY + 1], D::Error> {
+        let counts = Vec::<u64>::deserialize(deserializer)?;
+        let length = counts.len();
+        counts
+            .try_into()
+            .map_err(|_| D::Error::invalid_length(length, &"94 quality counts"))
+    }
+}
+
+impl Default for PositionProfile {
+    fn default() -> Self {
+        PositionProfile {
+            qualities: [0; MAX_QUALITY + 1],
+            bases: BaseCounts::default(),
+        }
+    }
+}
+
+impl PositionProfile {
+    /// The number of reads long enough to reach this position
+    pub fn count(&self) -> u64 {
+        self.qualities.iter().sum()
+    }
+
+    /// The mean quality score, or None if no reads reach this position
+    pub fn mean_quality(&self) -> Option<f64> {
+        let count = self.count();
+        let total: u64 = self
+            .qualities
+            .iter()
+            .enumerate()
+            .map(|(score, &n)| score as u64 * n)
+            .sum();
+        (count > 0).then(|| total as f64 / count as f64)
+    }
+
+    /// The lowest quality score that at least `fraction` (between 0 and 1)
+    /// of the bases are at or below: `quality_quantile(0.5)` is the median
+    pub fn quality_quantile(&self, fraction: f64) -> Option<u8> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let target = (fraction * count as f64).ceil().max(1.0) as u64;
+        let mut total = 0;
+        for (score, &n) in self.qualities.iter().enumerate() {
+            total += n;
+            if total >= target {
+                return Some(score as u8);
+            }
+        }
+        Some(MAX_QUALITY as u8)
+    }
+}
+
+/// Per-position quality distributions and base composition of a set of
+/// FASTQ reads, as shown by FastQC
+///
+/// Position 0 is the first base of every read. Later positions only count
+/// the reads that are long enough to reach them.
+///
+/// ```
+/// use prseq::stats::QualityProfile;
+///
+/// let mut profile = QualityProfile::new();
+/// profile.add(b"ACG", b"II5");
+/// profile.add(b"AC", b"I+");
+/// assert_eq!(profile.len(), 3);
+/// assert_eq!(profile.mean_qualities(), vec![40.0, 25.0, 20.0]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QualityProfile {
+    records: u64,
+    positions: Vec<PositionProfile>,
+}
+
+impl QualityProfile {
+    /// An empty profile
+    pub fn new() -> Self {
+        QualityProfile::default()
+    }
+
+    /// Add a read's sequence and Phred+33 quality. Quality beyond the end
+    /// of the sequence is ignored.
+    pub fn add(&mut self, sequence: &[u8], quality: &[u8]) {
+        self.records += 1;
+        if self.positions.len() < sequence.len() {
+            self.positions
+                .resize_with(sequence.len(), PositionProfile::default);
+        }
+        for (position, (&base, score)) in self
+            .positions
+            .iter_mut()
+            .zip(sequence.iter().zip(phred_scores(quality)))
+        {
+            position.qualities[usize::from(score).min(MAX_QUALITY)] += 1;
+            position.bases.add_base(base);
+        }
+    }
+
+    /// Add the reads of another profile to this one
+    pub fn merge(&mut self, other: &QualityProfile) {
+        self.records += other.records;
+        if self.positions.len() < other.positions.len() {
+            self.positions
+                .resize_with(other.positions.len(), PositionProfile::default);
+        }
+        for (position, other) in self.positions.iter_mut().zip(&other.positions) {
+            for (count, other) in position.qualities.iter_mut().zip(&other.qualities) {
+                *count += other;
+            }
+            position.bases.merge(&other.bases);
+        }
+    }
+
+    /// The number of reads added
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    /// The number of positions: the length of the longest read
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Whether no bases have been added
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// The profile of each position, in order
+    pub fn positions(&self) -> &[PositionProfile] {
+        &self.positions
+    }
+
+    /// The mean quality at each position
+    pub fn mean_qualities(&self) -> Vec<f64> {
+        self.positions
+            .iter()
+            .map(|position| position.mean_quality().unwrap_or(0.0))
+            .collect()
+    }
+
+    /// The GC content at each position (0 where there are no A, C, G or T
+    /// bases)
+    pub fn gc_contents(&self) -> Vec<f64> {
+        self.positions
+            .iter()
+            .map(|position| position.bases.gc_content().unwrap_or(0.0))
+            .collect()
+    }
+}
+
+impl<'a> Extend<&'a FastqRecord> for QualityProfile {
+    fn extend<I: IntoIterator<Item = &'a FastqRecord>>(&mut self, records: I) {
+        for record in records {
+            self.add(record.sequence.as_bytes(), record.quality.as_bytes());
+        }
+    }
+}
+
+impl Extend<FastqRecord> for QualityProfile {
+    fn extend<I: IntoIterator<Item = FastqRecord>>(&mut self, records: I) {
+        for record in records {
+            self.extend([&record]);
+        }
+    }
+}
+
+/// Collect a quality profile from a FASTQ reader (or any iterator of
+/// record results) in one pass, stopping at the first error
+pub fn collect_quality_profile<I>(records: I) -> Result<QualityProfile>
+where
+    I: IntoIterator<Item = Result<FastqRecord>>,
+{
+    let mut profile = QualityProfile::new();
+    for record in records {
+        profile.extend([record?]);
+    }
+    Ok(profile)
+}
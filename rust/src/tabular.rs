@@ -0,0 +1,401 @@
+use crate::any::AnyRecord;
+use crate::common::{create_reader_with_compression, split_header_str, utf8_str, LineReader};
+use crate::error::{Position, PrseqError, Result};
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use crate::sequence::gc_content;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// A column of tabular output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Column {
+    /// The ID: the header up to the first whitespace
+    Id,
+    /// The rest of the header, or empty
+    Description,
+    Sequence,
+    /// The quality string, or empty for FASTA records
+    Quality,
+    /// The sequence length
+    Length,
+    /// The GC content as a fraction of the A, C, G and T bases, or empty if
+    /// there are none
+    Gc,
+}
+
+impl Column {
+    /// The name of the column, as used in header rows
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::Description => "description",
+            Column::Sequence => "sequence",
+            Column::Quality => "quality",
+            Column::Length => "length",
+            Column::Gc => "gc",
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Column {
+    type Err = io::Error;
+
+    /// Parse a column name, ignoring case
+    fn from_str(name: &str) -> io::Result<Self> {
+        [
+            Column::Id,
+            Column::Description,
+            Column::Sequence,
+            Column::Quality,
+            Column::Length,
+            Column::Gc,
+        ]
+        .into_iter()
+        .find(|column| column.name().eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown column '{}'", name),
+            )
+        })
+    }
+}
+
+/// The columns written by default
+const DEFAULT_COLUMNS: [Column; 4] = [
+    Column::Id,
+    Column::Description,
+    Column::Sequence,
+    Column::Quality,
+];
+
+/// Writes records as delimited text (tab-separated by default), one row per
+/// record, with a header row of column names
+///
+/// Fields holding the delimiter, a quote or a line break are quoted, with
+/// quotes doubled, as in CSV.
+///
+/// ```
+/// use prseq::tabular::{Column, TabularWriter};
+/// use prseq::FastaRecord;
+///
+/// let mut writer = TabularWriter::new(Vec::new())
+///     .columns(vec![Column::Id, Column::Length, Column::Gc])
+///     .delimiter(b',');
+/// let record = FastaRecord { header: "s1 first".into(), sequence: "ACGG".into() };
+/// writer.write_fasta(&record).unwrap();
+/// let output = String::from_utf8(writer.into_inner()).unwrap();
+/// assert_eq!(output, "id,length,gc\ns1,4,0.7500\n");
+/// ```
+pub struct TabularWriter<W: Write> {
+    writer: W,
+    columns: Vec<Column>,
+    delimiter: u8,
+    header: bool,
+    started: bool,
+}
+
+impl TabularWriter<BufWriter<File>> {
+    /// Create a new TabularWriter that writes (uncompressed) to a file path
+    pub fn to_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> TabularWriter<W> {
+    /// Create a new TabularWriter that writes the id, description, sequence
+    /// and quality columns, separated by tabs
+    pub fn new(writer: W) -> Self {
+        TabularWriter {
+            writer,
+            columns: DEFAULT_COLUMNS.to_vec(),
+            delimiter: b'\t',
+            header: true,
+            started: false,
+        }
+    }
+
+    /// Set the columns to write, in order
+    pub fn columns(mut self, columns: Vec<Column>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Set the delimiter (such as b',' for CSV)
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Set whether a header row of column names is written first
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    fn write_row<'a>(&mut self, fields: impl Iterator<Item = &'a str>) -> io::Result<()> {
+        for (i, field) in fields.enumerate() {
+            if i > 0 {
+                self.writer.write_all(&[self.delimiter])?;
+            }
+            let delimiter = self.delimiter;
+            if field
+                .bytes()
+                .any(|b| b == delimiter || b == b'"' || b == b'\n' || b == b'\r')
+            {
+                write!(self.writer, "\"{}\"", field.replace('"', "\"\""))?;
+            } else {
+                self.writer.write_all(field.as_bytes())?;
+            }
+        }
+        self.writer.write_all(b"\n")
+    }
+
+    fn write_fields(&mut self, header: &str, sequence: &str, quality: &str) -> io::Result<()> {
+        if !self.started {
+            self.started = true;
+            if self.header {
+                let names: Vec<&str> = self.columns.iter().map(Column::name).collect();
+                self.write_row(names.into_iter())?;
+            }
+        }
+        let (id, description) = split_header_str(header);
+        let fields: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| match column {
+                Column::Id => id.to_string(),
+                Column::Description => description.unwrap_or("").to_string(),
+                Column::Sequence => sequence.to_string(),
+                Column::Quality => quality.to_string(),
+                Column::Length => sequence.len().to_string(),
+                Column::Gc => gc_content(sequence.as_bytes())
+                    .map(|gc| format!("{:.4}", gc))
+                    .unwrap_or_default(),
+            })
+            .collect();
+        self.write_row(fields.iter().map(String::as_str))
+    }
+
+    /// Write a FASTA record, with an empty quality
+    pub fn write_fasta(&mut self, record: &FastaRecord) -> io::Result<()> {
+        self.write_fields(&record.header, &record.sequence, "")
+    }
+
+    /// Write a FASTQ record
+    pub fn write_fastq(&mut self, record: &FastqRecord) -> io::Result<()> {
+        self.write_fields(&record.header, &record.sequence, &record.quality)
+    }
+
+    /// Write a record of either kind
+    pub fn write_record(&mut self, record: &AnyRecord) -> io::Result<()> {
+        match record {
+            AnyRecord::Fasta(record) => self.write_fasta(record),
+            AnyRecord::Fastq(record) => self.write_fastq(record),
+        }
+    }
+
+    /// Flush any buffered output
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Consume the writer, returning the underlying destination
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// The index of the field holding `column`
+fn field_index(columns: &[Option<Column>], column: Column) -> Option<usize> {
+    columns.iter().position(|c| *c == Some(column))
+}
+
+/// Split a row into its fields, removing the quotes around quoted fields
+/// and undoubling the quotes in them. None if a quote is not closed.
+fn split_row(text: &str, delimiter: char) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        if quoted {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            } else {
+                field.push(c);
+            }
+        } else if c == '"' && field.is_empty() {
+            quoted = true;
+        } else if c == delimiter {
+            fields.push(std::mem::take(&mut field));
+        } else {
+            field.push(c);
+        }
+    }
+    if quoted {
+        return None;
+    }
+    fields.push(field);
+    Some(fields)
+}
+
+/// Iterator over the records in delimited text (tab-separated by default),
+/// such as that written by `TabularWriter`
+///
+/// With a header row (the default), the columns are found from their
+/// names, and columns with other names are ignored; otherwise they are
+/// given with `columns`. There must be id and sequence columns. The length
+/// and gc columns are ignored, as they are derived from the sequence. Rows
+/// with a non-empty quality are returned as FASTQ records, and others as
+/// FASTA records. Quoted fields may not span lines.
+///
+/// ```
+/// use prseq::tabular::TabularReader;
+///
+/// let input = b"id,sequence,quality\nr1,ACGT,IIII\n";
+/// let mut reader = TabularReader::from_reader(&input[..]).unwrap().delimiter(b',');
+/// let record = reader.next().unwrap().unwrap();
+/// assert_eq!(record.quality(), Some("IIII"));
+/// ```
+pub struct TabularReader {
+    lines: LineReader,
+    records: u64,
+    /// The column of each field, with None for ignored fields
+    columns: Vec<Option<Column>>,
+    delimiter: u8,
+    header: bool,
+    started: bool,
+}
+
+impl TabularReader {
+    /// Create a new TabularReader from a file path
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Create a new TabularReader from any readable source with compression
+    /// detection
+    pub fn from_reader<R: Read + Send + 'static>(reader: R) -> Result<Self> {
+        let buf_reader = create_reader_with_compression(reader)?;
+        Ok(TabularReader {
+            lines: LineReader::new(Box::new(buf_reader)),
+            records: 0,
+            columns: DEFAULT_COLUMNS.into_iter().map(Some).collect(),
+            delimiter: b'\t',
+            header: true,
+            started: false,
+        })
+    }
+
+    /// Set the columns of input that has no header row, in order. This
+    /// also turns off reading a header row.
+    pub fn columns(mut self, columns: Vec<Column>) -> Self {
+        self.columns = columns.into_iter().map(Some).collect();
+        self.header = false;
+        self
+    }
+
+    /// Set the delimiter (such as b',' for CSV)
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Set whether the first row is a header row of column names
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    fn read_next(&mut self) -> Result<Option<AnyRecord>> {
+        let delimiter = self.delimiter as char;
+        loop {
+            let position = Position {
+                line: self.lines.line() + 1,
+                record: self.records + 1,
+            };
+            let Some((line, _)) = self.lines.next_line()? else {
+                return Ok(None);
+            };
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let text = utf8_str(line, "Tabular", Some(position))?;
+            let invalid = |message: String| {
+                PrseqError::invalid_format("Tabular", message, position, text.as_bytes())
+            };
+            let fields =
+                split_row(text, delimiter).ok_or_else(|| invalid("Unclosed quote".to_string()))?;
+
+            if !self.started {
+                self.started = true;
+                if self.header {
+                    self.columns = fields.iter().map(|name| name.parse().ok()).collect();
+                }
+                for column in [Column::Id, Column::Sequence] {
+                    if field_index(&self.columns, column).is_none() {
+                        return Err(invalid(format!("Tabular input has no {} column", column)));
+                    }
+                }
+                if self.header {
+                    continue;
+                }
+            }
+
+            let field = |column: Column| {
+                field_index(&self.columns, column)
+                    .and_then(|index| fields.get(index))
+                    .map_or("", String::as_str)
+            };
+            let id = field(Column::Id);
+            let description = field(Column::Description);
+            let header = if description.is_empty() {
+                id.to_string()
+            } else {
+                format!("{} {}", id, description)
+            };
+            let sequence = field(Column::Sequence).to_string();
+            let quality = field(Column::Quality);
+            self.records += 1;
+            if quality.is_empty() {
+                return Ok(Some(AnyRecord::Fasta(FastaRecord { header, sequence })));
+            }
+            if quality.len() != sequence.len() {
+                return Err(PrseqError::LengthMismatch {
+                    sequence_length: sequence.len(),
+                    quality_length: quality.len(),
+                    position,
+                });
+            }
+            return Ok(Some(AnyRecord::Fastq(FastqRecord {
+                header,
+                sequence,
+                quality: quality.to_string(),
+            })));
+        }
+    }
+}
+
+impl Iterator for TabularReader {
+    type Item = Result<AnyRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_next().transpose()
+    }
+}
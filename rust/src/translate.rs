@@ -0,0 +1,468 @@
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use crate::sequence::{base_bits, reverse_complement};
+use std::fmt;
+
+/// An NCBI genetic code: the amino acid each codon translates to
+///
+/// ```
+/// use prseq::translate::{translate, Frame, GeneticCode};
+///
+/// assert_eq!(translate(b"ATGGCCTGA", Frame::F1, &GeneticCode::STANDARD), "MA*");
+/// let mito = GeneticCode::ncbi(2).unwrap();
+/// assert_eq!(translate(b"ATGGCCTGA", Frame::F1, &mito), "MAW");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeneticCode {
+    id: u8,
+    name: &'static str,
+    /// The amino acids of the 64 codons, in TCAG order (TTT, TTC, TTA, TTG,
+    /// TCT, ...) as in the NCBI tables
+    amino_acids: &'static [u8; 64],
+}
+
+const fn code(id: u8, name: &'static str, amino_acids: &'static [u8; 64]) -> GeneticCode {
+    GeneticCode {
+        id,
+        name,
+        amino_acids,
+    }
+}
+
+/// The NCBI genetic codes, by transl_table number
+static CODES: &[GeneticCode] = &[
+    GeneticCode::STANDARD,
+    code(
+        2,
+        "Vertebrate Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+    ),
+    code(
+        3,
+        "Yeast Mitochondrial",
+        b"FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        4,
+        "Mold, Protozoan, and Coelenterate Mitochondrial and Mycoplasma/Spiroplasma",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        5,
+        "Invertebrate Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+    ),
+    code(
+        6,
+        "Ciliate, Dasycladacean and Hexamita Nuclear",
+        b"FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        9,
+        "Echinoderm and Flatworm Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+    ),
+    code(
+        10,
+        "Euplotid Nuclear",
+        b"FFLLSSSSYY**CCCWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        11,
+        "Bacterial, Archaeal and Plant Plastid",
+        b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        12,
+        "Alternative Yeast Nuclear",
+        b"FFLLSSSSYY**CC*WLLLSPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        13,
+        "Ascidian Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSGGVVVVAAAADDEEGGGG",
+    ),
+    code(
+        14,
+        "Alternative Flatworm Mitochondrial",
+        b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+    ),
+    code(
+        16,
+        "Chlorophycean Mitochondrial",
+        b"FFLLSSSSYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        21,
+        "Trematode Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+    ),
+    code(
+        22,
+        "Scenedesmus obliquus Mitochondrial",
+        b"FFLLSS*SYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        23,
+        "Thraustochytrium Mitochondrial",
+        b"FF*LSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        24,
+        "Rhabdopleuridae Mitochondrial",
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+    ),
+    code(
+        25,
+        "Candidate Division SR1 and Gracilibacteria",
+        b"FFLLSSSSYY**CCGWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        26,
+        "Pachysolen tannophilus Nuclear",
+        b"FFLLSSSSYY**CC*WLLLAPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        27,
+        "Karyorelict Nuclear",
+        b"FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        28,
+        "Condylostoma Nuclear",
+        b"FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        29,
+        "Mesodinium Nuclear",
+        b"FFLLSSSSYYYYCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        30,
+        "Peritrich Nuclear",
+        b"FFLLSSSSYYEECC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        31,
+        "Blastocrithidia Nuclear",
+        b"FFLLSSSSYYEECCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    ),
+    code(
+        33,
+        "Cephalodiscidae Mitochondrial",
+        b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+    ),
+];
+
+impl GeneticCode {
+    /// The standard code (NCBI table 1)
+    pub const STANDARD: GeneticCode = code(
+        1,
+        "Standard",
+        b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+    );
+
+    /// The code with NCBI transl_table number `id`, if there is one
+    pub fn ncbi(id: u8) -> Option<GeneticCode> {
+        CODES.iter().find(|code| code.id == id).copied()
+    }
+
+    /// All the NCBI genetic codes, in order of their numbers
+    pub fn all() -> &'static [GeneticCode] {
+        CODES
+    }
+
+    /// The NCBI transl_table number
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// The NCBI name of the code
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Translate a codon (upper or lower case, T or U)
+    ///
+    /// A codon with IUPAC ambiguity codes translates to the amino acid all
+    /// the codons it could be have in common (so GGN is G), and otherwise
+    /// to 'X', as does a codon with any other character. A gap codon,
+    /// "---", is '-'.
+    pub fn translate_codon(&self, codon: [u8; 3]) -> u8 {
+        if codon == *b"---" {
+            return b'-';
+        }
+        let bases = codon.map(base_bits);
+        let mut amino_acid = None;
+        for first in set_bits(bases[0]) {
+            for second in set_bits(bases[1]) {
+                for third in set_bits(bases[2]) {
+                    let translated = self.amino_acids[first * 16 + second * 4 + third];
+                    match amino_acid {
+                        None => amino_acid = Some(translated),
+                        Some(seen) if seen != translated => return b'X',
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
+        amino_acid.unwrap_or(b'X')
+    }
+}
+
+impl Default for GeneticCode {
+    fn default() -> Self {
+        GeneticCode::STANDARD
+    }
+}
+
+/// The indices of the set bits of a base set
+fn set_bits(bases: u8) -> impl Iterator<Item = usize> {
+    (0..4).filter(move |i| bases & (1 << i) != 0)
+}
+
+/// A reading frame: an offset of 0, 1 or 2 bases into the sequence or
+/// into its reverse complement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Frame {
+    F1,
+    F2,
+    F3,
+    R1,
+    R2,
+    R3,
+}
+
+impl Frame {
+    /// The six frames, forward then reverse
+    pub const ALL: [Frame; 6] = [
+        Frame::F1,
+        Frame::F2,
+        Frame::F3,
+        Frame::R1,
+        Frame::R2,
+        Frame::R3,
+    ];
+
+    /// The number of bases skipped before the first codon
+    pub fn offset(&self) -> usize {
+        match self {
+            Frame::F1 | Frame::R1 => 0,
+            Frame::F2 | Frame::R2 => 1,
+            Frame::F3 | Frame::R3 => 2,
+        }
+    }
+
+    /// Whether the frame reads the reverse complement
+    pub fn is_reverse(&self) -> bool {
+        matches!(self, Frame::R1 | Frame::R2 | Frame::R3)
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.is_reverse() { '-' } else { '+' };
+        write!(f, "{}{}", sign, self.offset() + 1)
+    }
+}
+
+/// What translation does at a stop codon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StopCodons {
+    /// Translate it as '*' and carry on (the default)
+    #[default]
+    Keep,
+    /// End the translation before the first stop codon
+    Truncate,
+}
+
+/// What translation does with bases left over after the last whole codon
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PartialCodons {
+    /// Leave them untranslated (the default)
+    #[default]
+    Drop,
+    /// Translate them as 'X'
+    Pad,
+}
+
+/// Options controlling translation
+///
+/// ```
+/// use prseq::translate::{translate_with_options, Frame, StopCodons, TranslateOptions};
+///
+/// let options = TranslateOptions::default().stop_codons(StopCodons::Truncate);
+/// assert_eq!(translate_with_options(b"ATGTAAGGG", Frame::F1, &options), "M");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TranslateOptions {
+    /// The genetic code (the standard code by default)
+    pub code: GeneticCode,
+    pub stop_codons: StopCodons,
+    pub partial_codons: PartialCodons,
+}
+
+impl TranslateOptions {
+    /// Set the genetic code
+    pub fn code(mut self, code: GeneticCode) -> Self {
+        self.code = code;
+        self
+    }
+
+    /// Set what to do at stop codons
+    pub fn stop_codons(mut self, stop_codons: StopCodons) -> Self {
+        self.stop_codons = stop_codons;
+        self
+    }
+
+    /// Set what to do with a trailing partial codon
+    pub fn partial_codons(mut self, partial_codons: PartialCodons) -> Self {
+        self.partial_codons = partial_codons;
+        self
+    }
+}
+
+/// Translate a nucleotide sequence in one frame with a genetic code
+pub fn translate(sequence: &[u8], frame: Frame, code: &GeneticCode) -> String {
+    translate_with_options(sequence, frame, &TranslateOptions::default().code(*code))
+}
+
+/// Translate a nucleotide sequence in one frame
+pub fn translate_with_options(sequence: &[u8], frame: Frame, options: &TranslateOptions) -> String {
+    let reversed;
+    let sequence = if frame.is_reverse() {
+        reversed = reverse_complement(sequence);
+        &reversed
+    } else {
+        sequence
+    };
+    let bases = sequence.get(frame.offset()..).unwrap_or_default();
+    let codons = bases.chunks_exact(3);
+    let partial = !codons.remainder().is_empty();
+    let mut protein = String::with_capacity(bases.len() / 3 + 1);
+    for codon in codons {
+        let amino_acid = options.code.translate_codon([codon[0], codon[1], codon[2]]);
+        if amino_acid == b'*' && options.stop_codons == StopCodons::Truncate {
+            return protein;
+        }
+        protein.push(amino_acid as char);
+    }
+    if partial && options.partial_codons == PartialCodons::Pad {
+        protein.push('X');
+    }
+    protein
+}
+
+/// Translate a nucleotide sequence in all six frames, in the order of
+/// `Frame::ALL`
+pub fn translate_six_frames(sequence: &[u8], code: &GeneticCode) -> [String; 6] {
+    Frame::ALL.map(|frame| translate(sequence, frame, code))
+}
+
+/// An open reading frame, from `find_orfs`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Orf {
+    /// The offset of the first base of the ORF on the forward strand
+    /// (0-based), whichever strand it is on
+    pub start: usize,
+    /// The offset after its last base on the forward strand, including the
+    /// stop codon if it has one
+    pub end: usize,
+    pub frame: Frame,
+    /// The translation, from the start codon's M up to (not including) the
+    /// stop codon
+    pub protein: String,
+    /// Whether the ORF ends with a stop codon, rather than running off the
+    /// end of the sequence
+    pub complete: bool,
+}
+
+/// Find the open reading frames of a nucleotide sequence in all six frames
+///
+/// An ORF starts at the first codon that translates to M (such as ATG)
+/// after the previous stop codon in its frame, and runs to the next stop
+/// codon, or to the end of the frame (then it is not `complete`). ORFs
+/// within longer ones are not reported. Only ORFs whose proteins have at
+/// least `min_len` amino acids are returned, frame by frame in the order of
+/// `Frame::ALL`, and by position within each frame.
+///
+/// ```
+/// use prseq::translate::{find_orfs, Frame, GeneticCode};
+///
+/// let orfs = find_orfs(b"CCATGAAATGGTAGC", 3, &GeneticCode::STANDARD);
+/// assert_eq!(orfs.len(), 1);
+/// assert_eq!((orfs[0].start, orfs[0].end, orfs[0].frame), (2, 14, Frame::F3));
+/// assert_eq!(orfs[0].protein, "MKW");
+/// assert!(orfs[0].complete);
+/// ```
+pub fn find_orfs(sequence: &[u8], min_len: usize, code: &GeneticCode) -> Vec<Orf> {
+    let mut orfs = Vec::new();
+    for frame in Frame::ALL {
+        let protein = translate(sequence, frame, code);
+        let mut add = |start: usize, end: usize, complete: bool| {
+            if end - start < min_len {
+                return;
+            }
+            // Codon i of the frame starts at offset + 3i in the strand read.
+            let first = frame.offset() + 3 * start;
+            let last = frame.offset() + 3 * (end + usize::from(complete));
+            let (start_base, end_base) = if frame.is_reverse() {
+                (sequence.len() - last, sequence.len() - first)
+            } else {
+                (first, last)
+            };
+            orfs.push(Orf {
+                start: start_base,
+                end: end_base,
+                frame,
+                protein: protein[start..end].to_string(),
+                complete,
+            });
+        };
+        let mut start = None;
+        for (i, amino_acid) in protein.bytes().enumerate() {
+            match amino_acid {
+                b'M' if start.is_none() => start = Some(i),
+                b'*' => {
+                    if let Some(start) = start.take() {
+                        add(start, i, true);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(start) = start {
+            add(start, protein.len(), false);
+        }
+    }
+    orfs
+}
+
+impl FastaRecord {
+    /// Translate the sequence, returning a protein record with the same
+    /// header
+    pub fn translate(&self, frame: Frame, options: &TranslateOptions) -> FastaRecord {
+        FastaRecord {
+            header: self.header.clone(),
+            sequence: translate_with_options(self.sequence.as_bytes(), frame, options),
+        }
+    }
+
+    /// The open reading frames of the sequence (see `find_orfs`)
+    pub fn find_orfs(&self, min_len: usize, code: &GeneticCode) -> Vec<Orf> {
+        find_orfs(self.sequence.as_bytes(), min_len, code)
+    }
+}
+
+impl FastqRecord {
+    /// Translate the sequence, returning a protein FASTA record with the
+    /// same header (quality has no meaning for a protein)
+    pub fn translate(&self, frame: Frame, options: &TranslateOptions) -> FastaRecord {
+        FastaRecord {
+            header: self.header.clone(),
+            sequence: translate_with_options(self.sequence.as_bytes(), frame, options),
+        }
+    }
+}
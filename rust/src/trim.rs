@@ -0,0 +1,617 @@
+use crate::error::Result;
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use crate::quality::phred_scores;
+use crate::sequence::base_bits;
+use std::cmp::Reverse;
+use std::ops::Range;
+
+/// Which end of a read an adapter is ligated to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdapterEnd {
+    /// A 3' adapter: it and everything after it are removed. The read may
+    /// end part way through it.
+    ThreePrime,
+    /// A 5' adapter: it and everything before it are removed. The read may
+    /// start part way through it.
+    FivePrime,
+}
+
+/// An adapter sequence to trim, as with cutadapt's `-a` and `-g`
+///
+/// The adapter may contain IUPAC ambiguity codes, which match any of the
+/// bases they stand for. An N in the read only matches an N in the adapter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Adapter {
+    sequence: Vec<u8>,
+    end: AdapterEnd,
+    /// The highest number of errors allowed per aligned adapter base
+    pub max_error_rate: f64,
+    /// The fewest adapter bases that must be aligned to a read end for a
+    /// partial adapter to be trimmed
+    pub min_overlap: usize,
+    /// Whether insertions and deletions count as errors in an alignment,
+    /// rather than only mismatches being allowed
+    pub indels: bool,
+}
+
+impl Adapter {
+    /// An adapter to find with up to one error in ten aligned bases, and at
+    /// least 3 bases of overlap with the read end
+    pub fn new(sequence: impl AsRef<[u8]>, end: AdapterEnd) -> Self {
+        Adapter {
+            sequence: sequence.as_ref().to_vec(),
+            end,
+            max_error_rate: 0.1,
+            min_overlap: 3,
+            indels: true,
+        }
+    }
+
+    /// A 3' adapter
+    pub fn three_prime(sequence: impl AsRef<[u8]>) -> Self {
+        Adapter::new(sequence, AdapterEnd::ThreePrime)
+    }
+
+    /// A 5' adapter
+    pub fn five_prime(sequence: impl AsRef<[u8]>) -> Self {
+        Adapter::new(sequence, AdapterEnd::FivePrime)
+    }
+
+    /// Set the highest number of errors allowed per aligned adapter base.
+    /// Zero finds exact matches only.
+    pub fn max_error_rate(mut self, rate: f64) -> Self {
+        self.max_error_rate = rate;
+        self
+    }
+
+    /// Set the fewest bases of a partial adapter that are trimmed
+    pub fn min_overlap(mut self, bases: usize) -> Self {
+        self.min_overlap = bases;
+        self
+    }
+
+    /// Set whether insertions and deletions are allowed, or only mismatches
+    pub fn indels(mut self, indels: bool) -> Self {
+        self.indels = indels;
+        self
+    }
+
+    /// The adapter sequence
+    pub fn sequence(&self) -> &[u8] {
+        &self.sequence
+    }
+
+    /// The read end the adapter is at
+    pub fn end(&self) -> AdapterEnd {
+        self.end
+    }
+
+    /// The best place the adapter aligns to `read`, if any
+    pub fn find(&self, read: &[u8]) -> Option<AdapterMatch> {
+        match self.end {
+            AdapterEnd::ThreePrime => self.align(read),
+            AdapterEnd::FivePrime => {
+                // A 5' adapter is a 3' adapter on the reversed read.
+                let read: Vec<u8> = read.iter().rev().copied().collect();
+                self.align(&read).map(|found| AdapterMatch {
+                    start: read.len() - found.end,
+                    end: read.len() - found.start,
+                    ..found
+                })
+            }
+        }
+    }
+
+    /// Align the adapter, or for a 5' adapter its reverse, to `read` by
+    /// semi-global alignment with unit costs: it may start anywhere in the
+    /// read, and must either be aligned in full or run off the read's end
+    fn align(&self, read: &[u8]) -> Option<AdapterMatch> {
+        let adapter: Vec<u8> = match self.end {
+            AdapterEnd::ThreePrime => self.sequence.iter().map(|&b| base_bits(b)).collect(),
+            AdapterEnd::FivePrime => self.sequence.iter().rev().map(|&b| base_bits(b)).collect(),
+        };
+        let length = adapter.len();
+        if length == 0 {
+            return None;
+        }
+        let min_overlap = self.min_overlap.clamp(1, length);
+        let allowed = |aligned: usize| (aligned as f64 * self.max_error_rate) as usize;
+        let unreachable = usize::MAX / 2;
+
+        // The cost of the best alignment of the first i adapter bases ending
+        // at the current read position, and where in the read it starts.
+        let initial = |i| {
+            if i == 0 || self.indels {
+                i
+            } else {
+                unreachable
+            }
+        };
+        let mut previous: Vec<(usize, usize)> = (0..=length).map(|i| (initial(i), 0)).collect();
+        let mut current = previous.clone();
+        let mut best: Option<AdapterMatch> = None;
+        let mut consider = |aligned: usize, (errors, start): (usize, usize), end: usize| {
+            if aligned < min_overlap || errors > allowed(aligned) {
+                return;
+            }
+            let found = AdapterMatch {
+                start,
+                end,
+                aligned,
+                errors,
+            };
+            if best.as_ref().is_none_or(|best| found.is_better_than(best)) {
+                best = Some(found);
+            }
+        };
+
+        for (j, &base) in read.iter().enumerate() {
+            let bits = base_bits(base);
+            current[0] = (0, j + 1);
+            for i in 1..=length {
+                let mismatch = bits == 0 || bits & !adapter[i - 1] != 0;
+                let (cost, start) = previous[i - 1];
+                let mut cell = (cost + usize::from(mismatch), start);
+                if self.indels {
+                    // A base missing from the read, or an extra base in it.
+                    let (cost, start) = current[i - 1];
+                    if cost + 1 < cell.0 {
+                        cell = (cost + 1, start);
+                    }
+                    let (cost, start) = previous[i];
+                    if cost + 1 < cell.0 {
+                        cell = (cost + 1, start);
+                    }
+                }
+                current[i] = cell;
+            }
+            consider(length, current[length], j + 1);
+            std::mem::swap(&mut previous, &mut current);
+        }
+
+        // Partial adapters running off the end of the read.
+        for (aligned, &cell) in previous.iter().enumerate().take(length) {
+            consider(aligned, cell, read.len());
+        }
+        best
+    }
+}
+
+/// Where an adapter was found in a read
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdapterMatch {
+    /// The 0-based start of the adapter in the read
+    pub start: usize,
+    /// The end (exclusive) of the adapter in the read
+    pub end: usize,
+    /// The number of adapter bases aligned to the read (fewer than its
+    /// length for a partial adapter at the read end)
+    pub aligned: usize,
+    /// The number of mismatches, insertions and deletions
+    pub errors: usize,
+}
+
+impl AdapterMatch {
+    /// Whether this is a better match than `other`: more matching bases,
+    /// then fewer errors, then further from the adapter's read end
+    fn is_better_than(&self, other: &AdapterMatch) -> bool {
+        let key = |found: &AdapterMatch| {
+            (
+                found.aligned.saturating_sub(found.errors),
+                Reverse(found.errors),
+                Reverse(found.start),
+            )
+        };
+        key(self) > key(other)
+    }
+}
+
+/// What adapter trimming did to one read
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrimReport {
+    /// The index of the adapter that was trimmed and where it was found, if
+    /// any was
+    pub adapter: Option<(usize, AdapterMatch)>,
+    /// The part of the original read that was kept
+    pub kept: Range<usize>,
+    /// The length of the original read
+    pub length: usize,
+}
+
+impl TrimReport {
+    /// Whether an adapter was found
+    pub fn is_trimmed(&self) -> bool {
+        self.adapter.is_some()
+    }
+
+    /// The number of bases removed
+    pub fn removed(&self) -> usize {
+        self.length - self.kept.len()
+    }
+}
+
+/// A set of adapters to trim from reads. The best match among all the
+/// adapters is trimmed from each read.
+///
+/// ```
+/// use prseq::trim::{Adapter, AdapterTrimmer};
+/// use prseq::FastqRecord;
+///
+/// let trimmer = AdapterTrimmer::new([Adapter::three_prime("AGATCGGAAGAGC")]);
+/// let read = FastqRecord {
+///     header: "r1".into(),
+///     sequence: "ACGTACGTTTAGATCGGAAG".into(),
+///     quality: "IIIIIIIIIIIIIIIIIIII".into(),
+/// };
+/// let (trimmed, report) = read.trim_adapters(&trimmer);
+/// assert_eq!(trimmed.sequence, "ACGTACGTTT");
+/// assert_eq!(trimmed.quality.len(), 10);
+/// assert_eq!(report.removed(), 10);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AdapterTrimmer {
+    adapters: Vec<Adapter>,
+}
+
+impl AdapterTrimmer {
+    /// A trimmer for `adapters`
+    pub fn new(adapters: impl IntoIterator<Item = Adapter>) -> Self {
+        AdapterTrimmer {
+            adapters: adapters.into_iter().collect(),
+        }
+    }
+
+    /// The adapters, in the order given
+    pub fn adapters(&self) -> &[Adapter] {
+        &self.adapters
+    }
+
+    /// Find the best adapter match in `read` and the part of the read to
+    /// keep
+    pub fn trim(&self, read: &[u8]) -> TrimReport {
+        let mut best: Option<(usize, AdapterMatch)> = None;
+        for (index, adapter) in self.adapters.iter().enumerate() {
+            if let Some(found) = adapter.find(read) {
+                if best
+                    .as_ref()
+                    .is_none_or(|(_, best)| found.is_better_than(best))
+                {
+                    best = Some((index, found));
+                }
+            }
+        }
+        let kept = match &best {
+            Some((index, found)) => match self.adapters[*index].end {
+                AdapterEnd::ThreePrime => 0..found.start,
+                AdapterEnd::FivePrime => found.end..read.len(),
+            },
+            None => 0..read.len(),
+        };
+        TrimReport {
+            adapter: best,
+            kept,
+            length: read.len(),
+        }
+    }
+}
+
+/// The part of a read to keep by Trimmomatic-style sliding-window trimming
+/// of its Phred+33 `quality`
+///
+/// The read is scanned from its start in windows of `window` bases, and cut
+/// at the first window whose mean quality is below `min_quality`. The bases
+/// at the start of that window that are themselves at least `min_quality`
+/// are kept. A read shorter than the window is treated as one window.
+///
+/// ```
+/// use prseq::trim::sliding_window_range;
+///
+/// // Qualities 40, 40, 40, 40, 30, 10, 2, 2
+/// assert_eq!(sliding_window_range(b"IIII?+##", 4, 20), 0..5);
+/// ```
+pub fn sliding_window_range(quality: &[u8], window: usize, min_quality: u8) -> Range<usize> {
+    let scores: Vec<u32> = phred_scores(quality).map(u32::from).collect();
+    let window = window.clamp(1, scores.len().max(1));
+    let required = u32::from(min_quality) * window as u32;
+    let mut total: u32 = scores.iter().take(window).sum();
+    let mut start = 0;
+    while start + window <= scores.len() {
+        if total < required {
+            let kept = scores[start..start + window]
+                .iter()
+                .take_while(|&&score| score >= u32::from(min_quality))
+                .count();
+            return 0..start + kept;
+        }
+        if start + window < scores.len() {
+            total = total + scores[start + window] - scores[start];
+        }
+        start += 1;
+    }
+    0..scores.len()
+}
+
+/// The part of a read to keep after removing the bases with a Phred+33
+/// quality below `min_quality` from both of its ends, as Trimmomatic's
+/// LEADING and TRAILING steps
+pub fn quality_ends_range(quality: &[u8], min_quality: u8) -> Range<usize> {
+    let scores: Vec<u8> = phred_scores(quality).collect();
+    let start = scores
+        .iter()
+        .position(|&score| score >= min_quality)
+        .unwrap_or(scores.len());
+    let end = scores
+        .iter()
+        .rposition(|&score| score >= min_quality)
+        .map_or(start, |end| end + 1);
+    start..end
+}
+
+/// Settings for trimming a homopolymer tail, such as a poly-A tail or the
+/// poly-G runs NovaSeq and NextSeq call where there is no signal, from the
+/// 3' end of reads
+///
+/// The tail may contain a few other bases (sequencing errors). It is only
+/// trimmed if it is at least `min_length` long, and the cut is always made
+/// at a base of the tail.
+///
+/// ```
+/// use prseq::trim::{poly_tail_start, PolyTrim};
+///
+/// let poly_a = PolyTrim::poly_a().min_length(5);
+/// assert_eq!(poly_tail_start(b"ACGTCAAAAAAA", None, &poly_a), 5);
+/// assert_eq!(poly_tail_start(b"ACGTCAAAA", None, &poly_a), 9);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolyTrim {
+    /// The base of the tail (in either case)
+    pub base: u8,
+    /// The shortest tail that is trimmed
+    pub min_length: usize,
+    /// The highest number of other bases allowed per tail base
+    pub max_error_rate: f64,
+    /// Other bases with a quality score below this are not counted as
+    /// errors, as they are likely miscalls (only used for FASTQ)
+    pub min_quality: u8,
+}
+
+impl PolyTrim {
+    /// Trim tails of `base` of 10 or more, allowing one other base in every
+    /// eight
+    pub fn new(base: u8) -> Self {
+        PolyTrim {
+            base,
+            min_length: 10,
+            max_error_rate: 0.125,
+            min_quality: 0,
+        }
+    }
+
+    /// Trim poly-A tails
+    pub fn poly_a() -> Self {
+        PolyTrim::new(b'A')
+    }
+
+    /// Trim poly-G tails
+    pub fn poly_g() -> Self {
+        PolyTrim::new(b'G')
+    }
+
+    /// Set the shortest tail that is trimmed
+    pub fn min_length(mut self, length: usize) -> Self {
+        self.min_length = length;
+        self
+    }
+
+    /// Set the highest number of other bases allowed per tail base
+    pub fn max_error_rate(mut self, rate: f64) -> Self {
+        self.max_error_rate = rate;
+        self
+    }
+
+    /// Set the quality below which other bases are not counted as errors
+    pub fn min_quality(mut self, quality: u8) -> Self {
+        self.min_quality = quality;
+        self
+    }
+}
+
+/// Where the homopolymer tail described by `trim` starts in `sequence`, or
+/// the sequence's length if it has none. The Phred+33 `quality`, if given,
+/// is used to ignore low-quality errors in the tail.
+pub fn poly_tail_start(sequence: &[u8], quality: Option<&[u8]>, trim: &PolyTrim) -> usize {
+    let base = trim.base.to_ascii_uppercase();
+    let lowest = trim.min_quality.saturating_add(33);
+    let min_length = trim.min_length.max(1);
+    let allowed = |length: usize| (length as f64 * trim.max_error_rate) as usize;
+    let mut start = sequence.len();
+    let mut errors = 0;
+    for (i, &b) in sequence.iter().enumerate().rev() {
+        let length = sequence.len() - i;
+        if b.to_ascii_uppercase() == base {
+            if length >= min_length && errors <= allowed(length) {
+                start = i;
+            }
+        } else if quality.is_none_or(|quality| quality.get(i).is_none_or(|&q| q >= lowest)) {
+            errors += 1;
+            if errors > allowed(length.max(min_length)) {
+                break;
+            }
+        }
+    }
+    start
+}
+
+/// The part of `sequence` left after removing runs of at least `min_length`
+/// Ns (in either case) from its start and end
+///
+/// ```
+/// use prseq::trim::n_ends_range;
+///
+/// assert_eq!(n_ends_range(b"NNACGTNnN", 1), 2..6);
+/// assert_eq!(n_ends_range(b"NNACGTNnN", 3), 0..6);
+/// ```
+pub fn n_ends_range(sequence: &[u8], min_length: usize) -> Range<usize> {
+    let min_length = min_length.max(1);
+    let is_n = |b: &u8| b | 0x20 == b'n';
+    let leading = sequence.iter().take_while(|b| is_n(b)).count();
+    let start = if leading >= min_length { leading } else { 0 };
+    let trailing = sequence[start..]
+        .iter()
+        .rev()
+        .take_while(|b| is_n(b))
+        .count();
+    let end = if trailing >= min_length {
+        sequence.len() - trailing
+    } else {
+        sequence.len()
+    };
+    start..end
+}
+
+/// The part of `text` in `range`, clamped to its length. A range that is
+/// not on character boundaries (in a sequence that is not ASCII) is cut
+/// lossily rather than panicking.
+pub(crate) fn trim_str(text: &str, range: &Range<usize>) -> String {
+    let end = range.end.min(text.len());
+    let start = range.start.min(end);
+    String::from_utf8_lossy(&text.as_bytes()[start..end]).into_owned()
+}
+
+impl FastaRecord {
+    /// The record with the best matching adapter of `trimmer` removed, and
+    /// a report of what was trimmed
+    pub fn trim_adapters(&self, trimmer: &AdapterTrimmer) -> (FastaRecord, TrimReport) {
+        let report = trimmer.trim(self.sequence.as_bytes());
+        let record = FastaRecord {
+            header: self.header.clone(),
+            sequence: trim_str(&self.sequence, &report.kept),
+        };
+        (record, report)
+    }
+
+    /// The record with its homopolymer tail removed (see `poly_tail_start`)
+    pub fn trim_poly_tail(&self, trim: &PolyTrim) -> FastaRecord {
+        let start = poly_tail_start(self.sequence.as_bytes(), None, trim);
+        FastaRecord {
+            header: self.header.clone(),
+            sequence: trim_str(&self.sequence, &(0..start)),
+        }
+    }
+
+    /// The record with runs of at least `min_length` Ns removed from both
+    /// ends of its sequence
+    pub fn trim_ns(&self, min_length: usize) -> FastaRecord {
+        FastaRecord {
+            header: self.header.clone(),
+            sequence: trim_str(
+                &self.sequence,
+                &n_ends_range(self.sequence.as_bytes(), min_length),
+            ),
+        }
+    }
+}
+
+impl FastqRecord {
+    /// The record with the best matching adapter of `trimmer` removed from
+    /// its sequence and quality, and a report of what was trimmed
+    pub fn trim_adapters(&self, trimmer: &AdapterTrimmer) -> (FastqRecord, TrimReport) {
+        let report = trimmer.trim(self.sequence.as_bytes());
+        (self.trimmed(&report.kept), report)
+    }
+
+    /// The record cut by sliding-window quality trimming (see
+    /// `sliding_window_range`)
+    ///
+    /// ```
+    /// use prseq::FastqRecord;
+    ///
+    /// let read = FastqRecord {
+    ///     header: "r1".into(),
+    ///     sequence: "ACGTACGT".into(),
+    ///     quality: "IIII?+##".into(),
+    /// };
+    /// let trimmed = read.trim_quality(4, 20);
+    /// assert_eq!((trimmed.sequence.as_str(), trimmed.quality.as_str()), ("ACGTA", "IIII?"));
+    /// ```
+    pub fn trim_quality(&self, window: usize, min_quality: u8) -> FastqRecord {
+        self.trimmed(&sliding_window_range(
+            self.quality.as_bytes(),
+            window,
+            min_quality,
+        ))
+    }
+
+    /// The record with the bases below `min_quality` removed from both ends
+    /// (see `quality_ends_range`)
+    pub fn trim_quality_ends(&self, min_quality: u8) -> FastqRecord {
+        self.trimmed(&quality_ends_range(self.quality.as_bytes(), min_quality))
+    }
+
+    /// The record with its homopolymer tail removed (see `poly_tail_start`),
+    /// ignoring low-quality errors in the tail if `trim.min_quality` is set
+    pub fn trim_poly_tail(&self, trim: &PolyTrim) -> FastqRecord {
+        let start = poly_tail_start(
+            self.sequence.as_bytes(),
+            Some(self.quality.as_bytes()),
+            trim,
+        );
+        self.trimmed(&(0..start))
+    }
+
+    /// The record with runs of at least `min_length` Ns removed from both
+    /// ends of its sequence and quality
+    pub fn trim_ns(&self, min_length: usize) -> FastqRecord {
+        self.trimmed(&n_ends_range(self.sequence.as_bytes(), min_length))
+    }
+
+    /// The record cut to `range`, with quality cut in lockstep
+    pub(crate) fn trimmed(&self, range: &Range<usize>) -> FastqRecord {
+        FastqRecord {
+            header: self.header.clone(),
+            sequence: trim_str(&self.sequence, range),
+            quality: trim_str(&self.quality, range),
+        }
+    }
+}
+
+/// Trimming adaptors for FASTQ readers (or any iterator of FASTQ record
+/// results). Errors are passed through.
+pub trait TrimExt: Iterator<Item = Result<FastqRecord>> + Sized {
+    /// Trim adapters from each read, giving the trimmed read and its report
+    fn trim_adapters(
+        self,
+        trimmer: AdapterTrimmer,
+    ) -> impl Iterator<Item = Result<(FastqRecord, TrimReport)>> {
+        self.map(move |result| result.map(|record| record.trim_adapters(&trimmer)))
+    }
+
+    /// Trim each read by sliding-window quality trimming
+    fn trim_quality(
+        self,
+        window: usize,
+        min_quality: u8,
+    ) -> impl Iterator<Item = Result<FastqRecord>> {
+        self.map(move |result| result.map(|record| record.trim_quality(window, min_quality)))
+    }
+
+    /// Trim the bases below `min_quality` from both ends of each read
+    fn trim_quality_ends(self, min_quality: u8) -> impl Iterator<Item = Result<FastqRecord>> {
+        self.map(move |result| result.map(|record| record.trim_quality_ends(min_quality)))
+    }
+
+    /// Trim a homopolymer tail from each read
+    fn trim_poly_tail(self, trim: PolyTrim) -> impl Iterator<Item = Result<FastqRecord>> {
+        self.map(move |result| result.map(|record| record.trim_poly_tail(&trim)))
+    }
+
+    /// Trim runs of at least `min_length` Ns from both ends of each read
+    fn trim_ns(self, min_length: usize) -> impl Iterator<Item = Result<FastqRecord>> {
+        self.map(move |result| result.map(|record| record.trim_ns(min_length)))
+    }
+}
+
+impl<I: Iterator<Item = Result<FastqRecord>>> TrimExt for I {}
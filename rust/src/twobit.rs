@@ -0,0 +1,305 @@
+use crate::error::{Position, PrseqError, Result};
+use crate::fasta::FastaRecord;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+/// The signature at the start of a 2bit file, as read in its own byte order
+const SIGNATURE: u32 = 0x1A41_2743;
+
+/// The bases for each 2-bit code
+const BASES: [u8; 4] = [b'T', b'C', b'A', b'G'];
+
+/// An error for a malformed 2bit file, in the header of the sequence
+/// numbered `record` (1-based), or in the file's own header if it is 0
+fn invalid(message: String, record: u64) -> PrseqError {
+    PrseqError::invalid_format("2bit", message, Position { line: 0, record }, &[])
+}
+
+/// Where a sequence's bases are, and its runs of N and soft-masked bases
+#[derive(Debug)]
+struct SequenceInfo {
+    length: u64,
+    /// The offset of the packed bases
+    dna_offset: u64,
+    /// (start, length) of each block of Ns, in order
+    n_blocks: Vec<(u64, u64)>,
+    /// (start, length) of each soft-masked (lower case) block, in order
+    mask_blocks: Vec<(u64, u64)>,
+}
+
+/// Random access to the sequences of a UCSC 2bit file
+///
+/// The index of sequence names is read when the file is opened, and each
+/// sequence's header (its length and N and mask blocks) the first time it
+/// is used. Only the bytes holding the requested bases are read.
+/// Soft-masked bases are returned in lower case unless `soft_mask(false)`
+/// is used.
+pub struct TwoBitReader<R: Read + Seek = BufReader<File>> {
+    reader: R,
+    /// The size of the file, which bounds the counts read from it
+    file_length: u64,
+    big_endian: bool,
+    /// The sequence names and header offsets, in file order
+    sequences: Vec<(String, u64)>,
+    positions: HashMap<String, usize>,
+    info: HashMap<usize, Arc<SequenceInfo>>,
+    soft_mask: bool,
+}
+
+impl TwoBitReader<BufReader<File>> {
+    /// Open a 2bit file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> TwoBitReader<R> {
+    /// Create a new TwoBitReader, reading the file header and index
+    pub fn new(mut reader: R) -> Result<Self> {
+        let file_length = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
+        let mut header = [0; 16];
+        reader.read_exact(&mut header)?;
+        let signature = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let big_endian = match signature {
+            SIGNATURE => false,
+            _ if signature.swap_bytes() == SIGNATURE => true,
+            _ => return Err(invalid("Not a 2bit file (bad signature)".to_string(), 0)),
+        };
+        let mut twobit = TwoBitReader {
+            reader,
+            file_length,
+            big_endian,
+            sequences: Vec::new(),
+            positions: HashMap::new(),
+            info: HashMap::new(),
+            soft_mask: true,
+        };
+        let word = |i: usize| twobit.u32_from(&header[i..i + 4]);
+        let (version, count) = (word(4), word(8));
+        if version > 1 {
+            return Err(invalid(format!("Unsupported 2bit version {}", version), 0));
+        }
+        for position in 0..count as usize {
+            let mut size = [0];
+            twobit.reader.read_exact(&mut size)?;
+            let mut name = vec![0; size[0] as usize];
+            twobit.reader.read_exact(&mut name)?;
+            let name = String::from_utf8(name)
+                .map_err(|_| invalid("Sequence name is not valid UTF-8".to_string(), 0))?;
+            // Version 1 files have 64-bit offsets, for files over 4 GB.
+            let offset = if version == 1 {
+                twobit.read_u64()?
+            } else {
+                u64::from(twobit.read_u32()?)
+            };
+            twobit.positions.insert(name.clone(), position);
+            twobit.sequences.push((name, offset));
+        }
+        Ok(twobit)
+    }
+
+    /// Set whether soft-masked bases are returned in lower case (the
+    /// default) or upper case
+    pub fn soft_mask(mut self, soft_mask: bool) -> Self {
+        self.soft_mask = soft_mask;
+        self
+    }
+
+    fn u32_from(&self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        let mut bytes = [0; 4];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(self.u32_from(&bytes))
+    }
+
+    fn read_u64(&mut self) -> Result<u64> {
+        let mut bytes = [0; 8];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(if self.big_endian {
+            u64::from_be_bytes(bytes)
+        } else {
+            u64::from_le_bytes(bytes)
+        })
+    }
+
+    /// Read a count of blocks and the blocks, given as all their starts
+    /// then all their lengths, for the sequence numbered `record`
+    fn read_blocks(&mut self, record: u64) -> Result<Vec<(u64, u64)>> {
+        let count = u64::from(self.read_u32()?);
+        // Check the count against the file before allocating for it.
+        let remaining = self.file_length - self.reader.stream_position()?;
+        if count * 8 > remaining {
+            return Err(invalid(
+                format!("Block count {} is more than the file holds", count),
+                record,
+            ));
+        }
+        let mut starts = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            starts.push(u64::from(self.read_u32()?));
+        }
+        let mut blocks = Vec::with_capacity(starts.len());
+        for start in starts {
+            let length = u64::from(self.read_u32()?);
+            if blocks
+                .last()
+                .is_some_and(|&(previous, previous_length)| previous + previous_length > start)
+            {
+                return Err(invalid("Blocks are out of order".to_string(), record));
+            }
+            blocks.push((start, length));
+        }
+        Ok(blocks)
+    }
+
+    /// The names of the sequences, in file order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.sequences.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// The number of sequences
+    pub fn len(&self) -> usize {
+        self.sequences.len()
+    }
+
+    /// Whether the file has no sequences
+    pub fn is_empty(&self) -> bool {
+        self.sequences.is_empty()
+    }
+
+    fn info(&mut self, name: &str) -> Result<Arc<SequenceInfo>> {
+        let position = *self.positions.get(name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Sequence '{}' is not in the 2bit file", name),
+            )
+        })?;
+        if let Some(info) = self.info.get(&position) {
+            return Ok(Arc::clone(info));
+        }
+        let record = position as u64 + 1;
+        let offset = self.sequences[position].1;
+        if offset >= self.file_length {
+            return Err(invalid(
+                format!("Sequence '{}' starts past the end of the file", name),
+                record,
+            ));
+        }
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let length = u64::from(self.read_u32()?);
+        let n_blocks = self.read_blocks(record)?;
+        let mask_blocks = self.read_blocks(record)?;
+        let _reserved = self.read_u32()?;
+        let dna_offset = self.reader.stream_position()?;
+        if dna_offset + length.div_ceil(4) > self.file_length {
+            return Err(invalid(
+                format!("Sequence '{}' runs past the end of the file", name),
+                record,
+            ));
+        }
+        let info = Arc::new(SequenceInfo {
+            length,
+            dna_offset,
+            n_blocks,
+            mask_blocks,
+        });
+        self.info.insert(position, Arc::clone(&info));
+        Ok(info)
+    }
+
+    /// The length of the named sequence
+    pub fn length(&mut self, name: &str) -> Result<u64> {
+        Ok(self.info(name)?.length)
+    }
+
+    /// Fetch the bases from `start` (0-based, inclusive) to `end` (exclusive)
+    /// of the named sequence. `end` is clipped to the sequence length.
+    pub fn fetch(&mut self, name: &str, start: u64, end: u64) -> Result<String> {
+        let info = self.info(name)?;
+        if start > end.min(info.length) {
+            return Err(PrseqError::OutOfRange {
+                range: format!("{}:{}-{}", name, start, end),
+                length: info.length as usize,
+            });
+        }
+        let end = end.min(info.length);
+        if start == end {
+            return Ok(String::new());
+        }
+
+        let first_byte = start / 4;
+        let mut packed = vec![0; ((end - 1) / 4 - first_byte + 1) as usize];
+        self.reader
+            .seek(SeekFrom::Start(info.dna_offset + first_byte))?;
+        self.reader.read_exact(&mut packed)?;
+        let mut bases: Vec<u8> = (start..end)
+            .map(|base| {
+                let byte = packed[(base / 4 - first_byte) as usize];
+                BASES[((byte >> (6 - 2 * (base % 4))) & 3) as usize]
+            })
+            .collect();
+
+        // Apply the blocks that overlap the range.
+        for range in overlapping(&info.n_blocks, start, end) {
+            bases[range].fill(b'N');
+        }
+        if self.soft_mask {
+            for range in overlapping(&info.mask_blocks, start, end) {
+                bases[range].make_ascii_lowercase();
+            }
+        }
+        Ok(bases.into_iter().map(char::from).collect())
+    }
+
+    /// Fetch the whole of the named sequence
+    pub fn fetch_sequence(&mut self, name: &str) -> Result<String> {
+        self.fetch(name, 0, u64::MAX)
+    }
+
+    /// Fetch the named sequence as a FASTA record
+    pub fn fetch_record(&mut self, name: &str) -> Result<FastaRecord> {
+        Ok(FastaRecord {
+            header: name.to_string(),
+            sequence: self.fetch_sequence(name)?,
+        })
+    }
+
+    /// Iterate over all the sequences, in file order, as FASTA records
+    pub fn records(&mut self) -> impl Iterator<Item = Result<FastaRecord>> + '_ {
+        (0..self.sequences.len()).map(move |position| {
+            let name = self.sequences[position].0.clone();
+            self.fetch_record(&name)
+        })
+    }
+}
+
+/// The parts of `start..end` covered by `blocks`, as ranges relative to
+/// `start`. The blocks are in order and do not overlap, so the first one
+/// that can overlap is found by binary search.
+fn overlapping(
+    blocks: &[(u64, u64)],
+    start: u64,
+    end: u64,
+) -> impl Iterator<Item = std::ops::Range<usize>> + '_ {
+    let first = blocks.partition_point(|&(block_start, length)| block_start + length <= start);
+    blocks[first..]
+        .iter()
+        .take_while(move |&&(block_start, _)| block_start < end)
+        .map(move |&(block_start, length)| {
+            let from = block_start.max(start);
+            let to = (block_start + length).min(end);
+            (from - start) as usize..(to - start) as usize
+        })
+}
@@ -0,0 +1,174 @@
+use crate::error::{PrseqError, Result};
+use crate::fastq::FastqRecord;
+use crate::trim::trim_str;
+
+/// How a UMI is added to a read's header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UmiFormat {
+    /// Append it to the ID after a separator: `_` gives `read1_ACGTAC` (as
+    /// umi_tools does), `:` gives the Casava 1.8 UMI field `read1:ACGTAC`,
+    /// and `:UMI_` gives `read1:UMI_ACGTAC`. It goes before a `/1` or `/2`
+    /// pair suffix.
+    Id(String),
+    /// Add it as a word at the end of the header after a prefix, e.g.
+    /// `RX:Z:` gives the SAM tag style `read1 1:N:0 RX:Z:ACGTAC`
+    Comment(String),
+}
+
+impl Default for UmiFormat {
+    fn default() -> Self {
+        UmiFormat::Id("_".to_string())
+    }
+}
+
+/// Which read of a pair a UMI is taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UmiRead {
+    /// The start of R1 (the default)
+    #[default]
+    R1,
+    /// The start of R2
+    R2,
+    /// The start of both reads, joined with R1's first
+    Both,
+}
+
+/// Moves fixed-length UMIs (unique molecular identifiers) from the start of
+/// reads, or from index reads, into the read headers, as `umi_tools
+/// extract` does
+///
+/// The UMI's bases and qualities are removed from the read. The same UMI is
+/// added to both reads of a pair.
+///
+/// ```
+/// use prseq::umi::{UmiExtractor, UmiFormat};
+/// use prseq::FastqRecord;
+///
+/// let read = FastqRecord {
+///     header: "read1 1:N:0".into(),
+///     sequence: "ACGTACGGGG".into(),
+///     quality: "##########".into(),
+/// };
+/// let extractor = UmiExtractor::new(6);
+/// assert_eq!(extractor.extract(&read).unwrap().header, "read1_ACGTAC 1:N:0");
+///
+/// let extractor = extractor.format(UmiFormat::Comment("RX:Z:".into()));
+/// let moved = extractor.extract(&read).unwrap();
+/// assert_eq!(moved.header, "read1 1:N:0 RX:Z:ACGTAC");
+/// assert_eq!(moved.sequence, "GGGG");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UmiExtractor {
+    /// The number of bases in a UMI
+    pub length: usize,
+    /// How the UMI is added to headers
+    pub format: UmiFormat,
+}
+
+impl UmiExtractor {
+    /// An extractor for UMIs of `length` bases, appended to the read ID
+    /// after an underscore
+    pub fn new(length: usize) -> Self {
+        UmiExtractor {
+            length,
+            format: UmiFormat::default(),
+        }
+    }
+
+    /// Set how the UMI is added to headers
+    pub fn format(mut self, format: UmiFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Split the UMI off the start of a read, giving the UMI and the rest
+    /// of the read, or an OutOfRange error if the read is too short
+    pub fn split(&self, record: &FastqRecord) -> Result<(String, FastqRecord)> {
+        let length = record.sequence.len();
+        if length < self.length {
+            return Err(PrseqError::OutOfRange {
+                range: format!("0..{}", self.length),
+                length,
+            });
+        }
+        let umi = trim_str(&record.sequence, &(0..self.length));
+        Ok((umi, record.trimmed(&(self.length..length))))
+    }
+
+    /// The record with `umi` added to its header
+    pub fn tag(&self, mut record: FastqRecord, umi: &str) -> FastqRecord {
+        record.header = match &self.format {
+            UmiFormat::Id(separator) => {
+                // Keep a /1 or /2 suffix at the end, so pairs still match.
+                let id = record.id();
+                let (name, suffix) = match id.strip_suffix("/1").or(id.strip_suffix("/2")) {
+                    Some(name) => id.split_at(name.len()),
+                    None => (id, ""),
+                };
+                let id = format!("{}{}{}{}", name, separator, umi, suffix);
+                match record.description() {
+                    Some(description) => format!("{} {}", id, description),
+                    None => id,
+                }
+            }
+            UmiFormat::Comment(prefix) => format!("{} {}{}", record.header, prefix, umi),
+        };
+        record
+    }
+
+    /// Move the UMI at the start of a read into its header, or return an
+    /// OutOfRange error if the read is too short
+    pub fn extract(&self, record: &FastqRecord) -> Result<FastqRecord> {
+        let (umi, rest) = self.split(record)?;
+        Ok(self.tag(rest, &umi))
+    }
+
+    /// Move the UMI at the start of one or both reads of a pair into both
+    /// headers
+    pub fn extract_pair(
+        &self,
+        r1: &FastqRecord,
+        r2: &FastqRecord,
+        from: UmiRead,
+    ) -> Result<(FastqRecord, FastqRecord)> {
+        let (umi, r1, r2) = match from {
+            UmiRead::R1 => {
+                let (umi, r1) = self.split(r1)?;
+                (umi, r1, r2.clone())
+            }
+            UmiRead::R2 => {
+                let (umi, r2) = self.split(r2)?;
+                (umi, r1.clone(), r2)
+            }
+            UmiRead::Both => {
+                let (umi1, r1) = self.split(r1)?;
+                let (umi2, r2) = self.split(r2)?;
+                (umi1 + &umi2, r1, r2)
+            }
+        };
+        Ok((self.tag(r1, &umi), self.tag(r2, &umi)))
+    }
+
+    /// Add the UMI at the start of an index read to a read's header. The
+    /// index read is left as it is.
+    pub fn extract_from_index(
+        &self,
+        record: &FastqRecord,
+        index: &FastqRecord,
+    ) -> Result<FastqRecord> {
+        let (umi, _) = self.split(index)?;
+        Ok(self.tag(record.clone(), &umi))
+    }
+}
+
+/// UMI extraction for FASTQ readers (or any iterator of FASTQ record
+/// results)
+pub trait UmiExt: Iterator<Item = Result<FastqRecord>> + Sized {
+    /// Move the UMI at the start of each read into its header. Reads too
+    /// short to hold a UMI give an OutOfRange error.
+    fn extract_umis(self, extractor: UmiExtractor) -> impl Iterator<Item = Result<FastqRecord>> {
+        self.map(move |result| result.and_then(|record| extractor.extract(&record)))
+    }
+}
+
+impl<I: Iterator<Item = Result<FastqRecord>>> UmiExt for I {}
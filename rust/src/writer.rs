@@ -0,0 +1,194 @@
+use crate::fasta::FastaRecord;
+use crate::fastq::FastqRecord;
+use crate::line_ending::LineEnding;
+use std::fs::File;
+use std::io::{BufWriter, Result, Write};
+use std::path::Path;
+
+/// Writes FASTA records to any writable destination
+pub struct FastaWriter<W: Write> {
+    writer: W,
+    line_width: usize,
+    line_ending: LineEnding,
+}
+
+impl FastaWriter<BufWriter<File>> {
+    /// Create a new FastaWriter that writes (uncompressed) to a file path
+    pub fn to_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> FastaWriter<W> {
+    /// Create a new FastaWriter that writes each sequence on a single line
+    pub fn new(writer: W) -> Self {
+        Self::with_line_width(writer, 0)
+    }
+
+    /// Create a new FastaWriter that wraps sequences at `line_width`
+    /// characters (0 means no wrapping)
+    pub fn with_line_width(writer: W, line_width: usize) -> Self {
+        FastaWriter {
+            writer,
+            line_width,
+            line_ending: LineEnding::Lf,
+        }
+    }
+
+    /// End lines with `line_ending` rather than "\n"
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Write a single record
+    pub fn write_record(&mut self, record: &FastaRecord) -> Result<()> {
+        self.write_parts(&record.header, &record.sequence)
+    }
+
+    /// Write a record with this header and sequence
+    fn write_parts(&mut self, header: &str, sequence: &str) -> Result<()> {
+        let ending = self.line_ending.as_bytes();
+        self.writer.write_all(b">")?;
+        self.writer.write_all(header.as_bytes())?;
+        self.writer.write_all(ending)?;
+        write_wrapped(&mut self.writer, sequence, self.line_width, ending)
+    }
+
+    /// Flush any buffered output
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+
+    /// Return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Writes FASTQ records (in four-line form) to any writable destination
+pub struct FastqWriter<W: Write> {
+    writer: W,
+    line_ending: LineEnding,
+}
+
+impl FastqWriter<BufWriter<File>> {
+    /// Create a new FastqWriter that writes (uncompressed) to a file path
+    pub fn to_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> FastqWriter<W> {
+    /// Create a new FastqWriter
+    pub fn new(writer: W) -> Self {
+        FastqWriter {
+            writer,
+            line_ending: LineEnding::Lf,
+        }
+    }
+
+    /// End lines with `line_ending` rather than "\n"
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Write a single record
+    pub fn write_record(&mut self, record: &FastqRecord) -> Result<()> {
+        let ending = self.line_ending.as_bytes();
+        for line in [
+            &b"@"[..],
+            record.header.as_bytes(),
+            ending,
+            record.sequence.as_bytes(),
+            ending,
+            b"+",
+            ending,
+            record.quality.as_bytes(),
+            ending,
+        ] {
+            self.writer.write_all(line)?;
+        }
+        Ok(())
+    }
+
+    /// Flush any buffered output
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+
+    /// Return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Writers of records of type `R`, so that code (such as a `Pipeline`) can
+/// write to either format
+///
+/// A `FastaWriter` also writes FASTQ records, leaving out their quality.
+pub trait RecordWriter<R> {
+    /// Write a single record
+    fn write_record(&mut self, record: &R) -> Result<()>;
+
+    /// Flush any buffered output
+    fn flush(&mut self) -> Result<()>;
+}
+
+impl<W: Write> RecordWriter<FastaRecord> for FastaWriter<W> {
+    fn write_record(&mut self, record: &FastaRecord) -> Result<()> {
+        FastaWriter::write_record(self, record)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        FastaWriter::flush(self)
+    }
+}
+
+impl<W: Write> RecordWriter<FastqRecord> for FastaWriter<W> {
+    fn write_record(&mut self, record: &FastqRecord) -> Result<()> {
+        self.write_parts(&record.header, &record.sequence)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        FastaWriter::flush(self)
+    }
+}
+
+impl<W: Write> RecordWriter<FastqRecord> for FastqWriter<W> {
+    fn write_record(&mut self, record: &FastqRecord) -> Result<()> {
+        FastqWriter::write_record(self, record)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        FastqWriter::flush(self)
+    }
+}
+
+impl<R, W: RecordWriter<R> + ?Sized> RecordWriter<R> for &mut W {
+    fn write_record(&mut self, record: &R) -> Result<()> {
+        (**self).write_record(record)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        (**self).flush()
+    }
+}
+
+fn write_wrapped<W: Write>(
+    writer: &mut W,
+    sequence: &str,
+    line_width: usize,
+    ending: &[u8],
+) -> Result<()> {
+    if line_width == 0 || sequence.len() <= line_width {
+        writer.write_all(sequence.as_bytes())?;
+        return writer.write_all(ending);
+    }
+    for line in sequence.as_bytes().chunks(line_width) {
+        writer.write_all(line)?;
+        writer.write_all(ending)?;
+    }
+    Ok(())
+}
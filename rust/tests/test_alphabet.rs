@@ -0,0 +1,77 @@
+// Tests for sequence alphabet validation
+use prseq::{Alphabet, FastaReader, FastqReader, OnError, ParserOptions, PrseqError};
+use std::io::Cursor;
+
+#[test]
+fn test_alphabets() {
+    assert!(Alphabet::Dna.contains(b'a'));
+    assert!(!Alphabet::Dna.contains(b'U'));
+    assert!(Alphabet::Rna.contains(b'u'));
+    assert!(!Alphabet::Rna.contains(b'T'));
+    assert!(Alphabet::Iupac.contains(b'R'));
+    assert!(Alphabet::Iupac.contains(b'-'));
+    assert!(!Alphabet::Iupac.contains(b'E'));
+    assert!(Alphabet::Protein.contains(b'E'));
+    assert!(Alphabet::Protein.contains(b'*'));
+    assert!(!Alphabet::Protein.contains(b'1'));
+
+    assert_eq!(Alphabet::Dna.find_invalid(b"ACGTNacgtn"), None);
+    assert_eq!(Alphabet::Dna.find_invalid(b"ACGRT"), Some(3));
+    assert_eq!(Alphabet::Dna.find_invalid(b""), None);
+}
+
+#[test]
+fn test_fasta_validation_error() {
+    let input = b">seq1\nACGT\n>seq2 bad\nACGT\nAC-T\n".to_vec();
+    let mut reader = FastaReader::from_reader_with_capacity(Cursor::new(input), 64)
+        .unwrap()
+        .validate(Alphabet::Dna);
+    reader.next().unwrap().unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    match &error {
+        PrseqError::InvalidSequence {
+            id,
+            alphabet,
+            offset,
+            byte,
+            position,
+        } => {
+            assert_eq!(id, "seq2");
+            assert_eq!(*alphabet, Alphabet::Dna);
+            assert_eq!(*offset, 6);
+            assert_eq!(*byte, b'-');
+            assert_eq!(position.record, 2);
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(
+        error.to_string(),
+        "Invalid DNA character '-' at offset 6 of sequence 'seq2' at line 3, record 2"
+    );
+}
+
+#[test]
+fn test_fastq_validation_with_skip() {
+    let input = b"@r1\nACGU\n+\nIIII\n@r2\nACGT\n+\nIIII\n@r3\nAC\xc3\n+\nIII\n".to_vec();
+    let mut reader = FastqReader::from_reader_with_capacity(Cursor::new(input), 64)
+        .unwrap()
+        .with_options(
+            ParserOptions::default()
+                .validate(Alphabet::Dna)
+                .on_error(OnError::Skip),
+        );
+    let ids: Vec<_> = reader.by_ref().map(|r| r.unwrap().header).collect();
+    assert_eq!(ids, vec!["r2"]);
+    assert_eq!(reader.skipped(), 2);
+}
+
+#[test]
+fn test_protein_validation() {
+    let input = b">p1\nMKV*\n>p2\nmkvl\n".to_vec();
+    let records: Vec<_> = FastaReader::from_reader_with_capacity(Cursor::new(input), 64)
+        .unwrap()
+        .validate(Alphabet::Protein)
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records[1].sequence, "mkvl");
+}
@@ -0,0 +1,70 @@
+// Tests for format-detecting AnyReader
+use prseq::{AnyReader, AnyRecord, FastqReader, SequenceFormat};
+use std::io::{Cursor, Write};
+
+fn read_all(content: &'static [u8]) -> (Option<SequenceFormat>, Vec<AnyRecord>) {
+    let reader = AnyReader::from_reader_with_capacity(Cursor::new(content), 64).unwrap();
+    let format = reader.format();
+    (format, reader.map(|r| r.unwrap()).collect())
+}
+
+#[test]
+fn test_detects_fasta() {
+    let (format, records) = read_all(b">seq1\nACGT\n>seq2\nGG\nCC\n");
+    assert_eq!(format, Some(SequenceFormat::Fasta));
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].header(), "seq2");
+    assert_eq!(records[1].sequence(), "GGCC");
+    assert_eq!(records[1].quality(), None);
+    assert_eq!(records[1].format(), SequenceFormat::Fasta);
+}
+
+#[test]
+fn test_detects_fastq_after_blank_lines() {
+    let (format, records) = read_all(b"\n  \n@read1\nACGT\n+\nIIII\n");
+    assert_eq!(format, Some(SequenceFormat::Fastq));
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].header(), "read1");
+    assert_eq!(records[0].quality(), Some("IIII"));
+    assert!(matches!(records[0], AnyRecord::Fastq(_)));
+}
+
+#[test]
+fn test_line_numbers_after_many_blank_lines() {
+    // More blank lines than fit in the 64 KiB input buffer, in each line
+    // ending, with a "\r\n" split between the first buffer and the next
+    let mut input = "\r".repeat(40_001) + &"\r\n".repeat(20_000) + &"\n".repeat(10_000);
+    input.push_str("@r1\nACGT\n+r2\nIIII\n");
+    let mut reader = AnyReader::from_reader_with_capacity(Cursor::new(input.clone()), 64).unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    // The same line as a FASTQ reader, which reads the blank lines itself
+    let mut fastq = FastqReader::from_reader_with_capacity(Cursor::new(input), 64).unwrap();
+    let expected = fastq.next().unwrap().unwrap_err();
+    assert_eq!(error.position(), expected.position());
+    assert_eq!(error.position().unwrap().line, 70_004);
+}
+
+#[test]
+fn test_detects_compressed_fastq() {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(b"@read1\nACGT\n+\nIIII\n").unwrap();
+
+    let reader =
+        AnyReader::from_reader_with_capacity(Cursor::new(encoder.finish().unwrap()), 64).unwrap();
+    assert_eq!(reader.format(), Some(SequenceFormat::Fastq));
+    assert_eq!(reader.count(), 1);
+}
+
+#[test]
+fn test_empty_input() {
+    let (format, records) = read_all(b"\n\n");
+    assert_eq!(format, None);
+    assert!(records.is_empty());
+}
+
+#[test]
+fn test_unknown_format() {
+    let result = AnyReader::from_reader_with_capacity(Cursor::new(b"ACGT\n"), 64);
+    let error = result.err().unwrap();
+    assert!(error.to_string().contains("Cannot detect sequence format"));
+}
@@ -0,0 +1,101 @@
+// Tests for converting records to Arrow record batches
+#![cfg(feature = "arrow")]
+use arrow_array::{Array, StringArray, UInt64Array};
+use prseq::arrow::{schema, ArrowExt, RecordBatchBuilder};
+use prseq::{FastaReader, FastqReader, FastqRecord};
+
+fn strings(batch: &arrow_array::RecordBatch, name: &str) -> Vec<Option<String>> {
+    let column = batch
+        .column_by_name(name)
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    column.iter().map(|value| value.map(String::from)).collect()
+}
+
+#[test]
+fn test_schema() {
+    let schema = schema();
+    let names: Vec<&str> = schema
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect();
+    assert_eq!(
+        names,
+        ["id", "description", "sequence", "quality", "length"]
+    );
+    assert!(!schema.field_with_name("id").unwrap().is_nullable());
+    assert!(schema.field_with_name("quality").unwrap().is_nullable());
+}
+
+#[test]
+fn test_fastq_batches() {
+    let input = b"@r1 first\nACGT\n+\nIIII\n@r2\nGG\n+\n#I\n@r3\nT\n+\n5\n";
+    let reader = FastqReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    let batches: Vec<_> = reader.record_batches(2).map(Result::unwrap).collect();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].num_rows(), 2);
+    assert_eq!(batches[1].num_rows(), 1);
+
+    let batch = &batches[0];
+    assert_eq!(batch.schema(), schema());
+    assert_eq!(
+        strings(batch, "id"),
+        [Some("r1".to_string()), Some("r2".to_string())]
+    );
+    assert_eq!(
+        strings(batch, "description"),
+        [Some("first".to_string()), None]
+    );
+    assert_eq!(
+        strings(batch, "quality"),
+        [Some("IIII".to_string()), Some("#I".to_string())]
+    );
+    let lengths = batch
+        .column_by_name("length")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<UInt64Array>()
+        .unwrap();
+    assert_eq!(lengths.values().to_vec(), [4, 2]);
+}
+
+#[test]
+fn test_fasta_batches_have_null_quality() {
+    let reader = FastaReader::from_reader_with_capacity(&b">s1\nACGT\n>s2\nGG\n"[..], 64).unwrap();
+    let batches: Vec<_> = reader.record_batches(10).map(Result::unwrap).collect();
+    assert_eq!(batches.len(), 1);
+    assert_eq!(
+        batches[0].column_by_name("quality").unwrap().null_count(),
+        2
+    );
+    assert_eq!(
+        strings(&batches[0], "sequence"),
+        [Some("ACGT".to_string()), Some("GG".to_string())]
+    );
+}
+
+#[test]
+fn test_batches_pass_on_errors() {
+    let input = b"@r1\nACGT\n+\nIIII\n@r2\nACGT\n+\nII\n";
+    let reader = FastqReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    let mut batches = reader.record_batches(10);
+    assert!(batches.next().unwrap().is_err());
+}
+
+#[test]
+fn test_builder_is_reusable() {
+    let mut builder = RecordBatchBuilder::new();
+    assert!(builder.is_empty());
+    builder.push(&FastqRecord {
+        header: "r1".to_string(),
+        sequence: "AC".to_string(),
+        quality: "II".to_string(),
+    });
+    assert_eq!(builder.len(), 1);
+    assert_eq!(builder.finish().num_rows(), 1);
+    assert!(builder.is_empty());
+    assert_eq!(builder.finish().num_rows(), 0);
+}
@@ -0,0 +1,50 @@
+// Tests for the asynchronous readers
+#![cfg(feature = "async")]
+use prseq::{AsyncFastaReader, AsyncFastqReader};
+use std::io::Write;
+use tokio::io::AsyncWriteExt;
+
+#[tokio::test]
+async fn test_async_fastq_reader_with_gzip() {
+    let content: Vec<u8> = (0..3000)
+        .flat_map(|i| format!("@read{}\nACGT\n+\nIIII\n", i).into_bytes())
+        .collect();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&content).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    // Feed the reader through an in-memory pipe, as from a socket.
+    let (mut writer, reader) = tokio::io::duplex(1024);
+    tokio::spawn(async move {
+        writer.write_all(&compressed).await.unwrap();
+    });
+
+    let mut reader = AsyncFastqReader::from_reader(reader).await.unwrap();
+    let mut count = 0;
+    while let Some(record) = reader.next_record().await {
+        assert_eq!(record.unwrap().header, format!("read{}", count));
+        count += 1;
+    }
+    assert_eq!(count, 3000);
+}
+
+#[tokio::test]
+async fn test_async_fasta_reader_from_file() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(b">seq1\nACGT\nGG\n>seq2\nTT\n").unwrap();
+    file.flush().unwrap();
+
+    let mut reader = AsyncFastaReader::from_file(file.path()).await.unwrap();
+    assert_eq!(
+        reader.next_record().await.unwrap().unwrap().sequence,
+        "ACGTGG"
+    );
+    assert_eq!(reader.next_record().await.unwrap().unwrap().header, "seq2");
+    assert!(reader.next_record().await.is_none());
+}
+
+#[tokio::test]
+async fn test_async_reader_reports_parse_errors() {
+    let mut reader = AsyncFastaReader::from_reader(&b"ACGT\n"[..]).await.unwrap();
+    assert!(reader.next_record().await.unwrap().is_err());
+}
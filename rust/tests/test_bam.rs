@@ -0,0 +1,212 @@
+// Tests for reading reads from BAM files
+#![cfg(feature = "bam")]
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use prseq::bam::BamReader;
+use prseq::sam::{FLAG_PAIRED, FLAG_READ1, FLAG_READ2, FLAG_REVERSE, FLAG_SECONDARY};
+use std::io::{Cursor, Write};
+
+/// The empty block that bgzip writes at the end of every file
+const EOF_BLOCK: [u8; 28] = [
+    0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43, 0x02, 0x00,
+    0x1b, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Compress `data` into BGZF blocks holding at most `block_size` bytes each
+fn bgzf_compress(data: &[u8], block_size: usize) -> Vec<u8> {
+    let mut output = Vec::new();
+    for chunk in data.chunks(block_size) {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(chunk).unwrap();
+        let deflated = encoder.finish().unwrap();
+        let mut crc = Crc::new();
+        crc.update(chunk);
+
+        let bsize = (deflated.len() + 25) as u16;
+        output.extend_from_slice(&[
+            0x1f, 0x8b, 0x08, 0x04, 0, 0, 0, 0, 0, 0xff, 6, 0, b'B', b'C', 2, 0,
+        ]);
+        output.extend_from_slice(&bsize.to_le_bytes());
+        output.extend_from_slice(&deflated);
+        output.extend_from_slice(&crc.sum().to_le_bytes());
+        output.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+    }
+    output.extend_from_slice(&EOF_BLOCK);
+    output
+}
+
+/// Encode an unaligned BAM record with a read group tag. A quality of
+/// None is stored as missing.
+fn encode_record(name: &str, flag: u16, sequence: &str, quality: Option<&[u8]>) -> Vec<u8> {
+    let mut record = Vec::new();
+    record.extend_from_slice(&(-1i32).to_le_bytes()); // refID
+    record.extend_from_slice(&(-1i32).to_le_bytes()); // pos
+    record.push(name.len() as u8 + 1);
+    record.push(0); // mapq
+    record.extend_from_slice(&4680u16.to_le_bytes()); // bin
+    record.extend_from_slice(&0u16.to_le_bytes()); // n_cigar_op
+    record.extend_from_slice(&flag.to_le_bytes());
+    record.extend_from_slice(&(sequence.len() as u32).to_le_bytes());
+    record.extend_from_slice(&(-1i32).to_le_bytes()); // next refID
+    record.extend_from_slice(&(-1i32).to_le_bytes()); // next pos
+    record.extend_from_slice(&0i32.to_le_bytes()); // tlen
+    record.extend_from_slice(name.as_bytes());
+    record.push(0);
+    let codes: Vec<u8> = sequence
+        .bytes()
+        .map(|base| b"=ACMGRSVTWYHKDBN".iter().position(|&b| b == base).unwrap() as u8)
+        .collect();
+    for pair in codes.chunks(2) {
+        record.push(pair[0] << 4 | pair.get(1).copied().unwrap_or(0));
+    }
+    match quality {
+        Some(scores) => record.extend_from_slice(scores),
+        None => record.extend(std::iter::repeat_n(0xff, sequence.len())),
+    }
+    // An auxiliary tag, which is skipped
+    record.extend_from_slice(b"RGZgroup1\0");
+
+    let mut block = (record.len() as u32).to_le_bytes().to_vec();
+    block.extend(record);
+    block
+}
+
+/// Encode an unaligned BAM file with the given records
+fn encode_bam(records: &[Vec<u8>]) -> Vec<u8> {
+    let text = b"@HD\tVN:1.6\tSO:unsorted\n@RG\tID:group1\n";
+    let mut data = b"BAM\x01".to_vec();
+    data.extend_from_slice(&(text.len() as u32).to_le_bytes());
+    data.extend_from_slice(text);
+    data.extend_from_slice(&0u32.to_le_bytes());
+    for record in records {
+        data.extend_from_slice(record);
+    }
+    bgzf_compress(&data, 50)
+}
+
+fn reader(records: &[Vec<u8>]) -> BamReader<Cursor<Vec<u8>>> {
+    BamReader::new(Cursor::new(encode_bam(records))).unwrap()
+}
+
+#[test]
+fn test_bam_header() {
+    let bam = reader(&[]);
+    assert_eq!(
+        bam.header_text(),
+        "@HD\tVN:1.6\tSO:unsorted\n@RG\tID:group1\n"
+    );
+    assert!(bam.references().is_empty());
+}
+
+#[test]
+fn test_bam_reads() {
+    let records = reader(&[
+        encode_record("r1", 4, "ACGTN", Some(&[40, 30, 20, 10, 0])),
+        encode_record("r2", 4, "GGC", None),
+    ])
+    .collect::<prseq::error::Result<Vec<_>>>()
+    .unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].header, "r1");
+    assert_eq!(records[0].sequence, "ACGTN");
+    assert_eq!(records[0].quality, "I?5+!");
+    assert_eq!(records[1].sequence, "GGC");
+    assert_eq!(records[1].quality, "\"\"\"");
+}
+
+#[test]
+fn test_bam_reverse_and_secondary() {
+    let input = [
+        encode_record("r1", FLAG_REVERSE, "AACG", Some(&[10, 20, 30, 40])),
+        encode_record("r1", FLAG_SECONDARY, "AACG", None),
+    ];
+    let records: Vec<_> = reader(&input).map(Result::unwrap).collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].sequence, "CGTT");
+    assert_eq!(records[0].quality, "I?5+");
+
+    let records: Vec<_> = reader(&input)
+        .include_secondary(true)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records.len(), 2);
+}
+
+#[test]
+fn test_bam_mate_suffix() {
+    let input = [
+        encode_record("pair", FLAG_PAIRED | FLAG_READ1, "ACG", None),
+        encode_record("pair", FLAG_PAIRED | FLAG_READ2, "TTG", None),
+    ];
+    let names: Vec<String> = reader(&input)
+        .mate_suffix(true)
+        .map(|record| record.unwrap().header)
+        .collect();
+    assert_eq!(names, ["pair/1", "pair/2"]);
+}
+
+#[test]
+fn test_bam_sam_records() {
+    let mut bam = reader(&[encode_record("r1", FLAG_SECONDARY, "ACGT", None)]);
+    let record = bam.next_sam_record().unwrap().unwrap();
+    assert_eq!(record.name, "r1");
+    assert!(record.is_secondary());
+    assert_eq!(record.quality, "");
+    assert!(bam.next_sam_record().unwrap().is_none());
+}
+
+#[test]
+fn test_bam_bad_magic() {
+    let data = bgzf_compress(b"SAM\x01\0\0\0\0", 50);
+    assert!(BamReader::new(Cursor::new(data)).is_err());
+}
+
+#[test]
+fn test_bam_truncated_record() {
+    let mut record = encode_record("r1", 4, "ACGT", None);
+    record.truncate(record.len() - 3);
+    let mut bam = reader(&[record]);
+    let error = bam.next().unwrap().unwrap_err();
+    assert!(error.to_string().contains("BAM record 1 is truncated"));
+}
+
+#[test]
+fn test_bam_lengths_past_end_of_input() {
+    // A header claiming 4 GiB of text, with none following
+    let mut data = b"BAM\x01".to_vec();
+    data.extend_from_slice(&u32::MAX.to_le_bytes());
+    let error = BamReader::new(Cursor::new(bgzf_compress(&data, 50)))
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    // A reference name of 4 GiB
+    let mut data = b"BAM\x01".to_vec();
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.extend_from_slice(&1u32.to_le_bytes());
+    data.extend_from_slice(&u32::MAX.to_le_bytes());
+    data.extend_from_slice(b"chr1\0");
+    let error = BamReader::new(Cursor::new(bgzf_compress(&data, 50)))
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    // A record of 4 GiB
+    let mut record = encode_record("r1", 4, "ACGT", None);
+    record[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    let mut bam = reader(&[record]);
+    let error = bam.next().unwrap().unwrap_err();
+    assert!(error.to_string().contains("BAM record 1 is truncated"));
+}
+
+#[test]
+fn test_bam_from_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reads.bam");
+    std::fs::write(&path, encode_bam(&[encode_record("r1", 4, "ACGT", None)])).unwrap();
+    let records: Vec<_> = BamReader::from_file(&path)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records[0].sequence, "ACGT");
+}
@@ -0,0 +1,258 @@
+// Tests for BGZF reading and virtual offsets
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use prseq::bgzf::{is_bgzf, BgzfReader, GziIndex, VirtualOffset};
+use prseq::{FastaReader, FastqReader};
+use std::io::{Cursor, Read, Write};
+
+/// The empty block that bgzip writes at the end of every file
+const EOF_BLOCK: [u8; 28] = [
+    0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43, 0x02, 0x00,
+    0x1b, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Compress `data` into BGZF blocks holding at most `block_size` bytes each
+fn bgzf_compress(data: &[u8], block_size: usize) -> Vec<u8> {
+    let mut output = Vec::new();
+    for chunk in data.chunks(block_size) {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(chunk).unwrap();
+        let deflated = encoder.finish().unwrap();
+        let mut crc = Crc::new();
+        crc.update(chunk);
+
+        let bsize = (deflated.len() + 25) as u16;
+        output.extend_from_slice(&[
+            0x1f, 0x8b, 0x08, 0x04, 0, 0, 0, 0, 0, 0xff, 6, 0, b'B', b'C', 2, 0,
+        ]);
+        output.extend_from_slice(&bsize.to_le_bytes());
+        output.extend_from_slice(&deflated);
+        output.extend_from_slice(&crc.sum().to_le_bytes());
+        output.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+    }
+    output.extend_from_slice(&EOF_BLOCK);
+    output
+}
+
+const FASTA: &[u8] =
+    b">seq1 first\nATCGATCG\nGCTA\n>seq2 second\nGGCCGGCC\n>seq3 third\nTTTTAAAA\n";
+
+#[test]
+fn test_virtual_offset_parts() {
+    let offset = VirtualOffset::new(12345, 678);
+    assert_eq!(offset.compressed(), 12345);
+    assert_eq!(offset.uncompressed(), 678);
+    assert_eq!(u64::from(offset), (12345 << 16) | 678);
+    assert_eq!(offset.to_string(), "12345:678");
+}
+
+#[test]
+fn test_is_bgzf() {
+    let compressed = bgzf_compress(FASTA, 16);
+    assert!(is_bgzf(&compressed));
+    assert!(!is_bgzf(b">seq1\nACGT\n"));
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(FASTA).unwrap();
+    assert!(!is_bgzf(&encoder.finish().unwrap()));
+}
+
+#[test]
+fn test_bgzf_reader_decompresses_all_blocks() {
+    let compressed = bgzf_compress(FASTA, 7);
+    let mut reader = BgzfReader::new(Cursor::new(compressed));
+    let mut decompressed = Vec::new();
+    reader.read_to_end(&mut decompressed).unwrap();
+    assert_eq!(decompressed, FASTA);
+}
+
+#[test]
+fn test_bgzf_is_read_by_automatic_detection() {
+    // BGZF is valid multi-member gzip, so the ordinary readers handle it too.
+    let compressed = bgzf_compress(FASTA, 10);
+    let records: Vec<_> = FastaReader::from_reader_with_capacity(Cursor::new(compressed), 1024)
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].sequence, "ATCGATCGGCTA");
+}
+
+#[test]
+fn test_fasta_virtual_offsets_allow_seeking_back() {
+    let compressed = bgzf_compress(FASTA, 10);
+
+    let mut reader = FastaReader::from_bgzf(BgzfReader::new(Cursor::new(compressed.clone())), 64);
+    assert_eq!(reader.virtual_offset(), None);
+    let mut located = Vec::new();
+    while let Some(record) = reader.next() {
+        located.push((reader.virtual_offset().unwrap(), record.unwrap()));
+    }
+    assert_eq!(located.len(), 3);
+    assert_eq!(located[0].0, VirtualOffset::new(0, 0));
+
+    // Seek to each record in turn (in reverse) and re-read it.
+    for (offset, record) in located.iter().rev() {
+        let mut bgzf = BgzfReader::new(Cursor::new(compressed.clone()));
+        bgzf.seek_virtual(*offset).unwrap();
+        let mut reader = FastaReader::from_bgzf(bgzf, 64);
+        assert_eq!(&reader.next().unwrap().unwrap(), record);
+        assert_eq!(reader.virtual_offset(), Some(*offset));
+    }
+}
+
+#[test]
+fn test_fastq_virtual_offsets() {
+    let content = b"@read1\nACGTACGT\n+\nIIIIIIII\n@read2\nTTGG\n+\nJJJJ\n";
+    let compressed = bgzf_compress(content, 9);
+
+    let mut reader = FastqReader::from_bgzf(BgzfReader::new(Cursor::new(compressed.clone())), 64);
+    reader.next().unwrap().unwrap();
+    reader.next().unwrap().unwrap();
+    let offset = reader.virtual_offset().unwrap();
+    assert!(reader.next().is_none());
+
+    let mut bgzf = BgzfReader::new(Cursor::new(compressed));
+    bgzf.seek_virtual(offset).unwrap();
+    let record = FastqReader::from_bgzf(bgzf, 64).next().unwrap().unwrap();
+    assert_eq!(record.header, "read2");
+    assert_eq!(record.quality, "JJJJ");
+}
+
+#[test]
+fn test_bgzf_corrupt_crc() {
+    let mut compressed = bgzf_compress(FASTA, 1024);
+    // The CRC32 of the first (and only data) block precedes its 4-byte ISIZE.
+    let crc_position = compressed.len() - EOF_BLOCK.len() - 8;
+    compressed[crc_position] ^= 0xff;
+
+    let mut reader = BgzfReader::new(Cursor::new(compressed));
+    let mut decompressed = Vec::new();
+    let error = reader.read_to_end(&mut decompressed).unwrap_err();
+    assert!(error.to_string().contains("CRC32 mismatch"));
+}
+
+#[test]
+fn test_parallel_bgzf_reader() {
+    use prseq::bgzf::ParallelBgzfReader;
+
+    let data: Vec<u8> = (0..5000)
+        .flat_map(|i| format!(">seq{}\nACGTACGTAC\n", i).into_bytes())
+        .collect();
+    let compressed = bgzf_compress(&data, 1000);
+
+    let mut reader = ParallelBgzfReader::new(Cursor::new(compressed), 4);
+    let mut decompressed = Vec::new();
+    reader.read_to_end(&mut decompressed).unwrap();
+    assert_eq!(decompressed, data);
+}
+
+#[test]
+fn test_parallel_bgzf_reader_reports_errors() {
+    use prseq::bgzf::ParallelBgzfReader;
+
+    let mut compressed = bgzf_compress(FASTA, 1024);
+    let crc_position = compressed.len() - EOF_BLOCK.len() - 8;
+    compressed[crc_position] ^= 0xff;
+
+    let mut reader = ParallelBgzfReader::new(Cursor::new(compressed), 2);
+    let mut decompressed = Vec::new();
+    assert!(reader.read_to_end(&mut decompressed).is_err());
+}
+
+#[test]
+fn test_readers_with_threads() {
+    let fastq: Vec<u8> = (0..2000)
+        .flat_map(|i| format!("@read{}\nACGTACGT\n+\nIIIIIIII\n", i).into_bytes())
+        .collect();
+
+    let mut gzip = flate2::write::GzEncoder::new(Vec::new(), Compression::default());
+    gzip.write_all(&fastq).unwrap();
+    let inputs = [bgzf_compress(&fastq, 500), gzip.finish().unwrap(), fastq];
+
+    for input in inputs {
+        for threads in [0, 1, 4] {
+            let records: Vec<_> =
+                FastqReader::from_reader_with_threads(Cursor::new(input.clone()), 64, threads)
+                    .unwrap()
+                    .map(|r| r.unwrap())
+                    .collect();
+            assert_eq!(records.len(), 2000);
+            assert_eq!(records[1999].header, "read1999");
+        }
+    }
+
+    let compressed = bgzf_compress(FASTA, 10);
+    let records: Vec<_> = FastaReader::from_reader_with_threads(Cursor::new(compressed), 64, 3)
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[2].sequence, "TTTTAAAA");
+}
+
+#[test]
+fn test_truncated_bgzf() {
+    let compressed = bgzf_compress(FASTA, 20);
+    let cut = compressed.len() - EOF_BLOCK.len() - 5;
+
+    let mut reader =
+        FastaReader::from_bgzf(BgzfReader::new(Cursor::new(compressed[..cut].to_vec())), 64);
+    let error = reader.find_map(|r| r.err()).unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+    assert!(error.to_string().contains("Truncated BGZF stream"));
+    assert!(error
+        .to_string()
+        .contains(&format!("ended at offset {}", cut)));
+}
+
+#[test]
+fn test_builder_detects_bgzf_and_reports_virtual_offsets() {
+    let compressed = bgzf_compress(FASTA, 10);
+    let mut reader = prseq::ReaderBuilder::new()
+        .reader(Cursor::new(compressed))
+        .build_fasta()
+        .unwrap();
+
+    reader.next().unwrap().unwrap();
+    assert_eq!(reader.virtual_offset(), Some(VirtualOffset::new(0, 0)));
+    reader.next().unwrap().unwrap();
+    assert!(reader.virtual_offset().unwrap() > VirtualOffset::new(0, 0));
+}
+
+#[test]
+fn test_gzi_index() {
+    let data: Vec<u8> = (0..100u8).collect();
+    let compressed = bgzf_compress(&data, 30);
+    let gzi = GziIndex::build(Cursor::new(compressed.clone())).unwrap();
+
+    let mut written = Vec::new();
+    gzi.write(&mut written).unwrap();
+    assert_eq!(GziIndex::from_reader(Cursor::new(written)).unwrap(), gzi);
+
+    for position in [0, 29, 30, 75, 99] {
+        let mut reader = BgzfReader::new(Cursor::new(compressed.clone()));
+        reader.seek_virtual(gzi.virtual_offset(position)).unwrap();
+        let mut byte = [0];
+        reader.read_exact(&mut byte).unwrap();
+        assert_eq!(byte[0], position as u8);
+    }
+}
+
+#[test]
+fn test_indexed_fasta_reader_with_bgzf() {
+    let fasta: Vec<u8> = (0..50)
+        .flat_map(|i| format!(">seq{}\nACGTACGTAC\nGGGGGCCCCC\nTTA\n", i).into_bytes())
+        .collect();
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&bgzf_compress(&fasta, 100)).unwrap();
+    file.flush().unwrap();
+
+    let mut reader = prseq::IndexedFastaReader::from_file(file.path()).unwrap();
+    assert_eq!(reader.index().len(), 50);
+    assert_eq!(reader.fetch("seq37", 8, 21).unwrap(), "ACGGGGGCCCCCT");
+    assert_eq!(
+        reader.fetch_sequence("seq49").unwrap(),
+        "ACGTACGTACGGGGGCCCCCTTA"
+    );
+}
@@ -0,0 +1,118 @@
+// Tests for ReaderBuilder
+use flate2::write::GzEncoder;
+use prseq::{Compression, ReaderBuilder};
+use std::io::{Cursor, Write};
+use tempfile::NamedTempFile;
+
+const FASTA: &[u8] = b">seq1 one\nACGT\nTTGG\n>seq2 two\nCCCC\n";
+const FASTQ: &[u8] = b"@read1\nACGT\n+\nIIII\n@read2\nGG\n+\nJJ\n";
+
+fn gzip(content: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(content).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn test_build_fasta_from_path() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(&gzip(FASTA)).unwrap();
+
+    let records: Vec<_> = ReaderBuilder::new()
+        .path(file.path())
+        .build_fasta()
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].header, "seq1 one");
+    assert_eq!(records[0].sequence, "ACGTTTGG");
+}
+
+#[test]
+fn test_build_fastq_with_explicit_compression() {
+    let records: Vec<_> = ReaderBuilder::new()
+        .reader(Cursor::new(gzip(FASTQ)))
+        .compression(Compression::Gzip)
+        .buffer_capacity(1 << 20)
+        .sequence_size_hint(150)
+        .build_fastq()
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].quality, "JJ");
+}
+
+#[test]
+fn test_compression_override_is_respected() {
+    // Forcing no decompression on gzip input means the parser sees the raw
+    // compressed bytes.
+    let mut reader = ReaderBuilder::new()
+        .reader(Cursor::new(gzip(FASTA)))
+        .compression(Compression::None)
+        .build_fasta()
+        .unwrap();
+    assert!(reader.next().unwrap().is_err());
+
+    // Forcing gzip on uncompressed input is an error too.
+    let mut reader = ReaderBuilder::new()
+        .reader(Cursor::new(FASTA))
+        .compression(Compression::Gzip)
+        .build_fasta()
+        .unwrap();
+    assert!(reader.next().unwrap().is_err());
+}
+
+#[test]
+fn test_tiny_buffer_capacity() {
+    let records: Vec<_> = ReaderBuilder::new()
+        .reader(Cursor::new(FASTQ))
+        .buffer_capacity(1)
+        .build_fastq()
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].sequence, "ACGT");
+}
+
+#[test]
+fn test_build_with_threads() {
+    let records: Vec<_> = ReaderBuilder::new()
+        .reader(Cursor::new(gzip(FASTA)))
+        .threads(2)
+        .build_fasta()
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].sequence, "CCCC");
+}
+
+#[test]
+fn test_lines_spanning_buffer_refills() {
+    // With a tiny buffer, almost every line straddles a refill.
+    let content: Vec<u8> = (0..50)
+        .flat_map(|i| format!("@read{} extra\r\nACGTACGTAC\r\n+\r\nIIIIIJJJJJ\r\n", i).into_bytes())
+        .collect();
+    for capacity in [1, 3, 7, 64] {
+        let mut reader = ReaderBuilder::new()
+            .reader(Cursor::new(content.clone()))
+            .buffer_capacity(capacity)
+            .build_fastq()
+            .unwrap();
+        let mut count = 0;
+        while let Some(record) = reader.next_ref() {
+            let record = record.unwrap();
+            assert_eq!(record.header(), format!("read{} extra", count).as_bytes());
+            assert_eq!(record.sequence(), b"ACGTACGTAC");
+            assert_eq!(record.quality(), b"IIIIIJJJJJ");
+            count += 1;
+        }
+        assert_eq!(count, 50);
+        assert_eq!(reader.tell(), content.len() as u64);
+    }
+}
@@ -0,0 +1,100 @@
+// Tests for byte-oriented records
+use prseq::{FastaReader, FastaRecord, FastaRecordBytes, FastqReader, FastqRecordBytes};
+use std::io::Cursor;
+
+// A Latin-1 header ("é" is 0xE9), which is not valid UTF-8.
+const LATIN1_FASTA: &[u8] = b">seq1 caf\xe9\nACGT\nGG\n>seq2\nTTTT\n";
+
+#[test]
+fn test_fasta_byte_records_accept_non_utf8() {
+    let records: Vec<_> = FastaReader::from_reader_with_capacity(Cursor::new(LATIN1_FASTA), 64)
+        .unwrap()
+        .byte_records()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].header, b"seq1 caf\xe9");
+    assert_eq!(records[0].id(), b"seq1");
+    assert_eq!(records[0].description(), Some(&b"caf\xe9"[..]));
+    assert_eq!(records[0].sequence, b"ACGTGG");
+
+    assert!(records[0].clone().into_record().is_err());
+    assert_eq!(records[0].to_record_lossy().header, "seq1 caf\u{fffd}");
+    assert_eq!(
+        FastaRecord::try_from(records[1].clone()).unwrap(),
+        FastaRecord {
+            header: "seq2".to_string(),
+            sequence: "TTTT".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_string_reader_reports_non_utf8() {
+    let mut reader = FastaReader::from_reader_with_capacity(Cursor::new(LATIN1_FASTA), 64).unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(error.to_string().contains("not valid UTF-8"));
+    // The following record is still readable.
+    assert_eq!(reader.next().unwrap().unwrap().header, "seq2");
+}
+
+#[test]
+fn test_fastq_next_bytes() {
+    let content = b"@read1 \xff\nACGT\n+\nII\xffI\n";
+    let mut reader = FastqReader::from_reader_with_capacity(Cursor::new(content), 64).unwrap();
+    let record = reader.next_bytes().unwrap().unwrap();
+    assert_eq!(
+        record,
+        FastqRecordBytes {
+            header: b"read1 \xff".to_vec(),
+            sequence: b"ACGT".to_vec(),
+            quality: b"II\xffI".to_vec(),
+        }
+    );
+    assert!(reader.next_bytes().is_none());
+}
+
+#[test]
+fn test_conversion_round_trip() {
+    let record = FastaRecord {
+        header: "seq1 description".to_string(),
+        sequence: "ACGT".to_string(),
+    };
+    let bytes = FastaRecordBytes::from(record.clone());
+    assert_eq!(bytes.into_record().unwrap(), record);
+}
+
+#[test]
+fn test_fasta_next_ref_reuses_buffers() {
+    let content = b">seq1\nACGT\nGG\n>seq2 second\nTTTT\n>seq3\n";
+    let mut reader = FastaReader::from_reader_with_capacity(Cursor::new(content), 64).unwrap();
+
+    let mut owned = Vec::new();
+    let mut total_length = 0;
+    while let Some(record) = reader.next_ref() {
+        let record = record.unwrap();
+        total_length += record.sequence().len();
+        owned.push(record.to_owned());
+    }
+    assert_eq!(total_length, 10);
+    assert_eq!(owned[1].header, b"seq2 second");
+    assert_eq!(owned[2].sequence, b"");
+}
+
+#[test]
+fn test_fastq_next_ref() {
+    let content = b"@read1\nACGT\n+\nIIII\n@read2\nGG\n+read2\nJJ\n";
+    let mut reader = FastqReader::from_reader_with_capacity(Cursor::new(content), 64).unwrap();
+
+    let record = reader.next_ref().unwrap().unwrap();
+    assert_eq!(record.header(), b"read1");
+    assert_eq!(record.id(), b"read1");
+    assert_eq!(record.description(), None);
+    assert_eq!(record.quality(), b"IIII");
+
+    let record = reader.next_ref().unwrap().unwrap().to_record().unwrap();
+    assert_eq!(record.header, "read2");
+    assert_eq!(record.sequence, "GG");
+    assert!(reader.next_ref().is_none());
+}
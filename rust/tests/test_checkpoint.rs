@@ -0,0 +1,208 @@
+// Tests for saving and resuming reader positions
+use flate2::write::{DeflateEncoder, GzEncoder};
+use flate2::Crc;
+use prseq::checkpoint::Checkpoint;
+use prseq::{FastaReader, FastqReader, PrseqError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const FASTA: &[u8] = b">a first\nACGT\nAC\n\n>b\nGGG\n>c\nTT\n>d\nA\n";
+const FASTQ: &[u8] = b"@r1\nACGT\n+\nIIII\n@r2\nGG\n+\nII\n@r3\nTTT\n+\nIII\n@r4\nC\n+\nI\n";
+
+/// The empty block that bgzip writes at the end of every file
+const EOF_BLOCK: [u8; 28] = [
+    0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43, 0x02, 0x00,
+    0x1b, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Compress `data` into BGZF blocks holding at most `block_size` bytes each
+fn bgzf_compress(data: &[u8], block_size: usize) -> Vec<u8> {
+    let mut output = Vec::new();
+    for chunk in data.chunks(block_size) {
+        let mut encoder = DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(chunk).unwrap();
+        let deflated = encoder.finish().unwrap();
+        let mut crc = Crc::new();
+        crc.update(chunk);
+        let bsize = (deflated.len() + 25) as u16;
+        output.extend_from_slice(&[
+            0x1f, 0x8b, 0x08, 0x04, 0, 0, 0, 0, 0, 0xff, 6, 0, b'B', b'C', 2, 0,
+        ]);
+        output.extend_from_slice(&bsize.to_le_bytes());
+        output.extend_from_slice(&deflated);
+        output.extend_from_slice(&crc.sum().to_le_bytes());
+        output.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+    }
+    output.extend_from_slice(&EOF_BLOCK);
+    output
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn write(dir: &TempDir, name: &str, data: &[u8]) -> PathBuf {
+    let path = dir.path().join(name);
+    std::fs::write(&path, data).unwrap();
+    path
+}
+
+fn fasta_headers(reader: FastaReader) -> Vec<String> {
+    reader.map(|record| record.unwrap().header).collect()
+}
+
+fn fastq_headers(reader: FastqReader) -> Vec<String> {
+    reader.map(|record| record.unwrap().header).collect()
+}
+
+/// Read `skip` records from `path`, then take a checkpoint
+fn fasta_checkpoint(path: &Path, skip: usize) -> Checkpoint {
+    let mut reader = FastaReader::from_file(path).unwrap();
+    for _ in 0..skip {
+        reader.next().unwrap().unwrap();
+    }
+    reader.checkpoint()
+}
+
+#[test]
+fn test_resume_uncompressed_fasta() {
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "seqs.fasta", FASTA);
+    let checkpoint = fasta_checkpoint(&path, 2);
+    assert_eq!(checkpoint.offset(), 25);
+    assert_eq!(checkpoint.records(), 2);
+    assert_eq!(checkpoint.virtual_offset(), None);
+
+    let reader = FastaReader::resume_from(&path, &checkpoint).unwrap();
+    assert_eq!(fasta_headers(reader), ["c", "d"]);
+}
+
+#[test]
+fn test_resume_carries_on_counts() {
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "seqs.fasta", FASTA);
+    let checkpoint = fasta_checkpoint(&path, 1);
+
+    let mut reader = FastaReader::resume_from(&path, &checkpoint).unwrap();
+    assert_eq!(reader.by_ref().count(), 3);
+    let stats = reader.stats();
+    assert_eq!((stats.records, stats.bases), (4, 12));
+    assert_eq!(stats.bytes, FASTA.len() as u64);
+}
+
+#[test]
+fn test_resumed_errors_have_original_positions() {
+    let dir = TempDir::new().unwrap();
+    let path = write(
+        &dir,
+        "reads.fastq",
+        b"@r1\nACGT\n+\nIIII\n@r2\nGG\n+\nII\nr3\n",
+    );
+    let mut reader = FastqReader::from_file(&path).unwrap();
+    reader.next().unwrap().unwrap();
+    let checkpoint = reader.checkpoint();
+
+    let mut reader = FastqReader::resume_from(&path, &checkpoint).unwrap();
+    assert_eq!(reader.next().unwrap().unwrap().header, "r2");
+    let error = reader.next().unwrap().unwrap_err();
+    let position = error.position().unwrap();
+    assert_eq!((position.line, position.record), (9, 3));
+}
+
+#[test]
+fn test_resume_bgzf_fastq_by_virtual_offset() {
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "reads.fastq.gz", &bgzf_compress(FASTQ, 20));
+    let mut reader = FastqReader::from_bgzf_file(&path).unwrap();
+    reader.next().unwrap().unwrap();
+    reader.next().unwrap().unwrap();
+    let checkpoint = reader.checkpoint();
+    assert!(checkpoint.virtual_offset().is_some());
+
+    let reader = FastqReader::resume_from(&path, &checkpoint).unwrap();
+    assert_eq!(fastq_headers(reader), ["r3", "r4"]);
+}
+
+#[test]
+fn test_resume_compressed_without_virtual_offsets() {
+    let dir = TempDir::new().unwrap();
+    for (name, data) in [
+        ("bgzf.fastq.gz", bgzf_compress(FASTQ, 20)),
+        ("gzip.fastq.gz", gzip(FASTQ)),
+    ] {
+        let path = write(&dir, name, &data);
+        let mut reader = FastqReader::from_file(&path).unwrap();
+        reader.next().unwrap().unwrap();
+        let checkpoint = reader.checkpoint();
+        assert_eq!(checkpoint.virtual_offset(), None);
+
+        let reader = FastqReader::resume_from(&path, &checkpoint).unwrap();
+        assert_eq!(fastq_headers(reader), ["r2", "r3", "r4"]);
+    }
+}
+
+#[test]
+fn test_checkpoint_after_peek() {
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "seqs.fasta", FASTA);
+    let mut reader = FastaReader::from_file(&path).unwrap();
+    reader.next().unwrap().unwrap();
+    reader.peek();
+    let checkpoint = reader.checkpoint();
+    assert_eq!(checkpoint.records(), 1);
+
+    let mut resumed = FastaReader::resume_from(&path, &checkpoint).unwrap();
+    assert_eq!(resumed.next().unwrap().unwrap().header, "b");
+    assert_eq!(resumed.stats().records, 2);
+}
+
+#[test]
+fn test_checkpoint_at_end() {
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "seqs.fasta", FASTA);
+    let checkpoint = fasta_checkpoint(&path, 4);
+    let mut reader = FastaReader::resume_from(&path, &checkpoint).unwrap();
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_checkpoint_text_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "seqs.fasta", FASTA);
+    let checkpoint = fasta_checkpoint(&path, 1);
+    let text = checkpoint.to_string();
+    assert_eq!(text.parse::<Checkpoint>().unwrap(), checkpoint);
+
+    assert!("".parse::<Checkpoint>().is_err());
+    assert!("2:0:-:0:0:0:0:0".parse::<Checkpoint>().is_err());
+    assert!("1:x:-:0:0:0:0:0".parse::<Checkpoint>().is_err());
+    assert!("1:0:-:0:0:0:0".parse::<Checkpoint>().is_err());
+}
+
+#[test]
+fn test_virtual_offset_checkpoint_needs_bgzf() {
+    let dir = TempDir::new().unwrap();
+    let bgzf = write(&dir, "reads.fastq.gz", &bgzf_compress(FASTQ, 20));
+    let plain = write(&dir, "reads.fastq", FASTQ);
+    let mut reader = FastqReader::from_bgzf_file(&bgzf).unwrap();
+    reader.next().unwrap().unwrap();
+    let checkpoint = reader.checkpoint();
+
+    let error = FastqReader::resume_from(&plain, &checkpoint).err().unwrap();
+    assert!(matches!(error, PrseqError::Io(_)));
+}
+
+#[test]
+fn test_checkpoint_past_end_of_compressed_file() {
+    let dir = TempDir::new().unwrap();
+    let long = write(&dir, "long.fastq", FASTQ);
+    let short = write(&dir, "short.fastq.gz", &gzip(&FASTQ[..16]));
+    let mut reader = FastqReader::from_file(&long).unwrap();
+    reader.next().unwrap().unwrap();
+    reader.next().unwrap().unwrap();
+    let checkpoint = reader.checkpoint();
+    assert!(FastqReader::resume_from(&short, &checkpoint).is_err());
+}
@@ -0,0 +1,78 @@
+// Tests for sequence checksums and file digests
+#![cfg(feature = "checksum")]
+use prseq::checksum::{file_digest, normalize_sequence, sequence_md5, FileDigest};
+use prseq::{FastaReader, FastaRecord, FastqRecord};
+use std::io::Write;
+
+#[test]
+fn test_record_checksums() {
+    let record = FastaRecord {
+        header: "s1".to_string(),
+        sequence: "ACGT".to_string(),
+    };
+    assert_eq!(record.md5(), "f1f8f4bf413b16ad135722aa4591043e");
+    assert_eq!(
+        record.sha256(),
+        "1dff3e84fe7877e0673b69bbddcf40124e396e3f9943dd890c91b6a09adb9af0"
+    );
+    let record = FastqRecord {
+        header: "r1".to_string(),
+        sequence: "a.c-gt".to_string(),
+        quality: "IIIIII".to_string(),
+    };
+    assert_eq!(record.md5(), "f1f8f4bf413b16ad135722aa4591043e");
+    assert_eq!(sequence_md5(b"ACGT"), record.md5());
+}
+
+#[test]
+fn test_normalize_sequence() {
+    assert_eq!(normalize_sequence(b"ac-G.t N\n"), b"ACGTN");
+}
+
+#[test]
+fn test_file_digest_ignores_names_order_and_case() {
+    let first = FastaReader::from_string(">a\nACGT\n>b\nGGA\n").unwrap();
+    let second = FastaReader::from_string(">y x\nGGA\n>z\nac\n-gt\n").unwrap();
+    let first = FileDigest::collect(first).unwrap();
+    let second = FileDigest::collect(second).unwrap();
+    assert_eq!(first.digest(), second.digest());
+    assert_eq!((first.records(), first.total_bases()), (2, 7));
+
+    let third = FastaReader::from_string(">a\nACGT\n>b\nGGT\n").unwrap();
+    assert_ne!(FileDigest::collect(third).unwrap().digest(), first.digest());
+
+    // A repeated sequence changes the digest.
+    let mut repeated = first.clone();
+    repeated.add(b"ACGT");
+    assert_ne!(repeated.digest(), first.digest());
+}
+
+#[test]
+fn test_file_digest_both_strands() {
+    let mut forward = FileDigest::new().both_strands(true);
+    forward.add(b"AACG");
+    let mut reverse = FileDigest::new().both_strands(true);
+    reverse.add(b"CGTT");
+    assert_eq!(forward.digest(), reverse.digest());
+
+    let mut single = FileDigest::new();
+    single.add(b"CGTT");
+    assert_ne!(single.digest(), reverse.digest());
+}
+
+#[test]
+fn test_file_digest_of_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let fasta = dir.path().join("seqs.fasta");
+    std::fs::write(&fasta, ">a\nACGT\n>b\nGG\n").unwrap();
+    let fastq = dir.path().join("seqs.fastq.gz");
+    let mut encoder = flate2::write::GzEncoder::new(
+        std::fs::File::create(&fastq).unwrap(),
+        flate2::Compression::default(),
+    );
+    encoder
+        .write_all(b"@r1\nGG\n+\nII\n@r2\nACGT\n+\nIIII\n")
+        .unwrap();
+    encoder.finish().unwrap();
+    assert_eq!(file_digest(&fasta).unwrap(), file_digest(&fastq).unwrap());
+}
@@ -0,0 +1,113 @@
+// Tests for sequence complexity
+use prseq::complexity::{dust_score, entropy};
+use prseq::filter::FilterExt;
+use prseq::{FastaRecord, FastqReader, FastqRecord};
+
+const RANDOM: &[u8] = b"ACGTTGCAAGGCTTAACCGGATCGTAGCATGCTAGCTAGGATCCGATCGTA";
+
+#[test]
+fn test_entropy_homopolymer() {
+    assert_eq!(entropy(b"AAAAAAAAAAAAAAAAAAAA", 1), 0.0);
+    assert_eq!(entropy(b"AAAAAAAAAAAAAAAAAAAA", 5), 0.0);
+}
+
+#[test]
+fn test_entropy_all_different() {
+    // Four different 1-mers, equally common
+    assert_eq!(entropy(b"ACGT", 1), 1.0);
+    // Every 3-mer is different: as complex as a sequence this short can be
+    assert_eq!(entropy(b"ACGTT", 3), 1.0);
+}
+
+#[test]
+fn test_entropy_dinucleotide_repeat() {
+    let repeat = b"CACACACACACACACACACA";
+    assert!(entropy(repeat, 3) < 0.3);
+    assert!(entropy(RANDOM, 3) > 0.8);
+}
+
+#[test]
+fn test_entropy_ignores_case_and_ambiguity() {
+    assert_eq!(entropy(b"acgt", 1), entropy(b"ACGT", 1));
+    // The k-mers with N are skipped, leaving AC and GT
+    assert_eq!(entropy(b"ACNGT", 2), 1.0);
+}
+
+#[test]
+fn test_entropy_too_short() {
+    assert_eq!(entropy(b"", 3), 0.0);
+    assert_eq!(entropy(b"ACG", 3), 0.0);
+    assert_eq!(entropy(b"NNNNN", 3), 0.0);
+}
+
+#[test]
+#[should_panic]
+fn test_entropy_zero_k() {
+    entropy(b"ACGT", 0);
+}
+
+#[test]
+fn test_dust_score() {
+    // 10 copies of AAA: 45 repeated pairs over 9
+    assert_eq!(dust_score(b"AAAAAAAAAAAA"), 5.0);
+    // ACG, CGT, GTA, TAC, ACG: one repeated pair over 4
+    assert_eq!(dust_score(b"ACGTACG"), 0.25);
+    assert!(dust_score(RANDOM) < 1.0);
+    assert!(dust_score(b"CACACACACACACACACACA") > 4.0);
+}
+
+#[test]
+fn test_dust_score_too_short() {
+    assert_eq!(dust_score(b""), 0.0);
+    assert_eq!(dust_score(b"ACG"), 0.0);
+    assert_eq!(dust_score(b"ACNGT"), 0.0);
+}
+
+#[test]
+fn test_record_complexity() {
+    let fasta = FastaRecord {
+        header: "s1".to_string(),
+        sequence: "AAAAAAAAAAAA".to_string(),
+    };
+    let fastq = FastqRecord {
+        header: "r1".to_string(),
+        sequence: "AAAAAAAAAAAA".to_string(),
+        quality: "IIIIIIIIIIII".to_string(),
+    };
+    assert_eq!(fasta.entropy(3), 0.0);
+    assert_eq!(fasta.dust_score(), 5.0);
+    assert_eq!(fastq.entropy(3), 0.0);
+    assert_eq!(fastq.dust_score(), 5.0);
+}
+
+fn reads() -> FastqReader {
+    let random = std::str::from_utf8(RANDOM).unwrap();
+    let input = format!(
+        "@random\n{}\n+\n{}\n@polyA\n{}\n+\n{}\n@ca\n{}\n+\n{}\n",
+        random,
+        "I".repeat(random.len()),
+        "A".repeat(30),
+        "I".repeat(30),
+        "CA".repeat(15),
+        "I".repeat(30),
+    );
+    FastqReader::from_string(&input).unwrap()
+}
+
+#[test]
+fn test_filter_entropy() {
+    let ids: Vec<String> = reads()
+        .filter_entropy(3, 0.5)
+        .map(|record| record.unwrap().header)
+        .collect();
+    assert_eq!(ids, ["random"]);
+}
+
+#[test]
+fn test_filter_dust() {
+    let ids: Vec<String> = reads()
+        .filter_dust(2.0)
+        .map(|record| record.unwrap().header)
+        .collect();
+    assert_eq!(ids, ["random"]);
+}
@@ -9,11 +9,11 @@ fn test_basic_reading() {
     let mut reader = FastaReader::from_reader_with_capacity(cursor, 1024).unwrap();
 
     let record1 = reader.next().unwrap().unwrap();
-    assert_eq!(record1.id, "seq1 test");
+    assert_eq!(record1.header, "seq1 test");
     assert_eq!(record1.sequence, "ATCGGCTA");
 
     let record2 = reader.next().unwrap().unwrap();
-    assert_eq!(record2.id, "seq2 test");
+    assert_eq!(record2.header, "seq2 test");
     assert_eq!(record2.sequence, "GGCC");
 
     assert!(reader.next().is_none());
@@ -33,11 +33,11 @@ fn test_gzip_compression() {
     let mut reader = FastaReader::from_reader_with_capacity(cursor, 1024).unwrap();
 
     let record1 = reader.next().unwrap().unwrap();
-    assert_eq!(record1.id, "seq1 compressed");
+    assert_eq!(record1.header, "seq1 compressed");
     assert_eq!(record1.sequence, "ATCG");
 
     let record2 = reader.next().unwrap().unwrap();
-    assert_eq!(record2.id, "seq2 compressed");
+    assert_eq!(record2.header, "seq2 compressed");
     assert_eq!(record2.sequence, "GGCC");
 
     assert!(reader.next().is_none());
@@ -57,11 +57,11 @@ fn test_bzip2_compression() {
     let mut reader = FastaReader::from_reader_with_capacity(cursor, 1024).unwrap();
 
     let record1 = reader.next().unwrap().unwrap();
-    assert_eq!(record1.id, "seq1 bz2");
+    assert_eq!(record1.header, "seq1 bz2");
     assert_eq!(record1.sequence, "ATCG");
 
     let record2 = reader.next().unwrap().unwrap();
-    assert_eq!(record2.id, "seq2 bz2");
+    assert_eq!(record2.header, "seq2 bz2");
     assert_eq!(record2.sequence, "GGCC");
 
     assert!(reader.next().is_none());
@@ -78,12 +78,197 @@ fn test_file_reading() {
     let mut reader = FastaReader::from_file(temp_file.path()).unwrap();
 
     let record1 = reader.next().unwrap().unwrap();
-    assert_eq!(record1.id, "seq1 file test");
+    assert_eq!(record1.header, "seq1 file test");
     assert_eq!(record1.sequence, "ATCGGCTA");
 
     let record2 = reader.next().unwrap().unwrap();
-    assert_eq!(record2.id, "seq2 file test");
+    assert_eq!(record2.header, "seq2 file test");
     assert_eq!(record2.sequence, "GGCC");
 
     assert!(reader.next().is_none());
 }
+
+#[cfg(feature = "zstd")]
+#[test]
+fn test_zstd_compression() {
+    let content = b">seq1 zstd\nATCG\n>seq2 zstd\nGGCC\n";
+    let compressed = zstd::stream::encode_all(&content[..], 0).unwrap();
+
+    let cursor = Cursor::new(compressed);
+    let mut reader = FastaReader::from_reader_with_capacity(cursor, 1024).unwrap();
+
+    let record1 = reader.next().unwrap().unwrap();
+    assert_eq!(record1.header, "seq1 zstd");
+    assert_eq!(record1.sequence, "ATCG");
+
+    let record2 = reader.next().unwrap().unwrap();
+    assert_eq!(record2.header, "seq2 zstd");
+    assert_eq!(record2.sequence, "GGCC");
+
+    assert!(reader.next().is_none());
+}
+
+#[cfg(not(feature = "zstd"))]
+#[test]
+fn test_zstd_without_feature() {
+    let content = vec![0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x00];
+    let result = FastaReader::from_reader_with_capacity(Cursor::new(content), 1024);
+
+    let error = result.err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+    assert!(error.to_string().contains("'zstd' feature"));
+}
+
+#[test]
+fn test_xz_compression() {
+    use xz2::write::XzEncoder;
+
+    let content = b">seq1 xz\nATCG\n>seq2 xz\nGGCC\n";
+    let mut encoder = XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(content).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let cursor = Cursor::new(compressed);
+    let mut reader = FastaReader::from_reader_with_capacity(cursor, 1024).unwrap();
+
+    let record1 = reader.next().unwrap().unwrap();
+    assert_eq!(record1.header, "seq1 xz");
+    assert_eq!(record1.sequence, "ATCG");
+
+    let record2 = reader.next().unwrap().unwrap();
+    assert_eq!(record2.header, "seq2 xz");
+    assert_eq!(record2.sequence, "GGCC");
+
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_gzip_multi_member() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    // Equivalent to: cat a.fasta.gz b.fasta.gz > all.fasta.gz
+    let mut compressed = Vec::new();
+    for content in [&b">seq1 member1\nATCG\n"[..], &b">seq2 member2\nGGCC\n"[..]] {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).unwrap();
+        compressed.extend(encoder.finish().unwrap());
+    }
+
+    let cursor = Cursor::new(compressed);
+    let records: Vec<_> = FastaReader::from_reader_with_capacity(cursor, 1024)
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].header, "seq1 member1");
+    assert_eq!(records[1].header, "seq2 member2");
+    assert_eq!(records[1].sequence, "GGCC");
+}
+
+#[test]
+fn test_bzip2_multi_stream() {
+    use bzip2::write::BzEncoder;
+    use bzip2::Compression;
+
+    let mut compressed = Vec::new();
+    for content in [&b">seq1 stream1\nATCG\n"[..], &b">seq2 stream2\nGGCC\n"[..]] {
+        let mut encoder = BzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).unwrap();
+        compressed.extend(encoder.finish().unwrap());
+    }
+
+    let cursor = Cursor::new(compressed);
+    let records: Vec<_> = FastaReader::from_reader_with_capacity(cursor, 1024)
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].header, "seq2 stream2");
+}
+
+/// Read all records from `input`, returning the number read before an error
+fn count_until_error(input: Vec<u8>) -> (usize, Option<prseq::PrseqError>) {
+    let reader = FastaReader::from_reader_with_capacity(Cursor::new(input), 1024).unwrap();
+    let mut count = 0;
+    for result in reader {
+        match result {
+            Ok(_) => count += 1,
+            Err(e) => return (count, Some(e)),
+        }
+    }
+    (count, None)
+}
+
+fn many_records() -> Vec<u8> {
+    (0..1000)
+        .flat_map(|i| format!(">seq{}\nACGTACGTTTGACCA\n", i).into_bytes())
+        .collect()
+}
+
+#[test]
+fn test_truncated_gzip() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&many_records()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    // Cut in the middle of the data and also just before the end, where all
+    // records decompress but the gzip trailer is missing.
+    for cut in [compressed.len() / 2, compressed.len() - 3] {
+        let (_, error) = count_until_error(compressed[..cut].to_vec());
+        let error = error.expect("truncation should be reported");
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+        let message = error.to_string();
+        assert!(message.contains("Truncated gzip stream"), "{}", message);
+        assert!(
+            message.contains(&format!("after {} bytes", cut)),
+            "{}",
+            message
+        );
+    }
+}
+
+#[test]
+fn test_truncated_bzip2() {
+    use bzip2::write::BzEncoder;
+    use bzip2::Compression;
+
+    let mut encoder = BzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&many_records()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let (_, error) = count_until_error(compressed[..compressed.len() - 2].to_vec());
+    let error = error.expect("truncation should be reported");
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+    assert!(error.to_string().contains("Truncated bzip2 stream"));
+}
+
+#[test]
+fn test_truncated_xz() {
+    use xz2::write::XzEncoder;
+
+    let mut encoder = XzEncoder::new(Vec::new(), 6);
+    encoder.write_all(&many_records()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let (_, error) = count_until_error(compressed[..compressed.len() / 2].to_vec());
+    assert!(error.unwrap().to_string().contains("Truncated xz stream"));
+}
+
+#[test]
+fn test_complete_gzip_is_not_truncated() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&many_records()).unwrap();
+
+    let (count, error) = count_until_error(encoder.finish().unwrap());
+    assert!(error.is_none());
+    assert_eq!(count, 1000);
+}
@@ -0,0 +1,63 @@
+// Tests for converting between FASTA and FASTQ
+use prseq::convert::{fasta_to_fastq, ConvertExt, FastaConvertExt};
+use prseq::error::Result;
+use prseq::{AnyReader, FastaReader, FastaRecord, FastqRecord};
+
+fn read() -> FastqRecord {
+    FastqRecord {
+        header: "r1 x".to_string(),
+        sequence: "ACGT".to_string(),
+        quality: "I#I#".to_string(),
+    }
+}
+
+#[test]
+fn test_fastq_to_fasta() {
+    let expected = FastaRecord {
+        header: "r1 x".to_string(),
+        sequence: "ACGT".to_string(),
+    };
+    assert_eq!(read().to_fasta(), expected);
+    assert_eq!(FastaRecord::from(read()), expected);
+    let record: FastaRecord = prseq::AnyRecord::Fastq(read()).into();
+    assert_eq!(record, expected);
+}
+
+#[test]
+fn test_fasta_to_fastq() {
+    let record = FastaRecord {
+        header: "s".to_string(),
+        sequence: "ACG".to_string(),
+    };
+    let fastq = record.to_fastq('5');
+    assert_eq!(fastq.header, "s");
+    assert_eq!(fastq.quality, "555");
+    assert_eq!(fasta_to_fastq(record, 'I').quality, "III");
+
+    let empty = FastaRecord {
+        header: "e".to_string(),
+        sequence: String::new(),
+    };
+    assert_eq!(fasta_to_fastq(empty, 'I').quality, "");
+}
+
+#[test]
+fn test_streaming() {
+    let input = b">a\nAC\n>b\nG\n";
+    let reader = FastaReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    let reads: Vec<FastqRecord> = reader.to_fastq('I').collect::<Result<_>>().unwrap();
+    assert_eq!(reads[0].quality, "II");
+    assert_eq!(reads[1].header, "b");
+
+    let records: Vec<FastaRecord> = vec![Ok(read())]
+        .into_iter()
+        .to_fasta()
+        .collect::<Result<_>>()
+        .unwrap();
+    assert_eq!(records[0].sequence, "ACGT");
+
+    let input = b"@a\nAC\n+\nII\n";
+    let reader = AnyReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    let records: Vec<FastaRecord> = reader.to_fasta().collect::<Result<_>>().unwrap();
+    assert_eq!(records[0].header, "a");
+}
@@ -0,0 +1,111 @@
+// Tests for counting records and bases without parsing
+use flate2::write::GzEncoder;
+use prseq::count::{count, count_bases, count_reader, count_records, Counts};
+use prseq::{FastqReader, PrseqError};
+use std::io::{Cursor, Write};
+use tempfile::NamedTempFile;
+
+const FASTA: &[u8] = b"\n>a first\nACGT\nAC\n\n>b\n  GGG  \n>c\n>d\nT\n";
+const FASTQ: &[u8] =
+    b"@r1\nACGT\n+\nIIII\n\n@r2\nAC\nGT\nA\n+r2\nIII\nII\n@r3\n+\n\n@r4\nGG\n+\n@I\n";
+
+fn counted(input: &'static [u8]) -> prseq::error::Result<Counts> {
+    count_reader(Cursor::new(input))
+}
+
+#[test]
+fn test_count_fasta() {
+    assert_eq!(
+        counted(FASTA).unwrap(),
+        Counts {
+            records: 4,
+            bases: 10
+        }
+    );
+}
+
+#[test]
+fn test_count_fastq() {
+    assert_eq!(
+        counted(FASTQ).unwrap(),
+        Counts {
+            records: 4,
+            bases: 11
+        }
+    );
+}
+
+#[test]
+fn test_count_matches_parser() {
+    let records: Vec<_> = FastqReader::from_reader_with_capacity(Cursor::new(FASTQ), 64)
+        .unwrap()
+        .map(|record| record.unwrap())
+        .collect();
+    let counts = counted(FASTQ).unwrap();
+    assert_eq!(counts.records, records.len() as u64);
+    assert_eq!(
+        counts.bases,
+        records.iter().map(|r| r.sequence.len() as u64).sum::<u64>()
+    );
+}
+
+#[test]
+fn test_count_empty_input() {
+    assert_eq!(counted(b"").unwrap(), Counts::default());
+    assert_eq!(counted(b"\n\n").unwrap(), Counts::default());
+}
+
+#[test]
+fn test_count_files() {
+    let mut fasta = NamedTempFile::new().unwrap();
+    fasta.write_all(FASTA).unwrap();
+    assert_eq!(count_records(fasta.path()).unwrap(), 4);
+    assert_eq!(count_bases(fasta.path()).unwrap(), 10);
+
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(FASTQ).unwrap();
+    let mut fastq = NamedTempFile::with_suffix(".fastq.gz").unwrap();
+    fastq.write_all(&encoder.finish().unwrap()).unwrap();
+    assert_eq!(
+        count(fastq.path()).unwrap(),
+        Counts {
+            records: 4,
+            bases: 11
+        }
+    );
+}
+
+#[test]
+fn test_count_truncated_fastq() {
+    let error = counted(b"@r1\nACGT\n+\nIIII\n@r2\nACGT\n+\nII").unwrap_err();
+    assert!(matches!(error, PrseqError::UnexpectedEof { .. }));
+    assert_eq!(error.position().unwrap().record, 2);
+
+    let error = counted(b"@r1\nACGT\n").unwrap_err();
+    assert!(matches!(error, PrseqError::UnexpectedEof { .. }));
+}
+
+#[test]
+fn test_count_fastq_length_mismatch() {
+    let error = counted(b"@r1\nACGT\n+\nIIIII\n").unwrap_err();
+    assert!(matches!(
+        error,
+        PrseqError::LengthMismatch {
+            sequence_length: 4,
+            quality_length: 5,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_count_fastq_missing_header() {
+    let error = counted(b"@r1\nACGT\n+\nIIII\nr2\nACGT\n+\nIIII\n").unwrap_err();
+    assert!(matches!(error, PrseqError::InvalidFastq { .. }));
+    assert_eq!(error.position().unwrap().line, 5);
+}
+
+#[test]
+fn test_count_unknown_format() {
+    assert!(counted(b"ACGT\n").is_err());
+}
@@ -0,0 +1,94 @@
+// Tests for duplicate removal
+use prseq::dedupe::DedupeKey;
+use prseq::error::Result;
+use prseq::{FastaReader, FastaRecord, FilterExt, PrseqError};
+use tempfile::TempDir;
+
+fn record(header: &str, sequence: &str) -> Result<FastaRecord> {
+    Ok(FastaRecord {
+        header: header.to_string(),
+        sequence: sequence.to_string(),
+    })
+}
+
+#[test]
+fn test_dedupe_by_sequence() {
+    let input = b">a\nACGT\n>b\nACGT\n>c\nTTTT\n>a\nTTTT\n>d\nacgt\n";
+    let reader = FastaReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    let mut records = reader.dedupe(DedupeKey::Sequence);
+    let kept: Vec<String> = records
+        .by_ref()
+        .map(|record| record.unwrap().header)
+        .collect();
+    // Sequences are compared exactly, so acgt is not a duplicate of ACGT.
+    assert_eq!(kept, ["a", "c", "d"]);
+    assert_eq!(records.removed(), 2);
+}
+
+#[test]
+fn test_dedupe_by_id_and_sequence() {
+    let records = vec![
+        record("a x", "ACGT"),
+        record("b", "ACGT"),
+        record("a y", "ACGT"),
+        record("a", "TTTT"),
+    ];
+    let kept: Vec<String> = records
+        .into_iter()
+        .dedupe(DedupeKey::IdAndSequence)
+        .map(|record| record.unwrap().header)
+        .collect();
+    assert_eq!(kept, ["a x", "b", "a"]);
+}
+
+#[test]
+fn test_dedupe_passes_errors() {
+    let records = vec![
+        record("a", "ACGT"),
+        Err(PrseqError::Io(std::io::Error::other("bad"))),
+        record("b", "ACGT"),
+    ];
+    let results: Vec<_> = records.into_iter().dedupe(DedupeKey::Sequence).collect();
+    assert_eq!(results.len(), 2);
+    assert!(results[1].is_err());
+}
+
+#[test]
+fn test_dedupe_with_spill() {
+    let dir = TempDir::new().unwrap();
+    let sequences: Vec<String> = (0..1000).map(|i| format!("ACGT{}", i % 300)).collect();
+    let records = sequences
+        .iter()
+        .enumerate()
+        .map(|(i, sequence)| record(&format!("seq{}", i), sequence));
+    let mut deduped = records.dedupe(DedupeKey::Sequence).spill_to(dir.path(), 64);
+    let kept: Vec<String> = deduped
+        .by_ref()
+        .map(|record| record.unwrap().header)
+        .collect();
+    assert_eq!(kept.len(), 300);
+    assert_eq!(kept[299], "seq299");
+    assert_eq!(deduped.removed(), 700);
+    assert_eq!(deduped.spilled_runs(), 4);
+    // The spill files have no names to find (or clash with).
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_dedupe_with_many_spilled_runs() {
+    let dir = TempDir::new().unwrap();
+    // Each key twice: once early on, and again after many runs are spilled.
+    let keys = (0..3000).chain(0..3000).map(|i| format!("ACGT{}", i));
+    let records = keys
+        .enumerate()
+        .map(|(i, sequence)| record(&format!("seq{}", i), &sequence));
+    let mut deduped = records.dedupe(DedupeKey::Sequence).spill_to(dir.path(), 10);
+    let kept: Vec<String> = deduped
+        .by_ref()
+        .map(|record| record.unwrap().sequence)
+        .collect();
+    assert_eq!(kept.len(), 3000);
+    assert_eq!(kept[2999], "ACGT2999");
+    assert_eq!(deduped.removed(), 3000);
+    assert_eq!(deduped.spilled_runs(), 300);
+}
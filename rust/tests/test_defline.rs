@@ -0,0 +1,94 @@
+// Tests for NCBI defline parsing
+use prseq::defline::{Defline, SeqId};
+use prseq::{FastaRecord, FastaRecordBytes};
+
+#[test]
+fn test_plain_accession() {
+    let defline = Defline::new("NC_045512.2 Severe acute respiratory syndrome coronavirus 2 isolate Wuhan-Hu-1, complete genome");
+    assert_eq!(defline.id(), "NC_045512.2");
+    assert_eq!(defline.accession(), Some("NC_045512.2"));
+    assert_eq!(defline.version(), Some(2));
+    assert_eq!(defline.gi(), None);
+    assert!(defline.ids().is_empty());
+    assert_eq!(defline.organism(), None);
+    assert!(defline.description().unwrap().starts_with("Severe acute"));
+}
+
+#[test]
+fn test_database_tags() {
+    let defline = Defline::new("gi|1234|gb|AB000263.1|AB000263 Homo sapiens mRNA [Homo sapiens]");
+    assert_eq!(
+        defline.ids(),
+        vec![
+            SeqId {
+                database: "gi",
+                values: vec!["1234"],
+            },
+            SeqId {
+                database: "gb",
+                values: vec!["AB000263.1", "AB000263"],
+            },
+        ]
+    );
+    assert_eq!(defline.gi(), Some(1234));
+    assert_eq!(defline.accession(), Some("AB000263.1"));
+    assert_eq!(defline.get("gb"), Some(vec!["AB000263.1", "AB000263"]));
+    assert_eq!(defline.get("emb"), None);
+    assert_eq!(defline.organism(), Some("Homo sapiens"));
+}
+
+#[test]
+fn test_trailing_pipe_and_uniprot() {
+    let defline = Defline::new("ref|NC_001477.1|");
+    assert_eq!(defline.accession(), Some("NC_001477.1"));
+    assert_eq!(defline.description(), None);
+
+    let defline = Defline::new("sp|P69905|HBA_HUMAN Hemoglobin subunit alpha OS=Homo sapiens");
+    assert_eq!(defline.accession(), Some("P69905"));
+    assert_eq!(defline.version(), None);
+    assert_eq!(defline.get("sp"), Some(vec!["P69905", "HBA_HUMAN"]));
+}
+
+#[test]
+fn test_no_accession() {
+    let defline = Defline::new("gnl|taxon|9606 something");
+    assert_eq!(defline.get("gnl"), Some(vec!["taxon", "9606"]));
+    assert_eq!(defline.accession(), None);
+    assert_eq!(Defline::new("").accession(), None);
+}
+
+#[test]
+fn test_record_defline() {
+    let record = FastaRecord {
+        header: "emb|X12345.3| Some virus [Some virus strain A]".to_string(),
+        sequence: "ACGT".to_string(),
+    };
+    assert_eq!(record.defline().accession(), Some("X12345.3"));
+    assert_eq!(record.defline().organism(), Some("Some virus strain A"));
+}
+
+/// Headers and the ID and description they split into. The Python
+/// package's tests check its records against the same cases.
+const SPLITS: &[(&str, &str, Option<&str>)] = &[
+    ("id description", "id", Some("description")),
+    ("id \t two  words ", "id", Some("two  words ")),
+    ("  id after spaces", "id", Some("after spaces")),
+    ("id\x0bvertical tab", "id\x0bvertical", Some("tab")),
+    ("id\u{a0}no-break space", "id\u{a0}no-break", Some("space")),
+    ("id\u{2003}em space", "id\u{2003}em", Some("space")),
+    ("   ", "", None),
+];
+
+#[test]
+fn test_headers_split_at_ascii_whitespace() {
+    for &(header, id, description) in SPLITS {
+        let defline = Defline::new(header);
+        assert_eq!((defline.id(), defline.description()), (id, description));
+        let record = FastaRecordBytes {
+            header: header.as_bytes().to_vec(),
+            sequence: Vec::new(),
+        };
+        assert_eq!(record.id(), id.as_bytes());
+        assert_eq!(record.description(), description.map(str::as_bytes));
+    }
+}
@@ -0,0 +1,204 @@
+// Tests for barcode demultiplexing
+use prseq::demux::{BarcodeLocation, Demultiplexer, UNDETERMINED};
+use prseq::{FastqReader, FastqRecord, FastqWriter, PrseqError};
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+fn read(header: &str, sequence: &str) -> FastqRecord {
+    FastqRecord {
+        header: header.to_string(),
+        sequence: sequence.to_string(),
+        quality: "I".repeat(sequence.len()),
+    }
+}
+
+fn writers(names: &[&str]) -> HashMap<String, FastqWriter<Vec<u8>>> {
+    names
+        .iter()
+        .map(|name| (name.to_string(), FastqWriter::new(Vec::new())))
+        .collect()
+}
+
+fn written(writers: HashMap<String, FastqWriter<Vec<u8>>>, name: &str) -> Vec<FastqRecord> {
+    let mut writers = writers;
+    let data = writers.remove(name).unwrap().into_inner();
+    FastqReader::from_reader_with_capacity(std::io::Cursor::new(data), 64)
+        .unwrap()
+        .map(|record| record.unwrap())
+        .collect()
+}
+
+#[test]
+fn test_invalid_barcodes() {
+    let empty = Demultiplexer::new([("", "s1")], BarcodeLocation::Header);
+    assert!(empty.is_err());
+    let duplicate = Demultiplexer::new([("ACGT", "s1"), ("acgt", "s2")], BarcodeLocation::Header);
+    assert!(duplicate.unwrap_err().to_string().contains("'s1' and 's2'"));
+    let lengths = [("ACGT", "s1"), ("ACG", "s2")];
+    assert!(Demultiplexer::new(lengths, BarcodeLocation::ReadStart).is_err());
+    assert!(Demultiplexer::new(lengths, BarcodeLocation::Header).is_ok());
+}
+
+#[test]
+fn test_sample_names_that_are_not_file_names() {
+    for sample in ["", ".", "..", "../x", "a/b", "a\\b", "nul\0"] {
+        let error = Demultiplexer::new([("ACGT", sample)], BarcodeLocation::Header).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(error.to_string().starts_with("Sample name '"));
+    }
+}
+
+#[test]
+fn test_sample_named_undetermined() {
+    let error = Demultiplexer::new(
+        [("ACGT", "s1"), ("TTTT", "undetermined")],
+        BarcodeLocation::Header,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Sample name 'undetermined' is reserved for reads that match no barcode"
+    );
+}
+
+#[test]
+fn test_assign_with_mismatches() {
+    let demux = Demultiplexer::new(
+        [("AAAAAA", "s1"), ("AAAATT", "s2"), ("CCCCCC", "s3")],
+        BarcodeLocation::ReadStart,
+    )
+    .unwrap();
+    assert_eq!(demux.assign(b"AAAAAA"), Some("s1"));
+    assert_eq!(demux.assign(b"aaaaaa"), Some("s1"));
+    assert_eq!(demux.assign(b"CCCCCA"), None);
+    assert_eq!(demux.assign(b"CCC"), None);
+
+    let demux = demux.max_mismatches(1);
+    assert_eq!(demux.assign(b"CCCCCA"), Some("s3"));
+    // An N is a mismatch.
+    assert_eq!(demux.assign(b"CCCCCN"), Some("s3"));
+    assert_eq!(demux.assign(b"CCCCNN"), None);
+    // AAAAAT is one mismatch from both s1 and s2.
+    assert_eq!(demux.assign(b"AAAAAT"), None);
+    assert_eq!(demux.samples().collect::<Vec<_>>(), ["s1", "s2", "s3"]);
+}
+
+#[test]
+fn test_demultiplex_by_header() {
+    let demux = Demultiplexer::new(
+        [("ATCACG+GTACTG", "s1"), ("CGATGT+GTACTG", "s2")],
+        BarcodeLocation::Header,
+    )
+    .unwrap()
+    .max_mismatches(1);
+    let records = vec![
+        Ok(read("M1:1:FC:1:1101:100:1 1:N:0:ATCACG+GTACTG", "ACGT")),
+        Ok(read("M1:1:FC:1:1101:100:2 1:N:0:CGATGA+GTACTG", "ACGT")),
+        Ok(read("M1:1:FC:1:1101:100:3 1:N:0:TTTTTT+GTACTG", "ACGT")),
+        Ok(read("M1:1:FC:1:1101:100:4", "ACGT")),
+        Ok(read("M1:1:FC:1:1101:100:5 1:N:0:ATCACG+GTACTG", "ACGT")),
+    ];
+    let mut writers = writers(&["s1", "s2", UNDETERMINED]);
+    let report = demux.demultiplex(records, &mut writers).unwrap();
+    assert_eq!(report.count("s1"), 2);
+    assert_eq!(report.count("s2"), 1);
+    assert_eq!(report.undetermined(), 2);
+    assert_eq!(report.total(), 5);
+
+    let s1: Vec<String> = written(writers, "s1")
+        .into_iter()
+        .map(|record| record.id().to_string())
+        .collect();
+    assert_eq!(s1, ["M1:1:FC:1:1101:100:1", "M1:1:FC:1:1101:100:5"]);
+}
+
+#[test]
+fn test_demultiplex_by_read_start() {
+    let demux =
+        Demultiplexer::new([("ACGT", "s1"), ("TTTT", "s2")], BarcodeLocation::ReadStart).unwrap();
+    let records = vec![
+        Ok(read("r1", "ACGTCCCC")),
+        Ok(read("r2", "TTTTGGGG")),
+        Ok(read("r3", "GGGGAAAA")),
+        Ok(read("r4", "AC")),
+    ];
+    let mut writers = writers(&["s1", UNDETERMINED]);
+    let report = demux.demultiplex(records, &mut writers).unwrap();
+    // s2 has no writer: its read is counted but not written.
+    assert_eq!(report.count("s2"), 1);
+    assert_eq!(report.undetermined(), 2);
+    let undetermined: Vec<String> = writers
+        .remove(UNDETERMINED)
+        .map(|writer| String::from_utf8(writer.into_inner()).unwrap())
+        .unwrap()
+        .lines()
+        .filter(|line| line.starts_with('@'))
+        .map(str::to_string)
+        .collect();
+    assert_eq!(undetermined, ["@r3", "@r4"]);
+
+    let s1 = written(writers, "s1");
+    assert_eq!(s1[0].sequence, "CCCC");
+    assert_eq!(s1[0].quality, "IIII");
+
+    let untrimmed = demux.trim_barcode(false);
+    let (sample, record) = untrimmed.classify(read("r1", "ACGTCCCC"), None);
+    assert_eq!(sample, Some("s1"));
+    assert_eq!(record.sequence, "ACGTCCCC");
+}
+
+#[test]
+fn test_demultiplex_with_index_reads() {
+    let demux =
+        Demultiplexer::new([("ACGT", "s1"), ("TTTT", "s2")], BarcodeLocation::IndexRead).unwrap();
+    let records = vec![Ok(read("r1 1:N:0", "CCCC")), Ok(read("r2 1:N:0", "GGGG"))];
+    let index = vec![Ok(read("r1 3:N:0", "TTTT")), Ok(read("r2 3:N:0", "ACGT"))];
+    let mut writers = writers(&["s1", "s2"]);
+    let report = demux
+        .demultiplex_with_index(records, index, &mut writers)
+        .unwrap();
+    assert_eq!((report.count("s1"), report.count("s2")), (1, 1));
+    assert_eq!(written(writers, "s2")[0].sequence, "CCCC");
+
+    // Out of sync index reads
+    let records = vec![Ok(read("r1", "CCCC")), Ok(read("r2", "GGGG"))];
+    let index = vec![Ok(read("r2", "TTTT"))];
+    let error = demux
+        .demultiplex_with_index(
+            records,
+            index,
+            &mut HashMap::<_, FastqWriter<Vec<u8>>>::new(),
+        )
+        .unwrap_err();
+    assert!(matches!(error, PrseqError::PairMismatch { pair: 1, .. }));
+
+    // Too few index reads
+    let records = vec![Ok(read("r1", "CCCC")), Ok(read("r2", "GGGG"))];
+    let index = vec![Ok(read("r1", "TTTT"))];
+    let error = demux
+        .demultiplex_with_index(
+            records,
+            index,
+            &mut HashMap::<_, FastqWriter<Vec<u8>>>::new(),
+        )
+        .unwrap_err();
+    assert!(matches!(error, PrseqError::PairMismatch { pair: 2, .. }));
+}
+
+#[test]
+fn test_demultiplex_to_dir() {
+    let dir = TempDir::new().unwrap();
+    let out = dir.path().join("out");
+    let demux =
+        Demultiplexer::new([("ACGT", "s1"), ("TTTT", "s2")], BarcodeLocation::ReadStart).unwrap();
+    let records = vec![Ok(read("r1", "ACGTCC")), Ok(read("r2", "GGGGCC"))];
+    let report = demux.demultiplex_to_dir(records, &out).unwrap();
+    assert_eq!(report.count("s1"), 1);
+    assert_eq!(
+        std::fs::read_to_string(out.join("s1.fastq")).unwrap(),
+        "@r1\nCC\n+\nII\n"
+    );
+    assert!(out.join("undetermined.fastq").exists());
+    // No reads for s2, so no file.
+    assert!(!out.join("s2.fastq").exists());
+}
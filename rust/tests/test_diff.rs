@@ -0,0 +1,112 @@
+// Tests for comparing the records of two inputs
+use prseq::diff::{diff, diff_summary, DiffEntry, DiffSummary};
+use prseq::error::Result;
+use prseq::{FastaReader, FastaRecord, FastqReader};
+
+fn fasta(header: &str, sequence: &str) -> Result<FastaRecord> {
+    Ok(FastaRecord {
+        header: header.to_string(),
+        sequence: sequence.to_string(),
+    })
+}
+
+#[test]
+fn test_diff_identical_inputs() {
+    let input = ">a x\nACGT\n>b\nGG\n";
+    let summary = diff_summary(
+        FastaReader::from_string(input).unwrap(),
+        FastaReader::from_string(input).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        summary,
+        DiffSummary {
+            identical: 2,
+            ..Default::default()
+        }
+    );
+    assert!(summary.is_identical());
+}
+
+#[test]
+fn test_diff_entries() {
+    let first = vec![
+        fasta("a", "ACGT"),
+        fasta("b one", "GG"),
+        fasta("c", "TT"),
+        fasta("d", "A"),
+    ];
+    let second = vec![
+        fasta("e", "C"),
+        fasta("c", "TA"),
+        fasta("b two", "GG"),
+        fasta("a", "ACGT"),
+    ];
+    let entries: Vec<DiffEntry> = diff(first, second).map(Result::unwrap).collect();
+    assert_eq!(
+        entries,
+        [
+            DiffEntry::Changed {
+                id: "c".to_string(),
+                description: false,
+                sequence: true,
+                quality: false,
+            },
+            DiffEntry::Changed {
+                id: "b".to_string(),
+                description: true,
+                sequence: false,
+                quality: false,
+            },
+            DiffEntry::Identical("a".to_string()),
+            DiffEntry::OnlyInFirst("d".to_string()),
+            DiffEntry::OnlyInSecond("e".to_string()),
+        ]
+    );
+    assert_eq!(entries[3].id(), "d");
+}
+
+#[test]
+fn test_diff_repeated_ids_and_uneven_lengths() {
+    let first = vec![fasta("a", "A"), fasta("a", "C")];
+    let second = vec![
+        fasta("a", "A"),
+        fasta("a", "G"),
+        fasta("a", "T"),
+        fasta("b", "T"),
+    ];
+    let summary = diff_summary(first, second).unwrap();
+    assert_eq!(
+        summary,
+        DiffSummary {
+            identical: 1,
+            changed: 1,
+            only_in_first: 0,
+            only_in_second: 2,
+        }
+    );
+    assert!(!summary.is_identical());
+}
+
+#[test]
+fn test_diff_fasta_against_fastq() {
+    let fasta = FastaReader::from_string(">r1\nACGT\n").unwrap();
+    let fastq = FastqReader::from_string("@r1\nACGT\n+\nIIII\n").unwrap();
+    let entries: Vec<DiffEntry> = diff(fasta, fastq).map(Result::unwrap).collect();
+    assert_eq!(
+        entries,
+        [DiffEntry::Changed {
+            id: "r1".to_string(),
+            description: false,
+            sequence: false,
+            quality: true,
+        }]
+    );
+}
+
+#[test]
+fn test_diff_reading_error() {
+    let first = FastaReader::from_string("ACGT\n").unwrap();
+    let second = FastaReader::from_string(">a\nACGT\n").unwrap();
+    assert!(diff_summary(first, second).is_err());
+}
@@ -0,0 +1,106 @@
+// Tests for reading EMBL flat files
+use prseq::embl::{EmblReader, EmblRecord};
+use prseq::error::Result;
+use prseq::{FastaRecord, PrseqError};
+
+const ENTRY: &str = "ID   X56734; SV 1; linear; mRNA; STD; PLN; 24 BP.
+XX
+AC   X56734; S46826;
+AC   X00001;
+XX
+DT   12-SEP-1991 (Rel. 29, Created)
+XX
+DE   Trifolium repens mRNA for non-cyanogenic beta-glucosidase
+DE   (partial)
+XX
+OS   Trifolium repens (white clover)
+OC   Eukaryota; Viridiplantae.
+XX
+FH   Key             Location/Qualifiers
+FH
+FT   source          1..24
+FT                   /organism=\"Trifolium repens\"
+FT                   /mol_type=\"mRNA\"
+FT   CDS             complement(join(1..6,
+FT                   10..20))
+FT                   /product=\"beta-
+FT                   glucosidase\"
+FT                   /translation=\"MDFIV
+FT                   AIFALF\"
+XX
+SQ   Sequence 24 BP; 10 A; 4 C; 3 G; 7 T; 0 other;
+     aaacaaacca aatatggatt                                              20
+     ttat                                                               24
+//
+";
+
+fn read(input: &str) -> Vec<EmblRecord> {
+    EmblReader::from_reader(std::io::Cursor::new(input.as_bytes().to_vec()))
+        .unwrap()
+        .collect::<Result<_>>()
+        .unwrap()
+}
+
+#[test]
+fn test_fields() {
+    let record = &read(ENTRY)[0];
+    assert_eq!(record.name, "X56734");
+    assert_eq!(record.length, Some(24));
+    assert_eq!(record.accession.as_deref(), Some("X56734"));
+    assert_eq!(record.version.as_deref(), Some("X56734.1"));
+    assert_eq!(record.id(), "X56734.1");
+    assert_eq!(
+        record.description,
+        "Trifolium repens mRNA for non-cyanogenic beta-glucosidase (partial)"
+    );
+    assert_eq!(
+        record.organism.as_deref(),
+        Some("Trifolium repens (white clover)")
+    );
+    assert_eq!(record.sequence, "AAACAAACCAAATATGGATTTTAT");
+}
+
+#[test]
+fn test_features() {
+    let record = &read(ENTRY)[0];
+    assert_eq!(record.features.len(), 2);
+    assert_eq!(record.features[0].qualifier("mol_type"), Some("mRNA"));
+    let cds: Vec<_> = record.features_of_kind("CDS").collect();
+    assert_eq!(cds[0].location, "complement(join(1..6,10..20))");
+    assert_eq!(cds[0].qualifier("product"), Some("beta- glucosidase"));
+    assert_eq!(cds[0].qualifier("translation"), Some("MDFIVAIFALF"));
+}
+
+#[test]
+fn test_to_fasta_and_old_id_line() {
+    let input = format!(
+        "{}ID   AB000263 standard; RNA; PRI; 4 BP.\nSQ   Sequence 4 BP;\n     acgt 4\n//\n",
+        ENTRY
+    );
+    let mut records = read(&input);
+    assert_eq!(records.len(), 2);
+    let old = records.pop().unwrap();
+    assert_eq!(old.name, "AB000263");
+    assert_eq!(old.length, Some(4));
+    assert_eq!(old.version, None);
+    assert_eq!(old.to_fasta().header, "AB000263");
+    let fasta: FastaRecord = records.pop().unwrap().into();
+    assert_eq!(
+        fasta.header,
+        "X56734.1 Trifolium repens mRNA for non-cyanogenic beta-glucosidase (partial)"
+    );
+}
+
+#[test]
+fn test_errors() {
+    let mut reader = EmblReader::from_reader(&b"LOCUS       X1\n"[..]).unwrap();
+    assert!(matches!(
+        reader.next(),
+        Some(Err(PrseqError::InvalidFormat { format: "EMBL", .. }))
+    ));
+    let mut reader = EmblReader::from_reader(&b"ID   X1; SV 1;\nSQ\n acgt\n"[..]).unwrap();
+    assert!(matches!(
+        reader.next(),
+        Some(Err(PrseqError::UnexpectedEof { .. }))
+    ));
+}
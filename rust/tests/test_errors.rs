@@ -0,0 +1,172 @@
+// Tests for the structured errors returned by the readers
+use prseq::{AnyReader, FastaReader, FastqReader, PairedFastqReader, Position, PrseqError};
+use std::io::{Cursor, ErrorKind};
+
+fn fasta(input: &str) -> FastaReader {
+    FastaReader::from_reader_with_capacity(Cursor::new(input.as_bytes().to_vec()), 64).unwrap()
+}
+
+fn fastq(input: &str) -> FastqReader {
+    FastqReader::from_reader_with_capacity(Cursor::new(input.as_bytes().to_vec()), 64).unwrap()
+}
+
+#[test]
+fn test_invalid_fasta_position_and_content() {
+    let error = fasta("not a header\n>seq1\nACGT\n")
+        .next()
+        .unwrap()
+        .unwrap_err();
+    match &error {
+        PrseqError::InvalidFasta {
+            position, content, ..
+        } => {
+            assert_eq!(*position, Position { line: 1, record: 1 });
+            assert_eq!(content, "not a header");
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        error.to_string(),
+        "FASTA record must start with '>' at line 1, record 1: 'not a header'"
+    );
+}
+
+#[test]
+fn test_fastq_errors_report_line_and_record() {
+    let input = "@read1\nACGT\n+\nIIII\n\n@read2\nACGT\n+read3\nIIII\n";
+    let mut reader = fastq(input);
+    reader.next().unwrap().unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(matches!(error, PrseqError::InvalidFastq { .. }));
+    assert_eq!(error.position(), Some(Position { line: 8, record: 2 }));
+}
+
+#[test]
+fn test_truncated_fastq_is_unexpected_eof() {
+    let error = fastq("@read1\nACGT\n").next().unwrap().unwrap_err();
+    assert!(matches!(error, PrseqError::UnexpectedEof { .. }));
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(error.position(), Some(Position { line: 3, record: 1 }));
+}
+
+#[test]
+fn test_long_content_is_shortened() {
+    let line = "x".repeat(500);
+    let error = fasta(&line).next().unwrap().unwrap_err();
+    let PrseqError::InvalidFasta { content, .. } = error else {
+        panic!("expected InvalidFasta");
+    };
+    assert_eq!(content, format!("{}...", "x".repeat(60)));
+}
+
+#[test]
+fn test_invalid_utf8_has_position() {
+    let mut reader = FastaReader::from_reader_with_capacity(
+        Cursor::new(b">ok\nAC\n>bad\xff\nGT\n".to_vec()),
+        64,
+    )
+    .unwrap();
+    reader.next().unwrap().unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(matches!(
+        error,
+        PrseqError::InvalidUtf8 {
+            format: "FASTA",
+            ..
+        }
+    ));
+    assert_eq!(error.position(), Some(Position { line: 3, record: 2 }));
+}
+
+#[test]
+fn test_line_numbers_count_skipped_leading_lines() {
+    // The quality is one short, so the error is at the end of input.
+    let input = format!("{}@read1\nACGT\n+\nIII\n", "\n".repeat(100_000));
+    let error = AnyReader::from_reader_with_capacity(Cursor::new(input.into_bytes()), 64)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(
+        error.position(),
+        Some(Position {
+            line: 100_005,
+            record: 1
+        })
+    );
+}
+
+#[test]
+fn test_pair_mismatch() {
+    let r1 = fastq("@a/1\nA\n+\nI\n@b/1\nC\n+\nI\n");
+    let r2 = fastq("@a/2\nA\n+\nI\n@c/2\nC\n+\nI\n");
+    let error = PairedFastqReader::new(r1, r2).nth(1).unwrap().unwrap_err();
+    assert!(matches!(error, PrseqError::PairMismatch { pair: 2, .. }));
+}
+
+#[test]
+fn test_conversion_to_and_from_io_error() {
+    let error = fasta("bad\n").next().unwrap().unwrap_err();
+    let message = error.to_string();
+
+    let io_error: std::io::Error = error.into();
+    assert_eq!(io_error.kind(), ErrorKind::InvalidData);
+    assert_eq!(io_error.to_string(), message);
+
+    // Converting back recovers the original error.
+    let error = PrseqError::from(io_error);
+    assert_eq!(error.position(), Some(Position { line: 1, record: 1 }));
+
+    let error = FastaReader::from_file("/nonexistent/file.fa")
+        .err()
+        .unwrap();
+    assert!(matches!(error, PrseqError::Io(_)));
+    assert_eq!(error.kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn test_multi_file_errors_name_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("worse.fa"), "oops\n").unwrap();
+
+    let error = FastaReader::from_paths(&[dir.path().join("worse.fa")])
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.path(), Some(dir.path().join("worse.fa").as_path()));
+    assert_eq!(error.position(), Some(Position { line: 1, record: 1 }));
+    assert!(error
+        .to_string()
+        .starts_with(&dir.path().join("worse.fa").display().to_string()));
+}
+
+#[test]
+fn test_file_reader_errors_name_the_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let fasta_path = dir.path().join("bad.fa");
+    let fastq_path = dir.path().join("bad.fq");
+    std::fs::write(&fasta_path, "oops\n").unwrap();
+    std::fs::write(&fastq_path, "@r1\nAC\n+\nII\nr2\n").unwrap();
+
+    let error = FastaReader::from_file(&fasta_path)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.path(), Some(fasta_path.as_path()));
+    assert_eq!(error.position(), Some(Position { line: 1, record: 1 }));
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+    let mut reader = FastqReader::from_file(&fastq_path).unwrap();
+    reader.next().unwrap().unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    assert_eq!(error.path(), Some(fastq_path.as_path()));
+    assert!(error
+        .to_string()
+        .starts_with(&format!("{}: ", fastq_path.display())));
+
+    // Readers that were not given a path report no file
+    let error = fasta("oops\n").next().unwrap().unwrap_err();
+    assert_eq!(error.path(), None);
+}
@@ -10,12 +10,14 @@ fn test_basic_fastq_reading() {
     let mut reader = FastqReader::from_reader_with_capacity(cursor, 1024).unwrap();
 
     let record1 = reader.next().unwrap().unwrap();
-    assert_eq!(record1.id, "seq1 test");
+    assert_eq!(record1.header, "seq1 test");
+    assert_eq!(record1.id(), "seq1");
+    assert_eq!(record1.description(), Some("test"));
     assert_eq!(record1.sequence, "ATCG");
     assert_eq!(record1.quality, "IIII");
 
     let record2 = reader.next().unwrap().unwrap();
-    assert_eq!(record2.id, "seq2 test");
+    assert_eq!(record2.header, "seq2 test");
     assert_eq!(record2.sequence, "GGCC");
     assert_eq!(record2.quality, "JJJJ");
 
@@ -30,12 +32,12 @@ fn test_multiline_fastq() {
     let mut reader = FastqReader::from_reader_with_capacity(cursor, 1024).unwrap();
 
     let record1 = reader.next().unwrap().unwrap();
-    assert_eq!(record1.id, "seq1 multiline");
+    assert_eq!(record1.header, "seq1 multiline");
     assert_eq!(record1.sequence, "ATCGGCTA");
     assert_eq!(record1.quality, "IIIIJJJJ");
 
     let record2 = reader.next().unwrap().unwrap();
-    assert_eq!(record2.id, "seq2");
+    assert_eq!(record2.header, "seq2");
     assert_eq!(record2.sequence, "GGCC");
     assert_eq!(record2.quality, "KKLL");
 
@@ -74,6 +76,26 @@ fn test_fastq_length_mismatch() {
     );
 }
 
+#[test]
+fn test_fastq_quality_too_long() {
+    // Extra quality characters are an error, not silently dropped
+    for (content, lengths) in [
+        (&b"@seq1\nATCG\n+\nIIIIII\n@seq2\nAT\n+\nII\n"[..], (4, 6)),
+        (&b"@seq1\nATCG\nAT\n+\nIIII\nIII\n"[..], (6, 7)),
+    ] {
+        let mut reader =
+            FastqReader::from_reader_with_capacity(Cursor::new(content), 1024).unwrap();
+        match reader.next().unwrap().unwrap_err() {
+            prseq::PrseqError::LengthMismatch {
+                sequence_length,
+                quality_length,
+                ..
+            } => assert_eq!((sequence_length, quality_length), lengths),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+}
+
 #[test]
 fn test_fastq_file_reading() {
     let content = "@seq1 file test\nATCG\nGCTA\n+seq1 file test\nIIII\nJJJJ\n";
@@ -83,7 +105,7 @@ fn test_fastq_file_reading() {
     let mut reader = FastqReader::from_file(temp_file.path()).unwrap();
 
     let record = reader.next().unwrap().unwrap();
-    assert_eq!(record.id, "seq1 file test");
+    assert_eq!(record.header, "seq1 file test");
     assert_eq!(record.sequence, "ATCGGCTA");
     assert_eq!(record.quality, "IIIIJJJJ");
 
@@ -99,7 +121,7 @@ fn test_fastq_convenience_function() {
     let records = read_fastq(temp_file.path()).unwrap();
 
     assert_eq!(records.len(), 2);
-    assert_eq!(records[0].id, "seq1");
+    assert_eq!(records[0].header, "seq1");
     assert_eq!(records[0].sequence, "ATCG");
     assert_eq!(records[0].quality, "IIII");
     assert_eq!(records[1].sequence, "GGCC");
@@ -133,14 +155,100 @@ fn test_fastq_gzip_compression() {
     let mut reader = FastqReader::from_reader_with_capacity(cursor, 1024).unwrap();
 
     let record1 = reader.next().unwrap().unwrap();
-    assert_eq!(record1.id, "seq1 compressed");
+    assert_eq!(record1.header, "seq1 compressed");
     assert_eq!(record1.sequence, "ATCG");
     assert_eq!(record1.quality, "IIII");
 
     let record2 = reader.next().unwrap().unwrap();
-    assert_eq!(record2.id, "seq2 compressed");
+    assert_eq!(record2.header, "seq2 compressed");
     assert_eq!(record2.sequence, "GGCC");
     assert_eq!(record2.quality, "JJJJ");
 
     assert!(reader.next().is_none());
 }
+
+#[test]
+fn test_fastq_gzip_multi_member() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    // A record split across two members must still parse correctly.
+    let mut compressed = Vec::new();
+    for content in [
+        &b"@seq1\nATCG\n+\nIIII\n@seq2\nGG"[..],
+        &b"CC\n+\nJJJJ\n"[..],
+    ] {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(content).unwrap();
+        compressed.extend(encoder.finish().unwrap());
+    }
+
+    let cursor = Cursor::new(compressed);
+    let records: Vec<_> = FastqReader::from_reader_with_capacity(cursor, 1024)
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].header, "seq2");
+    assert_eq!(records[1].sequence, "GGCC");
+    assert_eq!(records[1].quality, "JJJJ");
+}
+
+#[test]
+fn test_fastq_read_into() {
+    let content = b"@seq1\nATCGATCG\n+\nIIIIIIII\n@seq2\nGG\n+\nJJ\n";
+    let mut reader = FastqReader::from_reader_with_capacity(Cursor::new(content), 64).unwrap();
+    let mut record = prseq::FastqRecord {
+        header: String::new(),
+        sequence: String::new(),
+        quality: String::new(),
+    };
+    assert!(reader.read_into(&mut record).unwrap());
+    let capacity = record.sequence.capacity();
+    assert!(reader.read_into(&mut record).unwrap());
+    assert_eq!(record.header, "seq2");
+    assert_eq!(record.sequence, "GG");
+    assert_eq!(record.quality, "JJ");
+    assert_eq!(record.sequence.capacity(), capacity);
+    assert!(!reader.read_into(&mut record).unwrap());
+}
+
+#[test]
+fn test_fastq_next_chunk() {
+    let content: Vec<u8> = (0..10)
+        .flat_map(|i| format!("@read{}\nACGT\n+\nIIII\n", i).into_bytes())
+        .collect();
+    let mut reader = FastqReader::from_reader_with_capacity(Cursor::new(content), 64).unwrap();
+    assert_eq!(reader.next_chunk(4).unwrap().len(), 4);
+    assert_eq!(reader.next_chunk(4).unwrap().len(), 4);
+    let last = reader.next_chunk(4).unwrap();
+    assert_eq!(last.len(), 2);
+    assert_eq!(last[1].header, "read9");
+    assert!(reader.next_chunk(4).unwrap().is_empty());
+}
+
+#[test]
+fn test_fastq_reader_from_memory() {
+    let input = "@r1\nACGT\n+\nIIII\n@r2\nGG\n+\n#I\n";
+    let records: Vec<_> = FastqReader::from_string(input)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].quality, "#I");
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(input.as_bytes()).unwrap();
+    let records_from_gzip: Vec<_> = FastqReader::from_vec(encoder.finish().unwrap())
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records_from_gzip, records);
+
+    let records_from_bytes: Vec<_> = FastqReader::from_bytes(input.as_bytes())
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records_from_bytes, records);
+}
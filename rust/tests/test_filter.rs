@@ -0,0 +1,134 @@
+// Tests for the record filtering adaptors
+use prseq::search::Search;
+use prseq::{AnyReader, FastaReader, FastqReader, FilterExt, IdMatch, IdSet};
+use std::collections::HashSet;
+use std::io::{Cursor, Write};
+use tempfile::NamedTempFile;
+
+const FASTA: &[u8] = b">a one\nACGT\n>b two\nACGTACGT\n>c three\nA\n>d four\nACGTAC\n";
+
+fn fasta_reader() -> FastaReader {
+    FastaReader::from_reader_with_capacity(Cursor::new(FASTA), 64).unwrap()
+}
+
+fn ids<R: prseq::Filterable>(
+    records: impl Iterator<Item = prseq::error::Result<R>>,
+) -> Vec<String> {
+    records.map(|r| r.unwrap().id().to_string()).collect()
+}
+
+#[test]
+fn test_filter_length() {
+    assert_eq!(ids(fasta_reader().filter_length(4..=6)), ["a", "d"]);
+    assert_eq!(ids(fasta_reader().filter_length(5..)), ["b", "d"]);
+    assert_eq!(ids(fasta_reader().filter_length(..2)), ["c"]);
+}
+
+#[test]
+fn test_filter_ids() {
+    let wanted: HashSet<String> = ["b", "c", "z"].iter().map(|s| s.to_string()).collect();
+    assert_eq!(ids(fasta_reader().filter_ids(wanted)), ["b", "c"]);
+}
+
+#[test]
+fn test_filter_records_and_chaining() {
+    let records = fasta_reader()
+        .filter_length(2..)
+        .filter_records(|record| record.sequence.starts_with("ACGTA"));
+    assert_eq!(ids(records), ["b", "d"]);
+}
+
+#[test]
+fn test_filter_passes_errors_through() {
+    let input = b"@r1\nACGT\n+\nIIII\n@r2\nACGT\n+r9\nIIII\n@r3\nAC\n+\nII\n";
+    let reader = FastqReader::from_reader_with_capacity(Cursor::new(input), 64).unwrap();
+    let mut records = reader.filter_length(4..);
+    assert_eq!(records.next().unwrap().unwrap().header, "r1");
+    assert!(records.next().unwrap().is_err());
+}
+
+#[test]
+fn test_filter_any_records() {
+    let reader = AnyReader::from_reader_with_capacity(Cursor::new(FASTA), 64).unwrap();
+    assert_eq!(ids(reader.filter_length(8..)), ["b"]);
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_filter_header_regex() {
+    let pattern = regex::Regex::new(r"^[ab] ").unwrap();
+    assert_eq!(ids(fasta_reader().filter_header_regex(pattern)), ["a", "b"]);
+}
+
+#[test]
+fn test_select_by_ids() {
+    assert_eq!(
+        ids(fasta_reader().select_by_ids(IdSet::new(["a", "d"]))),
+        ["a", "d"]
+    );
+    assert_eq!(
+        ids(fasta_reader().select_by_ids(IdSet::new(["a", "d"]).invert())),
+        ["b", "c"]
+    );
+    // In ID mode a full header does not match.
+    assert!(ids(fasta_reader().select_by_ids(IdSet::new(["a one"]))).is_empty());
+    assert_eq!(
+        ids(fasta_reader().select_by_ids(IdSet::headers(["a one", "b"]))),
+        ["a"]
+    );
+}
+
+#[test]
+fn test_id_set_from_file() {
+    let mut file = NamedTempFile::new().unwrap();
+    write!(file, "b\n\n  c three\nzzz\n").unwrap();
+
+    let set = IdSet::from_file(file.path(), IdMatch::Id).unwrap();
+    assert_eq!(set.len(), 3);
+    assert_eq!(ids(fasta_reader().select_by_ids(set)), ["b", "c"]);
+
+    let set = IdSet::from_file(file.path(), IdMatch::Header).unwrap();
+    assert_eq!(ids(fasta_reader().select_by_ids(set)), ["c"]);
+}
+
+#[test]
+fn test_grep_sequences_and_headers() {
+    assert_eq!(
+        ids(fasta_reader().grep(Search::substring("GTAC"))),
+        ["b", "d"]
+    );
+    assert_eq!(
+        ids(fasta_reader().grep(Search::substring("t").in_headers())),
+        ["b", "c"]
+    );
+    assert!(ids(fasta_reader().grep(Search::substring("acgt"))).is_empty());
+}
+
+#[test]
+#[allow(clippy::single_range_in_vec_init)]
+fn test_grep_matches() {
+    let hits: Vec<(String, Vec<std::ops::Range<usize>>)> = fasta_reader()
+        .grep_matches(Search::substring("AC"))
+        .map(|result| {
+            let (record, matches) = result.unwrap();
+            (record.header, matches)
+        })
+        .collect();
+    assert_eq!(
+        hits,
+        [
+            ("a one".to_string(), vec![0..2]),
+            ("b two".to_string(), vec![0..2, 4..6]),
+            ("d four".to_string(), vec![0..2, 4..6]),
+        ]
+    );
+    assert!(Search::substring("").find_in("ACGT").is_empty());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_grep_regex() {
+    let search = Search::regex(regex::Regex::new("(?i)gta+c").unwrap());
+    assert_eq!(search.find_in("acgtaacGTAC"), [2..7, 7..11]);
+    assert_eq!(ids(fasta_reader().grep(search)), ["b", "d"]);
+}
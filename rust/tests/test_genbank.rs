@@ -0,0 +1,134 @@
+// Tests for reading GenBank flat files
+use prseq::error::Result;
+use prseq::genbank::{GenBankReader, GenBankRecord};
+use prseq::{FastaRecord, PrseqError};
+
+const RECORD: &str = "LOCUS       MN908947               20 bp    RNA     linear   VRL 18-MAR-2020
+DEFINITION  Severe acute respiratory syndrome coronavirus 2 isolate Wuhan-Hu-1,
+            complete genome.
+ACCESSION   MN908947 MN908948
+VERSION     MN908947.3
+KEYWORDS    .
+SOURCE      Severe acute respiratory syndrome coronavirus 2 (SARS-CoV-2)
+  ORGANISM  Severe acute respiratory syndrome coronavirus 2
+            Viruses; Riboviria; Orthornavirae.
+REFERENCE   1  (bases 1 to 20)
+  AUTHORS   Wu,F.
+  TITLE     A new coronavirus
+FEATURES             Location/Qualifiers
+     source          1..20
+                     /organism=\"Severe acute respiratory syndrome coronavirus
+                     2\"
+                     /mol_type=\"genomic RNA\"
+     CDS             join(1..6,
+                     10..15)
+                     /gene=\"orf1ab\"
+                     /note=\"a \"\"quoted\"\" word\"
+                     /ribosomal_slippage
+                     /codon_start=1
+                     /translation=\"MESLVPGFNE
+                     KTHVQL\"
+     misc_feature    complement(16..20)
+ORIGIN      
+        1 attaaaggtt tatacctt
+       19 cc
+//
+";
+
+fn read(input: &str) -> Vec<GenBankRecord> {
+    GenBankReader::from_reader(std::io::Cursor::new(input.as_bytes().to_vec()))
+        .unwrap()
+        .collect::<Result<_>>()
+        .unwrap()
+}
+
+#[test]
+fn test_fields() {
+    let records = read(RECORD);
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.name, "MN908947");
+    assert_eq!(record.length, Some(20));
+    assert_eq!(
+        record.definition,
+        "Severe acute respiratory syndrome coronavirus 2 isolate Wuhan-Hu-1, complete genome."
+    );
+    assert_eq!(record.accession.as_deref(), Some("MN908947"));
+    assert_eq!(record.version.as_deref(), Some("MN908947.3"));
+    assert_eq!(record.id(), "MN908947.3");
+    assert_eq!(
+        record.organism.as_deref(),
+        Some("Severe acute respiratory syndrome coronavirus 2")
+    );
+    assert_eq!(record.sequence, "ATTAAAGGTTTATACCTTCC");
+}
+
+#[test]
+fn test_features() {
+    let record = &read(RECORD)[0];
+    assert_eq!(record.features.len(), 3);
+    let source = &record.features[0];
+    assert_eq!(source.kind, "source");
+    assert_eq!(source.location, "1..20");
+    assert_eq!(
+        source.qualifier("organism"),
+        Some("Severe acute respiratory syndrome coronavirus 2")
+    );
+
+    let cds: Vec<_> = record.features_of_kind("CDS").collect();
+    assert_eq!(cds.len(), 1);
+    assert_eq!(cds[0].location, "join(1..6,10..15)");
+    assert_eq!(cds[0].qualifier("note"), Some("a \"quoted\" word"));
+    assert_eq!(cds[0].qualifier("ribosomal_slippage"), Some(""));
+    assert_eq!(
+        cds[0].qualifiers[2],
+        ("ribosomal_slippage".to_string(), None)
+    );
+    assert_eq!(cds[0].qualifier("codon_start"), Some("1"));
+    assert_eq!(cds[0].qualifier("translation"), Some("MESLVPGFNEKTHVQL"));
+    assert_eq!(cds[0].qualifier("product"), None);
+
+    assert_eq!(record.features[2].location, "complement(16..20)");
+}
+
+#[test]
+fn test_to_fasta() {
+    let record = read(RECORD).remove(0);
+    let expected = "MN908947.3 Severe acute respiratory syndrome coronavirus 2 isolate Wuhan-Hu-1, complete genome";
+    assert_eq!(record.to_fasta().header, expected);
+    let fasta: FastaRecord = record.into();
+    assert_eq!(fasta.header, expected);
+    assert_eq!(fasta.sequence.len(), 20);
+}
+
+#[test]
+fn test_several_records() {
+    let input = format!(
+        "\n{}\nLOCUS       X1  2 bp DNA\nORIGIN\n        1 ac\n//\n",
+        RECORD
+    );
+    let records = read(&input);
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[1].id(), "X1");
+    assert_eq!(records[1].to_fasta().header, "X1");
+    assert_eq!(records[1].sequence, "AC");
+}
+
+#[test]
+fn test_errors() {
+    let mut reader =
+        GenBankReader::from_reader(&b"ID   X; SV 1; linear; DNA; STD; VRL; 2 BP.\n"[..]).unwrap();
+    match reader.next() {
+        Some(Err(error @ PrseqError::InvalidFormat { format, .. })) => {
+            assert_eq!(format, "GenBank");
+            assert_eq!(error.position().unwrap().line, 1);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let mut reader = GenBankReader::from_reader(&b"LOCUS       X1\nORIGIN\n 1 ac\n"[..]).unwrap();
+    assert!(matches!(
+        reader.next(),
+        Some(Err(PrseqError::UnexpectedEof { .. }))
+    ));
+}
@@ -0,0 +1,60 @@
+// Tests for reading the FASTA section of GFF3 files
+use prseq::error::Result;
+use prseq::{FastaReader, FastaRecord};
+use std::io::Write;
+
+const GFF3: &str = "##gff-version 3
+##sequence-region ctg123 1 12
+ctg123\t.\tgene\t1\t12\t.\t+\t.\tID=gene1;Name=EDEN
+# comments and annotation are skipped, even >ctg999
+##FASTA
+>ctg123 contig
+ACGTAC
+GTACGT
+>ctg124
+NNNN
+";
+
+fn records(reader: FastaReader) -> Vec<FastaRecord> {
+    reader.collect::<Result<_>>().unwrap()
+}
+
+#[test]
+fn test_gff3_fasta_section() {
+    let reader =
+        FastaReader::from_gff3_reader(std::io::Cursor::new(GFF3.as_bytes().to_vec())).unwrap();
+    let records = records(reader);
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].header, "ctg123 contig");
+    assert_eq!(records[0].sequence, "ACGTACGTACGT");
+    assert_eq!(records[1].sequence, "NNNN");
+}
+
+#[test]
+fn test_gff3_file_and_compression() {
+    let path = std::env::temp_dir().join(format!("prseq-test-{}.gff3.gz", std::process::id()));
+    let mut encoder = flate2::write::GzEncoder::new(
+        std::fs::File::create(&path).unwrap(),
+        flate2::Compression::default(),
+    );
+    encoder.write_all(GFF3.as_bytes()).unwrap();
+    encoder.finish().unwrap();
+    let records = records(FastaReader::from_gff3(&path).unwrap());
+    assert_eq!(records.len(), 2);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_gff3_without_fasta() {
+    let input = b"##gff-version 3\nctg123\t.\tgene\t1\t12\t.\t+\t.\tID=gene1\n";
+    let reader = FastaReader::from_gff3_reader(&input[..]).unwrap();
+    assert!(records(reader).is_empty());
+}
+
+#[test]
+fn test_gff3_error_line_numbers() {
+    let input = b"##gff-version 3\n##FASTA\nACGT\n";
+    let mut reader = FastaReader::from_gff3_reader(&input[..]).unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    assert_eq!(error.position().unwrap().line, 3);
+}
@@ -0,0 +1,81 @@
+// Tests for homopolymer compression
+use prseq::homopolymer::homopolymer_compress;
+use prseq::FastaRecord;
+
+#[test]
+fn test_compress() {
+    let compressed = homopolymer_compress(b"AAACGGGGTTA");
+    assert_eq!(compressed.sequence(), b"ACGTA");
+    assert_eq!(compressed.len(), 5);
+    assert_eq!(compressed.original_len(), 11);
+    assert_eq!(
+        compressed.run_lengths().collect::<Vec<_>>(),
+        [3, 1, 4, 2, 1]
+    );
+}
+
+#[test]
+fn test_no_runs() {
+    let compressed = homopolymer_compress(b"ACGT");
+    assert_eq!(compressed.sequence(), b"ACGT");
+    assert!(compressed.run_lengths().all(|length| length == 1));
+}
+
+#[test]
+fn test_empty() {
+    let compressed = homopolymer_compress(b"");
+    assert!(compressed.is_empty());
+    assert_eq!(compressed.original_len(), 0);
+    assert_eq!(compressed.original_range(0), None);
+    assert_eq!(compressed.compressed_position(0), None);
+    assert_eq!(compressed.expand(), b"");
+}
+
+#[test]
+fn test_case_matters() {
+    let compressed = homopolymer_compress(b"AAaa");
+    assert_eq!(compressed.sequence(), b"Aa");
+}
+
+#[test]
+fn test_coordinate_mapping() {
+    let original = b"TTTGAACCCCG";
+    let compressed = homopolymer_compress(original);
+    assert_eq!(compressed.sequence(), b"TGACG");
+    for i in 0..compressed.len() {
+        let range = compressed.original_range(i).unwrap();
+        assert!(original[range.clone()]
+            .iter()
+            .all(|&base| base == compressed.sequence()[i]));
+        for position in range {
+            assert_eq!(compressed.compressed_position(position), Some(i));
+        }
+    }
+    assert_eq!(compressed.original_range(5), None);
+    assert_eq!(compressed.compressed_position(11), None);
+}
+
+#[test]
+fn test_original_span() {
+    let compressed = homopolymer_compress(b"TTTGAACCCCG");
+    // GAC in the compressed sequence is GAACCCC in the original
+    assert_eq!(compressed.original_span(1..4), Some(3..10));
+    assert_eq!(compressed.original_span(2..2), Some(4..4));
+    assert_eq!(compressed.original_span(0..5), Some(0..11));
+    assert_eq!(compressed.original_span(3..6), None);
+}
+
+#[test]
+fn test_expand() {
+    let original = b"NNNNACGGTTTTTTa";
+    assert_eq!(homopolymer_compress(original).expand(), original);
+}
+
+#[test]
+fn test_record_homopolymer_compress() {
+    let record = FastaRecord {
+        header: "read1".to_string(),
+        sequence: "GGGATTTC".to_string(),
+    };
+    assert_eq!(record.homopolymer_compress().sequence(), b"GATC");
+}
@@ -0,0 +1,80 @@
+// Tests for Illumina read header parsing
+use prseq::{FastqReader, IlluminaHeader};
+use std::io::Cursor;
+
+#[test]
+fn test_casava_header() {
+    let header =
+        IlluminaHeader::parse("M00123:45:000000000-ABCDE:1:1101:15589:1333 2:Y:18:ATCACG+GTACTG")
+            .unwrap();
+    assert_eq!(
+        header,
+        IlluminaHeader {
+            instrument: "M00123".to_string(),
+            run: Some(45),
+            flowcell: Some("000000000-ABCDE".to_string()),
+            lane: 1,
+            tile: 1101,
+            x: 15589,
+            y: 1333,
+            umi: None,
+            read: Some(2),
+            filtered: Some(true),
+            control: Some(18),
+            index: Some("ATCACG+GTACTG".to_string()),
+        }
+    );
+}
+
+#[test]
+fn test_casava_header_with_umi_and_no_comment() {
+    let header = IlluminaHeader::parse("NB501234:7:HXXXXBGXY:4:21612:2305:1059:ACGTACGT").unwrap();
+    assert_eq!(header.umi.as_deref(), Some("ACGTACGT"));
+    assert_eq!(header.read, None);
+    assert_eq!(header.filtered, None);
+    assert_eq!(header.index, None);
+}
+
+#[test]
+fn test_casava_header_with_sample_number() {
+    let header = IlluminaHeader::parse("A00123:8:H5XXXDSXX:2:1101:1000:1000 1:N:0:1").unwrap();
+    assert_eq!(header.filtered, Some(false));
+    assert_eq!(header.index.as_deref(), Some("1"));
+}
+
+#[test]
+fn test_legacy_header() {
+    let header = IlluminaHeader::parse("HWUSI-EAS100R:6:73:941:1973#ATCACG/1").unwrap();
+    assert_eq!(header.instrument, "HWUSI-EAS100R");
+    assert_eq!(header.run, None);
+    assert_eq!(header.flowcell, None);
+    assert_eq!(
+        (header.lane, header.tile, header.x, header.y),
+        (6, 73, 941, 1973)
+    );
+    assert_eq!(header.index.as_deref(), Some("ATCACG"));
+    assert_eq!(header.read, Some(1));
+}
+
+#[test]
+fn test_non_illumina_headers() {
+    for header in [
+        "read1",
+        "SRR001666.1 071112_SLXA-EAS1_s_7:5:1:817:345 length=36",
+        "M00123:45:FC:1:1101:x:1333",
+        "M00123:45:FC:1:1101:15589:1333 1:Q:0:ATCACG",
+        "",
+    ] {
+        assert_eq!(IlluminaHeader::parse(header), None, "{}", header);
+    }
+}
+
+#[test]
+fn test_parse_record_header() {
+    let input = b"@M00123:45:FC1:1:1101:15589:1333 1:N:0:ATCACG\nACGT\n+\nIIII\n";
+    let mut reader = FastqReader::from_reader_with_capacity(Cursor::new(input), 64).unwrap();
+    let record = reader.next().unwrap().unwrap();
+    let header = record.parse_illumina_header().unwrap();
+    assert_eq!(header.flowcell.as_deref(), Some("FC1"));
+    assert_eq!(header.tile, 1101);
+}
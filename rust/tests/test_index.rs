@@ -0,0 +1,96 @@
+// Tests for FASTA index (.fai) building
+use prseq::index::{fai_path, index_fasta};
+use prseq::{FaiRecord, FastaIndex, FastaReader};
+use std::io::{Cursor, Write};
+use tempfile::NamedTempFile;
+
+const FASTA: &[u8] = b">chr1 first\nACGTA\nCGTAC\nGT\n>chr2\nAAAA\nCC\n\n>chr3\r\nTTT\r\nGG\r\n";
+
+fn build(content: &'static [u8]) -> prseq::error::Result<FastaIndex> {
+    FastaIndex::build_from_reader(
+        FastaReader::from_reader_with_capacity(Cursor::new(content), 64).unwrap(),
+    )
+}
+
+#[test]
+fn test_build_index_matches_samtools() {
+    let index = build(FASTA).unwrap();
+    let mut output = Vec::new();
+    index.write(&mut output).unwrap();
+    // As produced by `samtools faidx`.
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "chr1\t12\t12\t5\t6\nchr2\t6\t33\t4\t5\nchr3\t5\t49\t3\t5\n"
+    );
+    assert_eq!(index.get("chr2").unwrap().length, 6);
+    assert!(index.get("chr4").is_none());
+}
+
+#[test]
+fn test_index_while_streaming() {
+    let mut reader = FastaReader::from_reader_with_capacity(Cursor::new(FASTA), 64)
+        .unwrap()
+        .with_index();
+    let ids: Vec<_> = reader.by_ref().map(|r| r.unwrap().header).collect();
+    assert_eq!(ids, vec!["chr1 first", "chr2", "chr3"]);
+    let index = reader.take_index().unwrap().unwrap();
+    assert_eq!(index.len(), 3);
+    assert_eq!(index.records()[0].offset, 12);
+}
+
+#[test]
+fn test_index_rejects_irregular_lines() {
+    let error = build(b">chr1\nACGT\nAC\nACGT\n").unwrap_err();
+    assert!(error.to_string().contains("Different line length"));
+
+    let error = build(b">chr1\nACGT\nACGTA\n").unwrap_err();
+    assert!(error.to_string().contains("Different line length"));
+
+    let error = build(b">chr1\nACGT\n\nACGT\n").unwrap_err();
+    assert!(error.to_string().contains("Different line length"));
+}
+
+#[test]
+fn test_index_rejects_duplicate_names() {
+    let error = build(b">chr1\nACGT\n>chr1 again\nACGT\n").unwrap_err();
+    assert!(error.to_string().contains("Duplicate sequence name 'chr1'"));
+}
+
+#[test]
+fn test_index_rejects_corrupt_line_lengths() {
+    let error = FastaIndex::from_reader(&b"s\t4\t3\t0\t5\n"[..]).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Invalid line lengths for sequence 's'"));
+
+    let error = FastaIndex::from_reader(&b"s\t4\t3\t4\t3\n"[..]).unwrap_err();
+    assert!(error.to_string().contains("4 bases in 3 bytes"));
+
+    // An empty sequence has no lines.
+    let index = FastaIndex::from_reader(&b"s\t0\t3\t0\t0\n"[..]).unwrap();
+    assert_eq!(index.get("s").unwrap().length, 0);
+}
+
+#[test]
+fn test_index_fasta_writes_and_reads_fai() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(FASTA).unwrap();
+    file.flush().unwrap();
+
+    let index = index_fasta(file.path()).unwrap();
+    let fai = fai_path(file.path());
+    let read_back = FastaIndex::from_file(&fai).unwrap();
+    std::fs::remove_file(&fai).unwrap();
+
+    assert_eq!(read_back, index);
+    assert_eq!(
+        read_back.get("chr3"),
+        Some(&FaiRecord {
+            name: "chr3".to_string(),
+            length: 5,
+            offset: 49,
+            line_bases: 3,
+            line_width: 5,
+        })
+    );
+}
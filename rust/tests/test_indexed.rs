@@ -0,0 +1,109 @@
+// Tests for random access to indexed FASTA files
+use prseq::index::{fai_path, index_fasta};
+use prseq::{FastaIndex, FastaReader, IndexedFastaReader, PrseqError, Region};
+use std::io::{Cursor, Write};
+use tempfile::NamedTempFile;
+
+const FASTA: &[u8] = b">chr1 first\nACGTA\nCGTAC\nGT\n>chr2\nAAAA\nCC\n>chr3\r\nTTT\r\nGG\r\n";
+
+fn indexed() -> IndexedFastaReader<Cursor<&'static [u8]>> {
+    let index = FastaIndex::build_from_reader(
+        FastaReader::from_reader_with_capacity(Cursor::new(FASTA), 64).unwrap(),
+    )
+    .unwrap();
+    IndexedFastaReader::new(Cursor::new(FASTA), index)
+}
+
+#[test]
+fn test_fetch_subsequences() {
+    let mut reader = indexed();
+    assert_eq!(reader.fetch("chr1", 0, 5).unwrap(), "ACGTA");
+    assert_eq!(reader.fetch("chr1", 3, 11).unwrap(), "TACGTACG");
+    assert_eq!(reader.fetch("chr1", 10, 100).unwrap(), "GT");
+    assert_eq!(reader.fetch("chr2", 4, 4).unwrap(), "");
+    assert_eq!(reader.fetch("chr3", 1, 5).unwrap(), "TTGG");
+    assert_eq!(reader.fetch_sequence("chr2").unwrap(), "AAAACC");
+}
+
+#[test]
+fn test_fetch_errors() {
+    let mut reader = indexed();
+    let error = reader.fetch("chr9", 0, 1).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+    match reader.fetch("chr2", 7, 8).unwrap_err() {
+        PrseqError::OutOfRange { range, length } => {
+            assert_eq!(range, "chr2:7-8");
+            assert_eq!(length, 6);
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(matches!(
+        reader.fetch_region("chr3:2-1").unwrap_err(),
+        PrseqError::InvalidFormat {
+            format: "region",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_from_file_uses_or_builds_fai() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(FASTA).unwrap();
+    file.flush().unwrap();
+
+    let mut reader = IndexedFastaReader::from_file(file.path()).unwrap();
+    assert_eq!(reader.fetch("chr1", 4, 6).unwrap(), "AC");
+    assert!(!fai_path(file.path()).exists());
+
+    index_fasta(file.path()).unwrap();
+    let mut reader = IndexedFastaReader::from_file(file.path()).unwrap();
+    assert_eq!(reader.index().len(), 3);
+    assert_eq!(reader.fetch("chr3", 0, 3).unwrap(), "TTT");
+    std::fs::remove_file(fai_path(file.path())).unwrap();
+}
+
+#[test]
+fn test_from_file_rejects_plain_gzip() {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(FASTA).unwrap();
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(&encoder.finish().unwrap()).unwrap();
+    file.flush().unwrap();
+
+    let error = IndexedFastaReader::from_file(file.path()).err().unwrap();
+    assert!(error.to_string().contains("not BGZF"));
+}
+
+#[test]
+fn test_region_parsing() {
+    let region: Region = "chr2:1,000-2,000".parse().unwrap();
+    assert_eq!(region, Region::new("chr2", 1000, Some(2000)).unwrap());
+    assert_eq!(region.zero_based(), (999, Some(2000)));
+    assert_eq!(region.to_string(), "chr2:1000-2000");
+
+    assert_eq!("chr2".parse::<Region>().unwrap(), Region::whole("chr2"));
+    assert_eq!(
+        "chr2:50-".parse::<Region>().unwrap(),
+        Region::new("chr2", 50, None).unwrap()
+    );
+    assert_eq!(
+        "chr2:50".parse::<Region>().unwrap(),
+        Region::new("chr2", 50, None).unwrap()
+    );
+    assert_eq!("HLA:A:1-5".parse::<Region>().unwrap().name, "HLA:A");
+
+    for invalid in ["", "chr2:0-5", "chr2:10-5", "chr2:a-5", ":1-5", "chr2:1-x"] {
+        assert!(invalid.parse::<Region>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_fetch_region() {
+    let mut reader = indexed();
+    assert_eq!(reader.fetch_region("chr1:4-12").unwrap(), "TACGTACGT");
+    assert_eq!(reader.fetch_region("chr1:11-").unwrap(), "GT");
+    assert_eq!(reader.fetch_region("chr2").unwrap(), "AAAACC");
+    assert_eq!(reader.fetch_region("chr3:2-2").unwrap(), "T");
+    assert!(reader.fetch_region("chr3:2-1").is_err());
+}
@@ -21,12 +21,28 @@ fn test_fasta_reader_iterator() {
     let records: Vec<FastaRecord> = reader.map(|r| r.unwrap()).collect();
 
     assert_eq!(records.len(), 2);
-    assert_eq!(records[0].id, "seq1 description one");
+    assert_eq!(records[0].header, "seq1 description one");
     assert_eq!(records[0].sequence, "ATCGATCGGCTAGCTA");
-    assert_eq!(records[1].id, "seq2 description two");
+    assert_eq!(records[1].header, "seq2 description two");
     assert_eq!(records[1].sequence, "GGGGCCCC");
 }
 
+#[test]
+fn test_fasta_id_and_description() {
+    let input = b">seq1 description one\nACGT\n>seq2\nGG\n>seq3 \t two  words\nT\n";
+    let records: Vec<FastaRecord> = FastaReader::from_reader_with_capacity(&input[..], 64)
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+
+    assert_eq!(records[0].id(), "seq1");
+    assert_eq!(records[0].description(), Some("description one"));
+    assert_eq!(records[1].id(), "seq2");
+    assert_eq!(records[1].description(), None);
+    assert_eq!(records[2].id(), "seq3");
+    assert_eq!(records[2].description(), Some("two  words"));
+}
+
 #[test]
 fn test_read_fasta_convenience() {
     let file = create_test_fasta();
@@ -46,3 +62,52 @@ fn test_invalid_fasta() {
 
     assert!(result.is_err());
 }
+
+#[test]
+fn test_read_into_reuses_record() {
+    let file = create_test_fasta();
+    let mut reader = FastaReader::from_file(file.path()).unwrap();
+    let mut record = FastaRecord {
+        header: String::new(),
+        sequence: String::new(),
+    };
+    let mut ids = Vec::new();
+    while reader.read_into(&mut record).unwrap() {
+        ids.push(record.header.clone());
+    }
+    assert_eq!(ids.len(), 2);
+    // The last record is left in place at end of input.
+    assert_eq!(record.header, ids[1]);
+}
+
+#[test]
+fn test_fasta_next_chunk_reports_errors() {
+    let content = b">seq1\nACGT\n>seq2\nGG\n".to_vec();
+    let mut reader =
+        FastaReader::from_reader_with_capacity(std::io::Cursor::new(content), 64).unwrap();
+    let chunk = reader.next_chunk(10).unwrap();
+    assert_eq!(chunk.len(), 2);
+    assert_eq!(chunk[1].sequence, "GG");
+
+    let mut reader =
+        FastaReader::from_reader_with_capacity(std::io::Cursor::new(b"ACGT\n".to_vec()), 64)
+            .unwrap();
+    assert!(reader.next_chunk(10).is_err());
+}
+
+#[test]
+fn test_fasta_reader_from_memory() {
+    let input = ">seq1 first\nACGT\nAC\n>seq2\nGG\n";
+    let from_string: Vec<FastaRecord> = FastaReader::from_string(input)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(from_string.len(), 2);
+    assert_eq!(from_string[0].sequence, "ACGTAC");
+
+    let from_bytes: Vec<FastaRecord> = FastaReader::from_bytes(input.as_bytes())
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(from_bytes, from_string);
+}
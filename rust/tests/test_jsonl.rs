@@ -0,0 +1,146 @@
+// Tests for JSON Lines input and output, and the serde derives
+#![cfg(feature = "serde")]
+use prseq::any::AnyRecord;
+use prseq::jsonl::{JsonlReader, JsonlWriter};
+use prseq::{FastaRecord, FastqRecord};
+
+fn fasta(header: &str, sequence: &str) -> FastaRecord {
+    FastaRecord {
+        header: header.to_string(),
+        sequence: sequence.to_string(),
+    }
+}
+
+fn fastq(header: &str, sequence: &str, quality: &str) -> FastqRecord {
+    FastqRecord {
+        header: header.to_string(),
+        sequence: sequence.to_string(),
+        quality: quality.to_string(),
+    }
+}
+
+#[test]
+fn test_jsonl_writer_output() {
+    let mut writer = JsonlWriter::new(Vec::new());
+    writer.write_fasta(&fasta("s1", "ACGT")).unwrap();
+    writer.write_fastq(&fastq("r1 1:N:0", "GG", "I#")).unwrap();
+    let output = String::from_utf8(writer.into_inner()).unwrap();
+    assert_eq!(
+        output,
+        "{\"id\":\"s1\",\"sequence\":\"ACGT\"}\n\
+         {\"id\":\"r1\",\"description\":\"1:N:0\",\"sequence\":\"GG\",\"quality\":\"I#\"}\n"
+    );
+}
+
+#[test]
+fn test_jsonl_round_trip() {
+    let records = vec![
+        AnyRecord::Fasta(fasta("s1 with \"quotes\"\tand tab", "ACGT")),
+        AnyRecord::Fastq(fastq("r1", "ACG", "I\\I")),
+    ];
+    let mut writer = JsonlWriter::new(Vec::new());
+    for record in &records {
+        writer.write_record(record).unwrap();
+    }
+    let output = writer.into_inner();
+    let read: Vec<AnyRecord> = JsonlReader::from_reader(std::io::Cursor::new(output))
+        .unwrap()
+        .collect::<prseq::error::Result<_>>()
+        .unwrap();
+    assert_eq!(read, records);
+}
+
+#[test]
+fn test_jsonl_reader_skips_blank_lines_and_extra_fields() {
+    let input = b"\n{\"id\": \"s1\", \"sequence\": \"AC\", \"length\": 2}\n\n";
+    let records: Vec<_> = JsonlReader::from_reader(&input[..])
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records, [AnyRecord::Fasta(fasta("s1", "AC"))]);
+}
+
+#[test]
+fn test_jsonl_reader_errors() {
+    let input = b"{\"id\": \"s1\", \"sequence\": \"AC\"}\n{\"id\": \"s2\"}\n";
+    let mut reader = JsonlReader::from_reader(&input[..]).unwrap();
+    assert!(reader.next().unwrap().is_ok());
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(error.to_string().contains("Invalid JSONL record"));
+    assert!(error.to_string().contains("missing field `sequence`"));
+    let position = error.position().unwrap();
+    assert_eq!((position.line, position.record), (2, 2));
+}
+
+#[test]
+fn test_jsonl_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("records.jsonl");
+    let mut writer = JsonlWriter::to_file(&path).unwrap();
+    writer.write_fasta(&fasta("s1", "ACGT")).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+    let records: Vec<_> = JsonlReader::from_file(&path)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records[0].sequence(), "ACGT");
+}
+
+#[test]
+fn test_record_serde_derives() {
+    let record = fastq("r1", "ACGT", "IIII");
+    let json = serde_json::to_string(&record).unwrap();
+    assert_eq!(
+        json,
+        "{\"header\":\"r1\",\"sequence\":\"ACGT\",\"quality\":\"IIII\"}"
+    );
+    assert_eq!(serde_json::from_str::<FastqRecord>(&json).unwrap(), record);
+
+    let record = fasta("s1", "ACGT");
+    let json = serde_json::to_string(&record).unwrap();
+    assert_eq!(serde_json::from_str::<FastaRecord>(&json).unwrap(), record);
+}
+
+#[test]
+fn test_stats_serde_derives() {
+    let reader = prseq::FastqReader::from_reader_with_capacity(
+        &b"@r1\nACGT\n+\nII5#\n@r2\nGG\n+\nII\n"[..],
+        64,
+    )
+    .unwrap();
+    let stats = prseq::stats::collect_stats(reader).unwrap();
+    let json = serde_json::to_string(&stats).unwrap();
+    assert_eq!(
+        serde_json::from_str::<prseq::stats::SeqStats>(&json).unwrap(),
+        stats
+    );
+
+    let mut profile = prseq::stats::QualityProfile::new();
+    profile.add(b"ACG", b"II5");
+    let json = serde_json::to_string(&profile).unwrap();
+    assert_eq!(
+        serde_json::from_str::<prseq::stats::QualityProfile>(&json).unwrap(),
+        profile
+    );
+}
+
+#[test]
+fn test_error_serialization() {
+    let position = prseq::error::Position { line: 3, record: 2 };
+    let json = serde_json::to_string(&position).unwrap();
+    assert_eq!(json, "{\"line\":3,\"record\":2}");
+    assert_eq!(
+        serde_json::from_str::<prseq::error::Position>(&json).unwrap(),
+        position
+    );
+
+    let mut reader =
+        prseq::FastqReader::from_reader_with_capacity(&b"@r1\nACG\n+\nIIII\n"[..], 64).unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    let value = serde_json::to_value(&error).unwrap();
+    assert_eq!(value["kind"], "InvalidData");
+    assert_eq!(value["message"], error.to_string());
+    assert_eq!(value["position"]["record"], 1);
+    assert!(value["path"].is_null());
+}
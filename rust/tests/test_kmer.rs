@@ -0,0 +1,262 @@
+// Tests for k-mer iteration
+use prseq::kmer::{canonical_kmers, kmers, CanonicalKmer, CountMinSketch, Counter};
+use prseq::sequence::reverse_complement;
+use prseq::FastaReader;
+use prseq::{FastaRecord, FastqRecord};
+use std::collections::HashSet;
+
+#[test]
+fn test_kmers() {
+    let kmers: Vec<&[u8]> = kmers(b"ACGTN", 2).collect();
+    assert_eq!(kmers, [b"AC", b"CG", b"GT", b"TN"]);
+}
+
+#[test]
+fn test_kmers_whole_sequence() {
+    let kmers: Vec<&[u8]> = kmers(b"ACG", 3).collect();
+    assert_eq!(kmers, [b"ACG"]);
+}
+
+#[test]
+fn test_kmers_longer_than_sequence() {
+    assert_eq!(kmers(b"ACG", 4).count(), 0);
+    assert_eq!(kmers(b"", 1).count(), 0);
+}
+
+#[test]
+fn test_kmers_zero_k() {
+    assert_eq!(kmers(b"ACG", 0).count(), 0);
+    assert_eq!(kmers(b"ACG", 0).len(), 0);
+}
+
+#[test]
+fn test_kmers_exact_size() {
+    let mut iter = kmers(b"ACGTACGT", 3);
+    assert_eq!(iter.len(), 6);
+    iter.next();
+    assert_eq!(iter.len(), 5);
+    assert_eq!(iter.by_ref().count(), 5);
+    assert_eq!(iter.len(), 0);
+}
+
+#[test]
+fn test_canonical_kmer() {
+    let forward = CanonicalKmer::new(b"AAC");
+    assert!(!forward.is_reverse());
+    assert_eq!(forward.to_vec(), b"AAC");
+    let reverse = CanonicalKmer::new(b"GTT");
+    assert!(reverse.is_reverse());
+    assert_eq!(reverse.window(), b"GTT");
+    assert_eq!(reverse.to_vec(), b"AAC");
+    assert_eq!(forward, reverse);
+}
+
+#[test]
+fn test_canonical_kmer_palindrome() {
+    let kmer = CanonicalKmer::new(b"ACGT");
+    assert!(!kmer.is_reverse());
+    assert_eq!(kmer.to_string(), "ACGT");
+}
+
+#[test]
+fn test_canonical_kmer_ignores_case() {
+    assert_eq!(CanonicalKmer::new(b"gtt"), CanonicalKmer::new(b"AAC"));
+    assert_eq!(CanonicalKmer::new(b"gtt").to_string(), "AAC");
+}
+
+#[test]
+fn test_canonical_kmer_ordering() {
+    let mut kmers = [
+        CanonicalKmer::new(b"TTT"),
+        CanonicalKmer::new(b"CCA"),
+        CanonicalKmer::new(b"ACA"),
+    ];
+    kmers.sort();
+    let sorted: Vec<String> = kmers.iter().map(|kmer| kmer.to_string()).collect();
+    // TTT is AAA, CCA is TGG
+    assert_eq!(sorted, ["AAA", "ACA", "CCA"]);
+}
+
+#[test]
+fn test_canonical_kmers_hash() {
+    // Both strands of a sequence have the same canonical k-mers
+    let forward: HashSet<CanonicalKmer> = canonical_kmers(b"AACGGTCA", 4).collect();
+    let reverse: HashSet<CanonicalKmer> = canonical_kmers(b"TGACCGTT", 4).collect();
+    assert_eq!(forward, reverse);
+    assert_eq!(forward.len(), 5);
+}
+
+#[test]
+fn test_record_kmers() {
+    let fasta = FastaRecord {
+        header: "s1".to_string(),
+        sequence: "ACGTT".to_string(),
+    };
+    let fastq = FastqRecord {
+        header: "r1".to_string(),
+        sequence: "ACGTT".to_string(),
+        quality: "IIIII".to_string(),
+    };
+    assert_eq!(
+        fasta.kmers(3).collect::<Vec<_>>(),
+        fastq.kmers(3).collect::<Vec<_>>()
+    );
+    let canonical: Vec<String> = fasta.canonical_kmers(3).map(|k| k.to_string()).collect();
+    assert_eq!(canonical, ["ACG", "ACG", "AAC"]);
+    assert_eq!(fastq.canonical_kmers(3).count(), 3);
+}
+
+/// Counts found by counting every k-mer window directly
+fn naive_counts(sequence: &[u8], k: usize, canonical: bool) -> Vec<(Vec<u8>, u64)> {
+    let mut counts = std::collections::BTreeMap::new();
+    for kmer in kmers(sequence, k) {
+        let kmer: Vec<u8> = kmer.to_ascii_uppercase();
+        if !kmer.iter().all(|base| b"ACGT".contains(base)) {
+            continue;
+        }
+        let kmer = if canonical {
+            kmer.clone().min(reverse_complement(&kmer))
+        } else {
+            kmer
+        };
+        *counts.entry(kmer).or_insert(0) += 1;
+    }
+    counts.into_iter().collect()
+}
+
+const SEQUENCE: &[u8] = b"ACGTTGCAAGGCTTANACGTTGCAAGGCTTAACCGGTTAAACGTTGCAAGGCTTAGGCAT";
+
+#[test]
+fn test_counter_matches_naive_counts() {
+    for k in [1, 3, 5, 31, 32, 33, 40] {
+        for canonical in [false, true] {
+            let mut counter = Counter::new(k).canonical(canonical);
+            counter.add(SEQUENCE);
+            assert_eq!(
+                counter.counts(),
+                naive_counts(SEQUENCE, k, canonical),
+                "k = {k}, canonical = {canonical}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_counter_skips_ambiguous_kmers() {
+    let mut counter = Counter::new(3);
+    counter.add(b"ACGNACG");
+    assert_eq!(counter.total(), 2);
+    assert_eq!(counter.distinct(), 1);
+    assert_eq!(counter.count(b"ACG"), 2);
+    assert_eq!(counter.count(b"CGN"), 0);
+}
+
+#[test]
+fn test_counter_case_and_u() {
+    let mut counter = Counter::new(3);
+    counter.add(b"acgu");
+    assert_eq!(
+        counter.counts(),
+        [(b"ACG".to_vec(), 1), (b"CGT".to_vec(), 1)]
+    );
+    assert_eq!(counter.count(b"cgu"), 1);
+}
+
+#[test]
+fn test_counter_canonical_queries() {
+    for k in [3, 35] {
+        let mut counter = Counter::new(k).canonical(true);
+        let kmer = &SEQUENCE[16..16 + k];
+        counter.add(kmer);
+        assert_eq!(counter.count(kmer), 1);
+        assert_eq!(counter.count(&reverse_complement(kmer)), 1);
+    }
+}
+
+#[test]
+fn test_counter_wrong_length_query() {
+    let mut counter = Counter::new(3);
+    counter.add(b"ACGT");
+    assert_eq!(counter.count(b"AC"), 0);
+}
+
+#[test]
+fn test_counter_collect_reader() {
+    let reader = FastaReader::from_string(">a\nACGT\n>b\nCGTA\n").unwrap();
+    let counter = Counter::new(3).collect(reader).unwrap();
+    assert_eq!(counter.k(), 3);
+    assert_eq!(
+        counter.counts(),
+        [
+            (b"ACG".to_vec(), 1),
+            (b"CGT".to_vec(), 2),
+            (b"GTA".to_vec(), 1)
+        ]
+    );
+}
+
+#[test]
+fn test_counter_collect_stops_at_error() {
+    let reader = FastaReader::from_string("ACGT\n").unwrap();
+    assert!(Counter::new(3).collect(reader).is_err());
+}
+
+#[test]
+fn test_counter_extend_and_merge() {
+    let records = [
+        FastaRecord {
+            header: "a".to_string(),
+            sequence: "ACGT".to_string(),
+        },
+        FastaRecord {
+            header: "b".to_string(),
+            sequence: "ACGA".to_string(),
+        },
+    ];
+    let mut first = Counter::new(3);
+    first.extend(records[..1].iter().cloned());
+    let mut second = Counter::new(3);
+    second.extend(records[1..].iter().cloned());
+    first.merge(&second);
+    let mut both = Counter::new(3);
+    both.extend(records);
+    assert_eq!(first.counts(), both.counts());
+    assert_eq!(first.total(), 4);
+}
+
+#[test]
+#[should_panic]
+fn test_counter_merge_different_k() {
+    Counter::new(3).merge(&Counter::new(4));
+}
+
+#[test]
+fn test_count_min_sketch_never_undercounts() {
+    for k in [4, 36] {
+        let mut counter = Counter::new(k);
+        // A narrow sketch, so that k-mers share cells
+        let mut sketch = CountMinSketch::new(k, 16, 3);
+        counter.add(SEQUENCE);
+        sketch.add(SEQUENCE);
+        assert_eq!(sketch.total(), counter.total());
+        for (kmer, count) in counter.counts() {
+            assert!(sketch.count(&kmer) >= count);
+        }
+    }
+}
+
+#[test]
+fn test_count_min_sketch_exact_when_wide() {
+    let mut counter = Counter::new(5).canonical(true);
+    let mut sketch = CountMinSketch::new(5, 1 << 16, 4).canonical(true);
+    counter.add(SEQUENCE);
+    sketch.extend([FastaRecord {
+        header: "s".to_string(),
+        sequence: String::from_utf8(SEQUENCE.to_vec()).unwrap(),
+    }]);
+    for (kmer, count) in counter.counts() {
+        assert_eq!(sketch.count(&kmer), count);
+        assert_eq!(sketch.count(&reverse_complement(&kmer)), count);
+    }
+    assert_eq!(sketch.count(b"ACGTN"), 0);
+}
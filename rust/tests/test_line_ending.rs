@@ -0,0 +1,170 @@
+// Tests for reading and writing "\n", "\r\n" and lone "\r" line endings
+use prseq::{
+    FastaReader, FastaRecord, FastaWriter, FastqReader, FastqRecord, FastqWriter, LineEnding,
+    LineEndings, PrseqError,
+};
+use std::io::{Cursor, Read};
+
+/// Input that is read one byte at a time, so that every line straddles a
+/// buffer refill (including between the "\r" and "\n" of a "\r\n")
+struct ByteAtATime(Cursor<Vec<u8>>);
+
+impl Read for ByteAtATime {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = buf.len().min(1);
+        self.0.read(&mut buf[..length])
+    }
+}
+
+fn fasta(input: &[u8]) -> Vec<FastaRecord> {
+    let whole = FastaReader::from_bytes(input).unwrap();
+    let records: Vec<_> = whole.map(|record| record.unwrap()).collect();
+    let bytes = ByteAtATime(Cursor::new(input.to_vec()));
+    let split = FastaReader::from_reader_with_capacity(bytes, 64).unwrap();
+    let split: Vec<_> = split.map(|record| record.unwrap()).collect();
+    assert_eq!(records, split);
+    records
+}
+
+fn fastq(input: &[u8]) -> Vec<FastqRecord> {
+    let whole = FastqReader::from_bytes(input).unwrap();
+    let records: Vec<_> = whole.map(|record| record.unwrap()).collect();
+    let bytes = ByteAtATime(Cursor::new(input.to_vec()));
+    let split = FastqReader::from_reader_with_capacity(bytes, 64).unwrap();
+    let split: Vec<_> = split.map(|record| record.unwrap()).collect();
+    assert_eq!(records, split);
+    records
+}
+
+fn fasta_record(header: &str, sequence: &str) -> FastaRecord {
+    FastaRecord {
+        header: header.into(),
+        sequence: sequence.into(),
+    }
+}
+
+#[test]
+fn test_fasta_line_endings() {
+    let expected = [fasta_record("a one", "ACGTAC"), fasta_record("b", "GG")];
+    for input in [
+        &b">a one\nACGT\nAC\n>b\nGG\n"[..],
+        b">a one\r\nACGT\r\nAC\r\n>b\r\nGG\r\n",
+        b">a one\rACGT\rAC\r>b\rGG\r",
+        b">a one\r\nACGT\rAC\n\r\n>b\rGG",
+    ] {
+        assert_eq!(fasta(input), expected);
+    }
+}
+
+#[test]
+fn test_fastq_line_endings() {
+    for input in [
+        &b"@r1\r\nACGT\r\n+\r\nIIII\r\n@r2\r\nGG\r\n+r2\r\nJJ\r\n"[..],
+        b"@r1\rACGT\r+\rIIII\r@r2\rGG\r+r2\rJJ",
+        b"@r1\rAC\r\nGT\n+\r\nII\rII\r@r2\nGG\r\n+r2\rJJ\r\n",
+    ] {
+        let records = fastq(input);
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            (records[0].sequence.as_str(), records[0].quality.as_str()),
+            ("ACGT", "IIII")
+        );
+        assert_eq!(records[1].header, "r2");
+    }
+}
+
+#[test]
+fn test_strict_fastq_with_lone_cr() {
+    let reader = FastqReader::from_bytes(b"@r1\rACGT\r+\rIIII\r@r2\rG\r+\rJ\r")
+        .unwrap()
+        .with_options(prseq::ParserOptions::default().strict_four_line(true));
+    let headers: Vec<String> = reader.map(|record| record.unwrap().header).collect();
+    assert_eq!(headers, ["r1", "r2"]);
+}
+
+#[test]
+fn test_line_endings_report() {
+    let mut reader = FastaReader::from_bytes(b">a\r\nAC\r\nGT\n>b\r\nCC").unwrap();
+    assert_eq!(reader.line_endings(), LineEndings::default());
+    assert_eq!(reader.line_endings().style(), None);
+    reader.by_ref().for_each(drop);
+    let endings = reader.line_endings();
+    assert_eq!(
+        endings,
+        LineEndings {
+            lf: 1,
+            crlf: 3,
+            cr: 0
+        }
+    );
+    assert_eq!(endings.style(), Some(LineEnding::CrLf));
+    assert!(endings.is_mixed());
+
+    let mut reader = FastqReader::from_bytes(b"@r1\rA\r+\rI\r").unwrap();
+    reader.next().unwrap().unwrap();
+    assert_eq!(reader.line_endings().style(), Some(LineEnding::Cr));
+    assert!(!reader.line_endings().is_mixed());
+
+    let tie = LineEndings {
+        lf: 2,
+        crlf: 2,
+        cr: 1,
+    };
+    assert_eq!(tie.style(), Some(LineEnding::Lf));
+}
+
+#[test]
+fn test_offsets_and_line_numbers_count_every_terminator() {
+    let input = b">a\r\nAC\rGT\r\n>b\rCC\n>c\nTT\n";
+    let mut reader = FastaReader::from_bytes(input).unwrap();
+    reader.next().unwrap().unwrap();
+    reader.next().unwrap().unwrap();
+    assert_eq!(reader.offset(), Some(11));
+    assert_eq!(reader.tell(), 17);
+
+    let error = FastqReader::from_bytes(b"@r1\rAC\r+\rII\rr2\rGG\r")
+        .unwrap()
+        .nth(1)
+        .unwrap()
+        .unwrap_err();
+    assert!(matches!(error, PrseqError::InvalidFastq { .. }));
+    assert_eq!(error.position().unwrap().line, 5);
+}
+
+#[test]
+fn test_writers_preserve_line_endings() {
+    let input = b">a\r\nACGTAC\r\n>b\r\nGG\r\n";
+    let mut reader = FastaReader::from_bytes(input).unwrap();
+    let records: Vec<_> = reader.by_ref().map(|record| record.unwrap()).collect();
+    let ending = reader.line_endings().style().unwrap_or_default();
+    let mut writer = FastaWriter::new(Vec::new()).line_ending(ending);
+    for record in &records {
+        writer.write_record(record).unwrap();
+    }
+    assert_eq!(writer.into_inner(), input);
+
+    let mut writer = FastaWriter::with_line_width(Vec::new(), 4).line_ending(LineEnding::Cr);
+    writer.write_record(&records[0]).unwrap();
+    assert_eq!(writer.into_inner(), b">a\rACGT\rAC\r");
+
+    let input = b"@r1\r\nACGT\r\n+\r\nIIII\r\n";
+    let mut reader = FastqReader::from_bytes(input).unwrap();
+    let record = reader.next().unwrap().unwrap();
+    let ending = reader.line_endings().style().unwrap_or_default();
+    let mut writer = FastqWriter::new(Vec::new()).line_ending(ending);
+    writer.write_record(&record).unwrap();
+    assert_eq!(writer.into_inner(), input);
+}
+
+#[test]
+fn test_writers_default_to_lf() {
+    let mut writer = FastqWriter::new(Vec::new());
+    let record = FastqRecord {
+        header: "r1".into(),
+        sequence: "AC".into(),
+        quality: "II".into(),
+    };
+    writer.write_record(&record).unwrap();
+    assert_eq!(writer.into_inner(), b"@r1\nAC\n+\nII\n");
+    assert_eq!(LineEnding::default().as_bytes(), b"\n");
+}
@@ -0,0 +1,122 @@
+// Tests for pairing records with the location they were read from
+use prseq::location::Located;
+use prseq::{FastaReader, FastqReader, Location, OnError, ParserOptions, Position, ReaderBuilder};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const FASTA: &[u8] = b"\n>a\nACGT\nAC\n>b\nGG\n\n>c\nTT\n";
+const FASTQ: &[u8] = b"@r1\nACGT\n+\nIIII\n@r2\nGG\n+\nII\n";
+
+fn write(dir: &TempDir, name: &str, data: &[u8]) -> PathBuf {
+    let path = dir.path().join(name);
+    std::fs::write(&path, data).unwrap();
+    path
+}
+
+/// The line number and record index of each location
+fn lines_and_indexes(locations: &[Location]) -> Vec<(u64, u64)> {
+    locations
+        .iter()
+        .map(|location| (location.line_number, location.record_index))
+        .collect()
+}
+
+#[test]
+fn test_fasta_locations() {
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "seqs.fasta", FASTA);
+    let (locations, records): (Vec<_>, Vec<_>) = FastaReader::from_file(&path)
+        .unwrap()
+        .with_locations()
+        .map(|result| result.unwrap())
+        .unzip();
+    assert_eq!(records.len(), 3);
+    assert_eq!(lines_and_indexes(&locations), [(2, 0), (5, 1), (8, 2)]);
+    assert!(locations
+        .iter()
+        .all(|location| location.file.as_deref() == Some(path.as_path())));
+}
+
+#[test]
+fn test_fastq_locations_without_a_file() {
+    let locations: Vec<_> = FastqReader::from_bytes(FASTQ)
+        .unwrap()
+        .with_locations()
+        .map(|result| result.unwrap().0)
+        .collect();
+    assert_eq!(lines_and_indexes(&locations), [(1, 0), (5, 1)]);
+    assert_eq!(locations[0].file, None);
+}
+
+#[test]
+fn test_location_display_and_position() {
+    let location = Location {
+        file: Some(Path::new("reads.fastq").into()),
+        line_number: 9,
+        record_index: 2,
+    };
+    assert_eq!(location.to_string(), "reads.fastq, line 9, record 3");
+    assert_eq!(location.position(), Position { line: 9, record: 3 });
+    let location = Location {
+        file: None,
+        ..location
+    };
+    assert_eq!(location.to_string(), "line 9, record 3");
+}
+
+#[test]
+fn test_locations_count_skipped_records() {
+    let input = b"@r1\nACGT\n+\nIIII\n@r2\nGG\n+r9\nII\n@r3\nTT\n+\nII\n";
+    let reader = FastqReader::from_bytes(input)
+        .unwrap()
+        .with_options(ParserOptions::default().on_error(OnError::Skip));
+    let located: Vec<_> = reader
+        .with_locations()
+        .map(|result| result.unwrap())
+        .collect();
+    assert_eq!(located.len(), 2);
+    assert_eq!(located[1].1.header, "r3");
+    assert_eq!(
+        (located[1].0.line_number, located[1].0.record_index),
+        (9, 2)
+    );
+}
+
+#[test]
+fn test_location_after_peek() {
+    let mut reader = FastaReader::from_bytes(FASTA).unwrap();
+    reader.next().unwrap().unwrap();
+    reader.peek();
+    let mut located = reader.with_locations();
+    let (location, record) = located.next().unwrap().unwrap();
+    assert_eq!(record.header, "b");
+    assert_eq!((location.line_number, location.record_index), (5, 1));
+}
+
+#[test]
+fn test_locations_across_files() {
+    let dir = TempDir::new().unwrap();
+    let first = write(&dir, "1.fastq", FASTQ);
+    let second = write(&dir, "2.fastq", b"\n@r3\nA\n+\nI\n");
+    let located: Vec<_> = FastqReader::from_paths(&[&first, &second])
+        .with_locations()
+        .map(|result| result.unwrap().0)
+        .collect();
+    assert_eq!(lines_and_indexes(&located), [(1, 0), (5, 1), (2, 0)]);
+    assert_eq!(located[1].file.as_deref(), Some(first.as_path()));
+    assert_eq!(located[2].file.as_deref(), Some(second.as_path()));
+}
+
+#[test]
+fn test_readers_know_their_paths() {
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "seqs.fasta", FASTA);
+    assert_eq!(
+        FastaReader::from_file(&path).unwrap().path(),
+        Some(path.as_path())
+    );
+    assert_eq!(FastaReader::from_bytes(FASTA).unwrap().path(), None);
+    let mut reader = ReaderBuilder::new().path(&path).build_fasta().unwrap();
+    reader.next().unwrap().unwrap();
+    assert_eq!(reader.location().file.as_deref(), Some(path.as_path()));
+}
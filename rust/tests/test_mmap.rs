@@ -0,0 +1,65 @@
+// Tests for the memory-mapped FASTA reader
+#![cfg(feature = "mmap")]
+use prseq::mmap::MmapFastaRecords;
+use prseq::{FastaReader, MmapFastaReader, Position, PrseqError};
+use std::borrow::Cow;
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+const FASTA: &[u8] = b"\n>seq1 first\nACGT\nGG\r\n\n>seq2\nTTTT\n  >seq3\n>seq4\r\nCC";
+
+#[test]
+fn test_mmap_records_match_fasta_reader() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(FASTA).unwrap();
+    file.flush().unwrap();
+
+    let reader = MmapFastaReader::from_file(file.path()).unwrap();
+    let records: Vec<_> = reader
+        .records()
+        .map(|r| r.unwrap().to_record().unwrap())
+        .collect();
+    let expected: Vec<_> = FastaReader::from_file(file.path())
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records, expected);
+}
+
+#[test]
+fn test_mmap_records_borrow_single_line_sequences() {
+    let records: Vec<_> = MmapFastaRecords::new(FASTA).map(|r| r.unwrap()).collect();
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[0].header(), b"seq1 first");
+    assert_eq!(records[0].raw_sequence(), b"ACGT\nGG");
+    assert_eq!(
+        records[0].sequence(),
+        Cow::<[u8]>::Owned(b"ACGTGG".to_vec())
+    );
+    assert!(matches!(records[1].sequence(), Cow::Borrowed(b"TTTT")));
+    assert_eq!(records[2].raw_sequence(), b"");
+    assert_eq!(records[3].sequence(), Cow::<[u8]>::Borrowed(b"CC"));
+}
+
+#[test]
+fn test_mmap_records_invalid_start() {
+    let mut records = MmapFastaRecords::new(b"\nACGT\n>seq1\nAC\n");
+    let error = records.next().unwrap().unwrap_err();
+    assert!(matches!(error, PrseqError::InvalidFasta { .. }));
+    assert_eq!(error.position(), Some(Position { line: 2, record: 1 }));
+    assert!(records.next().is_none());
+}
+
+#[test]
+fn test_mmap_records_with_lone_cr() {
+    let data = b">seq1 first\rACGT\rGG\r\r>seq2\r\nTT\r\n";
+    let records: Vec<_> = MmapFastaRecords::new(data)
+        .map(|r| r.unwrap().to_record().unwrap())
+        .collect();
+    let expected: Vec<_> = FastaReader::from_bytes(data)
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records, expected);
+    assert_eq!(records[0].sequence, "ACGTGG");
+}
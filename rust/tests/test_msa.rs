@@ -0,0 +1,310 @@
+// Tests for the Clustal, PHYLIP and Stockholm alignment readers and for
+// Alignment
+use prseq::error::{PrseqError, Result};
+use prseq::msa::{
+    Alignment, AlignmentFormat, AlignmentReader, AnyAlignmentReader, ClustalReader, PhylipReader,
+    StockholmReader,
+};
+use prseq::FastaRecord;
+
+fn sequences(records: &[FastaRecord]) -> Vec<(&str, &str)> {
+    records
+        .iter()
+        .map(|record| (record.header.as_str(), record.sequence.as_str()))
+        .collect()
+}
+
+const CLUSTAL: &[u8] = b"CLUSTAL O(1.2.4) multiple sequence alignment
+
+
+seqA      MKV-LLAG 7
+seqB      MKVALL-G 7
+          *** ** *
+
+seqA      TT--
+seqB      TTAA 9
+          **
+";
+
+#[test]
+fn test_clustal_blocks() {
+    let mut reader = ClustalReader::from_reader(CLUSTAL).unwrap();
+    let records = reader.read_alignment().unwrap().unwrap();
+    assert_eq!(
+        sequences(&records),
+        [("seqA", "MKV-LLAGTT--"), ("seqB", "MKVALL-GTTAA")]
+    );
+    assert!(reader.read_alignment().unwrap().is_none());
+}
+
+#[test]
+fn test_clustal_muscle_header() {
+    let input = b"MUSCLE (3.8) multiple sequence alignment\n\ns1 AC-G\ns2 ACTG\n";
+    let records: Vec<_> = ClustalReader::from_reader(&input[..])
+        .unwrap()
+        .records()
+        .collect::<Result<_>>()
+        .unwrap();
+    assert_eq!(sequences(&records), [("s1", "AC-G"), ("s2", "ACTG")]);
+}
+
+#[test]
+fn test_clustal_errors() {
+    let mut reader = ClustalReader::from_reader(&b"s1 ACGT\n"[..]).unwrap();
+    let error = reader.read_alignment().unwrap_err();
+    assert!(error.to_string().contains("must start with a 'CLUSTAL'"));
+
+    let input = b"CLUSTAL W\n\ns1 ACGT\ns2 ACG\n";
+    let mut reader = ClustalReader::from_reader(&input[..]).unwrap();
+    let error = reader.read_alignment().unwrap_err();
+    assert!(error.to_string().contains("differ in length"));
+
+    let input = b"CLUSTAL W\n\ns1\n";
+    let mut reader = ClustalReader::from_reader(&input[..]).unwrap();
+    let error = reader.read_alignment().unwrap_err();
+    assert!(error.to_string().contains("must have a name and residues"));
+    assert_eq!(error.position().unwrap().line, 3);
+}
+
+const STOCKHOLM: &[u8] = b"# STOCKHOLM 1.0
+#=GF ID   example
+#=GS seq1 DE First sequence
+
+seq1         ACG.UU
+seq2         AC-GUU
+#=GC SS_cons <<..>>
+
+seq1         GG
+seq2         G-
+//
+# STOCKHOLM 1.0
+seq3 AAA
+//
+";
+
+#[test]
+fn test_stockholm_alignments() {
+    let mut reader = StockholmReader::from_reader(STOCKHOLM).unwrap();
+    let records = reader.read_alignment().unwrap().unwrap();
+    assert_eq!(
+        sequences(&records),
+        [("seq1 First sequence", "ACG.UUGG"), ("seq2", "AC-GUUG-")]
+    );
+    let records = reader.read_alignment().unwrap().unwrap();
+    assert_eq!(sequences(&records), [("seq3", "AAA")]);
+    assert!(reader.read_alignment().unwrap().is_none());
+}
+
+#[test]
+fn test_stockholm_missing_terminator() {
+    let input = b"# STOCKHOLM 1.0\nseq1 ACGT\n";
+    let mut reader = StockholmReader::from_reader(&input[..]).unwrap();
+    let error = reader.read_alignment().unwrap_err();
+    assert!(error.to_string().contains("ended without '//'"));
+}
+
+#[test]
+fn test_phylip_sequential() {
+    let input = b" 3 10
+Turkey    AAGCTNGGGC
+Salmo_gair AAGCC TTGGC
+H._sapiens
+ACCGGTTGGA
+";
+    let mut reader = PhylipReader::from_reader(&input[..]).unwrap();
+    let records = reader.read_alignment().unwrap().unwrap();
+    assert_eq!(
+        sequences(&records),
+        [
+            ("Turkey", "AAGCTNGGGC"),
+            ("Salmo_gair", "AAGCCTTGGC"),
+            ("H._sapiens", "ACCGGTTGGA")
+        ]
+    );
+}
+
+#[test]
+fn test_phylip_sequential_over_several_lines() {
+    let input = b"2 8\ns1 ACGT\nACGT\ns2 AC--\nTTTT\n";
+    let mut reader = PhylipReader::from_reader(&input[..]).unwrap();
+    let records = reader.read_alignment().unwrap().unwrap();
+    assert_eq!(
+        sequences(&records),
+        [("s1", "ACGTACGT"), ("s2", "AC--TTTT")]
+    );
+}
+
+#[test]
+fn test_phylip_interleaved_and_several_datasets() {
+    let input = b"2 8
+s1 ACGT
+s2 AC-G
+
+AAAA
+T-TT
+2 3
+s1 AAA
+s2 CCC
+";
+    let mut reader = PhylipReader::from_reader(&input[..]).unwrap();
+    let records = reader.read_alignment().unwrap().unwrap();
+    assert_eq!(
+        sequences(&records),
+        [("s1", "ACGTAAAA"), ("s2", "AC-GT-TT")]
+    );
+    let records = reader.read_alignment().unwrap().unwrap();
+    assert_eq!(sequences(&records), [("s1", "AAA"), ("s2", "CCC")]);
+    assert!(reader.read_alignment().unwrap().is_none());
+}
+
+#[test]
+fn test_phylip_errors() {
+    let mut reader = PhylipReader::from_reader(&b"s1 ACGT\n"[..]).unwrap();
+    let error = reader.read_alignment().unwrap_err();
+    assert!(error.to_string().contains("numbers of sequences and sites"));
+
+    let mut reader = PhylipReader::from_reader(&b"2 4\ns1 ACGT\ns2 ACG\n"[..]).unwrap();
+    let error = reader.read_alignment().unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("does not have the 2 sequences of 4 sites"));
+    assert_eq!(error.position().unwrap().line, 1);
+}
+
+#[test]
+fn test_any_alignment_reader_detects_format() {
+    let reader = AnyAlignmentReader::from_reader(CLUSTAL).unwrap();
+    assert_eq!(reader.format(), Some(AlignmentFormat::Clustal));
+    assert_eq!(reader.records().count(), 2);
+
+    let reader = AnyAlignmentReader::from_reader(STOCKHOLM).unwrap();
+    assert_eq!(reader.format(), Some(AlignmentFormat::Stockholm));
+    assert_eq!(reader.records().count(), 3);
+
+    let reader = AnyAlignmentReader::from_reader(&b"\n 1 2\ns1 AC\n"[..]).unwrap();
+    assert_eq!(reader.format(), Some(AlignmentFormat::Phylip));
+    let records: Vec<_> = reader.records().collect::<Result<_>>().unwrap();
+    assert_eq!(sequences(&records), [("s1", "AC")]);
+
+    let mut reader = AnyAlignmentReader::from_reader(&b""[..]).unwrap();
+    assert_eq!(reader.format(), None);
+    assert!(reader.read_alignment().unwrap().is_none());
+
+    let error = AnyAlignmentReader::from_reader(&b"\n>s1\nACGT\n"[..])
+        .err()
+        .unwrap();
+    match error {
+        PrseqError::InvalidFormat {
+            format,
+            position,
+            content,
+            ..
+        } => {
+            assert_eq!(format, "alignment");
+            assert_eq!(position.line, 2);
+            assert_eq!(content, ">s1");
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[test]
+fn test_boxed_alignment_readers() {
+    let readers: Vec<Box<dyn AlignmentReader>> = vec![
+        Box::new(ClustalReader::from_reader(CLUSTAL).unwrap()),
+        Box::new(StockholmReader::from_reader(STOCKHOLM).unwrap()),
+    ];
+    let counts: Vec<usize> = readers
+        .into_iter()
+        .map(|reader| reader.records().count())
+        .collect();
+    assert_eq!(counts, [2, 3]);
+}
+
+fn alignment(sequences: &[&str]) -> Alignment {
+    let records = sequences
+        .iter()
+        .enumerate()
+        .map(|(i, sequence)| FastaRecord {
+            header: format!("s{}", i + 1),
+            sequence: sequence.to_string(),
+        })
+        .collect();
+    Alignment::new(records).unwrap()
+}
+
+#[test]
+fn test_alignment_requires_equal_lengths() {
+    let records = vec![
+        FastaRecord {
+            header: "s1".to_string(),
+            sequence: "ACGT".to_string(),
+        },
+        FastaRecord {
+            header: "s2".to_string(),
+            sequence: "ACG".to_string(),
+        },
+    ];
+    let error = Alignment::try_from(records).unwrap_err();
+    match &error {
+        PrseqError::InvalidFormat {
+            format, position, ..
+        } => {
+            assert_eq!(*format, "alignment");
+            assert_eq!(position.record, 2);
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(error.to_string().contains("'s2' has 3"));
+
+    let empty = Alignment::new(Vec::new()).unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(empty.width(), 0);
+    assert_eq!(empty.consensus(), "");
+}
+
+#[test]
+fn test_alignment_columns() {
+    let alignment = alignment(&["AC-T", "AG-T", "TG.T"]);
+    assert_eq!(alignment.len(), 3);
+    assert_eq!(alignment.width(), 4);
+    assert_eq!(alignment.column(1).unwrap(), b"CGG");
+    assert_eq!(alignment.column(4), None);
+    let columns: Vec<Vec<u8>> = alignment.columns().collect();
+    assert_eq!(columns[2], b"--.");
+    assert_eq!(columns.len(), 4);
+}
+
+#[test]
+fn test_alignment_remove_gap_only_columns() {
+    let mut alignment = alignment(&["-A-C.", "-G.-.", "-T-T-"]);
+    assert_eq!(alignment.remove_gap_only_columns(), 3);
+    assert_eq!(alignment.width(), 2);
+    assert_eq!(
+        sequences(alignment.records()),
+        [("s1", "AC"), ("s2", "G-"), ("s3", "TT")]
+    );
+    assert_eq!(alignment.remove_gap_only_columns(), 0);
+    assert_eq!(alignment.into_records().len(), 3);
+}
+
+#[test]
+fn test_alignment_consensus() {
+    let alignment = alignment(&["ACGT-a", "ACCA-g", "aTCA-G", "AT-A-N"]);
+    // Ties go to the residue that sorts first, and case is ignored
+    assert_eq!(alignment.consensus(), "ACCA-G");
+}
+
+#[test]
+fn test_alignment_iupac_consensus() {
+    let nucleotides = alignment(&["AACGT-A", "AGCGC-R", "A-TGA-Y", "AC-CT-A"]);
+    assert_eq!(nucleotides.iupac_consensus(), "AVYSH-N");
+    // Residues that are not nucleotide codes give N
+    assert_eq!(alignment(&["XA", "AA"]).iupac_consensus(), "NA");
+}
+
+#[test]
+fn test_alignment_column_identity() {
+    let alignment = alignment(&["AAC-", "AAG-", "aT--", "A-G-"]);
+    assert_eq!(alignment.column_identity(), [1.0, 0.5, 0.5, 0.0]);
+}
@@ -0,0 +1,62 @@
+// Tests for reading several files in turn
+use prseq::{FastaReader, FastqReader};
+use std::fs;
+use std::io::Write;
+
+#[test]
+fn test_from_paths_with_mixed_compression() {
+    let dir = tempfile::tempdir().unwrap();
+    let plain = dir.path().join("a.fa");
+    let gzipped = dir.path().join("b.fa.gz");
+    fs::write(&plain, b">seq1\nACGT\n>seq2\nGG").unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(b">seq3\nTTTT\n").unwrap();
+    fs::write(&gzipped, encoder.finish().unwrap()).unwrap();
+
+    let records: Vec<_> = FastaReader::from_paths(&[&plain, &gzipped])
+        .with_source()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[1].1.sequence, "GG");
+    assert_eq!(&*records[1].0, plain.as_path());
+    assert_eq!(records[2].1.header, "seq3");
+    assert_eq!(&*records[2].0, gzipped.as_path());
+}
+
+#[test]
+fn test_from_glob() {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, id) in [
+        ("r2.fastq", "read2"),
+        ("r1.fastq", "read1"),
+        ("x.txt", "no"),
+    ] {
+        fs::write(dir.path().join(name), format!("@{}\nAC\n+\nII\n", id)).unwrap();
+    }
+    let pattern = format!("{}/*.fastq", dir.path().display());
+    let ids: Vec<_> = FastqReader::from_glob(&pattern)
+        .unwrap()
+        .map(|r| r.unwrap().header)
+        .collect();
+    assert_eq!(ids, vec!["read1", "read2"]);
+
+    let pattern = format!("{}/*.fa", dir.path().display());
+    let error = FastqReader::from_glob(&pattern).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_errors_name_the_file_and_reading_continues() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.fa");
+    let good = dir.path().join("good.fa");
+    fs::write(&good, b">seq1\nACGT\n").unwrap();
+
+    let mut reader = FastaReader::from_paths(&[&missing, &good]);
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(error.to_string().contains("missing.fa"));
+    assert_eq!(reader.next().unwrap().unwrap().header, "seq1");
+    assert_eq!(reader.current_path(), Some(good.as_path()));
+    assert!(reader.next().is_none());
+}
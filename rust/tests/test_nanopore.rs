@@ -0,0 +1,134 @@
+// Tests for nanopore read header parsing and filtering
+use prseq::nanopore::NanoporeFilter;
+use prseq::{FastqReader, FilterExt, NanoporeHeader};
+use std::io::Cursor;
+
+const GUPPY: &str = "0e3c4b7a-5f1d-4c8e-9a2b-7d6f1e0c3b9a runid=8f2e1d0c read=5512 ch=118 \
+    start_time=2023-05-04T10:15:30Z flow_cell_id=FAT12345 protocol_group_id=run1 \
+    sample_id=sampleA barcode=barcode07 basecall_model_version_id=dna_r10.4.1_e8.2_400bps_hac@v4.2.0";
+
+fn reads() -> String {
+    let headers = [
+        "r1 runid=x read=1 ch=5 start_time=2023-05-04T10:00:00Z barcode=barcode01",
+        "r2 runid=x read=2 ch=300 start_time=2023-05-04T11:00:00Z barcode=barcode01",
+        "r3 runid=x read=3 ch=12 start_time=2023-05-04T12:30:00Z barcode=barcode02",
+        "r4 runid=x read=4 ch=40 start_time=2023-05-04T09:00:00Z barcode=unclassified",
+        "r5 runid=x read=5 start_time=2023-05-04T10:00:00Z",
+        "r6",
+    ];
+    headers
+        .iter()
+        .map(|header| format!("@{header}\nACGT\n+\nIIII\n"))
+        .collect()
+}
+
+fn filtered(filter: NanoporeFilter) -> Vec<String> {
+    FastqReader::from_reader_with_capacity(Cursor::new(reads()), 64)
+        .unwrap()
+        .filter_nanopore(filter)
+        .map(|record| record.unwrap().id().to_string())
+        .collect()
+}
+
+#[test]
+fn test_parse_guppy_header() {
+    let header = NanoporeHeader::parse(GUPPY).unwrap();
+    assert_eq!(
+        header,
+        NanoporeHeader {
+            read_id: "0e3c4b7a-5f1d-4c8e-9a2b-7d6f1e0c3b9a".to_string(),
+            run_id: Some("8f2e1d0c".to_string()),
+            read: Some(5512),
+            channel: Some(118),
+            start_time: Some("2023-05-04T10:15:30Z".to_string()),
+            flow_cell_id: Some("FAT12345".to_string()),
+            sample_id: Some("sampleA".to_string()),
+            barcode: Some("barcode07".to_string()),
+            basecall_model: Some("dna_r10.4.1_e8.2_400bps_hac@v4.2.0".to_string()),
+            other: vec![("protocol_group_id".to_string(), "run1".to_string())],
+        }
+    );
+}
+
+#[test]
+fn test_parse_partial_header() {
+    let header = NanoporeHeader::parse("read1 sampleid=old ch=3").unwrap();
+    assert_eq!(header.read_id, "read1");
+    assert_eq!(header.channel, Some(3));
+    assert_eq!(header.sample_id.as_deref(), Some("old"));
+    assert_eq!(header.run_id, None);
+    assert_eq!(header.barcode, None);
+}
+
+#[test]
+fn test_parse_non_nanopore_headers() {
+    assert_eq!(NanoporeHeader::parse("read1"), None);
+    assert_eq!(NanoporeHeader::parse("read1 some description"), None);
+    assert_eq!(
+        NanoporeHeader::parse("M00123:45:FC1:1:1101:15589:1333 1:N:0:ATCACG"),
+        None
+    );
+    assert_eq!(NanoporeHeader::parse("read1 ch=five"), None);
+    assert_eq!(NanoporeHeader::parse("read1 read=-1"), None);
+}
+
+#[test]
+fn test_record_parse_nanopore_header() {
+    let mut reader = FastqReader::from_reader_with_capacity(
+        Cursor::new(format!("@{GUPPY}\nACGT\n+\nIIII\n")),
+        64,
+    )
+    .unwrap();
+    let header = reader
+        .next()
+        .unwrap()
+        .unwrap()
+        .parse_nanopore_header()
+        .unwrap();
+    assert_eq!(header.read, Some(5512));
+}
+
+#[test]
+fn test_filter_by_channel() {
+    assert_eq!(
+        filtered(NanoporeFilter::new().channels(1..=256)),
+        ["r1", "r3", "r4"]
+    );
+}
+
+#[test]
+fn test_filter_by_barcode() {
+    assert_eq!(
+        filtered(NanoporeFilter::new().barcodes(["barcode01"])),
+        ["r1", "r2"]
+    );
+    assert_eq!(
+        filtered(NanoporeFilter::new().barcodes(["barcode02", "unclassified"])),
+        ["r3", "r4"]
+    );
+}
+
+#[test]
+fn test_filter_by_start_time() {
+    let filter = NanoporeFilter::new()
+        .started_from("2023-05-04T10:00:00Z")
+        .started_before("2023-05-04T12:00:00Z");
+    assert_eq!(filtered(filter), ["r1", "r2", "r5"]);
+}
+
+#[test]
+fn test_filter_combined() {
+    let filter = NanoporeFilter::new()
+        .channels(1..=256)
+        .barcodes(["barcode01", "barcode02"])
+        .started_before("2023-05-04T12:00:00Z");
+    assert_eq!(filtered(filter), ["r1"]);
+}
+
+#[test]
+fn test_empty_filter_keeps_nanopore_reads() {
+    assert_eq!(
+        filtered(NanoporeFilter::new()),
+        ["r1", "r2", "r3", "r4", "r5"]
+    );
+}
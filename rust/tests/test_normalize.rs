@@ -0,0 +1,140 @@
+use prseq::normalize::{BaseConversion, NormalizeExt, NormalizeOptions};
+use prseq::{AnyReader, FastaReader, FastaRecord, FastqReader, FastqRecord};
+
+fn fasta(header: &str, sequence: &str) -> FastaRecord {
+    FastaRecord {
+        header: header.to_string(),
+        sequence: sequence.to_string(),
+    }
+}
+
+#[test]
+fn test_default_changes_nothing() {
+    let mut record = fasta("s1  x", "ac-gu .X");
+    record.normalize(&NormalizeOptions::new());
+    assert_eq!(record, fasta("s1  x", "ac-gu .X"));
+}
+
+#[test]
+fn test_uppercase() {
+    let mut record = fasta("s1", "acgTn");
+    record.normalize(&NormalizeOptions::new().uppercase(true));
+    assert_eq!(record.sequence, "ACGTN");
+}
+
+#[test]
+fn test_convert_preserves_case() {
+    let mut record = fasta("s1", "AuUgc");
+    record.normalize(&NormalizeOptions::new().convert(BaseConversion::RnaToDna));
+    assert_eq!(record.sequence, "AtTgc");
+    record.normalize(&NormalizeOptions::new().convert(BaseConversion::DnaToRna));
+    assert_eq!(record.sequence, "AuUgc");
+}
+
+#[test]
+fn test_strip_gaps() {
+    let mut record = fasta("s1", "A-C..G T");
+    record.normalize(&NormalizeOptions::new().strip_gaps(true));
+    assert_eq!(record.sequence, "ACG T");
+}
+
+#[test]
+fn test_collapse_whitespace() {
+    let mut record = fasta("s1 \t a   b ", "AC G\tT");
+    record.normalize(&NormalizeOptions::new().collapse_whitespace(true));
+    assert_eq!(record.header, "s1 a b");
+    assert_eq!(record.sequence, "ACGT");
+}
+
+#[test]
+fn test_replace_invalid() {
+    let mut record = fasta("s1", "ACXGT-.*ryj");
+    record.normalize(&NormalizeOptions::new().replace_invalid(true));
+    // Gaps and IUPAC codes of either case are kept
+    assert_eq!(record.sequence, "ACNGT-.NryN");
+}
+
+#[test]
+fn test_replace_invalid_after_conversion() {
+    // U is a valid IUPAC code, and stays valid once converted
+    let mut record = fasta("s1", "ACGU");
+    let options = NormalizeOptions::all().convert(BaseConversion::RnaToDna);
+    record.normalize(&options);
+    assert_eq!(record.sequence, "ACGT");
+}
+
+#[test]
+fn test_all() {
+    let mut record = fasta("s1  x", "ac-gu .X");
+    record.normalize(&NormalizeOptions::all());
+    assert_eq!(record.header, "s1 x");
+    assert_eq!(record.sequence, "ACGUN");
+}
+
+#[test]
+fn test_fastq_quality_follows_removed_bases() {
+    let mut record = FastqRecord {
+        header: "r1".to_string(),
+        sequence: "A-C.g".to_string(),
+        quality: "ABCDE".to_string(),
+    };
+    record.normalize(&NormalizeOptions::all());
+    assert_eq!(record.sequence, "ACG");
+    assert_eq!(record.quality, "ACE");
+}
+
+#[test]
+fn test_fastq_non_ascii_quality_does_not_panic() {
+    // The reader accepts a quality that is not ASCII; removing the score of
+    // a gap that falls inside a multi-byte character is done lossily.
+    let mut record = FastqReader::from_string("@r\nA-C\n+\n\u{e9}?\n")
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    record.normalize(&NormalizeOptions::new().strip_gaps(true));
+    assert_eq!(record.sequence, "AC");
+    assert_eq!(record.quality, "\u{fffd}?");
+}
+
+#[test]
+fn test_normalized_fasta_reader() {
+    let reader = FastaReader::from_string(">s1\nac-gu\n>s2\nNN..x\n").unwrap();
+    let options = NormalizeOptions::all().convert(BaseConversion::RnaToDna);
+    let sequences: Vec<String> = reader
+        .normalized(options)
+        .map(|record| record.unwrap().sequence)
+        .collect();
+    assert_eq!(sequences, vec!["ACGT", "NNN"]);
+}
+
+#[test]
+fn test_normalized_fastq_reader() {
+    let reader = FastqReader::from_string("@r1\nac-t\n+\nABCD\n").unwrap();
+    let records: Vec<FastqRecord> = reader
+        .normalized(NormalizeOptions::all())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(records[0].sequence, "ACT");
+    assert_eq!(records[0].quality, "ABD");
+}
+
+#[test]
+fn test_normalized_any_reader() {
+    let reader =
+        AnyReader::from_reader_with_capacity(std::io::Cursor::new(b">s1\nac-g\n".to_vec()), 1024)
+            .unwrap();
+    let record = reader
+        .normalized(NormalizeOptions::all())
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_eq!(prseq::SequenceRecord::sequence(&record), "ACG");
+}
+
+#[test]
+fn test_normalized_passes_errors_through() {
+    let reader = FastqReader::from_string("@r1\nACGT\n+\nII\n").unwrap();
+    let mut records = reader.normalized(NormalizeOptions::all());
+    assert!(records.next().unwrap().is_err());
+}
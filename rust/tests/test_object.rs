@@ -0,0 +1,35 @@
+// Tests for reading from object stores (using the local filesystem store)
+#![cfg(feature = "object_store")]
+use prseq::{FastaReader, FastqReader};
+use std::io::Write;
+
+#[test]
+fn test_fastq_from_object_store() {
+    let content: Vec<u8> = (0..20000)
+        .flat_map(|i| format!("@read{}\nACGT\n+\nIIII\n", i).into_bytes())
+        .collect();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&content).unwrap();
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&encoder.finish().unwrap()).unwrap();
+    file.flush().unwrap();
+
+    let url = format!("file://{}", file.path().display());
+    let records: Vec<_> = FastqReader::from_object_store(&url)
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records.len(), 20000);
+    assert_eq!(records[19999].header, "read19999");
+}
+
+#[test]
+fn test_object_store_errors() {
+    let error = FastaReader::from_object_store("not a url").err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+
+    let dir = tempfile::tempdir().unwrap();
+    let url = format!("file://{}/missing.fa", dir.path().display());
+    let error = FastaReader::from_object_store(&url).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+}
@@ -0,0 +1,59 @@
+// Tests for record byte offsets
+use prseq::{AnyReader, FastaReader, FastqReader};
+use std::io::{Cursor, Write};
+
+const FASTA: &[u8] = b"\n>seq1 first\r\nACGT\r\nGG\r\n\r\n>seq2\nTTTT\n>seq3\nCC\n";
+
+#[test]
+fn test_fasta_offsets() {
+    let mut reader = FastaReader::from_reader_with_capacity(Cursor::new(FASTA), 64).unwrap();
+    assert_eq!(reader.offset(), None);
+    assert_eq!(reader.tell(), 0);
+
+    let mut offsets = Vec::new();
+    while let Some(record) = reader.next() {
+        let record = record.unwrap();
+        let offset = reader.offset().unwrap() as usize;
+        assert!(FASTA[offset..].starts_with(format!(">{}", record.header).as_bytes()));
+        offsets.push(offset);
+    }
+    assert_eq!(offsets, vec![1, 26, 37]);
+    assert_eq!(reader.tell(), FASTA.len() as u64);
+}
+
+#[test]
+fn test_fasta_tell_allows_resuming() {
+    let mut reader = FastaReader::from_reader_with_capacity(Cursor::new(FASTA), 64).unwrap();
+    reader.next().unwrap().unwrap();
+    let resume_at = reader.tell() as usize;
+    assert_eq!(resume_at, 26);
+
+    let rest: Vec<_> =
+        FastaReader::from_reader_with_capacity(Cursor::new(FASTA[resume_at..].to_vec()), 64)
+            .unwrap()
+            .map(|r| r.unwrap().header)
+            .collect();
+    assert_eq!(rest, vec!["seq2", "seq3"]);
+}
+
+#[test]
+fn test_fastq_offsets_in_compressed_input() {
+    let content = b"@read1\nACGT\n+\nIIII\n\n@read2\nGG\n+\nJJ\n";
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(content).unwrap();
+
+    let mut reader =
+        FastqReader::from_reader_with_capacity(Cursor::new(encoder.finish().unwrap()), 64).unwrap();
+    reader.next().unwrap().unwrap();
+    assert_eq!(reader.offset(), Some(0));
+    assert_eq!(reader.tell(), 19);
+    reader.next().unwrap().unwrap();
+    assert_eq!(reader.offset(), Some(20));
+}
+
+#[test]
+fn test_any_reader_offsets_count_leading_whitespace() {
+    let mut reader = AnyReader::from_reader_with_capacity(Cursor::new(FASTA), 64).unwrap();
+    reader.next().unwrap().unwrap();
+    assert_eq!(reader.offset(), Some(1));
+}
@@ -0,0 +1,614 @@
+// Tests for parser options (lenient parsing)
+use prseq::{
+    Alphabet, DuplicateIds, EmptyRecords, FastaReader, FastqReader, FastqRecord, OnError,
+    ParserOptions, PrseqError, ReaderBuilder, SoftMask,
+};
+use std::io::{Cursor, Read, Write};
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+
+fn lenient(on_error: OnError) -> ParserOptions {
+    ParserOptions::default().on_error(on_error)
+}
+
+fn fastq(input: &[u8], options: ParserOptions) -> FastqReader {
+    FastqReader::from_reader_with_capacity(Cursor::new(input.to_vec()), 64)
+        .unwrap()
+        .with_options(options)
+}
+
+const CORRUPT_FASTQ: &[u8] = b"@read1\nACGT\n+\nIIII\n\
+    garbage line\nmore garbage\n\
+    @read2\nACGT\n+\nIIII\n\
+    @read3\nACGT\n+other\n@III\n\
+    @read4\nAC\xffT\n+\nIIII\n\
+    @read5\nGGCC\n+read5\nJJJJ\n";
+
+#[test]
+fn test_fail_is_the_default() {
+    let mut reader = fastq(CORRUPT_FASTQ, ParserOptions::default());
+    assert_eq!(reader.options().on_error, OnError::Fail);
+    reader.next().unwrap().unwrap();
+    assert!(reader.next().unwrap().is_err());
+    assert_eq!(reader.skipped(), 0);
+}
+
+#[test]
+fn test_skip_malformed_fastq_records() {
+    for on_error in [OnError::Skip, OnError::Warn] {
+        let mut reader = fastq(CORRUPT_FASTQ, lenient(on_error));
+        let ids: Vec<_> = reader.by_ref().map(|r| r.unwrap().header).collect();
+        // The garbage lines, the '+' mismatch (whose quality starts with
+        // '@') and the invalid UTF-8 are each one skipped record.
+        assert_eq!(ids, vec!["read1", "read2", "read5"]);
+        assert_eq!(reader.skipped(), 3);
+    }
+}
+
+/// A logger that keeps the warnings logged by each thread, so that tests
+/// running at the same time see only their own
+struct Warnings;
+
+static WARNINGS: Mutex<Vec<(ThreadId, String)>> = Mutex::new(Vec::new());
+
+impl log::Log for Warnings {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            let warning = (thread::current().id(), record.args().to_string());
+            WARNINGS.lock().unwrap().push(warning);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// The warnings logged so far by this thread
+fn logged_warnings() -> Vec<String> {
+    static LOGGER: Warnings = Warnings;
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(log::LevelFilter::Warn);
+    }
+    let id = thread::current().id();
+    let warnings = WARNINGS.lock().unwrap();
+    warnings
+        .iter()
+        .filter(|(thread, _)| *thread == id)
+        .map(|(_, warning)| warning.clone())
+        .collect()
+}
+
+#[test]
+fn test_warn_logs_skipped_records() {
+    assert!(logged_warnings().is_empty());
+    let mut reader = fastq(CORRUPT_FASTQ, lenient(OnError::Warn));
+    assert_eq!(reader.by_ref().filter_map(Result::ok).count(), 3);
+    let warnings = logged_warnings();
+    assert_eq!(warnings.len(), 3);
+    assert_eq!(reader.skipped(), 3);
+    assert!(warnings[0].starts_with("Skipping malformed record: "));
+
+    // Skip does not log.
+    let reader = fastq(CORRUPT_FASTQ, lenient(OnError::Skip));
+    assert_eq!(reader.filter_map(Result::ok).count(), 3);
+    assert_eq!(logged_warnings().len(), 3);
+}
+
+#[test]
+fn test_skip_with_read_into() {
+    let mut reader = fastq(CORRUPT_FASTQ, lenient(OnError::Skip));
+    let mut record = FastqRecord {
+        header: String::new(),
+        sequence: String::new(),
+        quality: String::new(),
+    };
+    let mut ids = Vec::new();
+    while reader.read_into(&mut record).unwrap() {
+        ids.push(record.header.clone());
+    }
+    assert_eq!(ids, vec!["read1", "read2", "read5"]);
+    assert_eq!(reader.skipped(), 3);
+}
+
+#[test]
+fn test_skip_truncated_final_record() {
+    let mut reader = fastq(
+        b"@read1\nACGT\n+\nIIII\n@read2\nACGT\n",
+        lenient(OnError::Skip),
+    );
+    assert_eq!(reader.next().unwrap().unwrap().header, "read1");
+    assert!(reader.next().is_none());
+    assert_eq!(reader.skipped(), 1);
+}
+
+#[test]
+fn test_skip_leading_fasta_garbage() {
+    let input = b"junk\nmore junk\n>seq1\nACGT\n>seq2\nGG\n".to_vec();
+    let mut reader = FastaReader::from_reader_with_capacity(Cursor::new(input), 64)
+        .unwrap()
+        .with_options(lenient(OnError::Skip));
+    let ids: Vec<_> = reader.by_ref().map(|r| r.unwrap().header).collect();
+    assert_eq!(ids, vec!["seq1", "seq2"]);
+    assert_eq!(reader.skipped(), 1);
+}
+
+#[test]
+fn test_io_errors_are_not_skipped() {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    for i in 0..1000 {
+        write!(encoder, "@read{}\nACGT\n+\nIIII\n", i).unwrap();
+    }
+    let compressed = encoder.finish().unwrap();
+    let truncated = &compressed[..compressed.len() / 2];
+
+    let mut reader = fastq(truncated, lenient(OnError::Skip));
+    assert!(reader.find_map(|r| r.err()).is_some());
+}
+
+#[test]
+fn test_builder_options() {
+    let mut reader = ReaderBuilder::new()
+        .reader(Cursor::new(CORRUPT_FASTQ.to_vec()))
+        .options(lenient(OnError::Skip))
+        .build_fastq()
+        .unwrap();
+    assert_eq!(reader.by_ref().count(), 3);
+    assert_eq!(reader.skipped(), 3);
+}
+
+#[test]
+fn test_four_line_fastq() {
+    let input = b"@read1\nACGT\n+\n@III\n@read2\nGG\n+read2\n+I\n";
+    let records: Vec<_> = fastq(input, ParserOptions::default())
+        .four_line()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].quality, "@III");
+    assert_eq!(records[1].sequence, "GG");
+    assert_eq!(records[1].quality, "+I");
+}
+
+#[test]
+fn test_four_line_from_file() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(b"@read1\nACGT\n+\n@III\n@read2\nGG\n+\n+I\n")
+        .unwrap();
+    file.flush().unwrap();
+    let records: Vec<_> = FastqReader::four_line_from_file(file.path())
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].quality, "@III");
+    assert_eq!(records[1].quality, "+I");
+}
+
+#[test]
+fn test_four_line_rejects_multi_line_records() {
+    let input = b"@read1\nACGT\nACGT\n+\nIIIIIIII\n";
+    let mut reader = fastq(input, ParserOptions::default().strict_four_line(true));
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("FASTQ record's third line must start with '+'"));
+    assert_eq!(error.position().unwrap().line, 3);
+}
+
+#[test]
+fn test_four_line_length_mismatch() {
+    let input = b"@read1\nACGT\n+\nIIIII\n@read2\nAC\n+\nII\n";
+    let mut reader = fastq(input, lenient(OnError::Skip).strict_four_line(true));
+    let ids: Vec<_> = reader.by_ref().map(|r| r.unwrap().header).collect();
+    assert_eq!(ids, vec!["read2"]);
+    assert_eq!(reader.skipped(), 1);
+
+    let error = fastq(input, ParserOptions::default())
+        .four_line()
+        .next()
+        .unwrap()
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        prseq::PrseqError::LengthMismatch {
+            sequence_length: 4,
+            quality_length: 5,
+            ..
+        }
+    ));
+}
+
+const DUPLICATE_FASTA: &[u8] = b">a one\nAC\n>b\nGT\n>a two\nTT\n>c\nGG\n";
+
+fn fasta(input: &[u8], options: ParserOptions) -> FastaReader {
+    FastaReader::from_reader_with_capacity(Cursor::new(input.to_vec()), 64)
+        .unwrap()
+        .with_options(options)
+}
+
+#[test]
+fn test_duplicate_ids_allowed_by_default() {
+    let reader = fasta(DUPLICATE_FASTA, ParserOptions::default());
+    let records: Vec<_> = reader.map(|record| record.unwrap()).collect();
+    assert_eq!(records.len(), 4);
+}
+
+#[test]
+fn test_duplicate_ids_error() {
+    let options = ParserOptions::default().duplicate_ids(DuplicateIds::Error);
+    let mut reader = fasta(DUPLICATE_FASTA, options);
+    reader.next().unwrap().unwrap();
+    reader.next().unwrap().unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    match &error {
+        PrseqError::DuplicateId {
+            id,
+            first,
+            position,
+        } => {
+            assert_eq!(id, "a");
+            assert_eq!(*first, 1);
+            assert_eq!(position.record, 3);
+            assert_eq!(position.line, 5);
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(
+        error.to_string(),
+        "Duplicate ID 'a' at line 5, record 3 (first seen in record 1)"
+    );
+    // The reader carries on after the duplicate.
+    assert_eq!(reader.next().unwrap().unwrap().header, "c");
+}
+
+#[test]
+fn test_duplicate_ids_skipped() {
+    let options = ParserOptions::default()
+        .duplicate_ids(DuplicateIds::Error)
+        .on_error(OnError::Skip);
+    let mut reader = fasta(DUPLICATE_FASTA, options);
+    let headers: Vec<String> = reader
+        .by_ref()
+        .map(|record| record.unwrap().header)
+        .collect();
+    assert_eq!(headers, ["a one", "b", "c"]);
+    assert_eq!(reader.skipped(), 1);
+}
+
+#[test]
+fn test_duplicate_ids_warn() {
+    assert!(logged_warnings().is_empty());
+    let options = ParserOptions::default().duplicate_ids(DuplicateIds::Warn);
+    let mut reader = fasta(DUPLICATE_FASTA, options);
+    let records: Vec<_> = reader.by_ref().map(|record| record.unwrap()).collect();
+    assert_eq!(records.len(), 4);
+    assert_eq!(reader.stats().duplicates, 1);
+    let warnings = logged_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].starts_with("Duplicate ID 'a' "));
+}
+
+#[test]
+fn test_duplicate_fastq_ids() {
+    let input = b"@r1/1\nA\n+\nI\n@r2\nC\n+\nI\n@r1/1 again\nG\n+\nI\n";
+    let options = ParserOptions::default().duplicate_ids(DuplicateIds::Error);
+    let results: Vec<_> = fastq(input, options).collect();
+    assert!(results[0].is_ok() && results[1].is_ok());
+    assert!(matches!(
+        results[2],
+        Err(PrseqError::DuplicateId { first: 1, .. })
+    ));
+}
+
+const MASKED_FASTA: &[u8] = b">chr1\nACgt\nnnTA\n>chr2\nacgt\n";
+
+fn sequences(reader: FastaReader) -> Vec<String> {
+    reader.map(|record| record.unwrap().sequence).collect()
+}
+
+#[test]
+fn test_soft_mask_kept_by_default() {
+    let reader = fasta(MASKED_FASTA, ParserOptions::default());
+    assert_eq!(sequences(reader), ["ACgtnnTA", "acgt"]);
+}
+
+#[test]
+fn test_soft_mask_unmask() {
+    let reader = fasta(
+        MASKED_FASTA,
+        ParserOptions::default().soft_mask(SoftMask::Unmask),
+    );
+    assert_eq!(sequences(reader), ["ACGTNNTA", "ACGT"]);
+}
+
+#[test]
+fn test_soft_mask_to_n() {
+    let reader = FastaReader::from_bytes(MASKED_FASTA)
+        .unwrap()
+        .soft_mask(SoftMask::MaskToN);
+    assert_eq!(sequences(reader), ["ACNNNNTA", "NNNN"]);
+}
+
+#[test]
+fn test_soft_mask_applied_before_validation() {
+    // Lower case letters outside the alphabet are still caught once masked
+    let options = ParserOptions::default()
+        .soft_mask(SoftMask::Unmask)
+        .validate(Alphabet::Dna);
+    let mut reader = fasta(b">s1\nacgt\n>s2\nacgx\n", options);
+    assert_eq!(reader.next().unwrap().unwrap().sequence, "ACGT");
+    assert!(matches!(
+        reader.next().unwrap(),
+        Err(PrseqError::InvalidSequence { .. })
+    ));
+}
+
+/// A FASTA or FASTQ record whose sequence lines never end
+struct Endless {
+    header: &'static [u8],
+    sent: usize,
+}
+
+impl std::io::Read for Endless {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        const LINE: &[u8] = b"ACGTACGTAC\n";
+        for byte in buf.iter_mut() {
+            *byte = match self.header.get(self.sent) {
+                Some(&byte) => byte,
+                None => LINE[(self.sent - self.header.len()) % LINE.len()],
+            };
+            self.sent += 1;
+        }
+        Ok(buf.len())
+    }
+}
+
+#[test]
+fn test_max_sequence_length() {
+    let options = ParserOptions::default().max_sequence_length(8);
+    let mut reader = fasta(b">a\nACGT\nACGT\n>b\nACGT\nACGTA\n", options);
+    assert_eq!(reader.next().unwrap().unwrap().sequence, "ACGTACGT");
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(matches!(
+        error,
+        PrseqError::RecordTooLong {
+            format: "FASTA",
+            part: "sequence",
+            limit: 8,
+            ..
+        }
+    ));
+    assert_eq!(error.position().unwrap().record, 2);
+    assert_eq!(
+        error.to_string(),
+        "FASTA record's sequence is longer than the limit of 8 bytes at line 4, \
+         record 2 (is the input malformed?)"
+    );
+}
+
+#[test]
+fn test_max_sequence_length_stops_endless_records() {
+    let options = ParserOptions::default().max_sequence_length(1000);
+    let input = Endless {
+        header: b">a\n",
+        sent: 0,
+    };
+    let mut reader = FastaReader::from_reader_with_capacity(input, 64)
+        .unwrap()
+        .with_options(options.clone());
+    assert!(matches!(
+        reader.next().unwrap(),
+        Err(PrseqError::RecordTooLong { .. })
+    ));
+
+    // A FASTQ record missing its '+' line
+    let input = Endless {
+        header: b"@r1\n",
+        sent: 0,
+    };
+    let mut reader = FastqReader::from_reader_with_capacity(input, 64)
+        .unwrap()
+        .with_options(options);
+    assert!(matches!(
+        reader.next().unwrap(),
+        Err(PrseqError::RecordTooLong { .. })
+    ));
+}
+
+#[test]
+fn test_max_sequence_length_stops_lines_that_never_end() {
+    let options = ParserOptions::default().max_sequence_length(1000);
+    let input = Cursor::new(b">a\n".to_vec()).chain(std::io::repeat(b'A'));
+    let mut reader = FastaReader::from_reader_with_capacity(input, 64)
+        .unwrap()
+        .with_options(options.clone());
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(matches!(
+        error,
+        PrseqError::RecordTooLong {
+            format: "FASTA",
+            part: "sequence",
+            limit: 1000,
+            ..
+        }
+    ));
+    assert_eq!(error.position().unwrap().line, 2);
+    assert_eq!(error.position().unwrap().record, 1);
+
+    let input = Cursor::new(b"@r1\n".to_vec()).chain(std::io::repeat(b'A'));
+    let mut reader = FastqReader::from_reader_with_capacity(input, 64)
+        .unwrap()
+        .with_options(options.clone());
+    assert!(matches!(
+        reader.next().unwrap(),
+        Err(PrseqError::RecordTooLong {
+            part: "sequence",
+            ..
+        })
+    ));
+
+    // The rest of a skipped line is not mistaken for records.
+    let mut input = b">a\n".to_vec();
+    input.extend(std::iter::repeat_n(b'A', 100_000));
+    input.extend(b">x\n\n>b\nACGT\n");
+    let reader = fasta(&input, options.on_error(OnError::Skip));
+    let headers: Vec<String> = reader.map(|r| r.unwrap().header).collect();
+    assert_eq!(headers, ["b"]);
+}
+
+#[test]
+fn test_max_header_length() {
+    let options = ParserOptions::default().max_header_length(5);
+    let mut reader = fasta(b">short\nAC\n>long one\nAC\n", options.clone());
+    assert_eq!(reader.next().unwrap().unwrap().header, "short");
+    assert!(matches!(
+        reader.next().unwrap(),
+        Err(PrseqError::RecordTooLong { part: "header", .. })
+    ));
+    let mut reader = fastq(b"@ok\nA\n+\nI\n@too long\nA\n+\nI\n", options);
+    reader.next().unwrap().unwrap();
+    assert!(matches!(
+        reader.next().unwrap(),
+        Err(PrseqError::RecordTooLong {
+            format: "FASTQ",
+            part: "header",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_skip_records_that_are_too_long() {
+    let options = ParserOptions::default()
+        .max_sequence_length(6)
+        .on_error(OnError::Skip);
+    let mut reader = fasta(b">a\nACGT\nACGT\nAC\n>b\nAC\n", options.clone());
+    let headers: Vec<String> = reader.by_ref().map(|r| r.unwrap().header).collect();
+    assert_eq!(headers, ["b"]);
+    assert_eq!(reader.skipped(), 1);
+
+    let input = b"@r1\nACGTACGT\n+\nIIIIIIII\n@r2\nAC\n+\nII\n";
+    for strict in [false, true] {
+        let reader = fastq(input, options.clone().strict_four_line(strict));
+        let headers: Vec<String> = reader.map(|r| r.unwrap().header).collect();
+        assert_eq!(headers, ["r2"]);
+    }
+}
+
+const LEGACY_FASTA: &[u8] = b";a comment before the first record\n\
+    >p1 kinase\n;another comment\nMKV\nLLA*\n\n>p2\nMK*\n;trailing comment\n";
+
+#[test]
+fn test_legacy_fasta_fails_by_default() {
+    let mut reader = fasta(LEGACY_FASTA, ParserOptions::default());
+    let error = reader.next().unwrap().unwrap_err();
+    assert_eq!(error.position().unwrap().line, 1);
+    assert!(error.to_string().contains("must start with '>'"));
+}
+
+#[test]
+fn test_skip_comments_and_strip_trailing_stop() {
+    let options = ParserOptions::default()
+        .skip_comments(true)
+        .strip_trailing_stop(true);
+    let reader = fasta(LEGACY_FASTA, options);
+    let records: Vec<_> = reader.map(|record| record.unwrap()).collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].header, "p1 kinase");
+    assert_eq!(records[0].sequence, "MKVLLA");
+    assert_eq!(records[1].sequence, "MK");
+}
+
+#[test]
+fn test_skip_comments_keeps_stops() {
+    let reader = fasta(LEGACY_FASTA, ParserOptions::default().skip_comments(true));
+    assert_eq!(sequences(reader), ["MKVLLA*", "MK*"]);
+}
+
+#[test]
+fn test_strip_trailing_stop_before_validation() {
+    let options = ParserOptions::default()
+        .strip_trailing_stop(true)
+        .validate(Alphabet::Dna);
+    // Only one trailing '*' is removed, and not an internal one.
+    let mut reader = fasta(b">a\nACGT*\n>b\nAC*GT\n>c\nACGT**\n", options);
+    assert_eq!(reader.next().unwrap().unwrap().sequence, "ACGT");
+    assert!(reader.next().unwrap().is_err());
+    assert!(reader.next().unwrap().is_err());
+}
+
+const EMPTY_FASTA: &[u8] = b">a\nAC\n>empty1\n>b\nGT\n>empty2\n\n>c\nTT\n>empty3\n";
+
+fn headers(reader: FastaReader) -> Vec<String> {
+    reader.map(|record| record.unwrap().header).collect()
+}
+
+#[test]
+fn test_empty_records_kept_by_default() {
+    let reader = fasta(EMPTY_FASTA, ParserOptions::default());
+    assert_eq!(
+        headers(reader),
+        ["a", "empty1", "b", "empty2", "c", "empty3"]
+    );
+}
+
+#[test]
+fn test_skip_empty_records() {
+    let options = ParserOptions::default().empty_records(EmptyRecords::Skip);
+    let mut reader = fasta(EMPTY_FASTA, options);
+    let records: Vec<_> = reader.by_ref().map(|record| record.unwrap()).collect();
+    let headers: Vec<_> = records
+        .iter()
+        .map(|record| record.header.as_str())
+        .collect();
+    assert_eq!(headers, ["a", "b", "c"]);
+    assert_eq!(reader.skipped(), 0);
+    assert_eq!(reader.stats().records, 3);
+
+    let options = ParserOptions::default().empty_records(EmptyRecords::Skip);
+    let reader = fastq(b"@r1\n\n+\n\n@r2\nA\n+\nI\n@r3\n\n+\n\n", options);
+    let headers: Vec<String> = reader.map(|record| record.unwrap().header).collect();
+    assert_eq!(headers, ["r2"]);
+}
+
+#[test]
+fn test_skipped_empty_records_do_not_count_as_duplicates() {
+    let options = ParserOptions::default()
+        .empty_records(EmptyRecords::Skip)
+        .duplicate_ids(DuplicateIds::Error);
+    let reader = fasta(b">a\n>a\nAC\n", options);
+    assert_eq!(headers(reader), ["a"]);
+}
+
+#[test]
+fn test_empty_records_as_errors() {
+    let options = ParserOptions::default().empty_records(EmptyRecords::Error);
+    let mut reader = fasta(EMPTY_FASTA, options.clone());
+    reader.next().unwrap().unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(matches!(error, PrseqError::InvalidFasta { .. }));
+    assert_eq!(error.position().unwrap().line, 3);
+    assert_eq!(error.position().unwrap().record, 2);
+    assert_eq!(
+        error.to_string(),
+        "FASTA record has an empty sequence at line 3, record 2: 'empty1'"
+    );
+
+    let mut reader = fasta(EMPTY_FASTA, options.on_error(OnError::Skip));
+    let headers: Vec<String> = reader
+        .by_ref()
+        .map(|record| record.unwrap().header)
+        .collect();
+    assert_eq!(headers, ["a", "b", "c"]);
+    assert_eq!(reader.skipped(), 3);
+
+    let options = ParserOptions::default().empty_records(EmptyRecords::Error);
+    let mut reader = fastq(b"@r1\n\n+\n\n", options);
+    assert!(matches!(
+        reader.next().unwrap(),
+        Err(PrseqError::InvalidFastq { .. })
+    ));
+}
@@ -0,0 +1,96 @@
+// Tests for 2-bit packed sequences
+use prseq::packed::{PackedRecord, PackedSeq};
+use prseq::{FastaReader, FastaRecord, PrseqError};
+
+#[test]
+fn test_round_trip() {
+    for sequence in [
+        &b""[..],
+        b"A",
+        b"ACG",
+        b"ACGT",
+        b"ACGTA",
+        b"NNNNNNNNNN",
+        b"acgtNNNNnnnnRYKMSWBDHV-.U*",
+        b"ACGTTGCAnnnnACGTTGCAACGTTGCA",
+    ] {
+        let packed = PackedSeq::new(sequence);
+        assert_eq!(packed.len(), sequence.len());
+        assert_eq!(packed.to_vec(), sequence);
+    }
+}
+
+#[test]
+fn test_get() {
+    let packed = PackedSeq::new(b"ACgtNNaR");
+    let bases: Vec<u8> = (0..8).map(|i| packed.get(i).unwrap()).collect();
+    assert_eq!(bases, b"ACgtNNaR");
+    assert_eq!(packed.get(8), None);
+    assert_eq!(packed.get(usize::MAX), None);
+}
+
+#[test]
+fn test_get_range() {
+    let packed = PackedSeq::new(b"ACGTNNNNacgtACGT");
+    assert_eq!(packed.get_range(2..6).unwrap(), b"GTNN");
+    assert_eq!(packed.get_range(6..10).unwrap(), b"NNac");
+    assert_eq!(packed.get_range(11..16).unwrap(), b"tACGT");
+    assert_eq!(packed.get_range(5..5).unwrap(), b"");
+    assert_eq!(packed.get_range(12..17), None);
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = packed.get_range(6..5);
+    assert_eq!(reversed, None);
+}
+
+#[test]
+fn test_exceptions_are_runs() {
+    let mut genome = vec![b'N'; 10_000];
+    genome.extend(b"ACGT".repeat(10_000));
+    genome.extend(vec![b'N'; 10_000]);
+    let packed = PackedSeq::new(&genome);
+    assert_eq!(packed.exception_count(), 20_000);
+    // 2 bits per base, and a few bytes for the runs of N
+    assert!(packed.heap_size() < genome.len() / 4 + 100);
+    assert_eq!(packed.to_vec(), genome);
+}
+
+#[test]
+fn test_display_and_conversions() {
+    let packed = PackedSeq::from("ACGTnRY");
+    assert_eq!(packed.to_string(), "ACGTnRY");
+    assert_eq!(PackedSeq::from(&b"ACGTnRY"[..]), packed);
+    assert!(PackedSeq::default().is_empty());
+}
+
+#[test]
+fn test_packed_record() {
+    let record = FastaRecord {
+        header: "chr1 test".to_string(),
+        sequence: "ACGTNNacgt".to_string(),
+    };
+    let packed = PackedRecord::from(record.clone());
+    assert_eq!(packed.header, "chr1 test");
+    assert_eq!(packed.sequence.len(), 10);
+    assert_eq!(FastaRecord::from(packed), record);
+}
+
+#[test]
+fn test_packed_records_from_reader() {
+    let input = ">chr1\nACGT\nNNac\n>chr2\nGGCC\n";
+    let records: Vec<PackedRecord> = FastaReader::from_string(input)
+        .unwrap()
+        .packed_records()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].header, "chr1");
+    assert_eq!(records[0].sequence.to_string(), "ACGTNNac");
+    assert_eq!(records[1].to_record().sequence, "GGCC");
+}
+
+#[test]
+fn test_packed_records_invalid_utf8() {
+    let reader = FastaReader::from_bytes(b">chr1\nAC\xffGT\n").unwrap();
+    let result = reader.packed_records().next().unwrap();
+    assert!(matches!(result, Err(PrseqError::InvalidUtf8 { .. })));
+}
@@ -0,0 +1,214 @@
+// Tests for paired-end FASTQ reading
+use prseq::paired::{pair_id, PairedFilterExt};
+use prseq::{deinterleave, interleave, FastqWriter, InterleavedFastqReader, PairedFastqReader};
+use std::io::{Cursor, Write};
+use tempfile::NamedTempFile;
+
+fn paired(r1: &'static [u8], r2: &'static [u8]) -> PairedFastqReader {
+    PairedFastqReader::from_readers_with_capacity(Cursor::new(r1), Cursor::new(r2), 64).unwrap()
+}
+
+#[test]
+fn test_pair_id() {
+    assert_eq!(pair_id("read1/1"), "read1");
+    assert_eq!(pair_id("read1/2"), "read1");
+    assert_eq!(pair_id("read1 1:N:0:ATCACG"), "read1");
+    assert_eq!(pair_id("read1/1 extra"), "read1");
+    assert_eq!(pair_id("read1/3"), "read1/3");
+}
+
+#[test]
+fn test_paired_reading() {
+    let pairs: Vec<_> = paired(
+        b"@r1/1\nACGT\n+\nIIII\n@r2 1:N:0:A\nGG\n+\nII\n",
+        b"@r1/2\nTTTT\n+\nJJJJ\n@r2 2:N:0:A\nCC\n+\nJJ\n",
+    )
+    .map(|p| p.unwrap())
+    .collect();
+    assert_eq!(pairs.len(), 2);
+    assert_eq!(pairs[0].0.sequence, "ACGT");
+    assert_eq!(pairs[0].1.sequence, "TTTT");
+    assert_eq!(pairs[1].1.header, "r2 2:N:0:A");
+}
+
+#[test]
+fn test_paired_from_files() {
+    let mut r1 = NamedTempFile::new().unwrap();
+    let mut r2 = NamedTempFile::new().unwrap();
+    r1.write_all(b"@r1/1\nACGT\n+\nIIII\n").unwrap();
+    r2.write_all(b"@r1/2\nTTTT\n+\nJJJJ\n").unwrap();
+    r1.flush().unwrap();
+    r2.flush().unwrap();
+
+    let reader = PairedFastqReader::from_files(r1.path(), r2.path()).unwrap();
+    assert_eq!(reader.count(), 1);
+}
+
+#[test]
+fn test_paired_id_mismatch() {
+    let mut reader = paired(
+        b"@r1/1\nACGT\n+\nIIII\n@r2/1\nGG\n+\nII\n",
+        b"@r1/2\nTTTT\n+\nJJJJ\n@r3/2\nCC\n+\nJJ\n",
+    );
+    assert!(reader.next().unwrap().is_ok());
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(error.to_string().contains("out of sync at pair 2"));
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_paired_unequal_lengths() {
+    let mut reader = paired(
+        b"@r1/1\nACGT\n+\nIIII\n",
+        b"@r1/2\nTTTT\n+\nJJJJ\n@r2/2\nCC\n+\nJJ\n",
+    );
+    assert!(reader.next().unwrap().is_ok());
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("R1 ended after 1 records but R2 has more"));
+}
+
+#[test]
+fn test_interleaved_reading() {
+    let content =
+        b"@r1/1\nACGT\n+\nIIII\n@r1/2\nTTTT\n+\nJJJJ\n@r2/1\nGG\n+\nII\n@r2/2\nCC\n+\nJJ\n";
+    let pairs: Vec<_> = InterleavedFastqReader::from_reader_with_capacity(Cursor::new(content), 64)
+        .unwrap()
+        .map(|p| p.unwrap())
+        .collect();
+    assert_eq!(pairs.len(), 2);
+    assert_eq!(pairs[1].0.sequence, "GG");
+    assert_eq!(pairs[1].1.sequence, "CC");
+}
+
+#[test]
+fn test_interleaved_unpaired_final_read() {
+    let content = b"@r1/1\nACGT\n+\nIIII\n@r1/2\nTTTT\n+\nJJJJ\n@r2/1\nGG\n+\nII\n";
+    let mut reader =
+        InterleavedFastqReader::from_reader_with_capacity(Cursor::new(content), 64).unwrap();
+    assert!(reader.next().unwrap().is_ok());
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(error.to_string().contains("unpaired read 'r2/1'"));
+}
+
+#[test]
+fn test_interleave_and_deinterleave_round_trip() {
+    let r1 = b"@r1/1\nACGT\n+\nIIII\n@r2/1\nGG\n+\nII\n";
+    let r2 = b"@r1/2\nTTTT\n+\nJJJJ\n@r2/2\nCC\n+\nJJ\n";
+
+    let mut interleaved = FastqWriter::new(Vec::new());
+    assert_eq!(interleave(paired(r1, r2), &mut interleaved).unwrap(), 2);
+    let interleaved = interleaved.into_inner();
+
+    let mut out1 = FastqWriter::new(Vec::new());
+    let mut out2 = FastqWriter::new(Vec::new());
+    let pairs =
+        InterleavedFastqReader::from_reader_with_capacity(Cursor::new(interleaved), 64).unwrap();
+    assert_eq!(deinterleave(pairs, &mut out1, &mut out2).unwrap(), 2);
+    assert_eq!(out1.into_inner(), r1);
+    assert_eq!(out2.into_inner(), r2);
+}
+
+const FILTER_R1: &[u8] =
+    b"@a/1\nACGTACGT\n+\nIIIIIIII\n@b/1\nACGTACGT\n+\nIIIIIIII\n@c/1\nAC\n+\nII\n@d/1\nAC\n+\nII\n@e/1\nACGTAC\n+\n######\n";
+const FILTER_R2: &[u8] =
+    b"@a/2\nTTTTTTTT\n+\nIIIIIIII\n@b/2\nTT\n+\nII\n@c/2\nTTTTTTTT\n+\nIIIIIIII\n@d/2\nTT\n+\nII\n@e/2\nTTTTTT\n+\nIIIIII\n";
+
+fn ids(
+    pairs: impl Iterator<Item = prseq::error::Result<(prseq::FastqRecord, prseq::FastqRecord)>>,
+) -> Vec<String> {
+    pairs.map(|pair| pair.unwrap().0.header).collect()
+}
+
+#[test]
+fn test_filter_pairs_drops_both_mates() {
+    let mut filtered = paired(FILTER_R1, FILTER_R2).filter_pair_length(4..);
+    assert_eq!(ids(filtered.by_ref()), ["a/1", "e/1"]);
+    assert_eq!(filtered.kept(), 2);
+    assert_eq!(filtered.dropped(), 3);
+    assert_eq!(filtered.orphans(), 2);
+    assert!(filtered.into_orphan_writer().is_none());
+}
+
+#[test]
+fn test_filter_pair_quality() {
+    let filtered = paired(FILTER_R1, FILTER_R2).filter_pair_quality(30.0);
+    assert_eq!(ids(filtered), ["a/1", "b/1", "c/1", "d/1"]);
+}
+
+#[test]
+fn test_filter_pairs_writes_orphans() {
+    let mut filtered = paired(FILTER_R1, FILTER_R2)
+        .filter_pairs(|record| record.sequence.len() >= 4 && !record.quality.contains('#'))
+        .orphans_to(FastqWriter::new(Vec::new()));
+    assert_eq!(ids(filtered.by_ref()), ["a/1"]);
+    let orphans = filtered.into_orphan_writer().unwrap().into_inner();
+    assert_eq!(
+        String::from_utf8(orphans).unwrap(),
+        "@b/1\nACGTACGT\n+\nIIIIIIII\n@c/2\nTTTTTTTT\n+\nIIIIIIII\n@e/2\nTTTTTT\n+\nIIIIII\n"
+    );
+}
+
+#[test]
+fn test_orphans_from_either_mate() {
+    let mut filtered = paired(FILTER_R1, FILTER_R2)
+        .filter_pair_length(4..)
+        .orphans_to(FastqWriter::new(Vec::new()));
+    filtered.by_ref().for_each(drop);
+    let orphans = filtered.into_orphan_writer().unwrap().into_inner();
+    assert_eq!(
+        String::from_utf8(orphans).unwrap(),
+        "@b/1\nACGTACGT\n+\nIIIIIIII\n@c/2\nTTTTTTTT\n+\nIIIIIIII\n"
+    );
+}
+
+#[test]
+fn test_flag_pairs() {
+    let flagged: Vec<_> = paired(FILTER_R1, FILTER_R2)
+        .flag_pairs(|record| record.sequence.len() >= 4)
+        .map(|pair| pair.unwrap())
+        .collect();
+    assert_eq!(flagged.len(), 5);
+    let flags: Vec<_> = flagged
+        .iter()
+        .map(|pair| (pair.r1_passed, pair.r2_passed))
+        .collect();
+    assert_eq!(
+        flags,
+        [
+            (true, true),
+            (true, false),
+            (false, true),
+            (false, false),
+            (true, true)
+        ]
+    );
+    assert!(flagged[0].passed());
+    assert_eq!(flagged[1].orphan().unwrap().header, "b/1");
+    assert_eq!(flagged[2].orphan().unwrap().header, "c/2");
+    assert!(flagged[3].orphan().is_none());
+}
+
+#[test]
+fn test_filter_pairs_passes_errors_through() {
+    let results: Vec<_> = paired(
+        b"@a/1\nACGT\n+\nIIII\n@b/1\nACGT\n+\nIIII\n",
+        b"@a/2\nACGT\n+\nIIII\n@x/2\nACGT\n+\nIIII\n",
+    )
+    .filter_pair_length(1..)
+    .collect();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+}
+
+#[test]
+fn test_filter_interleaved_pairs() {
+    let reader = InterleavedFastqReader::from_reader_with_capacity(
+        Cursor::new(&b"@a/1\nACGT\n+\nIIII\n@a/2\nAC\n+\nII\n@b/1\nACGT\n+\nIIII\n@b/2\nACGT\n+\nIIII\n"[..]),
+        64,
+    )
+    .unwrap();
+    assert_eq!(ids(reader.filter_pair_length(4..)), ["b/1"]);
+}
@@ -0,0 +1,57 @@
+// Tests for parallel record processing
+#![cfg(feature = "rayon")]
+use prseq::{FastqReader, ParallelRecords};
+use std::io::Cursor;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn reads(count: usize) -> FastqReader {
+    let content: Vec<u8> = (0..count)
+        .flat_map(|i| {
+            format!(
+                "@read{}\nACGT{}\n+\n{}\n",
+                i,
+                "A".repeat(i % 7),
+                "I".repeat(4 + i % 7)
+            )
+            .into_bytes()
+        })
+        .collect();
+    FastqReader::from_reader_with_capacity(Cursor::new(content), 64).unwrap()
+}
+
+#[test]
+fn test_par_map_preserves_order() {
+    let lengths: Vec<_> = reads(5000)
+        .par_map(4, |record| (record.header, record.sequence.len()))
+        .unwrap()
+        .batch_size(100)
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(lengths.len(), 5000);
+    for (i, (id, length)) in lengths.iter().enumerate() {
+        assert_eq!(id, &format!("read{}", i));
+        assert_eq!(*length, 4 + i % 7);
+    }
+}
+
+#[test]
+fn test_par_map_passes_errors_through() {
+    let content = b"@read1\nACGT\n+\nIIII\nnot a record\n";
+    let reader = FastqReader::from_reader_with_capacity(Cursor::new(content), 64).unwrap();
+    let results: Vec<_> = reader.par_map(2, |record| record.header).unwrap().collect();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap(), "read1");
+    assert!(results[1].is_err());
+}
+
+#[test]
+fn test_process_parallel() {
+    let bases = AtomicUsize::new(0);
+    reads(3000)
+        .process_parallel(0, |record| {
+            bases.fetch_add(record.sequence.len(), Ordering::Relaxed);
+        })
+        .unwrap();
+    let expected: usize = (0..3000).map(|i| 4 + i % 7).sum();
+    assert_eq!(bases.into_inner(), expected);
+}
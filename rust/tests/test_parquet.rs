@@ -0,0 +1,91 @@
+// Tests for writing records to Parquet files
+#![cfg(feature = "parquet")]
+use arrow_array::{Array, StringArray};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::basic::Compression;
+use prseq::arrow::ArrowExt;
+use prseq::parquet::{ParquetCompression, ParquetOptions, ParquetSequenceWriter};
+use prseq::{FastaRecord, FastqReader};
+use std::fs::File;
+
+const FASTQ: &[u8] = b"@r1 first\nACGT\n+\nIIII\n@r2\nGG\n+\n#I\n@r3\nTTT\n+\n555\n";
+
+fn reader(path: &std::path::Path) -> ParquetRecordBatchReaderBuilder<File> {
+    ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap()).unwrap()
+}
+
+#[test]
+fn test_parquet_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reads.parquet");
+    let mut writer = ParquetSequenceWriter::to_file(&path).unwrap();
+    for record in FastqReader::from_reader_with_capacity(FASTQ, 64).unwrap() {
+        writer.write_record(&record.unwrap()).unwrap();
+    }
+    writer.close().unwrap();
+
+    let builder = reader(&path);
+    assert_eq!(builder.metadata().num_row_groups(), 1);
+    assert!(matches!(
+        builder.metadata().row_group(0).column(0).compression(),
+        Compression::ZSTD(_)
+    ));
+    let batches: Vec<_> = builder.build().unwrap().map(Result::unwrap).collect();
+    let batch = &batches[0];
+    assert_eq!(batch.num_rows(), 3);
+    let sequences = batch
+        .column_by_name("sequence")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(sequences.value(2), "TTT");
+    assert_eq!(batch.column_by_name("description").unwrap().null_count(), 2);
+}
+
+#[test]
+fn test_parquet_options() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("reads.parquet");
+    let options = ParquetOptions::new()
+        .row_group_size(2)
+        .compression(ParquetCompression::Snappy);
+    let mut writer = ParquetSequenceWriter::to_file_with_options(&path, &options).unwrap();
+    for batch in FastqReader::from_reader_with_capacity(FASTQ, 64)
+        .unwrap()
+        .record_batches(10)
+    {
+        writer.write_batch(&batch.unwrap()).unwrap();
+    }
+    writer.close().unwrap();
+
+    let builder = reader(&path);
+    assert_eq!(builder.metadata().num_row_groups(), 2);
+    assert_eq!(
+        builder.metadata().row_group(0).column(0).compression(),
+        Compression::SNAPPY
+    );
+}
+
+#[test]
+fn test_parquet_writer_to_memory() {
+    let mut writer = ParquetSequenceWriter::new(Vec::new()).unwrap();
+    writer
+        .write_record(&FastaRecord {
+            header: "s1".to_string(),
+            sequence: "ACGT".to_string(),
+        })
+        .unwrap();
+    let bytes = writer.close().unwrap();
+    assert_eq!(&bytes[..4], b"PAR1");
+    assert_eq!(&bytes[bytes.len() - 4..], b"PAR1");
+}
+
+#[test]
+fn test_parquet_row_group_size_must_be_positive() {
+    let options = ParquetOptions::new().row_group_size(0);
+    let error = ParquetSequenceWriter::with_options(Vec::new(), &options)
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,107 @@
+// Tests for looking at the next record without consuming it
+use prseq::{FastaReader, FastaRecord, FastqReader, FastqRecord};
+
+const FASTA: &str = ">a.1\nACGT\n>a.2\nGG\n>b.1\nTTT\n";
+const FASTQ: &str = "@r1\nACGT\n+\nIIII\n@r2\nGG\n+\n#I\n";
+
+#[test]
+fn test_fasta_peek() {
+    let mut reader = FastaReader::from_string(FASTA).unwrap();
+    let peeked = reader.peek().unwrap().as_ref().unwrap().clone();
+    assert_eq!(peeked.header, "a.1");
+    assert_eq!(reader.peek().unwrap().as_ref().unwrap(), &peeked);
+    assert_eq!(reader.next().unwrap().unwrap(), peeked);
+    assert_eq!(reader.peek().unwrap().as_ref().unwrap().header, "a.2");
+    let headers: Vec<String> = reader.map(|record| record.unwrap().header).collect();
+    assert_eq!(headers, ["a.2", "b.1"]);
+}
+
+#[test]
+fn test_peek_at_end() {
+    let mut reader = FastaReader::from_string(">a\nAC\n").unwrap();
+    assert!(reader.peek().is_some());
+    assert!(reader.next().is_some());
+    assert!(reader.peek().is_none());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_peek_then_other_reading_methods() {
+    let mut reader = FastaReader::from_string(FASTA).unwrap();
+    reader.peek();
+    let record = reader.next_ref().unwrap().unwrap();
+    assert_eq!(
+        (record.header(), record.sequence()),
+        (&b"a.1"[..], &b"ACGT"[..])
+    );
+    reader.peek();
+    let mut record = FastaRecord {
+        header: String::new(),
+        sequence: String::new(),
+    };
+    assert!(reader.read_into(&mut record).unwrap());
+    assert_eq!(record.header, "a.2");
+    reader.peek();
+    assert_eq!(reader.next_bytes().unwrap().unwrap().sequence, b"TTT");
+    assert!(reader.next_bytes().is_none());
+}
+
+#[test]
+fn test_peek_keeps_tell_at_the_peeked_record() {
+    let mut reader = FastaReader::from_string(FASTA).unwrap();
+    reader.next();
+    let start = reader.tell();
+    assert_eq!(start, 10);
+    reader.peek();
+    assert_eq!(reader.tell(), start);
+    assert_eq!(reader.offset(), Some(10));
+
+    let mut reader = FastqReader::from_string(FASTQ).unwrap();
+    reader.next();
+    let start = reader.tell();
+    reader.peek();
+    assert_eq!(reader.tell(), start);
+}
+
+#[test]
+fn test_fastq_peek() {
+    let mut reader = FastqReader::from_string(FASTQ).unwrap();
+    assert_eq!(reader.peek().unwrap().as_ref().unwrap().quality, "IIII");
+    let mut record = FastqRecord {
+        header: String::new(),
+        sequence: String::new(),
+        quality: String::new(),
+    };
+    assert!(reader.read_into(&mut record).unwrap());
+    assert_eq!(record.header, "r1");
+    assert_eq!(reader.peek().unwrap().as_ref().unwrap().header, "r2");
+    let record = reader.next_ref().unwrap().unwrap();
+    assert_eq!(record.quality(), b"#I");
+    assert!(reader.peek().is_none());
+}
+
+#[test]
+fn test_peek_error() {
+    let mut reader = FastqReader::from_string("@r1\nACGT\n+\nII\n").unwrap();
+    assert!(reader.peek().unwrap().is_err());
+    assert!(reader.next().unwrap().is_err());
+}
+
+#[test]
+fn test_group_by_id_prefix() {
+    let mut reader = FastaReader::from_string(FASTA).unwrap();
+    let mut groups = Vec::new();
+    while let Some(record) = reader.next() {
+        let record = record.unwrap();
+        let prefix = record.header.split('.').next().unwrap().to_string();
+        let mut group = vec![record];
+        while let Some(Ok(next)) = reader.peek() {
+            if !next.header.starts_with(&format!("{}.", prefix)) {
+                break;
+            }
+            group.push(reader.next().unwrap().unwrap());
+        }
+        groups.push((prefix, group.len()));
+    }
+    assert_eq!(groups, [("a".to_string(), 2), ("b".to_string(), 1)]);
+}
@@ -0,0 +1,146 @@
+// Tests for the record pipeline builder
+use prseq::trim::{Adapter, AdapterTrimmer, PolyTrim};
+use prseq::{
+    FastaReader, FastaWriter, FastqReader, FastqWriter, OnError, ParserOptions, Pipeline,
+    PipelineStats, PrseqError, SequenceRecord,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+const FASTQ: &str = "@r1\nACGTACGTAGATCGGAAG\n+\nIIIIIIIIIIIIIIIIII\n\
+    @r2\nNNACGTAAAAAA\n+\nIIIIIIIIIIII\n\
+    @r3\nACG\n+\nIII\n\
+    @r4\nGGGGCCCC\n+\nIIII####\n";
+
+fn fastq() -> FastqReader {
+    FastqReader::from_string(FASTQ).unwrap()
+}
+
+fn fastq_output(writer: FastqWriter<Vec<u8>>) -> String {
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+#[test]
+fn test_empty_pipeline_copies_records() {
+    let mut writer = FastqWriter::new(Vec::new());
+    let stats = Pipeline::new(fastq()).write_to(&mut writer).unwrap();
+    assert_eq!(
+        stats,
+        PipelineStats {
+            read: 4,
+            written: 4
+        }
+    );
+    assert_eq!(fastq_output(writer), FASTQ);
+}
+
+#[test]
+fn test_steps_run_in_order() {
+    let headers: Vec<String> = Pipeline::new(fastq())
+        .trim_quality_ends(20)
+        .filter_length(5..)
+        .map(|mut record| {
+            record.header.insert_str(0, "kept_");
+            record
+        })
+        .map(|record| record)
+        .map(|record| record)
+        .filter(|record| !record.header.ends_with("r2"))
+        .into_iter()
+        .map(|record| record.map(|record| record.header))
+        .collect::<prseq::error::Result<_>>()
+        .unwrap();
+    assert_eq!(headers, ["kept_r1"]);
+
+    // Filtering before trimming keeps r4, which is then cut to 4 bases
+    let lengths: Vec<usize> = Pipeline::new(fastq())
+        .filter_length(5..)
+        .trim_quality_ends(20)
+        .into_iter()
+        .map(|record| record.unwrap().len())
+        .collect();
+    assert_eq!(lengths, [18, 12, 4]);
+}
+
+#[test]
+fn test_trimming_steps() {
+    let trimmer = AdapterTrimmer::new([Adapter::three_prime("AGATCGGAAG")]);
+    let records: Vec<_> = Pipeline::new(fastq())
+        .trim_adapters(trimmer)
+        .trim_ns(2)
+        .trim_poly_tail(PolyTrim::poly_a().min_length(4))
+        .into_iter()
+        .map(|record| record.unwrap())
+        .collect();
+    let sequences: Vec<_> = records.iter().map(|r| r.sequence.as_str()).collect();
+    assert_eq!(sequences, ["ACGTACGT", "ACGT", "ACG", "GGGGCCCC"]);
+    assert!(records.iter().all(|r| r.sequence.len() == r.quality.len()));
+}
+
+#[test]
+fn test_inspect_and_run() {
+    let bases = Arc::new(AtomicU64::new(0));
+    let counted = bases.clone();
+    let stats = Pipeline::new(fastq())
+        .filter(|record| record.id() != "r3")
+        .inspect(move |record| {
+            counted.fetch_add(record.len() as u64, Ordering::Relaxed);
+        })
+        .run()
+        .unwrap();
+    assert_eq!((stats.read, stats.written), (4, 3));
+    assert_eq!(bases.load(Ordering::Relaxed), 38);
+}
+
+#[test]
+fn test_fastq_to_fasta() {
+    let mut writer = FastaWriter::new(Vec::new());
+    Pipeline::new(fastq())
+        .filter_length(..4)
+        .write_to(&mut writer)
+        .unwrap();
+    assert_eq!(writer.into_inner(), b">r3\nACG\n");
+}
+
+#[test]
+fn test_fasta_pipeline() {
+    let reader = FastaReader::from_string(">a\nACGT\n>b\nAC\n>c\nGGGGG\n").unwrap();
+    let mut writer = FastaWriter::with_line_width(Vec::new(), 3);
+    let stats = Pipeline::new(reader)
+        .filter_map(|mut record| {
+            record.sequence.make_ascii_lowercase();
+            (record.sequence.len() > 2).then_some(record)
+        })
+        .write_to(&mut writer)
+        .unwrap();
+    assert_eq!(stats.written, 2);
+    assert_eq!(writer.into_inner(), b">a\nacg\nt\n>c\nggg\ngg\n");
+}
+
+#[test]
+fn test_errors_stop_write_to() {
+    let input = "@r1\nAC\n+\nII\nr2\nAC\n+\nII\n@r3\nAC\n+\nII\n";
+    let mut writer = FastqWriter::new(Vec::new());
+    let error = Pipeline::new(FastqReader::from_string(input).unwrap())
+        .write_to(&mut writer)
+        .unwrap_err();
+    assert!(matches!(error, PrseqError::InvalidFastq { .. }));
+    assert_eq!(fastq_output(writer), "@r1\nAC\n+\nII\n");
+
+    // Skipping malformed records lets the pipeline run to the end
+    let reader = FastqReader::from_string(input)
+        .unwrap()
+        .with_options(ParserOptions::default().on_error(OnError::Skip));
+    let mut records = Pipeline::new(reader).into_iter();
+    assert_eq!(records.by_ref().count(), 2);
+    assert_eq!(records.stats().read, 2);
+}
+
+#[test]
+fn test_pipelines_can_be_sent_between_threads() {
+    let pipeline = Pipeline::new(fastq()).filter_length(10..);
+    let count = std::thread::spawn(move || pipeline.into_iter().count())
+        .join()
+        .unwrap();
+    assert_eq!(count, 2);
+}
@@ -0,0 +1,40 @@
+// Tests for background-thread prefetching
+use prseq::{spawn_reader, FastaReader, FastqReader};
+use std::io::Cursor;
+
+#[test]
+fn test_spawn_fastq_reader() {
+    let content: Vec<u8> = (0..5000)
+        .flat_map(|i| format!("@read{}\nACGT\n+\nIIII\n", i).into_bytes())
+        .collect();
+    let reader = FastqReader::from_reader_with_capacity(Cursor::new(content), 64).unwrap();
+    let ids: Vec<_> = spawn_reader(reader, 8).map(|r| r.unwrap().header).collect();
+    assert_eq!(ids.len(), 5000);
+    assert_eq!(ids[4999], "read4999");
+}
+
+#[test]
+fn test_spawn_reader_delivers_errors() {
+    let reader =
+        FastaReader::from_reader_with_capacity(Cursor::new(b">seq1\nACGT\n".to_vec()), 64).unwrap();
+    let mut records = spawn_reader(reader, 1);
+    assert!(records.next().unwrap().is_ok());
+    assert!(records.next().is_none());
+
+    let reader =
+        FastaReader::from_reader_with_capacity(Cursor::new(b"ACGT\n".to_vec()), 64).unwrap();
+    assert!(spawn_reader(reader, 1).next().unwrap().is_err());
+}
+
+#[test]
+fn test_dropping_prefetch_reader_early() {
+    let records = spawn_reader(0..1_000_000, 4);
+    assert_eq!(records.take(3).collect::<Vec<_>>(), vec![0, 1, 2]);
+}
+
+#[test]
+#[should_panic(expected = "reader failed")]
+fn test_spawn_reader_resumes_panics() {
+    let failing = (0..3).map(|i| if i == 2 { panic!("reader failed") } else { i });
+    for _ in spawn_reader(failing, 1) {}
+}
@@ -0,0 +1,163 @@
+// Tests for reader progress reporting and statistics
+use flate2::write::GzEncoder;
+use prseq::progress::{Progress, ReaderStats};
+use prseq::{BgzfReader, FastaReader, FastaRecord, FastqReader, OnError, ParserOptions};
+use std::io::{Cursor, Write};
+use std::sync::{Arc, Mutex};
+
+fn fastq(records: usize) -> Vec<u8> {
+    (0..records)
+        .flat_map(|i| format!("@read{i}\nACGTACGTAC\n+\nIIIIIIIIII\n").into_bytes())
+        .collect()
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn reports() -> (
+    Arc<Mutex<Vec<Progress>>>,
+    impl FnMut(&Progress) + Send + 'static,
+) {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&reports);
+    (reports, move |progress: &Progress| {
+        sink.lock().unwrap().push(*progress)
+    })
+}
+
+#[test]
+fn test_progress_callback_interval() {
+    let (reports, callback) = reports();
+    let reader = FastqReader::from_reader_with_capacity(Cursor::new(fastq(10)), 64)
+        .unwrap()
+        .on_progress(3, callback);
+    assert_eq!(reader.count(), 10);
+    let reports = reports.lock().unwrap();
+    let records: Vec<_> = reports.iter().map(|p| (p.records, p.finished)).collect();
+    assert_eq!(records, [(3, false), (6, false), (9, false), (10, true)]);
+    let last = reports.last().unwrap();
+    assert_eq!(last.bytes, fastq(10).len() as u64);
+    assert_eq!(last.input_bytes, Some(fastq(10).len() as u64));
+}
+
+#[test]
+fn test_progress_reports_compressed_bytes() {
+    let data = fastq(1000);
+    let compressed = gzip(&data);
+    let (reports, callback) = reports();
+    let mut reader = FastqReader::from_reader_with_capacity(Cursor::new(compressed.clone()), 64)
+        .unwrap()
+        .on_progress(100, callback);
+    assert_eq!(reader.by_ref().count(), 1000);
+    assert_eq!(reports.lock().unwrap().len(), 11);
+
+    let progress = reader.progress();
+    assert_eq!(progress.records, 1000);
+    assert_eq!(progress.bytes, data.len() as u64);
+    assert_eq!(progress.input_bytes, Some(compressed.len() as u64));
+    assert!(compressed.len() < data.len());
+}
+
+#[test]
+fn test_progress_is_reported_once_at_end() {
+    let (reports, callback) = reports();
+    let mut reader = FastaReader::from_string(">a\nACGT\n>b\nGG\n")
+        .unwrap()
+        .on_progress(100, callback);
+    assert_eq!(reader.by_ref().count(), 2);
+    assert!(reader.next().is_none());
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(
+        reports[0],
+        Progress {
+            records: 2,
+            bytes: 14,
+            input_bytes: Some(14),
+            finished: true,
+        }
+    );
+}
+
+#[test]
+fn test_progress_without_callback() {
+    let mut reader = FastaReader::from_string(">a\nACGT\n>b\nGG\n").unwrap();
+    assert_eq!(reader.progress().records, 0);
+    assert_eq!(reader.progress().bytes, 0);
+    reader.next().unwrap().unwrap();
+    assert_eq!(reader.progress().records, 1);
+    assert!(!reader.progress().finished);
+}
+
+#[test]
+fn test_progress_from_bgzf_reader_is_not_counted() {
+    let reader = FastaReader::from_bgzf(BgzfReader::new(Cursor::new(gzip(b">a\nACGT\n"))), 64);
+    assert_eq!(reader.progress().input_bytes, None);
+}
+
+#[test]
+fn test_stats_of_compressed_fastq() {
+    let data = fastq(50);
+    let compressed = gzip(&data);
+    let mut reader =
+        FastqReader::from_reader_with_capacity(Cursor::new(compressed.clone()), 64).unwrap();
+    assert_eq!(reader.by_ref().count(), 50);
+    assert_eq!(
+        reader.stats(),
+        ReaderStats {
+            records: 50,
+            bases: 500,
+            skipped: 0,
+            duplicates: 0,
+            bytes: data.len() as u64,
+            input_bytes: Some(compressed.len() as u64),
+        }
+    );
+}
+
+#[test]
+fn test_stats_count_skipped_records() {
+    let input = "@r1\nACGT\n+\nIIII\n@r2\nACGT\n+r9\nIIII\n@r3\nAC\n+\nII\n";
+    let mut reader = FastqReader::from_reader_with_capacity(Cursor::new(input), 64)
+        .unwrap()
+        .with_options(ParserOptions::default().on_error(OnError::Skip));
+    assert_eq!(reader.by_ref().count(), 2);
+    let stats = reader.stats();
+    assert_eq!((stats.records, stats.bases, stats.skipped), (2, 6, 1));
+}
+
+#[test]
+fn test_stats_exclude_records_that_are_not_utf8() {
+    let input = b">a\nACGT\n>b\nAC\xffGT\n>c\nGG\n".to_vec();
+    let options = ParserOptions::default().on_error(OnError::Skip);
+
+    let mut reader = FastaReader::from_vec(input.clone())
+        .unwrap()
+        .with_options(options.clone());
+    assert_eq!(reader.by_ref().count(), 2);
+    let stats = reader.stats();
+    assert_eq!((stats.records, stats.bases, stats.skipped), (2, 6, 1));
+
+    let mut reader = FastaReader::from_vec(input).unwrap().with_options(options);
+    let mut record = FastaRecord {
+        header: String::new(),
+        sequence: String::new(),
+    };
+    while reader.read_into(&mut record).unwrap() {}
+    let stats = reader.stats();
+    assert_eq!((stats.records, stats.bases, stats.skipped), (2, 6, 1));
+}
+
+#[test]
+fn test_stats_count_peeked_records_once() {
+    let mut reader = FastaReader::from_string(">a\nACGT\n>b\nGG\n").unwrap();
+    reader.peek();
+    reader.peek();
+    assert_eq!(reader.stats().records, 1);
+    assert_eq!(reader.by_ref().count(), 2);
+    assert_eq!(reader.stats().records, 2);
+    assert_eq!(reader.stats().bases, 6);
+}
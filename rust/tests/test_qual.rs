@@ -0,0 +1,92 @@
+// Tests for reading FASTA + QUAL file pairs
+use prseq::error::Result;
+use prseq::qual::{FastaQualReader, QualReader, QualRecord};
+use prseq::{FastaReader, FastqRecord, PrseqError};
+
+fn reader(fasta: &'static [u8], qual: &'static [u8]) -> FastaQualReader {
+    FastaQualReader::new(
+        FastaReader::from_reader_with_capacity(fasta, 64).unwrap(),
+        QualReader::from_reader(qual).unwrap(),
+    )
+}
+
+#[test]
+fn test_qual_reader() {
+    let input = b"\n>r1 desc\n40 40 30\n 20\n\n>r2\n>r3\n0 93\n";
+    let records: Vec<QualRecord> = QualReader::from_reader(&input[..])
+        .unwrap()
+        .collect::<Result<_>>()
+        .unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].header, "r1 desc");
+    assert_eq!(records[0].id(), "r1");
+    assert_eq!(records[0].scores, [40, 40, 30, 20]);
+    assert!(records[1].scores.is_empty());
+    assert_eq!(records[2].quality(), "!~");
+}
+
+#[test]
+fn test_qual_reader_errors() {
+    for input in [&b">r1\n40 x\n"[..], b">r1\n94\n", b">r1\n-1\n"] {
+        let mut reader = QualReader::from_reader(input).unwrap();
+        match reader.next() {
+            Some(Err(PrseqError::InvalidFasta { position, .. })) => assert_eq!(position.line, 2),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    let mut reader = QualReader::from_reader(&b"40 40\n"[..]).unwrap();
+    assert!(reader.next().unwrap().is_err());
+}
+
+#[test]
+fn test_fasta_qual() {
+    let records: Vec<FastqRecord> =
+        reader(b">r1 x\nAC\nGT\n>r2\nA\n", b">r1 x\n40 30\n20 10\n>r2\n2\n")
+            .collect::<Result<_>>()
+            .unwrap();
+    assert_eq!(records[0].header, "r1 x");
+    assert_eq!(records[0].sequence, "ACGT");
+    assert_eq!(records[0].quality, "I?5+");
+    assert_eq!(records[1].quality, "#");
+}
+
+#[test]
+fn test_fasta_qual_id_mismatch() {
+    let mut records = reader(b">r1\nA\n>r2\nC\n", b">r1\n40\n>r3\n40\n");
+    assert!(records.next().unwrap().is_ok());
+    match records.next() {
+        Some(Err(PrseqError::PairMismatch { pair, message })) => {
+            assert_eq!(pair, 2);
+            assert!(message.contains("'r3'"));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(records.next().is_none());
+}
+
+#[test]
+fn test_fasta_qual_length_mismatch() {
+    let mut records = reader(b">r1\nA\n>r2\nCG\n", b">r1\n40\n>r2\n40\n");
+    assert!(records.next().unwrap().is_ok());
+    match records.next() {
+        Some(Err(PrseqError::LengthMismatch {
+            sequence_length,
+            quality_length,
+            position,
+        })) => {
+            assert_eq!((sequence_length, quality_length), (2, 1));
+            assert_eq!((position.line, position.record), (3, 2));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn test_fasta_qual_uneven() {
+    let mut records = reader(b">r1\nA\n>r2\nC\n", b">r1\n40\n");
+    assert!(records.next().unwrap().is_ok());
+    assert!(matches!(
+        records.next(),
+        Some(Err(PrseqError::PairMismatch { pair: 2, .. }))
+    ));
+}
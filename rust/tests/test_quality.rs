@@ -0,0 +1,114 @@
+// Tests for FASTQ quality encodings
+use prseq::{FastqReader, FastqRecord, ParserOptions, PrseqError, QualityEncoding};
+use std::io::{Cursor, Write};
+
+fn record(quality: &str) -> FastqRecord {
+    FastqRecord {
+        header: "read1".to_string(),
+        sequence: "A".repeat(quality.len()),
+        quality: quality.to_string(),
+    }
+}
+
+#[test]
+fn test_detect_encoding() {
+    assert_eq!(
+        QualityEncoding::detect(["IIII", "#!IJ"]),
+        Some(QualityEncoding::Phred33)
+    );
+    assert_eq!(
+        QualityEncoding::detect(["hhhh", "BBfg"]),
+        Some(QualityEncoding::Phred64)
+    );
+    // Characters between '@' and 'K' fit both encodings.
+    assert_eq!(QualityEncoding::detect(["IIII", "@AB"]), None);
+    assert_eq!(QualityEncoding::detect(Vec::<&str>::new()), None);
+}
+
+#[test]
+fn test_detect_file() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    for i in 0..10 {
+        write!(file, "@read{}\nACGT\n+\nhhBh\n", i).unwrap();
+    }
+    file.flush().unwrap();
+    assert_eq!(
+        QualityEncoding::detect_file(file.path(), 5).unwrap(),
+        Some(QualityEncoding::Phred64)
+    );
+}
+
+#[test]
+fn test_record_conversion() {
+    let phred64 = record("h@B~");
+    let phred33 = phred64.to_phred33().unwrap();
+    assert_eq!(phred33.quality, "I!#_");
+    assert_eq!(phred33.sequence, phred64.sequence);
+    assert_eq!(phred33.to_phred64().unwrap(), phred64);
+}
+
+#[test]
+fn test_record_conversion_out_of_range() {
+    let error = record("hh5h").to_phred33().unwrap_err();
+    match &error {
+        PrseqError::InvalidQuality {
+            encoding,
+            offset,
+            byte,
+            position,
+            ..
+        } => {
+            assert_eq!(*encoding, QualityEncoding::Phred64);
+            assert_eq!((*offset, *byte, *position), (2, b'5', None));
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(
+        error.to_string(),
+        "Invalid Phred+64 quality character '5' at offset 2 of record 'read1'"
+    );
+    // Phred+33 characters above '_' have no Phred+64 equivalent.
+    assert!(record("II`I").to_phred64().is_err());
+}
+
+#[test]
+fn test_reader_converts_phred64() {
+    let input = b"@read1\nACGT\n+\nhhBh\n@read2\nAC\n+\n!!\n".to_vec();
+    let options = ParserOptions::default().quality_encoding(QualityEncoding::Phred64);
+    let mut reader = FastqReader::from_reader_with_capacity(Cursor::new(input), 64)
+        .unwrap()
+        .with_options(options);
+    assert_eq!(reader.next().unwrap().unwrap().quality, "II#I");
+    let error = reader.next().unwrap().unwrap_err();
+    assert_eq!(error.position().unwrap().record, 2);
+    assert!(matches!(
+        error,
+        PrseqError::InvalidQuality { offset: 0, .. }
+    ));
+}
+
+#[test]
+fn test_quality_scores() {
+    let record = record("!+5?I");
+    assert_eq!(record.quality_scores(), vec![0, 10, 20, 30, 40]);
+    assert_eq!(record.mean_quality(), Some(20.0));
+    assert_eq!(self::record("").mean_quality(), None);
+}
+
+#[test]
+fn test_expected_errors() {
+    // Q10, Q20 and Q30 have error probabilities of 0.1, 0.01 and 0.001.
+    let errors = record("+5?").expected_errors();
+    assert!((errors - 0.111).abs() < 1e-9);
+    assert_eq!(record("").expected_errors(), 0.0);
+    assert_eq!(record("!!").expected_errors(), 2.0);
+}
+
+#[test]
+fn test_quality_functions_on_bytes() {
+    assert_eq!(
+        prseq::quality::phred_scores(b"I#").collect::<Vec<_>>(),
+        vec![40, 2]
+    );
+    assert_eq!(prseq::quality::mean_quality(b"II"), Some(40.0));
+}
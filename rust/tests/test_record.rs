@@ -0,0 +1,90 @@
+// Tests for the SequenceRecord trait and boxed readers
+use prseq::any::AnyRecord;
+use prseq::error::Result;
+use prseq::record::{BoxRecordsExt, DynReader};
+use prseq::SequenceRecord;
+use prseq::{collect_stats, FastaReader, FastaRecord, FastqReader, FastqRecord, FilterExt};
+
+fn fasta(header: &str, sequence: &str) -> FastaRecord {
+    FastaRecord {
+        header: header.to_string(),
+        sequence: sequence.to_string(),
+    }
+}
+
+fn fastq(header: &str, sequence: &str, quality: &str) -> FastqRecord {
+    FastqRecord {
+        header: header.to_string(),
+        sequence: sequence.to_string(),
+        quality: quality.to_string(),
+    }
+}
+
+/// The parts of a record, through the trait
+fn parts<R: SequenceRecord>(record: &R) -> (&str, Option<&str>, &str, Option<&str>, usize) {
+    (
+        record.id(),
+        record.description(),
+        record.sequence(),
+        record.quality(),
+        record.len(),
+    )
+}
+
+#[test]
+fn test_sequence_record_impls() {
+    let record = fasta("s1  first one", "ACGT");
+    assert_eq!(parts(&record), ("s1", Some("first one"), "ACGT", None, 4));
+
+    let record = fastq("r1", "GG", "I#");
+    assert_eq!(parts(&record), ("r1", None, "GG", Some("I#"), 2));
+
+    let record = AnyRecord::Fastq(record);
+    assert_eq!(parts(&record), ("r1", None, "GG", Some("I#"), 2));
+    assert!(!SequenceRecord::is_empty(&record));
+
+    let record: Box<dyn SequenceRecord> = Box::new(fasta("s2", ""));
+    assert_eq!(parts(&record), ("s2", None, "", None, 0));
+    assert!(record.is_empty());
+}
+
+fn open(fastq: bool) -> Result<DynReader> {
+    Ok(if fastq {
+        FastqReader::from_string("@r1\nACGT\n+\nIIII\n@r2\nAC\n+\nII\n")?.boxed()
+    } else {
+        FastaReader::from_string(">s1\nACGTAC\n>s2\nA\n")?.boxed()
+    })
+}
+
+#[test]
+fn test_boxed_readers() {
+    let ids: Vec<String> = open(false)
+        .unwrap()
+        .map(|record| record.unwrap().id().to_string())
+        .collect();
+    assert_eq!(ids, ["s1", "s2"]);
+
+    let records: Vec<_> = open(true)
+        .unwrap()
+        .filter_length(3..)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].quality(), Some("IIII"));
+}
+
+#[test]
+fn test_boxed_reader_stats() {
+    let stats = collect_stats(open(true).unwrap()).unwrap();
+    assert_eq!(stats.records(), 2);
+    assert_eq!(stats.total_bases(), 6);
+
+    let stats = collect_stats(open(false).unwrap()).unwrap();
+    assert_eq!(stats.total_bases(), 7);
+}
+
+#[test]
+fn test_boxed_reader_errors() {
+    let mut reader = FastaReader::from_string("ACGT\n").unwrap().boxed();
+    assert!(reader.next().unwrap().is_err());
+}
@@ -0,0 +1,109 @@
+// Tests for reading over HTTP, using a minimal local server
+#![cfg(feature = "remote")]
+use prseq::index::FastaIndex;
+use prseq::{FastaReader, FastqReader, IndexedFastaReader};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Cursor, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Serve `files` over HTTP/1.1 (with Range support) on a local port,
+/// recording the ranges requested, and return the base URL
+fn serve(files: HashMap<&'static str, Vec<u8>>, ranges: Arc<Mutex<Vec<String>>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            reader.read_line(&mut request).unwrap();
+            let mut range = None;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = header.strip_prefix("Range: bytes=") {
+                    range = Some(value.trim().trim_end_matches('-').parse::<usize>().unwrap());
+                }
+            }
+            let mut parts = request.split_whitespace();
+            let method = parts.next().unwrap().to_string();
+            let path = parts.next().unwrap().to_string();
+            let response = match files.get(path.as_str()) {
+                None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec(),
+                Some(body) => {
+                    let (status, body) = match range {
+                        Some(start) => {
+                            ranges.lock().unwrap().push(format!("{}:{}", path, start));
+                            ("206 Partial Content", &body[start..])
+                        }
+                        None => ("200 OK", &body[..]),
+                    };
+                    let mut response = format!(
+                        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        status,
+                        body.len()
+                    )
+                    .into_bytes();
+                    if method != "HEAD" {
+                        response.extend_from_slice(body);
+                    }
+                    response
+                }
+            };
+            let _ = stream.write_all(&response);
+        }
+    });
+    url
+}
+
+const FASTA: &[u8] = b">chr1\nACGTA\nCGTAC\nGT\n>chr2\nAAAA\nCC\n";
+
+#[test]
+fn test_reader_from_url_with_gzip() {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(b"@read1\nACGT\n+\nIIII\n").unwrap();
+    let files = HashMap::from([
+        ("/reads.fq.gz", encoder.finish().unwrap()),
+        ("/genome.fa", FASTA.to_vec()),
+    ]);
+    let url = serve(files, Arc::default());
+
+    let records: Vec<_> = FastqReader::from_url(&format!("{}/reads.fq.gz", url))
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records[0].sequence, "ACGT");
+
+    let reader = FastaReader::from_url(&format!("{}/genome.fa", url)).unwrap();
+    assert_eq!(reader.count(), 2);
+
+    assert!(FastaReader::from_url(&format!("{}/missing.fa", url)).is_err());
+}
+
+#[test]
+fn test_indexed_reader_from_url_uses_range_requests() {
+    let index = FastaIndex::build_from_reader(
+        FastaReader::from_reader_with_capacity(Cursor::new(FASTA), 64).unwrap(),
+    )
+    .unwrap();
+    let mut fai = Vec::new();
+    index.write(&mut fai).unwrap();
+
+    let files = HashMap::from([("/genome.fa", FASTA.to_vec()), ("/genome.fa.fai", fai)]);
+    let ranges = Arc::new(Mutex::new(Vec::new()));
+    let url = serve(files, ranges.clone());
+
+    let mut reader = IndexedFastaReader::from_url(&format!("{}/genome.fa", url)).unwrap();
+    assert_eq!(reader.fetch_region("chr2:2-5").unwrap(), "AAAC");
+    assert_eq!(reader.fetch("chr1", 3, 7).unwrap(), "TACG");
+    // After sniffing the start of the file, only the fetched regions are read.
+    assert_eq!(
+        ranges.lock().unwrap().as_slice(),
+        ["/genome.fa:0", "/genome.fa:28", "/genome.fa:9"]
+    );
+}
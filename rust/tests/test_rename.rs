@@ -0,0 +1,86 @@
+// Tests for renaming records
+use prseq::rename::{RenameExt, Renamer};
+use prseq::{AnyRecord, FastaRecord, FastqReader, FastqRecord};
+use std::collections::HashMap;
+
+fn fasta(header: &str) -> FastaRecord {
+    FastaRecord {
+        header: header.to_string(),
+        sequence: "ACGT".to_string(),
+    }
+}
+
+fn renamed(renamer: &mut Renamer, header: &str) -> String {
+    let mut record = fasta(header);
+    renamer.rename(&mut record);
+    record.header
+}
+
+#[test]
+fn test_template() {
+    let mut renamer = Renamer::template("seq{n}");
+    assert_eq!(renamed(&mut renamer, "a desc"), "seq1 desc");
+    assert_eq!(renamed(&mut renamer, "b"), "seq2");
+
+    let mut renamer = Renamer::template("{id}_{n}").start_at(10);
+    assert_eq!(renamed(&mut renamer, "a x"), "a_10 x");
+    assert_eq!(renamed(&mut renamer, "b"), "b_11");
+}
+
+#[test]
+fn test_template_text() {
+    // Unknown placeholders and lone braces are kept as text.
+    let mut renamer = Renamer::template("{x}-{n}-{");
+    assert_eq!(renamed(&mut renamer, "a"), "{x}-1-{");
+    let mut renamer = Renamer::template("fixed");
+    assert_eq!(renamed(&mut renamer, "a b"), "fixed b");
+}
+
+#[test]
+fn test_keep_original() {
+    let mut renamer = Renamer::template("s{n}").keep_original(true);
+    assert_eq!(
+        renamed(&mut renamer, "NODE_1 length=5"),
+        "s1 NODE_1 length=5"
+    );
+    assert_eq!(renamed(&mut renamer, "NODE_2"), "s2 NODE_2");
+}
+
+#[test]
+fn test_map() {
+    let ids: HashMap<String, String> = [("a", "alpha"), ("b", "beta")]
+        .into_iter()
+        .map(|(old, new)| (old.to_string(), new.to_string()))
+        .collect();
+    let mut renamer = Renamer::map(ids);
+    assert_eq!(renamed(&mut renamer, "a one"), "alpha one");
+    assert_eq!(renamed(&mut renamer, "c two"), "c two");
+    let mut renamer = renamer.keep_original(true);
+    assert_eq!(renamed(&mut renamer, "b x"), "beta b x");
+}
+
+#[test]
+fn test_other_record_types() {
+    let mut renamer = Renamer::template("r{n}");
+    let mut read = FastqRecord {
+        header: "old 1:N:0".to_string(),
+        sequence: "A".to_string(),
+        quality: "I".to_string(),
+    };
+    renamer.rename(&mut read);
+    assert_eq!(read.header, "r1 1:N:0");
+    let mut any = AnyRecord::Fasta(fasta("x"));
+    renamer.rename(&mut any);
+    assert_eq!(any.header(), "r2");
+}
+
+#[test]
+fn test_rename_stream() {
+    let input = b"@a\nA\n+\nI\n@b x\nC\n+\nI\n";
+    let reader = FastqReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    let headers: Vec<String> = reader
+        .rename(Renamer::template("read{n}"))
+        .map(|record| record.unwrap().header)
+        .collect();
+    assert_eq!(headers, ["read1", "read2 x"]);
+}
@@ -0,0 +1,85 @@
+// Tests for extracting reads from SAM files
+use prseq::error::Result;
+use prseq::sam::{SamReader, SamRecord, FLAG_REVERSE};
+use prseq::{FastqRecord, PrseqError};
+
+const SAM: &str = "@HD\tVN:1.6\tSO:unsorted
+@SQ\tSN:chr1\tLN:1000
+@PG\tID:bwa\tPN:bwa
+r1\t77\t*\t0\t0\t*\t*\t0\t0\tACGTT\tABCDE\tRG:Z:x
+r1\t141\t*\t0\t0\t*\t*\t0\t0\tGGAA\tIIII
+r2\t16\tchr1\t100\t60\t5M\t*\t0\t0\tAACGT\t12345
+r2\t256\tchr1\t500\t0\t5M\t*\t0\t0\t*\t*
+r3\t2048\tchr1\t700\t0\t2H3M\t*\t0\t0\tCGT\tIII
+r4\t4\t*\t0\t0\t*\t*\t0\t0\tNNA\t*
+";
+
+fn reader(input: &str) -> SamReader {
+    SamReader::from_reader(std::io::Cursor::new(input.as_bytes().to_vec())).unwrap()
+}
+
+fn read(reader: SamReader) -> Vec<FastqRecord> {
+    reader.collect::<Result<_>>().unwrap()
+}
+
+#[test]
+fn test_reads() {
+    let reads = read(reader(SAM));
+    let headers: Vec<&str> = reads.iter().map(|read| read.header.as_str()).collect();
+    assert_eq!(headers, ["r1", "r1", "r2", "r4"]);
+    assert_eq!(reads[0].sequence, "ACGTT");
+    assert_eq!(reads[0].quality, "ABCDE");
+    // Reverse-strand reads are turned back
+    assert_eq!(reads[2].sequence, "ACGTT");
+    assert_eq!(reads[2].quality, "54321");
+    // Missing qualities get Phred 1
+    assert_eq!(reads[3].quality, "\"\"\"");
+}
+
+#[test]
+fn test_options() {
+    let reads = read(reader(SAM).mate_suffix(true).include_secondary(true));
+    let headers: Vec<&str> = reads.iter().map(|read| read.header.as_str()).collect();
+    assert_eq!(headers, ["r1/1", "r1/2", "r2", "r2", "r3", "r4"]);
+    assert_eq!(reads[3].sequence, "");
+}
+
+#[test]
+fn test_sam_records() {
+    let mut reader = reader(SAM);
+    let record = reader.next_sam_record().unwrap().unwrap();
+    assert_eq!(record.flag, 77);
+    assert!(!record.is_reverse());
+    let records: Vec<SamRecord> =
+        std::iter::from_fn(|| reader.next_sam_record().unwrap()).collect();
+    assert_eq!(records.len(), 5);
+    assert!(records[1].flag & FLAG_REVERSE != 0);
+    assert!(records[2].is_secondary());
+    assert!(records[3].is_secondary());
+}
+
+#[test]
+fn test_errors() {
+    let mut records = reader("@HD\tVN:1.6\nr1\t4\t*\t0\n");
+    match records.next() {
+        Some(Err(PrseqError::InvalidFormat {
+            format, position, ..
+        })) => {
+            assert_eq!(format, "SAM");
+            assert_eq!((position.line, position.record), (2, 1));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let mut records = reader("r1\tx\t*\t0\t0\t*\t*\t0\t0\tACGT\tIIII\n");
+    assert!(matches!(
+        records.next(),
+        Some(Err(PrseqError::InvalidFormat { .. }))
+    ));
+
+    let mut records = reader("r1\t4\t*\t0\t0\t*\t*\t0\t0\tACGT\tII\n");
+    assert!(matches!(
+        records.next(),
+        Some(Err(PrseqError::LengthMismatch { .. }))
+    ));
+}
@@ -0,0 +1,130 @@
+// Tests for subsampling
+use prseq::error::Result;
+use prseq::sample::SampleExt;
+use prseq::{FastaRecord, PairedFastqReader, PrseqError};
+
+fn records(count: usize) -> Vec<Result<FastaRecord>> {
+    (0..count)
+        .map(|i| {
+            Ok(FastaRecord {
+                header: format!("seq{}", i),
+                sequence: "ACGT".to_string(),
+            })
+        })
+        .collect()
+}
+
+fn ids(records: &[FastaRecord]) -> Vec<String> {
+    records.iter().map(|record| record.header.clone()).collect()
+}
+
+#[test]
+fn test_sample_fraction() {
+    let kept: Vec<FastaRecord> = records(10_000)
+        .into_iter()
+        .sample_fraction(0.1, 7)
+        .map(|record| record.unwrap())
+        .collect();
+    assert!((800..1200).contains(&kept.len()), "{}", kept.len());
+
+    // Reproducible with the same seed, different with another
+    let again: Vec<FastaRecord> = records(10_000)
+        .into_iter()
+        .sample_fraction(0.1, 7)
+        .map(|record| record.unwrap())
+        .collect();
+    assert_eq!(ids(&kept), ids(&again));
+    let other: Vec<FastaRecord> = records(10_000)
+        .into_iter()
+        .sample_fraction(0.1, 8)
+        .map(|record| record.unwrap())
+        .collect();
+    assert_ne!(ids(&kept), ids(&other));
+
+    assert_eq!(records(100).into_iter().sample_fraction(0.0, 1).count(), 0);
+    assert_eq!(
+        records(100).into_iter().sample_fraction(1.0, 1).count(),
+        100
+    );
+}
+
+#[test]
+fn test_sample_fraction_passes_errors() {
+    let mut input = records(3);
+    input.insert(1, Err(PrseqError::Io(std::io::Error::other("bad"))));
+    let results: Vec<_> = input.into_iter().sample_fraction(0.0, 1).collect();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].is_err());
+}
+
+#[test]
+fn test_sample_count() {
+    let sample = records(1000).into_iter().sample_count(10, 3).unwrap();
+    assert_eq!(sample.len(), 10);
+    // In input order, without repeats
+    let numbers: Vec<usize> = sample
+        .iter()
+        .map(|record| record.header[3..].parse().unwrap())
+        .collect();
+    assert!(numbers.windows(2).all(|pair| pair[0] < pair[1]));
+
+    let again = records(1000).into_iter().sample_count(10, 3).unwrap();
+    assert_eq!(ids(&sample), ids(&again));
+
+    // Fewer records than asked for: all of them
+    let all = records(5).into_iter().sample_count(10, 3).unwrap();
+    assert_eq!(ids(&all), ["seq0", "seq1", "seq2", "seq3", "seq4"]);
+    assert!(records(5)
+        .into_iter()
+        .sample_count(0, 3)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_sample_count_is_uniform() {
+    // Each of 10 records should be chosen about 3000 times in 10000 samples
+    // of 3.
+    let mut chosen = [0u32; 10];
+    for seed in 0..10_000 {
+        for record in records(10).into_iter().sample_count(3, seed).unwrap() {
+            chosen[record.header[3..].parse::<usize>().unwrap()] += 1;
+        }
+    }
+    assert!(
+        chosen.iter().all(|&n| (2700..3300).contains(&n)),
+        "{:?}",
+        chosen
+    );
+}
+
+#[test]
+fn test_sample_count_stops_at_error() {
+    let mut input = records(3);
+    input.insert(1, Err(PrseqError::Io(std::io::Error::other("bad"))));
+    assert!(input.into_iter().sample_count(2, 1).is_err());
+}
+
+#[test]
+fn test_sample_pairs() {
+    let mut r1 = String::new();
+    let mut r2 = String::new();
+    for i in 0..100 {
+        r1.push_str(&format!("@read{}/1\nACGT\n+\nIIII\n", i));
+        r2.push_str(&format!("@read{}/2\nTTTT\n+\nIIII\n", i));
+    }
+    let pairs = PairedFastqReader::from_readers_with_capacity(
+        std::io::Cursor::new(r1.into_bytes()),
+        std::io::Cursor::new(r2.into_bytes()),
+        64,
+    )
+    .unwrap();
+    let sample = pairs.sample_count(5, 11).unwrap();
+    assert_eq!(sample.len(), 5);
+    for (r1, r2) in &sample {
+        assert_eq!(
+            r1.header.trim_end_matches("/1"),
+            r2.header.trim_end_matches("/2")
+        );
+    }
+}
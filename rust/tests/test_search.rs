@@ -0,0 +1,79 @@
+// Tests for motif search
+use prseq::search::{find_motif, Motif, MotifMatch, Strand};
+use prseq::FastaRecord;
+
+fn found(matches: &[MotifMatch]) -> Vec<(usize, Strand, usize)> {
+    matches
+        .iter()
+        .map(|m| (m.start, m.strand, m.mismatches))
+        .collect()
+}
+
+#[test]
+fn test_motif_validation() {
+    assert!(Motif::new("ACGTRYN").is_some());
+    assert!(Motif::new("acgt").is_some());
+    assert!(Motif::new("").is_none());
+    assert!(Motif::new("ACXT").is_none());
+    assert_eq!(Motif::new("ACGG").unwrap().len(), 4);
+}
+
+#[test]
+fn test_forward_and_reverse_matches() {
+    // CCGA is the reverse complement of TCGG.
+    let motif = Motif::new("TCGG").unwrap();
+    let matches = find_motif(b"ATCGGACCGAT", &motif, 0);
+    assert_eq!(
+        found(&matches),
+        [(1, Strand::Forward, 0), (6, Strand::Reverse, 0)]
+    );
+    assert_eq!(matches[1].end, 10);
+}
+
+#[test]
+fn test_ambiguity_codes() {
+    let motif = Motif::new("ARY").unwrap();
+    let matches = find_motif(b"AGCAATANT", &motif, 0);
+    // AGC matches; AAT matches; ANT does not (N could be C or T).
+    assert_eq!(
+        found(&matches)
+            .into_iter()
+            .filter(|m| m.1 == Strand::Forward)
+            .collect::<Vec<_>>(),
+        [(0, Strand::Forward, 0), (3, Strand::Forward, 0)]
+    );
+    // An R in the sequence matches an N in the motif.
+    assert_eq!(find_motif(b"CRC", &Motif::new("CNC").unwrap(), 0).len(), 1);
+}
+
+#[test]
+fn test_mismatches() {
+    let motif = Motif::new("GATTACA").unwrap();
+    let sequence = b"TTGATTACATTGATCACATT";
+    assert_eq!(
+        found(&find_motif(sequence, &motif, 0)),
+        [(2, Strand::Forward, 0)]
+    );
+    assert_eq!(
+        found(&find_motif(sequence, &motif, 1)),
+        [(2, Strand::Forward, 0), (11, Strand::Forward, 1)]
+    );
+}
+
+#[test]
+fn test_palindromic_motif_reported_once() {
+    let motif = Motif::new("GAATTC").unwrap();
+    let matches = find_motif(b"AAGAATTCAA", &motif, 0);
+    assert_eq!(found(&matches), [(2, Strand::Forward, 0)]);
+    assert!(find_motif(b"GAA", &motif, 0).is_empty());
+}
+
+#[test]
+fn test_record_find_motif() {
+    let record = FastaRecord {
+        header: "genome".to_string(),
+        sequence: "acgtGGATCCacgt".to_string(),
+    };
+    let matches = record.find_motif(&Motif::new("GGATCC").unwrap(), 0);
+    assert_eq!(matches[0].start, 4);
+}
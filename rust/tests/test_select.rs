@@ -0,0 +1,98 @@
+// Tests for selecting records by position
+use prseq::error::Result;
+use prseq::select::SelectRecords;
+use prseq::{FastaReader, FastqReader, Filterable};
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+fn fasta(count: usize) -> FastaReader {
+    let input: String = (0..count).map(|i| format!(">seq{}\nACGT\n", i)).collect();
+    FastaReader::from_reader_with_capacity(io::Cursor::new(input.into_bytes()), 64).unwrap()
+}
+
+fn headers<I, R>(records: I) -> Vec<String>
+where
+    I: Iterator<Item = Result<R>>,
+    R: Filterable,
+{
+    records
+        .map(|record| record.unwrap().header().to_string())
+        .collect()
+}
+
+#[test]
+fn test_take_skip_and_range() {
+    assert_eq!(headers(fasta(5).take_records(2)), ["seq0", "seq1"]);
+    assert_eq!(headers(fasta(5).skip_records(3)), ["seq3", "seq4"]);
+    assert_eq!(headers(fasta(5).record_range(1..3)), ["seq1", "seq2"]);
+    assert_eq!(
+        headers(fasta(5).record_range(1..=3)),
+        ["seq1", "seq2", "seq3"]
+    );
+    assert_eq!(headers(fasta(5).record_range(..)).len(), 5);
+}
+
+#[test]
+fn test_ranges_past_the_end() {
+    assert_eq!(headers(fasta(3).take_records(10)).len(), 3);
+    assert!(headers(fasta(3).skip_records(10)).is_empty());
+    assert!(headers(fasta(3).record_range(5..8)).is_empty());
+    assert!(headers(fasta(3).take_records(0)).is_empty());
+    #[allow(clippy::reversed_empty_ranges)]
+    let empty = headers(fasta(3).record_range(2..1));
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_fastq() {
+    let input = b"@r1\nA\n+\nI\n@r2\nC\n+\nI\n@r3\nG\n+\nI\n";
+    let reader = FastqReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    assert_eq!(headers(reader.record_range(1..2)), ["r2"]);
+}
+
+#[test]
+fn test_continue_after_range() {
+    let mut range = fasta(5).take_records(2);
+    assert_eq!(range.by_ref().count(), 2);
+    let rest = range.into_inner();
+    assert_eq!(headers(rest), ["seq2", "seq3", "seq4"]);
+}
+
+#[test]
+fn test_errors_in_skipped_records() {
+    let input = b"@r1\nA\n+\nI\n@r2\nCC\n+\nI\n@r3\nG\n+\nI\n";
+    let reader = FastqReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    let results: Vec<_> = reader.skip_records(2).collect();
+    assert!(results[0].is_err());
+}
+
+/// A reader that counts the bytes read through it
+struct Counting<R> {
+    inner: R,
+    count: Arc<AtomicUsize>,
+}
+
+impl<R: Read> Read for Counting<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buffer)?;
+        self.count.fetch_add(n, Ordering::SeqCst);
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_take_stops_reading() {
+    let input: String = (0..100_000).map(|i| format!(">seq{}\nACGT\n", i)).collect();
+    let total = input.len();
+    let count = Arc::new(AtomicUsize::new(0));
+    let source = Counting {
+        inner: io::Cursor::new(input.into_bytes()),
+        count: Arc::clone(&count),
+    };
+    let reader = FastaReader::from_reader_with_capacity(source, 64).unwrap();
+    let mut first = reader.take_records(10);
+    assert_eq!(first.by_ref().count(), 10);
+    assert_eq!(first.next().map(|r| r.is_ok()), None);
+    assert!(count.load(Ordering::SeqCst) < total / 2);
+}
@@ -0,0 +1,150 @@
+// Tests for sequence utilities
+use prseq::sequence::{
+    complement, gc_content, mask_to_n, masked_fraction, n_count, reverse_complement, unmask,
+    BaseCounts,
+};
+use prseq::{FastaRecord, FastqRecord, PrseqError};
+use std::ops::Bound;
+
+#[test]
+fn test_reverse_complement() {
+    assert_eq!(reverse_complement(b"AACGTT"), b"AACGTT");
+    assert_eq!(reverse_complement(b"ACCGu"), b"aCGGT");
+    assert_eq!(reverse_complement(b"RYKMBDHVNSW-"), b"-WSNBDHVKMRY");
+    assert_eq!(reverse_complement(b""), b"");
+}
+
+#[test]
+fn test_complement() {
+    assert_eq!(complement(b'a'), b't');
+    assert_eq!(complement(b'T'), b'A');
+    assert_eq!(complement(b'X'), b'X');
+}
+
+#[test]
+fn test_fasta_slice() {
+    let record = FastaRecord {
+        header: "seq1 description".to_string(),
+        sequence: "ACGTACGTAA".to_string(),
+    };
+    let slice = record.slice(2..5).unwrap();
+    assert_eq!(slice.header, "seq1 description");
+    assert_eq!(slice.sequence, "GTA");
+    assert_eq!(record.slice(..).unwrap(), record);
+    assert_eq!(record.slice(..-2).unwrap().sequence, "ACGTACGT");
+    assert_eq!(record.slice(-4..-1).unwrap().sequence, "GTA");
+    let inclusive = (Bound::Included(8), Bound::Included(-1));
+    assert_eq!(record.slice(inclusive).unwrap().sequence, "AA");
+    assert_eq!(record.slice(10..).unwrap().sequence, "");
+    assert_eq!(record.subsequence(0, 3).unwrap().sequence, "ACG");
+}
+
+#[test]
+fn test_slice_out_of_range() {
+    let record = FastaRecord {
+        header: "seq1".to_string(),
+        sequence: "ACGT".to_string(),
+    };
+    for error in [
+        record.slice(2..5).unwrap_err(),
+        record.slice(-5..).unwrap_err(),
+        record.subsequence(3, 1).unwrap_err(),
+        record.slice(..=4).unwrap_err(),
+    ] {
+        assert!(matches!(error, PrseqError::OutOfRange { length: 4, .. }));
+    }
+    assert_eq!(
+        record.subsequence(1, 9).unwrap_err().to_string(),
+        "Range 1..9 is out of bounds for a sequence of length 4"
+    );
+}
+
+#[test]
+fn test_fastq_slice() {
+    let record = FastqRecord {
+        header: "read1".to_string(),
+        sequence: "ACGTAC".to_string(),
+        quality: "ABCDEF".to_string(),
+    };
+    let slice = record.subsequence(1, -1).unwrap();
+    assert_eq!(slice.sequence, "CGTA");
+    assert_eq!(slice.quality, "BCDE");
+    assert_eq!(record.subsequence(-2, 6).unwrap().quality, "EF");
+    assert!(record.slice(..7).is_err());
+}
+
+#[test]
+fn test_base_counts() {
+    let counts = BaseCounts::new(b"AACGTUNnacg-R");
+    assert_eq!(
+        counts,
+        BaseCounts {
+            a: 3,
+            c: 2,
+            g: 2,
+            t: 2,
+            n: 2,
+            other: 2,
+        }
+    );
+    assert_eq!(counts.total(), 13);
+
+    let mut counts = BaseCounts::default();
+    assert_eq!(counts.gc_content(), None);
+    counts.add(b"GG");
+    counts.add(b"AT");
+    assert_eq!(counts.gc_content(), Some(0.5));
+}
+
+#[test]
+fn test_gc_content_and_n_count() {
+    assert_eq!(gc_content(b"GCGCAT"), Some(4.0 / 6.0));
+    assert_eq!(gc_content(b"gcNNNN"), Some(1.0));
+    assert_eq!(gc_content(b"NNN"), None);
+    assert_eq!(n_count(b"ANnNC"), 3);
+
+    let record = FastqRecord {
+        header: "read1".to_string(),
+        sequence: "GGATNN".to_string(),
+        quality: "IIIIII".to_string(),
+    };
+    assert_eq!(record.gc_content(), Some(0.5));
+    assert_eq!(record.n_count(), 2);
+    assert_eq!(record.base_counts().g, 2);
+}
+
+#[test]
+fn test_mask_to_n() {
+    let mut sequence = b"ACgtn-Nr".to_vec();
+    mask_to_n(&mut sequence);
+    assert_eq!(sequence, b"ACNNN-NN");
+}
+
+#[test]
+fn test_unmask() {
+    let mut sequence = b"ACgtn-r".to_vec();
+    unmask(&mut sequence);
+    assert_eq!(sequence, b"ACGTN-R");
+}
+
+#[test]
+fn test_masked_fraction() {
+    assert_eq!(masked_fraction(b"ACgt"), Some(0.5));
+    assert_eq!(masked_fraction(b"ACGT"), Some(0.0));
+    assert_eq!(masked_fraction(b"acgt"), Some(1.0));
+    assert_eq!(masked_fraction(b""), None);
+}
+
+#[test]
+fn test_record_soft_masking() {
+    let mut record = FastaRecord {
+        header: "chr1".to_string(),
+        sequence: "aaCCgg".to_string(),
+    };
+    let mut unmasked = record.clone();
+    unmasked.unmask();
+    assert_eq!(unmasked.sequence, "AACCGG");
+    assert_eq!(unmasked.masked_fraction(), Some(0.0));
+    record.mask_to_n();
+    assert_eq!(record.sequence, "NNCCNN");
+}
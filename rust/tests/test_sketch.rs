@@ -0,0 +1,178 @@
+// Tests for MinHash sketching
+use prseq::sequence::reverse_complement;
+use prseq::sketch::{jaccard, MinHash, SketchExt};
+use prseq::{FastaReader, FastaRecord};
+
+/// A deterministic pseudo-random sequence
+fn random_sequence(length: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed;
+    (0..length)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            b"ACGT"[(state >> 62) as usize]
+        })
+        .collect()
+}
+
+fn sketch(sequence: &[u8], k: usize, size: usize) -> MinHash {
+    let mut sketch = MinHash::new(k, size);
+    sketch.add(sequence);
+    sketch
+}
+
+#[test]
+fn test_sketch_keeps_smallest_hashes() {
+    let sequence = random_sequence(2000, 1);
+    let small = sketch(&sequence, 15, 50);
+    let large = sketch(&sequence, 15, 500);
+    assert_eq!(small.len(), 50);
+    assert_eq!(large.len(), 500);
+    let smallest: Vec<u64> = large.hashes().take(50).collect();
+    assert_eq!(small.hashes().collect::<Vec<_>>(), smallest);
+}
+
+#[test]
+fn test_sketch_smaller_than_size() {
+    let sketch = sketch(b"ACGTACGT", 4, 100);
+    // ACGT, CGTA, GTAC and TACG
+    assert_eq!(sketch.len(), 4);
+    assert!(sketch.contains(b"GTAC"));
+    assert!(!sketch.contains(b"AAAA"));
+    assert!(!sketch.contains(b"GTA"));
+}
+
+#[test]
+fn test_empty_sketch() {
+    let empty = sketch(b"NNNN", 3, 10);
+    assert!(empty.is_empty());
+    assert_eq!(empty.jaccard(&empty), 0.0);
+    assert_eq!(empty.distance(&empty), f64::INFINITY);
+}
+
+#[test]
+fn test_identical_sequences() {
+    let sequence = random_sequence(1000, 2);
+    let a = sketch(&sequence, 21, 200);
+    let b = sketch(&sequence, 21, 200);
+    assert_eq!(a, b);
+    assert_eq!(a.jaccard(&b), 1.0);
+    assert_eq!(a.distance(&b), 0.0);
+}
+
+#[test]
+fn test_unrelated_sequences() {
+    let a = sketch(&random_sequence(5000, 3), 21, 200);
+    let b = sketch(&random_sequence(5000, 4), 21, 200);
+    assert_eq!(jaccard(&a, &b), 0.0);
+}
+
+#[test]
+fn test_jaccard_estimate() {
+    // Two sequences sharing half their (distinct) 21-mers: J = 1/3
+    let shared = random_sequence(5000, 5);
+    let mut first = shared.clone();
+    first.extend(random_sequence(5000, 6));
+    let mut second = shared;
+    second.extend(random_sequence(5000, 7));
+    let similarity = sketch(&first, 21, 1000).jaccard(&sketch(&second, 21, 1000));
+    assert!((similarity - 1.0 / 3.0).abs() < 0.05, "{}", similarity);
+}
+
+#[test]
+fn test_canonical_sketches_ignore_strand() {
+    let sequence = random_sequence(1000, 8);
+    let mut forward = MinHash::new(21, 100).canonical(true);
+    forward.add(&sequence);
+    let mut reverse = MinHash::new(21, 100).canonical(true);
+    reverse.add(&reverse_complement(&sequence));
+    assert_eq!(forward.jaccard(&reverse), 1.0);
+    // Without canonical k-mers, the strands share nothing
+    let reverse = sketch(&reverse_complement(&sequence), 21, 100);
+    assert_eq!(sketch(&sequence, 21, 100).jaccard(&reverse), 0.0);
+}
+
+#[test]
+fn test_long_kmers() {
+    let sequence = random_sequence(1000, 9);
+    let a = sketch(&sequence, 40, 100);
+    let b = sketch(&sequence.to_ascii_lowercase(), 40, 100);
+    assert_eq!(a.len(), 100);
+    assert_eq!(a.jaccard(&b), 1.0);
+}
+
+#[test]
+fn test_merge() {
+    let first = random_sequence(1000, 10);
+    let second = random_sequence(1000, 11);
+    let mut merged = sketch(&first, 15, 100);
+    merged.merge(&sketch(&second, 15, 100));
+    let mut both = MinHash::new(15, 100);
+    both.add(&first);
+    both.add(&second);
+    assert_eq!(merged, both);
+}
+
+#[test]
+#[should_panic]
+fn test_incompatible_sketches() {
+    sketch(b"ACGT", 3, 10).jaccard(&sketch(b"ACGT", 2, 10));
+}
+
+#[test]
+fn test_collect_file() {
+    let reader = FastaReader::from_string(">a\nACGTT\n>b\nCCGGA\n").unwrap();
+    let file = MinHash::new(3, 100).collect(reader).unwrap();
+    let mut expected = MinHash::new(3, 100);
+    expected.extend([
+        FastaRecord {
+            header: "a".to_string(),
+            sequence: "ACGTT".to_string(),
+        },
+        FastaRecord {
+            header: "b".to_string(),
+            sequence: "CCGGA".to_string(),
+        },
+    ]);
+    assert_eq!(file, expected);
+    assert_eq!(file.len(), 6);
+}
+
+#[test]
+fn test_sketch_each() {
+    let reader = FastaReader::from_string(">a\nACGTT\n>b\nACGTA\n").unwrap();
+    let template = MinHash::new(4, 10).canonical(true);
+    let sketched: Vec<(FastaRecord, MinHash)> = reader
+        .sketch_each(template)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(sketched[0].0.header, "a");
+    assert_eq!(sketched[1].1.len(), 2);
+    assert_eq!(sketched[0].1.k(), 4);
+    // ACGT is shared, CGTT and CGTA are not
+    assert!((sketched[0].1.jaccard(&sketched[1].1) - 1.0 / 3.0).abs() < 1e-9);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialized_sketches_are_checked() {
+    let mut sketch = MinHash::new(4, 2);
+    sketch.add(b"ACGTTGCA");
+    let json = serde_json::to_string(&sketch).unwrap();
+    let read_back: MinHash = serde_json::from_str(&json).unwrap();
+    assert_eq!(read_back, sketch);
+
+    let error =
+        serde_json::from_str::<MinHash>(r#"{"k":0,"size":2,"canonical":false,"hashes":[]}"#)
+            .unwrap_err();
+    assert!(error.to_string().contains("at least 1 base long"));
+    let error =
+        serde_json::from_str::<MinHash>(r#"{"k":4,"size":0,"canonical":false,"hashes":[]}"#)
+            .unwrap_err();
+    assert!(error.to_string().contains("at least one hash"));
+    let error =
+        serde_json::from_str::<MinHash>(r#"{"k":4,"size":1,"canonical":false,"hashes":[1,2]}"#)
+            .unwrap_err();
+    assert!(error.to_string().contains("more than its size"));
+}
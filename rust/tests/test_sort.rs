@@ -0,0 +1,208 @@
+// Tests for sorting records
+use prseq::error::{PrseqError, Result};
+use prseq::sort::{merge_sorted, sort_by_id, sort_by_length, SortKey, SortOptions};
+use prseq::{AnyRecord, FastaReader, FastaRecord, FastqReader, FastqRecord};
+use std::fs;
+use std::path::PathBuf;
+
+fn fasta(header: &str, sequence: &str) -> Result<FastaRecord> {
+    Ok(FastaRecord {
+        header: header.to_string(),
+        sequence: sequence.to_string(),
+    })
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("prseq-test-sort-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn headers<R: prseq::Filterable>(records: impl Iterator<Item = Result<R>>) -> Vec<String> {
+    records
+        .map(|record| record.unwrap().header().to_string())
+        .collect()
+}
+
+fn records() -> Vec<Result<FastaRecord>> {
+    vec![
+        fasta("d x", "AAAA"),
+        fasta("b", "A"),
+        fasta("a", "AAA"),
+        fasta("e", "A"),
+        fasta("c", "AAAAA"),
+        fasta("b2", "AAA"),
+    ]
+}
+
+#[test]
+fn test_sort_by_length_in_memory() {
+    let sorted = sort_by_length(records(), &SortOptions::new()).unwrap();
+    assert_eq!(sorted.spilled_runs(), 0);
+    assert_eq!(headers(sorted), ["b", "e", "a", "b2", "d x", "c"]);
+
+    let sorted = sort_by_length(records(), &SortOptions::new().reverse(true)).unwrap();
+    assert_eq!(headers(sorted), ["c", "d x", "a", "b2", "b", "e"]);
+}
+
+#[test]
+fn test_sort_by_id() {
+    let sorted = sort_by_id(records(), &SortOptions::new()).unwrap();
+    assert_eq!(headers(sorted), ["a", "b", "b2", "c", "d x", "e"]);
+}
+
+#[test]
+fn test_sort_with_spill() {
+    let dir = temp_dir("spill");
+    // A tiny memory budget spills a run every record or two.
+    let options = SortOptions::new().max_memory(100).temp_dir(&dir);
+    let sorted = sort_by_length(records(), &options).unwrap();
+    assert!(sorted.spilled_runs() > 1);
+    assert_eq!(headers(sorted), ["b", "e", "a", "b2", "d x", "c"]);
+
+    let sorted = sort_by_id(records(), &options.clone().reverse(true)).unwrap();
+    assert_eq!(headers(sorted), ["e", "d x", "c", "b2", "b", "a"]);
+
+    // The temporary files are removed once the iterators are dropped.
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    fs::remove_dir(&dir).unwrap();
+}
+
+#[test]
+fn test_sort_with_many_runs() {
+    let dir = temp_dir("many");
+    // Every record is a run: far more than are merged at once.
+    let options = SortOptions::new().max_memory(1).temp_dir(&dir);
+    let input: Vec<Result<FastaRecord>> = (0..5000)
+        .map(|i| fasta(&format!("r{}", i), &"A".repeat(i * 7 % 10)))
+        .collect();
+    let mut sorted = sort_by_length(input, &options).unwrap();
+    assert_eq!(sorted.spilled_runs(), 5000);
+    // The spill files have no names to find (or clash with).
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+    let first = sorted.next().unwrap().unwrap();
+    assert_eq!(first.header, "r0");
+    let mut previous = (first.sequence.len(), 0);
+    let mut count = 1;
+    for record in sorted {
+        let record = record.unwrap();
+        let index: usize = record.header[1..].parse().unwrap();
+        // Equal lengths keep their input order.
+        assert!((record.sequence.len(), index) > previous);
+        previous = (record.sequence.len(), index);
+        count += 1;
+    }
+    assert_eq!(count, 5000);
+    fs::remove_dir(&dir).unwrap();
+}
+
+#[test]
+fn test_sort_fastq_and_any_with_spill() {
+    let dir = temp_dir("fastq");
+    let options = SortOptions::new().max_memory(1).temp_dir(&dir);
+    let reads: Vec<Result<FastqRecord>> = ["ACG", "", "A"]
+        .iter()
+        .enumerate()
+        .map(|(i, sequence)| {
+            Ok(FastqRecord {
+                header: format!("r{}", i),
+                sequence: sequence.to_string(),
+                quality: "I".repeat(sequence.len()),
+            })
+        })
+        .collect();
+    let sorted: Vec<FastqRecord> = sort_by_length(reads, &options)
+        .unwrap()
+        .collect::<Result<_>>()
+        .unwrap();
+    assert_eq!(sorted[0].header, "r1");
+    assert_eq!(sorted[0].quality, "");
+    assert_eq!(sorted[2].quality, "III");
+
+    let mixed = vec![
+        Ok(AnyRecord::Fasta(FastaRecord {
+            header: "z".to_string(),
+            sequence: "AC".to_string(),
+        })),
+        Ok(AnyRecord::Fastq(FastqRecord {
+            header: "y".to_string(),
+            sequence: "A".to_string(),
+            quality: "#".to_string(),
+        })),
+    ];
+    let sorted: Vec<AnyRecord> = sort_by_id(mixed, &options)
+        .unwrap()
+        .collect::<Result<_>>()
+        .unwrap();
+    assert!(matches!(&sorted[0], AnyRecord::Fastq(read) if read.quality == "#"));
+    assert!(matches!(&sorted[1], AnyRecord::Fasta(record) if record.header == "z"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_sort_reader_and_errors() {
+    let input = b">b\nAA\n>a\nA\n";
+    let reader = FastaReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    let sorted = sort_by_id(reader, &SortOptions::new()).unwrap();
+    assert_eq!(headers(sorted), ["a", "b"]);
+
+    let input = b"@r1\nA\n+\nI\n@r2\nCC\n+\nI\n";
+    let reader = FastqReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    assert!(sort_by_id(reader, &SortOptions::new()).is_err());
+}
+
+#[test]
+fn test_merge_sorted_by_id() {
+    let first = vec![fasta("a", "A"), fasta("c 1", "C"), fasta("e", "")];
+    let second = vec![fasta("b", "G"), fasta("c 2", "GG")];
+    let third: Vec<Result<FastaRecord>> = Vec::new();
+    let merged = merge_sorted([first, second, third].map(Vec::into_iter), SortKey::Id);
+    // Equal IDs come from the earlier input first.
+    assert_eq!(headers(merged), ["a", "b", "c 1", "c 2", "e"]);
+}
+
+#[test]
+fn test_merge_sorted_by_length_in_reverse() {
+    let options = SortOptions::new().reverse(true);
+    let mut first = records();
+    let second = first.split_off(3);
+    let first = sort_by_length(first, &options).unwrap();
+    let second = sort_by_length(second, &options).unwrap();
+    let merged = merge_sorted([first, second], SortKey::Length).reverse(true);
+    let all = sort_by_length(records(), &options).unwrap();
+    assert_eq!(headers(merged), headers(all));
+}
+
+#[test]
+fn test_merge_sorted_detects_unsorted_input() {
+    let first = vec![fasta("a", "A"), fasta("c", "C"), fasta("b", "G")];
+    let second = vec![fasta("d", "T")];
+    let mut merged = merge_sorted([first.into_iter(), second.into_iter()], SortKey::Id);
+    assert_eq!(merged.next().unwrap().unwrap().header, "a");
+    let error = merged.next().unwrap().unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Input 1 is not sorted: record 'b' follows 'c'"));
+    match error {
+        PrseqError::InvalidFormat {
+            format, position, ..
+        } => {
+            assert_eq!(format, "sorted");
+            assert_eq!(position.record, 3);
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(merged.next().is_none());
+}
+
+#[test]
+fn test_merge_sorted_readers() {
+    let first = FastqReader::from_string("@r1\nACGT\n+\nIIII\n@r3\nA\n+\nI\n").unwrap();
+    let second = FastqReader::from_string("@r2\nGG\n+\nII\n").unwrap();
+    let merged: Vec<FastqRecord> = merge_sorted([first, second], SortKey::Id)
+        .collect::<Result<_>>()
+        .unwrap();
+    let ids: Vec<&str> = merged.iter().map(|record| record.id()).collect();
+    assert_eq!(ids, ["r1", "r2", "r3"]);
+}
@@ -0,0 +1,106 @@
+// Tests for splitting records into files
+use prseq::error::Result;
+use prseq::split::{split, SplitBy};
+use prseq::{FastaReader, FastaRecord, FastqReader, FastqRecord};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("prseq-test-split-{}-{}", name, std::process::id()))
+}
+
+fn records(lengths: &[usize]) -> Vec<Result<FastaRecord>> {
+    lengths
+        .iter()
+        .enumerate()
+        .map(|(i, &length)| {
+            Ok(FastaRecord {
+                header: format!("s{}", i + 1),
+                sequence: "A".repeat(length),
+            })
+        })
+        .collect()
+}
+
+fn ids(path: &Path) -> Vec<String> {
+    FastaReader::from_file(path)
+        .unwrap()
+        .map(|record| record.unwrap().header)
+        .collect()
+}
+
+fn file_names(paths: &[PathBuf]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect()
+}
+
+#[test]
+fn test_split_parts() {
+    let dir = temp_dir("parts");
+    let paths = split(records(&[1, 1, 1, 1, 1]), &dir, "x", SplitBy::Parts(3)).unwrap();
+    assert_eq!(
+        file_names(&paths),
+        ["x.part_001.fasta", "x.part_002.fasta", "x.part_003.fasta"]
+    );
+    assert_eq!(ids(&paths[0]), ["s1", "s4"]);
+    assert_eq!(ids(&paths[1]), ["s2", "s5"]);
+    assert_eq!(ids(&paths[2]), ["s3"]);
+
+    // Every part is written, even if empty.
+    let paths = split(records(&[1]), &dir, "y", SplitBy::Parts(2)).unwrap();
+    assert_eq!(fs::metadata(&paths[1]).unwrap().len(), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_split_records() {
+    let dir = temp_dir("records");
+    let paths = split(records(&[1, 1, 1, 1, 1]), &dir, "x", SplitBy::Records(2)).unwrap();
+    assert_eq!(paths.len(), 3);
+    assert_eq!(ids(&paths[0]), ["s1", "s2"]);
+    assert_eq!(ids(&paths[2]), ["s5"]);
+
+    let paths = split(records(&[]), &dir, "none", SplitBy::Records(2)).unwrap();
+    assert!(paths.is_empty());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_split_bases() {
+    let dir = temp_dir("bases");
+    let paths = split(records(&[4, 5, 12, 3, 3, 4]), &dir, "x", SplitBy::Bases(10)).unwrap();
+    assert_eq!(paths.len(), 3);
+    assert_eq!(ids(&paths[0]), ["s1", "s2"]);
+    // A record longer than the cap is on its own.
+    assert_eq!(ids(&paths[1]), ["s3"]);
+    assert_eq!(ids(&paths[2]), ["s4", "s5", "s6"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_split_fastq() {
+    let dir = temp_dir("fastq");
+    let input = b"@a\nA\n+\nI\n@b\nC\n+\nI\n@c\nG\n+\nI\n";
+    let reader = FastqReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    let paths = split(reader, &dir, "reads", SplitBy::Records(2)).unwrap();
+    assert_eq!(
+        file_names(&paths),
+        ["reads.part_001.fastq", "reads.part_002.fastq"]
+    );
+    let reads: Vec<FastqRecord> = FastqReader::from_file(&paths[1])
+        .unwrap()
+        .collect::<Result<_>>()
+        .unwrap();
+    assert_eq!(reads[0].header, "c");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_split_invalid() {
+    let dir = temp_dir("invalid");
+    for by in [SplitBy::Parts(0), SplitBy::Records(0), SplitBy::Bases(0)] {
+        assert!(split(records(&[1]), &dir, "x", by).is_err());
+    }
+}
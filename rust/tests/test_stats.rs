@@ -0,0 +1,127 @@
+// Tests for sequence statistics
+use prseq::{
+    collect_quality_profile, collect_stats, FastaReader, FastaRecord, FastqReader, QualityProfile,
+    SeqStats,
+};
+use std::io::Cursor;
+
+#[test]
+fn test_fasta_stats() {
+    let input = b">a\nACGTACGTAC\n>b\nGGGCC\n>c\nATNN\n>d\nA\n";
+    let reader = FastaReader::from_reader_with_capacity(Cursor::new(input), 64).unwrap();
+    let stats = collect_stats(reader).unwrap();
+
+    assert_eq!(stats.records(), 4);
+    assert_eq!(stats.total_bases(), 20);
+    assert_eq!(stats.min_length(), Some(1));
+    assert_eq!(stats.max_length(), Some(10));
+    assert_eq!(stats.mean_length(), Some(5.0));
+    assert_eq!(stats.n50(), Some(10));
+    assert_eq!(stats.n90(), Some(4));
+    assert_eq!(stats.nx(0.6), Some(5));
+    assert_eq!(stats.base_counts().n, 2);
+    assert_eq!(stats.gc_content(), Some(10.0 / 18.0));
+    assert_eq!(stats.mean_quality(), None);
+    assert_eq!(stats.q30_fraction(), None);
+}
+
+#[test]
+fn test_fastq_stats() {
+    // '5' is Q20, '?' is Q30 and '+' is Q10.
+    let input = b"@r1\nACGT\n+\n?5++\n@r2\nGG\n+\n??\n";
+    let reader = FastqReader::from_reader_with_capacity(Cursor::new(input), 64).unwrap();
+    let stats = collect_stats(reader).unwrap();
+
+    assert_eq!(stats.records(), 2);
+    assert_eq!(
+        stats.mean_quality(),
+        Some((30.0 + 20.0 + 10.0 + 10.0 + 30.0 + 30.0) / 6.0)
+    );
+    assert_eq!(stats.q20_fraction(), Some(4.0 / 6.0));
+    assert_eq!(stats.q30_fraction(), Some(3.0 / 6.0));
+}
+
+#[test]
+fn test_empty_stats() {
+    let stats = SeqStats::new();
+    assert_eq!(stats.records(), 0);
+    assert_eq!(stats.min_length(), None);
+    assert_eq!(stats.mean_length(), None);
+    assert_eq!(stats.n50(), None);
+    assert_eq!(stats.gc_content(), None);
+}
+
+#[test]
+fn test_merge_and_extend() {
+    let records: Vec<FastaRecord> = ["AC", "GGGG", "T"]
+        .iter()
+        .map(|sequence| FastaRecord {
+            header: "x".to_string(),
+            sequence: sequence.to_string(),
+        })
+        .collect();
+    let mut all = SeqStats::new();
+    all.extend(&records);
+
+    let mut first = SeqStats::new();
+    first.extend(&records[..1]);
+    let mut rest = SeqStats::new();
+    rest.extend(records[1..].to_vec());
+    first.merge(&rest);
+    assert_eq!(first, all);
+    assert_eq!(all.total_bases(), 7);
+}
+
+#[test]
+fn test_collect_stats_stops_at_error() {
+    let input = b"@r1\nACGT\n+\nIIII\n@r2\nACGT\n+\nII\n";
+    let reader = FastqReader::from_reader_with_capacity(Cursor::new(input), 64).unwrap();
+    assert!(collect_stats(reader).is_err());
+}
+
+#[test]
+fn test_quality_profile() {
+    // 'I' is Q40, '5' is Q20 and '+' is Q10.
+    let input = b"@r1\nACGT\n+\nII5+\n@r2\nAGG\n+\nI55\n@r3\nN\n+\n+\n";
+    let reader = FastqReader::from_reader_with_capacity(Cursor::new(input), 64).unwrap();
+    let profile = collect_quality_profile(reader).unwrap();
+
+    assert_eq!(profile.records(), 3);
+    assert_eq!(profile.len(), 4);
+    let positions = profile.positions();
+    assert_eq!(positions[0].count(), 3);
+    assert_eq!(positions[0].qualities[40], 2);
+    assert_eq!(positions[0].qualities[10], 1);
+    assert_eq!(positions[0].bases.a, 2);
+    assert_eq!(positions[0].bases.n, 1);
+    assert_eq!(positions[3].count(), 1);
+    assert_eq!(profile.mean_qualities(), vec![30.0, 30.0, 20.0, 10.0]);
+    assert_eq!(profile.gc_contents(), vec![0.0, 1.0, 1.0, 0.0]);
+}
+
+#[test]
+fn test_position_quantiles() {
+    let mut profile = QualityProfile::new();
+    for quality in [b"+", b"5", b"5", b"I"] {
+        profile.add(b"A", quality);
+    }
+    let position = &profile.positions()[0];
+    assert_eq!(position.quality_quantile(0.0), Some(10));
+    assert_eq!(position.quality_quantile(0.25), Some(10));
+    assert_eq!(position.quality_quantile(0.5), Some(20));
+    assert_eq!(position.quality_quantile(1.0), Some(40));
+    assert_eq!(QualityProfile::new().positions().len(), 0);
+}
+
+#[test]
+fn test_quality_profile_merge() {
+    let mut a = QualityProfile::new();
+    a.add(b"AC", b"II");
+    let mut b = QualityProfile::new();
+    b.add(b"ACGT", b"5555");
+    let mut both = QualityProfile::new();
+    both.add(b"AC", b"II");
+    both.add(b"ACGT", b"5555");
+    a.merge(&b);
+    assert_eq!(a, both);
+}
@@ -0,0 +1,163 @@
+// Tests for tabular (TSV and CSV) output and input
+use prseq::any::AnyRecord;
+use prseq::error::{PrseqError, Result};
+use prseq::tabular::{Column, TabularReader, TabularWriter};
+use prseq::{FastaRecord, FastqRecord};
+
+fn fasta(header: &str, sequence: &str) -> FastaRecord {
+    FastaRecord {
+        header: header.to_string(),
+        sequence: sequence.to_string(),
+    }
+}
+
+fn fastq(header: &str, sequence: &str, quality: &str) -> FastqRecord {
+    FastqRecord {
+        header: header.to_string(),
+        sequence: sequence.to_string(),
+        quality: quality.to_string(),
+    }
+}
+
+/// The text written by `write` with `writer`
+fn output<W: FnOnce(&mut TabularWriter<Vec<u8>>)>(
+    mut writer: TabularWriter<Vec<u8>>,
+    write: W,
+) -> String {
+    write(&mut writer);
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+#[test]
+fn test_tabular_writer_defaults() {
+    let text = output(TabularWriter::new(Vec::new()), |writer| {
+        writer.write_fasta(&fasta("s1 first one", "ACGT")).unwrap();
+        writer.write_fastq(&fastq("r1", "GG", "I#")).unwrap();
+    });
+    assert_eq!(
+        text,
+        "id\tdescription\tsequence\tquality\ns1\tfirst one\tACGT\t\nr1\t\tGG\tI#\n"
+    );
+}
+
+#[test]
+fn test_tabular_writer_columns_and_quoting() {
+    let writer = TabularWriter::new(Vec::new())
+        .columns(vec![Column::Description, Column::Length, Column::Gc])
+        .delimiter(b',')
+        .header(false);
+    let text = output(writer, |writer| {
+        writer
+            .write_record(&AnyRecord::Fasta(fasta("s1 a, \"quoted\" name", "GGCA")))
+            .unwrap();
+        writer
+            .write_record(&AnyRecord::Fasta(fasta("s2", "NNN")))
+            .unwrap();
+    });
+    assert_eq!(text, "\"a, \"\"quoted\"\" name\",4,0.7500\n,3,\n");
+}
+
+#[test]
+fn test_column_names() {
+    assert_eq!("GC".parse::<Column>().unwrap(), Column::Gc);
+    assert_eq!(Column::Description.to_string(), "description");
+    let error = "width".parse::<Column>().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_tabular_round_trip() {
+    let records = vec![
+        AnyRecord::Fasta(fasta("s1 first, \"one\"", "ACGT")),
+        AnyRecord::Fastq(fastq("r1", "GGC", "I#5")),
+    ];
+    let writer = TabularWriter::new(Vec::new())
+        .columns(vec![
+            Column::Length,
+            Column::Id,
+            Column::Sequence,
+            Column::Description,
+            Column::Quality,
+        ])
+        .delimiter(b',');
+    let text = output(writer, |writer| {
+        for record in &records {
+            writer.write_record(record).unwrap();
+        }
+    });
+    let read: Vec<AnyRecord> = TabularReader::from_reader(std::io::Cursor::new(text))
+        .unwrap()
+        .delimiter(b',')
+        .collect::<Result<_>>()
+        .unwrap();
+    assert_eq!(read, records);
+}
+
+#[test]
+fn test_tabular_reader_ignores_other_columns() {
+    let input = b"Sequence\tsample\tID\ns1seq\tA\tid1\n\nGG\tB\tid2\n";
+    let records: Vec<_> = TabularReader::from_reader(&input[..])
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        records,
+        [
+            AnyRecord::Fasta(fasta("id1", "s1seq")),
+            AnyRecord::Fasta(fasta("id2", "GG"))
+        ]
+    );
+}
+
+#[test]
+fn test_tabular_reader_without_header() {
+    let input = b"r1\tACG\tIII\nr2\tTT\t\n";
+    let records: Vec<_> = TabularReader::from_reader(&input[..])
+        .unwrap()
+        .columns(vec![Column::Id, Column::Sequence, Column::Quality])
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        records,
+        [
+            AnyRecord::Fastq(fastq("r1", "ACG", "III")),
+            AnyRecord::Fasta(fasta("r2", "TT"))
+        ]
+    );
+}
+
+#[test]
+fn test_tabular_reader_errors() {
+    let mut reader = TabularReader::from_reader(&b"id\tquality\nr1\tII\n"[..]).unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(error.to_string().contains("no sequence column"));
+
+    let input = b"id\tsequence\tquality\nr1\tACG\tII\n";
+    let mut reader = TabularReader::from_reader(&input[..]).unwrap();
+    match reader.next().unwrap().unwrap_err() {
+        PrseqError::LengthMismatch { position, .. } => assert_eq!(position.line, 2),
+        error => panic!("Unexpected error {:?}", error),
+    }
+
+    let input = b"id,sequence\n\"r1,ACG\n";
+    let mut reader = TabularReader::from_reader(&input[..])
+        .unwrap()
+        .delimiter(b',');
+    let error = reader.next().unwrap().unwrap_err();
+    assert!(error.to_string().contains("Unclosed quote"));
+}
+
+#[test]
+fn test_tabular_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("records.tsv");
+    let mut writer = TabularWriter::to_file(&path).unwrap();
+    writer.write_fasta(&fasta("s1", "ACGT")).unwrap();
+    writer.flush().unwrap();
+    drop(writer);
+    let records: Vec<_> = TabularReader::from_file(&path)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records, [AnyRecord::Fasta(fasta("s1", "ACGT"))]);
+}
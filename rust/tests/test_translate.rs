@@ -0,0 +1,201 @@
+// Tests for translation
+use prseq::sequence::reverse_complement;
+use prseq::translate::{
+    find_orfs, translate, translate_six_frames, translate_with_options, Frame, GeneticCode, Orf,
+    PartialCodons, StopCodons, TranslateOptions,
+};
+use prseq::{FastaRecord, FastqRecord};
+
+#[test]
+fn test_genetic_codes() {
+    assert_eq!(GeneticCode::all().len(), 25);
+    assert_eq!(GeneticCode::ncbi(1), Some(GeneticCode::STANDARD));
+    assert_eq!(GeneticCode::ncbi(7), None);
+    assert_eq!(
+        GeneticCode::ncbi(11).unwrap().name(),
+        "Bacterial, Archaeal and Plant Plastid"
+    );
+    let ids: Vec<u8> = GeneticCode::all().iter().map(|code| code.id()).collect();
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_standard_codons() {
+    let code = GeneticCode::STANDARD;
+    assert_eq!(code.translate_codon(*b"ATG"), b'M');
+    assert_eq!(code.translate_codon(*b"atg"), b'M');
+    assert_eq!(code.translate_codon(*b"AUG"), b'M');
+    assert_eq!(code.translate_codon(*b"TGG"), b'W');
+    for stop in [b"TAA", b"TAG", b"TGA"] {
+        assert_eq!(code.translate_codon(*stop), b'*');
+    }
+    assert_eq!(code.translate_codon(*b"GGG"), b'G');
+}
+
+#[test]
+fn test_ambiguous_codons() {
+    let code = GeneticCode::STANDARD;
+    assert_eq!(code.translate_codon(*b"GGN"), b'G');
+    assert_eq!(code.translate_codon(*b"TTY"), b'F');
+    assert_eq!(code.translate_codon(*b"TTN"), b'X');
+    assert_eq!(code.translate_codon(*b"TAR"), b'*');
+    assert_eq!(code.translate_codon(*b"AT-"), b'X');
+    assert_eq!(code.translate_codon(*b"---"), b'-');
+}
+
+#[test]
+fn test_frames() {
+    let sequence = b"AATGGCCTGAC";
+    assert_eq!(
+        translate(sequence, Frame::F1, &GeneticCode::STANDARD),
+        "NGL"
+    );
+    assert_eq!(
+        translate(sequence, Frame::F2, &GeneticCode::STANDARD),
+        "MA*"
+    );
+    assert_eq!(
+        translate(sequence, Frame::F3, &GeneticCode::STANDARD),
+        "WPD"
+    );
+    // The reverse complement is GTCAGGCCATT.
+    assert_eq!(
+        translate(sequence, Frame::R1, &GeneticCode::STANDARD),
+        "VRP"
+    );
+    assert_eq!(
+        translate(sequence, Frame::R2, &GeneticCode::STANDARD),
+        "SGH"
+    );
+    assert_eq!(
+        translate_six_frames(sequence, &GeneticCode::STANDARD),
+        ["NGL", "MA*", "WPD", "VRP", "SGH", "QAI"].map(String::from)
+    );
+    assert_eq!(Frame::R2.to_string(), "-2");
+    assert_eq!(translate(b"A", Frame::F3, &GeneticCode::STANDARD), "");
+}
+
+#[test]
+fn test_options() {
+    let options = TranslateOptions::default()
+        .stop_codons(StopCodons::Truncate)
+        .partial_codons(PartialCodons::Pad);
+    assert_eq!(
+        translate_with_options(b"ATGTGAGG", Frame::F1, &options),
+        "M"
+    );
+    assert_eq!(
+        translate_with_options(b"ATGGGGG", Frame::F1, &options),
+        "MGX"
+    );
+
+    let options = options.code(GeneticCode::ncbi(2).unwrap());
+    assert_eq!(
+        translate_with_options(b"ATGTGAAGA", Frame::F1, &options),
+        "MW"
+    );
+}
+
+#[test]
+fn test_record_translation() {
+    let record = FastaRecord {
+        header: "seq1 gene".to_string(),
+        sequence: "ATGAAATAG".to_string(),
+    };
+    let protein = record.translate(Frame::F1, &TranslateOptions::default());
+    assert_eq!(protein.header, "seq1 gene");
+    assert_eq!(protein.sequence, "MK*");
+
+    let record = FastqRecord {
+        header: "read1".to_string(),
+        sequence: "ATGAAA".to_string(),
+        quality: "IIIIII".to_string(),
+    };
+    assert_eq!(
+        record
+            .translate(Frame::F1, &TranslateOptions::default())
+            .sequence,
+        "MK"
+    );
+}
+
+#[test]
+fn test_find_orfs_forward() {
+    // ATG AAA CCC TAG in frame +1, then more bases
+    let orfs = find_orfs(b"ATGAAACCCTAGCC", 3, &GeneticCode::STANDARD);
+    assert_eq!(
+        orfs,
+        [Orf {
+            start: 0,
+            end: 12,
+            frame: Frame::F1,
+            protein: "MKP".to_string(),
+            complete: true,
+        }]
+    );
+}
+
+#[test]
+fn test_find_orfs_reverse() {
+    // The reverse complement of CCC ATG AAA TGG TAG
+    let sequence = reverse_complement(b"CCCATGAAATGGTAG");
+    let orfs = find_orfs(&sequence, 3, &GeneticCode::STANDARD);
+    assert_eq!(orfs.len(), 1);
+    let orf = &orfs[0];
+    assert_eq!(orf.frame, Frame::R1);
+    assert_eq!(orf.protein, "MKW");
+    // Forward-strand coordinates: the stop codon CTA is at 0..3 and the
+    // start codon CAT at 9..12
+    assert_eq!((orf.start, orf.end), (0, 12));
+    assert_eq!(&sequence[orf.start..orf.end], b"CTACCATTTCAT");
+}
+
+#[test]
+fn test_find_orfs_incomplete() {
+    let orfs = find_orfs(b"GGATGAAACCCGG", 3, &GeneticCode::STANDARD);
+    let orf = orfs.iter().find(|orf| orf.frame == Frame::F3).unwrap();
+    assert_eq!(orf.protein, "MKP");
+    assert!(!orf.complete);
+    assert_eq!((orf.start, orf.end), (2, 11));
+}
+
+#[test]
+fn test_find_orfs_nested_and_repeated() {
+    // An ATG within an ORF does not start another; after the stop, the
+    // next ATG starts a new one
+    let orfs = find_orfs(b"ATGATGTAAATGCCCTGA", 1, &GeneticCode::STANDARD);
+    let forward: Vec<&str> = orfs
+        .iter()
+        .filter(|orf| orf.frame == Frame::F1)
+        .map(|orf| orf.protein.as_str())
+        .collect();
+    assert_eq!(forward, ["MM", "MP"]);
+}
+
+#[test]
+fn test_find_orfs_min_len() {
+    let sequence = b"ATGAAATAG";
+    assert_eq!(find_orfs(sequence, 2, &GeneticCode::STANDARD).len(), 1);
+    assert!(find_orfs(sequence, 3, &GeneticCode::STANDARD).is_empty());
+}
+
+#[test]
+fn test_find_orfs_genetic_code() {
+    // TGA is a stop in the standard code but W in vertebrate mitochondria
+    let sequence = b"ATGTGAAAATAG";
+    let standard = find_orfs(sequence, 1, &GeneticCode::STANDARD);
+    assert_eq!(standard[0].protein, "M");
+    let mito = find_orfs(sequence, 1, &GeneticCode::ncbi(2).unwrap());
+    assert_eq!(mito[0].protein, "MWK");
+}
+
+#[test]
+fn test_record_find_orfs() {
+    let record = FastaRecord {
+        header: "virus".to_string(),
+        sequence: "ATGAAACCCTAGCC".to_string(),
+    };
+    let orfs = record.find_orfs(3, &GeneticCode::STANDARD);
+    assert_eq!(orfs.len(), 1);
+    assert_eq!(orfs[0].frame.to_string(), "+1");
+}
@@ -0,0 +1,268 @@
+// Tests for adapter, quality, homopolymer and N trimming
+use prseq::trim::{
+    n_ends_range, poly_tail_start, quality_ends_range, sliding_window_range, Adapter,
+    AdapterTrimmer, PolyTrim, TrimExt,
+};
+use prseq::{FastaRecord, FastqReader, FastqRecord};
+
+const ADAPTER: &str = "AGATCGGAAGAGC";
+
+fn read(sequence: &str) -> FastqRecord {
+    FastqRecord {
+        header: "read1 extra".to_string(),
+        sequence: sequence.to_string(),
+        quality: "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789"[..sequence.len()].to_string(),
+    }
+}
+
+#[test]
+fn test_three_prime_full_adapter() {
+    let trimmer = AdapterTrimmer::new([Adapter::three_prime(ADAPTER)]);
+    let (trimmed, report) = read("CCCCCCAGATCGGAAGAGCTTTT").trim_adapters(&trimmer);
+    assert_eq!(trimmed.header, "read1 extra");
+    assert_eq!(trimmed.sequence, "CCCCCC");
+    assert_eq!(trimmed.quality, "ABCDEF");
+    assert_eq!(report.kept, 0..6);
+    assert_eq!(report.removed(), 17);
+    let (index, found) = report.adapter.unwrap();
+    assert_eq!((index, found.start, found.end), (0, 6, 19));
+    assert_eq!((found.aligned, found.errors), (13, 0));
+}
+
+#[test]
+fn test_three_prime_partial_adapter() {
+    let trimmer = AdapterTrimmer::new([Adapter::three_prime(ADAPTER)]);
+    let (trimmed, report) = read("CCCCCCCCAGAT").trim_adapters(&trimmer);
+    assert_eq!(trimmed.sequence, "CCCCCCCC");
+    assert_eq!(report.adapter.unwrap().1.aligned, 4);
+
+    // Two bases is less than the default minimum overlap.
+    let (trimmed, report) = read("CCCCCCCCAG").trim_adapters(&trimmer);
+    assert_eq!(trimmed.sequence, "CCCCCCCCAG");
+    assert!(!report.is_trimmed());
+
+    let trimmer = AdapterTrimmer::new([Adapter::three_prime(ADAPTER).min_overlap(1)]);
+    let (trimmed, _) = read("CCCCCCCCCA").trim_adapters(&trimmer);
+    assert_eq!(trimmed.sequence, "CCCCCCCCC");
+}
+
+#[test]
+fn test_mismatches_and_error_rate() {
+    // One mismatch in 13 bases is allowed at the default rate of 0.1.
+    let trimmer = AdapterTrimmer::new([Adapter::three_prime(ADAPTER)]);
+    let (trimmed, report) = read("CCCCAGATCGTAAGAGC").trim_adapters(&trimmer);
+    assert_eq!(trimmed.sequence, "CCCC");
+    assert_eq!(report.adapter.unwrap().1.errors, 1);
+
+    let exact = AdapterTrimmer::new([Adapter::three_prime(ADAPTER).max_error_rate(0.0)]);
+    let (trimmed, _) = read("CCCCAGATCGTAAGAGC").trim_adapters(&exact);
+    assert_eq!(trimmed.sequence, "CCCCAGATCGTAAGAGC");
+}
+
+#[test]
+fn test_indels() {
+    // The read is missing the adapter's second G.
+    let sequence = "CCCCAGATCGAAGAGC";
+    let trimmer = AdapterTrimmer::new([Adapter::three_prime(ADAPTER)]);
+    let (trimmed, report) = read(sequence).trim_adapters(&trimmer);
+    assert_eq!(trimmed.sequence, "CCCC");
+    assert_eq!(report.adapter.unwrap().1.errors, 1);
+
+    let trimmer = AdapterTrimmer::new([Adapter::three_prime(ADAPTER).indels(false)]);
+    let (trimmed, _) = read(sequence).trim_adapters(&trimmer);
+    assert_eq!(trimmed.sequence, sequence);
+}
+
+#[test]
+fn test_five_prime_adapter() {
+    let trimmer = AdapterTrimmer::new([Adapter::five_prime("ACACTCTTTCCC")]);
+    let (trimmed, report) = read("GGACACTCTTTCCCGTGT").trim_adapters(&trimmer);
+    assert_eq!(trimmed.sequence, "GTGT");
+    assert_eq!(trimmed.quality, "OPQR");
+    assert_eq!(report.kept, 14..18);
+
+    // The read starts part way through the adapter.
+    let (trimmed, _) = read("TTTCCCGTGT").trim_adapters(&trimmer);
+    assert_eq!(trimmed.sequence, "GTGT");
+}
+
+#[test]
+fn test_ambiguity_codes_in_adapter() {
+    let trimmer = AdapterTrimmer::new([Adapter::three_prime("AGNNCGG").max_error_rate(0.0)]);
+    let (trimmed, _) = read("TTTTAGTACGG").trim_adapters(&trimmer);
+    assert_eq!(trimmed.sequence, "TTTT");
+}
+
+#[test]
+fn test_best_of_several_adapters() {
+    let trimmer = AdapterTrimmer::new([
+        Adapter::three_prime("TTTTTTTT"),
+        Adapter::three_prime(ADAPTER),
+    ]);
+    let report = trimmer.trim(b"CCCCAGATCGGAAGAGC");
+    assert_eq!(report.adapter.unwrap().0, 1);
+    assert_eq!(report.kept, 0..4);
+
+    let report = trimmer.trim(b"GGGGGGGG");
+    assert!(!report.is_trimmed());
+    assert_eq!(report.kept, 0..8);
+}
+
+#[test]
+fn test_fasta_record() {
+    let trimmer = AdapterTrimmer::new([Adapter::three_prime(ADAPTER)]);
+    let record = FastaRecord {
+        header: "seq".to_string(),
+        sequence: "ACGTAGATCGGAAGAGCAAAA".to_string(),
+    };
+    let (trimmed, _) = record.trim_adapters(&trimmer);
+    assert_eq!(trimmed.sequence, "ACGT");
+}
+
+#[test]
+fn test_trim_stream() {
+    let input = b"@r1\nACGTAGATCGGAAG\n+\nIIIIIIIIIIIIII\n@r2\nACGTACGT\n+\nIIIIIIII\n";
+    let reader = FastqReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    let trimmer = AdapterTrimmer::new([Adapter::three_prime(ADAPTER)]);
+    let trimmed: Vec<_> = reader
+        .trim_adapters(trimmer)
+        .map(|result| result.unwrap())
+        .map(|(record, report)| (record.sequence, report.is_trimmed()))
+        .collect();
+    assert_eq!(
+        trimmed,
+        [("ACGT".to_string(), true), ("ACGTACGT".to_string(), false)]
+    );
+}
+
+#[test]
+fn test_sliding_window() {
+    // Qualities 40, 40, 40, 40, 30, 10, 2, 2
+    assert_eq!(sliding_window_range(b"IIII?+##", 4, 20), 0..5);
+    assert_eq!(sliding_window_range(b"IIII?+##", 4, 2), 0..8);
+    // The first window fails: its leading bases of quality 20+ are kept.
+    assert_eq!(sliding_window_range(b"5###IIII", 4, 20), 0..1);
+    assert_eq!(sliding_window_range(b"####IIII", 4, 20), 0..0);
+    // A dip that the window averages over is kept.
+    assert_eq!(sliding_window_range(b"III#IIII", 4, 20), 0..8);
+    assert_eq!(sliding_window_range(b"III#IIII", 1, 20), 0..3);
+}
+
+#[test]
+fn test_sliding_window_short_reads() {
+    // Shorter than the window: the whole read is one window.
+    assert_eq!(sliding_window_range(b"II", 4, 20), 0..2);
+    assert_eq!(sliding_window_range(b"#I", 4, 30), 0..0);
+    assert_eq!(sliding_window_range(b"", 4, 20), 0..0);
+    assert_eq!(sliding_window_range(b"I#", 0, 20), 0..1);
+}
+
+#[test]
+fn test_quality_ends() {
+    assert_eq!(quality_ends_range(b"##II#I##", 3), 2..6);
+    assert_eq!(quality_ends_range(b"IIII", 3), 0..4);
+    assert_eq!(quality_ends_range(b"####", 3), 4..4);
+    assert_eq!(quality_ends_range(b"", 3), 0..0);
+}
+
+#[test]
+fn test_quality_trimming_keeps_quality_in_sync() {
+    let read = FastqRecord {
+        header: "r1 x".to_string(),
+        sequence: "ACGTACGT".to_string(),
+        quality: "##II#I##".to_string(),
+    };
+    let trimmed = read.trim_quality_ends(3);
+    assert_eq!(trimmed.header, "r1 x");
+    assert_eq!(trimmed.sequence, "GTAC");
+    assert_eq!(trimmed.quality, "II#I");
+
+    let trimmed = read.trim_quality(2, 20);
+    assert_eq!(trimmed.sequence, "");
+    assert_eq!(trimmed.quality, "");
+}
+
+#[test]
+fn test_trim_quality_stream() {
+    let input = b"@r1\nACGTACGT\n+\nIIII?+##\n@r2\nACGT\n+\n##II\n";
+    let reader = FastqReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    let sequences: Vec<String> = reader
+        .trim_quality_ends(3)
+        .trim_quality(4, 20)
+        .map(|record| record.unwrap().sequence)
+        .collect();
+    assert_eq!(sequences, ["ACGTAC", "GT"]);
+}
+
+#[test]
+fn test_poly_tail() {
+    let poly_a = PolyTrim::poly_a();
+    assert_eq!(poly_tail_start(b"ACGTCAAAAAAAAAAAA", None, &poly_a), 5);
+    assert_eq!(poly_tail_start(b"ACGTCaaaaaaaaaaaa", None, &poly_a), 5);
+    // Too short to trim.
+    assert_eq!(poly_tail_start(b"ACGTCAAAAAAAA", None, &poly_a), 13);
+    assert_eq!(poly_tail_start(b"", None, &poly_a), 0);
+    // One error in the tail is allowed; the cut is never made at the error.
+    assert_eq!(poly_tail_start(b"CCCCCCAAAAACAAAAA", None, &poly_a), 6);
+    assert_eq!(poly_tail_start(b"CCCCCCCAAAAAAAAAA", None, &poly_a), 7);
+    // But two in eleven bases are not.
+    assert_eq!(
+        poly_tail_start(b"CCCCCCAAATACAAAAA", None, &poly_a.clone().min_length(5)),
+        12
+    );
+    let exact = PolyTrim::poly_g().min_length(3).max_error_rate(0.0);
+    assert_eq!(poly_tail_start(b"ACGGGTGGG", None, &exact), 6);
+}
+
+#[test]
+fn test_poly_tail_quality() {
+    let sequence = b"CCCCCCGGGTAGGGG";
+    let quality = b"IIIIIIIIII#IIII";
+    let poly_g = PolyTrim::poly_g().min_length(4).max_error_rate(0.0);
+    assert_eq!(poly_tail_start(sequence, Some(quality), &poly_g), 11);
+    // The low-quality A is not counted as an error, but the T is.
+    let poly_g = poly_g.min_quality(20);
+    assert_eq!(poly_tail_start(sequence, Some(quality), &poly_g), 11);
+    let quality = b"IIIIIIIII##IIII";
+    assert_eq!(poly_tail_start(sequence, Some(quality), &poly_g), 6);
+}
+
+#[test]
+fn test_n_ends() {
+    assert_eq!(n_ends_range(b"NNACGTNN", 1), 2..6);
+    assert_eq!(n_ends_range(b"NNACGTNNN", 3), 0..6);
+    assert_eq!(n_ends_range(b"ACNGT", 1), 0..5);
+    assert_eq!(n_ends_range(b"NNNN", 2), 4..4);
+    assert_eq!(n_ends_range(b"", 1), 0..0);
+}
+
+#[test]
+fn test_poly_and_n_trimming_records() {
+    let read = FastqRecord {
+        header: "r1".to_string(),
+        sequence: "NNACGTAAAAAAAAAAN".to_string(),
+        quality: "##IIIIIIIIIIIIII#".to_string(),
+    };
+    let trimmed = read.trim_ns(1).trim_poly_tail(&PolyTrim::poly_a());
+    assert_eq!(trimmed.sequence, "ACGT");
+    assert_eq!(trimmed.quality, "IIII");
+
+    let record = FastaRecord {
+        header: "seq".to_string(),
+        sequence: "NACGTCGGGGGGGGGGGG".to_string(),
+    };
+    let trimmed = record.trim_poly_tail(&PolyTrim::poly_g()).trim_ns(1);
+    assert_eq!(trimmed.sequence, "ACGTC");
+}
+
+#[test]
+fn test_poly_tail_stream() {
+    let input = b"@r1\nACGTCGGGGGGGGGG\n+\nIIIIIIIIIIIIIII\n@r2\nNACGT\n+\n#IIII\n";
+    let reader = FastqReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    let sequences: Vec<String> = reader
+        .trim_poly_tail(PolyTrim::poly_g())
+        .trim_ns(1)
+        .map(|record| record.unwrap().sequence)
+        .collect();
+    assert_eq!(sequences, ["ACGTC", "ACGT"]);
+}
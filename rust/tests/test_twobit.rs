@@ -0,0 +1,207 @@
+// Tests for reading UCSC 2bit files
+use prseq::error::{PrseqError, Result};
+use prseq::twobit::TwoBitReader;
+use prseq::FastaRecord;
+use std::io::Cursor;
+
+/// Encode sequences as a 2bit file. Lower case bases are soft-masked and
+/// Ns are recorded as N blocks.
+fn encode(sequences: &[(&str, &str)], big_endian: bool, version: u32) -> Vec<u8> {
+    let word = |value: u32| {
+        if big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        }
+    };
+    let blocks = |sequence: &[u8], test: &dyn Fn(u8) -> bool| {
+        let mut blocks: Vec<(u32, u32)> = Vec::new();
+        for (i, &base) in sequence.iter().enumerate() {
+            if test(base) {
+                match blocks.last_mut() {
+                    Some((start, length)) if (*start + *length) as usize == i => *length += 1,
+                    _ => blocks.push((i as u32, 1)),
+                }
+            }
+        }
+        blocks
+    };
+    let mut records = Vec::new();
+    for (_, sequence) in sequences {
+        let bytes = sequence.as_bytes();
+        let mut record = Vec::new();
+        record.extend(word(bytes.len() as u32));
+        for test in [
+            &(|b: u8| b.eq_ignore_ascii_case(&b'N')) as &dyn Fn(u8) -> bool,
+            &|b: u8| b.is_ascii_lowercase(),
+        ] {
+            let found = blocks(bytes, test);
+            record.extend(word(found.len() as u32));
+            for (start, _) in &found {
+                record.extend(word(*start));
+            }
+            for (_, length) in &found {
+                record.extend(word(*length));
+            }
+        }
+        record.extend(word(0));
+        for chunk in bytes.chunks(4) {
+            let mut byte = 0u8;
+            for (i, base) in chunk.iter().enumerate() {
+                let code = match base.to_ascii_uppercase() {
+                    b'T' => 0,
+                    b'C' => 1,
+                    b'A' => 2,
+                    b'G' => 3,
+                    _ => 0,
+                };
+                byte |= code << (6 - 2 * i);
+            }
+            record.push(byte);
+        }
+        records.push(record);
+    }
+
+    let mut file = Vec::new();
+    file.extend(word(0x1A41_2743));
+    file.extend(word(version));
+    file.extend(word(sequences.len() as u32));
+    file.extend(word(0));
+    let offset_size = if version == 1 { 8 } else { 4 };
+    let index_size: usize = sequences
+        .iter()
+        .map(|(name, _)| 1 + name.len() + offset_size)
+        .sum();
+    let mut offset = 16 + index_size;
+    for ((name, _), record) in sequences.iter().zip(&records) {
+        file.push(name.len() as u8);
+        file.extend(name.as_bytes());
+        if version == 1 {
+            let offset = offset as u64;
+            file.extend(if big_endian {
+                offset.to_be_bytes()
+            } else {
+                offset.to_le_bytes()
+            });
+        } else {
+            file.extend(word(offset as u32));
+        }
+        offset += record.len();
+    }
+    for record in records {
+        file.extend(record);
+    }
+    file
+}
+
+const SEQUENCES: [(&str, &str); 3] = [
+    ("chr1", "ACGTacgtNNNNACGTA"),
+    ("chr2", "GGCCTTAAG"),
+    ("empty", ""),
+];
+
+fn reader(big_endian: bool, version: u32) -> TwoBitReader<Cursor<Vec<u8>>> {
+    TwoBitReader::new(Cursor::new(encode(&SEQUENCES, big_endian, version))).unwrap()
+}
+
+#[test]
+fn test_names_and_lengths() {
+    let mut reader = reader(false, 0);
+    assert_eq!(
+        reader.names().collect::<Vec<_>>(),
+        ["chr1", "chr2", "empty"]
+    );
+    assert_eq!(reader.len(), 3);
+    assert_eq!(reader.length("chr1").unwrap(), 17);
+    assert_eq!(reader.length("empty").unwrap(), 0);
+    assert!(reader.length("chr3").is_err());
+}
+
+#[test]
+fn test_fetch() {
+    for (big_endian, version) in [(false, 0), (true, 0), (false, 1)] {
+        let mut reader = reader(big_endian, version);
+        assert_eq!(reader.fetch_sequence("chr1").unwrap(), "ACGTacgtNNNNACGTA");
+        assert_eq!(reader.fetch_sequence("chr2").unwrap(), "GGCCTTAAG");
+        assert_eq!(reader.fetch_sequence("empty").unwrap(), "");
+        assert_eq!(reader.fetch("chr1", 3, 10).unwrap(), "TacgtNN");
+        assert_eq!(reader.fetch("chr1", 15, 100).unwrap(), "TA");
+        assert_eq!(reader.fetch("chr2", 5, 6).unwrap(), "T");
+        assert!(matches!(
+            reader.fetch("chr2", 8, 3),
+            Err(PrseqError::OutOfRange { length: 9, .. })
+        ));
+    }
+}
+
+#[test]
+fn test_without_soft_mask() {
+    let mut reader = reader(false, 0).soft_mask(false);
+    assert_eq!(reader.fetch_sequence("chr1").unwrap(), "ACGTACGTNNNNACGTA");
+}
+
+#[test]
+fn test_records() {
+    let mut reader = reader(false, 0);
+    assert_eq!(
+        reader.fetch_record("chr2").unwrap(),
+        FastaRecord {
+            header: "chr2".to_string(),
+            sequence: "GGCCTTAAG".to_string(),
+        }
+    );
+    let records: Vec<FastaRecord> = reader.records().collect::<Result<_>>().unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].sequence, "ACGTacgtNNNNACGTA");
+}
+
+#[test]
+fn test_not_2bit() {
+    assert!(matches!(
+        TwoBitReader::new(Cursor::new(b">chr1\nACGT\nACGTACGTACGT\n".to_vec())),
+        Err(PrseqError::InvalidFormat { .. })
+    ));
+}
+
+#[test]
+fn test_fetch_within_many_blocks() {
+    let sequence = "ACGTNNNNacgtNNAnNaccNNNNNNTT";
+    let data = encode(&[("chr1", sequence)], false, 0);
+    let mut reader = TwoBitReader::new(Cursor::new(data)).unwrap();
+    for start in 0..sequence.len() {
+        for end in start..=sequence.len() {
+            let fetched = reader.fetch("chr1", start as u64, end as u64).unwrap();
+            assert_eq!(fetched, sequence[start..end]);
+        }
+    }
+}
+
+/// A one-sequence 2bit file with two N blocks, altered by `corrupt`, whose
+/// offsets are those of the sequence header (its length at 25, N block
+/// count at 29, N block starts at 33 and 37)
+fn corrupted(corrupt: impl Fn(&mut Vec<u8>)) -> PrseqError {
+    let mut data = encode(&[("chr1", "ACGTNNNNACGTNN")], false, 0);
+    corrupt(&mut data);
+    let mut reader = TwoBitReader::new(Cursor::new(data)).unwrap();
+    reader.fetch_sequence("chr1").unwrap_err()
+}
+
+#[test]
+fn test_malformed_sequence_headers() {
+    // A block count far beyond what the file holds is not allocated for.
+    let error = corrupted(|data| data[29..33].copy_from_slice(&u32::MAX.to_le_bytes()));
+    assert!(matches!(
+        error,
+        PrseqError::InvalidFormat { format: "2bit", .. }
+    ));
+    assert_eq!(error.position().unwrap().record, 1);
+
+    let error = corrupted(|data| {
+        data[33..37].copy_from_slice(&12u32.to_le_bytes());
+        data[37..41].copy_from_slice(&4u32.to_le_bytes());
+    });
+    assert!(error.to_string().starts_with("Blocks are out of order"));
+
+    let error = corrupted(|data| data[25..29].copy_from_slice(&1000u32.to_le_bytes()));
+    assert!(matches!(error, PrseqError::InvalidFormat { .. }));
+}
@@ -0,0 +1,105 @@
+// Tests for UMI extraction
+use prseq::umi::{UmiExt, UmiExtractor, UmiFormat, UmiRead};
+use prseq::{FastqReader, FastqRecord, PairedFastqReader, PrseqError};
+
+fn read(header: &str, sequence: &str, quality: &str) -> FastqRecord {
+    FastqRecord {
+        header: header.to_string(),
+        sequence: sequence.to_string(),
+        quality: quality.to_string(),
+    }
+}
+
+#[test]
+fn test_extract_formats() {
+    let record = read("r1 1:N:0:ATCACG", "AACCGGTTTT", "ABCDEFGHIJ");
+    let extractor = UmiExtractor::new(4);
+    let moved = extractor.extract(&record).unwrap();
+    assert_eq!(moved.header, "r1_AACC 1:N:0:ATCACG");
+    assert_eq!(moved.sequence, "GGTTTT");
+    assert_eq!(moved.quality, "EFGHIJ");
+
+    let casava = extractor.clone().format(UmiFormat::Id(":".to_string()));
+    assert_eq!(casava.extract(&record).unwrap().id(), "r1:AACC");
+
+    let prefixed = extractor.clone().format(UmiFormat::Id(":UMI_".to_string()));
+    let no_comment = read("r1", "AACCGG", "IIIIII");
+    assert_eq!(prefixed.extract(&no_comment).unwrap().header, "r1:UMI_AACC");
+
+    let tag = extractor.format(UmiFormat::Comment("RX:Z:".to_string()));
+    assert_eq!(
+        tag.extract(&record).unwrap().header,
+        "r1 1:N:0:ATCACG RX:Z:AACC"
+    );
+}
+
+#[test]
+fn test_short_read() {
+    let extractor = UmiExtractor::new(8);
+    assert!(matches!(
+        extractor.extract(&read("r1", "ACGT", "IIII")),
+        Err(PrseqError::OutOfRange { length: 4, .. })
+    ));
+    // A read that is all UMI is left empty.
+    let moved = extractor
+        .extract(&read("r1", "ACGTACGT", "IIIIIIII"))
+        .unwrap();
+    assert_eq!((moved.id(), moved.sequence.as_str()), ("r1_ACGTACGT", ""));
+}
+
+#[test]
+fn test_extract_pair() {
+    let r1 = read("p1 1:N:0", "AAAACCCC", "IIIIIIII");
+    let r2 = read("p1 2:N:0", "GGGGTTTT", "IIIIIIII");
+    let extractor = UmiExtractor::new(4);
+
+    let (a, b) = extractor.extract_pair(&r1, &r2, UmiRead::R1).unwrap();
+    assert_eq!(
+        (a.header.as_str(), a.sequence.as_str()),
+        ("p1_AAAA 1:N:0", "CCCC")
+    );
+    assert_eq!(
+        (b.header.as_str(), b.sequence.as_str()),
+        ("p1_AAAA 2:N:0", "GGGGTTTT")
+    );
+
+    let (a, b) = extractor.extract_pair(&r1, &r2, UmiRead::R2).unwrap();
+    assert_eq!((a.id(), a.sequence.as_str()), ("p1_GGGG", "AAAACCCC"));
+    assert_eq!((b.id(), b.sequence.as_str()), ("p1_GGGG", "TTTT"));
+
+    let (a, b) = extractor.extract_pair(&r1, &r2, UmiRead::Both).unwrap();
+    assert_eq!((a.id(), b.id()), ("p1_AAAAGGGG", "p1_AAAAGGGG"));
+}
+
+#[test]
+fn test_extract_from_index() {
+    let record = read("r1 1:N:0", "ACGT", "IIII");
+    let index = read("r1 2:N:0", "TTGGCCAA", "IIIIIIII");
+    let extractor = UmiExtractor::new(6);
+    let moved = extractor.extract_from_index(&record, &index).unwrap();
+    assert_eq!(moved.header, "r1_TTGGCC 1:N:0");
+    assert_eq!(moved.sequence, "ACGT");
+}
+
+#[test]
+fn test_extract_stream() {
+    let input = b"@r1\nAACCGG\n+\nIIIIII\n@r2\nAC\n+\nII\n";
+    let reader = FastqReader::from_reader_with_capacity(&input[..], 64).unwrap();
+    let results: Vec<_> = reader.extract_umis(UmiExtractor::new(3)).collect();
+    assert_eq!(results[0].as_ref().unwrap().header, "r1_AAC");
+    assert!(results[1].is_err());
+
+    // Pairs from a PairedFastqReader
+    let r1 = b"@p1/1\nAACCGG\n+\nIIIIII\n";
+    let r2 = b"@p1/2\nTTTTTT\n+\nIIIIII\n";
+    let pairs = PairedFastqReader::from_readers_with_capacity(&r1[..], &r2[..], 64).unwrap();
+    let extractor = UmiExtractor::new(2);
+    for pair in pairs {
+        let (r1, r2) = pair.unwrap();
+        let (r1, r2) = extractor.extract_pair(&r1, &r2, UmiRead::R1).unwrap();
+        assert_eq!(
+            (r1.header.as_str(), r2.header.as_str()),
+            ("p1_AA/1", "p1_AA/2")
+        );
+    }
+}
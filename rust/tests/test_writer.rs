@@ -0,0 +1,104 @@
+// Tests for FASTA and FASTQ writers
+use prseq::{FastaReader, FastaRecord, FastaWriter, FastqReader, FastqRecord, FastqWriter};
+use std::io::Cursor;
+use tempfile::NamedTempFile;
+
+fn fasta(header: &str, sequence: &str) -> FastaRecord {
+    FastaRecord {
+        header: header.to_string(),
+        sequence: sequence.to_string(),
+    }
+}
+
+#[test]
+fn test_fasta_writer() {
+    let mut writer = FastaWriter::new(Vec::new());
+    writer
+        .write_record(&fasta("seq1 desc", "ACGTACGT"))
+        .unwrap();
+    writer.write_record(&fasta("seq2", "")).unwrap();
+    assert_eq!(writer.into_inner(), b">seq1 desc\nACGTACGT\n>seq2\n\n");
+}
+
+#[test]
+fn test_fasta_writer_wraps_lines() {
+    let mut writer = FastaWriter::with_line_width(Vec::new(), 3);
+    writer.write_record(&fasta("seq1", "ACGTACGT")).unwrap();
+    let output = writer.into_inner();
+    assert_eq!(output, b">seq1\nACG\nTAC\nGT\n");
+
+    let records: Vec<_> = FastaReader::from_reader_with_capacity(Cursor::new(output), 64)
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records, vec![fasta("seq1", "ACGTACGT")]);
+}
+
+#[test]
+fn test_fastq_writer_to_file() {
+    let record = FastqRecord {
+        header: "read1".to_string(),
+        sequence: "ACGT".to_string(),
+        quality: "IIJJ".to_string(),
+    };
+    let file = NamedTempFile::new().unwrap();
+    let mut writer = FastqWriter::to_file(file.path()).unwrap();
+    writer.write_record(&record).unwrap();
+    writer.flush().unwrap();
+
+    let records: Vec<_> = FastqReader::from_file(file.path())
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect();
+    assert_eq!(records, vec![record]);
+}
+
+#[test]
+fn test_record_display() {
+    let record = fasta("seq1 desc", "ACGTACGT");
+    assert_eq!(record.to_string(), ">seq1 desc\nACGTACGT");
+    assert_eq!(record.wrapped(3).to_string(), ">seq1 desc\nACG\nTAC\nGT");
+    assert_eq!(record.wrapped(8).to_string(), ">seq1 desc\nACGTACGT");
+    assert_eq!(fasta("empty", "").to_string(), ">empty");
+
+    let record = FastqRecord {
+        header: "r1".to_string(),
+        sequence: "ACG".to_string(),
+        quality: "II#".to_string(),
+    };
+    assert_eq!(record.to_string(), "@r1\nACG\n+\nII#");
+    let any = prseq::any::AnyRecord::Fastq(record);
+    assert_eq!(any.to_string(), "@r1\nACG\n+\nII#");
+}
+
+#[test]
+fn test_record_from_str() {
+    let record: FastaRecord = ">seq1 desc\nACG\nTAC\nGT\n".parse().unwrap();
+    assert_eq!(record, fasta("seq1 desc", "ACGTACGT"));
+    assert_eq!(record.to_string().parse::<FastaRecord>().unwrap(), record);
+    let record: FastaRecord = record.wrapped(2).to_string().parse().unwrap();
+    assert_eq!(record.sequence, "ACGTACGT");
+
+    let record: FastqRecord = "@r1\nACG\n+\nII#".parse().unwrap();
+    assert_eq!(record.quality, "II#");
+    assert_eq!(record.to_string().parse::<FastqRecord>().unwrap(), record);
+}
+
+#[test]
+fn test_record_from_str_errors() {
+    let error = "".parse::<FastaRecord>().unwrap_err();
+    assert!(error.to_string().contains("No FASTA record"));
+
+    let error = ">s1\nAC\n>s2\nGG\n".parse::<FastaRecord>().unwrap_err();
+    assert!(error.to_string().contains("Expected a single FASTA record"));
+    let position = error.position().unwrap();
+    assert_eq!((position.line, position.record), (3, 2));
+
+    let error = "@r1\nAC\n+\nII\n@r2\nG\n+\nI\n"
+        .parse::<FastqRecord>()
+        .unwrap_err();
+    assert!(error.to_string().contains("Expected a single FASTQ record"));
+    assert_eq!(error.position().unwrap().line, 5);
+
+    assert!("@r1\nACG\n+\nII\n".parse::<FastqRecord>().is_err());
+}